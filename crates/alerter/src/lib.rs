@@ -9,13 +9,21 @@ use {
     model::order::{OrderClass, OrderKind, OrderStatus, OrderUid, BUY_ETH_ADDRESS},
     number::serialization::HexOrDecimalU256,
     primitive_types::{H160, U256},
-    prometheus::IntGauge,
+    prometheus::{Gauge, IntGauge, IntGaugeVec, Opts},
+    rate_limit::{Middleware, RateLimiter, Strategy},
     reqwest::Client,
     serde_with::serde_as,
+    shared::{
+        http_client::{HttpClientFactory, ProxyRule},
+        logging_args_with_default_filter,
+    },
     std::{
         collections::HashMap,
+        str::FromStr,
+        sync::Arc,
         time::{Duration, Instant},
     },
+    tracing::{level_filters::LevelFilter, Instrument},
     url::Url,
 };
 
@@ -76,6 +84,17 @@ impl OrderBookApi {
         Ok(auction.orders)
     }
 
+    pub async fn quote(&self, request: &QuoteRequest) -> reqwest::Result<()> {
+        let url = shared::url::join(&self.base, "api/v1/quote");
+        self.client
+            .post(url)
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
     pub async fn order(&self, uid: &OrderUid) -> reqwest::Result<Order> {
         let url = shared::url::join(&self.base, &format!("api/v1/orders/{uid}"));
         self.client
@@ -88,6 +107,97 @@ impl OrderBookApi {
     }
 }
 
+#[serde_as]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteRequest {
+    sell_token: H160,
+    buy_token: H160,
+    from: H160,
+    kind: OrderKind,
+    #[serde_as(as = "HexOrDecimalU256")]
+    sell_amount_before_fee: U256,
+}
+
+/// Periodically requests a quote to detect when quoting is slow or down.
+struct QuoteMonitor {
+    orderbook_api: OrderBookApi,
+    request: QuoteRequest,
+    max_latency: Duration,
+    errors_in_a_row_before_alert: u32,
+    errors_in_a_row: u32,
+    // Duration of the most recent quote request.
+    quote_latency_seconds: Gauge,
+    // Set to 1 if quotes are too slow or keep failing, 0 otherwise.
+    quote_alert: IntGauge,
+}
+
+impl QuoteMonitor {
+    fn new(
+        orderbook_api: OrderBookApi,
+        request: QuoteRequest,
+        max_latency: Duration,
+        errors_in_a_row_before_alert: u32,
+    ) -> Self {
+        let registry = observe::metrics::get_registry();
+        let quote_latency_seconds =
+            Gauge::new("quote_latency_seconds", "latency of the last quote").unwrap();
+        let quote_alert = IntGauge::new("quote_alert", "0 or 1").unwrap();
+        registry
+            .register(Box::new(quote_latency_seconds.clone()))
+            .unwrap();
+        registry.register(Box::new(quote_alert.clone())).unwrap();
+        Self {
+            orderbook_api,
+            request,
+            max_latency,
+            errors_in_a_row_before_alert,
+            errors_in_a_row: 0,
+            quote_latency_seconds,
+            quote_alert,
+        }
+    }
+
+    async fn update(&mut self) {
+        let start = Instant::now();
+        let result = self.orderbook_api.quote(&self.request).await;
+        let latency = start.elapsed();
+        self.quote_latency_seconds.set(latency.as_secs_f64());
+
+        match result {
+            Ok(()) => self.errors_in_a_row = 0,
+            Err(err) => {
+                self.errors_in_a_row += 1;
+                tracing::warn!(?err, "quote request failed");
+            }
+        }
+
+        let failing = self.errors_in_a_row >= self.errors_in_a_row_before_alert;
+        if failing {
+            tracing::error!(
+                "Quote requests failed {} times in a row.",
+                self.errors_in_a_row
+            );
+        }
+        let slow = latency > self.max_latency;
+        if slow {
+            tracing::error!(
+                "Quote request took {:?} which exceeds the limit of {:?}.",
+                latency,
+                self.max_latency
+            );
+        }
+        self.quote_alert.set((failing || slow).into());
+    }
+
+    async fn run_forever(mut self, interval: Duration) {
+        loop {
+            self.update().await;
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
 // Converts the eth placeholder address to weth. Leaves other addresses
 // untouched.
 fn convert_eth_to_weth(token: H160) -> H160 {
@@ -103,14 +213,24 @@ fn convert_eth_to_weth(token: H160) -> H160 {
 
 struct ZeroExApi {
     base: Url,
-    client: Client,
+    client: Middleware,
     api_key: String,
 }
 
 impl ZeroExApi {
-    pub fn new(client: Client, api_key: String) -> Self {
+    pub fn new(
+        client: Client,
+        base: Url,
+        api_key: String,
+        rate_limiter: Option<(Strategy, String)>,
+    ) -> Self {
+        let mut client = Middleware::new(client);
+        if let Some((strategy, name)) = rate_limiter {
+            let limiter = RateLimiter::from_strategy(strategy, name);
+            client = client.with_rate_limiter(base.host_str().unwrap(), Arc::new(limiter));
+        }
         Self {
-            base: "https://api.0x.org".parse().unwrap(),
+            base,
             client,
             api_key,
         }
@@ -140,11 +260,13 @@ impl ZeroExApi {
             pub buy_amount: U256,
         }
 
-        let response: Response = self
+        let request = self
             .client
             .get(url.clone())
-            .header("0x-api-key", self.api_key.clone())
-            .send()
+            .header("0x-api-key", self.api_key.clone());
+        let response: Response = self
+            .client
+            .send(request)
             .await?
             .error_for_status()?
             .json()
@@ -194,13 +316,8 @@ impl Alerter {
         zeroex_api: ZeroExApi,
         config: AlertConfig,
         api_get_order_min_interval: Duration,
+        no_trades_but_matchable_order: IntGauge,
     ) -> Self {
-        let registry = observe::metrics::get_registry();
-        let no_trades_but_matchable_order =
-            IntGauge::new("no_trades_but_matchable_order", "0 or 1").unwrap();
-        registry
-            .register(Box::new(no_trades_but_matchable_order.clone()))
-            .unwrap();
         Self {
             orderbook_api,
             zeroex_api,
@@ -322,14 +439,58 @@ impl AlertConfig {
     }
 }
 
+/// A network monitored by the alerter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Network {
+    name: String,
+    orderbook_api: Url,
+    zeroex_api: Url,
+}
+
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    /// Parses `<name>=<orderbook url>[,zeroex=<0x url>]`.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(',');
+        let (name, orderbook_api) = parts
+            .next()
+            .and_then(|network| network.split_once('='))
+            .context("expected <name>=<orderbook url>")?;
+        let mut zeroex_api = None;
+        for part in parts {
+            match part.split_once('=') {
+                Some(("zeroex", url)) => zeroex_api = Some(url.parse()?),
+                _ => anyhow::bail!("unknown network option {part:?}"),
+            }
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            orderbook_api: orderbook_api.parse()?,
+            zeroex_api: zeroex_api.unwrap_or_else(default_zeroex_api),
+        })
+    }
+}
+
+const DEFAULT_METRICS_PORT: u16 = 9588;
+
+fn default_zeroex_api() -> Url {
+    "https://api.0x.org".parse().unwrap()
+}
+
+logging_args_with_default_filter!(LoggingArguments, "alerter=debug");
+
 #[derive(Debug, Parser)]
 struct Arguments {
+    #[clap(flatten)]
+    logging: LoggingArguments,
+
     /// Alerter update interval.
     #[clap(
         long,
         env,
         default_value = "30s",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     update_interval: Duration,
 
@@ -338,7 +499,7 @@ struct Arguments {
         long,
         env,
         default_value = "10m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     time_without_trade: Duration,
 
@@ -347,7 +508,7 @@ struct Arguments {
         long,
         env,
         default_value = "3m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     min_order_age: Duration,
 
@@ -356,7 +517,7 @@ struct Arguments {
         long,
         env,
         default_value = "30m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     min_alert_interval: Duration,
 
@@ -368,21 +529,84 @@ struct Arguments {
     #[clap(long, env, default_value = "https://api.cow.fi/mainnet/")]
     orderbook_api: String,
 
-    #[clap(long, env, default_value = "9588")]
-    metrics_port: u16,
+    /// Networks to monitor in the form `<name>=<orderbook url>` with an
+    /// optional `,zeroex=<0x url>` for networks not served by the default 0x
+    /// API. Can be repeated. If not set only `--orderbook-api` gets monitored
+    /// as network "mainnet".
+    #[clap(long, env, use_value_delimiter = true, value_delimiter = ';')]
+    network: Vec<Network>,
+
+    /// Port serving the metrics and health probes. Defaults to the
+    /// `metrics-port` of the observability config, or 9588.
+    #[clap(long, env)]
+    metrics_port: Option<u16>,
 
     /// Minimum time between get order requests to the api. Without this the api
     /// can rate limit us.
-    #[clap(long, env, default_value = "200ms", value_parser = humantime::parse_duration)]
+    #[clap(long, env, default_value = "200ms", value_parser = shared::arguments::parse_duration)]
     api_get_order_min_interval: Duration,
 
     #[clap(long, env)]
     zero_ex_api_key: String,
+
+    /// Sell token of the quotes periodically requested to monitor the quote
+    /// endpoint of `--orderbook-api`. Quote monitoring is disabled if not set.
+    #[clap(long, env)]
+    quote_monitor_sell_token: Option<H160>,
+
+    /// Buy token of the monitoring quotes.
+    #[clap(
+        long,
+        env,
+        default_value = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+    )]
+    quote_monitor_buy_token: H160,
+
+    /// Sell amount (in sell token atoms) of the monitoring quotes.
+    #[clap(long, env, default_value = "1000000000", value_parser = shared::arguments::parse_wei)]
+    quote_monitor_sell_amount: U256,
+
+    /// Address on whose behalf the monitoring quotes get requested.
+    #[clap(
+        long,
+        env,
+        default_value = "0x0000000000000000000000000000000000000000"
+    )]
+    quote_monitor_from: H160,
+
+    /// How often a monitoring quote gets requested.
+    #[clap(long, env, default_value = "1m", value_parser = shared::arguments::parse_duration)]
+    quote_monitor_interval: Duration,
+
+    /// Alert if a monitoring quote takes longer than this.
+    #[clap(long, env, default_value = "10s", value_parser = shared::arguments::parse_duration)]
+    quote_monitor_max_latency: Duration,
+
+    /// How many monitoring quotes have to fail in a row before we alert.
+    #[clap(long, env, default_value = "3")]
+    quote_monitor_errors_in_a_row_before_alert: u32,
+
+    /// Rate limiting strategy for requests to the 0x API. See
+    /// --price-estimation-rate-limiter documentation for format details.
+    #[clap(long, env)]
+    zero_ex_rate_limiter: Option<Strategy>,
+
+    /// Proxy all outgoing HTTP requests are routed through unless overridden.
+    /// Supports `http`, `https`, `socks5` and `socks5h` URLs.
+    #[clap(long, env, value_parser = shared::http_client::parse_proxy)]
+    http_proxy: Option<Url>,
+
+    /// Per-destination overrides of the default proxy in the form of
+    /// "<HOST>|<PROXY>,..." where the proxy can be "direct" to bypass proxies.
+    #[clap(long, env, use_value_delimiter = true)]
+    http_proxy_overrides: Vec<ProxyRule>,
 }
 
 pub async fn start(args: impl Iterator<Item = String>) {
-    let args = Arguments::parse_from(args);
-    observe::tracing::initialize("alerter=debug", tracing::Level::ERROR.into());
+    let mut args = Arguments::parse_from(args);
+    let observe_config = args.logging.observe_config();
+    observe::tracing::initialize_with_config(&observe_config);
+    args.metrics_port = args.metrics_port.or(observe_config.metrics_port);
     observe::panic_hook::install();
     observe::metrics::setup_registry(Some("gp_v2_alerter".to_string()), None);
     tracing::info!("running alerter with {:#?}", args);
@@ -390,30 +614,98 @@ pub async fn start(args: impl Iterator<Item = String>) {
 }
 
 async fn run(args: Arguments) {
-    let filter = observe::metrics::handle_metrics();
-    tokio::task::spawn(warp::serve(filter).bind(([0, 0, 0, 0], args.metrics_port)));
+    let metrics_port = args.metrics_port.unwrap_or(DEFAULT_METRICS_PORT);
+    observe::health::HealthServer::default().serve(([0, 0, 0, 0], metrics_port).into());
+
+    let client = HttpClientFactory::new(&shared::http_client::Arguments {
+        http_timeout: Duration::from_secs(10),
+        http_proxy: args.http_proxy.clone(),
+        http_proxy_overrides: args.http_proxy_overrides.clone(),
+        http_cache_size: shared::http_client::DEFAULT_CACHE_SIZE,
+        http_cache_dir: None,
+    })
+    .create();
+
+    if let Some(sell_token) = args.quote_monitor_sell_token {
+        let monitor = QuoteMonitor::new(
+            OrderBookApi::new(client.clone(), &args.orderbook_api),
+            QuoteRequest {
+                sell_token,
+                buy_token: args.quote_monitor_buy_token,
+                from: args.quote_monitor_from,
+                kind: OrderKind::Sell,
+                sell_amount_before_fee: args.quote_monitor_sell_amount,
+            },
+            args.quote_monitor_max_latency,
+            args.quote_monitor_errors_in_a_row_before_alert,
+        );
+        tokio::task::spawn(monitor.run_forever(args.quote_monitor_interval));
+    }
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
+    let networks = match args.network.is_empty() {
+        true => vec![Network {
+            name: "mainnet".to_owned(),
+            orderbook_api: args.orderbook_api.parse().unwrap(),
+            zeroex_api: default_zeroex_api(),
+        }],
+        false => args.network.clone(),
+    };
+
+    let no_trades_but_matchable_order = IntGaugeVec::new(
+        Opts::new("no_trades_but_matchable_order", "0 or 1"),
+        &["network"],
+    )
+    .unwrap();
+    observe::metrics::get_registry()
+        .register(Box::new(no_trades_but_matchable_order.clone()))
         .unwrap();
 
-    let mut alerter = Alerter::new(
-        OrderBookApi::new(client.clone(), &args.orderbook_api),
-        ZeroExApi::new(client, args.zero_ex_api_key),
-        AlertConfig {
-            time_without_trade: args.time_without_trade,
-            min_order_solvable_time: args.min_order_age,
-            min_alert_interval: args.min_alert_interval,
-        },
-        args.api_get_order_min_interval,
-    );
+    let tasks: Vec<_> = networks
+        .into_iter()
+        .map(|network| {
+            let alerter = Alerter::new(
+                OrderBookApi::new(client.clone(), network.orderbook_api.as_str()),
+                ZeroExApi::new(
+                    client.clone(),
+                    network.zeroex_api,
+                    args.zero_ex_api_key.clone(),
+                    args.zero_ex_rate_limiter
+                        .clone()
+                        .map(|strategy| (strategy, format!("zeroex_{}", network.name))),
+                ),
+                AlertConfig {
+                    time_without_trade: args.time_without_trade,
+                    min_order_solvable_time: args.min_order_age,
+                    min_alert_interval: args.min_alert_interval,
+                },
+                args.api_get_order_min_interval,
+                no_trades_but_matchable_order.with_label_values(&[&network.name]),
+            );
+            tokio::task::spawn(
+                run_alerter(
+                    alerter,
+                    args.update_interval,
+                    args.errors_in_a_row_before_alert,
+                )
+                .instrument(tracing::info_span!("network", name = network.name)),
+            )
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
 
+async fn run_alerter(
+    mut alerter: Alerter,
+    update_interval: Duration,
+    errors_in_a_row_before_alert: u32,
+) {
     let mut errors_in_a_row = 0;
     loop {
         match alerter.update().await {
             Ok(()) => errors_in_a_row = 0,
-            Err(err) if errors_in_a_row < args.errors_in_a_row_before_alert => {
+            Err(err) if errors_in_a_row < errors_in_a_row_before_alert => {
                 errors_in_a_row += 1;
                 tracing::warn!(?err, "alerter update error");
             }
@@ -422,6 +714,39 @@ async fn run(args: Arguments) {
                 tracing::error!(?err, "alerter update error");
             }
         }
-        tokio::time::sleep(args.update_interval).await;
+        tokio::time::sleep(update_interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_network() {
+        assert_eq!(
+            "gnosis=https://api.cow.fi/xdai/"
+                .parse::<Network>()
+                .unwrap(),
+            Network {
+                name: "gnosis".to_owned(),
+                orderbook_api: "https://api.cow.fi/xdai/".parse().unwrap(),
+                zeroex_api: default_zeroex_api(),
+            }
+        );
+        assert_eq!(
+            "base=https://api.cow.fi/base/,zeroex=https://base.api.0x.org/"
+                .parse::<Network>()
+                .unwrap(),
+            Network {
+                name: "base".to_owned(),
+                orderbook_api: "https://api.cow.fi/base/".parse().unwrap(),
+                zeroex_api: "https://base.api.0x.org/".parse().unwrap(),
+            }
+        );
+        assert!("https://api.cow.fi/base/".parse::<Network>().is_err());
+        assert!("base=https://api.cow.fi/base/,foo=bar"
+            .parse::<Network>()
+            .is_err());
     }
 }
@@ -0,0 +1,252 @@
+//! Maintains the `app_code_report_rollups` table which backs the partner
+//! reports of the orderbook API.
+//!
+//! Trades get aggregated per app code, day and token. The last aggregated
+//! block is stored in the `last_indexed_blocks` table in the same transaction
+//! as the rollups so every trade gets counted exactly once.
+//!
+//! Only trades of blocks that are at least `finality_depth` blocks old get
+//! aggregated. The event indexer replaces the trades of reorged blocks within
+//! that depth, so rollups never contain trades that got reorged out.
+
+use {
+    crate::database::Postgres,
+    anyhow::{Context, Result},
+    chrono::{DateTime, NaiveDate},
+    database::{
+        app_code_reports::{self, Rollup, TradeRow, LAST_INDEXED_BLOCK_KEY},
+        last_indexed_blocks,
+    },
+    ethrpc::{block_stream::CurrentBlockWatcher, Web3},
+    std::{
+        collections::{BTreeSet, HashMap},
+        time::Duration,
+    },
+    tokio::time,
+    web3::types::BlockId,
+};
+
+/// Maximum number of blocks whose trades get aggregated in a single update.
+const MAX_BLOCKS_PER_UPDATE: i64 = 10_000;
+
+pub struct AppCodeReportUpdater {
+    db: Postgres,
+    web3: Web3,
+    current_block: CurrentBlockWatcher,
+    finality_depth: u64,
+    update_interval: Duration,
+}
+
+impl AppCodeReportUpdater {
+    pub fn new(
+        db: Postgres,
+        web3: Web3,
+        current_block: CurrentBlockWatcher,
+        finality_depth: u64,
+        update_interval: Duration,
+    ) -> Self {
+        Self {
+            db,
+            web3,
+            current_block,
+            finality_depth,
+            update_interval,
+        }
+    }
+
+    pub async fn run_forever(self) -> ! {
+        let mut interval = time::interval(self.update_interval);
+        loop {
+            interval.tick().await;
+            // Catch up as far as possible before waiting for the next tick.
+            loop {
+                match self.update().await {
+                    Ok(true) => continue,
+                    Ok(false) => break,
+                    Err(err) => {
+                        tracing::warn!(?err, "failed to update app code report rollups");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Aggregates the trades of the next range of blocks. Returns whether
+    /// there are more blocks left to aggregate.
+    async fn update(&self) -> Result<bool> {
+        let _timer = database::instrumentation::time_query("update_app_code_report_rollups");
+        let mut ex = database::instrumentation::begin(&self.db.pool).await?;
+
+        let from_block = last_indexed_blocks::fetch(&mut ex, LAST_INDEXED_BLOCK_KEY)
+            .await?
+            .unwrap_or_default();
+        let latest_block = final_block(self.current_block.borrow().number, self.finality_depth)
+            .min(database::orders::latest_settlement_block(&mut ex).await?);
+        if latest_block <= from_block {
+            return Ok(false);
+        }
+        let to_block = latest_block.min(from_block.saturating_add(MAX_BLOCKS_PER_UPDATE));
+
+        let trades = app_code_reports::trades(&mut ex, from_block, to_block).await?;
+        let days = self.block_days(&trades).await?;
+        let rollups = aggregate(trades, &days);
+
+        app_code_reports::add(&mut ex, &rollups).await?;
+        last_indexed_blocks::update(&mut ex, LAST_INDEXED_BLOCK_KEY, to_block).await?;
+        ex.commit().await?;
+
+        tracing::debug!(
+            from_block,
+            to_block,
+            rollups = rollups.len(),
+            "updated app code report rollups"
+        );
+        Metrics::get().app_code_report_last_block.set(to_block);
+        Ok(to_block < latest_block)
+    }
+
+    /// Fetches the UTC day on which each block containing one of the trades
+    /// got mined.
+    async fn block_days(&self, trades: &[TradeRow]) -> Result<HashMap<i64, NaiveDate>> {
+        let blocks: BTreeSet<_> = trades.iter().map(|trade| trade.block_number).collect();
+        let mut days = HashMap::with_capacity(blocks.len());
+        for block_number in blocks {
+            let block = u64::try_from(block_number).context("negative block number")?;
+            let timestamp = self
+                .web3
+                .eth()
+                .block(BlockId::Number(block.into()))
+                .await?
+                .with_context(|| format!("missing block {block}"))?
+                .timestamp;
+            let timestamp = i64::try_from(timestamp.as_u64()).context("timestamp overflow")?;
+            let day = DateTime::from_timestamp(timestamp, 0)
+                .context("invalid block timestamp")?
+                .date_naive();
+            days.insert(block_number, day);
+        }
+        Ok(days)
+    }
+}
+
+/// The latest block that can't get reorged anymore.
+fn final_block(current_block: u64, finality_depth: u64) -> i64 {
+    current_block
+        .saturating_sub(finality_depth)
+        .try_into()
+        .unwrap_or(i64::MAX)
+}
+
+/// Sums up fees and surplus of the trades per app code, day and token. Each
+/// trade only gets counted on the row of its fee token so summing up the
+/// trades of all tokens yields the number of trades.
+fn aggregate(trades: Vec<TradeRow>, days: &HashMap<i64, NaiveDate>) -> Vec<Rollup> {
+    let mut rollups = HashMap::<_, Rollup>::new();
+    for trade in trades {
+        let Some(&day) = days.get(&trade.block_number) else {
+            continue;
+        };
+
+        let fee = rollups
+            .entry((trade.app_code.clone(), day, trade.fee_token))
+            .or_insert_with(|| Rollup {
+                app_code: trade.app_code.clone(),
+                day,
+                token: trade.fee_token,
+                ..Default::default()
+            });
+        fee.trades += 1;
+        fee.fee += trade.fee;
+        fee.fee_in_wei += trade.fee_in_wei;
+
+        // The trade was already counted on the row of the fee token.
+        let surplus = rollups
+            .entry((trade.app_code.clone(), day, trade.surplus_token))
+            .or_insert_with(|| Rollup {
+                app_code: trade.app_code.clone(),
+                day,
+                token: trade.surplus_token,
+                ..Default::default()
+            });
+        surplus.surplus += trade.surplus;
+        surplus.surplus_in_wei += trade.surplus_in_wei;
+    }
+    rollups.into_values().collect()
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// The last block whose trades got aggregated into the app code reports.
+    app_code_report_last_block: prometheus::IntGauge,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, bigdecimal::BigDecimal, database::byte_array::ByteArray};
+
+    #[test]
+    fn only_aggregates_final_blocks() {
+        assert_eq!(final_block(100, 64), 36);
+        assert_eq!(final_block(100, 0), 100);
+        assert_eq!(final_block(10, 64), 0);
+    }
+
+    #[test]
+    fn aggregates_trades_per_app_code_day_and_token() {
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let days = HashMap::from([(1, day), (2, day)]);
+        let trade = |block_number, fee_token, surplus_token| TradeRow {
+            app_code: "partner".to_string(),
+            block_number,
+            fee_token: ByteArray(fee_token),
+            fee: 1.into(),
+            fee_in_wei: 2.into(),
+            surplus_token: ByteArray(surplus_token),
+            surplus: 3.into(),
+            surplus_in_wei: 4.into(),
+        };
+        let trades = vec![
+            trade(1, [1; 20], [1; 20]),
+            trade(2, [1; 20], [2; 20]),
+            // Trades in unknown blocks get ignored.
+            trade(3, [1; 20], [1; 20]),
+        ];
+
+        let mut rollups = aggregate(trades, &days);
+        rollups.sort_by_key(|rollup| rollup.token.0);
+        // Each trade gets counted once even if its fee and surplus tokens differ.
+        assert_eq!(rollups.iter().map(|rollup| rollup.trades).sum::<i64>(), 2);
+        assert_eq!(
+            rollups,
+            vec![
+                Rollup {
+                    app_code: "partner".to_string(),
+                    day,
+                    token: ByteArray([1; 20]),
+                    trades: 2,
+                    fee: 2.into(),
+                    fee_in_wei: 4.into(),
+                    surplus: 3.into(),
+                    surplus_in_wei: 4.into(),
+                },
+                Rollup {
+                    app_code: "partner".to_string(),
+                    day,
+                    token: ByteArray([2; 20]),
+                    trades: 0,
+                    fee: BigDecimal::default(),
+                    fee_in_wei: BigDecimal::default(),
+                    surplus: 3.into(),
+                    surplus_in_wei: 4.into(),
+                },
+            ]
+        );
+    }
+}
@@ -2,9 +2,9 @@ use {
     crate::{domain::fee::FeeFactor, infra},
     anyhow::Context,
     clap::ValueEnum,
-    primitive_types::H160,
+    primitive_types::{H160, U256},
     shared::{
-        arguments::{display_list, display_option, ExternalSolver},
+        arguments::{display_list, display_option, display_secret_option, ExternalSolver},
         bad_token::token_owner_finder,
         http_client,
         price_estimation::{self, NativePriceEstimators},
@@ -46,8 +46,11 @@ pub struct Arguments {
     #[clap(long, env)]
     pub tracing_node_url: Option<Url>,
 
-    #[clap(long, env, default_value = "0.0.0.0:9589")]
-    pub metrics_address: SocketAddr,
+    /// Address serving the metrics and health probes. Defaults to the
+    /// `metrics-port` of the observability config, or 9589, on all
+    /// interfaces.
+    #[clap(long, env)]
+    pub metrics_address: Option<SocketAddr>,
 
     /// Url of the Postgres database. By default connects to locally running
     /// postgres.
@@ -95,7 +98,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "1m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub min_order_validity_period: Duration,
 
@@ -109,13 +112,13 @@ pub struct Arguments {
         long,
         env,
         default_value = "5m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub max_auction_age: Duration,
 
     /// Used to filter out limit orders with prices that are too far from the
     /// market price. 0 means no filtering.
-    #[clap(long, env, default_value = "0")]
+    #[clap(long, env, default_value = "0", value_parser = shared::arguments::parse_non_negative_factor)]
     pub limit_order_price_factor: f64,
 
     /// The URL of a list of tokens our settlement contract is willing to
@@ -133,7 +136,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "1h",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub trusted_tokens_update_interval: Duration,
 
@@ -141,6 +144,95 @@ pub struct Arguments {
     #[clap(long, env, use_value_delimiter = true)]
     pub drivers: Vec<ExternalSolver>,
 
+    /// Capabilities advertised by drivers which are used to only send them
+    /// the subset of each auction they can handle. Supplied in the form of:
+    /// "<NAME>|<KEY>=<VALUE>|<KEY>=<VALUE>,<NAME>|<KEY>=<VALUE>"
+    /// - tokens: `;` separated list of supported tokens
+    /// - max-orders: maximum number of orders per auction
+    /// - order-classes: `;` separated list of `fill-or-kill` and
+    ///   `partially-fillable`
+    ///
+    /// Drivers without configured capabilities receive the full auction.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub driver_capabilities: Vec<DriverCapabilities>,
+
+    /// Further URLs serving the same drivers, e.g. from other regions.
+    /// Supplied in the form of: "<NAME>|<URL>;<URL>,<NAME>|<URL>"
+    ///
+    /// Auctions get sent to the healthy mirror with the lowest latency and
+    /// fail over to the other mirrors if it errors. Solutions are revealed and
+    /// settled on the mirror that computed them.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub driver_mirrors: Vec<DriverMirrors>,
+
+    /// Bonding pools backing the drivers. Supplied in the form of:
+    /// "<POOL>|<DRIVER1>;<DRIVER2>,<POOL>|<DRIVER3>"
+    ///
+    /// Drivers get suspended from the competition while their pool holds less
+    /// than any of the `bonding_pool_requirements`.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub bonding_pools: Vec<BondingPool>,
+
+    /// Minimum balances a bonding pool has to hold for its drivers to be
+    /// allowed to compete. Supplied in the form of:
+    /// "<TOKEN>|<AMOUNT>,<TOKEN>|<AMOUNT>" where amounts are in atoms.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub bonding_pool_requirements: Vec<BondingRequirement>,
+
+    /// Time interval between checks of the bonding pool balances.
+    #[clap(long, env, default_value = "1m", value_parser = shared::arguments::parse_duration)]
+    pub bonding_pool_update_interval: Duration,
+
+    /// How many of the most recently settled orders of a solver are
+    /// aggregated into its execution quality score.
+    #[clap(long, env, default_value = "1000")]
+    pub execution_quality_window: u64,
+
+    /// Minimum number of scored orders before a solver can get excluded
+    /// because of its execution quality.
+    #[clap(long, env, default_value = "20")]
+    pub execution_quality_min_orders: u64,
+
+    /// Solvers whose settled orders on average deviate from the promised
+    /// prices by less than this value (e.g. -0.01 for 1% worse than promised)
+    /// get excluded from the competition. Nobody gets excluded if unset.
+    #[clap(long, env, allow_hyphen_values = true)]
+    pub execution_quality_min_mean_deviation: Option<f64>,
+
+    /// Time interval between updates of the solver execution quality scores.
+    #[clap(long, env, default_value = "1m", value_parser = shared::arguments::parse_duration)]
+    pub execution_quality_update_interval: Duration,
+
+    /// How many of the most recent auctions a solver won (and whose deadline
+    /// passed) make up the share of them it settled.
+    #[clap(long, env, default_value = "50")]
+    pub settlement_rate_window: u64,
+
+    /// How many of the most recent auctions get searched for the auctions
+    /// solvers won. Solvers that won fewer than `--settlement-rate-window` of
+    /// them are judged on fewer auctions.
+    #[clap(long, env, default_value = "50000")]
+    pub settlement_rate_lookback: u64,
+
+    /// Minimum number of won auctions before a solver can get excluded for
+    /// not settling them.
+    #[clap(long, env, default_value = "10")]
+    pub settlement_rate_min_won_auctions: u64,
+
+    /// Solvers settling a smaller share of the auctions they won (e.g. 0.9)
+    /// get excluded from the competition for
+    /// `--unreliable-solver-cool-down`. Nobody gets excluded if unset.
+    #[clap(long, env)]
+    pub settlement_rate_min: Option<f64>,
+
+    /// How long solvers stay excluded for not settling the auctions they won.
+    #[clap(long, env, default_value = "1h", value_parser = shared::arguments::parse_duration)]
+    pub unreliable_solver_cool_down: Duration,
+
+    /// Solvers that never get excluded because of their reputation.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub reputation_exclusion_overrides: Vec<H160>,
+
     /// The maximum number of blocks to wait for a settlement to appear on
     /// chain.
     #[clap(long, env, default_value = "5")]
@@ -153,7 +245,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "1m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub max_settlement_transaction_wait: Duration,
 
@@ -173,10 +265,20 @@ pub struct Arguments {
         long,
         env,
         default_value = "15s",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub solve_deadline: Duration,
 
+    /// Drivers that may get more time than the solve deadline if they ask for
+    /// it, e.g. because their engines need more time to optimize, and the
+    /// maximum extension each of them gets. Supplied in the form of:
+    /// "<NAME>|<DURATION>,<NAME>|<DURATION>"
+    ///
+    /// Extensions asked for by other drivers are ignored. Responses of the
+    /// other drivers still get collected until the regular deadline.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub solve_deadline_extensions: Vec<DriverDeadlineExtension>,
+
     /// Describes how the protocol fees should be calculated.
     #[clap(long, env, use_value_delimiter = true)]
     pub fee_policies: Vec<FeePolicy>,
@@ -192,14 +294,20 @@ pub struct Arguments {
 
     /// Time interval in days between each cleanup operation of the
     /// `order_events` database table.
-    #[clap(long, env, default_value = "1d", value_parser = humantime::parse_duration)]
+    #[clap(long, env, default_value = "1d", value_parser = shared::arguments::parse_duration)]
     pub order_events_cleanup_interval: Duration,
 
     /// Age threshold in days for order events to be eligible for cleanup in the
-    /// `order_events` database table.
-    #[clap(long, env, default_value = "30d", value_parser = humantime::parse_duration)]
+    /// `order_events` database table. Events get aggregated into the
+    /// `order_lifecycle_summaries` table before they are deleted.
+    #[clap(long, env, default_value = "30d", value_parser = shared::arguments::parse_duration)]
     pub order_events_cleanup_threshold: Duration,
 
+    /// Time interval between updates of the aggregated fee and surplus
+    /// reports per partner app code.
+    #[clap(long, env, default_value = "10m", value_parser = shared::arguments::parse_duration)]
+    pub app_code_report_update_interval: Duration,
+
     /// Configurations for indexing CoW AMMs. Supplied in the form of:
     /// "<factory1>|<helper1>|<block1>,<factory2>|<helper2>,<block2>"
     /// - factory is contract address emmiting CoW AMM deployment events.
@@ -213,12 +321,12 @@ pub struct Arguments {
     /// If a new run loop would start more than this amount of time after the
     /// system noticed the latest block, wait for the next block to appear
     /// before continuing the run loop.
-    #[clap(long, env, default_value = "2s", value_parser = humantime::parse_duration)]
+    #[clap(long, env, default_value = "2s", value_parser = shared::arguments::parse_duration)]
     pub max_run_loop_delay: Duration,
 
     /// Maximum timeout for fetching the native prices in the run loop
     /// If the value is 0, the native prices are fetched from the cache
-    #[clap(long, env, default_value = "0s", value_parser = humantime::parse_duration)]
+    #[clap(long, env, default_value = "0s", value_parser = shared::arguments::parse_duration)]
     pub run_loop_native_price_timeout: Duration,
 
     #[clap(long, env, default_value = "1")]
@@ -234,8 +342,119 @@ pub struct Arguments {
     /// Archive node URL used to index CoW AMM
     #[clap(long, env)]
     pub archive_node_url: Option<Url>,
+
+    /// Export auction, competition and settlement events to Kafka by
+    /// producing records through the Kafka REST proxy at this `http(s)://`
+    /// URL.
+    #[clap(long, env)]
+    pub export_events_url: Option<Url>,
+
+    /// The Kafka topic exported events get published to.
+    #[clap(long, env, default_value = "autopilot.events")]
+    pub export_events_topic: String,
+
+    /// Maximum number of exported events waiting to be published before new
+    /// events get dropped.
+    #[clap(long, env, default_value = "1000")]
+    pub export_events_queue_size: usize,
+
+    /// For how many of the most recent auctions the candidate orders that got
+    /// filtered out, together with the reason, are kept in the database.
+    #[clap(long, env, default_value = "10000")]
+    pub filtered_orders_retention: u64,
+
+    /// How solutions with identical scores are ordered. `random` splits ties
+    /// randomly, `gas` prefers the solution with the lower simulated gas cost
+    /// and then the one that was submitted first.
+    #[clap(long, env, default_value = "random", value_enum)]
+    pub tie_breaking_policy: TieBreakingPolicy,
+
+    /// How the winners of an auction get selected. `score` lets the solution
+    /// with the highest score win alone, `surplus-per-gas` the solution with
+    /// the highest score per unit of simulated gas and `fair-combinatorial`
+    /// lets solutions trading disjoint tokens win together, up to
+    /// `max-winners-per-auction`.
+    #[clap(long, env, default_value = "fair-combinatorial", value_enum)]
+    pub winner_selection_policy: WinnerSelectionPolicy,
+
+    /// Address of the admin API used to pause auctions, settlements, solvers
+    /// or tokens during incidents.
+    #[clap(long, env, default_value = "0.0.0.0:9590")]
+    pub admin_api_address: SocketAddr,
+
+    /// Key that requests to the admin API have to carry in the `X-API-Key`
+    /// header. The admin API is disabled if no key is configured.
+    #[clap(long, env)]
+    pub admin_api_key: Option<String>,
+
+    /// Address of the API that lets solvers rank candidate solutions against
+    /// the solutions proposed for a recent auction. Requests have to carry the
+    /// `admin_api_key`. Disabled if either is unset.
+    #[clap(long, env)]
+    pub what_if_api_address: Option<SocketAddr>,
+
+    /// For how many of the most recent auctions the proposed solutions are
+    /// kept for the what-if API.
+    #[clap(long, env, default_value = "10")]
+    pub what_if_auctions: usize,
+
+    /// Additional settlement contracts (e.g. a staging deployment) whose
+    /// events get indexed alongside the main settlement contract. Each
+    /// contract is indexed into its own database so its data stays isolated.
+    /// The expected format is "<name>|<address>|<block>|<db url>" where block
+    /// is the block at which indexing should start. Multiple contracts are
+    /// separated by commas.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub additional_settlement_contracts: Vec<AdditionalSettlementContract>,
+
+    /// Bucket the inputs of failed run loop iterations get archived to so
+    /// they can be replayed. For the local backend this is a directory.
+    /// Snapshots are disabled if unset.
+    #[clap(long, env)]
+    pub run_loop_snapshot_bucket: Option<String>,
+
+    /// Which object storage run loop snapshots get archived to.
+    #[clap(long, env, value_enum, default_value = "s3")]
+    pub run_loop_snapshot_backend: infra::persistence::cli::Backend,
+
+    /// Prepended to the ids of the run loop snapshots to form their file
+    /// names.
+    #[clap(long, env, default_value = "run-loop-snapshots/")]
+    pub run_loop_snapshot_prefix: String,
+
+    /// How long run loop snapshots are kept before they get removed.
+    #[clap(long, env, default_value = "7d", value_parser = shared::arguments::parse_duration)]
+    pub run_loop_snapshot_retention: Duration,
+
+    /// Instead of running the autopilot, rank the solutions of the archived
+    /// run loop snapshot with this id again and log whether the outcome
+    /// matches the original iteration.
+    #[clap(long, env)]
+    pub replay_snapshot: Option<String>,
+}
+
+impl Arguments {
+    /// Where run loop snapshots get archived, if anywhere.
+    pub fn run_loop_snapshots(&self) -> Option<s3::Config> {
+        let bucket = self.run_loop_snapshot_bucket.clone()?;
+        Some(s3::Config {
+            backend: self.run_loop_snapshot_backend.with_bucket(bucket),
+            filename_prefix: self.run_loop_snapshot_prefix.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Where the metrics and health probes get served. Falls back to
+    /// `metrics_port` of the observability config if no address is set.
+    pub fn metrics_address(&self, metrics_port: Option<u16>) -> SocketAddr {
+        self.metrics_address.unwrap_or_else(|| {
+            SocketAddr::from(([0, 0, 0, 0], metrics_port.unwrap_or(DEFAULT_METRICS_PORT)))
+        })
+    }
 }
 
+const DEFAULT_METRICS_PORT: u16 = 9589;
+
 impl std::fmt::Display for Arguments {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let Self {
@@ -261,13 +480,30 @@ impl std::fmt::Display for Arguments {
             trusted_tokens,
             trusted_tokens_update_interval,
             drivers,
+            driver_capabilities,
+            driver_mirrors,
+            bonding_pools,
+            bonding_pool_requirements,
+            bonding_pool_update_interval,
+            execution_quality_window,
+            execution_quality_min_orders,
+            execution_quality_min_mean_deviation,
+            execution_quality_update_interval,
+            settlement_rate_window,
+            settlement_rate_lookback,
+            settlement_rate_min_won_auctions,
+            settlement_rate_min,
+            unreliable_solver_cool_down,
+            reputation_exclusion_overrides,
             submission_deadline,
             shadow,
             solve_deadline,
+            solve_deadline_extensions,
             fee_policies,
             fee_policy_max_partner_fee,
             order_events_cleanup_interval,
             order_events_cleanup_threshold,
+            app_code_report_update_interval,
             db_url,
             insert_batch_size,
             native_price_estimation_results_required,
@@ -279,6 +515,22 @@ impl std::fmt::Display for Arguments {
             max_winners_per_auction,
             archive_node_url,
             max_solutions_per_solver,
+            export_events_url,
+            export_events_topic,
+            export_events_queue_size,
+            filtered_orders_retention,
+            tie_breaking_policy,
+            winner_selection_policy,
+            admin_api_address,
+            admin_api_key,
+            what_if_api_address,
+            what_if_auctions,
+            additional_settlement_contracts,
+            run_loop_snapshot_bucket,
+            run_loop_snapshot_backend,
+            run_loop_snapshot_prefix,
+            run_loop_snapshot_retention,
+            replay_snapshot,
         } = self;
 
         write!(f, "{}", shared)?;
@@ -289,7 +541,7 @@ impl std::fmt::Display for Arguments {
         display_option(f, "tracing_node_url", tracing_node_url)?;
         writeln!(f, "ethflow_contract: {:?}", ethflow_contract)?;
         writeln!(f, "ethflow_indexing_start: {:?}", ethflow_indexing_start)?;
-        writeln!(f, "metrics_address: {}", metrics_address)?;
+        display_option(f, "metrics_address", metrics_address)?;
         let _intentionally_ignored = db_url;
         writeln!(f, "db_url: SECRET")?;
         writeln!(f, "skip_event_sync: {}", skip_event_sync)?;
@@ -317,9 +569,61 @@ impl std::fmt::Display for Arguments {
             trusted_tokens_update_interval
         )?;
         display_list(f, "drivers", drivers.iter())?;
+        writeln!(f, "driver_capabilities: {:?}", driver_capabilities)?;
+        writeln!(f, "driver_mirrors: {:?}", driver_mirrors)?;
+        writeln!(f, "bonding_pools: {:?}", bonding_pools)?;
+        writeln!(
+            f,
+            "bonding_pool_requirements: {:?}",
+            bonding_pool_requirements
+        )?;
+        writeln!(
+            f,
+            "bonding_pool_update_interval: {:?}",
+            bonding_pool_update_interval
+        )?;
+        writeln!(f, "execution_quality_window: {}", execution_quality_window)?;
+        writeln!(
+            f,
+            "execution_quality_min_orders: {}",
+            execution_quality_min_orders
+        )?;
+        display_option(
+            f,
+            "execution_quality_min_mean_deviation",
+            execution_quality_min_mean_deviation,
+        )?;
+        writeln!(
+            f,
+            "execution_quality_update_interval: {:?}",
+            execution_quality_update_interval
+        )?;
+        writeln!(f, "settlement_rate_window: {}", settlement_rate_window)?;
+        writeln!(f, "settlement_rate_lookback: {}", settlement_rate_lookback)?;
+        writeln!(
+            f,
+            "settlement_rate_min_won_auctions: {}",
+            settlement_rate_min_won_auctions
+        )?;
+        display_option(f, "settlement_rate_min", settlement_rate_min)?;
+        writeln!(
+            f,
+            "unreliable_solver_cool_down: {:?}",
+            unreliable_solver_cool_down
+        )?;
+        writeln!(
+            f,
+            "reputation_exclusion_overrides: {:?}",
+            reputation_exclusion_overrides
+        )?;
         writeln!(f, "submission_deadline: {}", submission_deadline)?;
         display_option(f, "shadow", shadow)?;
         writeln!(f, "solve_deadline: {:?}", solve_deadline)?;
+        writeln!(
+            f,
+            "solve_deadline_extensions: {:?}",
+            solve_deadline_extensions
+        )?;
         writeln!(f, "fee_policies: {:?}", fee_policies)?;
         writeln!(
             f,
@@ -336,6 +640,11 @@ impl std::fmt::Display for Arguments {
             "order_events_cleanup_threshold: {:?}",
             order_events_cleanup_threshold
         )?;
+        writeln!(
+            f,
+            "app_code_report_update_interval: {:?}",
+            app_code_report_update_interval
+        )?;
         writeln!(f, "insert_batch_size: {}", insert_batch_size)?;
         writeln!(
             f,
@@ -362,6 +671,38 @@ impl std::fmt::Display for Arguments {
             "max_solutions_per_solver: {:?}",
             max_solutions_per_solver
         )?;
+        display_secret_option(f, "export_events_url", export_events_url.as_ref())?;
+        writeln!(f, "export_events_topic: {}", export_events_topic)?;
+        writeln!(f, "export_events_queue_size: {}", export_events_queue_size)?;
+        writeln!(
+            f,
+            "filtered_orders_retention: {}",
+            filtered_orders_retention
+        )?;
+        writeln!(f, "tie_breaking_policy: {:?}", tie_breaking_policy)?;
+        writeln!(f, "winner_selection_policy: {:?}", winner_selection_policy)?;
+        writeln!(f, "admin_api_address: {}", admin_api_address)?;
+        display_secret_option(f, "admin_api_key", admin_api_key.as_ref())?;
+        display_option(f, "what_if_api_address", what_if_api_address)?;
+        writeln!(f, "what_if_auctions: {}", what_if_auctions)?;
+        display_list(
+            f,
+            "additional_settlement_contracts",
+            additional_settlement_contracts,
+        )?;
+        display_option(f, "run_loop_snapshot_bucket", run_loop_snapshot_bucket)?;
+        writeln!(
+            f,
+            "run_loop_snapshot_backend: {:?}",
+            run_loop_snapshot_backend
+        )?;
+        writeln!(f, "run_loop_snapshot_prefix: {}", run_loop_snapshot_prefix)?;
+        writeln!(
+            f,
+            "run_loop_snapshot_retention: {:?}",
+            run_loop_snapshot_retention
+        )?;
+        display_option(f, "replay_snapshot", replay_snapshot)?;
         Ok(())
     }
 }
@@ -403,6 +744,25 @@ pub enum FeePolicyKind {
     Volume { factor: FeeFactor },
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum TieBreakingPolicy {
+    /// Split ties randomly.
+    Random,
+    /// Prefer the solution with the lower simulated gas cost, then the one that
+    /// was submitted first.
+    Gas,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum WinnerSelectionPolicy {
+    /// The solution with the highest score wins alone.
+    Score,
+    /// The solution with the highest score per unit of gas wins alone.
+    SurplusPerGas,
+    /// Solutions trading disjoint tokens win together.
+    FairCombinatorial,
+}
+
 #[derive(clap::Parser, clap::ValueEnum, Clone, Debug)]
 pub enum FeePolicyOrderClass {
     /// If a fee policy needs to be applied to in-market orders.
@@ -522,6 +882,232 @@ impl FromStr for CowAmmConfig {
     }
 }
 
+#[derive(Clone)]
+pub struct AdditionalSettlementContract {
+    /// Name used to tell the indexers apart in logs.
+    pub name: String,
+    pub address: H160,
+    /// At which block indexing should start.
+    pub index_start: u64,
+    /// Database the events of the contract get stored in.
+    pub db_url: Url,
+}
+
+impl std::fmt::Display for AdditionalSettlementContract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The database URL is omitted because it may contain credentials.
+        write!(f, "{}({:?}@{})", self.name, self.address, self.index_start)
+    }
+}
+
+impl std::fmt::Debug for AdditionalSettlementContract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for AdditionalSettlementContract {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('|');
+        let name = parts
+            .next()
+            .filter(|name| !name.is_empty())
+            .context("config is missing name")?
+            .to_owned();
+        let address = parts
+            .next()
+            .context("config is missing address")?
+            .parse()
+            .context("could not parse address as H160")?;
+        let index_start = parts
+            .next()
+            .context("config is missing index_start")?
+            .parse()
+            .context("could not parse index_start as u64")?;
+        let db_url = parts
+            .next()
+            .context("config is missing db url")?
+            .parse()
+            .context("could not parse db url")?;
+        anyhow::ensure!(
+            parts.next().is_none(),
+            "supplied too many arguments for additional settlement contract"
+        );
+
+        Ok(Self {
+            name,
+            address,
+            index_start,
+            db_url,
+        })
+    }
+}
+
+/// Capabilities of a single driver. See [`Arguments::driver_capabilities`].
+#[derive(Debug, Clone)]
+pub struct DriverCapabilities {
+    pub name: String,
+    pub capabilities: infra::solvers::Capabilities,
+}
+
+impl FromStr for DriverCapabilities {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('|');
+        let name = parts
+            .next()
+            .filter(|name| !name.is_empty())
+            .context("config is missing driver name")?
+            .to_owned();
+        let mut capabilities = infra::solvers::Capabilities::default();
+        for part in parts {
+            let (key, value) = part
+                .split_once('=')
+                .with_context(|| format!("capability {part:?} is not of the form <KEY>=<VALUE>"))?;
+            match key {
+                "tokens" => {
+                    capabilities.tokens = Some(
+                        value
+                            .split(';')
+                            .map(|token| {
+                                token
+                                    .parse::<H160>()
+                                    .map(Into::into)
+                                    .with_context(|| format!("could not parse token {token:?}"))
+                            })
+                            .collect::<Result<_, _>>()?,
+                    );
+                }
+                "max-orders" => {
+                    capabilities.max_orders =
+                        Some(value.parse().context("could not parse max-orders")?);
+                }
+                "order-classes" => {
+                    capabilities.order_classes = Some(
+                        value
+                            .split(';')
+                            .map(|class| {
+                                infra::solvers::OrderClass::from_str(class, true)
+                                    .map_err(|err| anyhow::anyhow!("invalid order class: {err}"))
+                            })
+                            .collect::<Result<_, _>>()?,
+                    );
+                }
+                _ => anyhow::bail!("unknown driver capability {key:?}"),
+            }
+        }
+
+        Ok(Self { name, capabilities })
+    }
+}
+
+/// Mirrors of a single driver. See [`Arguments::driver_mirrors`].
+#[derive(Debug, Clone)]
+pub struct DriverMirrors {
+    pub name: String,
+    pub urls: Vec<Url>,
+}
+
+/// Maximum solve deadline extension of a single driver. See
+/// [`Arguments::solve_deadline_extensions`].
+#[derive(Debug, Clone)]
+pub struct DriverDeadlineExtension {
+    pub name: String,
+    pub max: Duration,
+}
+
+impl FromStr for DriverDeadlineExtension {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, max) = s
+            .split_once('|')
+            .context("config is not of the form <NAME>|<DURATION>")?;
+        anyhow::ensure!(!name.is_empty(), "config is missing driver name");
+        Ok(Self {
+            name: name.to_owned(),
+            max: shared::arguments::parse_duration(max)?,
+        })
+    }
+}
+
+impl FromStr for DriverMirrors {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, urls) = s
+            .split_once('|')
+            .context("config is not of the form <NAME>|<URL>;<URL>")?;
+        anyhow::ensure!(!name.is_empty(), "config is missing driver name");
+        let urls = urls
+            .split(';')
+            .map(|url| {
+                url.parse()
+                    .with_context(|| format!("could not parse mirror url {url:?}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            name: name.to_owned(),
+            urls,
+        })
+    }
+}
+
+/// A bonding pool and the drivers it backs. See [`Arguments::bonding_pools`].
+#[derive(Clone, Debug)]
+pub struct BondingPool(pub infra::bonding::Pool);
+
+impl FromStr for BondingPool {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, drivers) = s
+            .split_once('|')
+            .context("bonding pool is not of the form <POOL>|<DRIVERS>")?;
+        let address = address
+            .parse::<H160>()
+            .with_context(|| format!("could not parse bonding pool address {address:?}"))?;
+        let drivers = drivers
+            .split(';')
+            .map(|driver| {
+                anyhow::ensure!(!driver.is_empty(), "empty driver name");
+                Ok(driver.to_owned())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(infra::bonding::Pool {
+            address: address.into(),
+            drivers,
+        }))
+    }
+}
+
+/// Minimum balance of a bonding pool. See
+/// [`Arguments::bonding_pool_requirements`].
+#[derive(Clone, Copy, Debug)]
+pub struct BondingRequirement(pub infra::bonding::Requirement);
+
+impl FromStr for BondingRequirement {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (token, amount) = s
+            .split_once('|')
+            .context("bonding requirement is not of the form <TOKEN>|<AMOUNT>")?;
+        let token = token
+            .parse::<H160>()
+            .with_context(|| format!("could not parse token {token:?}"))?;
+        let amount = U256::from_dec_str(amount)
+            .with_context(|| format!("could not parse amount {amount:?}"))?;
+        Ok(Self(infra::bonding::Requirement {
+            token: token.into(),
+            amount: amount.into(),
+        }))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -549,4 +1135,104 @@ mod test {
                 .contains("Factor must be in the range [0, 1)"),)
         }
     }
+
+    #[test]
+    fn parse_additional_settlement_contract() {
+        let contract = AdditionalSettlementContract::from_str(
+            "staging|0x0101010101010101010101010101010101010101|100|postgresql://user:pw@db/\
+             staging",
+        )
+        .unwrap();
+        assert_eq!(contract.name, "staging");
+        assert_eq!(contract.address, H160([1; 20]));
+        assert_eq!(contract.index_start, 100);
+        assert!(!contract.to_string().contains("pw"));
+
+        assert!(AdditionalSettlementContract::from_str("staging|0x01|100").is_err());
+        assert!(AdditionalSettlementContract::from_str(
+            "staging|0x0101010101010101010101010101010101010101|100"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_driver_capabilities() {
+        let config = DriverCapabilities::from_str(
+            "solver1|tokens=0x0101010101010101010101010101010101010101;\
+             0x0202020202020202020202020202020202020202|max-orders=10|order-classes=fill-or-kill",
+        )
+        .unwrap();
+        assert_eq!(config.name, "solver1");
+        assert_eq!(config.capabilities.tokens.unwrap().len(), 2);
+        assert_eq!(config.capabilities.max_orders.unwrap().get(), 10);
+        assert_eq!(
+            config.capabilities.order_classes.unwrap(),
+            [infra::solvers::OrderClass::FillOrKill].into()
+        );
+
+        let config = DriverCapabilities::from_str("solver2").unwrap();
+        assert!(config.capabilities.tokens.is_none());
+        assert!(config.capabilities.max_orders.is_none());
+        assert!(config.capabilities.order_classes.is_none());
+    }
+
+    #[test]
+    fn parse_driver_capabilities_wrong_arguments() {
+        assert!(DriverCapabilities::from_str("").is_err());
+        assert!(DriverCapabilities::from_str("solver|max-orders").is_err());
+        assert!(DriverCapabilities::from_str("solver|max-orders=0").is_err());
+        assert!(DriverCapabilities::from_str("solver|order-classes=market").is_err());
+        assert!(DriverCapabilities::from_str("solver|unknown=1").is_err());
+    }
+
+    #[test]
+    fn parse_driver_deadline_extension() {
+        let config = DriverDeadlineExtension::from_str("solver1|1s 500ms").unwrap();
+        assert_eq!(config.name, "solver1");
+        assert_eq!(config.max, Duration::from_millis(1500));
+
+        assert!(DriverDeadlineExtension::from_str("solver1").is_err());
+        assert!(DriverDeadlineExtension::from_str("|1s").is_err());
+        assert!(DriverDeadlineExtension::from_str("solver1|soon").is_err());
+    }
+
+    #[test]
+    fn parse_driver_mirrors() {
+        let config =
+            DriverMirrors::from_str("solver1|http://us.solver.xyz;http://asia.solver.xyz:8080")
+                .unwrap();
+        assert_eq!(config.name, "solver1");
+        assert_eq!(
+            config.urls,
+            [
+                Url::parse("http://us.solver.xyz").unwrap(),
+                Url::parse("http://asia.solver.xyz:8080").unwrap(),
+            ]
+        );
+
+        assert!(DriverMirrors::from_str("solver1").is_err());
+        assert!(DriverMirrors::from_str("|http://us.solver.xyz").is_err());
+        assert!(DriverMirrors::from_str("solver1|not a url").is_err());
+    }
+
+    #[test]
+    fn parse_bonding_pools() {
+        let pool =
+            BondingPool::from_str("0x0101010101010101010101010101010101010101|solver1;solver2")
+                .unwrap();
+        assert_eq!(pool.0.address, H160([1; 20]).into());
+        assert_eq!(pool.0.drivers, vec!["solver1", "solver2"]);
+
+        let requirement =
+            BondingRequirement::from_str("0x0202020202020202020202020202020202020202|1000")
+                .unwrap();
+        assert_eq!(requirement.0.token, H160([2; 20]).into());
+        assert_eq!(requirement.0.amount, U256::from(1000).into());
+
+        assert!(BondingPool::from_str("0x0101010101010101010101010101010101010101").is_err());
+        assert!(BondingPool::from_str("0x0101010101010101010101010101010101010101|").is_err());
+        assert!(
+            BondingRequirement::from_str("0x0202020202020202020202020202020202020202|-1").is_err()
+        );
+    }
 }
@@ -45,7 +45,7 @@ impl EventStoring<contracts::gpv2_settlement::Event> for Indexer {
         events: Vec<ethcontract::Event<contracts::gpv2_settlement::Event>>,
         range: RangeInclusive<u64>,
     ) -> Result<()> {
-        let mut transaction = self.db.pool.begin().await?;
+        let mut transaction = database::instrumentation::begin(&self.db.pool).await?;
         let from_block = *range.start();
         crate::database::events::replace_events(&mut transaction, events, from_block).await?;
         database::settlements::delete(&mut transaction, from_block).await?;
@@ -59,7 +59,7 @@ impl EventStoring<contracts::gpv2_settlement::Event> for Indexer {
         &mut self,
         events: Vec<ethcontract::Event<contracts::gpv2_settlement::Event>>,
     ) -> Result<()> {
-        let mut transaction = self.db.pool.begin().await?;
+        let mut transaction = database::instrumentation::begin(&self.db.pool).await?;
         crate::database::events::append_events(&mut transaction, events).await?;
         transaction.commit().await?;
 
@@ -17,24 +17,18 @@ use {
 #[async_trait::async_trait]
 impl QuoteStoring for Postgres {
     async fn save(&self, data: QuoteData) -> Result<QuoteId> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["save_quote"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("save_quote");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let row = create_quote_row(data)?;
         let id = database::quotes::save(&mut ex, &row).await?;
         Ok(id)
     }
 
     async fn get(&self, id: QuoteId) -> Result<Option<QuoteData>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["get_quote"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("get_quote");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let quote = database::quotes::get(&mut ex, id).await?;
         quote.map(TryFrom::try_from).transpose()
     }
@@ -44,12 +38,9 @@ impl QuoteStoring for Postgres {
         params: QuoteSearchParameters,
         expiration: DateTime<Utc>,
     ) -> Result<Option<(QuoteId, QuoteData)>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["find_quote"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("find_quote");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let params = create_db_search_parameters(params, expiration);
         let quote = database::quotes::find(&mut ex, &params)
             .await
@@ -62,13 +53,10 @@ impl QuoteStoring for Postgres {
 
 impl Postgres {
     pub async fn all_solvable_orders(&self, min_valid_to: u32) -> Result<boundary::SolvableOrders> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["solvable_orders"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("solvable_orders");
 
         let start = chrono::offset::Utc::now();
-        let mut ex = self.pool.begin().await?;
+        let mut ex = database::instrumentation::begin(&self.pool).await?;
         // Set the transaction isolation level to REPEATABLE READ
         // so the both SELECT queries below are executed in the same database snapshot
         // taken at the moment before the first query is executed.
@@ -101,13 +89,10 @@ impl Postgres {
         &self,
         auction: &dto::RawAuctionData,
     ) -> Result<dto::AuctionId> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["replace_current_auction"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("replace_current_auction");
 
         let data = serde_json::to_value(auction)?;
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let id = database::auction::replace_auction(&mut ex, &data).await?;
         Ok(id)
     }
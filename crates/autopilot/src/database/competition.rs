@@ -36,14 +36,13 @@ pub struct Competition {
 
 impl super::Postgres {
     pub async fn save_competition(&self, competition: &Competition) -> anyhow::Result<()> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["save_competition"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("save_competition");
 
         let json = &serde_json::to_value(&competition.competition_table)?;
 
-        let mut ex = self.pool.begin().await.context("begin")?;
+        let mut ex = database::instrumentation::begin(&self.pool)
+            .await
+            .context("begin")?;
 
         database::solver_competition::save_solver_competition(
             &mut ex,
@@ -128,7 +127,9 @@ impl super::Postgres {
         auction_id: AuctionId,
         surplus_capturing_jit_order_owners: &[Address],
     ) -> anyhow::Result<()> {
-        let mut ex = self.pool.acquire().await.context("acquire")?;
+        let mut ex = database::instrumentation::acquire(&self.pool)
+            .await
+            .context("acquire")?;
 
         surplus_capturing_jit_order_owners::insert(
             &mut ex,
@@ -53,11 +53,8 @@ impl EventStoring<EthFlowEvent> for Postgres {
             refunds if !refunds.is_empty() => refunds,
             _ => return Ok(()),
         };
-        let _timer = crate::database::Metrics::get()
-            .database_queries
-            .with_label_values(&["append_ethflow_refund_events"])
-            .start_timer();
-        let mut ex = self.pool.begin().await?;
+        let _timer = database::instrumentation::time_query("append_ethflow_refund_events");
+        let mut ex = database::instrumentation::begin(&self.pool).await?;
         database::ethflow_orders::insert_refund_tx_hashes(&mut ex, &refunds).await?;
         ex.commit().await?;
         Ok(())
@@ -69,11 +66,8 @@ impl EventStoring<EthFlowEvent> for Postgres {
         range: RangeInclusive<u64>,
     ) -> Result<()> {
         let refunds = get_refunds(events)?;
-        let _timer = crate::database::Metrics::get()
-            .database_queries
-            .with_label_values(&["replace_ethflow_refund_events"])
-            .start_timer();
-        let mut ex = self.pool.begin().await?;
+        let _timer = database::instrumentation::time_query("replace_ethflow_refund_events");
+        let mut ex = database::instrumentation::begin(&self.pool).await?;
         database::ethflow_orders::delete_refunds(
             &mut ex,
             i64::try_from(*range.start()).unwrap_or(i64::MAX),
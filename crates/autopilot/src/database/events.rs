@@ -46,10 +46,7 @@ pub async fn append_events(
     transaction: &mut PgTransaction<'_>,
     events: Vec<EthContractEvent<ContractEvent>>,
 ) -> Result<()> {
-    let _timer = super::Metrics::get()
-        .database_queries
-        .with_label_values(&["append_events"])
-        .start_timer();
+    let _timer = database::instrumentation::time_query("append_events");
 
     let events = contract_to_db_events(events)?;
     database::events::append(transaction, &events)
@@ -63,10 +60,7 @@ pub async fn replace_events(
     events: Vec<EthContractEvent<ContractEvent>>,
     from_block: u64,
 ) -> Result<()> {
-    let _timer = super::Metrics::get()
-        .database_queries
-        .with_label_values(&["replace_events"])
-        .start_timer();
+    let _timer = database::instrumentation::time_query("replace_events");
 
     let events = contract_to_db_events(events)?;
     database::events::delete(transaction, from_block)
@@ -42,21 +42,21 @@ impl Postgres {
 
         // update table row metrics
         for &table in database::TABLES {
-            let mut ex = self.pool.acquire().await?;
+            let mut ex = database::instrumentation::acquire(&self.pool).await?;
             let count = count_rows_in_table(&mut ex, table).await?;
             metrics.table_rows.with_label_values(&[table]).set(count);
         }
 
         // update table row metrics
         for &table in database::LARGE_TABLES {
-            let mut ex = self.pool.acquire().await?;
+            let mut ex = database::instrumentation::acquire(&self.pool).await?;
             let count = estimate_rows_in_table(&mut ex, table).await?;
             metrics.table_rows.with_label_values(&[table]).set(count);
         }
 
         // update unused app data metric
         {
-            let mut ex = self.pool.acquire().await?;
+            let mut ex = database::instrumentation::acquire(&self.pool).await?;
             let count = count_unused_app_data(&mut ex).await?;
             metrics.unused_app_data.set(count);
         }
@@ -66,7 +66,7 @@ impl Postgres {
 
     pub async fn update_large_tables_stats(&self) -> sqlx::Result<()> {
         for &table in database::LARGE_TABLES {
-            let mut ex = self.pool.acquire().await?;
+            let mut ex = database::instrumentation::acquire(&self.pool).await?;
             analyze_table(&mut ex, table).await?;
         }
 
@@ -114,10 +114,6 @@ struct Metrics {
     /// These are entries in the `app_data` table that do not have a
     /// corresponding order in the `orders` table.
     unused_app_data: prometheus::IntGauge,
-
-    /// Timing of db queries.
-    #[metric(name = "autopilot_database_queries", labels("type"))]
-    database_queries: prometheus::HistogramVec,
 }
 
 impl Metrics {
@@ -131,6 +127,12 @@ pub fn run_database_metrics_work(db: Postgres) {
     // Spawn the task for updating large table statistics
     tokio::spawn(update_large_tables_stats(db.clone()).instrument(span.clone()));
 
+    // Spawn the task for connection pool metrics
+    tokio::task::spawn(
+        database::instrumentation::pool_metrics_task(db.pool.clone(), Duration::from_secs(10))
+            .instrument(span.clone()),
+    );
+
     // Spawn the task for database metrics
     tokio::task::spawn(database_metrics(db).instrument(span));
 }
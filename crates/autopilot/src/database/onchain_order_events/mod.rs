@@ -4,7 +4,6 @@ pub mod event_retriever;
 use {
     super::{
         events::{bytes_to_order_uid, meta_to_event_index},
-        Metrics as DatabaseMetrics,
         Postgres,
     },
     anyhow::{anyhow, bail, Context, Result},
@@ -155,18 +154,12 @@ impl<T: Sync + Send + Clone, W: Sync + Send + Clone> EventStoring<ContractEvent>
     for OnchainOrderParser<T, W>
 {
     async fn last_event_block(&self) -> Result<u64> {
-        let _timer = DatabaseMetrics::get()
-            .database_queries
-            .with_label_values(&["read_last_block_onchain_orders"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("read_last_block_onchain_orders");
         crate::boundary::events::read_last_block_from_db(&self.db.pool, INDEX_NAME).await
     }
 
     async fn persist_last_indexed_block(&mut self, latest_block: u64) -> Result<()> {
-        let _timer = DatabaseMetrics::get()
-            .database_queries
-            .with_label_values(&["update_last_block_onchain_orders"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("update_last_block_onchain_orders");
         crate::boundary::events::write_last_block_to_db(&self.db.pool, latest_block, INDEX_NAME)
             .await
     }
@@ -176,12 +169,9 @@ impl<T: Sync + Send + Clone, W: Sync + Send + Clone> EventStoring<ContractEvent>
         events: Vec<EthContractEvent<ContractEvent>>,
         range: RangeInclusive<u64>,
     ) -> Result<()> {
-        let _timer = DatabaseMetrics::get()
-            .database_queries
-            .with_label_values(&["replace_onchain_order_events"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("replace_onchain_order_events");
 
-        let mut transaction = self.db.pool.begin().await?;
+        let mut transaction = database::instrumentation::begin(&self.db.pool).await?;
 
         self.delete_events(&mut transaction, range).await?;
         self.insert_events(events, &mut transaction).await?;
@@ -192,12 +182,9 @@ impl<T: Sync + Send + Clone, W: Sync + Send + Clone> EventStoring<ContractEvent>
     }
 
     async fn append_events(&mut self, events: Vec<EthContractEvent<ContractEvent>>) -> Result<()> {
-        let _timer = DatabaseMetrics::get()
-            .database_queries
-            .with_label_values(&["append_onchain_order_events"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("append_onchain_order_events");
 
-        let mut transaction = self.db.pool.begin().await?;
+        let mut transaction = database::instrumentation::begin(&self.db.pool).await?;
         self.insert_events(events, &mut transaction).await?;
         transaction.commit().await.context("commit")?;
 
@@ -6,15 +6,41 @@ use {
     database::{
         byte_array::ByteArray,
         order_events::{self, OrderEvent},
+        partitions,
     },
     sqlx::{Acquire, Error, PgConnection},
     tokio::time::Instant,
 };
 
 impl super::Postgres {
-    /// Deletes events before the provided timestamp.
-    pub async fn delete_order_events_before(&self, timestamp: DateTime<Utc>) -> Result<u64, Error> {
-        order_events::delete_order_events_before(&self.pool, timestamp).await
+    /// Aggregates events before the provided timestamp into the order
+    /// lifecycle summaries and removes them, dropping partitions that only
+    /// hold expired events. Returns the number of updated summaries, deleted
+    /// events and dropped partitions.
+    pub async fn summarize_and_delete_order_events_before(
+        &self,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(u64, u64, usize), Error> {
+        let mut ex = self.pool.begin().await?;
+        let summarized = order_events::summarize_order_events_before(&mut ex, timestamp).await?;
+        let dropped =
+            partitions::drop_partitions_before(&mut ex, "order_events", timestamp).await?;
+        let deleted = order_events::delete_order_events_before(&mut ex, timestamp).await?;
+        ex.commit().await?;
+        Ok((summarized, deleted, dropped.len()))
+    }
+
+    /// Creates the `order_events` partitions needed to store events until the
+    /// provided timestamp. Returns the number of created partitions.
+    pub async fn create_order_events_partitions_until(
+        &self,
+        timestamp: DateTime<Utc>,
+    ) -> Result<usize, Error> {
+        let mut ex = self.pool.begin().await?;
+        let created =
+            partitions::create_partitions_until(&mut ex, "order_events", timestamp).await?;
+        ex.commit().await?;
+        Ok(created.len())
     }
 }
 
@@ -13,12 +13,9 @@ use {
 
 impl Postgres {
     pub async fn remove_expired_quotes(&self, max_expiry: DateTime<Utc>) -> Result<()> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["remove_expired_quotes"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("remove_expired_quotes");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         database::quotes::remove_expired_quotes(&mut ex, max_expiry).await?;
         Ok(())
     }
@@ -30,12 +27,9 @@ impl Postgres {
         &self,
         orders: impl Iterator<Item = &domain::OrderUid>,
     ) -> Result<HashMap<domain::OrderUid, domain::Quote>, sqlx::Error> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["read_quotes"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("read_quotes");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let order_uids: Vec<_> = orders.map(|uid| ByteArray(uid.0)).collect();
         let quotes: HashMap<_, _> = database::orders::read_quotes(&mut ex, &order_uids)
             .await?
@@ -1,5 +1,5 @@
 use {
-    super::{eth, Order},
+    super::{eth, Order, OrderUid},
     std::collections::HashMap,
 };
 
@@ -16,10 +16,22 @@ pub struct RawAuctionData {
     pub orders: Vec<Order>,
     pub prices: Prices,
     pub surplus_capturing_jit_order_owners: Vec<eth::Address>,
+    /// Candidate orders that are not part of the auction.
+    pub filtered_orders: Vec<FilteredOrder>,
 }
 
 pub type Id = i64;
 
+/// A candidate order that got filtered out while building an auction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilteredOrder {
+    pub uid: OrderUid,
+    /// Name of the filter that removed the order.
+    pub reason: &'static str,
+    /// Additional information about why the order got removed.
+    pub details: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Auction {
     pub id: Id,
@@ -2,12 +2,18 @@ use {
     super::auction::order,
     crate::domain::{self, auction, eth},
     derive_more::Display,
-    std::collections::HashMap,
+    std::{collections::HashMap, time::Duration},
 };
 
 mod participant;
+mod tie_breaking;
+mod winner_selection;
 
-pub use participant::{Participant, Ranked, Unranked};
+pub use {
+    participant::{Participant, Ranked, Unranked},
+    tie_breaking::TieBreaking,
+    winner_selection::WinnerSelection,
+};
 
 type SolutionId = u64;
 
@@ -18,6 +24,10 @@ pub struct Solution {
     score: Score,
     orders: HashMap<domain::OrderUid, TradedOrder>,
     prices: auction::Prices,
+    /// Gas the settlement of the solution used in the driver's simulation.
+    gas: Option<eth::Gas>,
+    /// Phase timings reported by the solver engine.
+    timings: Option<Timings>,
 }
 
 impl Solution {
@@ -27,6 +37,8 @@ impl Solution {
         score: Score,
         orders: HashMap<domain::OrderUid, TradedOrder>,
         prices: auction::Prices,
+        gas: Option<eth::Gas>,
+        timings: Option<Timings>,
     ) -> Self {
         Self {
             id,
@@ -34,6 +46,8 @@ impl Solution {
             score,
             orders,
             prices,
+            gas,
+            timings,
         }
     }
 
@@ -60,6 +74,23 @@ impl Solution {
     pub fn prices(&self) -> &HashMap<eth::TokenAddress, auction::Price> {
         &self.prices
     }
+
+    pub fn gas(&self) -> Option<eth::Gas> {
+        self.gas
+    }
+
+    pub fn timings(&self) -> Option<Timings> {
+        self.timings
+    }
+}
+
+/// Time the solver engine reported to have spent in the different phases of
+/// solving the auction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timings {
+    pub liquidity_processing: Option<Duration>,
+    pub optimization: Option<Duration>,
+    pub encoding: Option<Duration>,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -45,4 +45,8 @@ impl Participant<Ranked> {
     pub fn is_winner(&self) -> bool {
         self.state.is_winner
     }
+
+    pub fn unrank(self) -> Participant<Unranked> {
+        Participant::<Unranked>::new(self.solution, self.driver)
+    }
 }
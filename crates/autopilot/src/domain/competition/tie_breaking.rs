@@ -0,0 +1,144 @@
+//! Ordering of the solutions proposed in a competition.
+
+use {
+    super::{Participant, Unranked},
+    crate::arguments,
+    rand::{seq::SliceRandom, Rng},
+    serde::{Deserialize, Serialize},
+    std::cmp::Reverse,
+};
+
+/// How solutions with identical scores are ordered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TieBreaking {
+    /// Ties are split randomly.
+    #[default]
+    Random,
+    /// Ties are split in favour of the solution with the lower simulated gas
+    /// cost and then in favour of the solution that was submitted first.
+    /// Solutions without a gas estimate lose ties against solutions with one.
+    Gas,
+}
+
+impl From<arguments::TieBreakingPolicy> for TieBreaking {
+    fn from(value: arguments::TieBreakingPolicy) -> Self {
+        match value {
+            arguments::TieBreakingPolicy::Random => Self::Random,
+            arguments::TieBreakingPolicy::Gas => Self::Gas,
+        }
+    }
+}
+
+impl TieBreaking {
+    /// Sorts the solutions from best to worst.
+    ///
+    /// The solutions are expected in the order in which they were submitted.
+    /// Random ties are split with `rng`, so sorting the same solutions with
+    /// an identically seeded `rng` yields the same order.
+    pub fn sort(&self, solutions: &mut [Participant<Unranked>], rng: &mut impl Rng) {
+        match self {
+            Self::Random => {
+                solutions.shuffle(rng);
+                solutions.sort_unstable_by_key(|participant| {
+                    Reverse(participant.solution().score().get().0)
+                });
+            }
+            Self::Gas => {
+                // The sort is stable, so equal solutions keep their submission
+                // order.
+                solutions.sort_by_key(|participant| {
+                    let gas = participant.solution().gas();
+                    (
+                        Reverse(participant.solution().score().get().0),
+                        gas.is_none(),
+                        gas,
+                    )
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            domain::{
+                competition::{Score, Solution},
+                eth,
+            },
+            infra,
+        },
+        rand::{rngs::StdRng, SeedableRng},
+        std::sync::Arc,
+    };
+
+    fn participant(id: u64, score: u64, gas: Option<u64>) -> Participant<Unranked> {
+        let driver = infra::Driver::new(
+            "http://localhost".parse().unwrap(),
+            format!("solver{id}"),
+            None,
+            Default::default(),
+            Default::default(),
+            None,
+            &Default::default(),
+        );
+        Participant::new(
+            Solution::new(
+                id,
+                Default::default(),
+                Score::try_new(eth::Ether(score.into())).unwrap(),
+                Default::default(),
+                Default::default(),
+                gas.map(|gas| eth::Gas(gas.into())),
+                None,
+            ),
+            Arc::new(driver),
+        )
+    }
+
+    #[test]
+    fn gas_breaks_ties() {
+        let mut solutions = vec![
+            participant(0, 10, Some(200_000)),
+            participant(1, 20, None),
+            participant(2, 10, None),
+            participant(3, 10, Some(100_000)),
+            participant(4, 10, Some(200_000)),
+        ];
+        TieBreaking::Gas.sort(&mut solutions, &mut rand::thread_rng());
+        let ids: Vec<_> = solutions.iter().map(|p| p.solution().id()).collect();
+        // A higher score always wins, then the lower gas and finally the
+        // earlier submission.
+        assert_eq!(ids, [1, 3, 0, 4, 2]);
+    }
+
+    #[test]
+    fn random_only_reorders_ties() {
+        let mut solutions = vec![
+            participant(0, 10, None),
+            participant(1, 30, None),
+            participant(2, 10, None),
+            participant(3, 20, None),
+        ];
+        TieBreaking::Random.sort(&mut solutions, &mut rand::thread_rng());
+        let ids: Vec<_> = solutions.iter().map(|p| p.solution().id()).collect();
+        assert_eq!(ids[..2], [1, 3]);
+        assert!(ids[2..] == [0, 2] || ids[2..] == [2, 0]);
+    }
+
+    #[test]
+    fn random_is_reproducible_with_seed() {
+        let sorted = |seed| {
+            let mut solutions: Vec<_> = (0..8).map(|id| participant(id, 10, None)).collect();
+            TieBreaking::Random.sort(&mut solutions, &mut StdRng::seed_from_u64(seed));
+            solutions
+                .iter()
+                .map(|p| p.solution().id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(42), sorted(42));
+    }
+}
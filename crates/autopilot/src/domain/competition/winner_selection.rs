@@ -0,0 +1,224 @@
+//! Selection of the winners among the solutions proposed in a competition.
+
+use {
+    super::{Participant, Unranked},
+    crate::{arguments, domain::eth::U256},
+    number::ratio::Ratio256,
+    serde::{Deserialize, Serialize},
+    std::cmp::Ordering,
+};
+
+/// How the winners of a competition get selected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WinnerSelection {
+    /// The solution with the highest score wins alone.
+    Score,
+    /// The solution with the highest score per unit of simulated gas wins
+    /// alone. Solutions without a gas estimate rank behind solutions with one.
+    SurplusPerGas,
+    /// Solutions win one after the other from the highest score down as long
+    /// as they don't trade any token traded by a previous winner, up to the
+    /// maximum number of winners per auction.
+    #[default]
+    FairCombinatorial,
+}
+
+impl From<arguments::WinnerSelectionPolicy> for WinnerSelection {
+    fn from(value: arguments::WinnerSelectionPolicy) -> Self {
+        match value {
+            arguments::WinnerSelectionPolicy::Score => Self::Score,
+            arguments::WinnerSelectionPolicy::SurplusPerGas => Self::SurplusPerGas,
+            arguments::WinnerSelectionPolicy::FairCombinatorial => Self::FairCombinatorial,
+        }
+    }
+}
+
+impl From<WinnerSelection> for model::solver_competition::WinnerSelection {
+    fn from(value: WinnerSelection) -> Self {
+        match value {
+            WinnerSelection::Score => Self::Score,
+            WinnerSelection::SurplusPerGas => Self::SurplusPerGas,
+            WinnerSelection::FairCombinatorial => Self::FairCombinatorial,
+        }
+    }
+}
+
+impl WinnerSelection {
+    /// Sorts the solutions from best to worst.
+    ///
+    /// The solutions are expected to be sorted by score with ties already
+    /// broken. The sort is stable, so solutions the strategy considers equal
+    /// keep that order.
+    pub fn sort(&self, solutions: &mut [Participant<Unranked>]) {
+        match self {
+            Self::Score | Self::FairCombinatorial => (),
+            Self::SurplusPerGas => solutions.sort_by(|a, b| compare_score_per_gas(b, a)),
+        }
+    }
+
+    /// How many of the solutions may win an auction.
+    pub fn max_winners(&self, max_winners_per_auction: usize) -> usize {
+        match self {
+            Self::Score | Self::SurplusPerGas => max_winners_per_auction.min(1),
+            Self::FairCombinatorial => max_winners_per_auction,
+        }
+    }
+
+    /// The score the winner had to beat, given the solutions ranked from best
+    /// to worst. The winner's reward is relative to it.
+    ///
+    /// Ranked by score, this is the score of the runner-up. Ranked by score
+    /// per gas, the runner-up may have a higher score than the winner, so the
+    /// reference is the score at which the winner would have been exactly as
+    /// efficient as the runner-up. Without gas estimates to compare, it is the
+    /// lower of the two scores. It never exceeds the winner's score.
+    pub fn reference_score<T>(&self, solutions: &[Participant<T>]) -> U256 {
+        let (Some(winner), Some(runner_up)) = (solutions.first(), solutions.get(1)) else {
+            return U256::zero();
+        };
+        let winner_score = winner.solution().score().get().0;
+        let runner_up_score = runner_up.solution().score().get().0;
+        match self {
+            Self::Score | Self::FairCombinatorial => runner_up_score,
+            Self::SurplusPerGas => {
+                let gas = (winner.solution().gas(), runner_up.solution().gas());
+                let equally_efficient = match gas {
+                    (Some(winner_gas), Some(runner_up_gas)) => {
+                        Ratio256::new(winner_gas.0, runner_up_gas.0)
+                            .ok()
+                            .map(|gas| (Ratio256::from(runner_up_score) * &gas).saturating_floor())
+                    }
+                    _ => None,
+                };
+                equally_efficient
+                    .unwrap_or(runner_up_score)
+                    .min(winner_score)
+            }
+        }
+    }
+}
+
+/// Compares the scores per unit of gas of the solutions. Solutions without a
+/// gas estimate compare less than solutions with one.
+fn compare_score_per_gas(a: &Participant<Unranked>, b: &Participant<Unranked>) -> Ordering {
+    score_per_gas(a).cmp(&score_per_gas(b))
+}
+
+/// The exact score per unit of gas. A zero gas estimate counts as missing.
+fn score_per_gas(participant: &Participant<Unranked>) -> Option<Ratio256> {
+    let gas = participant.solution().gas()?;
+    Ratio256::new(participant.solution().score().get().0, gas.0).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            domain::{
+                competition::{Score, Solution},
+                eth,
+            },
+            infra,
+        },
+        std::sync::Arc,
+    };
+
+    fn participant(id: u64, score: u64, gas: Option<u64>) -> Participant<Unranked> {
+        let driver = infra::Driver::new(
+            "http://localhost".parse().unwrap(),
+            format!("solver{id}"),
+            None,
+            Default::default(),
+            Default::default(),
+            None,
+            &Default::default(),
+        );
+        Participant::new(
+            Solution::new(
+                id,
+                Default::default(),
+                Score::try_new(eth::Ether(score.into())).unwrap(),
+                Default::default(),
+                Default::default(),
+                gas.map(|gas| eth::Gas(gas.into())),
+                None,
+            ),
+            Arc::new(driver),
+        )
+    }
+
+    #[test]
+    fn surplus_per_gas_ranks_efficient_solutions_first() {
+        let mut solutions = vec![
+            participant(0, 40, Some(200_000)),
+            participant(1, 50, None),
+            participant(2, 30, Some(100_000)),
+            participant(3, 20, Some(100_000)),
+            participant(4, 10, Some(50_000)),
+            participant(5, 60, Some(0)),
+        ];
+        WinnerSelection::SurplusPerGas.sort(&mut solutions);
+        let ids: Vec<_> = solutions.iter().map(|p| p.solution().id()).collect();
+        // Equally efficient solutions keep their order and solutions without
+        // a usable gas estimate come last.
+        assert_eq!(ids, [2, 0, 3, 4, 1, 5]);
+    }
+
+    #[test]
+    fn score_keeps_order() {
+        let mut solutions = vec![
+            participant(0, 40, Some(200_000)),
+            participant(1, 30, Some(100_000)),
+        ];
+        WinnerSelection::Score.sort(&mut solutions);
+        let ids: Vec<_> = solutions.iter().map(|p| p.solution().id()).collect();
+        assert_eq!(ids, [0, 1]);
+    }
+
+    #[test]
+    fn reference_score_of_runner_up() {
+        let solutions = [
+            participant(0, 40, Some(200_000)),
+            participant(1, 30, Some(100_000)),
+        ];
+        assert_eq!(
+            WinnerSelection::Score.reference_score(&solutions),
+            30.into()
+        );
+        assert_eq!(
+            WinnerSelection::FairCombinatorial.reference_score(&solutions[..1]),
+            0.into()
+        );
+    }
+
+    #[test]
+    fn surplus_per_gas_reference_score_matches_efficiency() {
+        // The winner is more efficient but the runner-up has the higher score.
+        let solutions = [
+            participant(0, 30, Some(100_000)),
+            participant(1, 40, Some(200_000)),
+        ];
+        // At a score of 20 the winner would be exactly as efficient.
+        assert_eq!(
+            WinnerSelection::SurplusPerGas.reference_score(&solutions),
+            20.into()
+        );
+
+        // Without gas estimates the reference can't exceed the winner's score.
+        let solutions = [participant(0, 30, Some(100_000)), participant(1, 40, None)];
+        assert_eq!(
+            WinnerSelection::SurplusPerGas.reference_score(&solutions),
+            30.into()
+        );
+    }
+
+    #[test]
+    fn only_combinatorial_selects_multiple_winners() {
+        assert_eq!(WinnerSelection::Score.max_winners(3), 1);
+        assert_eq!(WinnerSelection::SurplusPerGas.max_winners(3), 1);
+        assert_eq!(WinnerSelection::FairCombinatorial.max_winners(3), 3);
+        assert_eq!(WinnerSelection::Score.max_winners(0), 0);
+    }
+}
@@ -69,26 +69,6 @@ impl From<H160> for WrappedNativeToken {
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, From, Into)]
 pub struct TokenAmount(pub U256);
 
-impl TokenAmount {
-    /// Applies a factor to the token amount.
-    ///
-    /// The factor is first multiplied by 10^18 to convert it to integer, to
-    /// avoid rounding to 0. Then, the token amount is divided by 10^18 to
-    /// convert it back to the original scale.
-    ///
-    /// The higher the conversion factor (10^18) the precision is higher. E.g.
-    /// 0.123456789123456789 will be converted to 123456789123456789.
-    pub fn apply_factor(&self, factor: f64) -> Option<Self> {
-        Some(
-            (self
-                .0
-                .checked_mul(U256::from_f64_lossy(factor * 1000000000000000000.))?
-                / 1000000000000000000u128)
-                .into(),
-        )
-    }
-}
-
 /// An ERC20 sell token amount.
 ///
 /// https://eips.ethereum.org/EIPS/eip-20
@@ -14,6 +14,7 @@ use {
     },
     app_data::Validator,
     derive_more::Into,
+    model::{app_data::AppDataDocument, fee_policy},
     primitive_types::{H160, U256},
     prometheus::core::Number,
     std::{collections::HashSet, str::FromStr},
@@ -36,6 +37,16 @@ impl From<arguments::FeePolicyOrderClass> for OrderClass {
     }
 }
 
+impl From<&OrderClass> for fee_policy::FeePolicyOrderClass {
+    fn from(value: &OrderClass) -> Self {
+        match value {
+            OrderClass::Market => Self::Market,
+            OrderClass::Limit => Self::Limit,
+            OrderClass::Any => Self::Any,
+        }
+    }
+}
+
 /// Constructs fee policies based on the current configuration.
 pub struct ProtocolFee {
     policy: policy::Policy,
@@ -73,6 +84,21 @@ impl ProtocolFees {
         }
     }
 
+    /// The configuration of the protocol fees as published to integrators.
+    pub fn configuration(&self) -> fee_policy::FeePolicyConfiguration {
+        fee_policy::FeePolicyConfiguration {
+            policies: self
+                .fee_policies
+                .iter()
+                .map(|fee_policy| fee_policy::ConfiguredFeePolicy {
+                    order_class: (&fee_policy.order_class).into(),
+                    kind: (&fee_policy.policy).into(),
+                })
+                .collect(),
+            max_partner_fee: self.max_partner_fee.into(),
+        }
+    }
+
     /// Converts an order from the boundary layer to the domain layer, applying
     /// protocol fees if necessary.
     pub fn apply(
@@ -86,18 +112,25 @@ impl ProtocolFees {
             .full_app_data
             .as_ref()
             .and_then(|full_app_data| {
-                Validator::new(usize::MAX)
-                    .validate(full_app_data.as_bytes())
-                    .ok()?
-                    .protocol
-                    .partner_fee
-                    .map(|partner_fee| Policy::Volume {
-                        factor: FeeFactor::try_from_capped(
-                            partner_fee.bps.into_f64() / 10_000.0,
-                            self.max_partner_fee.into(),
-                        )
-                        .unwrap(),
-                    })
+                let partner_fee = match AppDataDocument::parse(full_app_data) {
+                    Ok(document) => document.partner_fee().cloned(),
+                    // The document may not match the typed schema for reasons
+                    // unrelated to the partner fee.
+                    Err(_) => {
+                        Validator::new(usize::MAX)
+                            .validate(full_app_data.as_bytes())
+                            .ok()?
+                            .protocol
+                            .partner_fee
+                    }
+                };
+                partner_fee.map(|partner_fee| Policy::Volume {
+                    factor: FeeFactor::try_from_capped(
+                        partner_fee.bps.into_f64() / 10_000.0,
+                        self.max_partner_fee.into(),
+                    )
+                    .unwrap(),
+                })
             })
             .into_iter()
             .collect::<Vec<_>>();
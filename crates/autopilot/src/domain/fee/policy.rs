@@ -1,10 +1,13 @@
-use crate::{
-    arguments,
-    boundary,
-    domain::{
-        self,
-        fee::{FeeFactor, Quote},
+use {
+    crate::{
+        arguments,
+        boundary,
+        domain::{
+            self,
+            fee::{FeeFactor, Quote},
+        },
     },
+    model::fee_policy,
 };
 
 pub enum Policy {
@@ -49,6 +52,30 @@ impl From<arguments::FeePolicyKind> for Policy {
     }
 }
 
+impl From<&Policy> for fee_policy::FeePolicyKind {
+    fn from(policy: &Policy) -> Self {
+        match policy {
+            Policy::Surplus(Surplus {
+                factor,
+                max_volume_factor,
+            }) => Self::Surplus {
+                factor: (*factor).into(),
+                max_volume_factor: (*max_volume_factor).into(),
+            },
+            Policy::PriceImprovement(PriceImprovement {
+                factor,
+                max_volume_factor,
+            }) => Self::PriceImprovement {
+                factor: (*factor).into(),
+                max_volume_factor: (*max_volume_factor).into(),
+            },
+            Policy::Volume(Volume { factor }) => Self::Volume {
+                factor: (*factor).into(),
+            },
+        }
+    }
+}
+
 impl Surplus {
     pub fn apply(&self, order: &boundary::Order) -> Option<domain::fee::Policy> {
         match order.metadata.class {
@@ -0,0 +1,95 @@
+//! How well a settlement delivered what its solver promised.
+//!
+//! During the competition every solution reports the amounts it is going to
+//! trade for each order. Once the settlement is observed onchain the actually
+//! traded amounts are compared with the promised ones. The per-order
+//! deviations are aggregated into an execution quality score per solver.
+
+use crate::domain::{self, eth};
+
+/// Amounts traded for an order, including all fees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Amounts {
+    /// The effective amount that left the user's wallet including all fees.
+    pub sell: eth::TokenAmount,
+    /// The effective amount the user received after all fees.
+    pub buy: eth::TokenAmount,
+}
+
+/// Promised and executed amounts of a single settled order.
+#[derive(Clone, Debug)]
+pub struct ExecutionQuality {
+    pub order: domain::OrderUid,
+    pub promised: Amounts,
+    pub executed: Amounts,
+}
+
+impl ExecutionQuality {
+    /// Relative difference between the executed and the promised price,
+    /// i.e. `(executed.buy / executed.sell) / (promised.buy / promised.sell) -
+    /// 1`. A negative value means the user got a worse price than promised.
+    ///
+    /// Returns `None` if any of the amounts is zero.
+    pub fn deviation(&self) -> Option<f64> {
+        let amounts = [
+            self.promised.sell,
+            self.promised.buy,
+            self.executed.sell,
+            self.executed.buy,
+        ];
+        if amounts.iter().any(|amount| amount.0.is_zero()) {
+            return None;
+        }
+        let executed = self.executed.buy.0.to_f64_lossy() * self.promised.sell.0.to_f64_lossy();
+        let promised = self.promised.buy.0.to_f64_lossy() * self.executed.sell.0.to_f64_lossy();
+        Some(executed / promised - 1.)
+    }
+}
+
+/// Execution quality of a solver aggregated over its most recently settled
+/// orders.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolverScore {
+    pub solver: eth::Address,
+    /// Number of orders the score is based on.
+    pub orders: u64,
+    /// Average deviation of all orders. See [`ExecutionQuality::deviation`].
+    pub mean_deviation: f64,
+    /// Number of orders that were executed at a worse price than promised.
+    pub shortfalls: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, primitive_types::U256};
+
+    fn quality(promised: (u64, u64), executed: (u64, u64)) -> ExecutionQuality {
+        let amounts = |(sell, buy): (u64, u64)| Amounts {
+            sell: U256::from(sell).into(),
+            buy: U256::from(buy).into(),
+        };
+        ExecutionQuality {
+            order: domain::OrderUid([0; 56]),
+            promised: amounts(promised),
+            executed: amounts(executed),
+        }
+    }
+
+    #[test]
+    fn deviation() {
+        // Delivered exactly what was promised.
+        assert_eq!(quality((100, 200), (100, 200)).deviation(), Some(0.));
+        // Partially filled at the promised price.
+        assert_eq!(quality((100, 200), (50, 100)).deviation(), Some(0.));
+        // User received 10% less than promised.
+        let deviation = quality((100, 200), (100, 180)).deviation().unwrap();
+        assert!((deviation + 0.1).abs() < 1e-12);
+        // User sold 25% more than promised for the same buy amount.
+        let deviation = quality((100, 200), (125, 200)).deviation().unwrap();
+        assert!((deviation + 0.2).abs() < 1e-12);
+        // Better than promised.
+        assert!(quality((100, 200), (100, 210)).deviation().unwrap() > 0.);
+        // Nothing traded.
+        assert_eq!(quality((100, 200), (0, 0)).deviation(), None);
+    }
+}
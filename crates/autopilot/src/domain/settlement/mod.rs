@@ -12,11 +12,21 @@ use {
 };
 
 mod auction;
+pub mod execution_quality;
 mod observer;
+pub mod reliability;
 mod trade;
 mod transaction;
+mod unauthorized;
 use chain::Chain;
-pub use {auction::Auction, observer::Observer, trade::Trade, transaction::Transaction};
+pub use {
+    auction::Auction,
+    execution_quality::ExecutionQuality,
+    observer::Observer,
+    trade::Trade,
+    transaction::Transaction,
+    unauthorized::Unauthorized,
+};
 
 /// A settled transaction together with the `Auction`, for which it was executed
 /// on-chain.
@@ -50,6 +60,16 @@ impl Settlement {
         self.gas_price
     }
 
+    /// The address of the solver that submitted the settlement.
+    pub fn solver(&self) -> eth::Address {
+        self.solver
+    }
+
+    /// The block in which the settlement was included.
+    pub fn block(&self) -> eth::BlockNo {
+        self.block
+    }
+
     /// Total surplus for all trades in the settlement.
     pub fn surplus_in_ether(&self) -> eth::Ether {
         self.trades
@@ -90,37 +110,100 @@ impl Settlement {
 
     /// Per order fees breakdown. Contains all orders from the settlement
     pub fn fee_breakdown(&self) -> HashMap<domain::OrderUid, trade::FeeBreakdown> {
-        self.trades
-            .iter()
-            .map(|trade| {
-                let fee_breakdown = trade.fee_breakdown(&self.auction).unwrap_or_else(|err| {
-                    tracing::warn!(
-                        ?err,
-                        trade = %trade.uid(),
-                        "possible incomplete fee breakdown calculation",
-                    );
-                    trade::FeeBreakdown {
-                        total: eth::Asset {
-                            // TODO surplus token
-                            token: trade.sell_token(),
-                            amount: num::zero(),
-                        },
-                        protocol: vec![],
-                    }
-                });
-                (*trade.uid(), fee_breakdown)
+        self.executions()
+            .into_iter()
+            .filter_map(|(uid, trades)| {
+                let fee_breakdown = trades
+                    .into_iter()
+                    .map(|trade| {
+                        trade.fee_breakdown(&self.auction).unwrap_or_else(|err| {
+                            tracing::warn!(
+                                ?err,
+                                trade = %trade.uid(),
+                                "possible incomplete fee breakdown calculation",
+                            );
+                            trade::FeeBreakdown {
+                                total: eth::Asset {
+                                    // TODO surplus token
+                                    token: trade.sell_token(),
+                                    amount: num::zero(),
+                                },
+                                protocol: vec![],
+                            }
+                        })
+                    })
+                    .reduce(|mut total, fee_breakdown| {
+                        total.accumulate(fee_breakdown);
+                        total
+                    })?;
+                Some((*uid, fee_breakdown))
+            })
+            .collect()
+    }
+
+    /// Compares the traded amounts of all orders with the amounts the solver
+    /// promised for them. Orders without a promise are skipped. Orders that
+    /// got executed multiple times are compared with the sum of all their
+    /// executions.
+    pub fn execution_quality(
+        &self,
+        promised: &HashMap<domain::OrderUid, execution_quality::Amounts>,
+    ) -> Vec<ExecutionQuality> {
+        self.executions()
+            .into_iter()
+            .filter_map(|(uid, trades)| {
+                let promised = *promised.get(uid)?;
+                let executed = trades
+                    .into_iter()
+                    .map(|trade| trade.traded_amounts())
+                    .collect::<Result<Vec<_>, _>>()
+                    .inspect_err(|err| {
+                        tracing::warn!(
+                            ?err,
+                            trade = %uid,
+                            "failed to compute traded amounts",
+                        )
+                    })
+                    .ok()?
+                    .into_iter()
+                    .reduce(|total, amounts| execution_quality::Amounts {
+                        sell: total.sell + amounts.sell,
+                        buy: total.buy + amounts.buy,
+                    })?;
+                Some(ExecutionQuality {
+                    order: *uid,
+                    promised,
+                    executed,
+                })
             })
             .collect()
     }
 
     /// Return all trades that are classified as Just-In-Time (JIT) orders.
+    /// Orders executed multiple times are only returned once.
     pub fn jit_orders(&self) -> Vec<&trade::Jit> {
-        self.trades
-            .iter()
-            .filter_map(|trade| trade.as_jit())
+        self.executions()
+            .into_iter()
+            .filter_map(|(_, trades)| trades.into_iter().next()?.as_jit())
             .collect()
     }
 
+    /// Trades grouped by the order they executed, in the order of their first
+    /// execution. A single settlement can execute the same partially fillable
+    /// order multiple times.
+    fn executions(&self) -> Vec<(&domain::OrderUid, Vec<&Trade>)> {
+        let mut executions: Vec<(&domain::OrderUid, Vec<&Trade>)> = Vec::new();
+        let mut indices = HashMap::new();
+        for trade in &self.trades {
+            let index = *indices.entry(trade.uid()).or_insert_with(|| {
+                executions.push((trade.uid(), Vec::new()));
+                executions.len() - 1
+            });
+            executions[index].1.push(trade);
+        }
+        executions
+    }
+
     pub async fn new(
         settled: Transaction,
         persistence: &infra::Persistence,
@@ -221,6 +304,7 @@ mod tests {
             domain::{auction, eth},
         },
         hex_literal::hex,
+        shared::encoded_settlement::EncodedSettlement,
         std::collections::{HashMap, HashSet},
     };
 
@@ -444,7 +528,7 @@ mod tests {
         ));
         let transaction = super::transaction::Transaction::new(
             &domain::eth::Transaction {
-                input: calldata.into(),
+                input: calldata.clone().into(),
                 ..Default::default()
             },
             &domain_separator,
@@ -495,6 +579,64 @@ mod tests {
             trade.score(&auction).unwrap().0,
             eth::U256::from(769018961144624u128) // 2 x surplus
         );
+
+        // The order is partially fillable, so it could have been executed
+        // multiple times within the same settlement. Replay the settlement
+        // with the trade encoded twice, all executions are accounted for
+        // together.
+        let (settle, auction_id) = calldata.split_at(calldata.len() - 8);
+        let mut settle = EncodedSettlement::decode_calldata(settle).unwrap();
+        settle.trades.push(settle.trades[0].clone());
+        let transaction = super::transaction::Transaction::new(
+            &domain::eth::Transaction {
+                input: [settle.encode_calldata().as_slice(), auction_id]
+                    .concat()
+                    .into(),
+                ..Default::default()
+            },
+            &domain_separator,
+        )
+        .unwrap();
+        assert_eq!(transaction.auction_id, 9212204);
+        let settlement = super::Settlement {
+            gas: Default::default(),
+            gas_price: Default::default(),
+            solver: Default::default(),
+            block: Default::default(),
+            trades: transaction
+                .trades
+                .into_iter()
+                .map(|trade| super::trade::Trade::new(trade, &auction, 0))
+                .collect(),
+            auction,
+        };
+        assert_eq!(
+            settlement.surplus_in_ether().0,
+            eth::U256::from(2 * 384509480572312u128)
+        );
+
+        let single = trade.fee_breakdown(&settlement.auction).unwrap();
+        let fee_breakdown = settlement.fee_breakdown();
+        assert_eq!(fee_breakdown.len(), 1);
+        let fee_breakdown = &fee_breakdown[&order_uid];
+        assert_eq!(fee_breakdown.total.amount.0, single.total.amount.0 * 2);
+        assert_eq!(fee_breakdown.protocol.len(), 1);
+        assert_eq!(
+            fee_breakdown.protocol[0].fee.amount.0,
+            single.protocol[0].fee.amount.0 * 2
+        );
+
+        let executed = trade.traded_amounts().unwrap();
+        let promised = HashMap::from([(
+            order_uid,
+            super::execution_quality::Amounts {
+                sell: executed.sell + executed.sell,
+                buy: executed.buy + executed.buy,
+            },
+        )]);
+        let qualities = settlement.execution_quality(&promised);
+        assert_eq!(qualities.len(), 1);
+        assert_eq!(qualities[0].deviation(), Some(0.));
     }
 
     // https://etherscan.io/tx/0x24ea2ea3d70db3e864935008d14170389bda124c786ca90dfb745278db9d24ee
@@ -11,7 +11,10 @@
 // used etc.
 
 use {
-    crate::{domain::settlement, infra},
+    crate::{
+        domain::{self, eth, settlement},
+        infra::{self, export::dto},
+    },
     anyhow::{anyhow, Result},
 };
 
@@ -19,13 +22,22 @@ use {
 pub struct Observer {
     eth: infra::Ethereum,
     persistence: infra::Persistence,
+    exporter: infra::Exporter,
 }
 
 impl Observer {
     /// Creates a new Observer and asynchronously schedules the first update
     /// run.
-    pub fn new(eth: infra::Ethereum, persistence: infra::Persistence) -> Self {
-        Self { eth, persistence }
+    pub fn new(
+        eth: infra::Ethereum,
+        persistence: infra::Persistence,
+        exporter: infra::Exporter,
+    ) -> Self {
+        Self {
+            eth,
+            persistence,
+            exporter,
+        }
     }
 
     /// Fetches all the available missing data needed for bookkeeping.
@@ -114,8 +126,104 @@ impl Observer {
             ));
         }
 
+        if let Some(settlement) = &settlement {
+            self.exporter.publish(dto::Event::SettlementObserved(
+                dto::SettlementObserved::new(&event, auction_id, settlement),
+            ));
+            self.check_authorization(&event, auction_id, settlement.solver())
+                .await?;
+            if let Err(err) = self.record_execution_quality(auction_id, settlement).await {
+                tracing::warn!(hash = ?event.transaction, ?auction_id, ?err, "failed to record execution quality");
+            }
+        }
+
         Ok(true)
     }
+
+    /// Checks that the solver won the auction it settled and records the
+    /// settlement otherwise. Only settlements referencing an auction of this
+    /// environment get checked, so production settlements observed by staging
+    /// don't get flagged.
+    async fn check_authorization(
+        &self,
+        event: &eth::SettlementEvent,
+        auction_id: domain::auction::Id,
+        solver: eth::Address,
+    ) -> Result<()> {
+        let won = self.persistence.solver_won(auction_id, solver).await?;
+        let Some(reason) = settlement::Unauthorized::classify(won) else {
+            return Ok(());
+        };
+        tracing::error!(
+            hash = ?event.transaction,
+            ?auction_id,
+            ?solver,
+            reason = reason.as_str(),
+            "solver settled an auction it did not win"
+        );
+        Metrics::get()
+            .unauthorized_settlements
+            .with_label_values(&[&format!("{:?}", solver.0), reason.as_str()])
+            .inc();
+        self.persistence
+            .save_unauthorized_settlement(event, auction_id, solver, reason)
+            .await?;
+        Ok(())
+    }
+
+    /// Compares the traded amounts of the settlement with the amounts the
+    /// solver promised in its winning solution and stores the result.
+    async fn record_execution_quality(
+        &self,
+        auction_id: domain::auction::Id,
+        settlement: &settlement::Settlement,
+    ) -> Result<()> {
+        let promised = self
+            .persistence
+            .fetch_promised_amounts(auction_id, settlement.solver())
+            .await?;
+        let qualities = settlement.execution_quality(&promised);
+        let solver = format!("{:?}", settlement.solver().0);
+        let metrics = Metrics::get();
+        for deviation in qualities.iter().filter_map(|quality| quality.deviation()) {
+            metrics
+                .execution_quality_orders
+                .with_label_values(&[&solver])
+                .inc();
+            if deviation < 0. {
+                metrics
+                    .execution_quality_shortfalls
+                    .with_label_values(&[&solver])
+                    .inc();
+            }
+        }
+        self.persistence
+            .save_execution_quality(auction_id, settlement, &qualities)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "settlement_observer")]
+struct Metrics {
+    /// Settlements of auctions the submitting solver did not win.
+    #[metric(labels("solver", "reason"))]
+    unauthorized_settlements: prometheus::IntCounterVec,
+
+    /// Settled orders whose execution got compared with the winning solution.
+    #[metric(labels("solver"))]
+    execution_quality_orders: prometheus::IntCounterVec,
+
+    /// Settled orders that were executed at a worse price than promised.
+    #[metric(labels("solver"))]
+    execution_quality_shortfalls: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
 }
 
 /// Whether Observer loop should retry on the given error.
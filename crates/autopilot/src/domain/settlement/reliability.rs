@@ -0,0 +1,120 @@
+//! How reliably solvers settle the auctions they win.
+//!
+//! A won auction that didn't get settled before its deadline means that the
+//! solver's settlement reverted, was never submitted or missed the deadline.
+//! Either way the orders of the auction didn't get executed even though no
+//! other solver was allowed to settle them.
+
+use {
+    crate::domain::{auction, eth},
+    chrono::{DateTime, Utc},
+    std::collections::HashMap,
+};
+
+/// An auction a solver won whose deadline passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WonAuction {
+    pub auction: auction::Id,
+    pub solver: eth::Address,
+    /// Whether the solver settled the auction before its deadline.
+    pub settled: bool,
+}
+
+/// Exclusion of a solver from the competition for not settling the auctions
+/// it won.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Exclusion {
+    pub solver: eth::Address,
+    pub until: DateTime<Utc>,
+    /// The most recent won auction the exclusion was based on. Only auctions
+    /// won afterwards count towards excluding the solver again.
+    pub latest_auction: auction::Id,
+}
+
+/// Share of the auctions a solver won that it also settled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SettlementRate {
+    pub solver: eth::Address,
+    pub won: u64,
+    pub settled: u64,
+    /// The most recent auction the rate is based on.
+    pub latest_auction: auction::Id,
+}
+
+impl SettlementRate {
+    pub fn rate(&self) -> f64 {
+        self.settled as f64 / self.won as f64
+    }
+}
+
+/// Aggregates the won auctions into the settlement rate of every solver. Only
+/// auctions after the one `since` returns for a solver are taken into account.
+pub fn settlement_rates(
+    auctions: &[WonAuction],
+    since: impl Fn(&eth::Address) -> Option<auction::Id>,
+) -> Vec<SettlementRate> {
+    let mut rates = HashMap::<eth::Address, SettlementRate>::new();
+    for won in auctions {
+        if since(&won.solver).is_some_and(|since| won.auction <= since) {
+            continue;
+        }
+        let rate = rates.entry(won.solver).or_insert(SettlementRate {
+            solver: won.solver,
+            won: 0,
+            settled: 0,
+            latest_auction: won.auction,
+        });
+        rate.won += 1;
+        rate.settled += u64::from(won.settled);
+        rate.latest_auction = rate.latest_auction.max(won.auction);
+    }
+    rates.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, primitive_types::H160};
+
+    #[test]
+    fn aggregates_auctions_since_exclusion() {
+        let solver = |byte| eth::Address::from(H160([byte; 20]));
+        let won = |auction, byte, settled| WonAuction {
+            auction,
+            solver: solver(byte),
+            settled,
+        };
+        let auctions = [
+            won(1, 1, false),
+            won(2, 1, true),
+            won(3, 2, false),
+            won(4, 1, true),
+            won(5, 2, true),
+        ];
+
+        let mut rates = settlement_rates(&auctions, |_| None);
+        rates.sort_by_key(|rate| rate.solver);
+        assert_eq!(
+            rates,
+            [
+                SettlementRate {
+                    solver: solver(1),
+                    won: 3,
+                    settled: 2,
+                    latest_auction: 4,
+                },
+                SettlementRate {
+                    solver: solver(2),
+                    won: 2,
+                    settled: 1,
+                    latest_auction: 5,
+                },
+            ]
+        );
+
+        // Auctions up to the given one are ignored for the solver.
+        let rates = settlement_rates(&auctions, |solver| (solver.0 == H160([1; 20])).then_some(2));
+        let rate = rates.iter().find(|rate| rate.solver == solver(1)).unwrap();
+        assert_eq!((rate.won, rate.settled), (1, 1));
+        assert_eq!(rate.rate(), 1.);
+    }
+}
@@ -1,21 +1,22 @@
 pub use error::Error;
 use {
     super::ExecutedProtocolFee,
-    crate::{
-        domain::{
-            self,
-            auction::{self, order},
-            eth,
-            fee,
-            settlement::{
-                transaction::{ClearingPrices, Prices},
-                {self},
-            },
+    crate::domain::{
+        self,
+        auction::{self, order},
+        eth,
+        fee,
+        settlement::{
+            transaction::{ClearingPrices, Prices},
+            {self},
         },
-        util::conv::U256Ext,
     },
     bigdecimal::Zero,
-    num::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub},
+    num::{CheckedAdd, CheckedSub},
+    number::{
+        conversions::{mul_ratio_ceil, mul_ratio_floor, safe_mul_ceil_div, safe_mul_div},
+        ratio::Ratio256,
+    },
 };
 
 /// A trade containing bare minimum of onchain information required to calculate
@@ -51,43 +52,24 @@ impl Trade {
         match self.side {
             order::Side::Buy => {
                 // scale limit sell to support partially fillable orders
-                let limit_sell = price_limits
-                    .sell
-                    .0
-                    .checked_mul(self.executed.into())
-                    .ok_or(error::Math::Overflow)?
-                    .checked_div(price_limits.buy.0)
-                    .ok_or(error::Math::DivisionByZero)?;
-                let sold = self
-                    .executed
-                    .0
-                    .checked_mul(prices.buy)
-                    .ok_or(error::Math::Overflow)?
-                    .checked_div(prices.sell)
-                    .ok_or(error::Math::DivisionByZero)?;
+                let limit_sell = mul_ratio_floor(
+                    price_limits.sell.0,
+                    self.executed.into(),
+                    price_limits.buy.0,
+                )?;
+                let sold = mul_ratio_floor(self.executed.0, prices.buy, prices.sell)?;
                 limit_sell.checked_sub(sold).ok_or(error::Math::Negative)
             }
             order::Side::Sell => {
                 // scale limit buy to support partially fillable orders
 
-                // `checked_ceil_div`` to be consistent with how settlement contract calculates
+                // `mul_ratio_ceil` to be consistent with how settlement contract calculates
                 // traded buy amounts
                 // smallest allowed executed_buy_amount per settlement contract is
                 // executed_sell_amount * ceil(price_limits.buy / price_limits.sell)
-                let limit_buy = self
-                    .executed
-                    .0
-                    .checked_mul(price_limits.buy.0)
-                    .ok_or(error::Math::Overflow)?
-                    .checked_ceil_div(&price_limits.sell.0)
-                    .ok_or(error::Math::DivisionByZero)?;
-                let bought = self
-                    .executed
-                    .0
-                    .checked_mul(prices.sell)
-                    .ok_or(error::Math::Overflow)?
-                    .checked_ceil_div(&prices.buy)
-                    .ok_or(error::Math::DivisionByZero)?;
+                let limit_buy =
+                    mul_ratio_ceil(self.executed.0, price_limits.buy.0, price_limits.sell.0)?;
+                let bought = mul_ratio_ceil(self.executed.0, prices.sell, prices.buy)?;
                 bought.checked_sub(limit_buy).ok_or(error::Math::Negative)
             }
         }
@@ -122,11 +104,11 @@ impl Trade {
     fn fee_into_sell_token(&self, fee: eth::TokenAmount) -> Result<eth::SellTokenAmount, Error> {
         let fee_in_sell_token = match self.side {
             order::Side::Buy => fee,
-            order::Side::Sell => fee
-                .checked_mul(&self.prices.uniform.buy.into())
-                .ok_or(error::Math::Overflow)?
-                .checked_div(&self.prices.uniform.sell.into())
-                .ok_or(error::Math::DivisionByZero)?,
+            order::Side::Sell => {
+                mul_ratio_floor(fee.0, self.prices.uniform.buy, self.prices.uniform.sell)
+                    .map_err(error::Math::from)?
+                    .into()
+            }
         }
         .into();
         Ok(fee_in_sell_token)
@@ -195,16 +177,14 @@ impl Trade {
     /// The effective amount that left the user's wallet including all fees.
     ///
     /// Note how the `executed` amount is used to build actual traded amounts.
-    fn sell_amount(&self) -> Result<eth::TokenAmount, error::Math> {
+    pub fn sell_amount(&self) -> Result<eth::TokenAmount, error::Math> {
         Ok(match self.side {
             order::Side::Sell => self.executed.0,
-            order::Side::Buy => self
-                .executed
-                .0
-                .checked_mul(self.prices.custom.buy)
-                .ok_or(error::Math::Overflow)?
-                .checked_div(self.prices.custom.sell)
-                .ok_or(error::Math::DivisionByZero)?,
+            order::Side::Buy => safe_mul_div(
+                self.executed.0,
+                self.prices.custom.buy,
+                self.prices.custom.sell,
+            )?,
         }
         .into())
     }
@@ -214,15 +194,13 @@ impl Trade {
     /// Note how the `executed` amount is used to build actual traded amounts.
     ///
     /// Settlement contract uses `ceil` division for buy amount calculation.
-    fn buy_amount(&self) -> Result<eth::TokenAmount, error::Math> {
+    pub fn buy_amount(&self) -> Result<eth::TokenAmount, error::Math> {
         Ok(match self.side {
-            order::Side::Sell => self
-                .executed
-                .0
-                .checked_mul(self.prices.custom.sell)
-                .ok_or(error::Math::Overflow)?
-                .checked_ceil_div(&self.prices.custom.buy)
-                .ok_or(error::Math::DivisionByZero)?,
+            order::Side::Sell => safe_mul_ceil_div(
+                self.executed.0,
+                self.prices.custom.sell,
+                self.prices.custom.buy,
+            )?,
             order::Side::Buy => self.executed.0,
         }
         .into())
@@ -359,10 +337,15 @@ impl Trade {
         //
         // Finally:
         //     fee = surplus_after_fee * factor / (1 - factor)
-        let fee = surplus
-            .amount
-            .apply_factor(factor / (1.0 - factor))
-            .ok_or(error::Math::Overflow)?;
+        let factor = Ratio256::from_factor(factor);
+        let remainder = Ratio256::one()
+            .checked_sub(&factor)
+            .ok_or(error::Math::Negative)?;
+        let factor = (&factor / &remainder).map_err(error::Math::from)?;
+        let fee = (Ratio256::from(surplus.amount.0) * &factor)
+            .floor()
+            .map_err(error::Math::from)?
+            .into();
 
         Ok(eth::Asset {
             token: surplus.token,
@@ -404,18 +387,21 @@ impl Trade {
             order::Side::Buy => self.sell_amount()?,
             order::Side::Sell => self.buy_amount()?,
         };
-        let factor = match self.side {
-            order::Side::Sell => factor / (1.0 - factor),
-            order::Side::Buy => factor / (1.0 + factor),
+        let factor = Ratio256::from_factor(factor);
+        let denominator = match self.side {
+            order::Side::Sell => Ratio256::one()
+                .checked_sub(&factor)
+                .ok_or(error::Math::Negative)?,
+            order::Side::Buy => &Ratio256::one() + &factor,
         };
+        let factor = (&factor / &denominator).map_err(error::Math::from)?;
 
         Ok(eth::Asset {
             token: self.surplus_token(),
-            amount: {
-                executed_in_surplus_token
-                    .apply_factor(factor)
-                    .ok_or(error::Math::Overflow)?
-            },
+            amount: (Ratio256::from(executed_in_surplus_token.0) * &factor)
+                .floor()
+                .map_err(error::Math::from)?
+                .into(),
         })
     }
 
@@ -471,20 +457,10 @@ fn adjust_quote_to_order_limits(order: Order, quote: Quote) -> Result<PriceLimit
         order::Side::Sell => {
             let quote_buy_amount = quote
                 .buy
-                .checked_sub(
-                    &quote
-                        .fee
-                        .checked_mul(&quote.buy)
-                        .ok_or(error::Math::Overflow)?
-                        .checked_div(&quote.sell)
-                        .ok_or(error::Math::DivisionByZero)?,
-                )
+                .checked_sub(&mul_ratio_floor(quote.fee.0, quote.buy.0, quote.sell.0)?.into())
                 .ok_or(error::Math::Negative)?;
-            let scaled_buy_amount = quote_buy_amount
-                .checked_mul(&order.sell)
-                .ok_or(error::Math::Overflow)?
-                .checked_div(&quote.sell)
-                .ok_or(error::Math::DivisionByZero)?;
+            let scaled_buy_amount: eth::TokenAmount =
+                mul_ratio_floor(quote_buy_amount.0, order.sell.0, quote.sell.0)?.into();
             let buy_amount = order.buy.max(scaled_buy_amount);
             Ok(PriceLimits {
                 sell: order.sell,
@@ -496,11 +472,8 @@ fn adjust_quote_to_order_limits(order: Order, quote: Quote) -> Result<PriceLimit
                 .sell
                 .checked_add(&quote.fee)
                 .ok_or(error::Math::Overflow)?;
-            let scaled_sell_amount = quote_sell_amount
-                .checked_mul(&order.buy)
-                .ok_or(error::Math::Overflow)?
-                .checked_div(&quote.buy)
-                .ok_or(error::Math::DivisionByZero)?;
+            let scaled_sell_amount: eth::TokenAmount =
+                mul_ratio_floor(quote_sell_amount.0, order.buy.0, quote.buy.0)?.into();
             let sell_amount = order.sell.min(scaled_sell_amount);
             Ok(PriceLimits {
                 sell: sell_amount,
@@ -578,4 +551,13 @@ pub mod error {
         #[error("negative")]
         Negative,
     }
+
+    impl From<number::conversions::RatioError> for Math {
+        fn from(err: number::conversions::RatioError) -> Self {
+            match err {
+                number::conversions::RatioError::Overflow => Self::Overflow,
+                number::conversions::RatioError::DivisionByZero => Self::DivisionByZero,
+            }
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use {
-    super::{transaction, transaction::Prices},
+    super::{execution_quality, transaction, transaction::Prices},
     crate::domain::{
         self,
         auction::{self, order},
@@ -62,6 +62,15 @@ impl Trade {
         }
     }
 
+    /// The amounts that were effectively traded, including all fees.
+    pub fn traded_amounts(&self) -> Result<execution_quality::Amounts, math::Error> {
+        let trade = math::Trade::from(self);
+        Ok(execution_quality::Amounts {
+            sell: trade.sell_amount()?,
+            buy: trade.buy_amount()?,
+        })
+    }
+
     /// Total fee taken for the trade.
     pub fn fee_in_ether(&self, prices: &auction::Prices) -> Result<eth::Ether, math::Error> {
         math::Trade::from(self).fee_in_ether(prices)
@@ -174,6 +183,34 @@ pub struct FeeBreakdown {
     pub protocol: Vec<ExecutedProtocolFee>,
 }
 
+impl FeeBreakdown {
+    /// Adds the fees of another execution of the same order. Protocol fees
+    /// are summed per policy and token, and a total in a different token
+    /// (only possible if computing the breakdown of an execution failed) is
+    /// only taken over if no fees were accounted for yet.
+    pub fn accumulate(&mut self, other: Self) {
+        if self.total.token == other.total.token {
+            self.total.amount += other.total.amount;
+        } else if self.total.amount.0.is_zero() {
+            self.total = other.total;
+        } else if !other.total.amount.0.is_zero() {
+            tracing::warn!(
+                total = ?self.total,
+                other = ?other.total,
+                "dropping fee of an execution in a different token",
+            );
+        }
+        for other in other.protocol {
+            match self.protocol.iter_mut().find(|executed| {
+                executed.policy == other.policy && executed.fee.token == other.fee.token
+            }) {
+                Some(executed) => executed.fee.amount += other.fee.amount,
+                None => self.protocol.push(other),
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecutedProtocolFee {
     /// Policy that was used to calculate the fee.
@@ -1,6 +1,6 @@
-use crate::{
-    boundary,
-    domain::{self, auction::order, eth},
+use {
+    crate::domain::{self, auction::order, eth},
+    shared::encoded_settlement::EncodedSettlement,
 };
 
 mod tokenized;
@@ -52,60 +52,54 @@ impl Transaction {
             gas: transaction.gas,
             gas_price: transaction.gas_price,
             trades: {
-                let tokenized::Tokenized {
-                    tokens,
-                    clearing_prices,
-                    trades: decoded_trades,
-                    interactions: _interactions,
-                } = tokenized::Tokenized::try_new(&crate::util::Bytes(data.to_vec()))?;
-
-                let mut trades = Vec::with_capacity(decoded_trades.len());
-                for trade in decoded_trades {
-                    let flags = tokenized::TradeFlags(trade.8);
-                    let sell_token_index = trade.0.as_usize();
-                    let buy_token_index = trade.1.as_usize();
-                    let sell_token = tokens[sell_token_index];
-                    let buy_token = tokens[buy_token_index];
-                    let uniform_sell_token_index = tokens
-                        .iter()
-                        .position(|token| token == &sell_token)
-                        .unwrap();
-                    let uniform_buy_token_index =
-                        tokens.iter().position(|token| token == &buy_token).unwrap();
+                let settlement = EncodedSettlement::decode_calldata(data)?;
+                let price = |index: usize| {
+                    settlement
+                        .clearing_prices
+                        .get(index)
+                        .copied()
+                        .ok_or(Error::MissingClearingPrice(index))
+                };
+                let mut trades = Vec::with_capacity(settlement.trades.len());
+                for trade in settlement.decode_trades()? {
+                    let uniform_index = |token| {
+                        settlement
+                            .tokens
+                            .iter()
+                            .position(|t| *t == token)
+                            .expect("decoded trades only reference settlement tokens")
+                    };
+                    let uniform_sell_token_index = uniform_index(trade.order.sell_token);
+                    let uniform_buy_token_index = uniform_index(trade.order.buy_token);
                     trades.push(EncodedTrade {
-                        uid: tokenized::order_uid(&trade, &tokens, domain_separator)
+                        uid: tokenized::order_uid(&trade, domain_separator)
                             .map_err(Error::OrderUidRecover)?,
                         sell: eth::Asset {
-                            token: sell_token.into(),
-                            amount: trade.3.into(),
+                            token: trade.order.sell_token.into(),
+                            amount: trade.order.sell_amount.into(),
                         },
                         buy: eth::Asset {
-                            token: buy_token.into(),
-                            amount: trade.4.into(),
+                            token: trade.order.buy_token.into(),
+                            amount: trade.order.buy_amount.into(),
                         },
-                        side: flags.side(),
-                        receiver: trade.2.into(),
-                        valid_to: trade.5,
-                        app_data: domain::auction::order::AppDataHash(trade.6 .0),
-                        fee_amount: trade.7.into(),
-                        sell_token_balance: flags.sell_token_balance().into(),
-                        buy_token_balance: flags.buy_token_balance().into(),
-                        partially_fillable: flags.partially_fillable(),
-                        signature: (boundary::Signature::from_bytes(
-                            flags.signing_scheme(),
-                            &trade.10 .0,
-                        )
-                        .map_err(Error::SignatureRecover)?)
-                        .into(),
-                        executed: trade.9.into(),
+                        side: trade.order.kind.into(),
+                        receiver: trade.order.receiver.unwrap_or_default().into(),
+                        valid_to: trade.order.valid_to,
+                        app_data: domain::auction::order::AppDataHash(trade.order.app_data.0),
+                        fee_amount: trade.order.fee_amount.into(),
+                        sell_token_balance: trade.order.sell_token_balance.into(),
+                        buy_token_balance: trade.order.buy_token_balance.into(),
+                        partially_fillable: trade.order.partially_fillable,
+                        signature: trade.signature.into(),
+                        executed: trade.executed_amount.into(),
                         prices: Prices {
                             uniform: ClearingPrices {
-                                sell: clearing_prices[uniform_sell_token_index].into(),
-                                buy: clearing_prices[uniform_buy_token_index].into(),
+                                sell: price(uniform_sell_token_index)?,
+                                buy: price(uniform_buy_token_index)?,
                             },
                             custom: ClearingPrices {
-                                sell: clearing_prices[sell_token_index].into(),
-                                buy: clearing_prices[buy_token_index].into(),
+                                sell: price(trade.sell_token_index)?,
+                                buy: price(trade.buy_token_index)?,
                             },
                         },
                     })
@@ -156,8 +150,8 @@ pub enum Error {
     MissingAuctionId,
     #[error(transparent)]
     Decoding(#[from] tokenized::error::Decoding),
+    #[error("missing clearing price for token index {0}")]
+    MissingClearingPrice(usize),
     #[error("failed to recover order uid {0}")]
     OrderUidRecover(tokenized::error::Uid),
-    #[error("failed to recover signature {0}")]
-    SignatureRecover(#[source] anyhow::Error),
 }
@@ -0,0 +1,31 @@
+//! Detection of settlements that were not authorized by the competition.
+
+/// Why a solver was not allowed to settle the auction its settlement
+/// references.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unauthorized {
+    /// The solver did not propose a solution for the auction.
+    NotParticipated,
+    /// The solver proposed a solution for the auction but did not win.
+    NotWinner,
+}
+
+impl Unauthorized {
+    /// Classifies a settlement based on whether its solver won the referenced
+    /// auction (`None` if the solver did not participate). Returns `None` if
+    /// the settlement was authorized.
+    pub fn classify(won: Option<bool>) -> Option<Self> {
+        match won {
+            None => Some(Self::NotParticipated),
+            Some(false) => Some(Self::NotWinner),
+            Some(true) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotParticipated => "not_participated",
+            Self::NotWinner => "not_winner",
+        }
+    }
+}
@@ -23,19 +23,19 @@ where
     ///
     /// If a start sync block is specified, it will always resync events from
     /// this poing on creation, regardless of them being already available
-    /// in the database.
+    /// in the database. Reorgs up to `finality_depth` blocks deep get
+    /// reindexed.
     pub fn new(
         contract: W,
         db: Database,
         block_retriever: Arc<dyn BlockRetrieving>,
         start_sync_at_block: Option<BlockNumberHash>,
+        finality_depth: u64,
     ) -> Self {
-        Self(Mutex::new(EventHandler::new(
-            block_retriever,
-            contract,
-            db,
-            start_sync_at_block,
-        )))
+        Self(Mutex::new(
+            EventHandler::new(block_retriever, contract, db, start_sync_at_block)
+                .with_finality_depth(finality_depth),
+        ))
     }
 
     /// Creates a new event updater.
@@ -50,6 +50,7 @@ where
         db: Database,
         block_retriever: Arc<dyn BlockRetrieving>,
         start_sync_at_block: BlockNumberHash,
+        finality_depth: u64,
     ) -> Result<Self> {
         Ok(Self(Mutex::new(
             EventHandler::new_skip_blocks_before(
@@ -58,7 +59,8 @@ where
                 db,
                 start_sync_at_block,
             )
-            .await?,
+            .await?
+            .with_finality_depth(finality_depth),
         )))
     }
 }
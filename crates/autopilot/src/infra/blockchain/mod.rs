@@ -21,12 +21,14 @@ pub struct Rpc {
 
 impl Rpc {
     /// Instantiate an RPC client to an Ethereum (or Ethereum-compatible) node
-    /// at the specifed URL.
+    /// at the specifed URL which fails over to the fallback nodes in order.
     pub async fn new(
         url: &url::Url,
+        fallbacks: &[Url],
         ethrpc_args: &shared::ethrpc::Arguments,
+        http_factory: &shared::http_client::HttpClientFactory,
     ) -> Result<Self, Error> {
-        let web3 = boundary::web3_client(url, ethrpc_args);
+        let web3 = boundary::web3_client(url, fallbacks, ethrpc_args, http_factory);
         let chain =
             Chain::try_from(web3.eth().chain_id().await?).map_err(|_| Error::UnsupportedChain)?;
 
@@ -88,6 +90,16 @@ impl Ethereum {
         }
     }
 
+    /// Returns a view of the same blockchain that uses the contracts at the
+    /// given addresses, e.g. to observe another settlement contract
+    /// deployment.
+    pub async fn with_contracts(&self, addresses: contracts::Addresses) -> Self {
+        Self {
+            contracts: Contracts::new(&self.web3, &self.chain, addresses).await,
+            ..self.clone()
+        }
+    }
+
     pub fn chain(&self) -> &Chain {
         &self.chain
     }
@@ -123,6 +135,19 @@ impl Ethereum {
             .ok_or(Error::TransactionNotFound)?;
         into_domain(transaction, receipt, block.timestamp).map_err(Error::IncompleteTransactionData)
     }
+
+    /// Returns the balance of `owner` of the given ERC20 token.
+    pub async fn erc20_balance(
+        &self,
+        token: eth::TokenAddress,
+        owner: eth::Address,
+    ) -> Result<eth::TokenAmount, Error> {
+        let balance = ::contracts::ERC20::at(&self.web3, token.0)
+            .balance_of(owner.0)
+            .call()
+            .await?;
+        Ok(balance.into())
+    }
 }
 
 fn into_domain(
@@ -158,6 +183,8 @@ fn into_domain(
 pub enum Error {
     #[error("web3 error: {0:?}")]
     Web3(#[from] web3::error::Error),
+    #[error("contract call error: {0:?}")]
+    Contract(#[from] ethcontract::errors::MethodError),
     #[error("missing field {0}, node client bug?")]
     IncompleteTransactionData(anyhow::Error),
     #[error("transaction not found")]
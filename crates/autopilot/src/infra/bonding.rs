@@ -0,0 +1,206 @@
+//! Monitors the bonding pools backing the solvers.
+//!
+//! Solvers are only allowed to compete as long as their bonding pool holds
+//! at least the required amount of each bonding token. The balances of all
+//! pools get polled periodically and deposits and withdrawals are detected
+//! by comparing them with the previously observed balances. Drivers whose
+//! pool falls below any requirement get suspended from the competition
+//! until the pool gets topped up again.
+
+use {
+    crate::{domain::eth, infra},
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, Mutex, RwLock},
+        time::Duration,
+    },
+    tokio::time,
+};
+
+/// A bonding pool and the drivers it backs.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub address: eth::Address,
+    pub drivers: Vec<String>,
+}
+
+/// Minimum amount of a token a bonding pool has to hold.
+#[derive(Clone, Copy, Debug)]
+pub struct Requirement {
+    pub token: eth::TokenAddress,
+    pub amount: eth::TokenAmount,
+}
+
+pub struct BondingPools {
+    eth: infra::Ethereum,
+    pools: Vec<Pool>,
+    requirements: Vec<Requirement>,
+    /// Last observed balances used to detect deposits and withdrawals.
+    balances: Mutex<HashMap<(eth::Address, eth::TokenAddress), eth::TokenAmount>>,
+    /// Names of the drivers currently suspended from the competition.
+    suspended: RwLock<HashSet<String>>,
+}
+
+impl BondingPools {
+    pub fn new(eth: infra::Ethereum, pools: Vec<Pool>, requirements: Vec<Requirement>) -> Self {
+        Self {
+            eth,
+            pools,
+            requirements,
+            balances: Default::default(),
+            suspended: Default::default(),
+        }
+    }
+
+    /// Returns whether the driver is currently suspended because its bonding
+    /// pool does not hold enough funds. Drivers without a configured bonding
+    /// pool are never suspended.
+    pub fn is_suspended(&self, driver: &str) -> bool {
+        self.suspended.read().unwrap().contains(driver)
+    }
+
+    pub async fn run_forever(self: Arc<Self>, update_interval: Duration) -> ! {
+        let mut interval = time::interval(update_interval);
+        loop {
+            interval.tick().await;
+            self.update().await;
+        }
+    }
+
+    async fn update(&self) {
+        let pools = futures::future::join_all(self.pools.iter().map(|pool| async move {
+            let balances = futures::future::join_all(
+                self.requirements
+                    .iter()
+                    .map(|requirement| self.eth.erc20_balance(requirement.token, pool.address)),
+            )
+            .await;
+            (pool, balances)
+        }))
+        .await;
+
+        'pools: for (pool, balances) in pools {
+            let mut sufficient = true;
+            for (requirement, balance) in self.requirements.iter().zip(balances) {
+                let balance = match balance {
+                    Ok(balance) => balance,
+                    Err(err) => {
+                        // Keep the previous state of the pool's drivers
+                        // instead of suspending them because of a node issue.
+                        tracing::warn!(
+                            ?err,
+                            pool = ?pool.address,
+                            token = ?requirement.token,
+                            "failed to fetch bonding pool balance"
+                        );
+                        continue 'pools;
+                    }
+                };
+                self.observe_balance(pool, requirement.token, balance);
+                sufficient &= balance >= requirement.amount;
+            }
+            self.set_suspended(pool, !sufficient);
+        }
+    }
+
+    /// Records the balance and detects deposits and withdrawals.
+    fn observe_balance(&self, pool: &Pool, token: eth::TokenAddress, balance: eth::TokenAmount) {
+        let pool_label = format!("{:?}", pool.address.0);
+        let token_label = format!("{:?}", token.0);
+        let token_label =
+            observe::cardinality::bounded("bonding_pool_balance", "token", &token_label);
+        let metrics = Metrics::get();
+        metrics
+            .bonding_pool_balance
+            .with_label_values(&[&pool_label, &token_label])
+            .set(balance.0.to_f64_lossy());
+
+        let previous = self
+            .balances
+            .lock()
+            .unwrap()
+            .insert((pool.address, token), balance);
+        let Some(previous) = previous else {
+            tracing::info!(pool = ?pool.address, ?token, ?balance, "initial bonding pool balance");
+            return;
+        };
+        let kind = match balance.cmp(&previous) {
+            std::cmp::Ordering::Greater => "deposit",
+            std::cmp::Ordering::Less => "withdrawal",
+            std::cmp::Ordering::Equal => return,
+        };
+        tracing::info!(
+            pool = ?pool.address,
+            ?token,
+            ?previous,
+            ?balance,
+            kind,
+            "bonding pool balance changed"
+        );
+        metrics
+            .bonding_pool_transfers
+            .with_label_values(&[&pool_label, &token_label, kind])
+            .inc();
+    }
+
+    fn set_suspended(&self, pool: &Pool, suspend: bool) {
+        let metrics = Metrics::get();
+        let mut suspended = self.suspended.write().unwrap();
+        for driver in &pool.drivers {
+            let changed = if suspend {
+                suspended.insert(driver.clone())
+            } else {
+                suspended.remove(driver)
+            };
+            metrics
+                .bonding_pool_driver_suspended
+                .with_label_values(&[driver])
+                .set(i64::from(suspend));
+            if !changed {
+                continue;
+            }
+            if suspend {
+                tracing::warn!(
+                    driver,
+                    pool = ?pool.address,
+                    "suspending driver because its bonding pool is insufficiently funded"
+                );
+                metrics
+                    .bonding_pool_suspensions
+                    .with_label_values(&[driver])
+                    .inc();
+            } else {
+                tracing::info!(
+                    driver,
+                    pool = ?pool.address,
+                    "reinstating driver because its bonding pool is sufficiently funded"
+                );
+            }
+        }
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Balance of a bonding pool in atoms of the token.
+    #[metric(labels("pool", "token"))]
+    bonding_pool_balance: prometheus::GaugeVec,
+
+    /// Number of detected deposits into and withdrawals from bonding pools.
+    #[metric(labels("pool", "token", "kind"))]
+    bonding_pool_transfers: prometheus::IntCounterVec,
+
+    /// Whether a driver is currently suspended because of its bonding pool.
+    #[metric(labels("driver"))]
+    bonding_pool_driver_suspended: prometheus::IntGaugeVec,
+
+    /// Number of times a driver got suspended because of its bonding pool.
+    #[metric(labels("driver"))]
+    bonding_pool_suspensions: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
@@ -0,0 +1,245 @@
+//! Schema of the exported events.
+//!
+//! Every message is a JSON object with a `type` field identifying the event
+//! and a `version` field which gets incremented on breaking changes of the
+//! schema. Token amounts, prices and scores are encoded as decimal strings,
+//! addresses, transaction hashes and order UIDs as `0x` prefixed hex strings.
+
+use {
+    crate::domain::{self, competition, eth},
+    number::serialization::HexOrDecimalU256,
+    primitive_types::{H160, H256, U256},
+    serde::Serialize,
+    serde_with::serde_as,
+    std::collections::BTreeMap,
+};
+
+/// Current version of the schema.
+pub const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Message {
+    pub version: u32,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Event {
+    AuctionCreated(AuctionCreated),
+    CompetitionFinalized(CompetitionFinalized),
+    SettlementObserved(SettlementObserved),
+}
+
+impl Event {
+    /// Name of the event used for message keys and metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::AuctionCreated(_) => "auctionCreated",
+            Self::CompetitionFinalized(_) => "competitionFinalized",
+            Self::SettlementObserved(_) => "settlementObserved",
+        }
+    }
+
+    /// Auction the event belongs to. Used as the message key so all events of
+    /// an auction end up in the same partition.
+    pub fn auction_id(&self) -> domain::auction::Id {
+        match self {
+            Self::AuctionCreated(event) => event.auction_id,
+            Self::CompetitionFinalized(event) => event.auction_id,
+            Self::SettlementObserved(event) => event.auction_id,
+        }
+    }
+}
+
+/// A new auction was created and is about to be sent to the solvers.
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionCreated {
+    pub auction_id: domain::auction::Id,
+    /// Block on which the auction is based.
+    pub block: u64,
+    /// UIDs of all orders in the auction.
+    pub orders: Vec<String>,
+    /// Native token prices of all tokens in the auction.
+    #[serde_as(as = "BTreeMap<_, HexOrDecimalU256>")]
+    pub prices: BTreeMap<H160, U256>,
+}
+
+impl AuctionCreated {
+    pub fn new(auction: &domain::Auction) -> Self {
+        Self {
+            auction_id: auction.id,
+            block: auction.block,
+            orders: auction
+                .orders
+                .iter()
+                .map(|order| order.uid.to_string())
+                .collect(),
+            prices: auction
+                .prices
+                .iter()
+                .map(|(token, price)| (token.0, price.get().0))
+                .collect(),
+        }
+    }
+}
+
+/// The solver competition of an auction was concluded and its results were
+/// stored.
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompetitionFinalized {
+    pub auction_id: domain::auction::Id,
+    /// Last block in which the winning solutions may be settled.
+    pub block_deadline: u64,
+    /// Block on which the solutions were simulated.
+    pub competition_simulation_block: u64,
+    /// All valid solutions ordered from best to worst.
+    pub solutions: Vec<Solution>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Solution {
+    /// Name of the driver that proposed the solution.
+    pub driver: String,
+    /// Address of the solver that would settle the solution.
+    pub solver: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub score: U256,
+    pub is_winner: bool,
+    pub orders: Vec<TradedOrder>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradedOrder {
+    pub uid: String,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub executed_sell: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub executed_buy: U256,
+}
+
+impl CompetitionFinalized {
+    pub fn new(
+        auction_id: domain::auction::Id,
+        block_deadline: u64,
+        competition_simulation_block: u64,
+        solutions: &[competition::Participant],
+    ) -> Self {
+        Self {
+            auction_id,
+            block_deadline,
+            competition_simulation_block,
+            solutions: solutions
+                .iter()
+                .map(|participant| Solution {
+                    driver: participant.driver().name.clone(),
+                    solver: participant.solution().solver().0,
+                    score: participant.solution().score().get().0,
+                    is_winner: participant.is_winner(),
+                    orders: participant
+                        .solution()
+                        .orders()
+                        .iter()
+                        .map(|(uid, order)| TradedOrder {
+                            uid: uid.to_string(),
+                            executed_sell: order.executed_sell.0,
+                            executed_buy: order.executed_buy.0,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A settlement of an auction was observed on-chain and processed.
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementObserved {
+    pub auction_id: domain::auction::Id,
+    pub transaction: H256,
+    pub block_number: u64,
+    pub log_index: u64,
+    /// Address of the solver that submitted the settlement.
+    pub solver: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub gas_used: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub effective_gas_price: U256,
+    /// Surplus of all trades denominated in the native token.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub surplus: U256,
+    /// Fees of all trades denominated in the native token.
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub fee: U256,
+    /// UIDs of the settled orders.
+    pub orders: Vec<String>,
+}
+
+impl SettlementObserved {
+    pub fn new(
+        event: &eth::SettlementEvent,
+        auction_id: domain::auction::Id,
+        settlement: &domain::settlement::Settlement,
+    ) -> Self {
+        let mut orders: Vec<_> = settlement
+            .fee_breakdown()
+            .keys()
+            .map(ToString::to_string)
+            .collect();
+        orders.sort();
+        Self {
+            auction_id,
+            transaction: event.transaction.0,
+            block_number: event.block.0,
+            log_index: event.log_index,
+            solver: settlement.solver().0,
+            gas_used: settlement.gas().0,
+            effective_gas_price: settlement.gas_price().0 .0,
+            surplus: settlement.surplus_in_ether().0,
+            fee: settlement.fee_in_ether().0,
+            orders,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn serializes_messages() {
+        let message = Message {
+            version: VERSION,
+            event: Event::AuctionCreated(AuctionCreated {
+                auction_id: 1,
+                block: 2,
+                orders: vec!["0x01".to_string()],
+                prices: BTreeMap::from([(H160([3; 20]), U256::from(4))]),
+            }),
+        };
+        assert_eq!(
+            serde_json::to_value(message).unwrap(),
+            json!({
+                "version": 1,
+                "type": "auctionCreated",
+                "auctionId": 1,
+                "block": 2,
+                "orders": ["0x01"],
+                "prices": {
+                    "0x0303030303030303030303030303030303030303": "4",
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Publisher producing records through a Kafka REST proxy.
+//!
+//! https://docs.confluent.io/platform/current/kafka-rest/api.html#records-v2
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::json,
+    url::Url,
+};
+
+const CONTENT_TYPE: &str = "application/vnd.kafka.json.v2+json";
+
+pub struct Publisher {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl Publisher {
+    pub fn new(client: reqwest::Client, url: Url) -> Self {
+        Self { client, url }
+    }
+
+    pub async fn publish(&self, topic: &str, key: &str, value: &serde_json::Value) -> Result<()> {
+        let url = crate::util::join(&self.url, &format!("topics/{topic}"));
+        let response = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, CONTENT_TYPE)
+            .body(
+                json!({
+                    "records": [{ "key": key, "value": value }],
+                })
+                .to_string(),
+            )
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("unexpected status {status}: {body}"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Streams auction and competition data to Kafka for downstream analytics.
+//! Only producing records through a Kafka REST proxy is supported.
+//!
+//! Events get queued in memory and published by a background task so the
+//! run loop never waits for Kafka. If the queue is full or publishing keeps
+//! failing the events get dropped. The schema of the events is documented in
+//! [`dto`].
+
+use {
+    anyhow::{anyhow, Result},
+    std::time::Duration,
+    tokio::sync::mpsc,
+    url::Url,
+};
+
+pub mod dto;
+mod kafka;
+
+/// How often publishing an event gets attempted before it gets dropped.
+const MAX_ATTEMPTS: usize = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+pub struct Config {
+    /// URL of the Kafka REST proxy the records get produced through.
+    pub url: Url,
+    /// The Kafka topic the events get published to.
+    pub topic: String,
+    /// Maximum number of events waiting to be published.
+    pub queue_size: usize,
+}
+
+/// Handle to publish events. Events get silently discarded if the export is
+/// not configured.
+#[derive(Clone, Default)]
+pub struct Exporter {
+    sender: Option<mpsc::Sender<dto::Event>>,
+}
+
+impl Exporter {
+    /// Creates an exporter that discards all events.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Creates an exporter and spawns the background task publishing the
+    /// events.
+    pub fn new(config: Config, client: reqwest::Client) -> Result<Self> {
+        let publisher = match config.url.scheme() {
+            "http" | "https" => kafka::Publisher::new(client, config.url),
+            scheme => return Err(anyhow!("unsupported event export scheme {scheme:?}")),
+        };
+        let (sender, receiver) = mpsc::channel(config.queue_size);
+        tokio::spawn(publish_forever(publisher, config.topic, receiver));
+        Ok(Self {
+            sender: Some(sender),
+        })
+    }
+
+    /// Queues the event for publishing without waiting for it to be
+    /// published.
+    pub fn publish(&self, event: dto::Event) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let kind = event.kind();
+        if let Err(err) = sender.try_send(event) {
+            tracing::warn!(?err, kind, "dropping exported event");
+            Metrics::get()
+                .exported_events
+                .with_label_values(&[kind, "dropped"])
+                .inc();
+        }
+    }
+}
+
+async fn publish_forever(
+    publisher: kafka::Publisher,
+    topic: String,
+    mut receiver: mpsc::Receiver<dto::Event>,
+) {
+    while let Some(event) = receiver.recv().await {
+        let kind = event.kind();
+        let key = event.auction_id().to_string();
+        let message = match serde_json::to_value(dto::Message {
+            version: dto::VERSION,
+            event,
+        }) {
+            Ok(message) => message,
+            Err(err) => {
+                tracing::error!(?err, kind, "failed to serialize exported event");
+                continue;
+            }
+        };
+
+        let metrics = Metrics::get();
+        let mut result = Ok(());
+        for attempt in 1..=MAX_ATTEMPTS {
+            result = {
+                let _timer = metrics.export_time.start_timer();
+                publisher.publish(&topic, &key, &message).await
+            };
+            match &result {
+                Ok(()) => break,
+                Err(err) => {
+                    tracing::debug!(?err, kind, attempt, "failed to publish exported event");
+                    if attempt < MAX_ATTEMPTS {
+                        tokio::time::sleep(RETRY_DELAY).await;
+                    }
+                }
+            }
+        }
+        let label = match result {
+            Ok(()) => "success",
+            Err(err) => {
+                tracing::warn!(?err, kind, "dropping exported event after failed attempts");
+                "failure"
+            }
+        };
+        metrics
+            .exported_events
+            .with_label_values(&[kind, label])
+            .inc();
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Number of exported events by type and result.
+    #[metric(labels("type", "result"))]
+    exported_events: prometheus::IntCounterVec,
+
+    /// Time it takes to publish a single event.
+    export_time: prometheus::Histogram,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
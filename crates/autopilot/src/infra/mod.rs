@@ -1,11 +1,19 @@
 pub mod blockchain;
+pub mod bonding;
+pub mod export;
+pub mod pauses;
 pub mod persistence;
+pub mod reputation;
 pub mod shadow;
 pub mod solvers;
 
 pub use {
     blockchain::Ethereum,
+    bonding::BondingPools,
+    export::Exporter,
     order_validation::banned,
+    pauses::Pauses,
     persistence::Persistence,
+    reputation::Reputation,
     solvers::Driver,
 };
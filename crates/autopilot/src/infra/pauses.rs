@@ -0,0 +1,323 @@
+//! Scoped emergency pauses.
+//!
+//! Operators can pause parts of the protocol during incidents without taking
+//! the autopilot down, so solving stays observable. Pauses take effect in the
+//! next run loop iteration and are controlled through the admin API, which
+//! also flips the [`FeatureFlags`] shared by all services.
+
+use {
+    crate::domain::eth,
+    primitive_types::H160,
+    serde::{Deserialize, Serialize},
+    shared::feature_flags::{FeatureFlags, StoredValue},
+    std::{
+        collections::BTreeSet,
+        convert::Infallible,
+        future::Future,
+        net::SocketAddr,
+        sync::{Arc, RwLock},
+    },
+    tokio::task::JoinHandle,
+    warp::{
+        http::StatusCode,
+        reply::{Json, WithStatus},
+        Filter,
+    },
+};
+
+/// The part of the protocol a pause applies to.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "scope", rename_all = "camelCase")]
+pub enum Scope {
+    /// No new auctions get created.
+    Auctions,
+    /// Auctions still get solved but winners don't settle.
+    Settlements,
+    /// The solver doesn't participate in auctions.
+    Solver { name: String },
+    /// Orders trading the token are excluded from auctions.
+    Token { address: H160 },
+}
+
+/// All currently active pauses.
+#[derive(Clone, Debug, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct State {
+    pub auctions: bool,
+    pub settlements: bool,
+    pub solvers: BTreeSet<String>,
+    pub tokens: BTreeSet<H160>,
+}
+
+#[derive(Default)]
+pub struct Pauses(RwLock<State>);
+
+impl Pauses {
+    pub fn state(&self) -> State {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Pauses the scope. Returns whether it was not already paused.
+    pub fn pause(&self, scope: Scope) -> bool {
+        self.set(scope, true)
+    }
+
+    /// Resumes the scope. Returns whether it was paused.
+    pub fn resume(&self, scope: Scope) -> bool {
+        self.set(scope, false)
+    }
+
+    fn set(&self, scope: Scope, paused: bool) -> bool {
+        let mut state = self.0.write().unwrap();
+        let (changed, target) = match &scope {
+            Scope::Auctions => (
+                std::mem::replace(&mut state.auctions, paused) != paused,
+                None,
+            ),
+            Scope::Settlements => (
+                std::mem::replace(&mut state.settlements, paused) != paused,
+                None,
+            ),
+            Scope::Solver { name } => {
+                let changed = match paused {
+                    true => state.solvers.insert(name.clone()),
+                    false => state.solvers.remove(name),
+                };
+                (changed, Some(name.clone()))
+            }
+            Scope::Token { address } => {
+                let changed = match paused {
+                    true => state.tokens.insert(*address),
+                    false => state.tokens.remove(address),
+                };
+                (changed, Some(format!("{address:?}")))
+            }
+        };
+        if changed {
+            tracing::warn!(?scope, paused, "emergency pause updated");
+            Metrics::get()
+                .paused
+                .with_label_values(&[scope.label(), target.as_deref().unwrap_or_default()])
+                .set(i64::from(paused));
+        }
+        changed
+    }
+
+    pub fn auctions_paused(&self) -> bool {
+        self.0.read().unwrap().auctions
+    }
+
+    pub fn settlements_paused(&self) -> bool {
+        self.0.read().unwrap().settlements
+    }
+
+    pub fn is_solver_paused(&self, name: &str) -> bool {
+        self.0.read().unwrap().solvers.contains(name)
+    }
+
+    pub fn is_token_paused(&self, token: eth::TokenAddress) -> bool {
+        self.0.read().unwrap().tokens.contains(&token.0)
+    }
+}
+
+/// Serves the admin API controlling the pauses and feature flags. Every
+/// request has to carry the configured key in the `X-API-Key` header.
+///
+/// - `GET /api/v1/pauses` returns the active pauses
+/// - `POST /api/v1/pauses` pauses the scope in the JSON body
+/// - `DELETE /api/v1/pauses` resumes the scope in the JSON body
+/// - `GET /api/v1/flags` returns the stored feature flag values
+/// - `PUT /api/v1/flags/{name}` stores the value in the JSON body for all chains
+///   or, with `"chainSpecific": true`, for the current chain only
+/// - `DELETE /api/v1/flags/{name}` removes the value for all chains or, with
+///   `?chainSpecific=true`, for the current chain only
+pub fn serve(
+    pauses: Arc<Pauses>,
+    feature_flags: Arc<FeatureFlags>,
+    address: SocketAddr,
+    api_key: String,
+) -> JoinHandle<()> {
+    let authorized = warp::header::optional::<String>("x-api-key")
+        .map(move |key: Option<String>| key.as_deref() == Some(api_key.as_str()));
+
+    let get = {
+        let pauses = pauses.clone();
+        warp::path!("api" / "v1" / "pauses")
+            .and(authorized.clone())
+            .and(warp::get())
+            .map(move |authorized| reply(authorized, || pauses.state()))
+    };
+    let update = warp::path!("api" / "v1" / "pauses")
+        .and(authorized.clone())
+        .and(
+            warp::post()
+                .map(|| true)
+                .or(warp::delete().map(|| false))
+                .unify(),
+        )
+        .and(warp::body::json::<Scope>())
+        .map(move |authorized, paused, scope| {
+            reply(authorized, || {
+                match paused {
+                    true => pauses.pause(scope),
+                    false => pauses.resume(scope),
+                };
+                pauses.state()
+            })
+        });
+
+    let get_flags = {
+        let flags = feature_flags.clone();
+        warp::path!("api" / "v1" / "flags")
+            .and(authorized.clone())
+            .and(warp::get())
+            .and_then(move |authorized| {
+                let flags = flags.clone();
+                async move { flags_reply(authorized, flags.stored()).await }
+            })
+    };
+    let set_flag = {
+        let flags = feature_flags.clone();
+        warp::path!("api" / "v1" / "flags" / String)
+            .and(authorized.clone())
+            .and(warp::put())
+            .and(warp::body::json::<FlagUpdate>())
+            .and_then(move |name: String, authorized, update: FlagUpdate| {
+                let flags = flags.clone();
+                async move {
+                    let handle = async {
+                        flags
+                            .set(&name, update.chain_specific, update.value)
+                            .await?;
+                        flags.stored().await
+                    };
+                    flags_reply(authorized, handle).await
+                }
+            })
+    };
+    let unset_flag = warp::path!("api" / "v1" / "flags" / String)
+        .and(authorized)
+        .and(warp::delete())
+        .and(warp::query::<FlagScope>())
+        .and_then(move |name: String, authorized, scope: FlagScope| {
+            let flags = feature_flags.clone();
+            async move {
+                let handle = async {
+                    flags.unset(&name, scope.chain_specific).await?;
+                    flags.stored().await
+                };
+                flags_reply(authorized, handle).await
+            }
+        });
+
+    tracing::info!(%address, "serving admin api");
+    tokio::task::spawn(
+        warp::serve(get.or(update).or(get_flags).or(set_flag).or(unset_flag)).bind(address),
+    )
+}
+
+fn reply(authorized: bool, handle: impl FnOnce() -> State) -> impl warp::Reply {
+    if !authorized {
+        return unauthorized();
+    }
+    warp::reply::with_status(warp::reply::json(&handle()), StatusCode::OK)
+}
+
+async fn flags_reply(
+    authorized: bool,
+    handle: impl Future<Output = anyhow::Result<Vec<StoredValue>>>,
+) -> Result<WithStatus<Json>, Infallible> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    Ok(match handle.await {
+        Ok(flags) => warp::reply::with_status(warp::reply::json(&flags), StatusCode::OK),
+        Err(err) => {
+            tracing::error!(?err, "feature flag admin request failed");
+            warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({ "errorType": "InternalServerError" })),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        }
+    })
+}
+
+fn unauthorized() -> WithStatus<Json> {
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({ "errorType": "Unauthorized" })),
+        StatusCode::UNAUTHORIZED,
+    )
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FlagUpdate {
+    value: serde_json::Value,
+    #[serde(default)]
+    chain_specific: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FlagScope {
+    #[serde(default)]
+    chain_specific: bool,
+}
+
+impl Scope {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Auctions => "auctions",
+            Self::Settlements => "settlements",
+            Self::Solver { .. } => "solver",
+            Self::Token { .. } => "token",
+        }
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "emergency_pause")]
+struct Metrics {
+    /// Whether a scope is currently paused.
+    #[metric(labels("scope", "target"))]
+    paused: prometheus::IntGaugeVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn pauses_and_resumes_scopes() {
+        let pauses = Pauses::default();
+        let token = H160([1; 20]);
+        let scope: Scope = serde_json::from_value(json!({
+            "scope": "token",
+            "address": "0x0101010101010101010101010101010101010101",
+        }))
+        .unwrap();
+        assert_eq!(scope, Scope::Token { address: token });
+
+        assert!(pauses.pause(scope.clone()));
+        assert!(!pauses.pause(scope.clone()));
+        assert!(pauses.pause(Scope::Settlements));
+        assert!(pauses.pause(Scope::Solver {
+            name: "solver".to_string()
+        }));
+        assert!(pauses.is_token_paused(eth::TokenAddress(token)));
+        assert!(pauses.is_solver_paused("solver"));
+        assert!(!pauses.is_solver_paused("other"));
+        assert!(pauses.settlements_paused());
+        assert!(!pauses.auctions_paused());
+
+        assert!(pauses.resume(scope));
+        assert!(!pauses.is_token_paused(eth::TokenAddress(token)));
+        assert!(!pauses.resume(Scope::Auctions));
+    }
+}
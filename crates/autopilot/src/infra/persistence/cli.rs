@@ -1,19 +1,58 @@
 //! Command line arguments for persistence.
 
-use anyhow::Result;
+use {anyhow::Result, std::path::PathBuf};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Backend {
+    /// AWS S3
+    S3,
+    /// Google Cloud Storage
+    Gcs,
+    /// The local filesystem
+    Local,
+}
+
+impl Backend {
+    /// The storage for the bucket. For the local backend the bucket is a
+    /// directory.
+    pub fn with_bucket(self, bucket: String) -> s3::Backend {
+        match self {
+            Backend::S3 => s3::Backend::S3 { bucket },
+            Backend::Gcs => s3::Backend::Gcs { bucket },
+            Backend::Local => s3::Backend::Local {
+                path: PathBuf::from(bucket),
+            },
+        }
+    }
+}
 
 #[derive(clap::Parser, Debug, Clone)]
 pub struct S3 {
     #[clap(long, env)]
     /// The s3_instance_upload_* arguments configure how auction instances
-    /// should be uploaded to AWS S3.
-    /// They must either all be set or all not set.
+    /// should be uploaded to an object storage.
+    /// The bucket and filename prefix must either both be set or both not be
+    /// set. For the local backend the bucket is the directory instances get
+    /// written to.
     pub s3_instance_upload_bucket: Option<String>,
 
+    /// Which object storage auction instances get uploaded to.
+    #[clap(long, env, value_enum, default_value = "s3")]
+    pub s3_instance_upload_backend: Backend,
+
     /// Prepended to the auction id to form the final instance filename on S3.
     /// Something like "staging/mainnet/"
     #[clap(long, env)]
     pub s3_instance_upload_filename_prefix: Option<String>,
+
+    /// Size in bytes of the compressed parts large instances get uploaded in.
+    /// S3 requires parts to be at least 5MiB.
+    #[clap(long, env, default_value = "8388608")]
+    pub s3_instance_upload_part_size: usize,
+
+    /// How many parts of a single instance may be uploaded concurrently.
+    #[clap(long, env, default_value = "4")]
+    pub s3_instance_upload_concurrency: usize,
 }
 
 impl S3 {
@@ -29,9 +68,12 @@ impl S3 {
             "either set all s3_instance_upload bucket arguments or none"
         );
         Ok(if all_some {
+            let bucket = self.s3_instance_upload_bucket.unwrap();
             Some(s3::Config {
-                bucket: self.s3_instance_upload_bucket.unwrap(),
+                backend: self.s3_instance_upload_backend.with_bucket(bucket),
                 filename_prefix: self.s3_instance_upload_filename_prefix.unwrap(),
+                part_size: self.s3_instance_upload_part_size,
+                upload_concurrency: self.s3_instance_upload_concurrency,
             })
         } else {
             None
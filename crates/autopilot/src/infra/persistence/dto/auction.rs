@@ -47,7 +47,7 @@ pub struct RawAuctionData {
 pub type AuctionId = i64;
 
 #[serde_as]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Auction {
     pub id: AuctionId,
@@ -49,13 +49,16 @@ pub struct Persistence {
 
 impl Persistence {
     pub async fn new(config: Option<s3::Config>, postgres: Arc<Postgres>) -> Self {
-        Self {
-            s3: match config {
-                Some(config) => Some(s3::Uploader::new(config).await),
-                None => None,
-            },
-            postgres,
-        }
+        let s3 = match config {
+            // Archiving auctions is not essential so don't refuse to start if
+            // the storage is not usable.
+            Some(config) => s3::Uploader::new(config)
+                .await
+                .inspect_err(|err| tracing::error!(?err, "auction archiving disabled"))
+                .ok(),
+            None => None,
+        };
+        Self { s3, postgres }
     }
 
     /// There is always only one `current` auction.
@@ -102,7 +105,7 @@ impl Persistence {
         tokio::spawn(
             async move {
                 match uploader
-                    .upload(instance.id.to_string(), &instance.auction)
+                    .upload_stream(instance.id.to_string(), s3::json_reader(instance.auction))
                     .await
                 {
                     Ok(key) => {
@@ -135,12 +138,9 @@ impl Persistence {
         auction_id: domain::auction::Id,
         solutions: &[domain::competition::Participant],
     ) -> Result<(), DatabaseError> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["save_solutions"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("save_solutions");
 
-        let mut ex = self.postgres.pool.begin().await?;
+        let mut ex = database::instrumentation::begin(&self.postgres.pool).await?;
 
         database::solver_competition::save(
             &mut ex,
@@ -224,25 +224,114 @@ impl Persistence {
         let order_uids = order_uids.into_iter().collect();
         tokio::spawn(
             async move {
-                let mut tx = db.pool.acquire().await.expect("failed to acquire tx");
+                let mut tx = database::instrumentation::acquire(&db.pool)
+                    .await
+                    .expect("failed to acquire tx");
                 store_order_events(&mut tx, order_uids, label, Utc::now()).await;
             }
             .instrument(tracing::Span::current()),
         );
     }
 
+    /// Marks the orders as unsupported because of the associated tokens and
+    /// removes the mark from orders whose tokens are supported again. Errors
+    /// only get logged since the marks are purely informational for users.
+    pub fn update_unsupported_token_orders(
+        &self,
+        unsupported: Vec<(domain::OrderUid, eth::TokenAddress)>,
+        supported: Vec<domain::OrderUid>,
+    ) {
+        if unsupported.is_empty() && supported.is_empty() {
+            return;
+        }
+        let db = self.postgres.clone();
+        tokio::spawn(
+            async move {
+                let _timer =
+                    database::instrumentation::time_query("update_unsupported_token_orders");
+                let result = async {
+                    let mut ex = database::instrumentation::begin(&db.pool).await?;
+                    let unsupported: Vec<_> = unsupported
+                        .into_iter()
+                        .map(|(uid, token)| (ByteArray(uid.0), ByteArray(token.0 .0)))
+                        .collect();
+                    let marked = database::unsupported_token_orders::insert(
+                        &mut ex,
+                        &unsupported,
+                        Utc::now(),
+                    )
+                    .await?;
+                    let supported: Vec<_> =
+                        supported.into_iter().map(|uid| ByteArray(uid.0)).collect();
+                    database::unsupported_token_orders::delete(&mut ex, &supported).await?;
+                    ex.commit().await?;
+                    Ok::<_, sqlx::Error>(marked)
+                }
+                .await;
+                match result {
+                    Ok(marked) if !marked.is_empty() => {
+                        tracing::info!(orders = ?marked, "marked orders with unsupported tokens");
+                    }
+                    Ok(_) => (),
+                    Err(err) => tracing::warn!(?err, "failed to update unsupported token orders"),
+                }
+            }
+            .instrument(tracing::Span::current()),
+        );
+    }
+
+    /// Stores why candidate orders are not part of the auction and deletes
+    /// the records of auctions older than the `retention` most recent ones.
+    /// Errors only get logged since the records are only used for debugging.
+    pub fn store_filtered_orders(
+        &self,
+        auction_id: domain::auction::Id,
+        orders: Vec<domain::auction::FilteredOrder>,
+        retention: u64,
+    ) {
+        let db = self.postgres.clone();
+        tokio::spawn(
+            async move {
+                let _timer = database::instrumentation::time_query("store_filtered_orders");
+                let result = async {
+                    let mut ex = database::instrumentation::begin(&db.pool).await?;
+                    let orders: Vec<_> = orders
+                        .into_iter()
+                        .map(|order| database::auction_filtered_orders::FilteredOrder {
+                            auction_id,
+                            order_uid: ByteArray(order.uid.0),
+                            reason: order.reason.to_string(),
+                            details: order.details,
+                        })
+                        .collect();
+                    database::auction_filtered_orders::insert(&mut ex, &orders).await?;
+                    database::auction_filtered_orders::delete_before(
+                        &mut ex,
+                        auction_id.saturating_sub(i64::try_from(retention).unwrap_or(i64::MAX)),
+                    )
+                    .await?;
+                    ex.commit().await
+                }
+                .await;
+                if let Err(err) = result {
+                    tracing::warn!(?err, "failed to store filtered orders");
+                }
+            }
+            .instrument(tracing::Span::current()),
+        );
+    }
+
     /// Saves the given fee policies to the DB as a single batch.
     pub async fn store_fee_policies(
         &self,
         auction_id: domain::auction::Id,
         fee_policies: Vec<(domain::OrderUid, Vec<domain::fee::Policy>)>,
     ) -> anyhow::Result<()> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["store_fee_policies"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("store_fee_policies");
 
-        let mut ex = self.postgres.pool.begin().await.context("begin")?;
+        let mut ex = database::instrumentation::begin(&self.postgres.pool)
+            .await
+            .context("begin")?;
         for chunk in fee_policies.chunks(self.postgres.config.insert_batch_size.get()) {
             crate::database::fee_policies::insert_batch(&mut ex, auction_id, chunk.iter().cloned())
                 .await
@@ -252,6 +341,28 @@ impl Persistence {
         ex.commit().await.context("commit")
     }
 
+    /// Records the protocol fee configuration as the currently active one
+    /// unless it didn't change since the last start.
+    pub async fn store_fee_policy_configuration(
+        &self,
+        configuration: &model::fee_policy::FeePolicyConfiguration,
+    ) -> anyhow::Result<()> {
+        let _timer = database::instrumentation::time_query("store_fee_policy_configuration");
+
+        let configuration = serde_json::to_value(configuration)?;
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        if let Some(version) = database::fee_policy_configurations::insert_if_changed(
+            &mut ex,
+            &configuration,
+            Utc::now(),
+        )
+        .await?
+        {
+            tracing::info!(version, ?configuration, "new fee policy configuration");
+        }
+        Ok(())
+    }
+
     /// For a given auction and solver, tries to find the settlement
     /// transaction.
     pub async fn find_settlement_transaction(
@@ -259,12 +370,11 @@ impl Persistence {
         auction_id: i64,
         solver: eth::Address,
     ) -> Result<Option<eth::TxId>, DatabaseError> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["find_settlement_transaction"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("find_settlement_transaction");
 
-        let mut ex = self.postgres.pool.acquire().await.context("acquire")?;
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool)
+            .await
+            .context("acquire")?;
         Ok(database::settlements::find_settlement_transaction(
             &mut ex,
             auction_id,
@@ -280,12 +390,9 @@ impl Persistence {
         auction: &domain::Auction,
         deadline: u64, // to become part of the auction struct
     ) -> Result<(), DatabaseError> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["save_auction"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("save_auction");
 
-        let mut ex = self.postgres.pool.begin().await?;
+        let mut ex = database::instrumentation::begin(&self.postgres.pool).await?;
 
         database::auction::save(
             &mut ex,
@@ -325,10 +432,7 @@ impl Persistence {
         &self,
         auction_id: domain::auction::Id,
     ) -> Result<domain::settlement::Auction, error::Auction> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["get_auction"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("get_auction");
 
         let mut ex = self
             .postgres
@@ -453,7 +557,9 @@ impl Persistence {
         tracing::debug!(?after_timestamp, ?after_block, "fetch orders updated since");
         let after_block = i64::try_from(after_block).context("block number value exceeds i64")?;
         let started_at = chrono::offset::Utc::now();
-        let mut tx = self.postgres.pool.begin().await.context("begin")?;
+        let mut tx = database::instrumentation::begin(&self.postgres.pool)
+            .await
+            .context("begin")?;
         // Set the transaction isolation level to REPEATABLE READ
         // so all the SELECT queries below are executed in the same database snapshot
         // taken at the moment before the first query is executed.
@@ -463,10 +569,7 @@ impl Persistence {
 
         // Find order uids for orders that were updated after the given block.
         let updated_order_uids = {
-            let _timer = Metrics::get()
-                .database_queries
-                .with_label_values(&["updated_order_uids"])
-                .start_timer();
+            let _timer = database::instrumentation::time_query("updated_order_uids");
 
             database::orders::updated_order_uids_after(&mut tx, after_block).await?
         };
@@ -474,10 +577,7 @@ impl Persistence {
         // Fetch the orders that were updated after the given block and were created or
         // cancelled after the given timestamp.
         let next_orders: HashMap<domain::OrderUid, model::order::Order> = {
-            let _timer = Metrics::get()
-                .database_queries
-                .with_label_values(&["open_orders_after"])
-                .start_timer();
+            let _timer = database::instrumentation::time_query("open_orders_after");
 
             database::orders::open_orders_by_time_or_uids(
                 &mut tx,
@@ -537,10 +637,7 @@ impl Persistence {
         current_quotes.retain(|uid, _| current_orders.contains_key(uid));
 
         {
-            let _timer = Metrics::get()
-                .database_queries
-                .with_label_values(&["read_quotes"])
-                .start_timer();
+            let _timer = database::instrumentation::time_query("read_quotes");
 
             // Fetch quotes only for newly created and also on-chain placed orders due to
             // the following case: if a block containing an on-chain order
@@ -580,12 +677,9 @@ impl Persistence {
     pub async fn get_settlement_without_auction(
         &self,
     ) -> Result<Option<domain::eth::SettlementEvent>, DatabaseError> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["get_settlement_without_auction"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("get_settlement_without_auction");
 
-        let mut ex = self.postgres.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
         let event = database::settlements::get_settlement_without_auction(&mut ex)
             .await?
             .map(|event| {
@@ -607,12 +701,9 @@ impl Persistence {
         &self,
         settlement: &domain::eth::SettlementEvent,
     ) -> Result<Vec<domain::eth::TradeEvent>, DatabaseError> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["get_trades_for_settlement"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("get_trades_for_settlement");
 
-        let mut ex = self.postgres.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
         database::trades::get_trades_for_settlement(
             &mut ex,
             EventIndex {
@@ -635,18 +726,222 @@ impl Persistence {
         .collect()
     }
 
+    /// Returns whether the solver won the auction or `None` if it did not
+    /// propose any solution for it.
+    pub async fn solver_won(
+        &self,
+        auction_id: domain::auction::Id,
+        solver: eth::Address,
+    ) -> Result<Option<bool>, DatabaseError> {
+        let _timer = database::instrumentation::time_query("solver_won");
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        Ok(
+            database::solver_competition::solver_won(&mut ex, auction_id, &ByteArray(solver.0 .0))
+                .await?,
+        )
+    }
+
+    /// Records a settlement that was not authorized by the competition.
+    pub async fn save_unauthorized_settlement(
+        &self,
+        event: &domain::eth::SettlementEvent,
+        auction_id: domain::auction::Id,
+        solver: eth::Address,
+        reason: domain::settlement::Unauthorized,
+    ) -> Result<(), DatabaseError> {
+        let _timer = database::instrumentation::time_query("save_unauthorized_settlement");
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        database::unauthorized_settlements::insert(
+            &mut ex,
+            &database::unauthorized_settlements::UnauthorizedSettlement {
+                block_number: i64::try_from(event.block.0).context("block overflow")?,
+                log_index: i64::try_from(event.log_index).context("log index overflow")?,
+                tx_hash: ByteArray(event.transaction.0 .0),
+                auction_id,
+                solver: ByteArray(solver.0 .0),
+                reason: match reason {
+                    domain::settlement::Unauthorized::NotParticipated => {
+                        database::unauthorized_settlements::Reason::NotParticipated
+                    }
+                    domain::settlement::Unauthorized::NotWinner => {
+                        database::unauthorized_settlements::Reason::NotWinner
+                    }
+                },
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the amounts the winning solution of `solver` promised for each
+    /// order of the auction.
+    pub async fn fetch_promised_amounts(
+        &self,
+        auction_id: domain::auction::Id,
+        solver: eth::Address,
+    ) -> Result<
+        HashMap<domain::OrderUid, domain::settlement::execution_quality::Amounts>,
+        DatabaseError,
+    > {
+        let _timer = database::instrumentation::time_query("fetch_promised_amounts");
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        database::order_execution_quality::fetch_promises(
+            &mut ex,
+            auction_id,
+            &ByteArray(solver.0 .0),
+        )
+        .await?
+        .into_iter()
+        .map(|promise| {
+            let amounts = domain::settlement::execution_quality::Amounts {
+                sell: big_decimal_to_u256(&promise.executed_sell)
+                    .context("invalid promised sell amount")?
+                    .into(),
+                buy: big_decimal_to_u256(&promise.executed_buy)
+                    .context("invalid promised buy amount")?
+                    .into(),
+            };
+            Ok::<_, DatabaseError>((domain::OrderUid(promise.order_uid.0), amounts))
+        })
+        .collect()
+    }
+
+    /// Records how well the orders of a settlement were executed compared to
+    /// what the solver promised. Orders without a meaningful deviation get
+    /// skipped.
+    pub async fn save_execution_quality(
+        &self,
+        auction_id: domain::auction::Id,
+        settlement: &domain::settlement::Settlement,
+        qualities: &[domain::settlement::ExecutionQuality],
+    ) -> Result<(), DatabaseError> {
+        let _timer = database::instrumentation::time_query("save_execution_quality");
+
+        let block_number = i64::try_from(settlement.block().0).context("block overflow")?;
+        let rows = qualities
+            .iter()
+            .filter_map(|quality| {
+                Some(database::order_execution_quality::ExecutionQuality {
+                    auction_id,
+                    order_uid: ByteArray(quality.order.0),
+                    solver: ByteArray(settlement.solver().0 .0),
+                    block_number,
+                    promised_sell: u256_to_big_decimal(&quality.promised.sell.0),
+                    promised_buy: u256_to_big_decimal(&quality.promised.buy.0),
+                    executed_sell: u256_to_big_decimal(&quality.executed.sell.0),
+                    executed_buy: u256_to_big_decimal(&quality.executed.buy.0),
+                    deviation: quality.deviation()?,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        database::order_execution_quality::insert(&mut ex, &rows).await?;
+        Ok(())
+    }
+
+    /// Aggregates the execution quality of the `window` most recently settled
+    /// orders of every solver.
+    pub async fn solver_execution_quality(
+        &self,
+        window: u64,
+    ) -> Result<Vec<domain::settlement::execution_quality::SolverScore>, DatabaseError> {
+        let _timer = database::instrumentation::time_query("solver_execution_quality");
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        database::order_execution_quality::solver_scores(
+            &mut ex,
+            i64::try_from(window).context("window overflow")?,
+        )
+        .await?
+        .into_iter()
+        .map(|score| {
+            Ok::<_, DatabaseError>(domain::settlement::execution_quality::SolverScore {
+                solver: eth::H160(score.solver.0).into(),
+                orders: u64::try_from(score.orders).context("negative order count")?,
+                mean_deviation: score.mean_deviation,
+                shortfalls: u64::try_from(score.shortfalls).context("negative shortfalls")?,
+            })
+        })
+        .collect()
+    }
+
+    /// The most recent auctions every solver won whose deadline passed, out of
+    /// the `lookback` most recent auctions.
+    pub async fn won_auctions(
+        &self,
+        window: u64,
+        lookback: u64,
+    ) -> Result<Vec<domain::settlement::reliability::WonAuction>, DatabaseError> {
+        let _timer = database::instrumentation::time_query("won_auctions");
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        Ok(database::solver_competition::won_auctions(
+            &mut ex,
+            i64::try_from(window).context("window overflow")?,
+            i64::try_from(lookback).context("lookback overflow")?,
+        )
+        .await?
+        .into_iter()
+        .map(|won| domain::settlement::reliability::WonAuction {
+            auction: won.auction_id,
+            solver: eth::H160(won.solver.0).into(),
+            settled: won.settled,
+        })
+        .collect())
+    }
+
+    /// The latest exclusion of every solver excluded for not settling the
+    /// auctions it won, including exclusions that already ended.
+    pub async fn solver_exclusions(
+        &self,
+    ) -> Result<Vec<domain::settlement::reliability::Exclusion>, DatabaseError> {
+        let _timer = database::instrumentation::time_query("solver_exclusions");
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        Ok(database::solver_exclusions::fetch_all(&mut ex)
+            .await?
+            .into_iter()
+            .map(|exclusion| domain::settlement::reliability::Exclusion {
+                solver: eth::H160(exclusion.solver.0).into(),
+                until: exclusion.excluded_until,
+                latest_auction: exclusion.latest_auction,
+            })
+            .collect())
+    }
+
+    /// Stores the latest exclusion of the solver.
+    pub async fn store_solver_exclusion(
+        &self,
+        exclusion: &domain::settlement::reliability::Exclusion,
+    ) -> Result<(), DatabaseError> {
+        let _timer = database::instrumentation::time_query("store_solver_exclusion");
+
+        let mut ex = database::instrumentation::acquire(&self.postgres.pool).await?;
+        database::solver_exclusions::upsert(
+            &mut ex,
+            &database::solver_exclusions::Exclusion {
+                solver: ByteArray(exclusion.solver.0 .0),
+                excluded_until: exclusion.until,
+                latest_auction: exclusion.latest_auction,
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
     pub async fn save_settlement(
         &self,
         event: domain::eth::SettlementEvent,
         auction_id: domain::auction::Id,
         settlement: Option<&domain::settlement::Settlement>,
     ) -> Result<(), DatabaseError> {
-        let _timer = Metrics::get()
-            .database_queries
-            .with_label_values(&["save_settlement"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("save_settlement");
 
-        let mut ex = self.postgres.pool.begin().await?;
+        let mut ex = database::instrumentation::begin(&self.postgres.pool).await?;
 
         let block_number = i64::try_from(event.block.0).context("block overflow")?;
         let log_index = i64::try_from(event.log_index).context("log index overflow")?;
@@ -799,19 +1094,6 @@ impl Persistence {
     }
 }
 
-#[derive(prometheus_metric_storage::MetricStorage)]
-struct Metrics {
-    /// Timing of db queries.
-    #[metric(name = "persistence_database_queries", labels("type"))]
-    database_queries: prometheus::HistogramVec,
-}
-
-impl Metrics {
-    fn get() -> &'static Self {
-        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
-    }
-}
-
 #[derive(Debug, thiserror::Error)]
 #[error("failed communication with the database")]
 pub struct DatabaseError(#[from] pub anyhow::Error);
@@ -0,0 +1,387 @@
+//! Solver reputation derived from how well solvers execute their solutions.
+//!
+//! The execution quality of every settled order gets recorded by the
+//! settlement observer. This component periodically aggregates the most
+//! recent orders of every solver into a score, exposes it as metrics and,
+//! if configured, excludes solvers from the competition whose settlements
+//! consistently deliver worse prices than their solutions promised.
+//!
+//! Likewise it aggregates how many of the most recent auctions every solver
+//! won it also settled and, if configured, excludes solvers that fail to
+//! settle too many of them for a cool-down period. Once reinstated, only the
+//! auctions they win afterwards count towards excluding them again. The
+//! exclusions are persisted so that both still hold after a restart.
+
+use {
+    crate::{
+        domain::{
+            eth,
+            settlement::{
+                execution_quality::SolverScore,
+                reliability::{self, Exclusion, SettlementRate},
+            },
+        },
+        infra,
+    },
+    chrono::{DateTime, Utc},
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, RwLock},
+        time::Duration,
+    },
+    tokio::time,
+};
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// How many of the most recently settled orders of a solver make up its
+    /// score.
+    pub window: u64,
+    /// Minimum number of scored orders before a solver can get excluded.
+    pub min_orders: u64,
+    /// Solvers whose mean deviation falls below this value get excluded from
+    /// the competition. Nobody gets excluded if unset.
+    pub min_mean_deviation: Option<f64>,
+    /// How many of the most recent auctions a solver won make up its
+    /// settlement rate.
+    pub settlement_window: u64,
+    /// How many of the most recent auctions get searched for the auctions
+    /// solvers won. Bounds the cost of computing the settlement rates.
+    pub settlement_lookback: u64,
+    /// Minimum number of won auctions before a solver can get excluded for
+    /// not settling them.
+    pub min_won_auctions: u64,
+    /// Solvers settling a smaller share of the auctions they won get excluded
+    /// from the competition. Nobody gets excluded if unset.
+    pub min_settlement_rate: Option<f64>,
+    /// How long solvers stay excluded for not settling the auctions they won.
+    pub cool_down: Duration,
+    /// Solvers that never get excluded, whatever their reputation.
+    pub never_excluded: HashSet<eth::Address>,
+}
+
+pub struct Reputation {
+    persistence: infra::Persistence,
+    config: Config,
+    scores: RwLock<HashMap<eth::Address, SolverScore>>,
+    /// The latest exclusion of every solver for not settling the auctions it
+    /// won. Expired exclusions are kept to know which auctions to ignore.
+    exclusions: RwLock<HashMap<eth::Address, Exclusion>>,
+}
+
+impl Reputation {
+    pub fn new(persistence: infra::Persistence, config: Config) -> Self {
+        Self {
+            persistence,
+            config,
+            scores: Default::default(),
+            exclusions: Default::default(),
+        }
+    }
+
+    /// The most recently computed execution quality score of the solver.
+    pub fn score(&self, solver: eth::Address) -> Option<SolverScore> {
+        self.scores.read().unwrap().get(&solver).cloned()
+    }
+
+    /// Returns whether the solver is currently excluded from the competition
+    /// because of its execution quality.
+    pub fn is_excluded(&self, solver: eth::Address) -> bool {
+        !self.config.never_excluded.contains(&solver)
+            && self
+                .score(solver)
+                .is_some_and(|score| self.config.excludes(&score))
+    }
+
+    /// Returns whether the solver is currently excluded from the competition
+    /// because it didn't settle enough of the auctions it won.
+    pub fn is_unreliable(&self, solver: eth::Address) -> bool {
+        !self.config.never_excluded.contains(&solver)
+            && self
+                .exclusions
+                .read()
+                .unwrap()
+                .get(&solver)
+                .is_some_and(|exclusion| Utc::now() < exclusion.until)
+    }
+
+    pub async fn run_forever(self: Arc<Self>, update_interval: Duration) -> ! {
+        self.load_exclusions().await;
+        let mut interval = time::interval(update_interval);
+        loop {
+            interval.tick().await;
+            self.update().await;
+        }
+    }
+
+    /// Restores the exclusions from before the last restart.
+    async fn load_exclusions(&self) {
+        match self.persistence.solver_exclusions().await {
+            Ok(loaded) => {
+                let mut exclusions = self.exclusions.write().unwrap();
+                for exclusion in loaded {
+                    exclusions.insert(exclusion.solver, exclusion);
+                }
+            }
+            Err(err) => {
+                tracing::warn!(?err, "failed to load solver exclusions");
+            }
+        }
+    }
+
+    async fn update(&self) {
+        self.update_execution_quality().await;
+        self.update_settlement_rates().await;
+    }
+
+    async fn update_execution_quality(&self) {
+        let scores = match self
+            .persistence
+            .solver_execution_quality(self.config.window)
+            .await
+        {
+            Ok(scores) => scores,
+            Err(err) => {
+                // Keep the previous scores instead of reinstating all solvers
+                // because of a database issue.
+                tracing::warn!(?err, "failed to fetch solver execution quality");
+                return;
+            }
+        };
+
+        let metrics = Metrics::get();
+        let mut current = self.scores.write().unwrap();
+        for score in &scores {
+            let solver = format!("{:?}", score.solver.0);
+            let excluded = self.config.excludes(score);
+            let was_excluded = current
+                .get(&score.solver)
+                .is_some_and(|previous| self.config.excludes(previous));
+            if excluded && !was_excluded {
+                tracing::warn!(
+                    solver,
+                    mean_deviation = score.mean_deviation,
+                    orders = score.orders,
+                    "excluding solver because of poor execution quality"
+                );
+            } else if !excluded && was_excluded {
+                tracing::info!(
+                    solver,
+                    mean_deviation = score.mean_deviation,
+                    "reinstating solver because its execution quality recovered"
+                );
+            }
+            metrics
+                .solver_execution_quality
+                .with_label_values(&[&solver])
+                .set(score.mean_deviation);
+            metrics
+                .solver_execution_quality_excluded
+                .with_label_values(&[&solver])
+                .set(i64::from(excluded));
+        }
+        *current = scores
+            .into_iter()
+            .map(|score| (score.solver, score))
+            .collect();
+    }
+
+    async fn update_settlement_rates(&self) {
+        let auctions = match self
+            .persistence
+            .won_auctions(
+                self.config.settlement_window,
+                self.config.settlement_lookback,
+            )
+            .await
+        {
+            Ok(auctions) => auctions,
+            Err(err) => {
+                // Keep the current exclusions because of a database issue.
+                tracing::warn!(?err, "failed to fetch won auctions");
+                return;
+            }
+        };
+
+        let new_exclusions = self.exclude_unreliable_solvers(&auctions);
+        for exclusion in &new_exclusions {
+            if let Err(err) = self.persistence.store_solver_exclusion(exclusion).await {
+                tracing::warn!(?err, ?exclusion, "failed to store solver exclusion");
+            }
+        }
+    }
+
+    /// Excludes the solvers that settled too few of the auctions they won
+    /// since their previous exclusion. Returns the new exclusions.
+    fn exclude_unreliable_solvers(&self, auctions: &[reliability::WonAuction]) -> Vec<Exclusion> {
+        let metrics = Metrics::get();
+        let now = Utc::now();
+        let mut new_exclusions = Vec::new();
+        let mut exclusions = self.exclusions.write().unwrap();
+        let rates = reliability::settlement_rates(auctions, |solver| {
+            exclusions
+                .get(solver)
+                .map(|exclusion| exclusion.latest_auction)
+        });
+        for rate in &rates {
+            let solver = format!("{:?}", rate.solver.0);
+            metrics
+                .solver_settlement_rate
+                .with_label_values(&[&solver])
+                .set(rate.rate());
+            let cooling_down = exclusions
+                .get(&rate.solver)
+                .is_some_and(|exclusion| now < exclusion.until);
+            if cooling_down || !self.config.is_unreliable(rate) {
+                continue;
+            }
+            tracing::warn!(
+                solver,
+                won = rate.won,
+                settled = rate.settled,
+                cool_down = ?self.config.cool_down,
+                "excluding solver because it didn't settle the auctions it won"
+            );
+            let exclusion = Exclusion {
+                solver: rate.solver,
+                until: self.config.excluded_until(now),
+                latest_auction: rate.latest_auction,
+            };
+            exclusions.insert(rate.solver, exclusion);
+            new_exclusions.push(exclusion);
+        }
+        for (solver, exclusion) in exclusions.iter() {
+            metrics
+                .solver_unreliable_excluded
+                .with_label_values(&[&format!("{:?}", solver.0)])
+                .set(i64::from(now < exclusion.until));
+        }
+        new_exclusions
+    }
+}
+
+impl Config {
+    fn excludes(&self, score: &SolverScore) -> bool {
+        self.min_mean_deviation
+            .is_some_and(|min| score.orders >= self.min_orders && score.mean_deviation < min)
+    }
+
+    fn is_unreliable(&self, rate: &SettlementRate) -> bool {
+        self.min_settlement_rate
+            .is_some_and(|min| rate.won >= self.min_won_auctions && rate.rate() < min)
+    }
+
+    /// When the cool-down of an exclusion starting `now` ends.
+    fn excluded_until(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        chrono::Duration::from_std(self.cool_down)
+            .ok()
+            .and_then(|cool_down| now.checked_add_signed(cool_down))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Mean deviation of the executed from the promised price over the most
+    /// recently settled orders of a solver.
+    #[metric(labels("solver"))]
+    solver_execution_quality: prometheus::GaugeVec,
+
+    /// Whether a solver is currently excluded because of its execution
+    /// quality.
+    #[metric(labels("solver"))]
+    solver_execution_quality_excluded: prometheus::IntGaugeVec,
+
+    /// Share of the most recent auctions a solver won that it also settled.
+    #[metric(labels("solver"))]
+    solver_settlement_rate: prometheus::GaugeVec,
+
+    /// Whether a solver is currently excluded for not settling the auctions
+    /// it won.
+    #[metric(labels("solver"))]
+    solver_unreliable_excluded: prometheus::IntGaugeVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, primitive_types::H160};
+
+    fn config() -> Config {
+        Config {
+            window: 100,
+            min_orders: 10,
+            min_mean_deviation: Some(-0.01),
+            settlement_window: 50,
+            settlement_lookback: 1000,
+            min_won_auctions: 5,
+            min_settlement_rate: Some(0.8),
+            cool_down: Duration::from_secs(3600),
+            never_excluded: Default::default(),
+        }
+    }
+
+    #[test]
+    fn excludes_solvers_below_threshold() {
+        let config = config();
+        let score = |orders, mean_deviation| SolverScore {
+            solver: H160([1; 20]).into(),
+            orders,
+            mean_deviation,
+            shortfalls: 0,
+        };
+
+        assert!(config.excludes(&score(10, -0.02)));
+        assert!(!config.excludes(&score(10, -0.005)));
+        // Not enough orders to judge the solver yet.
+        assert!(!config.excludes(&score(9, -0.02)));
+
+        let config = Config {
+            min_mean_deviation: None,
+            ..config
+        };
+        assert!(!config.excludes(&score(10, -0.5)));
+    }
+
+    #[test]
+    fn excludes_unreliable_solvers() {
+        let config = config();
+        let rate = |won, settled| SettlementRate {
+            solver: H160([1; 20]).into(),
+            won,
+            settled,
+            latest_auction: 1,
+        };
+
+        assert!(config.is_unreliable(&rate(10, 7)));
+        assert!(!config.is_unreliable(&rate(10, 8)));
+        // Not enough won auctions to judge the solver yet.
+        assert!(!config.is_unreliable(&rate(4, 0)));
+
+        let config = Config {
+            min_settlement_rate: None,
+            ..config
+        };
+        assert!(!config.is_unreliable(&rate(10, 0)));
+    }
+
+    #[test]
+    fn exclusions_end_after_cool_down() {
+        let now = Utc::now();
+        assert_eq!(
+            config().excluded_until(now),
+            now + chrono::Duration::hours(1)
+        );
+
+        let config = Config {
+            cool_down: Duration::MAX,
+            ..config()
+        };
+        assert_eq!(config.excluded_until(now), DateTime::<Utc>::MAX_UTC);
+    }
+}
@@ -12,6 +12,7 @@ use {
     serde_with::{serde_as, DisplayFromStr},
     std::{
         collections::{HashMap, HashSet},
+        num::NonZeroUsize,
         time::Duration,
     },
 };
@@ -22,6 +23,7 @@ impl Request {
         trusted_tokens: &HashSet<H160>,
         time_limit: Duration,
     ) -> Self {
+        let now = Utc::now();
         Self {
             id: auction.id,
             orders: auction
@@ -45,7 +47,7 @@ impl Request {
                 }))
                 .unique_by(|token| token.address)
                 .collect(),
-            deadline: Utc::now() + chrono::Duration::from_std(time_limit).unwrap(),
+            deadline: now + chrono::Duration::from_std(time_limit).unwrap(),
             surplus_capturing_jit_order_owners: auction
                 .surplus_capturing_jit_order_owners
                 .iter()
@@ -64,6 +66,19 @@ impl Response {
             .map(Solution::into_domain)
             .collect()
     }
+
+    /// Estimates how far the clock of the driver is ahead of the clock of the
+    /// autopilot the way NTP does, i.e. assuming that the request and the
+    /// response took equally long to arrive.
+    pub fn clock_offset(
+        &self,
+        request_sent_at: DateTime<Utc>,
+        received_at: DateTime<Utc>,
+    ) -> Option<chrono::Duration> {
+        let request_delay = self.received_at? - request_sent_at;
+        let response_delay = received_at - self.sent_at?;
+        Some((request_delay - response_delay) / 2)
+    }
 }
 
 #[serde_as]
@@ -78,6 +93,43 @@ pub struct Request {
     pub surplus_capturing_jit_order_owners: Vec<H160>,
 }
 
+/// A [`Request`] that is encoded except for the time it gets sent at.
+///
+/// Encoding large auctions takes a while, so the time gets stamped as
+/// `sentAt` after the rest of the request is encoded. Otherwise the encoding
+/// would count as network delay, which shifts the deadline drivers interpret
+/// relative to it and the estimated clock offset.
+pub struct EncodedRequest {
+    id: i64,
+    /// The JSON object of the request without its closing brace.
+    json: Vec<u8>,
+}
+
+impl EncodedRequest {
+    pub fn new(request: &Request) -> Self {
+        let mut json = serde_json::to_vec(request).expect("requests are serializable");
+        let closing_brace = json.pop();
+        debug_assert_eq!(closing_brace, Some(b'}'));
+        Self {
+            id: request.id,
+            json,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// The body of the request when sending it at the specified time.
+    pub fn body(&self, sent_at: DateTime<Utc>) -> Vec<u8> {
+        let mut body = self.json.clone();
+        body.extend_from_slice(br#","sentAt":"#);
+        serde_json::to_writer(&mut body, &sent_at).expect("timestamps are serializable");
+        body.push(b'}');
+        body
+    }
+}
+
 #[serde_as]
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -106,6 +158,12 @@ impl Solution {
                     domain::auction::Price::try_new(price.into()).map(|price| (token.into(), price))
                 })
                 .collect::<Result<_, _>>()?,
+            self.gas.map(|gas| eth::Gas(gas.into())),
+            self.timings.map(|timings| domain::competition::Timings {
+                liquidity_processing: timings.liquidity_processing,
+                optimization: timings.optimization,
+                encoding: timings.encoding,
+            }),
         ))
     }
 }
@@ -115,7 +173,7 @@ impl Solution {
 /// auction, so autopilot can be aware of them before the solution is
 /// settled on-chain.
 #[serde_as]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TradedOrder {
     side: Side,
@@ -157,7 +215,7 @@ impl TradedOrder {
 }
 
 #[serde_as]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Side {
     Buy,
@@ -165,7 +223,7 @@ pub enum Side {
 }
 
 #[serde_as]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Solution {
     /// Unique ID of the solution (per driver competition), used to identify
@@ -179,10 +237,96 @@ pub struct Solution {
     #[serde_as(as = "HashMap<_, HexOrDecimalU256>")]
     pub clearing_prices: HashMap<H160, U256>,
     pub gas: Option<u64>,
+    #[serde(default)]
+    pub timings: Option<Timings>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+/// Milliseconds the solver engine spent in the different phases of solving
+/// the auction.
+#[serde_as]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timings {
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(default)]
+    pub liquidity_processing: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(default)]
+    pub optimization: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(default)]
+    pub encoding: Option<Duration>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
     pub solutions: Vec<Solution>,
+    /// The maximum number of orders the driver wants future auctions to
+    /// have, e.g. to stay within its memory budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_orders: Option<NonZeroUsize>,
+    /// How much longer than the regular solve deadline the driver wants
+    /// future auctions to give it.
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_extension: Option<Duration>,
+    /// When the driver received the request according to its clock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<DateTime<Utc>>,
+    /// When the driver sent the response according to its clock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn stamps_time_of_sending() {
+        let request = EncodedRequest::new(&Request {
+            id: 1,
+            deadline: time("2024-01-01T00:00:10Z"),
+            ..Default::default()
+        });
+        let body: serde_json::Value =
+            serde_json::from_slice(&request.body(time("2024-01-01T00:00:00.5Z"))).unwrap();
+        assert_eq!(body["id"], "1");
+        assert_eq!(body["deadline"], "2024-01-01T00:00:10Z");
+        assert_eq!(body["sentAt"], "2024-01-01T00:00:00.500Z");
+    }
+
+    #[test]
+    fn estimates_clock_offset() {
+        let response = Response {
+            solutions: Default::default(),
+            max_orders: None,
+            deadline_extension: None,
+            received_at: Some(time("2024-01-01T00:00:02.1Z")),
+            sent_at: Some(time("2024-01-01T00:00:03.1Z")),
+        };
+        // The request and the response each took 100ms to arrive and the
+        // clock of the driver is 2s ahead.
+        assert_eq!(
+            response.clock_offset(time("2024-01-01T00:00:00Z"), time("2024-01-01T00:00:01.2Z")),
+            Some(chrono::Duration::seconds(2))
+        );
+
+        // Drivers that don't tell when they received the request and sent the
+        // response don't allow estimating the offset.
+        let response = Response {
+            received_at: None,
+            ..response
+        };
+        assert_eq!(
+            response.clock_offset(time("2024-01-01T00:00:00Z"), time("2024-01-01T00:00:01.2Z")),
+            None
+        );
+    }
 }
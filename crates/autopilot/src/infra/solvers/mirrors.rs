@@ -0,0 +1,237 @@
+//! Drivers can be hosted in multiple regions, each region serving the same
+//! API. Requests get sent to the healthy mirror with the lowest latency and
+//! fail over to the other mirrors if it errors.
+
+use {
+    std::{
+        collections::VecDeque,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+    url::Url,
+};
+
+/// How long a mirror is considered unhealthy after a failed request. After
+/// that it gets ranked by its latency again, so it recovers once it serves
+/// requests successfully.
+const FAILURE_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Weight of the latest response time in the moving average of a mirror's
+/// latency.
+const LATENCY_WEIGHT: f64 = 0.2;
+
+/// How many auctions to remember the mirror that solved them for.
+const REMEMBERED_AUCTIONS: usize = 16;
+
+pub struct Mirrors {
+    driver: String,
+    mirrors: Vec<Mirror>,
+    /// Which mirror solved the latest auctions. Solutions only exist on the
+    /// mirror that computed them, so they have to be revealed and settled
+    /// there.
+    solved_by: Mutex<VecDeque<(i64, usize)>>,
+}
+
+struct Mirror {
+    url: Url,
+    /// Identifies the mirror in logs and metrics without leaking secrets that
+    /// might be part of its URL.
+    label: String,
+    health: Mutex<Health>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    /// Moving average of the response times.
+    latency: Option<Duration>,
+    /// When the latest request failed, if it failed.
+    failed_at: Option<Instant>,
+}
+
+impl Health {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.failed_at.map_or(true, |failed_at| {
+            now.duration_since(failed_at) > FAILURE_COOLDOWN
+        })
+    }
+
+    fn succeeded(&mut self, latency: Duration) {
+        self.latency = Some(match self.latency {
+            Some(average) => average.mul_f64(1. - LATENCY_WEIGHT) + latency.mul_f64(LATENCY_WEIGHT),
+            None => latency,
+        });
+        self.failed_at = None;
+    }
+
+    fn failed(&mut self, now: Instant) {
+        self.failed_at = Some(now);
+    }
+}
+
+impl Mirrors {
+    /// Creates the mirrors of a driver. The primary URL is preferred as long
+    /// as the latencies of the other mirrors are unknown.
+    pub fn new(driver: String, primary: Url, mirrors: Vec<Url>) -> Self {
+        Self {
+            driver,
+            mirrors: std::iter::once(primary)
+                .chain(mirrors)
+                .map(|url| Mirror {
+                    label: match url.port() {
+                        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+                        None => url.host_str().unwrap_or_default().to_owned(),
+                    },
+                    url,
+                    health: Default::default(),
+                })
+                .collect(),
+            solved_by: Default::default(),
+        }
+    }
+
+    pub fn url(&self, mirror: usize) -> &Url {
+        &self.mirrors[mirror].url
+    }
+
+    pub fn label(&self, mirror: usize) -> &str {
+        &self.mirrors[mirror].label
+    }
+
+    /// Indices of the mirrors in the order they should be tried: healthy
+    /// mirrors before unhealthy ones, faster mirrors before slower ones.
+    /// Mirrors with unknown latencies get tried early so their latencies get
+    /// measured.
+    pub fn ranked(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let health: Vec<_> = self
+            .mirrors
+            .iter()
+            .map(|mirror| {
+                let health = mirror.health.lock().unwrap();
+                (!health.is_healthy(now), health.latency.unwrap_or_default())
+            })
+            .collect();
+        let mut ranked: Vec<_> = (0..self.mirrors.len()).collect();
+        ranked.sort_by_key(|&mirror| health[mirror]);
+        ranked
+    }
+
+    /// Records the outcome of a request to the mirror.
+    pub fn record(&self, mirror: usize, request: &str, result: Result<Duration, ()>) {
+        let mut health = self.mirrors[mirror].health.lock().unwrap();
+        let outcome = match result {
+            Ok(latency) => {
+                health.succeeded(latency);
+                "success"
+            }
+            Err(()) => {
+                health.failed(Instant::now());
+                "failure"
+            }
+        };
+        Metrics::get()
+            .driver_mirror_requests
+            .with_label_values(&[&self.driver, self.label(mirror), request, outcome])
+            .inc();
+    }
+
+    /// Remembers that the mirror solved the auction.
+    pub fn solved(&self, auction: i64, mirror: usize) {
+        let mut solved_by = self.solved_by.lock().unwrap();
+        if solved_by.len() == REMEMBERED_AUCTIONS {
+            solved_by.pop_front();
+        }
+        solved_by.push_back((auction, mirror));
+    }
+
+    /// The mirror that solved the auction. Falls back to the best ranked
+    /// mirror if it is unknown.
+    pub fn solver_of(&self, auction: Option<i64>) -> usize {
+        let solved_by = self
+            .solved_by
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(solved, _)| Some(*solved) == auction)
+            .map(|(_, mirror)| *mirror);
+        solved_by.unwrap_or_else(|| self.ranked()[0])
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Requests sent to the driver mirrors by outcome.
+    #[metric(labels("driver", "mirror", "request", "outcome"))]
+    driver_mirror_requests: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirrors() -> Mirrors {
+        Mirrors::new(
+            "solver".to_string(),
+            "http://eu.solver.xyz/solve".parse().unwrap(),
+            vec![
+                "http://us.solver.xyz/solve".parse().unwrap(),
+                "http://asia.solver.xyz:8080/solve".parse().unwrap(),
+            ],
+        )
+    }
+
+    #[test]
+    fn ranks_by_health_and_latency() {
+        let mirrors = mirrors();
+        assert_eq!(mirrors.ranked(), [0, 1, 2]);
+        assert_eq!(mirrors.label(2), "asia.solver.xyz:8080");
+
+        // Mirrors that weren't measured yet get tried first.
+        mirrors.record(0, "solve", Ok(Duration::from_millis(300)));
+        assert_eq!(mirrors.ranked(), [1, 2, 0]);
+        mirrors.record(1, "solve", Ok(Duration::from_millis(100)));
+        mirrors.record(2, "solve", Ok(Duration::from_millis(200)));
+        assert_eq!(mirrors.ranked(), [1, 2, 0]);
+
+        // Failing mirrors are only used as a last resort.
+        mirrors.record(1, "solve", Err(()));
+        assert_eq!(mirrors.ranked(), [2, 0, 1]);
+    }
+
+    #[test]
+    fn unhealthy_mirrors_recover() {
+        let now = Instant::now();
+        let mut health = Health::default();
+        health.failed(now);
+        assert!(!health.is_healthy(now));
+        assert!(health.is_healthy(now + FAILURE_COOLDOWN + Duration::from_secs(1)));
+        health.succeeded(Duration::from_millis(100));
+        assert!(health.is_healthy(now));
+
+        health.succeeded(Duration::from_millis(200));
+        assert_eq!(health.latency, Some(Duration::from_millis(120)));
+    }
+
+    #[test]
+    fn remembers_solving_mirror() {
+        let mirrors = mirrors();
+        mirrors.solved(1, 2);
+        mirrors.solved(2, 1);
+        assert_eq!(mirrors.solver_of(Some(1)), 2);
+        assert_eq!(mirrors.solver_of(Some(2)), 1);
+        assert_eq!(mirrors.solver_of(Some(3)), 0);
+        assert_eq!(mirrors.solver_of(None), 0);
+
+        for auction in 3..3 + REMEMBERED_AUCTIONS as i64 {
+            mirrors.solved(auction, 1);
+        }
+        assert_eq!(mirrors.solver_of(Some(1)), 0);
+    }
+}
@@ -1,16 +1,33 @@
 use {
     self::dto::{reveal, settle, solve},
-    crate::{domain::eth, util},
+    crate::{
+        domain::{self, eth},
+        util,
+    },
     anyhow::{anyhow, Context, Result},
+    chrono::Utc,
+    observe::distributed_tracing::RequestBuilderExt,
     reqwest::{Client, StatusCode},
-    std::time::Duration,
+    shared::{arguments::DriverCredential, http_client::HttpClientFactory},
+    std::{
+        borrow::Cow,
+        collections::HashSet,
+        num::NonZeroUsize,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
     url::Url,
 };
 
 pub mod dto;
+mod mirrors;
 
 const RESPONSE_SIZE_LIMIT: usize = 10_000_000;
 const RESPONSE_TIME_LIMIT: Duration = Duration::from_secs(60);
+/// Clock offsets between the autopilot and drivers beyond which deadlines
+/// become unreliable for drivers that don't interpret them relative to the
+/// time the request got sent.
+const MAX_CLOCK_OFFSET: chrono::Duration = chrono::Duration::milliseconds(500);
 
 pub struct Driver {
     pub name: String,
@@ -19,36 +36,222 @@ pub struct Driver {
     // winning solution should be discarded if it contains at least one order, which
     // another driver solved with surplus exceeding this driver's surplus by `threshold`
     pub fairness_threshold: Option<eth::Ether>,
+    /// The subset of auctions the driver is able to handle.
+    pub capabilities: Capabilities,
+    /// The maximum number of orders the driver asked for with its latest
+    /// response, e.g. to stay within its memory budget.
+    requested_max_orders: Mutex<Option<NonZeroUsize>>,
+    /// The extension of the solve deadline the driver asked for with its
+    /// latest response.
+    requested_deadline_extension: Mutex<Option<Duration>>,
+    /// The longest extension of the solve deadline operators allow the
+    /// driver to get. Drivers without allowance always get the regular
+    /// deadline.
+    max_deadline_extension: Duration,
+    /// The primary `url` and further URLs serving the same driver, e.g. from
+    /// other regions.
+    mirrors: mirrors::Mirrors,
     client: Client,
 }
 
 impl Driver {
-    pub fn new(url: Url, name: String, fairness_threshold: Option<eth::Ether>) -> Self {
+    pub fn new(
+        url: Url,
+        name: String,
+        fairness_threshold: Option<eth::Ether>,
+        capabilities: Capabilities,
+        mirrors: Vec<Url>,
+        credential: Option<&DriverCredential>,
+        http: &HttpClientFactory,
+    ) -> Self {
         Self {
+            mirrors: mirrors::Mirrors::new(name.clone(), url.clone(), mirrors),
             name,
             url,
             fairness_threshold,
-            client: Client::builder()
+            capabilities,
+            requested_max_orders: Default::default(),
+            requested_deadline_extension: Default::default(),
+            max_deadline_extension: Duration::ZERO,
+            client: http
+                .builder()
                 .timeout(RESPONSE_TIME_LIMIT)
+                .default_headers(
+                    credential
+                        .map(DriverCredential::headers)
+                        .unwrap_or_default(),
+                )
                 .build()
                 .unwrap(),
         }
     }
 
+    /// Sends the auction to the mirrors in the order of their health and
+    /// latency until one of them responds.
     pub async fn solve(&self, request: &solve::Request) -> Result<solve::Response> {
-        self.request_response("solve", request, None).await
+        let mut result = Err(anyhow!("driver has no mirrors"));
+        let request = solve::EncodedRequest::new(request);
+        for mirror in self.mirrors.ranked() {
+            // Further mirrors only get the request after the previous ones
+            // failed, so the time of sending tells them that they have less
+            // time until the deadline.
+            let sent_at = Utc::now();
+            let start = Instant::now();
+            result = self
+                .post(
+                    self.mirrors.url(mirror),
+                    "solve",
+                    request.body(sent_at),
+                    None,
+                )
+                .await;
+            match &result {
+                Ok(response) => {
+                    self.mirrors.record(mirror, "solve", Ok(start.elapsed()));
+                    self.mirrors.solved(request.id(), mirror);
+                    self.request_max_orders(response.max_orders);
+                    self.request_deadline_extension(response.deadline_extension);
+                    if let Some(offset) = response.clock_offset(sent_at, Utc::now()) {
+                        self.observe_clock_offset(offset);
+                    }
+                    break;
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        driver = %self.name,
+                        mirror = %self.mirrors.label(mirror),
+                        ?err,
+                        "driver mirror failed to solve"
+                    );
+                    self.mirrors.record(mirror, "solve", Err(()));
+                }
+            }
+        }
+        result
+    }
+
+    /// The capabilities of the driver limited by the number of orders it asked
+    /// for with its latest response.
+    pub fn current_capabilities(&self) -> Cow<'_, Capabilities> {
+        match *self.requested_max_orders.lock().unwrap() {
+            None => Cow::Borrowed(&self.capabilities),
+            Some(requested) => Cow::Owned(Capabilities {
+                max_orders: Some(
+                    self.capabilities
+                        .max_orders
+                        .map_or(requested, |max| max.min(requested)),
+                ),
+                ..self.capabilities.clone()
+            }),
+        }
+    }
+
+    fn request_max_orders(&self, max_orders: Option<NonZeroUsize>) {
+        let mut requested = self.requested_max_orders.lock().unwrap();
+        if *requested != max_orders {
+            tracing::info!(
+                driver = %self.name,
+                ?max_orders,
+                previous = ?*requested,
+                "driver requested different auction size"
+            );
+        }
+        *requested = max_orders;
+    }
+
+    /// Allows the driver to get up to `max` more time than the regular solve
+    /// deadline if it asks for it.
+    pub fn with_max_deadline_extension(mut self, max: Duration) -> Self {
+        self.max_deadline_extension = max;
+        self
+    }
+
+    /// How much longer than the regular solve deadline the driver gets to
+    /// solve: the extension it asked for but at most what operators allow.
+    pub fn deadline_extension(&self) -> Duration {
+        self.requested_deadline_extension
+            .lock()
+            .unwrap()
+            .map_or(Duration::ZERO, |requested| {
+                requested.min(self.max_deadline_extension)
+            })
+    }
+
+    fn request_deadline_extension(&self, extension: Option<Duration>) {
+        let mut requested = self.requested_deadline_extension.lock().unwrap();
+        if extension.is_some() && self.max_deadline_extension.is_zero() {
+            tracing::debug!(
+                driver = %self.name,
+                ?extension,
+                "ignoring deadline extension of driver without allowance"
+            );
+        } else if *requested != extension {
+            tracing::info!(
+                driver = %self.name,
+                ?extension,
+                previous = ?*requested,
+                "driver requested different deadline extension"
+            );
+        }
+        *requested = extension;
+    }
+
+    fn observe_clock_offset(&self, offset: chrono::Duration) {
+        let seconds = offset.num_milliseconds() as f64 / 1000.;
+        Metrics::get()
+            .driver_clock_offset_seconds
+            .with_label_values(&[&self.name])
+            .set(seconds);
+        if offset.abs() > MAX_CLOCK_OFFSET {
+            tracing::warn!(
+                driver = %self.name,
+                ?offset,
+                "clock of driver deviates from the autopilot's"
+            );
+        }
     }
 
+    /// Reveals the solution on the mirror that computed it.
     pub async fn reveal(&self, request: &reveal::Request) -> Result<reveal::Response> {
-        self.request_response("reveal", request, None).await
+        let mirror = self.mirrors.solver_of(request.auction_id);
+        let start = Instant::now();
+        let result = self
+            .request_response(self.mirrors.url(mirror), "reveal", request, None)
+            .await;
+        self.mirrors.record(
+            mirror,
+            "reveal",
+            result.as_ref().map(|_| start.elapsed()).map_err(|_| ()),
+        );
+        result
     }
 
+    /// Settles the solution on the mirror that computed it.
     pub async fn settle(
         &self,
         request: &settle::Request,
         timeout: std::time::Duration,
     ) -> Result<()> {
-        let url = util::join(&self.url, "settle");
+        let mirror = self.mirrors.solver_of(request.auction_id);
+        let start = Instant::now();
+        let result = self
+            .send_settle(self.mirrors.url(mirror), request, timeout)
+            .await;
+        self.mirrors.record(
+            mirror,
+            "settle",
+            result.as_ref().map(|_| start.elapsed()).map_err(|_| ()),
+        );
+        result
+    }
+
+    async fn send_settle(
+        &self,
+        url: &Url,
+        request: &settle::Request,
+        timeout: std::time::Duration,
+    ) -> Result<()> {
+        let url = util::join(url, "settle");
         tracing::trace!(
             path=&url.path(),
             body=%serde_json::to_string_pretty(request).unwrap(),
@@ -60,6 +263,7 @@ impl Driver {
             .post(url)
             .json(request)
             .timeout(timeout)
+            .with_tracing_headers()
             .send()
             .await
             .context("send")?;
@@ -76,6 +280,7 @@ impl Driver {
 
     async fn request_response<Response>(
         &self,
+        url: &Url,
         path: &str,
         request: &impl serde::Serialize,
         timeout: Option<std::time::Duration>,
@@ -83,13 +288,33 @@ impl Driver {
     where
         Response: serde::de::DeserializeOwned,
     {
-        let url = util::join(&self.url, path);
+        let body = serde_json::to_vec(request).context("encode")?;
+        self.post(url, path, body, timeout).await
+    }
+
+    /// Posts the JSON encoded body and decodes the response.
+    async fn post<Response>(
+        &self,
+        url: &Url,
+        path: &str,
+        body: Vec<u8>,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<Response>
+    where
+        Response: serde::de::DeserializeOwned,
+    {
+        let url = util::join(url, path);
         tracing::trace!(
             path=&url.path(),
-            body=%serde_json::to_string_pretty(request).unwrap(),
+            body=%String::from_utf8_lossy(&body),
             "solver request",
         );
-        let mut request = self.client.post(url.clone()).json(request);
+        let mut request = self
+            .client
+            .post(url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .with_tracing_headers();
 
         if let Some(timeout) = timeout {
             request = request.timeout(timeout);
@@ -110,6 +335,79 @@ impl Driver {
     }
 }
 
+/// Restrictions on which orders a driver is able to handle. The autopilot
+/// only sends the orders matching these restrictions to the driver, which
+/// avoids wasting work on orders the driver would ignore anyway.
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    /// Tokens the driver can trade. Orders buying or selling any other token
+    /// are filtered out. `None` means all tokens are supported.
+    pub tokens: Option<HashSet<eth::TokenAddress>>,
+    /// The maximum number of orders the driver can handle per auction.
+    pub max_orders: Option<NonZeroUsize>,
+    /// The classes of orders the driver can handle. `None` means all classes
+    /// are supported.
+    pub order_classes: Option<HashSet<OrderClass>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum OrderClass {
+    FillOrKill,
+    PartiallyFillable,
+}
+
+impl OrderClass {
+    fn of(order: &domain::Order) -> Self {
+        if order.partially_fillable {
+            Self::PartiallyFillable
+        } else {
+            Self::FillOrKill
+        }
+    }
+}
+
+impl Capabilities {
+    /// Whether the driver can handle every order of any auction.
+    pub fn is_unrestricted(&self) -> bool {
+        self.tokens.is_none() && self.max_orders.is_none() && self.order_classes.is_none()
+    }
+
+    /// Whether the driver is able to handle the given order.
+    pub fn supports(&self, order: &domain::Order) -> bool {
+        let supports_token = |token| {
+            self.tokens
+                .as_ref()
+                .map_or(true, |tokens| tokens.contains(token))
+        };
+        let supports_class = self
+            .order_classes
+            .as_ref()
+            .map_or(true, |classes| classes.contains(&OrderClass::of(order)));
+        supports_token(&order.sell.token) && supports_token(&order.buy.token) && supports_class
+    }
+
+    /// Returns the copy of the auction that should be sent to the driver.
+    /// Orders are kept in the original order so that the `max_orders` limit
+    /// keeps the orders that were prioritized by the auction.
+    pub fn filter(&self, auction: &domain::Auction) -> domain::Auction {
+        let orders = auction
+            .orders
+            .iter()
+            .filter(|order| self.supports(order))
+            .take(self.max_orders.map_or(usize::MAX, NonZeroUsize::get))
+            .cloned()
+            .collect();
+        domain::Auction {
+            id: auction.id,
+            block: auction.block,
+            orders,
+            prices: auction.prices.clone(),
+            surplus_capturing_jit_order_owners: auction.surplus_capturing_jit_order_owners.clone(),
+        }
+    }
+}
+
 /// Extracts the bytes of the response up to some size limit.
 ///
 /// Returns an error if the byte limit was exceeded.
@@ -127,3 +425,58 @@ pub async fn response_body_with_size_limit(
     }
     Ok(bytes)
 }
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// How far the clock of a driver was ahead of the autopilot's according to
+    /// its latest response.
+    #[metric(labels("driver"))]
+    driver_clock_offset_seconds: prometheus::GaugeVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_extension_requires_allowance() {
+        let driver = |max| {
+            Driver::new(
+                "http://localhost".parse().unwrap(),
+                "solver".to_string(),
+                None,
+                Default::default(),
+                Default::default(),
+                None,
+                &Default::default(),
+            )
+            .with_max_deadline_extension(max)
+        };
+
+        let driver_without_allowance = driver(Duration::ZERO);
+        driver_without_allowance.request_deadline_extension(Some(Duration::from_secs(3)));
+        assert_eq!(
+            driver_without_allowance.deadline_extension(),
+            Duration::ZERO
+        );
+
+        let driver_with_allowance = driver(Duration::from_secs(2));
+        assert_eq!(driver_with_allowance.deadline_extension(), Duration::ZERO);
+        driver_with_allowance.request_deadline_extension(Some(Duration::from_secs(1)));
+        assert_eq!(
+            driver_with_allowance.deadline_extension(),
+            Duration::from_secs(1)
+        );
+        driver_with_allowance.request_deadline_extension(Some(Duration::from_secs(3)));
+        assert_eq!(
+            driver_with_allowance.deadline_extension(),
+            Duration::from_secs(2)
+        );
+    }
+}
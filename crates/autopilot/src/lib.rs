@@ -1,3 +1,4 @@
+pub mod app_code_reports;
 pub mod arguments;
 pub mod boundary;
 pub mod database;
@@ -9,7 +10,9 @@ pub mod periodic_db_cleanup;
 pub mod run;
 pub mod run_loop;
 pub mod shadow;
+pub mod snapshot;
 pub mod solvable_orders;
 pub mod util;
+pub mod what_if;
 
 pub use self::run::{run, start};
@@ -29,6 +29,9 @@ use {
 pub struct Maintenance {
     /// Indexes and persists all events emited by the settlement contract.
     settlement_indexer: EventUpdater<Indexer, GPv2SettlementContract>,
+    /// Indexes events of additional settlement contracts (e.g. staging
+    /// deployments) into their own databases.
+    additional_settlement_indexers: Vec<EventUpdater<Indexer, GPv2SettlementContract>>,
     /// Indexes ethflow orders (orders selling native ETH).
     ethflow_indexer: Option<EthflowIndexer>,
     /// Used for periodic cleanup tasks to not have the DB overflow with old
@@ -47,6 +50,7 @@ impl Maintenance {
     ) -> Self {
         Self {
             settlement_indexer,
+            additional_settlement_indexers: Default::default(),
             db_cleanup,
             cow_amm_indexer: Default::default(),
             ethflow_indexer: None,
@@ -90,6 +94,14 @@ impl Maintenance {
             ),
             Self::timed_future("db_cleanup", self.db_cleanup.run_maintenance()),
             Self::timed_future("ethflow_indexer", self.index_ethflow_orders()),
+            Self::timed_future(
+                "additional_settlement_indexers",
+                futures::future::try_join_all(
+                    self.additional_settlement_indexers
+                        .iter()
+                        .map(|indexer| indexer.run_maintenance())
+                ),
+            ),
         )?;
 
         Ok(())
@@ -101,6 +113,13 @@ impl Maintenance {
         self.ethflow_indexer = Some(ethflow_indexer);
     }
 
+    pub fn with_additional_settlement_indexer(
+        &mut self,
+        indexer: EventUpdater<Indexer, GPv2SettlementContract>,
+    ) {
+        self.additional_settlement_indexers.push(indexer);
+    }
+
     pub fn with_cow_amms(&mut self, registry: &cow_amm::Registry) {
         self.cow_amm_indexer = registry.maintenance_tasks().clone();
     }
@@ -5,6 +5,10 @@ use {
     tokio::time,
 };
 
+/// How far ahead of time `order_events` partitions get created, so events
+/// keep being stored in daily partitions even if some cleanups fail.
+const PARTITIONS_AHEAD: chrono::Duration = chrono::Duration::days(7);
+
 pub struct OrderEventsCleanerConfig {
     cleanup_interval: Duration,
     event_age_threshold: chrono::Duration,
@@ -34,14 +38,30 @@ impl OrderEventsCleaner {
         loop {
             interval.tick().await;
 
+            let until = Utc::now() + PARTITIONS_AHEAD;
+            match self.db.create_order_events_partitions_until(until).await {
+                Ok(created_partitions) => {
+                    tracing::debug!(created_partitions, "order events partitions created")
+                }
+                Err(err) => tracing::warn!(?err, "failed to create order events partitions"),
+            }
+
             let timestamp: DateTime<Utc> = Utc::now() - self.config.event_age_threshold;
-            match self.db.delete_order_events_before(timestamp).await {
-                Ok(affected_rows_count) => {
-                    tracing::debug!(affected_rows_count, timestamp = %timestamp.to_string(), "order events cleanup");
+            match self
+                .db
+                .summarize_and_delete_order_events_before(timestamp)
+                .await
+            {
+                Ok((summarized_orders, affected_rows_count, dropped_partitions)) => {
+                    tracing::debug!(summarized_orders, affected_rows_count, dropped_partitions, timestamp = %timestamp.to_string(), "order events cleanup");
                     Metrics::get().order_events_cleanup_total.inc()
                 }
                 Err(err) => {
-                    tracing::warn!(?err, "failed to delete order events before {}", timestamp)
+                    tracing::warn!(
+                        ?err,
+                        "failed to summarize and delete order events before {}",
+                        timestamp
+                    )
                 }
             }
         }
@@ -1,6 +1,13 @@
 use {
     crate::{
-        arguments::Arguments,
+        arguments::{
+            Arguments,
+            BondingPool,
+            BondingRequirement,
+            DriverCapabilities,
+            DriverDeadlineExtension,
+            DriverMirrors,
+        },
         boundary,
         database::{
             ethflow_events::event_retriever::EthFlowRefundRetriever,
@@ -21,6 +28,7 @@ use {
         maintenance::Maintenance,
         run_loop::{self, RunLoop},
         shadow,
+        snapshot,
         solvable_orders::SolvableOrdersCache,
     },
     chain::Chain,
@@ -31,8 +39,10 @@ use {
     futures::StreamExt,
     model::DomainSeparator,
     observe::metrics::LivenessChecking,
+    rate_limit::RateLimiterRegistry,
     shared::{
         account_balances,
+        arguments::{DriverCredential, ExternalSolver},
         bad_token::{
             cache::CachingDetector,
             instrumented::InstrumentedBadTokenDetectorExt,
@@ -52,6 +62,7 @@ use {
         token_list::{AutoUpdatingTokenList, TokenListConfiguration},
     },
     std::{
+        collections::{BTreeMap, HashMap},
         sync::{Arc, RwLock},
         time::{Duration, Instant},
     },
@@ -86,8 +97,13 @@ impl Liveness {
     }
 }
 
-async fn ethrpc(url: &Url, ethrpc_args: &shared::ethrpc::Arguments) -> infra::blockchain::Rpc {
-    infra::blockchain::Rpc::new(url, ethrpc_args)
+async fn ethrpc(
+    url: &Url,
+    fallbacks: &[Url],
+    ethrpc_args: &shared::ethrpc::Arguments,
+    http_factory: &HttpClientFactory,
+) -> infra::blockchain::Rpc {
+    infra::blockchain::Rpc::new(url, fallbacks, ethrpc_args, http_factory)
         .await
         .expect("connect ethereum RPC")
 }
@@ -103,14 +119,19 @@ async fn ethereum(
 }
 
 pub async fn start(args: impl Iterator<Item = String>) {
-    let args = Arguments::parse_from(args);
-    observe::tracing::initialize(
-        args.shared.logging.log_filter.as_str(),
-        args.shared.logging.log_stderr_threshold,
-    );
+    let mut args = Arguments::parse_from(args);
+    let observe_config = args.shared.logging.observe_config();
+    observe::tracing::initialize_with_config(&observe_config);
     observe::panic_hook::install();
+    args.metrics_address = Some(args.metrics_address(observe_config.metrics_port));
     tracing::info!("running autopilot with validated arguments:\n{}", args);
     observe::metrics::setup_registry(Some("gp_v2_autopilot".into()), None);
+    args.shared.register_custom_chains();
+
+    if let Some(id) = &args.replay_snapshot {
+        replay_snapshot(&args, id).await;
+        return;
+    }
 
     if args.drivers.is_empty() {
         panic!("colocation is enabled but no drivers are configured");
@@ -126,17 +147,31 @@ pub async fn start(args: impl Iterator<Item = String>) {
 /// Assumes tracing and metrics registry have already been set up.
 pub async fn run(args: Arguments) {
     assert!(args.shadow.is_none(), "cannot run in shadow mode");
+    let metrics_address = args.metrics_address(None);
 
+    database::instrumentation::configure("autopilot", args.shared.db_slow_query_threshold);
     let db = Postgres::new(args.db_url.as_str(), args.insert_batch_size)
         .await
         .unwrap();
+    args.shared.ethrpc.budget.configure(Some(db.pool.clone()));
     crate::database::run_database_metrics_work(db.clone());
 
+    let snapshots = match args.run_loop_snapshots() {
+        // Snapshots only help debugging so don't refuse to start if the
+        // storage is not usable.
+        Some(config) => snapshot::Archive::new(config, args.run_loop_snapshot_retention)
+            .await
+            .inspect_err(|err| tracing::error!(?err, "run loop snapshots disabled"))
+            .ok(),
+        None => None,
+    };
+
     let http_factory = HttpClientFactory::new(&args.http_client);
-    let web3 = shared::ethrpc::web3(
+    let web3 = shared::ethrpc::web3_with_fallbacks(
         &args.shared.ethrpc,
         &http_factory,
         &args.shared.node_url,
+        &args.shared.fallback_node_urls,
         "base",
     );
     let simulation_web3 = args.shared.simulation_node_url.as_ref().map(|node_url| {
@@ -156,13 +191,22 @@ pub async fn run(args: Arguments) {
         );
     }
 
-    let ethrpc = ethrpc(&args.shared.node_url, &args.shared.ethrpc).await;
+    let ethrpc = ethrpc(
+        &args.shared.node_url,
+        &args.shared.fallback_node_urls,
+        &args.shared.ethrpc,
+        &http_factory,
+    )
+    .await;
     let chain = ethrpc.chain();
     let web3 = ethrpc.web3().clone();
     let url = ethrpc.url().clone();
     let contracts = infra::blockchain::contracts::Addresses {
         settlement: args.shared.settlement_contract_address,
-        weth: args.shared.native_token_address,
+        weth: args
+            .shared
+            .native_token_address
+            .or(chain.custom().map(|config| config.native_token)),
     };
     let eth = ethereum(
         web3.clone(),
@@ -258,6 +302,7 @@ pub async fn run(args: Arguments) {
     allowed_tokens.push(model::order::BUY_ETH_ADDRESS);
     let unsupported_tokens = args.unsupported_tokens.clone();
 
+    let rate_limiters = Arc::new(RateLimiterRegistry::default());
     let finder = token_owner_finder::init(
         &args.token_owner_finder,
         web3.clone(),
@@ -268,6 +313,7 @@ pub async fn run(args: Arguments) {
         uniswapv3_factory.as_ref(),
         &base_tokens,
         eth.contracts().settlement().address(),
+        &rate_limiters,
     )
     .await
     .expect("failed to initialize token owner finders");
@@ -301,6 +347,7 @@ pub async fn run(args: Arguments) {
 
     let token_info_fetcher = Arc::new(CachedTokenInfoFetcher::new(Arc::new(TokenInfoFetcher {
         web3: web3.clone(),
+        overrides: args.shared.token_info_overrides.clone(),
     })));
     let block_retriever = args.shared.current_block.retriever(web3.clone());
 
@@ -330,6 +377,8 @@ pub async fn run(args: Arguments) {
             bad_token_detector: bad_token_detector.clone(),
             tokens: token_info_fetcher.clone(),
             code_fetcher: code_fetcher.clone(),
+            db: db.pool.clone(),
+            rate_limiters: rate_limiters.clone(),
         },
     )
     .await
@@ -362,8 +411,23 @@ pub async fn run(args: Arguments) {
 
     let persistence =
         infra::persistence::Persistence::new(args.s3.into().unwrap(), Arc::new(db.clone())).await;
-    let settlement_observer =
-        crate::domain::settlement::Observer::new(eth.clone(), persistence.clone());
+    let exporter = match args.export_events_url {
+        Some(url) => infra::Exporter::new(
+            infra::export::Config {
+                url,
+                topic: args.export_events_topic,
+                queue_size: args.export_events_queue_size,
+            },
+            http_factory.create(),
+        )
+        .expect("invalid event export configuration"),
+        None => infra::Exporter::disabled(),
+    };
+    let settlement_observer = crate::domain::settlement::Observer::new(
+        eth.clone(),
+        persistence.clone(),
+        exporter.clone(),
+    );
     let settlement_contract_start_index =
         if let Some(DeploymentInformation::BlockNumber(settlement_contract_start_index)) =
             eth.contracts().settlement().deployment_information()
@@ -388,10 +452,11 @@ pub async fn run(args: Arguments) {
         ),
         block_retriever.clone(),
         skip_event_sync_start,
+        chain.finality_depth(),
     );
 
     let archive_node_web3 = args.archive_node_url.as_ref().map_or(web3.clone(), |url| {
-        boundary::web3_client(url, &args.shared.ethrpc)
+        boundary::web3_client(url, &[], &args.shared.ethrpc, &http_factory)
     });
 
     let mut cow_amm_registry = cow_amm::Registry::new(archive_node_web3);
@@ -424,6 +489,13 @@ pub async fn run(args: Arguments) {
         args.price_estimation.quote_verification,
     ));
 
+    let protocol_fees =
+        domain::ProtocolFees::new(&args.fee_policies, args.fee_policy_max_partner_fee);
+    persistence
+        .store_fee_policy_configuration(&protocol_fees.configuration())
+        .await
+        .expect("failed to store fee policy configuration");
+
     let solvable_orders_cache = SolvableOrdersCache::new(
         args.min_order_validity_period,
         persistence.clone(),
@@ -439,13 +511,24 @@ pub async fn run(args: Arguments) {
         args.limit_order_price_factor
             .try_into()
             .expect("limit order price factor can't be converted to BigDecimal"),
-        domain::ProtocolFees::new(&args.fee_policies, args.fee_policy_max_partner_fee),
+        protocol_fees,
         cow_amm_registry.clone(),
         args.run_loop_native_price_timeout,
     );
 
     let liveness = Arc::new(Liveness::new(args.max_auction_age));
-    observe::metrics::serve_metrics(liveness.clone(), args.metrics_address);
+    observe::health::HealthServer::default()
+        .with("run_loop", liveness.clone())
+        .with_report("rate_limits", move || {
+            let back_offs: BTreeMap<_, _> = rate_limiters
+                .back_offs()
+                .into_iter()
+                .chain(RateLimiterRegistry::global().back_offs())
+                .map(|(name, back_off)| (name, back_off.as_secs_f64()))
+                .collect();
+            warp::reply::json(&back_offs)
+        })
+        .serve(metrics_address);
 
     let order_events_cleaner_config = crate::periodic_db_cleanup::OrderEventsCleanerConfig::new(
         args.order_events_cleanup_interval,
@@ -462,11 +545,24 @@ pub async fn run(args: Arguments) {
             .instrument(tracing::info_span!("order_events_cleaner")),
     );
 
+    let app_code_report_updater = crate::app_code_reports::AppCodeReportUpdater::new(
+        db.clone(),
+        web3.clone(),
+        eth.current_block().clone(),
+        chain.finality_depth(),
+        args.app_code_report_update_interval,
+    );
+    tokio::task::spawn(
+        app_code_report_updater
+            .run_forever()
+            .instrument(tracing::info_span!("app_code_report_updater")),
+    );
+
     let market_makable_token_list_configuration = TokenListConfiguration {
         url: args.trusted_tokens_url,
         update_interval: args.trusted_tokens_update_interval,
         chain_id,
-        client: http_factory.create(),
+        client: http_factory.create_caching(),
         hardcoded: args.trusted_tokens.unwrap_or_default(),
     };
     // updated in background task
@@ -475,6 +571,32 @@ pub async fn run(args: Arguments) {
 
     let mut maintenance = Maintenance::new(settlement_event_indexer, db.clone());
     maintenance.with_cow_amms(&cow_amm_registry);
+    for contract in &args.additional_settlement_contracts {
+        let contract_db = Postgres::new(contract.db_url.as_str(), args.insert_batch_size)
+            .await
+            .unwrap();
+        let contract_eth = eth
+            .with_contracts(infra::blockchain::contracts::Addresses {
+                settlement: Some(contract.address),
+                ..contracts.clone()
+            })
+            .await;
+        let observer = crate::domain::settlement::Observer::new(
+            contract_eth.clone(),
+            infra::persistence::Persistence::new(None, Arc::new(contract_db.clone())).await,
+            infra::Exporter::disabled(),
+        );
+        tracing::info!(%contract, "indexing additional settlement contract");
+        maintenance.with_additional_settlement_indexer(EventUpdater::new(
+            boundary::events::settlement::GPv2SettlementContract::new(
+                contract_eth.contracts().settlement().clone(),
+            ),
+            boundary::events::settlement::Indexer::new(contract_db, observer, contract.index_start),
+            block_retriever.clone(),
+            skip_event_sync_start,
+            chain.finality_depth(),
+        ));
+    }
 
     if let Some(ethflow_contract) = args.ethflow_contract {
         let ethflow_refund_start_block = determine_ethflow_refund_indexing_start(
@@ -493,6 +615,7 @@ pub async fn run(args: Arguments) {
             db.clone(),
             block_retriever.clone(),
             ethflow_refund_start_block,
+            chain.finality_depth(),
         )
         .await
         .unwrap();
@@ -522,6 +645,7 @@ pub async fn run(args: Arguments) {
             onchain_order_event_parser,
             block_retriever,
             ethflow_start_block,
+            chain.finality_depth(),
         )
         .await
         .expect("Should be able to initialize event updater. Database read issues?");
@@ -542,31 +666,194 @@ pub async fn run(args: Arguments) {
         max_run_loop_delay: args.max_run_loop_delay,
         max_winners_per_auction: args.max_winners_per_auction,
         max_solutions_per_solver: args.max_solutions_per_solver,
+        filtered_orders_retention: args.filtered_orders_retention,
+        tie_breaking: args.tie_breaking_policy.into(),
+        winner_selection: args.winner_selection_policy.into(),
+        what_if_auctions: match (&args.what_if_api_address, &args.admin_api_key) {
+            (Some(_), Some(_)) => args.what_if_auctions,
+            _ => 0,
+        },
     };
 
-    let run = RunLoop::new(
+    let drivers = drivers(
+        args.drivers,
+        args.driver_capabilities,
+        args.driver_mirrors,
+        args.solve_deadline_extensions,
+        &args.shared.driver_credentials,
+        &http_factory,
+    );
+    let bonding_pools = bonding_pools(
+        eth.clone(),
+        &drivers,
+        args.bonding_pools,
+        args.bonding_pool_requirements,
+    );
+    tokio::task::spawn(
+        bonding_pools
+            .clone()
+            .run_forever(args.bonding_pool_update_interval)
+            .instrument(tracing::info_span!("bonding_pools")),
+    );
+
+    let reputation = Arc::new(infra::Reputation::new(
+        persistence.clone(),
+        infra::reputation::Config {
+            window: args.execution_quality_window,
+            min_orders: args.execution_quality_min_orders,
+            min_mean_deviation: args.execution_quality_min_mean_deviation,
+            settlement_window: args.settlement_rate_window,
+            settlement_lookback: args.settlement_rate_lookback,
+            min_won_auctions: args.settlement_rate_min_won_auctions,
+            min_settlement_rate: args.settlement_rate_min,
+            cool_down: args.unreliable_solver_cool_down,
+            never_excluded: args
+                .reputation_exclusion_overrides
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        },
+    ));
+    tokio::task::spawn(
+        reputation
+            .clone()
+            .run_forever(args.execution_quality_update_interval)
+            .instrument(tracing::info_span!("reputation")),
+    );
+
+    let pauses = Arc::new(infra::Pauses::default());
+    let feature_flags = Arc::new(shared::feature_flags::FeatureFlags::new(
+        db.pool.clone(),
+        chain_id,
+        args.shared.feature_flags_cache_ttl,
+    ));
+    if let Some(api_key) = &args.admin_api_key {
+        infra::pauses::serve(
+            pauses.clone(),
+            feature_flags,
+            args.admin_api_address,
+            api_key.clone(),
+        );
+    }
+
+    let run = Arc::new(RunLoop::new(
         run_loop_config,
         eth,
         persistence.clone(),
-        args.drivers
-            .into_iter()
-            .map(|driver| {
-                Arc::new(infra::Driver::new(
-                    driver.url,
-                    driver.name,
-                    driver.fairness_threshold.map(Into::into),
-                ))
-            })
-            .collect(),
+        exporter,
+        drivers,
+        bonding_pools,
+        pauses,
+        reputation,
         solvable_orders_cache,
         trusted_tokens,
         liveness.clone(),
         Arc::new(maintenance),
-    );
+        snapshots,
+    ));
+    if let (Some(address), Some(api_key)) = (args.what_if_api_address, args.admin_api_key) {
+        crate::what_if::serve(run.clone(), address, api_key);
+    }
     run.run_forever().await;
 }
 
+/// Ranks the solutions of an archived run loop iteration again.
+async fn replay_snapshot(args: &Arguments, id: &str) {
+    let config = args
+        .run_loop_snapshots()
+        .expect("replaying a snapshot requires a run loop snapshot bucket");
+    let archive = snapshot::Archive::new(config, args.run_loop_snapshot_retention)
+        .await
+        .expect("failed to access run loop snapshots");
+    if let Err(err) = snapshot::replay_from_archive(&archive, id).await {
+        tracing::error!(?err, id, "failed to replay run loop snapshot");
+    }
+}
+
+fn drivers(
+    drivers: Vec<ExternalSolver>,
+    capabilities: Vec<DriverCapabilities>,
+    mirrors: Vec<DriverMirrors>,
+    deadline_extensions: Vec<DriverDeadlineExtension>,
+    credentials: &[DriverCredential],
+    http_factory: &HttpClientFactory,
+) -> Vec<Arc<infra::Driver>> {
+    let mut capabilities = capabilities
+        .into_iter()
+        .map(|config| (config.name, config.capabilities))
+        .collect::<HashMap<_, _>>();
+    let mut mirrors = mirrors
+        .into_iter()
+        .map(|config| (config.name, config.urls))
+        .collect::<HashMap<_, _>>();
+    let mut deadline_extensions = deadline_extensions
+        .into_iter()
+        .map(|config| (config.name, config.max))
+        .collect::<HashMap<_, _>>();
+    let drivers = drivers
+        .into_iter()
+        .map(|driver| {
+            let capabilities = capabilities.remove(&driver.name).unwrap_or_default();
+            let mirrors = mirrors.remove(&driver.name).unwrap_or_default();
+            let max_deadline_extension =
+                deadline_extensions.remove(&driver.name).unwrap_or_default();
+            Arc::new(
+                infra::Driver::new(
+                    driver.url,
+                    driver.name,
+                    driver.fairness_threshold.map(Into::into),
+                    capabilities,
+                    mirrors,
+                    DriverCredential::find(credentials, &driver.name),
+                    http_factory,
+                )
+                .with_max_deadline_extension(max_deadline_extension),
+            )
+        })
+        .collect();
+    assert!(
+        capabilities.is_empty(),
+        "capabilities configured for unknown drivers: {:?}",
+        capabilities.keys()
+    );
+    assert!(
+        mirrors.is_empty(),
+        "mirrors configured for unknown drivers: {:?}",
+        mirrors.keys()
+    );
+    assert!(
+        deadline_extensions.is_empty(),
+        "deadline extensions configured for unknown drivers: {:?}",
+        deadline_extensions.keys()
+    );
+    drivers
+}
+
+fn bonding_pools(
+    eth: infra::Ethereum,
+    drivers: &[Arc<infra::Driver>],
+    pools: Vec<BondingPool>,
+    requirements: Vec<BondingRequirement>,
+) -> Arc<infra::BondingPools> {
+    let pools: Vec<_> = pools.into_iter().map(|pool| pool.0).collect();
+    let unknown: Vec<_> = pools
+        .iter()
+        .flat_map(|pool| &pool.drivers)
+        .filter(|name| !drivers.iter().any(|driver| &driver.name == *name))
+        .collect();
+    assert!(
+        unknown.is_empty(),
+        "bonding pools configured for unknown drivers: {unknown:?}"
+    );
+    Arc::new(infra::BondingPools::new(
+        eth,
+        pools,
+        requirements.into_iter().map(|r| r.0).collect(),
+    ))
+}
+
 async fn shadow_mode(args: Arguments) -> ! {
+    let metrics_address = args.metrics_address(None);
     let http_factory = HttpClientFactory::new(&args.http_client);
 
     let orderbook = infra::shadow::Orderbook::new(
@@ -574,23 +861,21 @@ async fn shadow_mode(args: Arguments) -> ! {
         args.shadow.expect("missing shadow mode configuration"),
     );
 
-    let drivers = args
-        .drivers
-        .into_iter()
-        .map(|driver| {
-            Arc::new(infra::Driver::new(
-                driver.url,
-                driver.name,
-                driver.fairness_threshold.map(Into::into),
-            ))
-        })
-        .collect();
+    let drivers = drivers(
+        args.drivers,
+        args.driver_capabilities,
+        args.driver_mirrors,
+        args.solve_deadline_extensions,
+        &args.shared.driver_credentials,
+        &http_factory,
+    );
 
     let trusted_tokens = {
-        let web3 = shared::ethrpc::web3(
+        let web3 = shared::ethrpc::web3_with_fallbacks(
             &args.shared.ethrpc,
             &http_factory,
             &args.shared.node_url,
+            &args.shared.fallback_node_urls,
             "base",
         );
 
@@ -611,14 +896,16 @@ async fn shadow_mode(args: Arguments) -> ! {
             url: args.trusted_tokens_url,
             update_interval: args.trusted_tokens_update_interval,
             chain_id,
-            client: http_factory.create(),
+            client: http_factory.create_caching(),
             hardcoded: args.trusted_tokens.unwrap_or_default(),
         })
         .await
     };
 
     let liveness = Arc::new(Liveness::new(args.max_auction_age));
-    observe::metrics::serve_metrics(liveness.clone(), args.metrics_address);
+    observe::health::HealthServer::default()
+        .with("run_loop", liveness.clone())
+        .serve(metrics_address);
 
     let current_block = ethrpc::block_stream::current_block_stream(
         args.shared.node_url,
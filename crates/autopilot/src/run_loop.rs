@@ -3,25 +3,26 @@ use {
         database::competition::Competition,
         domain::{
             self,
-            auction::Id,
             competition::{self, Solution, SolutionError, TradedOrder, Unranked},
             eth::{self, TxId},
             OrderUid,
         },
         infra::{
             self,
+            export::dto,
             solvers::dto::{settle, solve},
         },
         maintenance::Maintenance,
         run::Liveness,
+        snapshot,
         solvable_orders::SolvableOrdersCache,
     },
-    ::observe::metrics,
+    ::observe::{distributed_tracing, metrics},
     anyhow::Result,
     database::order_events::OrderEventLabel,
     ethcontract::U256,
     ethrpc::block_stream::BlockInfo,
-    futures::{FutureExt, TryFutureExt},
+    futures::{stream::FuturesUnordered, FutureExt, StreamExt, TryFutureExt},
     itertools::Itertools,
     model::solver_competition::{
         CompetitionAuction,
@@ -29,12 +30,17 @@ use {
         Score,
         SolverCompetitionDB,
         SolverSettlement,
+        SolverTimings,
     },
-    primitive_types::H256,
-    rand::seq::SliceRandom,
+    number::ratio::Ratio256,
+    primitive_types::{H160, H256},
+    rand::{rngs::StdRng, SeedableRng},
+    serde::{Deserialize, Serialize},
     shared::token_list::AutoUpdatingTokenList,
     std::{
-        collections::{HashMap, HashSet},
+        borrow::Cow,
+        collections::{HashMap, HashSet, VecDeque},
+        future::Future,
         sync::Arc,
         time::{Duration, Instant},
     },
@@ -52,13 +58,26 @@ pub struct Config {
     pub max_run_loop_delay: Duration,
     pub max_winners_per_auction: usize,
     pub max_solutions_per_solver: usize,
+    /// For how many of the most recent auctions the filtered orders are kept.
+    pub filtered_orders_retention: u64,
+    /// How solutions with identical scores are ordered.
+    pub tie_breaking: competition::TieBreaking,
+    /// How the winners of an auction get selected.
+    pub winner_selection: competition::WinnerSelection,
+    /// For how many of the most recent auctions the proposed solutions are
+    /// kept to evaluate what-if requests against.
+    pub what_if_auctions: usize,
 }
 
 pub struct RunLoop {
     config: Config,
     eth: infra::Ethereum,
     persistence: infra::Persistence,
+    exporter: infra::Exporter,
     drivers: Vec<Arc<infra::Driver>>,
+    bonding_pools: Arc<infra::BondingPools>,
+    pauses: Arc<infra::Pauses>,
+    reputation: Arc<infra::Reputation>,
     solvable_orders_cache: Arc<SolvableOrdersCache>,
     trusted_tokens: AutoUpdatingTokenList,
     in_flight_orders: Arc<Mutex<HashSet<OrderUid>>>,
@@ -66,6 +85,122 @@ pub struct RunLoop {
     /// Maintenance tasks that should run before every runloop to have
     /// the most recent data available.
     maintenance: Arc<Maintenance>,
+    recent_competitions: std::sync::Mutex<VecDeque<RecentCompetition>>,
+    /// Where the inputs of failed iterations get archived, if anywhere.
+    snapshots: Option<snapshot::Archive>,
+}
+
+/// All solutions proposed for an auction before they got ranked.
+struct RecentCompetition {
+    auction: domain::Auction,
+    solutions: Vec<competition::Participant<Unranked>>,
+}
+
+/// The solutions of a competition after applying all filters.
+pub(crate) struct Ranking {
+    /// Solutions that passed all filters from best to worst.
+    ranked: Vec<competition::Participant>,
+    discarded: Vec<(competition::Participant<Unranked>, Discarded)>,
+}
+
+/// Why a solution didn't get ranked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Discarded {
+    /// The driver is suspended because of its bonding pool.
+    Suspended,
+    /// The driver is paused by an operator.
+    Paused,
+    /// The solver is excluded because of its execution quality.
+    PoorExecutionQuality,
+    /// The solver is excluded because it didn't settle enough of the auctions
+    /// it won.
+    Unreliable,
+    /// The driver already proposed the maximum number of solutions.
+    SolutionLimit,
+    /// The solution is too much worse for one of its orders than a worse
+    /// ranked solution.
+    Unfair,
+    /// The solver is not allowed to settle by the authenticator contract.
+    DenyListed,
+}
+
+/// How a solution fares in a competition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Outcome {
+    /// The solution got ranked at the position, 0 being the best.
+    #[serde(rename_all = "camelCase")]
+    Ranked {
+        position: usize,
+        is_winner: bool,
+    },
+    Discarded(Discarded),
+}
+
+/// Everything solutions get ranked by besides the auction and the solutions
+/// themselves. Ranking the same solutions with the same rules always has the
+/// same outcome.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rules {
+    pub max_winners_per_auction: usize,
+    pub max_solutions_per_solver: usize,
+    pub tie_breaking: competition::TieBreaking,
+    /// Snapshots of competitions from before the strategy was configurable
+    /// used the default strategy.
+    #[serde(default)]
+    pub winner_selection: competition::WinnerSelection,
+    pub wrapped_native_token: H160,
+    /// Solvers that are excluded because of their execution quality.
+    pub excluded_solvers: HashSet<H160>,
+    /// Solvers that are excluded because they didn't settle enough of the
+    /// auctions they won.
+    #[serde(default)]
+    pub unreliable_solvers: HashSet<H160>,
+    /// Seeds the random tie breaking.
+    pub seed: u64,
+}
+
+/// Result of ranking a candidate solution against the solutions of a recent
+/// auction.
+pub struct WhatIf {
+    pub candidate: Outcome,
+    /// How the other solutions of the auction fare given the candidate.
+    pub others: Vec<(competition::Participant<Unranked>, Outcome)>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WhatIfError {
+    #[error("auction is unknown or not recent enough")]
+    UnknownAuction,
+    #[error("driver is unknown")]
+    UnknownDriver,
+}
+
+impl Ranking {
+    /// The outcome of every ranked and discarded solution.
+    pub(crate) fn outcomes(self) -> Vec<(competition::Participant<Unranked>, Outcome)> {
+        let ranked = self
+            .ranked
+            .into_iter()
+            .enumerate()
+            .map(|(position, participant)| {
+                let is_winner = participant.is_winner();
+                (
+                    participant.unrank(),
+                    Outcome::Ranked {
+                        position,
+                        is_winner,
+                    },
+                )
+            });
+        let discarded = self
+            .discarded
+            .into_iter()
+            .map(|(participant, reason)| (participant, Outcome::Discarded(reason)));
+        ranked.chain(discarded).collect()
+    }
 }
 
 impl RunLoop {
@@ -74,41 +209,48 @@ impl RunLoop {
         config: Config,
         eth: infra::Ethereum,
         persistence: infra::Persistence,
+        exporter: infra::Exporter,
         drivers: Vec<Arc<infra::Driver>>,
+        bonding_pools: Arc<infra::BondingPools>,
+        pauses: Arc<infra::Pauses>,
+        reputation: Arc<infra::Reputation>,
         solvable_orders_cache: Arc<SolvableOrdersCache>,
         trusted_tokens: AutoUpdatingTokenList,
         liveness: Arc<Liveness>,
         maintenance: Arc<Maintenance>,
+        snapshots: Option<snapshot::Archive>,
     ) -> Self {
         Self {
             config,
             eth,
             persistence,
+            exporter,
             drivers,
+            bonding_pools,
+            pauses,
+            reputation,
             solvable_orders_cache,
             trusted_tokens,
             in_flight_orders: Default::default(),
             liveness,
             maintenance,
+            recent_competitions: Default::default(),
+            snapshots,
         }
     }
 
-    pub async fn run_forever(self) -> ! {
+    pub async fn run_forever(self: Arc<Self>) -> ! {
         Maintenance::spawn_cow_amm_indexing_task(
             self.maintenance.clone(),
             self.eth.current_block().clone(),
         );
         let mut last_auction = None;
         let mut last_block = None;
-        let self_arc = Arc::new(self);
         loop {
-            let auction = self_arc
-                .next_auction(&mut last_auction, &mut last_block)
-                .await;
+            let auction = self.next_auction(&mut last_auction, &mut last_block).await;
             if let Some(auction) = auction {
                 let auction_id = auction.id;
-                self_arc
-                    .single_run(auction)
+                distributed_tracing::with_auction_id(auction_id, self.single_run(auction))
                     .instrument(tracing::info_span!("auction", auction_id))
                     .await;
             };
@@ -156,6 +298,10 @@ impl RunLoop {
             auction_block
         };
 
+        if self.pauses.auctions_paused() {
+            tracing::warn!("auction creation is paused");
+            return None;
+        }
         let auction = self.cut_auction().await?;
 
         // Only run the solvers if the auction or block has changed.
@@ -200,6 +346,12 @@ impl RunLoop {
             }
         };
 
+        self.persistence.store_filtered_orders(
+            id,
+            auction.filtered_orders,
+            self.config.filtered_orders_retention,
+        );
+
         if auction.orders.is_empty() {
             // Updating liveness probe to not report unhealthy due to this optimization
             self.liveness.auction();
@@ -207,13 +359,18 @@ impl RunLoop {
             return None;
         }
 
-        Some(domain::Auction {
+        let auction = domain::Auction {
             id,
             block: auction.block,
             orders: auction.orders,
             prices: auction.prices,
             surplus_capturing_jit_order_owners: auction.surplus_capturing_jit_order_owners,
-        })
+        };
+        self.exporter
+            .publish(dto::Event::AuctionCreated(dto::AuctionCreated::new(
+                &auction,
+            )));
+        Some(auction)
     }
 
     async fn single_run(self: &Arc<Self>, auction: domain::Auction) {
@@ -221,13 +378,14 @@ impl RunLoop {
         tracing::info!(auction_id = ?auction.id, "solving");
 
         let auction = self.remove_in_flight_orders(auction).await;
+        let auction = self.remove_paused_token_orders(auction);
 
         // Mark all auction orders as `Ready` for competition
         self.persistence
             .store_order_events(auction.orders.iter().map(|o| o.uid), OrderEventLabel::Ready);
 
         // Collect valid solutions from all drivers
-        let solutions = self.competition(&auction).await;
+        let (solutions, competition) = self.competition(&auction).await;
         observe::solutions(&solutions);
         if solutions.is_empty() {
             return;
@@ -248,6 +406,7 @@ impl RunLoop {
             .await
         {
             tracing::error!(?err, "failed to post-process competition");
+            self.store_snapshot(auction.id.to_string(), &auction, competition, &err);
             return;
         }
 
@@ -275,9 +434,14 @@ impl RunLoop {
         {
             let (driver, solution) = (winner.driver(), winner.solution());
             tracing::info!(driver = %driver.name, solution = %solution.id(), "winner");
+            if self.pauses.settlements_paused() {
+                tracing::warn!(driver = %driver.name, "settlements are paused, not settling");
+                continue;
+            }
 
             self.start_settlement_execution(
-                auction.id,
+                &auction,
+                &competition,
                 single_run_start,
                 driver,
                 solution,
@@ -292,7 +456,8 @@ impl RunLoop {
     /// only to get access to the locks.
     async fn start_settlement_execution(
         self: &Arc<Self>,
-        auction_id: Id,
+        auction: &domain::Auction,
+        competition: &snapshot::Competition,
         single_run_start: Instant,
         driver: &Arc<infra::Driver>,
         solution: &Solution,
@@ -308,6 +473,12 @@ impl RunLoop {
         let solver = solution.solver();
         let self_ = self.clone();
         let driver_ = driver.clone();
+        let auction_id = auction.id;
+        // Only keep the inputs around if they can be archived.
+        let snapshot_inputs = self
+            .snapshots
+            .is_some()
+            .then(|| (auction.clone(), competition.clone()));
 
         let settle_fut = async move {
             tracing::info!(driver = %driver_.name, solution = %solution_id, "settling");
@@ -335,13 +506,35 @@ impl RunLoop {
                 Err(err) => {
                     Metrics::settle_err(&driver_, submission_start.elapsed(), &err);
                     tracing::warn!(?err, driver = %driver_.name, "settlement failed");
+                    if let Some((auction, competition)) = snapshot_inputs {
+                        self_.store_snapshot(
+                            format!("{auction_id}-{}", driver_.name),
+                            &auction,
+                            competition,
+                            &err,
+                        );
+                    }
                 }
             }
             Metrics::single_run_completed(single_run_start.elapsed());
         }
         .instrument(tracing::Span::current());
 
-        tokio::spawn(settle_fut);
+        ::observe::request_id::spawn_task_with_current_request_id(settle_fut);
+    }
+
+    /// Archives the inputs of a failed iteration so it can be replayed.
+    fn store_snapshot(
+        &self,
+        id: String,
+        auction: &domain::Auction,
+        competition: snapshot::Competition,
+        err: &dyn std::fmt::Debug,
+    ) {
+        if let Some(snapshots) = &self.snapshots {
+            let snapshot = snapshot::Snapshot::new(auction, competition, format!("{err:?}"));
+            snapshots.store(id, snapshot);
+        }
     }
 
     async fn post_processing(
@@ -363,11 +556,8 @@ impl RunLoop {
         };
         let winner = winning_solution.solver().into();
         let winning_score = winning_solution.score().get().0;
-        let reference_score = solutions
-            // todo multiple winners per auction
-            .get(1)
-            .map(|participant| participant.solution().score().get().0)
-            .unwrap_or_default();
+        // todo multiple winners per auction
+        let reference_score = self.config.winner_selection.reference_score(solutions);
         let participants = solutions
             .iter()
             .map(|participant| participant.solution().solver().into())
@@ -434,8 +624,19 @@ impl RunLoop {
                         .map(|(token, price)| (token.0, price.get().into()))
                         .collect(),
                     is_winner: participant.is_winner(),
+                    timings: participant.solution().timings().map(|timings| {
+                        let millis = |duration: Option<Duration>| {
+                            duration.map(|d| d.as_millis().try_into().unwrap_or(u64::MAX))
+                        };
+                        SolverTimings {
+                            liquidity_processing: millis(timings.liquidity_processing),
+                            optimization: millis(timings.optimization),
+                            encoding: millis(timings.encoding),
+                        }
+                    }),
                 })
                 .collect(),
+            winner_selection: Some(self.config.winner_selection.into()),
         };
         let competition = Competition {
             auction_id: auction.id,
@@ -482,6 +683,14 @@ impl RunLoop {
                 .store_fee_policies(auction.id, fee_policies)
                 .map_err(|e| e.context("failed to fee_policies")),
         )?;
+        self.exporter.publish(dto::Event::CompetitionFinalized(
+            dto::CompetitionFinalized::new(
+                auction.id,
+                block_deadline,
+                competition_simulation_block,
+                solutions,
+            ),
+        ));
 
         Metrics::post_processed(start.elapsed());
         Ok(())
@@ -489,65 +698,346 @@ impl RunLoop {
 
     /// Runs the solver competition, making all configured drivers participate.
     /// Returns all fair solutions sorted by their score (best to worst).
-    async fn competition(&self, auction: &domain::Auction) -> Vec<competition::Participant> {
-        let request = solve::Request::new(
-            auction,
-            &self.trusted_tokens.all(),
-            self.config.solve_deadline,
-        );
+    /// Collects and ranks the solutions of all drivers. Also returns what the
+    /// competition depended on for snapshots of the iteration.
+    async fn competition(
+        &self,
+        auction: &domain::Auction,
+    ) -> (Vec<competition::Participant>, snapshot::Competition) {
+        let trusted_tokens = self.trusted_tokens.all();
+        let request = solve::Request::new(auction, &trusted_tokens, self.config.solve_deadline);
         let request = &request;
 
-        let mut solutions = futures::future::join_all(
-            self.drivers
+        // Drivers with restricted capabilities only get the part of the auction
+        // they are able to handle.
+        let requests = self
+            .drivers
+            .iter()
+            .filter(|driver| {
+                let suspended = self.bonding_pools.is_suspended(&driver.name);
+                if suspended {
+                    tracing::debug!(driver = %driver.name, "driver suspended due to bonding pool");
+                }
+                !suspended
+            })
+            .filter(|driver| {
+                let paused = self.pauses.is_solver_paused(&driver.name);
+                if paused {
+                    tracing::warn!(driver = %driver.name, "driver paused");
+                }
+                !paused
+            })
+            .filter_map(|driver| {
+                // Drivers that are allowed to and asked for more time to
+                // optimize get a later deadline while the responses of the
+                // others are collected in the meantime.
+                let time_limit = self.config.solve_deadline + driver.deadline_extension();
+                let capabilities = driver.current_capabilities();
+                if capabilities.is_unrestricted() {
+                    if time_limit == self.config.solve_deadline {
+                        return Some((driver, Cow::Borrowed(request), time_limit));
+                    }
+                    let request = solve::Request::new(auction, &trusted_tokens, time_limit);
+                    return Some((driver, Cow::Owned(request), time_limit));
+                }
+                let auction = capabilities.filter(auction);
+                if auction.orders.is_empty() {
+                    tracing::debug!(driver = %driver.name, "no supported orders in auction");
+                    return None;
+                }
+                let request = solve::Request::new(&auction, &trusted_tokens, time_limit);
+                Some((driver, Cow::Owned(request), time_limit))
+            })
+            .collect::<Vec<_>>();
+
+        // Every driver is time-boxed by its deadline but the competition closes
+        // early as soon as every driver responded or irrecoverably failed.
+        let start = Instant::now();
+        let deadline = start
+            + requests
                 .iter()
-                .map(|driver| self.solve(driver.clone(), request)),
+                .map(|(_, _, time_limit)| *time_limit)
+                .max()
+                .unwrap_or(self.config.solve_deadline);
+        let responses = collect_responses(
+            start.into(),
+            requests.iter().map(|(driver, request, time_limit)| {
+                (
+                    (*driver, *time_limit),
+                    *time_limit,
+                    self.solve((*driver).clone(), request),
+                )
+            }),
         )
-        .await
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
+        .await;
+        let mut solutions = Vec::new();
+        let mut inputs = snapshot::Competition::default();
+        for ((driver, time_limit), response) in responses {
+            match response {
+                Some((participants, response)) => {
+                    solutions.extend(participants);
+                    inputs.drivers.push(response);
+                }
+                None => {
+                    tracing::warn!(driver = %driver.name, "solve error: timeout");
+                    Metrics::solve_err(driver, time_limit, &SolveError::Timeout);
+                }
+            }
+        }
+        Metrics::competition_closed(deadline.saturating_duration_since(Instant::now()));
 
-        // Shuffle so that sorting randomly splits ties.
-        solutions.shuffle(&mut rand::thread_rng());
-        solutions.sort_unstable_by_key(|participant| {
-            std::cmp::Reverse(participant.solution().score().get().0)
+        self.remember_competition(auction, &solutions);
+        let rules = self.ranking_rules(&solutions);
+        let Ranking { ranked, discarded } = Self::rank(solutions, auction, &rules);
+        inputs.rules = Some(rules);
+        inputs.outcomes = ranked
+            .iter()
+            .enumerate()
+            .map(|(position, participant)| {
+                let outcome = Outcome::Ranked {
+                    position,
+                    is_winner: participant.is_winner(),
+                };
+                snapshot::SolutionOutcome::new(participant, outcome)
+            })
+            .chain(discarded.iter().map(|(participant, reason)| {
+                snapshot::SolutionOutcome::new(participant, Outcome::Discarded(*reason))
+            }))
+            .collect();
+        for (participant, reason) in &discarded {
+            match reason {
+                Discarded::PoorExecutionQuality => tracing::warn!(
+                    driver = %participant.driver().name,
+                    solver = ?participant.solution().solver(),
+                    "discarding solution because of poor execution quality"
+                ),
+                Discarded::Unreliable => tracing::warn!(
+                    driver = %participant.driver().name,
+                    solver = ?participant.solution().solver(),
+                    "discarding solution because the solver didn't settle the auctions it won"
+                ),
+                Discarded::Unfair => tracing::warn!(
+                    invalidated = participant.driver().name,
+                    "fairness check invalidated of solution"
+                ),
+                Discarded::Suspended
+                | Discarded::Paused
+                | Discarded::SolutionLimit
+                | Discarded::DenyListed => (),
+            }
+        }
+        (ranked, inputs)
+    }
+
+    /// The rules the solutions currently get ranked by.
+    fn ranking_rules(&self, solutions: &[competition::Participant<Unranked>]) -> Rules {
+        Rules {
+            max_winners_per_auction: self.config.max_winners_per_auction,
+            max_solutions_per_solver: self.config.max_solutions_per_solver,
+            tie_breaking: self.config.tie_breaking,
+            winner_selection: self.config.winner_selection,
+            wrapped_native_token: eth::TokenAddress::from(
+                self.eth.contracts().wrapped_native_token(),
+            )
+            .0,
+            excluded_solvers: solutions
+                .iter()
+                .map(|participant| participant.solution().solver())
+                .filter(|solver| self.reputation.is_excluded(*solver))
+                .map(|solver| solver.0)
+                .collect(),
+            unreliable_solvers: solutions
+                .iter()
+                .map(|participant| participant.solution().solver())
+                .filter(|solver| self.reputation.is_unreliable(*solver))
+                .map(|solver| solver.0)
+                .collect(),
+            seed: rand::random(),
+        }
+    }
+    /// Keeps the proposed solutions of the most recent auctions around to
+    /// evaluate what-if requests against.
+    fn remember_competition(
+        &self,
+        auction: &domain::Auction,
+        solutions: &[competition::Participant<Unranked>],
+    ) {
+        if self.config.what_if_auctions == 0 {
+            return;
+        }
+        let mut recent = self.recent_competitions.lock().unwrap();
+        if recent.len() >= self.config.what_if_auctions {
+            recent.pop_front();
+        }
+        recent.push_back(RecentCompetition {
+            auction: auction.clone(),
+            solutions: solutions.to_vec(),
         });
+    }
+
+    /// Ranks a candidate solution of the driver against the solutions proposed
+    /// for a recent auction, as if the driver had proposed the candidate
+    /// instead of its actual solutions. This doesn't affect the competition in
+    /// any way.
+    ///
+    /// Bonding pools, pauses, the deny list and execution quality are
+    /// evaluated at the time of the request, not at the time of the auction.
+    /// With random tie breaking, solutions with equal scores may rank
+    /// differently on every request.
+    pub async fn what_if(
+        &self,
+        auction_id: domain::auction::Id,
+        driver: &str,
+        solution: Solution,
+    ) -> Result<WhatIf, WhatIfError> {
+        let driver = self
+            .drivers
+            .iter()
+            .find(|candidate| candidate.name == driver)
+            .ok_or(WhatIfError::UnknownDriver)?;
+        let (auction, solutions) = {
+            let recent = self.recent_competitions.lock().unwrap();
+            let competition = recent
+                .iter()
+                .find(|competition| competition.auction.id == auction_id)
+                .ok_or(WhatIfError::UnknownAuction)?;
+            (competition.auction.clone(), competition.solutions.clone())
+        };
+
+        let excluded = if self.bonding_pools.is_suspended(&driver.name) {
+            Some(Discarded::Suspended)
+        } else if self.pauses.is_solver_paused(&driver.name) {
+            Some(Discarded::Paused)
+        } else if !self.is_solver_allowed(driver, solution.solver()).await {
+            Some(Discarded::DenyListed)
+        } else {
+            None
+        };
+        Ok(Self::rank_candidate(
+            solutions,
+            competition::Participant::new(solution, driver.clone()),
+            excluded,
+            &auction,
+            |solutions| self.ranking_rules(solutions),
+        ))
+    }
+
+    /// Ranks the candidate in place of the other solutions of its driver
+    /// unless the candidate is excluded from the competition.
+    fn rank_candidate(
+        mut solutions: Vec<competition::Participant<Unranked>>,
+        candidate: competition::Participant<Unranked>,
+        excluded: Option<Discarded>,
+        auction: &domain::Auction,
+        rules: impl Fn(&[competition::Participant<Unranked>]) -> Rules,
+    ) -> WhatIf {
+        let driver = candidate.driver().name.clone();
+        solutions.retain(|participant| participant.driver().name != driver);
+
+        if let Some(reason) = excluded {
+            let rules = rules(&solutions);
+            let others = Self::rank(solutions, auction, &rules).outcomes();
+            return WhatIf {
+                candidate: Outcome::Discarded(reason),
+                others,
+            };
+        }
+
+        solutions.push(candidate);
+        let rules = rules(&solutions);
+        let (candidates, others): (Vec<_>, Vec<_>) = Self::rank(solutions, auction, &rules)
+            .outcomes()
+            .into_iter()
+            .partition(|(participant, _)| participant.driver().name == driver);
+        let (_, candidate) = candidates
+            .into_iter()
+            .next()
+            .expect("candidate is always part of the outcomes");
+        WhatIf { candidate, others }
+    }
+
+    /// Ranks the solutions from best to worst and selects the winners.
+    /// Solutions that don't pass all filters get discarded.
+    pub(crate) fn rank(
+        solutions: Vec<competition::Participant<Unranked>>,
+        auction: &domain::Auction,
+        rules: &Rules,
+    ) -> Ranking {
+        let mut discarded = Vec::new();
+
+        // Solvers whose settlements consistently deliver worse prices than
+        // promised don't get to win.
+        let (excluded, solutions): (Vec<_>, Vec<_>) =
+            solutions.into_iter().partition(|participant| {
+                rules
+                    .excluded_solvers
+                    .contains(&participant.solution().solver().0)
+            });
+        discarded.extend(
+            excluded
+                .into_iter()
+                .map(|participant| (participant, Discarded::PoorExecutionQuality)),
+        );
+
+        // Solvers that don't settle the auctions they win don't get to win
+        // for a while.
+        let (unreliable, mut solutions): (Vec<_>, Vec<_>) =
+            solutions.into_iter().partition(|participant| {
+                rules
+                    .unreliable_solvers
+                    .contains(&participant.solution().solver().0)
+            });
+        discarded.extend(
+            unreliable
+                .into_iter()
+                .map(|participant| (participant, Discarded::Unreliable)),
+        );
+
+        rules
+            .tie_breaking
+            .sort(&mut solutions, &mut StdRng::seed_from_u64(rules.seed));
+        rules.winner_selection.sort(&mut solutions);
 
         // Limit the number of accepted solutions per solver. Do not alter the ordering
         // of solutions
         let mut counter = HashMap::new();
-        solutions.retain(|participant| {
-            let driver = participant.driver().name.clone();
-            let count = counter.entry(driver).or_insert(0);
-            *count += 1;
-            *count <= self.config.max_solutions_per_solver
-        });
+        let (solutions, over_limit): (Vec<_>, Vec<_>) =
+            solutions.into_iter().partition(|participant| {
+                let driver = participant.driver().name.clone();
+                let count = counter.entry(driver).or_insert(0);
+                *count += 1;
+                *count <= rules.max_solutions_per_solver
+            });
+        discarded.extend(
+            over_limit
+                .into_iter()
+                .map(|participant| (participant, Discarded::SolutionLimit)),
+        );
 
         // Filter out solutions that are not fair
-        let solutions = solutions
-            .iter()
-            .enumerate()
-            .filter_map(|(index, participant)| {
-                if Self::is_solution_fair(participant, &solutions[index..], auction) {
-                    Some(participant)
-                } else {
-                    tracing::warn!(
-                        invalidated = participant.driver().name,
-                        "fairness check invalidated of solution"
-                    );
-                    None
-                }
-            });
+        let fair: Vec<_> = (0..solutions.len())
+            .map(|index| Self::is_solution_fair(&solutions[index], &solutions[index..], auction))
+            .collect();
+        let mut fair_solutions = Vec::new();
+        for (participant, fair) in solutions.into_iter().zip(fair) {
+            match fair {
+                true => fair_solutions.push(participant),
+                false => discarded.push((participant, Discarded::Unfair)),
+            }
+        }
 
         // Winners are selected one by one, starting from the best solution,
-        // until `max_winners_per_auction` are selected. The solution is a winner
-        // if it swaps tokens that are not yet swapped by any previously processed
-        // solution.
-        let wrapped_native_token = self.eth.contracts().wrapped_native_token();
+        // until the strategy's maximum number of winners is selected. The
+        // solution is a winner if it swaps tokens that are not yet swapped by
+        // any previously processed solution.
+        let max_winners = rules
+            .winner_selection
+            .max_winners(rules.max_winners_per_auction);
+        let wrapped_native_token = rules.wrapped_native_token.into();
         let mut already_swapped_tokens = HashSet::new();
         let mut winners = 0;
-        let solutions = solutions
-            .cloned()
+        let ranked = fair_solutions
+            .into_iter()
             .map(|participant| {
                 let swapped_tokens = participant
                     .solution()
@@ -561,8 +1051,8 @@ impl RunLoop {
                     })
                     .collect::<HashSet<_>>();
 
-                let is_winner = swapped_tokens.is_disjoint(&already_swapped_tokens)
-                    && winners < self.config.max_winners_per_auction;
+                let is_winner =
+                    swapped_tokens.is_disjoint(&already_swapped_tokens) && winners < max_winners;
 
                 already_swapped_tokens.extend(swapped_tokens);
                 winners += usize::from(is_winner);
@@ -571,7 +1061,7 @@ impl RunLoop {
             })
             .collect();
 
-        solutions
+        Ranking { ranked, discarded }
     }
 
     /// Returns true if solution is fair to other solutions
@@ -589,20 +1079,14 @@ impl RunLoop {
         // This takes differently partial fills into account.
         let improvement_in_buy = |left: &TradedOrder, right: &TradedOrder| {
             // If `left.sell / left.buy < right.sell / right.buy`, left is "better" as the
-            // trader either sells less or gets more. This can be reformulated as
-            // `right.sell * left.buy > left.sell * right.buy`.
-            let right_sell_left_buy = right.executed_sell.0.full_mul(left.executed_buy.0);
-            let left_sell_right_buy = left.executed_sell.0.full_mul(right.executed_buy.0);
-            let improvement = right_sell_left_buy
-                .checked_sub(left_sell_right_buy)
-                .unwrap_or_default();
-
-            // The difference divided by the original sell amount is the improvement in buy
-            // token. Casting to U256 is safe because the difference is smaller than the
-            // original product, which if re-divided by right.sell must fit in U256.
-            improvement
-                .checked_div(right.executed_sell.0.into())
-                .map(|v| U256::try_from(v).expect("improvement in buy fits in U256"))
+            // trader either sells less or gets more. The improvement in buy token is what
+            // `left` buys on top of what `right`'s price would buy for `left.sell`.
+            let Ok(right_price) = Ratio256::new(right.executed_buy.0, right.executed_sell.0) else {
+                return U256::zero();
+            };
+            Ratio256::from(left.executed_buy.0)
+                .checked_sub(&(Ratio256::from(left.executed_sell.0) * &right_price))
+                .map(|improvement| improvement.saturating_floor())
                 .unwrap_or_default()
         };
 
@@ -661,20 +1145,27 @@ impl RunLoop {
     }
 
     /// Sends a `/solve` request to the driver and manages all error cases and
-    /// records metrics and logs appropriately.
+    /// records metrics and logs appropriately. Also returns the response for
+    /// snapshots of the iteration.
     async fn solve(
         &self,
         driver: Arc<infra::Driver>,
         request: &solve::Request,
-    ) -> Vec<competition::Participant<Unranked>> {
+    ) -> (
+        Vec<competition::Participant<Unranked>>,
+        snapshot::DriverResponse,
+    ) {
         let start = Instant::now();
+        let mut recorded = snapshot::DriverResponse::new(&driver);
         let result = self.try_solve(&driver, request).await;
         let solutions = match result {
-            Ok(solutions) => {
+            Ok((response, solutions)) => {
                 Metrics::solve_ok(&driver, start.elapsed());
+                recorded.response = Some(response);
                 solutions
             }
             Err(err) => {
+                recorded.error = Some(err.to_string());
                 Metrics::solve_err(&driver, start.elapsed(), &err);
                 if matches!(err, SolveError::NoSolutions) {
                     tracing::debug!(driver = %driver.name, "solver found no solution");
@@ -685,7 +1176,7 @@ impl RunLoop {
             }
         };
 
-        solutions
+        let participants: Vec<_> = solutions
             .into_iter()
             .filter_map(|solution| match solution {
                 Ok(solution) => {
@@ -698,50 +1189,62 @@ impl RunLoop {
                     None
                 }
             })
-            .collect()
+            .collect();
+        recorded.accepted = participants
+            .iter()
+            .map(|participant| participant.solution().id())
+            .collect();
+        (participants, recorded)
     }
 
     /// Sends `/solve` request to the driver and forwards errors to the caller.
+    /// Returns the solutions together with the raw response.
     async fn try_solve(
         &self,
         driver: &infra::Driver,
         request: &solve::Request,
-    ) -> Result<Vec<Result<competition::Solution, domain::competition::SolutionError>>, SolveError>
-    {
-        let response = tokio::time::timeout(self.config.solve_deadline, driver.solve(request))
-            .await
-            .map_err(|_| SolveError::Timeout)?
-            .map_err(SolveError::Failure)?;
+    ) -> Result<
+        (
+            solve::Response,
+            Vec<Result<competition::Solution, domain::competition::SolutionError>>,
+        ),
+        SolveError,
+    > {
+        let response = driver.solve(request).await.map_err(SolveError::Failure)?;
         if response.solutions.is_empty() {
             return Err(SolveError::NoSolutions);
         }
-        let solutions = response.into_domain();
+        let solutions = response.clone().into_domain();
 
         // TODO: remove this workaround when implementing #2780
         // Discard any solutions from solvers that got deny listed in the mean time.
         let futures = solutions.into_iter().map(|solution| async {
             let solution = solution?;
-            let solver = solution.solver();
-            let authenticator = self.eth.contracts().authenticator();
-            let is_allowed = authenticator.is_solver(solver.into()).call().await;
-
-            match is_allowed {
-                Ok(true) => Ok(solution),
-                Ok(false) => Err(domain::competition::SolutionError::SolverDenyListed),
-                Err(err) => {
-                    // log warning but discard solution anyway to be on the safe side
-                    tracing::warn!(
-                        driver = driver.name,
-                        ?solver,
-                        ?err,
-                        "failed to check if solver is deny listed"
-                    );
-                    Err(domain::competition::SolutionError::SolverDenyListed)
-                }
+            match self.is_solver_allowed(driver, solution.solver()).await {
+                true => Ok(solution),
+                false => Err(domain::competition::SolutionError::SolverDenyListed),
             }
         });
 
-        Ok(futures::future::join_all(futures).await)
+        Ok((response, futures::future::join_all(futures).await))
+    }
+
+    /// Whether the solver is allowed to settle by the authenticator contract.
+    async fn is_solver_allowed(&self, driver: &infra::Driver, solver: eth::Address) -> bool {
+        let authenticator = self.eth.contracts().authenticator();
+        match authenticator.is_solver(solver.into()).call().await {
+            Ok(is_allowed) => is_allowed,
+            Err(err) => {
+                // log warning but treat the solver as deny listed to be on the safe side
+                tracing::warn!(
+                    driver = driver.name,
+                    ?solver,
+                    ?err,
+                    "failed to check if solver is deny listed"
+                );
+                false
+            }
+        }
     }
 
     /// Execute the solver's solution. Returns Ok when the corresponding
@@ -850,8 +1353,52 @@ impl RunLoop {
             return auction;
         };
 
-        auction.orders.retain(|o| !in_flight.contains(&o.uid));
+        let (orders, removed): (Vec<_>, Vec<_>) = std::mem::take(&mut auction.orders)
+            .into_iter()
+            .partition(|o| !in_flight.contains(&o.uid));
+        auction.orders = orders;
         tracing::debug!(orders = ?in_flight, "filtered out in-flight orders");
+        self.persistence.store_filtered_orders(
+            auction.id,
+            removed
+                .into_iter()
+                .map(|order| domain::auction::FilteredOrder {
+                    uid: order.uid,
+                    reason: "in_flight",
+                    details: None,
+                })
+                .collect(),
+            self.config.filtered_orders_retention,
+        );
+
+        auction
+    }
+
+    /// Removes orders trading tokens that are paused.
+    fn remove_paused_token_orders(&self, mut auction: domain::Auction) -> domain::Auction {
+        let (orders, removed): (Vec<_>, Vec<_>) = std::mem::take(&mut auction.orders)
+            .into_iter()
+            .partition(|o| {
+                !self.pauses.is_token_paused(o.sell.token)
+                    && !self.pauses.is_token_paused(o.buy.token)
+            });
+        auction.orders = orders;
+        if removed.is_empty() {
+            return auction;
+        }
+        tracing::warn!(orders = ?removed.iter().map(|o| o.uid).collect::<Vec<_>>(), "filtered out orders trading paused tokens");
+        self.persistence.store_filtered_orders(
+            auction.id,
+            removed
+                .into_iter()
+                .map(|order| domain::auction::FilteredOrder {
+                    uid: order.uid,
+                    reason: "paused_token",
+                    details: None,
+                })
+                .collect(),
+            self.config.filtered_orders_retention,
+        );
 
         auction
     }
@@ -934,6 +1481,11 @@ struct Metrics {
     /// function is started.
     #[metric(buckets(0, 0.25, 0.5, 0.75, 1, 1.5, 2, 2.5, 3, 4, 5, 6))]
     current_block_delay: prometheus::Histogram,
+
+    /// Time saved by closing the solver competition before the solve deadline
+    /// because all drivers already responded.
+    #[metric(buckets(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 14, 16, 18, 20))]
+    competition_time_saved: prometheus::Histogram,
 }
 
 impl Metrics {
@@ -1030,6 +1582,12 @@ impl Metrics {
         Self::get().single_run_time.observe(elapsed.as_secs_f64());
     }
 
+    fn competition_closed(time_saved: Duration) {
+        Self::get()
+            .competition_time_saved
+            .observe(time_saved.as_secs_f64())
+    }
+
     fn auction_ready(init_block_timestamp: Instant) {
         Self::get()
             .current_block_delay
@@ -1108,3 +1666,319 @@ pub mod observe {
         super::Metrics::matched_unsettled(winner.driver(), non_winning_orders);
     }
 }
+
+/// Awaits the responses of all drivers, each time-boxed by its own time limit
+/// counted from `start`. Responses are returned in the order they arrived and
+/// are `None` for drivers that ran out of time. Returns as soon as every
+/// driver responded or timed out, so drivers with a short time limit don't
+/// have to wait for the ones with an extended deadline.
+async fn collect_responses<D, T>(
+    start: tokio::time::Instant,
+    requests: impl IntoIterator<Item = (D, Duration, impl Future<Output = T>)>,
+) -> Vec<(D, Option<T>)> {
+    requests
+        .into_iter()
+        .map(|(driver, time_limit, response)| async move {
+            let response = tokio::time::timeout_at(start + time_limit, response).await;
+            (driver, response.ok())
+        })
+        .collect::<FuturesUnordered<_>>()
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::auction::order::Side};
+
+    fn participant(
+        id: u64,
+        score: u64,
+        gas: u64,
+        tokens: [u8; 2],
+    ) -> competition::Participant<Unranked> {
+        proposed(&format!("solver{id}"), 0, id, score, gas, tokens)
+    }
+
+    /// A solution of the driver proposed by the solver with the address
+    /// `[solver; 20]`.
+    fn proposed(
+        driver: &str,
+        solver: u8,
+        id: u64,
+        score: u64,
+        gas: u64,
+        tokens: [u8; 2],
+    ) -> competition::Participant<Unranked> {
+        let asset = |token| eth::Asset {
+            token: eth::TokenAddress(H160([token; 20])),
+            amount: eth::TokenAmount(1.into()),
+        };
+        let order = TradedOrder {
+            side: Side::Sell,
+            sell: asset(tokens[0]),
+            buy: asset(tokens[1]),
+            executed_sell: eth::TokenAmount(1.into()),
+            executed_buy: eth::TokenAmount(1.into()),
+        };
+        let driver = infra::Driver::new(
+            "http://localhost".parse().unwrap(),
+            driver.to_string(),
+            None,
+            Default::default(),
+            Default::default(),
+            None,
+            &Default::default(),
+        );
+        competition::Participant::new(
+            Solution::new(
+                id,
+                eth::Address(H160([solver; 20])),
+                competition::Score::try_new(eth::Ether(score.into())).unwrap(),
+                [(OrderUid([id.try_into().unwrap(); 56]), order)].into(),
+                Default::default(),
+                Some(eth::Gas(gas.into())),
+                None,
+            ),
+            Arc::new(driver),
+        )
+    }
+
+    fn auction() -> domain::Auction {
+        domain::Auction {
+            id: 1,
+            block: 2,
+            orders: Default::default(),
+            prices: Default::default(),
+            surplus_capturing_jit_order_owners: Default::default(),
+        }
+    }
+
+    fn rules(winner_selection: competition::WinnerSelection) -> Rules {
+        Rules {
+            max_winners_per_auction: 3,
+            max_solutions_per_solver: 1,
+            tie_breaking: competition::TieBreaking::Gas,
+            winner_selection,
+            wrapped_native_token: H160([0xff; 20]),
+            excluded_solvers: Default::default(),
+            unreliable_solvers: Default::default(),
+            seed: 0,
+        }
+    }
+
+    /// The ids of the solutions and their outcomes.
+    fn ids(outcomes: &[(competition::Participant<Unranked>, Outcome)]) -> Vec<(u64, Outcome)> {
+        outcomes
+            .iter()
+            .map(|(participant, outcome)| (participant.solution().id(), *outcome))
+            .collect()
+    }
+
+    /// Ranks the same solutions with the strategy and returns the ids of the
+    /// ranked solutions, whether they won and the reference score.
+    fn rank(winner_selection: competition::WinnerSelection) -> (Vec<(u64, bool)>, U256) {
+        let solutions = vec![
+            participant(0, 40, 400_000, [1, 2]),
+            participant(1, 30, 100_000, [3, 4]),
+            participant(2, 50, 200_000, [5, 6]),
+        ];
+        let ranking = RunLoop::rank(solutions, &auction(), &rules(winner_selection));
+        let reference_score = winner_selection.reference_score(&ranking.ranked);
+        let ranked = ranking
+            .ranked
+            .iter()
+            .map(|participant| (participant.solution().id(), participant.is_winner()))
+            .collect();
+        (ranked, reference_score)
+    }
+
+    #[test]
+    fn score_selects_single_winner() {
+        let (ranked, reference_score) = rank(competition::WinnerSelection::Score);
+        assert_eq!(ranked, [(2, true), (0, false), (1, false)]);
+        assert_eq!(reference_score, 40.into());
+    }
+
+    #[test]
+    fn surplus_per_gas_selects_most_efficient_winner() {
+        let (ranked, reference_score) = rank(competition::WinnerSelection::SurplusPerGas);
+        assert_eq!(ranked, [(1, true), (2, false), (0, false)]);
+        // The runner-up has a higher score than the winner, the reference is
+        // the score at which the winner would be as efficient as the runner-up.
+        assert_eq!(reference_score, 25.into());
+    }
+
+    #[test]
+    fn fair_combinatorial_selects_disjoint_winners() {
+        let (ranked, reference_score) = rank(competition::WinnerSelection::FairCombinatorial);
+        assert_eq!(ranked, [(2, true), (0, true), (1, true)]);
+        assert_eq!(reference_score, 40.into());
+    }
+
+    #[test]
+    fn rank_discards_filtered_solutions() {
+        let solutions = vec![
+            proposed("a", 1, 0, 90, 100_000, [1, 2]),
+            proposed("b", 2, 1, 80, 100_000, [3, 4]),
+            proposed("c", 3, 2, 50, 100_000, [5, 6]),
+            proposed("c", 3, 3, 40, 100_000, [7, 8]),
+        ];
+        let rules = Rules {
+            excluded_solvers: [H160([1; 20])].into(),
+            unreliable_solvers: [H160([2; 20])].into(),
+            ..rules(competition::WinnerSelection::Score)
+        };
+
+        let outcomes = RunLoop::rank(solutions, &auction(), &rules).outcomes();
+        assert_eq!(
+            ids(&outcomes),
+            [
+                (
+                    2,
+                    Outcome::Ranked {
+                        position: 0,
+                        is_winner: true
+                    }
+                ),
+                (0, Outcome::Discarded(Discarded::PoorExecutionQuality)),
+                (1, Outcome::Discarded(Discarded::Unreliable)),
+                (3, Outcome::Discarded(Discarded::SolutionLimit)),
+            ]
+        );
+    }
+
+    #[test]
+    fn what_if_replaces_solutions_of_driver() {
+        let solutions = vec![
+            proposed("a", 1, 0, 50, 100_000, [1, 2]),
+            proposed("b", 2, 1, 40, 100_000, [3, 4]),
+        ];
+        let candidate = proposed("b", 2, 2, 60, 100_000, [3, 4]);
+
+        let what_if = RunLoop::rank_candidate(solutions, candidate, None, &auction(), |_| {
+            rules(competition::WinnerSelection::Score)
+        });
+        assert_eq!(
+            what_if.candidate,
+            Outcome::Ranked {
+                position: 0,
+                is_winner: true
+            }
+        );
+        assert_eq!(
+            ids(&what_if.others),
+            [(
+                0,
+                Outcome::Ranked {
+                    position: 1,
+                    is_winner: false
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn what_if_discards_excluded_candidate() {
+        let solutions = vec![
+            proposed("a", 1, 0, 50, 100_000, [1, 2]),
+            proposed("b", 2, 1, 40, 100_000, [3, 4]),
+        ];
+        let candidate = proposed("b", 2, 2, 60, 100_000, [3, 4]);
+
+        let what_if = RunLoop::rank_candidate(
+            solutions,
+            candidate,
+            Some(Discarded::DenyListed),
+            &auction(),
+            |_| rules(competition::WinnerSelection::Score),
+        );
+        assert_eq!(what_if.candidate, Outcome::Discarded(Discarded::DenyListed));
+        // The actual solution of the driver doesn't compete either.
+        assert_eq!(
+            ids(&what_if.others),
+            [(
+                0,
+                Outcome::Ranked {
+                    position: 0,
+                    is_winner: true
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn what_if_rules_see_candidate() {
+        let solutions = vec![proposed("a", 1, 0, 50, 100_000, [1, 2])];
+        let candidate = proposed("b", 2, 1, 60, 100_000, [3, 4]);
+
+        // The candidate's solver gets excluded like any other solver would.
+        let what_if =
+            RunLoop::rank_candidate(solutions, candidate, None, &auction(), |solutions| {
+                assert_eq!(solutions.len(), 2);
+                Rules {
+                    excluded_solvers: [H160([2; 20])].into(),
+                    ..rules(competition::WinnerSelection::Score)
+                }
+            });
+        assert_eq!(
+            what_if.candidate,
+            Outcome::Discarded(Discarded::PoorExecutionQuality)
+        );
+    }
+
+    /// A driver responding after `delay` with `time_limit` to do so.
+    fn responding(
+        name: &'static str,
+        time_limit: u64,
+        delay: u64,
+    ) -> (&'static str, Duration, impl Future<Output = &'static str>) {
+        let response = async move {
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            name
+        };
+        (name, Duration::from_secs(time_limit), response)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn collects_responses_while_extended_drivers_solve() {
+        let start = tokio::time::Instant::now();
+        let responses = collect_responses(
+            start,
+            [
+                responding("extended", 5, 4),
+                responding("regular", 2, 1),
+                responding("late", 2, 3),
+            ],
+        )
+        .await;
+
+        assert_eq!(
+            responses,
+            [
+                ("regular", Some("regular")),
+                ("late", None),
+                ("extended", Some("extended")),
+            ]
+        );
+        // The competition closes once the last driver responded instead of
+        // waiting for the longest time limit.
+        assert_eq!(start.elapsed(), Duration::from_secs(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_drivers_exceeding_their_extension() {
+        let start = tokio::time::Instant::now();
+        let responses = collect_responses(
+            start,
+            [responding("extended", 5, 10), responding("regular", 2, 1)],
+        )
+        .await;
+
+        assert_eq!(
+            responses,
+            [("regular", Some("regular")), ("extended", None)]
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+    }
+}
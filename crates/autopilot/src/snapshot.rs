@@ -0,0 +1,319 @@
+//! Snapshots of failed run loop iterations.
+//!
+//! When an iteration fails, everything its competition depended on gets
+//! archived: the auction, the responses of the drivers and the rules the
+//! solutions were ranked by, including the seed of the random tie breaking.
+//! Running the autopilot with `--replay-snapshot` ranks the solutions of an
+//! archived iteration again, which yields the same outcome every time.
+
+use {
+    crate::{
+        domain::{self, competition, eth},
+        infra::{self, persistence::dto, solvers::dto::solve},
+        run_loop::{Outcome, Rules, RunLoop},
+    },
+    anyhow::{Context, Result},
+    number::serialization::HexOrDecimalU256,
+    primitive_types::U256,
+    serde::{Deserialize, Serialize},
+    serde_with::serde_as,
+    std::{sync::Arc, time::Duration},
+    tracing::Instrument,
+    url::Url,
+};
+
+/// Stores snapshots in an object storage and removes them once they
+/// expired.
+#[derive(Clone)]
+pub struct Archive {
+    uploader: s3::Uploader,
+    retention: Duration,
+}
+
+impl Archive {
+    pub async fn new(config: s3::Config, retention: Duration) -> Result<Self> {
+        Ok(Self {
+            uploader: s3::Uploader::new(config).await?,
+            retention,
+        })
+    }
+
+    /// Stores the snapshot under `id` and removes expired snapshots in a
+    /// background task.
+    pub fn store(&self, id: String, snapshot: Snapshot) {
+        let archive = self.clone();
+        tokio::spawn(
+            async move {
+                match archive.uploader.upload(id, snapshot).await {
+                    Ok(key) => tracing::info!(?key, "stored run loop snapshot"),
+                    Err(err) => tracing::warn!(?err, "failed to store run loop snapshot"),
+                }
+                if let Err(err) = archive.remove_expired().await {
+                    tracing::warn!(?err, "failed to remove expired run loop snapshots");
+                }
+            }
+            .instrument(tracing::Span::current()),
+        );
+    }
+
+    /// Loads the snapshot stored under `id`.
+    pub async fn load(&self, id: &str) -> Result<Snapshot> {
+        self.uploader
+            .get(id)
+            .await?
+            .with_context(|| format!("no snapshot with id {id}"))
+    }
+
+    async fn remove_expired(&self) -> Result<()> {
+        let retention = chrono::Duration::from_std(self.retention)?;
+        let expired = self
+            .uploader
+            .list("", ..chrono::Utc::now() - retention)
+            .await?;
+        for id in expired {
+            self.uploader.delete(&id).await?;
+            tracing::debug!(%id, "removed expired run loop snapshot");
+        }
+        Ok(())
+    }
+}
+
+/// The inputs of a failed run loop iteration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// Version of the autopilot that ran the iteration.
+    pub version: String,
+    /// Why the iteration failed.
+    pub error: String,
+    pub auction: dto::auction::Auction,
+    #[serde(flatten)]
+    pub competition: Competition,
+}
+
+impl Snapshot {
+    pub fn new(auction: &domain::Auction, competition: Competition, error: String) -> Self {
+        let raw = domain::RawAuctionData {
+            block: auction.block,
+            orders: auction.orders.clone(),
+            prices: auction.prices.clone(),
+            surplus_capturing_jit_order_owners: auction.surplus_capturing_jit_order_owners.clone(),
+            filtered_orders: Default::default(),
+        };
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            error,
+            auction: dto::auction::Auction {
+                id: auction.id,
+                auction: dto::auction::from_domain(raw),
+            },
+            competition,
+        }
+    }
+}
+
+/// What the competition of an iteration depended on and how it turned out.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Competition {
+    /// The drivers that responded in the order their responses arrived.
+    pub drivers: Vec<DriverResponse>,
+    pub rules: Option<Rules>,
+    pub outcomes: Vec<SolutionOutcome>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverResponse {
+    pub driver: String,
+    pub url: Url,
+    #[serde_as(as = "Option<HexOrDecimalU256>")]
+    pub fairness_threshold: Option<U256>,
+    /// The response to the `/solve` request if the driver proposed solutions.
+    pub response: Option<solve::Response>,
+    pub error: Option<String>,
+    /// Ids of the proposed solutions that were valid and whose solvers were
+    /// allowed to settle at the time.
+    pub accepted: Vec<u64>,
+}
+
+impl DriverResponse {
+    pub fn new(driver: &infra::Driver) -> Self {
+        Self {
+            driver: driver.name.clone(),
+            url: driver.url.clone(),
+            fairness_threshold: driver.fairness_threshold.map(|threshold| threshold.0),
+            response: None,
+            error: None,
+            accepted: Default::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolutionOutcome {
+    pub driver: String,
+    pub solution_id: u64,
+    pub outcome: Outcome,
+}
+
+impl SolutionOutcome {
+    pub fn new<T>(participant: &competition::Participant<T>, outcome: Outcome) -> Self {
+        Self {
+            driver: participant.driver().name.clone(),
+            solution_id: participant.solution().id(),
+            outcome,
+        }
+    }
+}
+
+/// Ranks the solutions of the snapshot again with the rules of the
+/// iteration. Solutions are ordered like in the original iteration.
+pub fn replay(snapshot: &Snapshot) -> Result<Vec<SolutionOutcome>> {
+    let auction = snapshot.auction.clone().try_into_domain()?;
+    let rules = snapshot
+        .competition
+        .rules
+        .as_ref()
+        .context("no solutions were ranked in the iteration")?;
+    let mut solutions = Vec::new();
+    for recorded in &snapshot.competition.drivers {
+        let Some(response) = recorded.response.clone() else {
+            continue;
+        };
+        let driver = Arc::new(infra::Driver::new(
+            recorded.url.clone(),
+            recorded.driver.clone(),
+            recorded.fairness_threshold.map(eth::Ether),
+            Default::default(),
+            Default::default(),
+            None,
+            &Default::default(),
+        ));
+        solutions.extend(
+            response
+                .into_domain()
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|solution| recorded.accepted.contains(&solution.id()))
+                .map(|solution| competition::Participant::new(solution, driver.clone())),
+        );
+    }
+    Ok(RunLoop::rank(solutions, &auction, rules)
+        .outcomes()
+        .iter()
+        .map(|(participant, outcome)| SolutionOutcome::new(participant, *outcome))
+        .collect())
+}
+
+/// Replays the snapshot stored under `id` and logs whether the outcome
+/// matches the one of the original iteration.
+pub async fn replay_from_archive(archive: &Archive, id: &str) -> Result<()> {
+    let snapshot = archive.load(id).await?;
+    tracing::info!(
+        auction = snapshot.auction.id,
+        version = %snapshot.version,
+        error = %snapshot.error,
+        "replaying run loop snapshot"
+    );
+    let outcomes = replay(&snapshot)?;
+    for outcome in &outcomes {
+        tracing::info!(?outcome, "replayed solution");
+    }
+    if outcomes == snapshot.competition.outcomes {
+        tracing::info!("replayed outcome matches the original iteration");
+    } else {
+        tracing::warn!(
+            original = ?snapshot.competition.outcomes,
+            "replayed outcome differs from the original iteration"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::domain::competition::TieBreaking, serde_json::json};
+
+    fn driver(name: &str, solutions: &[(u64, u64)], accepted: Vec<u64>) -> DriverResponse {
+        let solutions: Vec<_> = solutions
+            .iter()
+            .map(|(id, score)| {
+                json!({
+                    "solutionId": id,
+                    "score": score.to_string(),
+                    "submissionAddress": "0x0000000000000000000000000000000000000001",
+                    "orders": {},
+                    "clearingPrices": {},
+                    "gas": null,
+                })
+            })
+            .collect();
+        DriverResponse {
+            driver: name.to_owned(),
+            url: format!("http://{name}.solver.xyz").parse().unwrap(),
+            fairness_threshold: None,
+            response: Some(serde_json::from_value(json!({ "solutions": solutions })).unwrap()),
+            error: None,
+            accepted,
+        }
+    }
+
+    #[test]
+    fn replays_deterministically() {
+        let auction = domain::Auction {
+            id: 1,
+            block: 2,
+            orders: Default::default(),
+            prices: Default::default(),
+            surplus_capturing_jit_order_owners: Default::default(),
+        };
+        let competition = Competition {
+            // The second solution of the first driver got rejected in the
+            // original iteration.
+            drivers: vec![
+                driver("a", &[(1, 10), (2, 20)], vec![1]),
+                driver("b", &[(1, 10)], vec![1]),
+            ],
+            rules: Some(Rules {
+                max_winners_per_auction: 1,
+                max_solutions_per_solver: 1,
+                tie_breaking: TieBreaking::Random,
+                winner_selection: Default::default(),
+                wrapped_native_token: Default::default(),
+                excluded_solvers: Default::default(),
+                unreliable_solvers: Default::default(),
+                seed: 42,
+            }),
+            outcomes: Default::default(),
+        };
+        let snapshot = Snapshot::new(&auction, competition, "error".to_owned());
+
+        let outcomes = replay(&snapshot).unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|outcome| outcome.solution_id == 1));
+        let winners = outcomes
+            .iter()
+            .filter(|outcome| {
+                matches!(
+                    outcome.outcome,
+                    Outcome::Ranked {
+                        is_winner: true,
+                        ..
+                    }
+                )
+            })
+            .count();
+        assert_eq!(winners, 1);
+
+        // Archived snapshots replay the same way.
+        let archived: Snapshot =
+            serde_json::from_value(serde_json::to_value(&snapshot).unwrap()).unwrap();
+        assert_eq!(archived.auction.id, 1);
+        for _ in 0..8 {
+            assert_eq!(replay(&archived).unwrap(), outcomes);
+        }
+    }
+}
@@ -11,7 +11,7 @@ use {
     indexmap::IndexSet,
     itertools::{Either, Itertools},
     model::{
-        order::{Order, OrderClass, OrderUid},
+        order::{InvalidationReason, Order, OrderClass, OrderUid},
         signature::Signature,
         time::now_in_epoch_seconds,
     },
@@ -99,6 +99,8 @@ pub struct SolvableOrdersCache {
     protocol_fees: domain::ProtocolFees,
     cow_amm_registry: cow_amm::Registry,
     native_price_timeout: Duration,
+    /// Met once the first auction got cached.
+    warm: observe::readiness::Condition,
 }
 
 type Balances = HashMap<Query, U256>;
@@ -139,6 +141,7 @@ impl SolvableOrdersCache {
             protocol_fees,
             cow_amm_registry,
             native_price_timeout,
+            warm: observe::readiness::register("solvable_orders"),
         });
         self_
     }
@@ -170,13 +173,19 @@ impl SolvableOrdersCache {
 
         let mut counter = OrderFilterCounter::new(self.metrics, &orders);
         let mut invalid_order_uids = HashSet::new();
+        let mut unsupported_token_orders = Vec::new();
         let mut filtered_order_events = Vec::new();
 
         let (balances, orders, cow_amms) = {
             let queries = orders.iter().map(Query::from_order).collect::<Vec<_>>();
             tokio::join!(
                 self.fetch_balances(queries),
-                self.filter_invalid_orders(orders, &mut counter, &mut invalid_order_uids,),
+                self.filter_invalid_orders(
+                    orders,
+                    &mut counter,
+                    &mut invalid_order_uids,
+                    &mut unsupported_token_orders,
+                ),
                 self.timed_future("cow_amm_registry", self.cow_amm_registry.amms()),
             )
         };
@@ -224,13 +233,26 @@ impl SolvableOrdersCache {
         }
 
         let removed = counter.checkpoint("missing_price", &orders);
+        for uid in &removed {
+            let Some(order) = db_solvable_orders.orders.get(&domain::OrderUid(uid.0)) else {
+                continue;
+            };
+            let missing = [order.data.sell_token, order.data.buy_token]
+                .into_iter()
+                .filter(|token| !prices.contains_key(token))
+                .map(|token| format!("{token:?}"))
+                .join(", ");
+            if !missing.is_empty() {
+                counter.add_details(uid, format!("missing native prices for {missing}"));
+            }
+        }
         filtered_order_events.extend(removed);
 
         let orders = filter_mispriced_limit_orders(orders, &prices, &self.limit_order_price_factor);
         let removed = counter.checkpoint("out_of_market", &orders);
         filtered_order_events.extend(removed);
 
-        let removed = counter.record(&orders);
+        let (removed, filtered_orders) = counter.record(&orders);
         filtered_order_events.extend(removed);
 
         // spawning a background task since `order_events` table insert operation takes
@@ -245,6 +267,13 @@ impl SolvableOrdersCache {
                 .map(|id| domain::OrderUid(id.0)),
             OrderEventLabel::Filtered,
         );
+        self.persistence.store_order_events(
+            unsupported_token_orders
+                .iter()
+                .map(|(id, _)| domain::OrderUid(id.0)),
+            OrderEventLabel::Unsupported,
+        );
+        self.update_unsupported_token_orders(&db_solvable_orders, unsupported_token_orders);
 
         let surplus_capturing_jit_order_owners = cow_amms
             .iter()
@@ -285,12 +314,14 @@ impl SolvableOrdersCache {
                 })
                 .collect::<Result<_, _>>()?,
             surplus_capturing_jit_order_owners,
+            filtered_orders,
         };
 
         *self.cache.lock().await = Some(Inner {
             auction,
             solvable_orders: db_solvable_orders,
         });
+        self.warm.ready();
 
         tracing::debug!(%block, "updated current auction cache");
         self.metrics
@@ -366,8 +397,9 @@ impl SolvableOrdersCache {
         mut orders: Vec<Order>,
         counter: &mut OrderFilterCounter,
         invalid_order_uids: &mut HashSet<OrderUid>,
+        unsupported_token_orders: &mut Vec<(OrderUid, H160)>,
     ) -> Vec<Order> {
-        let (banned_user_orders, invalid_signature_orders, unsupported_token_orders) = tokio::join!(
+        let (banned_user_orders, invalid_signature_orders, unsupported_tokens) = tokio::join!(
             self.timed_future(
                 "banned_user_filtering",
                 find_banned_user_orders(&orders, &self.banned_users)
@@ -384,15 +416,59 @@ impl SolvableOrdersCache {
 
         counter.checkpoint_by_invalid_orders("banned_user", &banned_user_orders);
         counter.checkpoint_by_invalid_orders("invalid_signature", &invalid_signature_orders);
-        counter.checkpoint_by_invalid_orders("unsupported_token", &unsupported_token_orders);
+        let unsupported_token_uids: Vec<_> =
+            unsupported_tokens.iter().map(|(uid, _)| *uid).collect();
+        counter.checkpoint_by_invalid_orders("unsupported_token", &unsupported_token_uids);
+        for (uid, token) in &unsupported_tokens {
+            counter.add_details(uid, format!("{token:?}"));
+        }
         invalid_order_uids.extend(banned_user_orders);
         invalid_order_uids.extend(invalid_signature_orders);
-        invalid_order_uids.extend(unsupported_token_orders);
 
-        orders.retain(|order| !invalid_order_uids.contains(&order.metadata.uid));
+        unsupported_token_orders.extend(unsupported_tokens);
+        let unsupported_token_uids: HashSet<_> = unsupported_token_uids.into_iter().collect();
+
+        orders.retain(|order| {
+            !invalid_order_uids.contains(&order.metadata.uid)
+                && !unsupported_token_uids.contains(&order.metadata.uid)
+        });
         orders
     }
 
+    /// Marks orders with unsupported tokens so the reason shows up in the
+    /// order API and removes the mark from orders whose tokens became
+    /// supported again.
+    fn update_unsupported_token_orders(
+        &self,
+        solvable_orders: &SolvableOrders,
+        unsupported: Vec<(OrderUid, H160)>,
+    ) {
+        let unsupported_uids: HashSet<_> = unsupported.iter().map(|(uid, _)| *uid).collect();
+        let supported = solvable_orders
+            .orders
+            .values()
+            .filter(|order| {
+                matches!(
+                    order.metadata.invalidation_reason,
+                    Some(InvalidationReason::UnsupportedToken { .. })
+                ) && !unsupported_uids.contains(&order.metadata.uid)
+            })
+            .map(|order| domain::OrderUid(order.metadata.uid.0))
+            .collect();
+        let unsupported = unsupported
+            .into_iter()
+            .filter(|(uid, _)| {
+                solvable_orders
+                    .orders
+                    .get(&domain::OrderUid(uid.0))
+                    .is_some_and(|order| order.metadata.invalidation_reason.is_none())
+            })
+            .map(|(uid, token)| (domain::OrderUid(uid.0), token.into()))
+            .collect();
+        self.persistence
+            .update_unsupported_token_orders(unsupported, supported);
+    }
+
     pub fn track_auction_update(&self, result: &str) {
         self.metrics
             .auction_update
@@ -647,10 +723,12 @@ fn prioritize_missing_prices(mut orders: Vec<Order>) -> IndexSet<H160> {
     high_priority_tokens
 }
 
+/// Finds all orders trading unsupported tokens together with the first
+/// unsupported token of each order.
 async fn find_unsupported_tokens(
     orders: &[Order],
     bad_token: Arc<dyn BadTokenDetecting>,
-) -> Vec<OrderUid> {
+) -> Vec<(OrderUid, H160)> {
     let bad_tokens = join_all(
         orders
             .iter()
@@ -686,8 +764,8 @@ async fn find_unsupported_tokens(
                 .token_pair()
                 .into_iter()
                 .flatten()
-                .any(|token| bad_tokens.contains(&token))
-                .then_some(order.metadata.uid)
+                .find(|token| bad_tokens.contains(token))
+                .map(|token| (order.metadata.uid, token))
         })
         .collect()
 }
@@ -740,6 +818,8 @@ struct OrderFilterCounter {
     orders: HashMap<OrderUid, OrderClass>,
     /// Running tally for counts of filtered orders.
     counts: HashMap<Reason, usize>,
+    /// All filtered orders with the reason they got filtered for.
+    filtered: Vec<domain::auction::FilteredOrder>,
 }
 
 type Reason = &'static str;
@@ -767,6 +847,7 @@ impl OrderFilterCounter {
                 .map(|order| (order.metadata.uid, order.metadata.class))
                 .collect(),
             counts: HashMap::new(),
+            filtered: Vec::new(),
         }
     }
 
@@ -782,6 +863,7 @@ impl OrderFilterCounter {
         *self.counts.entry(reason).or_default() += filtered_orders.len();
         for order_uid in filtered_orders.keys() {
             self.orders.remove(order_uid).unwrap();
+            self.filter(*order_uid, reason);
         }
         if !filtered_orders.is_empty() {
             tracing::debug!(
@@ -803,6 +885,7 @@ impl OrderFilterCounter {
         for order_uid in invalid_orders {
             if self.orders.remove(order_uid).is_some() {
                 counter += 1;
+                self.filter(*order_uid, reason);
             }
         }
         *self.counts.entry(reason).or_default() += counter;
@@ -815,11 +898,31 @@ impl OrderFilterCounter {
         }
     }
 
+    fn filter(&mut self, order_uid: OrderUid, reason: Reason) {
+        self.filtered.push(domain::auction::FilteredOrder {
+            uid: domain::OrderUid(order_uid.0),
+            reason,
+            details: None,
+        });
+    }
+
+    /// Adds details about why an already filtered order got removed.
+    fn add_details(&mut self, order_uid: &OrderUid, details: String) {
+        if let Some(filtered) = self
+            .filtered
+            .iter_mut()
+            .rev()
+            .find(|filtered| filtered.uid.0 == order_uid.0)
+        {
+            filtered.details = Some(details);
+        }
+    }
+
     /// Records the filter counter to metrics.
     /// If there are orders that have been filtered out since the last
     /// checkpoint these orders will get recorded with the readon "other".
-    /// Returns these catch-all orders.
-    fn record(mut self, orders: &[Order]) -> Vec<OrderUid> {
+    /// Returns these catch-all orders together with all filtered orders.
+    fn record(mut self, orders: &[Order]) -> (Vec<OrderUid>, Vec<domain::auction::FilteredOrder>) {
         let removed = self.checkpoint("other", orders);
 
         self.metrics.auction_creations.inc();
@@ -840,7 +943,7 @@ impl OrderFilterCounter {
                 .set(i64::try_from(count).unwrap_or(i64::MAX));
         }
 
-        removed
+        (removed, self.filtered)
     }
 }
 
@@ -1206,7 +1309,10 @@ mod tests {
             .unwrap();
         assert_eq!(
             unsupported_tokens_orders,
-            [orders[0].metadata.uid, orders[2].metadata.uid]
+            [
+                (orders[0].metadata.uid, token0),
+                (orders[2].metadata.uid, token0)
+            ]
         );
     }
 
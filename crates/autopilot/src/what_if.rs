@@ -0,0 +1,219 @@
+//! What-if scoring of candidate solutions.
+//!
+//! Lets solver teams check offline how a solution would have fared against
+//! the current competition rules: it gets ranked against the solutions that
+//! were actually proposed for a recent auction, replacing the ones of its
+//! driver. The response contains the full breakdown of which filter discarded
+//! which solution. Nothing of this affects the actual competition.
+//!
+//! The response reveals the scores of competing solutions, so the API requires
+//! the admin API key.
+
+use {
+    crate::{
+        infra::solvers::dto::solve,
+        run_loop::{self, Discarded, RunLoop, WhatIfError},
+    },
+    number::serialization::HexOrDecimalU256,
+    primitive_types::{H160, U256},
+    serde::{Deserialize, Serialize},
+    serde_with::serde_as,
+    std::{convert::Infallible, net::SocketAddr, sync::Arc},
+    tokio::task::JoinHandle,
+    warp::{http::StatusCode, Filter},
+};
+
+const MAX_REQUEST_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    auction_id: i64,
+    /// Name of the driver whose solutions the candidate replaces.
+    driver: String,
+    solution: solve::Solution,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    #[serde_as(as = "HexOrDecimalU256")]
+    score: U256,
+    outcome: Outcome,
+    /// The other solutions proposed for the auction and how they fare given
+    /// the candidate.
+    competitors: Vec<Competitor>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Competitor {
+    driver: String,
+    solver: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    score: U256,
+    outcome: Outcome,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "status", rename_all = "camelCase")]
+enum Outcome {
+    #[serde(rename_all = "camelCase")]
+    Ranked {
+        position: usize,
+        is_winner: bool,
+    },
+    Discarded {
+        reason: Reason,
+    },
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum Reason {
+    Suspended,
+    Paused,
+    PoorExecutionQuality,
+    Unreliable,
+    SolutionLimit,
+    Unfair,
+    DenyListed,
+}
+
+/// Serves the what-if API. Every request has to carry the admin API key in
+/// the `X-API-Key` header.
+///
+/// - `POST /api/v1/what_if` ranks the solution in the JSON body against the
+///   solutions proposed for the auction
+pub fn serve(run_loop: Arc<RunLoop>, address: SocketAddr, api_key: String) -> JoinHandle<()> {
+    let what_if = warp::path!("api" / "v1" / "what_if")
+        .and(warp::post())
+        .and(warp::header::optional::<String>("x-api-key"))
+        .and(warp::body::content_length_limit(MAX_REQUEST_SIZE))
+        .and(warp::body::json::<Request>())
+        .and_then(move |key: Option<String>, request| {
+            let run_loop = run_loop.clone();
+            let authorized = key.as_deref() == Some(api_key.as_str());
+            async move { Result::<_, Infallible>::Ok(reply(&run_loop, authorized, request).await) }
+        });
+
+    tracing::info!(%address, "serving what-if api");
+    tokio::task::spawn(warp::serve(what_if).bind(address))
+}
+
+async fn reply(run_loop: &RunLoop, authorized: bool, request: Request) -> impl warp::Reply {
+    let result = match authorized {
+        true => handle(run_loop, request).await,
+        false => Err(("Unauthorized", StatusCode::UNAUTHORIZED)),
+    };
+    let (json, status) = match result {
+        Ok(response) => (warp::reply::json(&response), StatusCode::OK),
+        Err((error_type, status)) => (
+            warp::reply::json(&serde_json::json!({ "errorType": error_type })),
+            status,
+        ),
+    };
+    warp::reply::with_status(json, status)
+}
+
+async fn handle(
+    run_loop: &RunLoop,
+    request: Request,
+) -> Result<Response, (&'static str, StatusCode)> {
+    let solution = request
+        .solution
+        .into_domain()
+        .map_err(|_| ("InvalidSolution", StatusCode::BAD_REQUEST))?;
+    let score = solution.score().get().0;
+    let what_if = run_loop
+        .what_if(request.auction_id, &request.driver, solution)
+        .await
+        .map_err(|err| match err {
+            WhatIfError::UnknownAuction => ("AuctionNotFound", StatusCode::NOT_FOUND),
+            WhatIfError::UnknownDriver => ("UnknownDriver", StatusCode::BAD_REQUEST),
+        })?;
+    Ok(Response {
+        score,
+        outcome: what_if.candidate.into(),
+        competitors: what_if
+            .others
+            .into_iter()
+            .map(|(participant, outcome)| Competitor {
+                driver: participant.driver().name.clone(),
+                solver: participant.solution().solver().0,
+                score: participant.solution().score().get().0,
+                outcome: outcome.into(),
+            })
+            .collect(),
+    })
+}
+
+impl From<run_loop::Outcome> for Outcome {
+    fn from(outcome: run_loop::Outcome) -> Self {
+        match outcome {
+            run_loop::Outcome::Ranked {
+                position,
+                is_winner,
+            } => Self::Ranked {
+                position,
+                is_winner,
+            },
+            run_loop::Outcome::Discarded(reason) => Self::Discarded {
+                reason: match reason {
+                    Discarded::Suspended => Reason::Suspended,
+                    Discarded::Paused => Reason::Paused,
+                    Discarded::PoorExecutionQuality => Reason::PoorExecutionQuality,
+                    Discarded::Unreliable => Reason::Unreliable,
+                    Discarded::SolutionLimit => Reason::SolutionLimit,
+                    Discarded::Unfair => Reason::Unfair,
+                    Discarded::DenyListed => Reason::DenyListed,
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn serializes_outcomes() {
+        let response = Response {
+            score: 100.into(),
+            outcome: run_loop::Outcome::Ranked {
+                position: 0,
+                is_winner: true,
+            }
+            .into(),
+            competitors: vec![Competitor {
+                driver: "solver".to_string(),
+                solver: H160([1; 20]),
+                score: 50.into(),
+                outcome: run_loop::Outcome::Discarded(Discarded::Unfair).into(),
+            }],
+        };
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({
+                "score": "100",
+                "outcome": {
+                    "status": "ranked",
+                    "position": 0,
+                    "isWinner": true,
+                },
+                "competitors": [{
+                    "driver": "solver",
+                    "solver": "0x0101010101010101010101010101010101010101",
+                    "score": "50",
+                    "outcome": {
+                        "status": "discarded",
+                        "reason": "unfair",
+                    },
+                }],
+            })
+        );
+    }
+}
@@ -2,29 +2,46 @@ use {
     derive_more::{From, Into},
     ethcontract::{
         jsonrpc::serde::{de, Deserialize, Deserializer},
+        H160,
         U256,
     },
-    std::time::Duration,
+    std::{
+        collections::HashMap,
+        num::NonZeroU64,
+        str::FromStr,
+        sync::{OnceLock, RwLock},
+        time::Duration,
+    },
     thiserror::Error,
 };
 
 /// Represents each available chain
 #[derive(Clone, Copy, Debug, PartialEq)]
-#[repr(u64)]
 pub enum Chain {
-    Mainnet = 1,
-    Goerli = 5,
-    Gnosis = 100,
-    Sepolia = 11155111,
-    ArbitrumOne = 42161,
-    Base = 8453,
-    Hardhat = 31337,
+    Mainnet,
+    Goerli,
+    Gnosis,
+    Sepolia,
+    ArbitrumOne,
+    Base,
+    Hardhat,
+    /// A chain defined at runtime. See [`register`].
+    Custom(&'static ChainConfig),
 }
 
 impl Chain {
     /// Returns the chain's chain ID
     pub fn id(&self) -> u64 {
-        *self as u64
+        match self {
+            Self::Mainnet => 1,
+            Self::Goerli => 5,
+            Self::Gnosis => 100,
+            Self::Sepolia => 11155111,
+            Self::ArbitrumOne => 42161,
+            Self::Base => 8453,
+            Self::Hardhat => 31337,
+            Self::Custom(config) => config.id,
+        }
     }
 
     /// Returns the canonical name of the chain on CoW Protocol.
@@ -39,6 +56,7 @@ impl Chain {
             Self::ArbitrumOne => "Arbitrum One",
             Self::Base => "Base",
             Self::Hardhat => "Hardhat",
+            Self::Custom(config) => &config.name,
         }
     }
 
@@ -52,6 +70,7 @@ impl Chain {
             Self::Hardhat => {
                 panic!("unsupported chain for default amount to estimate native prices with")
             }
+            Self::Custom(config) => config.native_price_estimation_amount.into(),
         }
     }
 
@@ -65,6 +84,40 @@ impl Chain {
             Self::ArbitrumOne => Duration::from_millis(250),
             Self::Base => Duration::from_millis(2_000),
             Self::Hardhat => panic!("unsupported block time for Hardhat chain"),
+            Self::Custom(config) => Duration::from_millis(config.block_time_ms),
+        }
+    }
+
+    /// Returns the deepest reorg, in blocks, that indexing has to be able to
+    /// recover from. Blocks older than that are considered final.
+    pub fn finality_depth(&self) -> u64 {
+        match self {
+            // Ethereum blocks are finalized after two epochs.
+            Self::Mainnet | Self::Goerli | Self::Sepolia => 64,
+            // Gnosis chain epochs have 16 slots.
+            Self::Gnosis => 32,
+            // The sequencers order transactions so reorgs only happen in rare
+            // incidents.
+            Self::ArbitrumOne => 20,
+            Self::Base => 10,
+            Self::Hardhat => DEFAULT_FINALITY_DEPTH,
+            Self::Custom(config) => config
+                .finality_depth
+                .map_or(DEFAULT_FINALITY_DEPTH, NonZeroU64::get),
+        }
+    }
+
+    /// Returns how many blocks behind the head a block has to be to be assumed
+    /// safe from reorgs when its hash can't be verified, e.g. for data served
+    /// by subgraphs. Never exceeds [`Self::finality_depth`].
+    pub fn safe_block_offset(&self) -> u64 {
+        match self {
+            // Corresponds to the `safe` block tag which lags one epoch behind.
+            Self::Mainnet | Self::Goerli | Self::Sepolia => 32,
+            Self::Gnosis => 16,
+            Self::ArbitrumOne | Self::Base | Self::Hardhat | Self::Custom(_) => {
+                self.finality_depth()
+            }
         }
     }
 
@@ -73,26 +126,125 @@ impl Chain {
     pub fn blocks_in(&self, time_in_ms: u64) -> f64 {
         time_in_ms as f64 / self.block_time_in_ms().as_millis() as f64
     }
+
+    /// Returns the definition of the chain if it was registered at runtime.
+    pub fn custom(&self) -> Option<&'static ChainConfig> {
+        match *self {
+            Self::Custom(config) => Some(config),
+            _ => None,
+        }
+    }
 }
 
+/// Finality depth of chains that don't specify one.
+pub const DEFAULT_FINALITY_DEPTH: u64 = 64;
+
+const BUILT_IN: [Chain; 7] = [
+    Chain::Mainnet,
+    Chain::Goerli,
+    Chain::Gnosis,
+    Chain::Sepolia,
+    Chain::ArbitrumOne,
+    Chain::Base,
+    Chain::Hardhat,
+];
+
 impl TryFrom<u64> for Chain {
     type Error = ChainIdNotSupported;
 
     /// Initializes `Network` from a chain ID, returns error if the chain id is
-    /// not supported
+    /// neither built in nor registered
     fn try_from(value: u64) -> Result<Self, Self::Error> {
-        let network = match value {
-            x if x == Self::Mainnet as u64 => Self::Mainnet,
-            x if x == Self::Goerli as u64 => Self::Goerli,
-            x if x == Self::Gnosis as u64 => Self::Gnosis,
-            x if x == Self::Sepolia as u64 => Self::Sepolia,
-            x if x == Self::ArbitrumOne as u64 => Self::ArbitrumOne,
-            x if x == Self::Base as u64 => Self::Base,
-            x if x == Self::Hardhat as u64 => Self::Hardhat,
-            _ => Err(ChainIdNotSupported)?,
+        if let Some(chain) = BUILT_IN.into_iter().find(|chain| chain.id() == value) {
+            return Ok(chain);
+        }
+        custom_chains()
+            .read()
+            .unwrap()
+            .get(&value)
+            .copied()
+            .map(Self::Custom)
+            .ok_or(ChainIdNotSupported)
+    }
+}
+
+/// Definition of a chain that is not built into the services. Can be
+/// deserialized from a config file or parsed from the CLI in the form of
+/// `<ID>|<NAME>|<BLOCK_TIME_MS>|<NATIVE_TOKEN>|<NATIVE_PRICE_ESTIMATION_AMOUNT>`
+/// optionally followed by `|<FINALITY_DEPTH>`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ChainConfig {
+    pub id: u64,
+    pub name: String,
+    pub block_time_ms: u64,
+    /// Address of the wrapped native token.
+    pub native_token: H160,
+    /// Amount in native token atoms to use for native price estimation.
+    pub native_price_estimation_amount: u128,
+    /// Deepest reorg in blocks. Defaults to [`DEFAULT_FINALITY_DEPTH`]. Zero
+    /// is rejected because reorgs can't be ruled out.
+    #[serde(default)]
+    pub finality_depth: Option<NonZeroU64>,
+}
+
+impl FromStr for ChainConfig {
+    type Err = InvalidChainConfig;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split('|').collect();
+        let (parts, finality_depth) = match parts[..] {
+            [ref required @ .., finality_depth] if required.len() == 5 => {
+                (required, Some(finality_depth))
+            }
+            _ => (&parts[..], None),
+        };
+        let [id, name, block_time_ms, native_token, amount] = parts[..] else {
+            return Err(InvalidChainConfig(format!(
+                "expected <ID>|<NAME>|<BLOCK_TIME_MS>|<NATIVE_TOKEN>|<AMOUNT>[|<FINALITY_DEPTH>] \
+                 but got {s:?}"
+            )));
+        };
+        let invalid = |field: &str| InvalidChainConfig(format!("invalid {field} in {s:?}"));
+        let config = Self {
+            id: id.parse().map_err(|_| invalid("chain id"))?,
+            name: name.to_string(),
+            block_time_ms: block_time_ms.parse().map_err(|_| invalid("block time"))?,
+            native_token: native_token.parse().map_err(|_| invalid("native token"))?,
+            native_price_estimation_amount: amount.parse().map_err(|_| invalid("amount"))?,
+            finality_depth: finality_depth
+                .map(|depth| depth.parse().map_err(|_| invalid("finality depth")))
+                .transpose()?,
+        };
+        if config.block_time_ms == 0 {
+            return Err(invalid("block time"));
+        }
+        Ok(config)
+    }
+}
+
+fn custom_chains() -> &'static RwLock<HashMap<u64, &'static ChainConfig>> {
+    static CHAINS: OnceLock<RwLock<HashMap<u64, &'static ChainConfig>>> = OnceLock::new();
+    CHAINS.get_or_init(Default::default)
+}
+
+/// Registers a custom chain so it can be created from its chain ID. Chains
+/// are registered for the lifetime of the process, registering the same
+/// definition again is a no-op.
+pub fn register(config: ChainConfig) -> Result<Chain, RegistrationError> {
+    if BUILT_IN.iter().any(|chain| chain.id() == config.id) {
+        return Err(RegistrationError::BuiltIn(config.id));
+    }
+    let mut chains = custom_chains().write().unwrap();
+    if let Some(existing) = chains.get(&config.id) {
+        return match **existing == config {
+            true => Ok(Chain::Custom(*existing)),
+            false => Err(RegistrationError::Conflicting(config.id)),
         };
-        Ok(network)
     }
+    let config: &'static ChainConfig = Box::leak(Box::new(config));
+    chains.insert(config.id, config);
+    Ok(Chain::Custom(config))
 }
 
 impl TryFrom<U256> for Chain {
@@ -149,6 +301,18 @@ impl<'de> Deserialize<'de> for Chain {
 #[error("chain id not supported")]
 pub struct ChainIdNotSupported;
 
+#[derive(Error, Debug)]
+#[error("invalid chain config: {0}")]
+pub struct InvalidChainConfig(String);
+
+#[derive(Error, Debug)]
+pub enum RegistrationError {
+    #[error("chain id {0} belongs to a built-in chain")]
+    BuiltIn(u64),
+    #[error("chain id {0} is already registered with a different definition")]
+    Conflicting(u64),
+}
+
 #[cfg(test)]
 mod test {
     use {super::*, ethcontract::jsonrpc::serde_json};
@@ -165,6 +329,13 @@ mod test {
         assert_eq!(Chain::ArbitrumOne.blocks_in(TARGET_AGE).round(), 86400.0);
     }
 
+    #[test]
+    fn test_safe_block_offset_within_finality_depth() {
+        for chain in BUILT_IN {
+            assert!(chain.safe_block_offset() <= chain.finality_depth());
+        }
+    }
+
     #[test]
     fn test_deserialize_from_u64() {
         // Test valid u64 deserialization
@@ -186,6 +357,69 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_custom_chain() {
+        let config: ChainConfig =
+            "43114|Avalanche|2000|0xb31f66aa3c1e785363f0875a1b74e27b85fd66c7|100000000000000000"
+                .parse()
+                .unwrap();
+        assert!(Chain::try_from(43114).is_err());
+
+        let chain = register(config.clone()).unwrap();
+        assert_eq!(Chain::try_from(43114).unwrap(), chain);
+        assert_eq!(chain.id(), 43114);
+        assert_eq!(chain.name(), "Avalanche");
+        assert_eq!(chain.blocks_in(6_000).round(), 3.0);
+        assert_eq!(
+            chain.default_amount_to_estimate_native_prices_with(),
+            U256::from(10u128.pow(17))
+        );
+
+        // Registering the same definition again is fine, changing it is not.
+        assert_eq!(register(config.clone()).unwrap(), chain);
+        assert!(register(ChainConfig {
+            block_time_ms: 1_000,
+            ..config.clone()
+        })
+        .is_err());
+        // Built-in chains can't be overridden.
+        assert!(register(ChainConfig { id: 1, ..config }).is_err());
+
+        assert_eq!(chain.finality_depth(), DEFAULT_FINALITY_DEPTH);
+        assert_eq!(chain.safe_block_offset(), DEFAULT_FINALITY_DEPTH);
+
+        let config: ChainConfig =
+            "43115|Fuji|2000|0xd00ae08403b9bbb9124bb305c09058e32c39a48c|100000000000000000|12"
+                .parse()
+                .unwrap();
+        assert_eq!(config.finality_depth, NonZeroU64::new(12));
+        assert!(
+            "43115|Fuji|2000|0xd00ae08403b9bbb9124bb305c09058e32c39a48c|100000000000000000|0"
+                .parse::<ChainConfig>()
+                .is_err()
+        );
+
+        let config = |finality_depth: u64| {
+            serde_json::from_value::<ChainConfig>(serde_json::json!({
+                "id": 43115,
+                "name": "Fuji",
+                "block-time-ms": 2000,
+                "native-token": "0xd00ae08403b9bbb9124bb305c09058e32c39a48c",
+                "native-price-estimation-amount": 100000000000000000u64,
+                "finality-depth": finality_depth,
+            }))
+        };
+        assert_eq!(config(12).unwrap().finality_depth, NonZeroU64::new(12));
+        assert!(config(0).is_err());
+
+        assert!("43114|Avalanche|2000".parse::<ChainConfig>().is_err());
+        assert!(
+            "43114|Avalanche|0|0xb31f66aa3c1e785363f0875a1b74e27b85fd66c7|1"
+                .parse::<ChainConfig>()
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_deserialize_from_str() {
         // Test valid string deserialization
@@ -634,6 +634,7 @@ fn main() {
     });
     generate_contract("BalancerV2LiquidityBootstrappingPool");
     generate_contract("BalancerV2ComposableStablePool");
+    generate_contract("BalancerV2RateProvider");
     generate_contract_with_config("BaoswapRouter", |builder| {
         builder.add_network_str(GNOSIS, "0x6093AeBAC87d62b1A5a4cEec91204e35020E38bE")
     });
@@ -61,6 +61,7 @@ include_contracts! {
     BalancerV2LiquidityBootstrappingPool;
     BalancerV2LiquidityBootstrappingPoolFactory;
     BalancerV2NoProtocolFeeLiquidityBootstrappingPoolFactory;
+    BalancerV2RateProvider;
     BalancerV2StablePool;
     BalancerV2StablePoolFactoryV2;
     BalancerV2Vault;
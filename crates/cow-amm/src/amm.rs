@@ -52,6 +52,44 @@ impl Amm {
         self.convert_orders_reponse(order, signature, pre_interactions, post_interactions)
     }
 
+    /// Returns a JIT order for the AMM selling `sell_token` and buying
+    /// `buy_token` at the exchange rate implied by `sell_amount` and
+    /// `buy_amount`. The order (including its signature and interactions) is
+    /// generated by the helper contract, so the actually traded amounts are
+    /// the ones that rebalance the AMM to that exchange rate.
+    pub async fn jit_order(
+        &self,
+        sell_token: Address,
+        buy_token: Address,
+        sell_amount: U256,
+        buy_amount: U256,
+    ) -> Result<TemplateOrder> {
+        anyhow::ensure!(
+            !sell_amount.is_zero() && !buy_amount.is_zero(),
+            "amounts must not be zero"
+        );
+        // Prices need a common denominator so valuing the sell token in units of
+        // `buy_amount` and the buy token in units of `sell_amount` results in
+        // `sell_amount * sell_price == buy_amount * buy_price`.
+        let prices = self
+            .tradeable_tokens
+            .iter()
+            .map(|token| match *token {
+                token if token == sell_token => Ok(buy_amount),
+                token if token == buy_token => Ok(sell_amount),
+                token => Err(anyhow::anyhow!("no price for traded token {token:?}")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let template = self.template_order(prices).await?;
+        anyhow::ensure!(
+            template.order.sell_token == sell_token && template.order.buy_token == buy_token,
+            "AMM does not need to sell {sell_token:?} for {buy_token:?} to reach the requested \
+             price"
+        );
+        Ok(template)
+    }
+
     /// Generates a template order to rebalance the AMM but also verifies that
     /// the signature is actually valid to protect against buggy helper
     /// contracts.
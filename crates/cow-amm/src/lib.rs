@@ -4,4 +4,8 @@ mod factory;
 mod maintainers;
 mod registry;
 
-pub use {amm::Amm, contracts::CowAmmLegacyHelper as Helper, registry::Registry};
+pub use {
+    amm::{Amm, TemplateOrder},
+    contracts::CowAmmLegacyHelper as Helper,
+    registry::Registry,
+};
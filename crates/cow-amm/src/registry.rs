@@ -68,6 +68,14 @@ impl Registry {
         result
     }
 
+    /// Returns the deployed CoW AMM with the given address.
+    pub async fn amm(&self, address: Address) -> Option<Arc<Amm>> {
+        self.amms()
+            .await
+            .into_iter()
+            .find(|amm| *amm.address() == address)
+    }
+
     pub fn spawn_maintenance_task(&self, block_stream: CurrentBlockWatcher) {
         let maintenance = ServiceMaintenance::new(self.maintenance_tasks.clone());
         tokio::task::spawn(maintenance.run_maintenance_on_new_block(block_stream));
@@ -0,0 +1,38 @@
+use std::{env, fs, path::Path};
+
+fn main() {
+    // Embed the migrations in `database/sql` so the schema can be set up
+    // without external tooling (see `database::migrate`).
+    let dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("../../database/sql");
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let mut files: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "sql"))
+        .collect();
+    // Sort by the numeric version, file names would sort `V10` before `V9`.
+    // Invalid names are reported by `database::migrations()`.
+    files.sort_by_key(|path| {
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let version = name
+            .strip_prefix('V')
+            .and_then(|name| name.split_once("__"))
+            .and_then(|(version, _)| version.parse::<i64>().ok());
+        (version, name.to_owned())
+    });
+
+    let entries: String = files
+        .iter()
+        .map(|path| {
+            let path = path.canonicalize().unwrap();
+            format!(
+                "({:?}, include_str!({:?})),\n",
+                path.file_name().unwrap().to_str().unwrap(),
+                path.to_str().unwrap(),
+            )
+        })
+        .collect();
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("migrations.rs");
+    fs::write(out, format!("&[\n{entries}]\n")).unwrap();
+}
@@ -0,0 +1,281 @@
+use {
+    crate::{Address, PgTransaction},
+    bigdecimal::BigDecimal,
+    chrono::NaiveDate,
+    sqlx::{PgConnection, QueryBuilder},
+    std::ops::DerefMut,
+};
+
+/// Key under which the last block whose trades got aggregated is stored in
+/// the `last_indexed_blocks` table.
+pub const LAST_INDEXED_BLOCK_KEY: &str = "app_code_report_rollups";
+
+/// Fee and surplus of a single trade of an order with an `appCode`.
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct TradeRow {
+    pub app_code: String,
+    pub block_number: i64,
+    pub fee_token: Address,
+    pub fee: BigDecimal,
+    pub fee_in_wei: BigDecimal,
+    pub surplus_token: Address,
+    pub surplus: BigDecimal,
+    pub surplus_in_wei: BigDecimal,
+}
+
+/// Returns the fees and surpluses of all trades in blocks `(from, to]` whose
+/// orders specify an `appCode`.
+pub async fn trades(
+    ex: &mut PgConnection,
+    from_block: i64,
+    to_block: i64,
+) -> Result<Vec<TradeRow>, sqlx::Error> {
+    const QUERY: &str = r#"
+WITH trade_components AS (
+    SELECT
+        convert_from(ad.full_app_data, 'UTF8')::jsonb->>'appCode' AS app_code,
+        t.block_number,
+        oe.executed_fee_token AS fee_token,
+        oe.executed_fee AS fee,
+        COALESCE((
+            SELECT price FROM auction_prices ap
+            WHERE ap.token = oe.executed_fee_token AND ap.auction_id = oe.auction_id
+        ), 0) AS fee_token_native_price,
+        CASE o.kind WHEN 'sell' THEN o.buy_token ELSE o.sell_token END AS surplus_token,
+        CASE o.kind
+            -- amounts refer to tokens bought; more is better
+            WHEN 'sell' THEN t.buy_amount - (t.sell_amount - t.fee_amount) * o.buy_amount / o.sell_amount
+            -- amounts refer to tokens sold; less is better
+            WHEN 'buy' THEN t.buy_amount * o.sell_amount / o.buy_amount - (t.sell_amount - t.fee_amount)
+        END AS surplus,
+        COALESCE((
+            SELECT price FROM auction_prices ap
+            WHERE ap.token = (CASE o.kind WHEN 'sell' THEN o.buy_token ELSE o.sell_token END)
+                AND ap.auction_id = oe.auction_id
+        ), 0) AS surplus_token_native_price
+    FROM trades t
+    JOIN orders o ON o.uid = t.order_uid
+    JOIN order_execution oe ON oe.order_uid = t.order_uid AND oe.block_number = t.block_number
+    JOIN app_data ad ON ad.contract_app_data = o.app_data
+    WHERE t.block_number > $1 AND t.block_number <= $2
+)
+SELECT
+    app_code,
+    block_number,
+    fee_token,
+    fee,
+    fee * fee_token_native_price / POWER(10, 18) AS fee_in_wei,
+    surplus_token,
+    surplus,
+    surplus * surplus_token_native_price / POWER(10, 18) AS surplus_in_wei
+FROM trade_components
+WHERE app_code IS NOT NULL
+    "#;
+
+    sqlx::query_as(QUERY)
+        .bind(from_block)
+        .bind(to_block)
+        .fetch_all(ex)
+        .await
+}
+
+/// Aggregated fees and surplus of an app code on a single day denominated in
+/// a single token. `trades` counts the trades paying their fee in `token`.
+#[derive(Clone, Debug, Default, PartialEq, sqlx::FromRow)]
+pub struct Rollup {
+    pub app_code: String,
+    pub day: NaiveDate,
+    pub token: Address,
+    pub trades: i64,
+    pub fee: BigDecimal,
+    pub fee_in_wei: BigDecimal,
+    pub surplus: BigDecimal,
+    pub surplus_in_wei: BigDecimal,
+}
+
+/// Adds the given values to the existing rollups.
+pub async fn add(ex: &mut PgTransaction<'_>, rollups: &[Rollup]) -> Result<(), sqlx::Error> {
+    const BATCH_SIZE: usize = 1000;
+    const QUERY: &str = "INSERT INTO app_code_report_rollups (app_code, day, token, trades, fee, \
+                         fee_in_wei, surplus, surplus_in_wei) ";
+
+    for chunk in rollups.chunks(BATCH_SIZE) {
+        let mut query_builder = QueryBuilder::new(QUERY);
+        query_builder.push_values(chunk, |mut builder, rollup| {
+            builder
+                .push_bind(&rollup.app_code)
+                .push_bind(rollup.day)
+                .push_bind(rollup.token)
+                .push_bind(rollup.trades)
+                .push_bind(&rollup.fee)
+                .push_bind(&rollup.fee_in_wei)
+                .push_bind(&rollup.surplus)
+                .push_bind(&rollup.surplus_in_wei);
+        });
+        query_builder.push(
+            r#"
+ON CONFLICT (app_code, day, token) DO UPDATE SET
+    trades = app_code_report_rollups.trades + EXCLUDED.trades,
+    fee = app_code_report_rollups.fee + EXCLUDED.fee,
+    fee_in_wei = app_code_report_rollups.fee_in_wei + EXCLUDED.fee_in_wei,
+    surplus = app_code_report_rollups.surplus + EXCLUDED.surplus,
+    surplus_in_wei = app_code_report_rollups.surplus_in_wei + EXCLUDED.surplus_in_wei
+"#,
+        );
+        query_builder.build().execute(ex.deref_mut()).await?;
+    }
+
+    Ok(())
+}
+
+/// Totals of an app code over a time range.
+#[derive(Clone, Debug, Default, PartialEq, sqlx::FromRow)]
+pub struct Totals {
+    pub trades: i64,
+    pub fee_in_wei: BigDecimal,
+    pub surplus_in_wei: BigDecimal,
+}
+
+/// Fetches the totals of an app code for all days in `[from, to]`.
+pub async fn totals(
+    ex: &mut PgConnection,
+    app_code: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Totals, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT
+    COALESCE(SUM(trades), 0)::bigint AS trades,
+    COALESCE(SUM(fee_in_wei), 0) AS fee_in_wei,
+    COALESCE(SUM(surplus_in_wei), 0) AS surplus_in_wei
+FROM app_code_report_rollups
+WHERE app_code = $1 AND day >= $2 AND day <= $3
+    "#;
+
+    sqlx::query_as(QUERY)
+        .bind(app_code)
+        .bind(from)
+        .bind(to)
+        .fetch_one(ex)
+        .await
+}
+
+/// Fees and surplus of an app code denominated in a single token.
+#[derive(Clone, Debug, Default, PartialEq, sqlx::FromRow)]
+pub struct TokenTotals {
+    pub token: Address,
+    pub trades: i64,
+    pub fee: BigDecimal,
+    pub fee_in_wei: BigDecimal,
+    pub surplus: BigDecimal,
+    pub surplus_in_wei: BigDecimal,
+}
+
+/// Fetches the per token breakdown of an app code for all days in `[from,
+/// to]` ordered by token address. The page starts after the token `after`,
+/// usually the last token of the previous page, or at the first token if
+/// `None`.
+pub async fn token_totals(
+    ex: &mut PgConnection,
+    app_code: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    after: Option<&Address>,
+    limit: i64,
+) -> Result<Vec<TokenTotals>, sqlx::Error> {
+    // Keyset pagination on the primary key so later pages are as fast to
+    // fetch as the first one.
+    const QUERY: &str = r#"
+SELECT
+    token,
+    SUM(trades)::bigint AS trades,
+    SUM(fee) AS fee,
+    SUM(fee_in_wei) AS fee_in_wei,
+    SUM(surplus) AS surplus,
+    SUM(surplus_in_wei) AS surplus_in_wei
+FROM app_code_report_rollups
+WHERE app_code = $1 AND day >= $2 AND day <= $3 AND ($4::bytea IS NULL OR token > $4)
+GROUP BY token
+ORDER BY token
+LIMIT $5
+    "#;
+
+    sqlx::query_as(QUERY)
+        .bind(app_code)
+        .bind(from)
+        .bind(to)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(ex)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::byte_array::ByteArray, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_app_code_report_rollups_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let rollup = Rollup {
+            app_code: "partner".to_string(),
+            day,
+            token: ByteArray([1; 20]),
+            trades: 1,
+            fee: 10.into(),
+            fee_in_wei: 20.into(),
+            surplus: 30.into(),
+            surplus_in_wei: 40.into(),
+        };
+        add(&mut db, &[rollup.clone()]).await.unwrap();
+        // Adding the same values again accumulates them.
+        add(&mut db, &[rollup.clone()]).await.unwrap();
+        add(
+            &mut db,
+            &[Rollup {
+                token: ByteArray([2; 20]),
+                ..rollup.clone()
+            }],
+        )
+        .await
+        .unwrap();
+
+        let totals = totals(&mut db, "partner", day, day).await.unwrap();
+        assert_eq!(
+            totals,
+            Totals {
+                trades: 3,
+                fee_in_wei: 60.into(),
+                surplus_in_wei: 120.into(),
+            }
+        );
+
+        let tokens = token_totals(&mut db, "partner", day, day, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![TokenTotals {
+                token: ByteArray([1; 20]),
+                trades: 2,
+                fee: 20.into(),
+                fee_in_wei: 40.into(),
+                surplus: 60.into(),
+                surplus_in_wei: 80.into(),
+            }]
+        );
+        let tokens = token_totals(&mut db, "partner", day, day, Some(&ByteArray([1; 20])), 10)
+            .await
+            .unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token, ByteArray([2; 20]));
+
+        let totals = totals(&mut db, "other", day, day).await.unwrap();
+        assert_eq!(totals, Totals::default());
+    }
+}
@@ -18,6 +18,16 @@ LIMIT 1
     sqlx::query_as(QUERY).fetch_optional(ex).await
 }
 
+pub async fn load_most_recent_id(ex: &mut PgConnection) -> Result<Option<AuctionId>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT id
+FROM auctions
+ORDER BY id DESC
+LIMIT 1
+    ;"#;
+    sqlx::query_scalar(QUERY).fetch_optional(ex).await
+}
+
 pub async fn replace_auction(
     ex: &mut PgConnection,
     data: &JsonValue,
@@ -88,6 +98,7 @@ mod tests {
         let (id_, value_) = load_most_recent(&mut db).await.unwrap().unwrap();
         assert_eq!(id, id_);
         assert_eq!(value, value_);
+        assert_eq!(load_most_recent_id(&mut db).await.unwrap(), Some(id));
 
         let value = JsonValue::Number(2.into());
         let id_ = replace_auction(&mut db, &value).await.unwrap();
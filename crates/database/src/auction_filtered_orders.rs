@@ -0,0 +1,111 @@
+//! Records which candidate orders got filtered out of an auction and why.
+
+use {
+    crate::{auction::AuctionId, OrderUid, PgTransaction},
+    sqlx::{PgConnection, QueryBuilder},
+    std::ops::DerefMut,
+};
+
+/// A candidate order that is not part of an auction.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct FilteredOrder {
+    pub auction_id: AuctionId,
+    pub order_uid: OrderUid,
+    /// Name of the filter that removed the order.
+    pub reason: String,
+    /// Additional information about why the filter removed the order.
+    pub details: Option<String>,
+}
+
+pub async fn insert(
+    ex: &mut PgTransaction<'_>,
+    orders: &[FilteredOrder],
+) -> Result<(), sqlx::Error> {
+    const BATCH_SIZE: usize = 5000;
+    const QUERY: &str =
+        "INSERT INTO auction_filtered_orders (auction_id, order_uid, reason, details) ";
+
+    for chunk in orders.chunks(BATCH_SIZE) {
+        let mut query_builder = QueryBuilder::new(QUERY);
+        query_builder.push_values(chunk, |mut builder, order| {
+            builder
+                .push_bind(order.auction_id)
+                .push_bind(order.order_uid)
+                .push_bind(&order.reason)
+                .push_bind(&order.details);
+        });
+        query_builder.push(" ON CONFLICT DO NOTHING");
+        query_builder.build().execute(ex.deref_mut()).await?;
+    }
+
+    Ok(())
+}
+
+/// Deletes the filtered orders of all auctions older than the given one.
+pub async fn delete_before(
+    ex: &mut PgConnection,
+    auction_id: AuctionId,
+) -> Result<u64, sqlx::Error> {
+    const QUERY: &str = "DELETE FROM auction_filtered_orders WHERE auction_id < $1";
+    sqlx::query(QUERY)
+        .bind(auction_id)
+        .execute(ex)
+        .await
+        .map(|result| result.rows_affected())
+}
+
+/// Fetches the filtered orders of an auction, optionally only for a single
+/// order.
+pub async fn fetch(
+    ex: &mut PgConnection,
+    auction_id: AuctionId,
+    order_uid: Option<&OrderUid>,
+) -> Result<Vec<FilteredOrder>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT * FROM auction_filtered_orders
+WHERE auction_id = $1 AND ($2 IS NULL OR order_uid = $2)
+ORDER BY order_uid
+    "#;
+    sqlx::query_as(QUERY)
+        .bind(auction_id)
+        .bind(order_uid)
+        .fetch_all(ex)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::byte_array::ByteArray, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_auction_filtered_orders_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let order = |auction_id, uid, reason: &str| FilteredOrder {
+            auction_id,
+            order_uid: ByteArray([uid; 56]),
+            reason: reason.to_string(),
+            details: None,
+        };
+        let input = vec![
+            order(1, 1, "insufficient_balance"),
+            order(2, 1, "insufficient_balance"),
+            FilteredOrder {
+                details: Some("0x0303030303030303030303030303030303030303".to_string()),
+                ..order(2, 2, "unsupported_token")
+            },
+        ];
+        insert(&mut db, &input).await.unwrap();
+
+        let output = fetch(&mut db, 2, None).await.unwrap();
+        assert_eq!(output, input[1..]);
+        let output = fetch(&mut db, 2, Some(&ByteArray([2; 56]))).await.unwrap();
+        assert_eq!(output, input[2..]);
+
+        assert_eq!(delete_before(&mut db, 2).await.unwrap(), 1);
+        assert!(fetch(&mut db, 1, None).await.unwrap().is_empty());
+    }
+}
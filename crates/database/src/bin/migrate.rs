@@ -0,0 +1,61 @@
+//! Applies the embedded schema migrations to a database, e.g. to set up a
+//! local development database without flyway.
+
+use {
+    anyhow::Context,
+    clap::{Parser, Subcommand},
+    observe::config::Partial,
+    sqlx::PgPool,
+    std::path::PathBuf,
+};
+
+#[derive(Parser)]
+struct Arguments {
+    /// Url of the Postgres database.
+    #[clap(long, env, default_value = "postgresql://")]
+    db_url: String,
+
+    /// Filter for the logs. Defaults to "info".
+    #[clap(long, env)]
+    log_filter: Option<String>,
+
+    /// TOML file with the observability settings shared by all services.
+    /// Arguments and environment variables take precedence over the file.
+    #[clap(long, env)]
+    observe_config: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Applies all migrations that were not applied yet.
+    Run,
+    /// Lists the versions of the migrations that were not applied yet.
+    Pending,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Arguments::parse();
+    let arguments = Partial {
+        log_filter: args.log_filter.clone(),
+        ..Default::default()
+    };
+    let file = Partial::load_optional(args.observe_config.as_deref())
+        .context("invalid observability config file")?;
+    observe::tracing::initialize_with_config(&arguments.or(file).resolve("info"));
+    let pool = PgPool::connect(&args.db_url).await?;
+    match args.command {
+        Command::Run => {
+            database::migrate(&pool).await?;
+            tracing::info!("database schema is up to date");
+        }
+        Command::Pending => {
+            let pending = database::migrations::pending(&mut *pool.acquire().await?).await?;
+            tracing::info!(?pending, "pending migrations");
+        }
+    }
+    Ok(())
+}
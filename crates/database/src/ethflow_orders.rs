@@ -1,5 +1,6 @@
 use {
     crate::{OrderUid, PgTransaction, TransactionHash},
+    bigdecimal::BigDecimal,
     sqlx::{Executor, PgConnection},
 };
 
@@ -114,7 +115,7 @@ pub async fn refundable_orders(
     ex: &mut PgConnection,
     since_valid_to: i64,
     min_validity_duration: i64,
-    min_slippage: f64,
+    min_slippage: &BigDecimal,
 ) -> Result<Vec<EthOrderPlacement>, sqlx::Error> {
     // condition (1.0 - o.buy_amount / GREATEST(oq.buy_amount,1)) >= $3 is added to
     // skip refunding orders that have unrealistic slippage set. Those orders are
@@ -160,7 +161,6 @@ mod tests {
             onchain_invalidations::insert_onchain_invalidation,
             orders::{insert_order, insert_quote, Order, Quote},
         },
-        bigdecimal::BigDecimal,
         chrono::{TimeZone, Utc},
         sqlx::Connection,
     };
@@ -269,6 +269,10 @@ mod tests {
         assert!(order_2.refund_tx.is_none());
     }
 
+    fn slippage(value: &str) -> BigDecimal {
+        value.parse().unwrap()
+    }
+
     #[tokio::test]
     #[ignore]
     async fn postgres_refundable_ethflow_orders() {
@@ -324,16 +328,16 @@ mod tests {
         let order_parts = create_standard_ethflow_order_parts(order_uid_1);
         insert_order_parts_in_db(&mut db, &order_parts).await;
         // all criteria are fulfilled
-        let orders = refundable_orders(&mut db, 5, 1, 0.01).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.01")).await.unwrap();
         assert_eq!(orders, vec![order_parts.eth_order.clone()]);
         // slippage is not fulfilled
-        let orders = refundable_orders(&mut db, 5, 1, 0.53).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.53")).await.unwrap();
         assert_eq!(orders, Vec::new());
         // min_validity is not fulfilled
-        let orders = refundable_orders(&mut db, 1, 1, 0.01).await.unwrap();
+        let orders = refundable_orders(&mut db, 1, 1, &slippage("0.01")).await.unwrap();
         assert_eq!(orders, Vec::new());
         // min_duration is not fulfilled
-        let orders = refundable_orders(&mut db, 5, 3, 0.01).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 3, &slippage("0.01")).await.unwrap();
         assert_eq!(orders, Vec::new());
         // order already settled
         let trade = Trade {
@@ -343,7 +347,7 @@ mod tests {
         insert_trade(&mut db, &EventIndex::default(), &trade)
             .await
             .unwrap();
-        let orders = refundable_orders(&mut db, 5, 1, 0.01).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.01")).await.unwrap();
         assert_eq!(orders, Vec::new());
         let order_uid_2 = ByteArray([2u8; 56]);
         let mut order_parts = create_standard_ethflow_order_parts(order_uid_2);
@@ -353,7 +357,7 @@ mod tests {
         });
         insert_order_parts_in_db(&mut db, &order_parts).await;
         // order was refunded
-        let orders = refundable_orders(&mut db, 5, 1, 0.01).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.01")).await.unwrap();
         assert_eq!(orders, Vec::new());
 
         let order_uid_3 = ByteArray([3u8; 56]);
@@ -361,7 +365,7 @@ mod tests {
         order_parts.order.sell_amount = BigDecimal::from(99u32);
         insert_order_parts_in_db(&mut db, &order_parts).await;
         // sell_amount is not fulfilled
-        let orders = refundable_orders(&mut db, 5, 1, 0.01).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.01")).await.unwrap();
         assert_eq!(orders, Vec::new());
 
         let order_uid_4 = ByteArray([4u8; 56]);
@@ -369,14 +373,14 @@ mod tests {
         order_parts.order.partially_fillable = true;
         insert_order_parts_in_db(&mut db, &order_parts).await;
         // no refundable orders as order is partially fillable
-        let orders = refundable_orders(&mut db, 5, 1, 0.001).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.001")).await.unwrap();
         assert_eq!(orders, Vec::new());
 
         let order_uid_5 = ByteArray([5u8; 56]);
         let order_parts = create_standard_ethflow_order_parts(order_uid_5);
         insert_order_parts_in_db(&mut db, &order_parts).await;
         // the newly created order should be found
-        let orders = refundable_orders(&mut db, 5, 1, 0.001).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.001")).await.unwrap();
         assert_eq!(orders, vec![order_parts.eth_order]);
         insert_onchain_invalidation(
             &mut db,
@@ -388,7 +392,7 @@ mod tests {
         )
         .await
         .unwrap();
-        let orders = refundable_orders(&mut db, 5, 1, 0.001).await.unwrap();
+        let orders = refundable_orders(&mut db, 5, 1, &slippage("0.001")).await.unwrap();
         // but after invaldiation event, it should not longer be found
         assert_eq!(orders, Vec::new());
     }
@@ -458,7 +462,7 @@ mod tests {
         }
 
         let now = std::time::Instant::now();
-        refundable_orders(&mut db, 1, 1, 1.0).await.unwrap();
+        refundable_orders(&mut db, 1, 1, &slippage("1.0")).await.unwrap();
         let elapsed = now.elapsed();
         println!("{elapsed:?}");
         assert!(elapsed < std::time::Duration::from_secs(1));
@@ -1,5 +1,12 @@
 use {
-    crate::{Address, OrderUid, PgTransaction, TransactionHash},
+    crate::{
+        order_events::{insert_order_event, OrderEvent, OrderEventLabel},
+        Address,
+        OrderUid,
+        PgTransaction,
+        TransactionHash,
+    },
+    chrono::Utc,
     sqlx::{types::BigDecimal, Executor, PgConnection},
 };
 
@@ -100,8 +107,23 @@ async fn insert_invalidation(
         .bind(index.block_number)
         .bind(index.log_index)
         .bind(event.order_uid)
-        .execute(ex)
+        .execute(&mut *ex)
         .await?;
+    // Orders that can't be cancelled with an off-chain signature (e.g. EIP-1271
+    // orders of smart contract wallets) get cancelled by calling
+    // `invalidateOrder` on the settlement contract, so record the cancellation
+    // like an off-chain one.
+    insert_order_event(
+        ex,
+        &OrderEvent {
+            label: OrderEventLabel::Cancelled,
+            // Same as for ethflow invalidations, the timestamp of the event's
+            // block would be more accurate but now() is good enough.
+            timestamp: Utc::now(),
+            order_uid: event.order_uid,
+        },
+    )
+    .await?;
     Ok(())
 }
 
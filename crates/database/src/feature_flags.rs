@@ -0,0 +1,110 @@
+//! Runtime feature flags. Flags stored with [`ALL_CHAINS`] apply to every
+//! chain unless a value for the specific chain overrides them.
+
+use {
+    chrono::{DateTime, Utc},
+    sqlx::{types::JsonValue, PgConnection},
+};
+
+/// Chain id of the flag values that apply to all chains.
+pub const ALL_CHAINS: i64 = 0;
+
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub chain_id: i64,
+    pub value: JsonValue,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Sets the value of the flag for the chain.
+pub async fn upsert(ex: &mut PgConnection, flag: &FeatureFlag) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO feature_flags (name, chain_id, value, updated_at)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (name, chain_id) DO UPDATE SET
+    value = EXCLUDED.value,
+    updated_at = EXCLUDED.updated_at
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(&flag.name)
+        .bind(flag.chain_id)
+        .bind(&flag.value)
+        .bind(flag.updated_at)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+/// Removes the value of the flag for the chain. Returns whether a value was
+/// removed.
+pub async fn delete(ex: &mut PgConnection, name: &str, chain_id: i64) -> Result<bool, sqlx::Error> {
+    const QUERY: &str = "DELETE FROM feature_flags WHERE name = $1 AND chain_id = $2;";
+    let result = sqlx::query(QUERY)
+        .bind(name)
+        .bind(chain_id)
+        .execute(ex)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Returns all flag values relevant for the chain, i.e. the values specific to
+/// the chain and the ones for all chains.
+pub async fn load(ex: &mut PgConnection, chain_id: i64) -> Result<Vec<FeatureFlag>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT * FROM feature_flags
+WHERE chain_id = $1 OR chain_id = $2
+ORDER BY name, chain_id
+    ;"#;
+    sqlx::query_as(QUERY)
+        .bind(chain_id)
+        .bind(ALL_CHAINS)
+        .fetch_all(ex)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        chrono::SubsecRound,
+        sqlx::{Connection, PgConnection},
+    };
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_feature_flags_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let now = Utc::now().trunc_subsecs(0);
+        let flag = |name: &str, chain_id: i64, value: bool| FeatureFlag {
+            name: name.to_string(),
+            chain_id,
+            value: JsonValue::Bool(value),
+            updated_at: now,
+        };
+        let default = flag("a", ALL_CHAINS, false);
+        let mainnet = flag("a", 1, true);
+        let gnosis = flag("b", 100, true);
+        for flag in [&default, &mainnet, &gnosis] {
+            upsert(&mut db, flag).await.unwrap();
+        }
+        assert_eq!(
+            load(&mut db, 1).await.unwrap(),
+            vec![default.clone(), mainnet.clone()]
+        );
+
+        let updated = flag("a", 1, false);
+        upsert(&mut db, &updated).await.unwrap();
+        assert_eq!(
+            load(&mut db, 1).await.unwrap(),
+            vec![default.clone(), updated]
+        );
+
+        assert!(delete(&mut db, "a", 1).await.unwrap());
+        assert!(!delete(&mut db, "a", 1).await.unwrap());
+        assert_eq!(load(&mut db, 1).await.unwrap(), vec![default]);
+    }
+}
@@ -0,0 +1,99 @@
+//! Versioned protocol fee configurations of the autopilot.
+
+use {
+    chrono::{DateTime, Utc},
+    sqlx::{types::JsonValue, PgConnection},
+};
+
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct FeePolicyConfiguration {
+    pub version: i64,
+    pub configuration: JsonValue,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Records the configuration as a new version unless it is equal to the latest
+/// one. Returns the version of the configuration if a new one was recorded.
+pub async fn insert_if_changed(
+    ex: &mut PgConnection,
+    configuration: &JsonValue,
+    created_at: DateTime<Utc>,
+) -> Result<Option<i64>, sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO fee_policy_configurations (configuration, created_at)
+SELECT $1, $2
+WHERE NOT EXISTS (
+    SELECT 1 FROM (
+        SELECT configuration FROM fee_policy_configurations
+        ORDER BY version DESC
+        LIMIT 1
+    ) latest
+    WHERE latest.configuration = $1
+)
+RETURNING version
+    ;"#;
+    sqlx::query_scalar(QUERY)
+        .bind(configuration)
+        .bind(created_at)
+        .fetch_optional(ex)
+        .await
+}
+
+/// Returns the currently active configuration.
+pub async fn latest(ex: &mut PgConnection) -> Result<Option<FeePolicyConfiguration>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT * FROM fee_policy_configurations
+ORDER BY version DESC
+LIMIT 1
+    ;"#;
+    sqlx::query_as(QUERY).fetch_optional(ex).await
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        chrono::SubsecRound,
+        serde_json::json,
+        sqlx::{Connection, PgConnection},
+    };
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_fee_policy_configurations_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        assert_eq!(latest(&mut db).await.unwrap(), None);
+
+        let now = Utc::now().trunc_subsecs(0);
+        let first = json!({ "policies": [], "maxPartnerFee": 0.01 });
+        let version = insert_if_changed(&mut db, &first, now)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(insert_if_changed(&mut db, &first, now).await.unwrap(), None);
+
+        let second = json!({ "policies": [], "maxPartnerFee": 0.02 });
+        let next = insert_if_changed(&mut db, &second, now)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(next > version);
+        assert_eq!(
+            latest(&mut db).await.unwrap(),
+            Some(FeePolicyConfiguration {
+                version: next,
+                configuration: second,
+                created_at: now,
+            })
+        );
+
+        // Going back to a previous configuration is a new version as well.
+        assert!(insert_if_changed(&mut db, &first, now)
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
@@ -0,0 +1,195 @@
+//! Typed conditions on the columns orders and trades get filtered by.
+//!
+//! Many queries filter the same columns, e.g. the owner of an order, and used
+//! to repeat the SQL and the placeholder numbering for it. The columns here
+//! carry the type of their values, so that a filter can only be built with a
+//! value that fits the column, and [`Conditions`] appends the conditions to a
+//! [`QueryBuilder`] which numbers and binds their parameters.
+
+use {
+    crate::{orders::OrderClass, Address, OrderUid},
+    futures::{stream::BoxStream, StreamExt, TryStreamExt},
+    sqlx::{postgres::PgRow, Encode, FromRow, PgConnection, Postgres, QueryBuilder, Type},
+    std::{borrow::Borrow, marker::PhantomData},
+};
+
+/// A column whose values have type `T`.
+pub struct Column<T> {
+    /// Alias of the table the column belongs to in the query.
+    table: Option<&'static str>,
+    name: &'static str,
+    _value: PhantomData<fn(T)>,
+}
+
+impl<T> Column<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            table: None,
+            name,
+            _value: PhantomData,
+        }
+    }
+
+    /// The column of the table with the given alias, e.g. `o.owner`.
+    pub const fn of(self, table: &'static str) -> Self {
+        Self {
+            table: Some(table),
+            ..self
+        }
+    }
+}
+
+impl<T> Clone for Column<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Column<T> {}
+
+impl<T> std::fmt::Display for Column<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.table {
+            Some(table) => write!(f, "{table}.{}", self.name),
+            None => f.write_str(self.name),
+        }
+    }
+}
+
+/// Columns of the `orders` and `jit_orders` tables.
+pub mod orders {
+    use super::*;
+
+    pub const UID: Column<OrderUid> = Column::new("uid");
+    pub const OWNER: Column<Address> = Column::new("owner");
+    pub const CLASS: Column<OrderClass> = Column::new("class");
+    pub const VALID_TO: Column<i64> = Column::new("valid_to");
+}
+
+/// Columns of the `ethflow_orders` table.
+pub mod ethflow_orders {
+    use super::*;
+
+    pub const VALID_TO: Column<i64> = Column::new("valid_to");
+}
+
+/// Columns of the `onchain_placed_orders` table.
+pub mod onchain_placed_orders {
+    use super::*;
+
+    pub const SENDER: Column<Address> = Column::new("sender");
+}
+
+/// Conditions on typed columns appended to a query.
+pub trait Conditions<'args> {
+    /// Appends ` AND <column> = <value>`.
+    fn and_eq<T, V>(&mut self, column: Column<T>, value: V) -> &mut Self
+    where
+        V: 'args + Borrow<T> + Send + Encode<'args, Postgres> + Type<Postgres>;
+
+    /// Appends ` AND <column> >= <value>`.
+    fn and_ge<T, V>(&mut self, column: Column<T>, value: V) -> &mut Self
+    where
+        V: 'args + Borrow<T> + Send + Encode<'args, Postgres> + Type<Postgres>;
+
+    /// Appends ` AND (<column> IS NULL OR <column> >= <value>)`, e.g. for a
+    /// column of an outer joined table.
+    fn and_null_or_ge<T, V>(&mut self, column: Column<T>, value: V) -> &mut Self
+    where
+        V: 'args + Borrow<T> + Send + Encode<'args, Postgres> + Type<Postgres>;
+
+    /// Appends ` AND <column> = <value>` if there is a value.
+    fn and_eq_opt<T, V>(&mut self, column: Column<T>, value: Option<V>) -> &mut Self
+    where
+        V: 'args + Borrow<T> + Send + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        match value {
+            Some(value) => self.and_eq(column, value),
+            None => self,
+        }
+    }
+}
+
+impl<'args> Conditions<'args> for QueryBuilder<'args, Postgres> {
+    fn and_eq<T, V>(&mut self, column: Column<T>, value: V) -> &mut Self
+    where
+        V: 'args + Borrow<T> + Send + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        self.push(format_args!(" AND {column} = ")).push_bind(value)
+    }
+
+    fn and_ge<T, V>(&mut self, column: Column<T>, value: V) -> &mut Self
+    where
+        V: 'args + Borrow<T> + Send + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        self.push(format_args!(" AND {column} >= "))
+            .push_bind(value)
+    }
+
+    fn and_null_or_ge<T, V>(&mut self, column: Column<T>, value: V) -> &mut Self
+    where
+        V: 'args + Borrow<T> + Send + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        self.push(format_args!(" AND ({column} IS NULL OR {column} >= "))
+            .push_bind(value)
+            .push(")")
+    }
+}
+
+/// Streams the rows of the built query. Unlike [`QueryBuilder::build_query_as`]
+/// the stream owns the query, so it can be returned like the streams of
+/// queries with static SQL.
+pub fn fetch<'a, T>(
+    ex: &'a mut PgConnection,
+    mut query: QueryBuilder<'a, Postgres>,
+) -> BoxStream<'a, Result<T, sqlx::Error>>
+where
+    T: 'a + Send + Unpin + for<'r> FromRow<'r, PgRow>,
+{
+    async_stream::try_stream! {
+        let mut rows = query.build_query_as::<T>().fetch(ex);
+        while let Some(row) = rows.try_next().await? {
+            yield row;
+        }
+    }
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::byte_array::ByteArray};
+
+    #[test]
+    fn appends_conditions() {
+        let owner = ByteArray([1; 20]);
+        let mut query = QueryBuilder::new("SELECT * FROM orders o WHERE o.valid_to >= ");
+        query
+            .push_bind(0i64)
+            .and_eq(orders::OWNER.of("o"), &owner)
+            .and_eq_opt(orders::CLASS, None::<OrderClass>)
+            .and_eq_opt(orders::CLASS, Some(OrderClass::Limit))
+            .push(" ORDER BY o.creation_timestamp");
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM orders o WHERE o.valid_to >= $1 AND o.owner = $2 AND class = $3 ORDER \
+             BY o.creation_timestamp"
+        );
+    }
+
+    #[test]
+    fn appends_conditions_on_outer_joined_columns() {
+        let mut query = QueryBuilder::new(
+            "SELECT * FROM orders o LEFT JOIN ethflow_orders e ON e.uid = o.uid WHERE true",
+        );
+        query
+            .and_ge(orders::VALID_TO.of("o"), 1i64)
+            .and_null_or_ge(ethflow_orders::VALID_TO.of("e"), 1i64)
+            .push(" LIMIT ")
+            .push_bind(10i64);
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM orders o LEFT JOIN ethflow_orders e ON e.uid = o.uid WHERE true AND \
+             o.valid_to >= $1 AND (e.valid_to IS NULL OR e.valid_to >= $2) LIMIT $3"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Instrumentation of database queries and connection pools.
+//!
+//! Services wrap their queries with [`time_query`] so that every query gets
+//! a duration histogram labeled by the query name and queries exceeding the
+//! configured slow query threshold get logged. Connections should be acquired
+//! with [`acquire`] or [`begin`] to track how long callers wait for the pool.
+
+use {
+    sqlx::{pool::PoolConnection, PgPool, Postgres},
+    std::{
+        sync::{Mutex, OnceLock},
+        time::{Duration, Instant},
+    },
+};
+
+/// Name of the primary database pool in the pool metrics.
+pub const PRIMARY: &str = "primary";
+
+/// Queries running for longer than this are logged unless a different
+/// threshold got configured with [`configure`].
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_secs(1);
+
+static SLOW_QUERY_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+
+/// Name of the service whose queries get timed. Every service has its own
+/// query metric, e.g. `orderbook_database_queries`.
+static SERVICE: OnceLock<String> = OnceLock::new();
+
+/// Query durations by query name for every metrics registry queries got
+/// timed in (see [`observe::metrics::scope`]).
+static QUERIES: Mutex<Vec<(&'static prometheus::Registry, prometheus::HistogramVec)>> =
+    Mutex::new(Vec::new());
+
+/// Configures the service whose queries get timed and the threshold above
+/// which queries get logged as slow.
+///
+/// Should be called once at startup. Later calls are ignored.
+pub fn configure(service: &str, slow_query_threshold: Duration) {
+    let configured = SLOW_QUERY_THRESHOLD.set(slow_query_threshold).is_ok()
+        && SERVICE.set(service.to_string()).is_ok();
+    if !configured {
+        tracing::warn!("database instrumentation was already configured");
+    }
+}
+
+fn slow_query_threshold() -> Duration {
+    *SLOW_QUERY_THRESHOLD.get_or_init(|| DEFAULT_SLOW_QUERY_THRESHOLD)
+}
+
+fn queries() -> prometheus::HistogramVec {
+    let registry = observe::metrics::get_registry();
+    let mut queries = QUERIES.lock().unwrap();
+    if let Some((_, metric)) = queries
+        .iter()
+        .find(|(existing, _)| std::ptr::eq(*existing, registry))
+    {
+        return metric.clone();
+    }
+    let name = match SERVICE.get() {
+        Some(service) => format!("{service}_database_queries"),
+        None => "database_queries".to_string(),
+    };
+    let metric = prometheus::HistogramVec::new(
+        prometheus::HistogramOpts::new(name, "Timing of db queries."),
+        &["type"],
+    )
+    .unwrap();
+    registry.register(Box::new(metric.clone())).unwrap();
+    queries.push((registry, metric.clone()));
+    metric
+}
+
+/// Starts timing the query with the given name. The duration gets recorded
+/// when the returned guard is dropped.
+pub fn time_query(name: &'static str) -> QueryTimer {
+    QueryTimer {
+        name,
+        start: Instant::now(),
+    }
+}
+
+/// Guard measuring the duration of a single query. See [`time_query`].
+#[must_use = "the query duration is recorded when the timer is dropped"]
+pub struct QueryTimer {
+    name: &'static str,
+    start: Instant,
+}
+
+impl QueryTimer {
+    /// Records the duration of the query now instead of when the timer goes
+    /// out of scope.
+    pub fn stop_and_record(self) {}
+}
+
+impl Drop for QueryTimer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        queries()
+            .with_label_values(&[self.name])
+            .observe(elapsed.as_secs_f64());
+        if elapsed > slow_query_threshold() {
+            Metrics::get()
+                .database_slow_queries
+                .with_label_values(&[self.name])
+                .inc();
+            tracing::warn!(query = self.name, ?elapsed, "slow database query");
+        }
+    }
+}
+
+/// Acquires a connection from the primary pool while recording how long it
+/// took and the current pool utilization.
+pub async fn acquire(pool: &PgPool) -> sqlx::Result<PoolConnection<Postgres>> {
+    acquire_named(pool, PRIMARY).await
+}
+
+/// Like [`acquire`] but for a pool with the given name in the metrics.
+pub async fn acquire_named(
+    pool: &PgPool,
+    name: &'static str,
+) -> sqlx::Result<PoolConnection<Postgres>> {
+    let start = Instant::now();
+    let result = pool.acquire().await;
+    observe_pool(pool, name, start.elapsed());
+    result
+}
+
+/// Like [`acquire`] but starts a transaction on the acquired connection.
+pub async fn begin(pool: &PgPool) -> sqlx::Result<sqlx::Transaction<'static, Postgres>> {
+    begin_named(pool, PRIMARY).await
+}
+
+/// Like [`begin`] but for a pool with the given name in the metrics.
+pub async fn begin_named(
+    pool: &PgPool,
+    name: &'static str,
+) -> sqlx::Result<sqlx::Transaction<'static, Postgres>> {
+    let start = Instant::now();
+    let result = pool.begin().await;
+    observe_pool(pool, name, start.elapsed());
+    result
+}
+
+fn observe_pool(pool: &PgPool, name: &str, wait: Duration) {
+    let metrics = Metrics::get();
+    metrics
+        .database_pool_acquire_wait
+        .with_label_values(&[name])
+        .observe(wait.as_secs_f64());
+    update_pool_metrics(pool, name);
+}
+
+/// Updates the gauges tracking the number of idle and in-use connections of
+/// the pool with the given name.
+pub fn update_pool_metrics(pool: &PgPool, name: &str) {
+    let metrics = Metrics::get();
+    let size = i64::from(pool.size());
+    let idle = i64::try_from(pool.num_idle()).unwrap_or(i64::MAX);
+    metrics
+        .database_pool_connections
+        .with_label_values(&[name, "idle"])
+        .set(idle);
+    metrics
+        .database_pool_connections
+        .with_label_values(&[name, "in_use"])
+        .set(size.saturating_sub(idle));
+}
+
+/// Periodically updates the gauges of the primary pool so they stay accurate
+/// even if no connections get acquired for a while.
+pub async fn pool_metrics_task(pool: PgPool, interval: Duration) -> ! {
+    loop {
+        update_pool_metrics(&pool, PRIMARY);
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Number of queries that took longer than the slow query threshold.
+    #[metric(labels("type"))]
+    database_slow_queries: prometheus::IntCounterVec,
+
+    /// Number of connections in the pool by pool and state.
+    #[metric(labels("pool", "state"))]
+    database_pool_connections: prometheus::IntGaugeVec,
+
+    /// Time spent waiting for a connection from the pool by pool.
+    #[metric(labels("pool"), buckets(0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1., 5.))]
+    database_pool_acquire_wait: prometheus::HistogramVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
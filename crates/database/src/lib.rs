@@ -1,28 +1,46 @@
+pub mod app_code_reports;
 pub mod app_data;
 pub mod auction;
+pub mod auction_filtered_orders;
 pub mod auction_orders;
 pub mod auction_participants;
 pub mod auction_prices;
 pub mod byte_array;
 pub mod ethflow_orders;
 pub mod events;
+pub mod feature_flags;
 pub mod fee_policies;
+pub mod fee_policy_configurations;
+pub mod filters;
+pub mod instrumentation;
 pub mod jit_orders;
 pub mod last_indexed_blocks;
+pub mod migrations;
+pub mod notification_preferences;
 pub mod onchain_broadcasted_orders;
 pub mod onchain_invalidations;
+pub mod order_embargoes;
 pub mod order_events;
 pub mod order_execution;
+pub mod order_execution_quality;
 pub mod order_history;
 pub mod orders;
+pub mod partitions;
+pub mod price_estimator_usage;
 pub mod quotes;
+pub mod replica;
+pub mod rpc_usage;
 pub mod settlement_observations;
 pub mod settlement_scores;
 pub mod settlements;
 pub mod solver_competition;
+pub mod solver_exclusions;
 pub mod surplus_capturing_jit_order_owners;
 pub mod trades;
+pub mod unauthorized_settlements;
+pub mod unsupported_token_orders;
 
+pub use migrations::migrate;
 use {
     byte_array::ByteArray,
     sqlx::{Executor, PgPool},
@@ -72,6 +90,19 @@ pub const TABLES: &[&str] = &[
     "auction_participants",
     "app_data",
     "jit_orders",
+    "app_code_report_rollups",
+    "price_estimator_usage",
+    "unsupported_token_orders",
+    "auction_filtered_orders",
+    "unauthorized_settlements",
+    "order_lifecycle_summaries",
+    "order_embargoes",
+    "order_execution_quality",
+    "notification_preferences",
+    "feature_flags",
+    "fee_policy_configurations",
+    "solver_exclusions",
+    "rpc_usage",
 ];
 
 /// The names of potentially big volume tables we use in the db.
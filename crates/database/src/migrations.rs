@@ -0,0 +1,136 @@
+//! Versioned schema migrations embedded from `database/sql`.
+//!
+//! Deployments apply the migrations with flyway. Embedding them allows tests
+//! and local development setups to bootstrap the schema without it. Migrations
+//! applied this way are tracked by sqlx in `_sqlx_migrations`, so databases
+//! already managed by flyway are left alone.
+
+use {
+    futures::future::BoxFuture,
+    sqlx::{
+        error::BoxDynError,
+        migrate::{Migrate, MigrateError, Migration, MigrationSource, MigrationType, Migrator},
+        PgConnection,
+        PgPool,
+    },
+};
+
+/// File names and contents of the migrations, ordered by version.
+static MIGRATIONS: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/migrations.rs"));
+
+/// Parses the embedded migrations, ordered by version.
+pub fn migrations() -> Result<Vec<Migration>, MigrateError> {
+    let mut migrations = MIGRATIONS
+        .iter()
+        .map(|(file, sql)| {
+            let (version, description) = parse_file_name(file)?;
+            Ok(Migration::new(
+                version,
+                description.replace('_', " ").into(),
+                MigrationType::Simple,
+                (*sql).into(),
+                false,
+            ))
+        })
+        .collect::<Result<Vec<_>, MigrateError>>()?;
+    migrations.sort_by_key(|migration| migration.version);
+    Ok(migrations)
+}
+
+/// Parses the version and description of a migration file named after
+/// flyway's `V<VERSION>__<DESCRIPTION>.sql` convention. Only whole number
+/// versions are supported because sqlx tracks versions as integers, so flyway
+/// sub-versions like `V1_1` or `V1.1` are rejected.
+fn parse_file_name(file: &str) -> Result<(i64, &str), MigrateError> {
+    let invalid = |reason: &str| {
+        MigrateError::Source(format!("invalid migration file name {file:?}: {reason}").into())
+    };
+    let (version, description) = file
+        .strip_prefix('V')
+        .and_then(|file| file.strip_suffix(".sql"))
+        .and_then(|file| file.split_once("__"))
+        .ok_or_else(|| invalid("expected V<VERSION>__<DESCRIPTION>.sql"))?;
+    let version = version
+        .parse()
+        .map_err(|_| invalid("the version has to be a whole number"))?;
+    Ok((version, description))
+}
+
+#[derive(Debug)]
+struct Embedded;
+
+impl<'s> MigrationSource<'s> for Embedded {
+    fn resolve(self) -> BoxFuture<'s, Result<Vec<Migration>, BoxDynError>> {
+        Box::pin(async { migrations().map_err(Into::into) })
+    }
+}
+
+/// Whether the schema of the database is managed by flyway.
+async fn managed_by_flyway(ex: &mut PgConnection) -> Result<bool, sqlx::Error> {
+    const QUERY: &str = "SELECT to_regclass('flyway_schema_history') IS NOT NULL";
+    sqlx::query_scalar(QUERY).fetch_one(ex).await
+}
+
+/// Applies all embedded migrations that were not applied yet. Does nothing if
+/// the schema is managed by flyway.
+pub async fn migrate(pool: &PgPool) -> Result<(), MigrateError> {
+    if managed_by_flyway(&mut *pool.acquire().await?).await? {
+        tracing::debug!("database schema is managed by flyway, skipping migrations");
+        return Ok(());
+    }
+    Migrator::new(Embedded).await?.run(pool).await
+}
+
+/// Returns the versions of the embedded migrations that were not applied to
+/// the database yet.
+pub async fn pending(ex: &mut PgConnection) -> Result<Vec<i64>, MigrateError> {
+    ex.ensure_migrations_table().await?;
+    let applied = ex.list_applied_migrations().await?;
+    Ok(migrations()?
+        .into_iter()
+        .map(|migration| migration.version)
+        .filter(|version| !applied.iter().any(|applied| applied.version == *version))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_embedded_migrations() {
+        let migrations = migrations().unwrap();
+        assert_eq!(migrations[0].version, 1);
+        assert_eq!(migrations[0].description, "create orders");
+        // Versions have to be unique and increasing for migrations to get
+        // applied in the right order.
+        assert!(migrations
+            .windows(2)
+            .all(|pair| pair[0].version < pair[1].version));
+    }
+
+    #[test]
+    fn parses_every_migration_file() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../database/sql");
+        let mut files = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            if name.ends_with(".sql") {
+                parse_file_name(&name).unwrap();
+                files += 1;
+            }
+        }
+        assert_eq!(files, migrations().unwrap().len());
+    }
+
+    #[test]
+    fn rejects_sub_versions() {
+        assert_eq!(
+            parse_file_name("V12__create_table.sql").unwrap(),
+            (12, "create_table")
+        );
+        assert!(parse_file_name("V12_1__create_table.sql").is_err());
+        assert!(parse_file_name("V12.1__create_table.sql").is_err());
+        assert!(parse_file_name("create_table.sql").is_err());
+    }
+}
@@ -0,0 +1,404 @@
+//! Notification preferences of accounts and the order activity that gets
+//! reported to them in digests.
+
+use {
+    crate::{Address, OrderUid},
+    bigdecimal::BigDecimal,
+    chrono::{DateTime, Utc},
+    sqlx::PgConnection,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, sqlx::FromRow)]
+pub struct Preferences {
+    pub owner: Address,
+    pub webhook: Option<String>,
+    pub email: Option<String>,
+    pub fills: bool,
+    pub expiries: bool,
+    pub issued_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub digested_until: DateTime<Utc>,
+}
+
+/// Registers the preferences unless the owner already registered preferences
+/// issued at the same time or later. Returns whether the preferences got
+/// stored. Updates keep the `digested_until` of the existing preferences.
+pub async fn upsert(ex: &mut PgConnection, preferences: &Preferences) -> Result<bool, sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO notification_preferences (owner, webhook, email, fills, expiries, issued_at, updated_at, digested_until)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+ON CONFLICT (owner) DO UPDATE SET
+    webhook = EXCLUDED.webhook,
+    email = EXCLUDED.email,
+    fills = EXCLUDED.fills,
+    expiries = EXCLUDED.expiries,
+    issued_at = EXCLUDED.issued_at,
+    updated_at = EXCLUDED.updated_at
+WHERE notification_preferences.issued_at < EXCLUDED.issued_at
+    ;"#;
+    let result = sqlx::query(QUERY)
+        .bind(preferences.owner)
+        .bind(&preferences.webhook)
+        .bind(&preferences.email)
+        .bind(preferences.fills)
+        .bind(preferences.expiries)
+        .bind(preferences.issued_at)
+        .bind(preferences.updated_at)
+        .bind(preferences.digested_until)
+        .execute(ex)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn fetch(
+    ex: &mut PgConnection,
+    owner: &Address,
+) -> Result<Option<Preferences>, sqlx::Error> {
+    const QUERY: &str = "SELECT * FROM notification_preferences WHERE owner = $1;";
+    sqlx::query_as(QUERY).bind(owner).fetch_optional(ex).await
+}
+
+/// Claims up to `limit` preferences which want to be notified and whose last
+/// digest covered the time until `digested_before` at the latest. The ones
+/// that waited the longest get claimed first.
+///
+/// Claimed preferences don't get returned again until `claimed_until` has
+/// passed or they got marked as digested, so concurrent callers never send the
+/// same digest twice.
+pub async fn claim_due(
+    ex: &mut PgConnection,
+    digested_before: DateTime<Utc>,
+    now: DateTime<Utc>,
+    claimed_until: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<Preferences>, sqlx::Error> {
+    const QUERY: &str = r#"
+UPDATE notification_preferences SET claimed_until = $3
+WHERE owner IN (
+    SELECT owner FROM notification_preferences
+    WHERE
+        digested_until <= $1 AND
+        (claimed_until IS NULL OR claimed_until <= $2) AND
+        (webhook IS NOT NULL OR email IS NOT NULL) AND
+        (fills OR expiries)
+    ORDER BY digested_until
+    LIMIT $4
+    FOR UPDATE SKIP LOCKED
+)
+RETURNING *
+    ;"#;
+    sqlx::query_as(QUERY)
+        .bind(digested_before)
+        .bind(now)
+        .bind(claimed_until)
+        .bind(limit)
+        .fetch_all(ex)
+        .await
+}
+
+/// Records that the owner got notified about all activity until `until` and
+/// releases the claim on the preferences.
+pub async fn mark_digested(
+    ex: &mut PgConnection,
+    owner: &Address,
+    until: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    const QUERY: &str = "UPDATE notification_preferences SET digested_until = $2, claimed_until = \
+                         NULL WHERE owner = $1;";
+    sqlx::query(QUERY)
+        .bind(owner)
+        .bind(until)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+/// Opts the owner out of webhook notifications unless the webhook got changed
+/// in the meantime.
+pub async fn remove_webhook(
+    ex: &mut PgConnection,
+    owner: &Address,
+    webhook: &str,
+) -> Result<(), sqlx::Error> {
+    const QUERY: &str =
+        "UPDATE notification_preferences SET webhook = NULL WHERE owner = $1 AND webhook = $2;";
+    sqlx::query(QUERY)
+        .bind(owner)
+        .bind(webhook)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+/// Opts the owner out of email notifications unless the email address got
+/// changed in the meantime.
+pub async fn remove_email(
+    ex: &mut PgConnection,
+    owner: &Address,
+    email: &str,
+) -> Result<(), sqlx::Error> {
+    const QUERY: &str =
+        "UPDATE notification_preferences SET email = NULL WHERE owner = $1 AND email = $2;";
+    sqlx::query(QUERY)
+        .bind(owner)
+        .bind(email)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+/// An order of the owner that got traded.
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct Fill {
+    pub order_uid: OrderUid,
+    pub sell_token: Address,
+    pub buy_token: Address,
+    /// Amounts executed by all trades of the order so far.
+    pub executed_sell_amount: BigDecimal,
+    pub executed_buy_amount: BigDecimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Returns the orders of the owner that got traded in `(from, to]`.
+pub async fn fills(
+    ex: &mut PgConnection,
+    owner: &Address,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Fill>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT
+    o.uid AS order_uid,
+    o.sell_token,
+    o.buy_token,
+    COALESCE((SELECT SUM(t.sell_amount) FROM trades t WHERE t.order_uid = o.uid), 0) AS executed_sell_amount,
+    COALESCE((SELECT SUM(t.buy_amount) FROM trades t WHERE t.order_uid = o.uid), 0) AS executed_buy_amount,
+    oe.timestamp
+FROM orders o
+JOIN order_events oe ON oe.order_uid = o.uid
+WHERE
+    o.owner = $1 AND
+    oe.label = 'traded' AND
+    oe.timestamp > $2 AND
+    oe.timestamp <= $3
+ORDER BY oe.timestamp
+    ;"#;
+    sqlx::query_as(QUERY)
+        .bind(owner)
+        .bind(from)
+        .bind(to)
+        .fetch_all(ex)
+        .await
+}
+
+/// An order of the owner that expired without any fill.
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct Expiry {
+    pub order_uid: OrderUid,
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub valid_to: i64,
+}
+
+/// Returns the orders of the owner that expired in `(from, to]` without
+/// getting traded, cancelled or invalidated.
+pub async fn expiries(
+    ex: &mut PgConnection,
+    owner: &Address,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Expiry>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT o.uid AS order_uid, o.sell_token, o.buy_token, o.valid_to
+FROM orders o
+WHERE
+    o.owner = $1 AND
+    o.valid_to > $2 AND
+    o.valid_to <= $3 AND
+    o.cancellation_timestamp IS NULL AND
+    NOT EXISTS (SELECT 1 FROM trades t WHERE t.order_uid = o.uid) AND
+    NOT EXISTS (SELECT 1 FROM invalidations i WHERE i.order_uid = o.uid)
+ORDER BY o.valid_to
+    ;"#;
+    sqlx::query_as(QUERY)
+        .bind(owner)
+        .bind(from.timestamp())
+        .bind(to.timestamp())
+        .fetch_all(ex)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            byte_array::ByteArray,
+            events::{insert_trade, EventIndex, Trade},
+            order_events::{insert_order_event, OrderEvent, OrderEventLabel},
+            orders,
+        },
+        chrono::{Duration, SubsecRound},
+        sqlx::Connection,
+    };
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_upsert_and_due_preferences() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let now = Utc::now().trunc_subsecs(0);
+        let preferences = Preferences {
+            owner: ByteArray([1; 20]),
+            webhook: Some("https://example.com".to_string()),
+            email: None,
+            fills: true,
+            expiries: true,
+            issued_at: now - Duration::minutes(1),
+            updated_at: now,
+            digested_until: now - Duration::hours(2),
+        };
+        assert!(upsert(&mut db, &preferences).await.unwrap());
+        assert_eq!(
+            fetch(&mut db, &preferences.owner).await.unwrap().unwrap(),
+            preferences
+        );
+
+        // Preferences issued earlier don't replace newer ones.
+        let outdated = Preferences {
+            webhook: None,
+            issued_at: now - Duration::minutes(2),
+            ..preferences.clone()
+        };
+        assert!(!upsert(&mut db, &outdated).await.unwrap());
+
+        let due_before = now - Duration::hours(1);
+        let claimed_until = now + Duration::minutes(10);
+        assert_eq!(
+            claim_due(&mut db, due_before, now, claimed_until, 10)
+                .await
+                .unwrap(),
+            vec![preferences.clone()]
+        );
+        // Claimed preferences don't get claimed again until the claim expires.
+        assert!(claim_due(&mut db, due_before, now, claimed_until, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            claim_due(&mut db, due_before, claimed_until, claimed_until, 10)
+                .await
+                .unwrap(),
+            vec![preferences.clone()]
+        );
+        mark_digested(&mut db, &preferences.owner, now)
+            .await
+            .unwrap();
+        assert!(claim_due(&mut db, due_before, now, claimed_until, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Marking preferences as digested releases the claim.
+        mark_digested(&mut db, &preferences.owner, due_before)
+            .await
+            .unwrap();
+        assert_eq!(
+            claim_due(&mut db, due_before, now, claimed_until, 10)
+                .await
+                .unwrap(),
+            vec![Preferences {
+                digested_until: due_before,
+                ..preferences.clone()
+            }]
+        );
+
+        // Accounts without channels don't get digests.
+        mark_digested(&mut db, &preferences.owner, due_before)
+            .await
+            .unwrap();
+        remove_webhook(&mut db, &preferences.owner, "https://example.com")
+            .await
+            .unwrap();
+        assert!(claim_due(&mut db, due_before, now, claimed_until, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_fills_and_expiries() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let owner = ByteArray([1; 20]);
+        let now = Utc::now().trunc_subsecs(0);
+        let from = now - Duration::hours(1);
+        let order = |uid: u8, valid_to: DateTime<Utc>| orders::Order {
+            uid: ByteArray([uid; 56]),
+            owner,
+            valid_to: valid_to.timestamp(),
+            ..Default::default()
+        };
+        let filled = order(1, now + Duration::hours(1));
+        let expired = order(2, now - Duration::minutes(30));
+        let expired_before = order(3, now - Duration::hours(2));
+        let expired_after_fill = order(4, now - Duration::minutes(10));
+        for order in [&filled, &expired, &expired_before, &expired_after_fill] {
+            orders::insert_order(&mut db, order).await.unwrap();
+        }
+        for (i, order) in [&filled, &expired_after_fill].into_iter().enumerate() {
+            insert_trade(
+                &mut db,
+                &EventIndex {
+                    block_number: 1,
+                    log_index: i.try_into().unwrap(),
+                },
+                &Trade {
+                    order_uid: order.uid,
+                    sell_amount_including_fee: 10.into(),
+                    buy_amount: 20.into(),
+                    fee_amount: 0.into(),
+                },
+            )
+            .await
+            .unwrap();
+        }
+        let traded_at = now - Duration::minutes(5);
+        insert_order_event(
+            &mut db,
+            &OrderEvent {
+                order_uid: filled.uid,
+                timestamp: traded_at,
+                label: OrderEventLabel::Traded,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            fills(&mut db, &owner, from, now).await.unwrap(),
+            vec![Fill {
+                order_uid: filled.uid,
+                sell_token: filled.sell_token,
+                buy_token: filled.buy_token,
+                executed_sell_amount: 10.into(),
+                executed_buy_amount: 20.into(),
+                timestamp: traded_at,
+            }]
+        );
+        assert_eq!(
+            expiries(&mut db, &owner, from, now).await.unwrap(),
+            vec![Expiry {
+                order_uid: expired.uid,
+                sell_token: expired.sell_token,
+                buy_token: expired.buy_token,
+                valid_to: expired.valid_to,
+            }]
+        );
+    }
+}
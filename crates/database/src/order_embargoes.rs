@@ -0,0 +1,131 @@
+//! Orders that are hidden from the public order APIs until the next auction
+//! gets cut.
+
+use {
+    crate::OrderUid,
+    chrono::{DateTime, Utc},
+    sqlx::PgConnection,
+};
+
+/// Embargoes the order until the next auction gets cut, but at most until
+/// `until`.
+pub async fn insert(
+    ex: &mut PgConnection,
+    order_uid: &OrderUid,
+    until: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO order_embargoes (order_uid, auction_id, until)
+VALUES ($1, (SELECT COALESCE(MAX(id), 0) FROM auctions), $2)
+ON CONFLICT (order_uid) DO NOTHING
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(order_uid)
+        .bind(until)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{byte_array::ByteArray, orders},
+        chrono::Duration,
+        futures::TryStreamExt,
+        serde_json::json,
+        sqlx::Connection,
+    };
+
+    async fn is_embargoed(ex: &mut PgConnection, uid: &OrderUid) -> bool {
+        orders::single_full_order(ex, uid)
+            .await
+            .unwrap()
+            .unwrap()
+            .embargoed
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_embargo_lifts_with_next_auction() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let order = ByteArray([1; 56]);
+        let expired = ByteArray([2; 56]);
+        let public = ByteArray([3; 56]);
+        for uid in [order, expired, public] {
+            orders::insert_order(
+                &mut db,
+                &orders::Order {
+                    uid,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+        crate::auction::replace_auction(&mut db, &json!({}))
+            .await
+            .unwrap();
+        insert(&mut db, &order, Utc::now() + Duration::minutes(1))
+            .await
+            .unwrap();
+        insert(&mut db, &expired, Utc::now() - Duration::minutes(1))
+            .await
+            .unwrap();
+        assert!(is_embargoed(&mut db, &order).await);
+        assert!(!is_embargoed(&mut db, &expired).await);
+        assert!(!is_embargoed(&mut db, &public).await);
+
+        crate::auction::replace_auction(&mut db, &json!({}))
+            .await
+            .unwrap();
+        assert!(!is_embargoed(&mut db, &order).await);
+        let order = orders::single_full_order(&mut db, &order)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(order.embargo_until.is_some());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_embargoed_orders_dont_fill_user_orders_page() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let owner = ByteArray([1; 20]);
+        let public = ByteArray([1; 56]);
+        let embargoed = ByteArray([2; 56]);
+        for (uid, creation_timestamp) in [
+            (public, Utc::now() - Duration::seconds(1)),
+            (embargoed, Utc::now()),
+        ] {
+            orders::insert_order(
+                &mut db,
+                &orders::Order {
+                    uid,
+                    owner,
+                    creation_timestamp,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+        insert(&mut db, &embargoed, Utc::now() + Duration::minutes(1))
+            .await
+            .unwrap();
+
+        let page: Vec<_> = crate::order_history::user_orders(&mut db, &owner, None, Some(1))
+            .map_ok(|order| order.uid)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(page, vec![public]);
+    }
+}
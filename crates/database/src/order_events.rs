@@ -4,13 +4,15 @@
 use {
     crate::{byte_array::ByteArray, OrderUid},
     chrono::Utc,
-    sqlx::{types::chrono::DateTime, PgConnection, PgPool},
+    futures::stream::BoxStream,
+    sqlx::{types::chrono::DateTime, PgConnection},
 };
 
 /// Describes what kind of event was registered for an order.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, sqlx::Type)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, sqlx::Type, strum::EnumString)]
 #[sqlx(type_name = "OrderEventLabel")]
 #[sqlx(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
 pub enum OrderEventLabel {
     /// Order was added to the orderbook.
     Created,
@@ -30,6 +32,8 @@ pub enum OrderEventLabel {
     Traded,
     /// Order was cancelled by the user.
     Cancelled,
+    /// Order can not be settled because one of its tokens is not supported.
+    Unsupported,
 }
 
 /// Contains a single event of the life cycle of an order and when it was
@@ -45,6 +49,23 @@ pub struct OrderEvent {
     pub label: OrderEventLabel,
 }
 
+/// Channel on which inserted `created`, `cancelled`, `executing` and `traded`
+/// events get announced once their transaction committed.
+pub const NOTIFICATION_CHANNEL: &str = "order_events";
+
+/// Parses the payload of a notification on [`NOTIFICATION_CHANNEL`].
+pub fn parse_notification(payload: &str) -> Option<OrderEvent> {
+    let payload: serde_json::Value = serde_json::from_str(payload).ok()?;
+    let order_uid = hex::decode(payload["orderUid"].as_str()?).ok()?;
+    Some(OrderEvent {
+        order_uid: ByteArray(order_uid.try_into().ok()?),
+        timestamp: DateTime::parse_from_rfc3339(payload["timestamp"].as_str()?)
+            .ok()?
+            .with_timezone(&Utc),
+        label: payload["label"].as_str()?.parse().ok()?,
+    })
+}
+
 /// Inserts a row into the `order_events` table only if the latest event for the
 /// corresponding order UID has a different label than the provided event..
 pub async fn insert_order_event(
@@ -78,7 +99,7 @@ pub async fn insert_order_event(
 
 /// Deletes rows before the provided timestamp from the `order_events` table.
 pub async fn delete_order_events_before(
-    pool: &PgPool,
+    ex: &mut PgConnection,
     timestamp: DateTime<Utc>,
 ) -> Result<u64, sqlx::Error> {
     const QUERY: &str = r#"
@@ -87,11 +108,118 @@ pub async fn delete_order_events_before(
     "#;
     sqlx::query(QUERY)
         .bind(timestamp)
-        .execute(pool)
+        .execute(ex)
+        .await
+        .map(|result| result.rows_affected())
+}
+
+/// Compact summary of the events of an order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, sqlx::FromRow)]
+pub struct LifecycleSummary {
+    pub order_uid: OrderUid,
+    /// Timestamp of the earliest event.
+    pub first_seen: DateTime<Utc>,
+    /// How often the order became ready, i.e. was (re-)included in an auction.
+    pub auctions: i64,
+    pub first_execution: Option<DateTime<Utc>>,
+    pub last_execution: Option<DateTime<Utc>>,
+    /// Label of the latest event.
+    pub final_state: OrderEventLabel,
+    /// Timestamp of the latest event.
+    pub final_state_at: DateTime<Utc>,
+}
+
+/// Aggregates all events before the provided timestamp into the lifecycle
+/// summaries of their orders. Existing summaries get merged with the new
+/// events, so the events must be deleted afterwards to not count them twice.
+/// Returns the number of updated summaries.
+pub async fn summarize_order_events_before(
+    ex: &mut PgConnection,
+    timestamp: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    const QUERY: &str = r#"
+        INSERT INTO order_lifecycle_summaries (
+            order_uid, first_seen, auctions, first_execution, last_execution, final_state,
+            final_state_at
+        )
+        SELECT
+            order_uid,
+            MIN(timestamp),
+            COUNT(*) FILTER (WHERE label = 'ready'),
+            MIN(timestamp) FILTER (WHERE label = 'executing'),
+            MAX(timestamp) FILTER (WHERE label = 'executing'),
+            (ARRAY_AGG(label ORDER BY timestamp DESC))[1],
+            MAX(timestamp)
+        FROM order_events
+        WHERE timestamp < $1
+        GROUP BY order_uid
+        ON CONFLICT (order_uid) DO UPDATE SET
+            first_seen = LEAST(order_lifecycle_summaries.first_seen, EXCLUDED.first_seen),
+            auctions = order_lifecycle_summaries.auctions + EXCLUDED.auctions,
+            first_execution = LEAST(
+                order_lifecycle_summaries.first_execution, EXCLUDED.first_execution
+            ),
+            last_execution = GREATEST(
+                order_lifecycle_summaries.last_execution, EXCLUDED.last_execution
+            ),
+            final_state = CASE
+                WHEN EXCLUDED.final_state_at >= order_lifecycle_summaries.final_state_at
+                THEN EXCLUDED.final_state
+                ELSE order_lifecycle_summaries.final_state
+            END,
+            final_state_at = GREATEST(
+                order_lifecycle_summaries.final_state_at, EXCLUDED.final_state_at
+            )
+    "#;
+    sqlx::query(QUERY)
+        .bind(timestamp)
+        .execute(ex)
         .await
         .map(|result| result.rows_affected())
 }
 
+/// Streams up to `limit` events in the order they happened, starting after
+/// the event `after`, usually the last event of the previous page, or at the
+/// oldest event if `None`.
+///
+/// Uses keyset pagination, so later pages of this large table are as fast to
+/// fetch as the first one.
+pub fn order_events_after<'a>(
+    ex: &'a mut PgConnection,
+    after: Option<&'a OrderEvent>,
+    limit: i64,
+) -> BoxStream<'a, Result<OrderEvent, sqlx::Error>> {
+    const FIRST_PAGE: &str = r#"
+        SELECT * FROM order_events
+        ORDER BY timestamp, order_uid, label
+        LIMIT $1
+    "#;
+    // Events are only unique in combination with their label.
+    const NEXT_PAGE: &str = r#"
+        SELECT * FROM order_events
+        WHERE (timestamp, order_uid, label) > ($2, $3, $4)
+        ORDER BY timestamp, order_uid, label
+        LIMIT $1
+    "#;
+    match after {
+        None => sqlx::query_as(FIRST_PAGE).bind(limit).fetch(ex),
+        Some(event) => sqlx::query_as(NEXT_PAGE)
+            .bind(limit)
+            .bind(event.timestamp)
+            .bind(event.order_uid)
+            .bind(event.label)
+            .fetch(ex),
+    }
+}
+
+pub async fn lifecycle_summary(
+    ex: &mut PgConnection,
+    order: &OrderUid,
+) -> Result<Option<LifecycleSummary>, sqlx::Error> {
+    const QUERY: &str = r#"SELECT * FROM order_lifecycle_summaries WHERE order_uid = $1"#;
+    sqlx::query_as(QUERY).bind(order).fetch_optional(ex).await
+}
+
 pub async fn get_latest(
     ex: &mut PgConnection,
     order: &OrderUid,
@@ -112,9 +240,66 @@ mod tests {
             byte_array::ByteArray,
             order_events::{OrderEvent, OrderEventLabel},
         },
-        sqlx::Connection,
+        futures::TryStreamExt,
+        sqlx::{postgres::PgListener, Connection, PgPool},
     };
 
+    #[test]
+    fn parses_notifications() {
+        let payload = serde_json::json!({
+            "orderUid": "01".repeat(56),
+            "timestamp": "2024-01-02T03:04:05.123456+01:00",
+            "label": "traded",
+        })
+        .to_string();
+        assert_eq!(
+            parse_notification(&payload),
+            Some(OrderEvent {
+                order_uid: ByteArray([1; 56]),
+                timestamp: "2024-01-02T02:04:05.123456Z".parse().unwrap(),
+                label: OrderEventLabel::Traded,
+            })
+        );
+        assert_eq!(parse_notification("{}"), None);
+        assert_eq!(
+            parse_notification(&payload.replace("traded", "unknown")),
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_notifies_committed_order_events() {
+        let pool = PgPool::connect("postgresql://").await.unwrap();
+        crate::clear_DANGER(&pool).await.unwrap();
+        let mut listener = PgListener::connect_with(&pool).await.unwrap();
+        listener.listen(NOTIFICATION_CHANNEL).await.unwrap();
+
+        // Postgres only stores micros, so the events compare equal to the ones
+        // notified.
+        let now = DateTime::from_timestamp_micros(Utc::now().timestamp_micros()).unwrap();
+        let event = |uid, label| OrderEvent {
+            order_uid: ByteArray([uid; 56]),
+            timestamp: now,
+            label,
+        };
+        let mut db = pool.begin().await.unwrap();
+        // Intermediate states of the auction don't get announced.
+        insert_order_event(&mut db, &event(1, OrderEventLabel::Ready))
+            .await
+            .unwrap();
+        insert_order_event(&mut db, &event(2, OrderEventLabel::Created))
+            .await
+            .unwrap();
+        db.commit().await.unwrap();
+
+        let notification = listener.recv().await.unwrap();
+        assert_eq!(
+            parse_notification(notification.payload()),
+            Some(event(2, OrderEventLabel::Created))
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn postgres_non_subsequent_order_events() {
@@ -172,6 +357,111 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_summarize_order_events() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let now = Utc::now();
+        let uid = ByteArray([1; 56]);
+        let at = |seconds| now + chrono::Duration::seconds(seconds);
+        let labels = [
+            OrderEventLabel::Created,
+            OrderEventLabel::Ready,
+            OrderEventLabel::Filtered,
+            OrderEventLabel::Ready,
+            OrderEventLabel::Executing,
+            OrderEventLabel::Ready,
+            OrderEventLabel::Executing,
+            OrderEventLabel::Traded,
+        ];
+        for (i, label) in labels.into_iter().enumerate() {
+            let event = OrderEvent {
+                order_uid: uid,
+                timestamp: at(i as i64),
+                label,
+            };
+            insert_order_event(&mut db, &event).await.unwrap();
+        }
+
+        // Summarize and delete the first 5 events.
+        assert_eq!(
+            summarize_order_events_before(&mut db, at(5)).await.unwrap(),
+            1
+        );
+        assert_eq!(delete_order_events_before(&mut db, at(5)).await.unwrap(), 5);
+        let summary = lifecycle_summary(&mut db, &uid).await.unwrap().unwrap();
+        assert_eq!(summary.auctions, 2);
+        assert_eq!(summary.final_state, OrderEventLabel::Executing);
+        assert_eq!(
+            summary.first_execution.unwrap().timestamp_micros(),
+            at(4).timestamp_micros()
+        );
+
+        // Merge the remaining events into the existing summary.
+        summarize_order_events_before(&mut db, at(8)).await.unwrap();
+        delete_order_events_before(&mut db, at(8)).await.unwrap();
+        let summary = lifecycle_summary(&mut db, &uid).await.unwrap().unwrap();
+        assert_eq!(
+            summary.first_seen.timestamp_micros(),
+            now.timestamp_micros()
+        );
+        assert_eq!(summary.auctions, 3);
+        assert_eq!(
+            summary.first_execution.unwrap().timestamp_micros(),
+            at(4).timestamp_micros()
+        );
+        assert_eq!(
+            summary.last_execution.unwrap().timestamp_micros(),
+            at(6).timestamp_micros()
+        );
+        assert_eq!(summary.final_state, OrderEventLabel::Traded);
+        assert_eq!(
+            summary.final_state_at.timestamp_micros(),
+            at(7).timestamp_micros()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_order_events_after() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        // Postgres only stores micros, so the events compare equal to the ones
+        // read back.
+        let now = DateTime::from_timestamp_micros(Utc::now().timestamp_micros()).unwrap();
+        let later = now + chrono::Duration::seconds(1);
+        let events = [
+            (1, now, OrderEventLabel::Created),
+            (1, now, OrderEventLabel::Ready),
+            (2, now, OrderEventLabel::Created),
+            (1, later, OrderEventLabel::Traded),
+        ]
+        .map(|(uid, timestamp, label)| OrderEvent {
+            order_uid: ByteArray([uid; 56]),
+            timestamp,
+            label,
+        });
+        for event in &events {
+            insert_order_event(&mut db, event).await.unwrap();
+        }
+
+        let first: Vec<_> = order_events_after(&mut db, None, 3)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(first, events[..3]);
+        let second: Vec<_> = order_events_after(&mut db, first.last(), 3)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(second, events[3..]);
+    }
+
     async fn all_order_events(ex: &mut PgConnection) -> Vec<OrderEvent> {
         const QUERY: &str = r#"
                 SELECT *
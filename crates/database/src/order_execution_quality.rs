@@ -0,0 +1,192 @@
+//! Deviation between the amounts a winning solver promised for an order during
+//! the competition and the amounts that were actually traded onchain.
+
+use {
+    crate::{auction::AuctionId, Address, OrderUid},
+    bigdecimal::BigDecimal,
+    sqlx::{PgConnection, QueryBuilder},
+};
+
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct ExecutionQuality {
+    pub auction_id: AuctionId,
+    pub order_uid: OrderUid,
+    pub solver: Address,
+    pub block_number: i64,
+    pub promised_sell: BigDecimal,
+    pub promised_buy: BigDecimal,
+    pub executed_sell: BigDecimal,
+    pub executed_buy: BigDecimal,
+    /// Relative difference between the executed and the promised price. A
+    /// negative value means the user got a worse price than promised.
+    pub deviation: f64,
+}
+
+pub async fn insert(ex: &mut PgConnection, rows: &[ExecutionQuality]) -> Result<(), sqlx::Error> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut builder = QueryBuilder::new(
+        r#"INSERT INTO order_execution_quality
+        (auction_id, order_uid, solver, block_number, promised_sell, promised_buy, executed_sell, executed_buy, deviation)"#,
+    );
+    builder.push_values(rows, |mut b, row| {
+        b.push_bind(row.auction_id)
+            .push_bind(row.order_uid)
+            .push_bind(row.solver)
+            .push_bind(row.block_number)
+            .push_bind(&row.promised_sell)
+            .push_bind(&row.promised_buy)
+            .push_bind(&row.executed_sell)
+            .push_bind(&row.executed_buy)
+            .push_bind(row.deviation);
+    });
+    builder.push(" ON CONFLICT (auction_id, order_uid) DO NOTHING;");
+    builder.build().execute(ex).await?;
+    Ok(())
+}
+
+/// Amounts the winning solutions of `solver` promised for each order of the
+/// auction.
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct Promise {
+    pub order_uid: OrderUid,
+    pub executed_sell: BigDecimal,
+    pub executed_buy: BigDecimal,
+}
+
+pub async fn fetch_promises(
+    ex: &mut PgConnection,
+    auction_id: AuctionId,
+    solver: &Address,
+) -> Result<Vec<Promise>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT pte.order_uid, pte.executed_sell, pte.executed_buy
+FROM proposed_solutions ps
+JOIN proposed_trade_executions pte
+    ON ps.auction_id = pte.auction_id AND ps.uid = pte.solution_uid
+WHERE ps.auction_id = $1 AND ps.solver = $2 AND ps.is_winner
+    ;"#;
+    sqlx::query_as(QUERY)
+        .bind(auction_id)
+        .bind(solver)
+        .fetch_all(ex)
+        .await
+}
+
+/// Execution quality of a solver aggregated over its most recently settled
+/// orders.
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct SolverScore {
+    pub solver: Address,
+    /// Number of orders the score is based on.
+    pub orders: i64,
+    pub mean_deviation: f64,
+    /// Number of orders that were executed at a worse price than promised.
+    pub shortfalls: i64,
+}
+
+/// Aggregates the execution quality of the `window` most recently settled
+/// orders of every solver.
+pub async fn solver_scores(
+    ex: &mut PgConnection,
+    window: i64,
+) -> Result<Vec<SolverScore>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT
+    solver,
+    COUNT(*) AS orders,
+    AVG(deviation) AS mean_deviation,
+    COUNT(*) FILTER (WHERE deviation < 0) AS shortfalls
+FROM (
+    SELECT
+        solver,
+        deviation,
+        ROW_NUMBER() OVER (PARTITION BY solver ORDER BY block_number DESC) AS recency
+    FROM order_execution_quality
+) recent
+WHERE recency <= $1
+GROUP BY solver
+    ;"#;
+    sqlx::query_as(QUERY).bind(window).fetch_all(ex).await
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            byte_array::ByteArray,
+            solver_competition::{self, Order, Solution},
+        },
+        sqlx::Connection,
+    };
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_order_execution_quality_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let solver = ByteArray([1; 20]);
+        let solution = |uid, solver, is_winner| Solution {
+            uid,
+            solver,
+            is_winner,
+            orders: vec![Order {
+                uid: ByteArray([uid as u8; 56]),
+                executed_sell: 100.into(),
+                executed_buy: 200.into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        solver_competition::save(
+            &mut db,
+            1,
+            &[
+                solution(1, solver, true),
+                solution(2, solver, false),
+                solution(3, ByteArray([2; 20]), true),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let promises = fetch_promises(&mut db, 1, &solver).await.unwrap();
+        assert_eq!(
+            promises,
+            [Promise {
+                order_uid: ByteArray([1; 56]),
+                executed_sell: 100.into(),
+                executed_buy: 200.into(),
+            }]
+        );
+
+        let row = |auction_id, block_number, deviation| ExecutionQuality {
+            auction_id,
+            order_uid: ByteArray([1; 56]),
+            solver,
+            block_number,
+            promised_sell: 100.into(),
+            promised_buy: 200.into(),
+            executed_sell: 100.into(),
+            executed_buy: 200.into(),
+            deviation,
+        };
+        insert(&mut db, &[row(1, 1, -0.5), row(2, 2, 0.), row(3, 3, -0.1)])
+            .await
+            .unwrap();
+        // Inserting the same order of an auction again is a no-op.
+        insert(&mut db, &[row(1, 1, 1.)]).await.unwrap();
+
+        let scores = solver_scores(&mut db, 2).await.unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].solver, solver);
+        assert_eq!(scores[0].orders, 2);
+        assert_eq!(scores[0].shortfalls, 1);
+        assert!((scores[0].mean_deviation + 0.05).abs() < 1e-9);
+    }
+}
@@ -1,50 +1,78 @@
 use {
-    crate::{jit_orders, orders, Address},
+    crate::{
+        filters::{self, Conditions},
+        jit_orders,
+        orders,
+        Address,
+        OrderUid,
+    },
     futures::stream::BoxStream,
-    sqlx::PgConnection,
+    sqlx::{PgConnection, Postgres, QueryBuilder},
 };
 
+/// Returns up to `limit` orders of `owner` ordered by creation date descending
+/// (newest orders first). The page starts after the order `after`, usually the
+/// last order of the previous page, or at the newest order if `None`. An
+/// unknown `after` order yields an empty page.
+///
+/// Uses keyset pagination, so the database seeks to the start of the page
+/// through the owner's creation timestamp index instead of enumerating all
+/// newer orders first like OFFSET would.
 pub fn user_orders<'a>(
     ex: &'a mut PgConnection,
     owner: &'a Address,
-    offset: i64,
+    after: Option<&'a OrderUid>,
     limit: Option<i64>,
 ) -> BoxStream<'a, Result<orders::FullOrder, sqlx::Error>> {
-    // As a future consideration for this query we could move from offset to an
-    // approach called keyset pagination where the offset is identified by "key"
-    // of the previous query. In our case that would be the lowest
-    // creation_timestamp. This way the database can start immediately at the
-    // offset through the index without enumerating the first N elements
-    // before as is the case with OFFSET.
-    // On the other hand that approach is less flexible so we will consider if we
-    // see that these queries are taking too long in practice.
+    const ORDER_BY: &str = " ORDER BY creation_timestamp DESC, uid DESC LIMIT ";
+    let page = |query: &mut QueryBuilder<'a, Postgres>| {
+        // Orders are unique by their creation timestamp and uid.
+        if let Some(after) = after {
+            query
+                .push(
+                    " AND (o.creation_timestamp, o.uid) < (SELECT creation_timestamp, uid FROM \
+                     orders WHERE uid = ",
+                )
+                .push_bind(after)
+                .push(" UNION ALL SELECT creation_timestamp, uid FROM jit_orders WHERE uid = ")
+                .push_bind(after)
+                .push(" LIMIT 1)");
+        }
+        query.push(ORDER_BY).push_bind(limit);
+    };
+
+    #[rustfmt::skip]
+    const PLACED_ORDERS: &str = const_format::concatcp!(
+        "SELECT ", orders::SELECT,
+        " FROM ", orders::FROM,
+        " LEFT OUTER JOIN onchain_placed_orders onchain_o on onchain_o.uid = o.uid",
+        " WHERE NOT ", orders::EMBARGOED,
+    );
+    // JIT orders that are also regular orders are only returned once, as
+    // regular orders.
     #[rustfmt::skip]
-    const QUERY: &str = const_format::concatcp!(
-"(SELECT ", orders::SELECT,
-" FROM ", orders::FROM,
-" LEFT OUTER JOIN onchain_placed_orders onchain_o on onchain_o.uid = o.uid",
-" WHERE o.owner = $1",
-" ORDER BY creation_timestamp DESC LIMIT $2 + $3 ) ",
-" UNION ",
-" (SELECT ", orders::SELECT,
-" FROM ", orders::FROM,
-" LEFT OUTER JOIN onchain_placed_orders onchain_o on onchain_o.uid = o.uid",
-" WHERE onchain_o.sender = $1 ",
-" ORDER BY creation_timestamp DESC LIMIT $2 + $3 ) ",
-" UNION ",
-" (SELECT ", jit_orders::SELECT,
-" FROM ", jit_orders::FROM,
-" WHERE o.owner = $1 AND NOT EXISTS (SELECT 1 FROM orders ord WHERE o.uid = ord.uid)",
-" ORDER BY creation_timestamp DESC LIMIT $2 + $3 ) ",
-" ORDER BY creation_timestamp DESC ",
-" LIMIT $2 ",
-" OFFSET $3 ",
+    const JIT_ORDERS: &str = const_format::concatcp!(
+        "SELECT ", jit_orders::SELECT,
+        " FROM ", jit_orders::FROM,
+        " WHERE NOT EXISTS (SELECT 1 FROM orders ord WHERE o.uid = ord.uid)",
     );
-    sqlx::query_as(QUERY)
-        .bind(owner)
-        .bind(limit)
-        .bind(offset)
-        .fetch(ex)
+    let order_owner = filters::orders::OWNER.of("o");
+    let sender = filters::onchain_placed_orders::SENDER.of("onchain_o");
+    let mut query = QueryBuilder::new("(");
+    query.push(PLACED_ORDERS).and_eq(order_owner, owner);
+    page(&mut query);
+    query
+        .push(") UNION (")
+        .push(PLACED_ORDERS)
+        .and_eq(sender, owner);
+    page(&mut query);
+    query
+        .push(") UNION (")
+        .push(JIT_ORDERS)
+        .and_eq(order_owner, owner);
+    page(&mut query);
+    query.push(")").push(ORDER_BY).push_bind(limit);
+    filters::fetch(ex, query)
 }
 
 #[cfg(test)]
@@ -56,7 +84,7 @@ mod tests {
             events::EventIndex,
             onchain_broadcasted_orders::{insert_onchain_order, OnchainOrderPlacement},
         },
-        chrono::{DateTime, Utc},
+        chrono::{DateTime, Duration, Utc},
         futures::StreamExt,
         sqlx::Connection,
     };
@@ -65,10 +93,10 @@ mod tests {
     async fn user_orders(
         ex: &mut PgConnection,
         owner: &Address,
-        offset: i64,
+        after: Option<&OrderUid>,
         limit: Option<i64>,
     ) -> Vec<Data> {
-        super::user_orders(ex, owner, offset, limit)
+        super::user_orders(ex, owner, after, limit)
             .map(|o| {
                 let o = o.unwrap();
                 (o.uid.0, o.owner, o.creation_timestamp)
@@ -77,6 +105,44 @@ mod tests {
             .await
     }
 
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_user_orders_pages() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        // Orders 2 and 3 are created at the same time, so the UID decides
+        // their order.
+        let owner = ByteArray([1; 20]);
+        let now = Utc::now();
+        for (i, created) in [(1, now - Duration::seconds(1)), (2, now), (3, now)] {
+            let order = orders::Order {
+                uid: ByteArray([i; 56]),
+                owner,
+                creation_timestamp: created,
+                ..Default::default()
+            };
+            orders::insert_order(&mut db, &order).await.unwrap();
+        }
+
+        let uids = |page: Vec<Data>| page.iter().map(|(uid, ..)| uid[0]).collect::<Vec<_>>();
+        let first = user_orders(&mut db, &owner, None, Some(2)).await;
+        assert_eq!(uids(first.clone()), [3, 2]);
+        let last = ByteArray(first.last().unwrap().0);
+        let second = user_orders(&mut db, &owner, Some(&last), Some(2)).await;
+        assert_eq!(uids(second.clone()), [1]);
+        let last = ByteArray(second.last().unwrap().0);
+        assert!(user_orders(&mut db, &owner, Some(&last), Some(2))
+            .await
+            .is_empty());
+        assert!(
+            user_orders(&mut db, &owner, Some(&ByteArray([4; 56])), Some(2))
+                .await
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn postgres_user_orders_performance_many_users_with_some_orders() {
@@ -122,7 +188,7 @@ mod tests {
         let now = std::time::Instant::now();
         let number_of_query_executions = 100;
         for _ in 0..number_of_query_executions {
-            let _result = user_orders(&mut db, &ByteArray([2u8; 20]), 10, Some(10)).await;
+            let _result = user_orders(&mut db, &ByteArray([2u8; 20]), None, Some(10)).await;
         }
         let elapsed = now.elapsed();
         println!(
@@ -165,7 +231,7 @@ mod tests {
         let now = std::time::Instant::now();
         let number_of_query_executions = 100;
         for _ in 0..number_of_query_executions {
-            let _result = user_orders(&mut db, &ByteArray([0u8; 20]), 10, Some(10)).await;
+            let _result = user_orders(&mut db, &ByteArray([0u8; 20]), None, Some(10)).await;
         }
         let elapsed = now.elapsed();
         println!(
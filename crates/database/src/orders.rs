@@ -1,5 +1,6 @@
 use {
     crate::{
+        filters::{self, Conditions},
         onchain_broadcasted_orders::OnchainOrderPlacementError,
         order_events::{insert_order_event, OrderEvent, OrderEventLabel},
         Address,
@@ -14,6 +15,7 @@ use {
             BigDecimal,
         },
         PgConnection,
+        Postgres,
         QueryBuilder,
     },
 };
@@ -496,6 +498,10 @@ pub struct FullOrder {
     pub executed_fee: BigDecimal,
     pub executed_fee_token: Address,
     pub full_app_data: Option<Vec<u8>>,
+    pub unsupported_token: Option<Address>,
+    pub embargo_until: Option<DateTime<Utc>>,
+    /// Whether the order is currently hidden from the public order APIs.
+    pub embargoed: bool,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -545,7 +551,8 @@ impl FullOrder {
 // SET enable_nestloop = false;
 // to get a better idea of what indexes postgres *could* use even if it decides
 // that with the current amount of data this wouldn't be better.
-pub const SELECT: &str = r#"
+pub const SELECT: &str = const_format::concatcp!(
+    r#"
 o.uid, o.owner, o.creation_timestamp, o.sell_token, o.buy_token, o.sell_amount, o.buy_amount,
 o.valid_to, o.app_data, o.fee_amount, o.full_fee_amount, o.kind, o.partially_fillable, o.signature,
 o.receiver, o.signing_scheme, o.settlement_contract, o.sell_token_balance, o.buy_token_balance,
@@ -573,8 +580,22 @@ array(Select (p.target, p.value, p.data) from interactions p where p.order_uid =
 (SELECT onchain_o.placement_error from onchain_placed_orders onchain_o where onchain_o.uid = o.uid limit 1) as onchain_placement_error,
 COALESCE((SELECT SUM(executed_fee) FROM order_execution oe WHERE oe.order_uid = o.uid), 0) as executed_fee,
 COALESCE((SELECT executed_fee_token FROM order_execution oe WHERE oe.order_uid = o.uid LIMIT 1), o.sell_token) as executed_fee_token, -- TODO surplus token
-(SELECT full_app_data FROM app_data ad WHERE o.app_data = ad.contract_app_data LIMIT 1) as full_app_data
-"#;
+(SELECT full_app_data FROM app_data ad WHERE o.app_data = ad.contract_app_data LIMIT 1) as full_app_data,
+(SELECT uto.token FROM unsupported_token_orders uto WHERE uto.order_uid = o.uid) as unsupported_token,
+(SELECT e.until FROM order_embargoes e WHERE e.order_uid = o.uid) as embargo_until,
+"#,
+    EMBARGOED,
+    " as embargoed"
+);
+
+/// Whether the order `o` is under an active embargo. Embargoes are lifted once
+/// a new auction got cut or they expired.
+pub const EMBARGOED: &str = r#"EXISTS (
+    SELECT 1 FROM order_embargoes e
+    WHERE e.order_uid = o.uid
+    AND e.until > now()
+    AND e.auction_id >= (SELECT COALESCE(MAX(id), 0) FROM auctions)
+)"#;
 
 pub const FROM: &str = "orders o";
 
@@ -591,6 +612,21 @@ pub async fn single_full_order(
     sqlx::query_as(QUERY).bind(uid).fetch_optional(ex).await
 }
 
+/// Fetches all orders with the given uids at once. Uids without a matching
+/// order are ignored.
+pub fn many_full_orders<'a>(
+    ex: &'a mut PgConnection,
+    uids: &'a [OrderUid],
+) -> BoxStream<'a, Result<FullOrder, sqlx::Error>> {
+    #[rustfmt::skip]
+    const QUERY: &str = const_format::concatcp!(
+"SELECT ", SELECT,
+" FROM ", FROM,
+" WHERE o.uid = ANY($1) ",
+    );
+    sqlx::query_as(QUERY).bind(uids).fetch(ex)
+}
+
 pub async fn single_full_order_with_quote(
     ex: &mut PgConnection,
     uid: &OrderUid,
@@ -657,42 +693,50 @@ WHERE
     sqlx::query_as(QUERY).bind(tx_hash).fetch(ex)
 }
 
-/// The base solvable orders query used in specialized queries. Parametrized by valid_to.
+/// The base solvable orders query used in specialized queries, which wrap it
+/// in `prefix` and append conditions on the columns of [`SELECT`] to it.
 ///
 /// Excludes orders for the following conditions:
-/// - valid_to is in the past
+/// - valid_to is before `min_valid_to`
 /// - fully executed
 /// - cancelled on chain
 /// - cancelled through API
 /// - pending pre-signature
 /// - ethflow specific invalidation conditions
-#[rustfmt::skip]
-const OPEN_ORDERS: &str = const_format::concatcp!(
-"SELECT * FROM ( ",
-    "SELECT ", SELECT,
-    " FROM ", FROM,
-    " LEFT OUTER JOIN ethflow_orders eth_o on eth_o.uid = o.uid ",
-    " WHERE o.valid_to >= $1",
-    " AND CASE WHEN eth_o.valid_to IS NULL THEN true ELSE eth_o.valid_to >= $1 END",
-r#") AS unfiltered
+fn open_orders<'a>(prefix: &str, min_valid_to: i64) -> QueryBuilder<'a, Postgres> {
+    #[rustfmt::skip]
+    const UNFILTERED: &str = const_format::concatcp!(
+        "SELECT * FROM ( ",
+            "SELECT ", SELECT,
+            " FROM ", FROM,
+            " LEFT OUTER JOIN ethflow_orders eth_o on eth_o.uid = o.uid",
+    );
+    const OPEN: &str = r#") AS unfiltered
 WHERE
     CASE kind
         WHEN 'sell' THEN sum_sell < sell_amount
         WHEN 'buy' THEN sum_buy < buy_amount
     END AND
     (NOT invalidated) AND
-    (onchain_placement_error IS NULL)
-"#
-);
+    (onchain_placement_error IS NULL)"#;
+    let mut query = QueryBuilder::new(prefix);
+    query
+        .push(UNFILTERED)
+        .push(" WHERE true")
+        .and_ge(filters::orders::VALID_TO.of("o"), min_valid_to)
+        .and_null_or_ge(filters::ethflow_orders::VALID_TO.of("eth_o"), min_valid_to)
+        .push(OPEN);
+    query
+}
 
-/// Uses the conditions from OPEN_ORDERS and checks the fok limit orders have
-/// surplus fee.
+/// Uses the conditions from [`open_orders`] and checks the fok limit orders
+/// have surplus fee.
 /// cleanup: fok limit orders should be allowed to not have surplus fee
 pub fn solvable_orders(
     ex: &mut PgConnection,
     min_valid_to: i64,
 ) -> BoxStream<'_, Result<FullOrder, sqlx::Error>> {
-    sqlx::query_as(OPEN_ORDERS).bind(min_valid_to).fetch(ex)
+    filters::fetch(ex, open_orders("", min_valid_to))
 }
 
 pub fn open_orders_by_time_or_uids<'a>(
@@ -722,25 +766,19 @@ FROM settlements
     sqlx::query_scalar(QUERY).fetch_one(ex).await
 }
 
-/// Counts the number of limit orders with the conditions of OPEN_ORDERS. Used
-/// to enforce a maximum number of limit orders per user.
+/// Counts the number of limit orders with the conditions of [`open_orders`].
+/// Used to enforce a maximum number of limit orders per user.
 pub async fn count_limit_orders_by_owner(
     ex: &mut PgConnection,
     min_valid_to: i64,
     owner: &Address,
 ) -> Result<i64, sqlx::Error> {
-    const QUERY: &str = const_format::concatcp!(
-        "SELECT COUNT (*) FROM (",
-        OPEN_ORDERS,
-        " AND class = 'limit'",
-        " AND owner = $2",
-        " ) AS subquery"
-    );
-    sqlx::query_scalar(QUERY)
-        .bind(min_valid_to)
-        .bind(owner)
-        .fetch_one(ex)
-        .await
+    let mut query = open_orders("SELECT COUNT (*) FROM (", min_valid_to);
+    query
+        .and_eq(filters::orders::CLASS, OrderClass::Limit)
+        .and_eq(filters::orders::OWNER, owner)
+        .push(" ) AS subquery");
+    query.build_query_scalar().fetch_one(ex).await
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -768,18 +806,15 @@ pub async fn user_orders_with_quote(
         " o_quotes.gas_price as quote_gas_price, o_quotes.sell_token_price as quote_sell_token_price",
         " FROM (",
             " SELECT *",
-            " FROM (", OPEN_ORDERS,
-            " AND owner = $2",
-            " AND class = 'limit'",
-            " ) AS subquery",
-        " ) AS o",
-        " INNER JOIN order_quotes o_quotes ON o.uid = o_quotes.order_uid"
+            " FROM (",
     );
-    sqlx::query_as::<_, OrderWithQuote>(QUERY)
-        .bind(min_valid_to)
-        .bind(owner)
-        .fetch_all(ex)
-        .await
+    let mut query = open_orders(QUERY, min_valid_to);
+    query
+        .and_eq(filters::orders::OWNER, owner)
+        .and_eq(filters::orders::CLASS, OrderClass::Limit)
+        .push(" ) AS subquery ) AS o")
+        .push(" INNER JOIN order_quotes o_quotes ON o.uid = o_quotes.order_uid");
+    query.build_query_as().fetch_all(ex).await
 }
 
 pub async fn updated_order_uids_after(
@@ -807,6 +842,45 @@ SELECT DISTINCT order_uid FROM (
     sqlx::query_as(QUERY).bind(after_block).fetch_all(ex).await
 }
 
+/// Streams up to `limit` orders in the order they were created. The page
+/// starts after the order with the given creation timestamp and UID, usually
+/// the last order of the previous page, or at the oldest order if `None`.
+///
+/// Unlike OFFSET based pagination this keyset pagination doesn't get slower
+/// for later pages because the database can seek to the start of the page in
+/// the index.
+pub fn orders_after<'a>(
+    ex: &'a mut PgConnection,
+    after: Option<(DateTime<Utc>, &'a OrderUid)>,
+    limit: i64,
+) -> BoxStream<'a, Result<FullOrder, sqlx::Error>> {
+    const FIRST_PAGE: &str = const_format::concatcp!(
+        "SELECT ",
+        SELECT,
+        " FROM ",
+        FROM,
+        " ORDER BY o.creation_timestamp, o.uid",
+        " LIMIT $1",
+    );
+    const NEXT_PAGE: &str = const_format::concatcp!(
+        "SELECT ",
+        SELECT,
+        " FROM ",
+        FROM,
+        " WHERE (o.creation_timestamp, o.uid) > ($2, $3)",
+        " ORDER BY o.creation_timestamp, o.uid",
+        " LIMIT $1",
+    );
+    match after {
+        None => sqlx::query_as(FIRST_PAGE).bind(limit).fetch(ex),
+        Some((created_at, uid)) => sqlx::query_as(NEXT_PAGE)
+            .bind(limit)
+            .bind(created_at)
+            .bind(uid)
+            .fetch(ex),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -892,6 +966,73 @@ mod tests {
         assert_eq!(order.buy_token_balance, full_order.buy_token_balance);
     }
 
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_many_full_orders() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let order = |i| Order {
+            uid: ByteArray([i; 56]),
+            ..Default::default()
+        };
+        for i in 1..=3 {
+            insert_order(&mut db, &order(i)).await.unwrap();
+        }
+
+        let uids = [ByteArray([1; 56]), ByteArray([3; 56]), ByteArray([4; 56])];
+        let mut orders: Vec<_> = many_full_orders(&mut db, &uids)
+            .map_ok(|order| order.uid)
+            .try_collect()
+            .await
+            .unwrap();
+        orders.sort_by_key(|uid| uid.0);
+        assert_eq!(orders, [ByteArray([1; 56]), ByteArray([3; 56])]);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_orders_after() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        // Orders 1 and 2 are created at the same time, so the UID decides
+        // their order.
+        let now = Utc::now();
+        for (i, created) in [(3, now), (2, now), (1, now - Duration::seconds(1))] {
+            let order = Order {
+                uid: ByteArray([i; 56]),
+                creation_timestamp: created,
+                ..Default::default()
+            };
+            insert_order(&mut db, &order).await.unwrap();
+        }
+
+        async fn page(
+            ex: &mut PgConnection,
+            after: Option<(DateTime<Utc>, OrderUid)>,
+        ) -> Vec<(DateTime<Utc>, OrderUid)> {
+            orders_after(ex, after.as_ref().map(|(created, uid)| (*created, uid)), 2)
+                .map_ok(|order| (order.creation_timestamp, order.uid))
+                .try_collect()
+                .await
+                .unwrap()
+        }
+        let first = page(&mut db, None).await;
+        assert_eq!(
+            first.iter().map(|(_, uid)| uid.0[0]).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        let second = page(&mut db, first.last().copied()).await;
+        assert_eq!(
+            second.iter().map(|(_, uid)| uid.0[0]).collect::<Vec<_>>(),
+            [3]
+        );
+        assert!(page(&mut db, second.last().copied()).await.is_empty());
+    }
+
     #[tokio::test]
     #[ignore]
     async fn postgres_order_roundtrip_with_function_irgnoring_duplications() {
@@ -1647,6 +1788,14 @@ mod tests {
         .await
         .unwrap();
         assert!(get_full_order(&mut db, 0).await.is_none());
+        assert_eq!(
+            crate::order_events::get_latest(&mut db, &order.uid)
+                .await
+                .unwrap()
+                .unwrap()
+                .label,
+            crate::order_events::OrderEventLabel::Cancelled
+        );
         crate::events::delete(&mut db, 0).await.unwrap();
 
         // solvable
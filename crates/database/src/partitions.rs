@@ -0,0 +1,331 @@
+//! Management of tables that are partitioned by time.
+//!
+//! Large append-only tables like `order_events` are partitioned into daily
+//! ranges of their timestamp. Expired rows get removed by dropping whole
+//! partitions instead of deleting them one by one, which avoids bloating the
+//! table and the vacuuming that comes with it. Partitions have to exist before
+//! rows of their day get inserted, so they get created ahead of time. Rows
+//! not covered by any range partition end up in the table's default partition
+//! named `<table>_default`. Tables are partitioned by their `timestamp`
+//! column.
+//!
+//! DDL statements can't take bind parameters, so table names get interpolated
+//! into the queries and must only come from trusted constants.
+
+use {
+    crate::PgTransaction,
+    chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc},
+    sqlx::PgConnection,
+    std::ops::DerefMut,
+};
+
+/// A range partition of a table.
+#[derive(Clone, Debug, Eq, PartialEq, sqlx::FromRow)]
+pub struct Partition {
+    pub name: String,
+    /// Inclusive lower bound of the timestamps. `None` if the partition is
+    /// unbounded below.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound of the timestamps.
+    pub to: DateTime<Utc>,
+}
+
+/// Returns the range partitions of the table ordered by their upper bound.
+/// The default partition is not included.
+pub async fn partitions(ex: &mut PgConnection, table: &str) -> Result<Vec<Partition>, sqlx::Error> {
+    // Postgres only exposes the bounds as an expression of the form
+    // `FOR VALUES FROM ('2024-01-01 00:00:00+00') TO ('2024-01-02 00:00:00+00')`
+    // with `MINVALUE` instead of a quoted timestamp for unbounded ranges.
+    const QUERY: &str = r#"
+        WITH bounds AS (
+            SELECT
+                child.relname::text AS name,
+                pg_get_expr(child.relpartbound, child.oid) AS bound
+            FROM pg_inherits
+            JOIN pg_class parent ON parent.oid = pg_inherits.inhparent
+            JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+            WHERE parent.relname = $1::name
+        )
+        SELECT
+            name,
+            substring(bound FROM 'FROM \(''([^'']+)''\)')::timestamptz AS "from",
+            substring(bound FROM 'TO \(''([^'']+)''\)')::timestamptz AS "to"
+        FROM bounds
+        WHERE bound <> 'DEFAULT'
+        ORDER BY "to"
+    "#;
+    sqlx::query_as(QUERY).bind(table).fetch_all(ex).await
+}
+
+/// Name of the partition of the table holding the rows of the given day.
+pub fn partition_name(table: &str, day: NaiveDate) -> String {
+    format!("{table}_p{}", day.format("%Y%m%d"))
+}
+
+fn start_of(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Creates the partition of the table holding the rows of the given day (in
+/// UTC). Does nothing if it already exists.
+pub async fn create_partition(
+    ex: &mut PgConnection,
+    table: &str,
+    day: NaiveDate,
+) -> Result<String, sqlx::Error> {
+    let name = partition_name(table, day);
+    let query = format!(
+        "CREATE TABLE IF NOT EXISTS {name} PARTITION OF {table} FOR VALUES FROM ('{}') TO ('{}')",
+        start_of(day).to_rfc3339(),
+        start_of(day + Days::new(1)).to_rfc3339(),
+    );
+    sqlx::query(&query).execute(ex).await?;
+    Ok(name)
+}
+
+/// Creates daily partitions following the latest existing partition until
+/// the table is partitioned up to at least `until`. Returns the names of the
+/// created partitions.
+///
+/// Partitions get created starting with the current day. Rows of the current
+/// day that went into the default partition because no partition covered it
+/// yet get moved into the new partition. Rows of earlier days stay in the
+/// default partition.
+pub async fn create_partitions_until(
+    ex: &mut PgTransaction<'_>,
+    table: &str,
+    until: DateTime<Utc>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let today = Utc::now().date_naive();
+    let mut day = partitions(ex.deref_mut(), table)
+        .await?
+        .last()
+        .map_or(today, |latest| latest.to.date_naive().max(today));
+    let mut created = Vec::new();
+    while start_of(day) < until {
+        let moved = match day == today {
+            true => take_default_rows(ex, table, day).await?,
+            false => false,
+        };
+        created.push(create_partition(ex.deref_mut(), table, day).await?);
+        if moved {
+            let query = format!("INSERT INTO {table} SELECT * FROM {MOVED_ROWS}");
+            sqlx::query(&query).execute(ex.deref_mut()).await?;
+        }
+        day = day + Days::new(1);
+    }
+    Ok(created)
+}
+
+/// Temporary table holding the rows moved out of a default partition.
+const MOVED_ROWS: &str = "partitions_moved_rows";
+
+/// Moves the rows of the day out of the default partition of the table into
+/// a temporary table, since a partition can't be created while the default
+/// partition holds rows belonging to it. Returns whether any rows got moved.
+async fn take_default_rows(
+    ex: &mut PgTransaction<'_>,
+    table: &str,
+    day: NaiveDate,
+) -> Result<bool, sqlx::Error> {
+    let query =
+        format!("CREATE TEMPORARY TABLE IF NOT EXISTS {MOVED_ROWS} (LIKE {table}) ON COMMIT DROP");
+    sqlx::query(&query).execute(ex.deref_mut()).await?;
+    let query = format!("TRUNCATE {MOVED_ROWS}");
+    sqlx::query(&query).execute(ex.deref_mut()).await?;
+    let query = format!(
+        "WITH moved AS (DELETE FROM {table}_default WHERE timestamp >= $1 AND timestamp < $2 \
+         RETURNING *) INSERT INTO {MOVED_ROWS} SELECT * FROM moved"
+    );
+    let moved = sqlx::query(&query)
+        .bind(start_of(day))
+        .bind(start_of(day + Days::new(1)))
+        .execute(ex.deref_mut())
+        .await?;
+    Ok(moved.rows_affected() > 0)
+}
+
+/// Attaches an existing table as the partition holding the rows with
+/// timestamps in `[from, to)`. Postgres validates that all rows of the table
+/// fall into that range.
+pub async fn attach_partition(
+    ex: &mut PgConnection,
+    table: &str,
+    partition: &str,
+    from: Option<DateTime<Utc>>,
+    to: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let from = match from {
+        Some(from) => format!("'{}'", from.to_rfc3339()),
+        None => "MINVALUE".to_owned(),
+    };
+    let query = format!(
+        "ALTER TABLE {table} ATTACH PARTITION {partition} FOR VALUES FROM ({from}) TO ('{}')",
+        to.to_rfc3339(),
+    );
+    sqlx::query(&query).execute(ex).await.map(|_| ())
+}
+
+/// Detaches the partition from the table. Its rows are kept in a standalone
+/// table of the same name.
+pub async fn detach_partition(
+    ex: &mut PgConnection,
+    table: &str,
+    partition: &str,
+) -> Result<(), sqlx::Error> {
+    let query = format!("ALTER TABLE {table} DETACH PARTITION {partition}");
+    sqlx::query(&query).execute(ex).await.map(|_| ())
+}
+
+/// Detaches and drops all partitions of the table that only hold rows before
+/// the timestamp. Returns the names of the dropped partitions.
+pub async fn drop_partitions_before(
+    ex: &mut PgTransaction<'_>,
+    table: &str,
+    timestamp: DateTime<Utc>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut dropped = Vec::new();
+    for partition in partitions(ex.deref_mut(), table).await? {
+        if partition.to > timestamp {
+            break;
+        }
+        detach_partition(ex.deref_mut(), table, &partition.name).await?;
+        sqlx::query(&format!("DROP TABLE {}", partition.name))
+            .execute(ex.deref_mut())
+            .await?;
+        dropped.push(partition.name);
+    }
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            byte_array::ByteArray,
+            order_events::{self, OrderEvent, OrderEventLabel},
+        },
+        sqlx::Connection,
+    };
+
+    #[test]
+    fn names_partitions_by_day() {
+        assert_eq!(
+            partition_name("order_events", NaiveDate::from_ymd_opt(2024, 3, 7).unwrap()),
+            "order_events_p20240307"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_manage_order_events_partitions() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let today = Utc::now().date_naive();
+        let in_days = |days| start_of(today + Days::new(days));
+
+        let created = create_partitions_until(&mut db, "order_events", in_days(3))
+            .await
+            .unwrap();
+        assert!(created.contains(&partition_name("order_events", today + Days::new(2))));
+        let latest = partitions(&mut db, "order_events").await.unwrap();
+        assert_eq!(latest.last().unwrap().to, in_days(3));
+        // Partitions that already exist are not created again.
+        assert!(create_partitions_until(&mut db, "order_events", in_days(3))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Rows get routed to the partition of their day.
+        let event = OrderEvent {
+            order_uid: ByteArray([1; 56]),
+            timestamp: in_days(2) + chrono::Duration::hours(1),
+            label: OrderEventLabel::Created,
+        };
+        order_events::insert_order_event(&mut db, &event)
+            .await
+            .unwrap();
+        let partition = partition_name("order_events", today + Days::new(2));
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {partition}"))
+            .fetch_one(db.deref_mut())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // Detached partitions keep their rows and can be attached again.
+        detach_partition(&mut db, "order_events", &partition)
+            .await
+            .unwrap();
+        assert!(order_events::get_latest(&mut db, &event.order_uid)
+            .await
+            .unwrap()
+            .is_none());
+        attach_partition(
+            &mut db,
+            "order_events",
+            &partition,
+            Some(in_days(2)),
+            in_days(3),
+        )
+        .await
+        .unwrap();
+        assert!(order_events::get_latest(&mut db, &event.order_uid)
+            .await
+            .unwrap()
+            .is_some());
+
+        // Only partitions entirely before the timestamp get dropped.
+        let dropped = drop_partitions_before(
+            &mut db,
+            "order_events",
+            in_days(2) + chrono::Duration::hours(12),
+        )
+        .await
+        .unwrap();
+        assert!(!dropped.is_empty());
+        assert!(!dropped.contains(&partition));
+        let remaining = partitions(&mut db, "order_events").await.unwrap();
+        assert_eq!(
+            remaining
+                .into_iter()
+                .map(|partition| partition.name)
+                .collect::<Vec<_>>(),
+            [partition]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_moves_rows_of_today_out_of_default_partition() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        for query in [
+            "CREATE TABLE partitions_test (timestamp timestamptz NOT NULL, value bigint NOT \
+             NULL) PARTITION BY RANGE (timestamp)",
+            "CREATE TABLE partitions_test_default PARTITION OF partitions_test DEFAULT",
+            "INSERT INTO partitions_test VALUES (now(), 1), (now() - interval '2 days', 2)",
+        ] {
+            sqlx::query(query).execute(db.deref_mut()).await.unwrap();
+        }
+
+        let today = Utc::now().date_naive();
+        let created =
+            create_partitions_until(&mut db, "partitions_test", start_of(today + Days::new(1)))
+                .await
+                .unwrap();
+        let partition = partition_name("partitions_test", today);
+        assert_eq!(created, [partition.clone()]);
+
+        async fn values(ex: &mut PgConnection, table: &str) -> Vec<i64> {
+            sqlx::query_scalar(&format!("SELECT value FROM {table}"))
+                .fetch_all(ex)
+                .await
+                .unwrap()
+        }
+        assert_eq!(values(&mut db, &partition).await, [1]);
+        assert_eq!(values(&mut db, "partitions_test_default").await, [2]);
+    }
+}
@@ -0,0 +1,47 @@
+use {chrono::NaiveDate, sqlx::PgConnection};
+
+/// Adds `requests` to the number of requests issued to the estimator on the
+/// given day and returns the new total.
+pub async fn add(
+    ex: &mut PgConnection,
+    estimator: &str,
+    day: NaiveDate,
+    requests: i64,
+) -> Result<i64, sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO price_estimator_usage (estimator, day, requests)
+VALUES ($1, $2, $3)
+ON CONFLICT (estimator, day)
+DO UPDATE SET requests = price_estimator_usage.requests + EXCLUDED.requests
+RETURNING requests
+    "#;
+
+    sqlx::query_scalar(QUERY)
+        .bind(estimator)
+        .bind(day)
+        .bind(requests)
+        .fetch_one(ex)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_price_estimator_usage_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(add(&mut db, "0x", day, 0).await.unwrap(), 0);
+        assert_eq!(add(&mut db, "0x", day, 5).await.unwrap(), 5);
+        assert_eq!(add(&mut db, "0x", day, 2).await.unwrap(), 7);
+        assert_eq!(add(&mut db, "1inch", day, 1).await.unwrap(), 1);
+
+        let next_day = day.succ_opt().unwrap();
+        assert_eq!(add(&mut db, "0x", next_day, 1).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+//! Routing of read-only queries to a read replica.
+//!
+//! Queries that can tolerate slightly outdated data can be sent to a read
+//! replica to take load off the primary. Whenever the replica lags behind the
+//! primary by more than the configured threshold, or its lag can't be
+//! determined, queries fall back to the primary.
+
+use {
+    crate::instrumentation,
+    sqlx::{pool::PoolConnection, PgConnection, PgPool, Postgres},
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+            Weak,
+        },
+        time::Duration,
+    },
+};
+
+/// How often the replication lag of a replica gets checked.
+const LAG_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Name of the replica pool in the pool metrics.
+const REPLICA: &str = "replica";
+
+#[derive(Clone, Debug)]
+pub struct ReadReplica {
+    pool: PgPool,
+    /// Whether the replica was caught up when its lag was last checked. Kept
+    /// up to date by a background task.
+    caught_up: Arc<AtomicBool>,
+}
+
+impl ReadReplica {
+    /// Creates a replica that only gets used while it lags at most `max_lag`
+    /// behind the primary. Spawns a task checking the lag in the background
+    /// until the replica gets dropped. Until the first check succeeded all
+    /// queries go to the primary.
+    pub fn new(pool: PgPool, primary: PgPool, max_lag: Duration) -> Self {
+        let caught_up = Arc::new(AtomicBool::new(false));
+        tokio::task::spawn(monitor_lag(
+            pool.clone(),
+            primary,
+            max_lag,
+            Arc::downgrade(&caught_up),
+        ));
+        Self { pool, caught_up }
+    }
+
+    /// Like [`Self::new`] but creates a pool that connects to the replica at
+    /// `url` once it gets used.
+    pub fn connect_lazy(
+        url: &str,
+        primary: PgPool,
+        max_lag: Duration,
+    ) -> Result<Self, sqlx::Error> {
+        Ok(Self::new(PgPool::connect_lazy(url)?, primary, max_lag))
+    }
+
+    /// Acquires a connection for read-only queries: from the replica if it is
+    /// caught up, from the primary otherwise.
+    pub async fn acquire(&self, primary: &PgPool) -> sqlx::Result<PoolConnection<Postgres>> {
+        let (pool, name) = self.route(primary);
+        instrumentation::acquire_named(pool, name).await
+    }
+
+    fn route<'a>(&'a self, primary: &'a PgPool) -> (&'a PgPool, &'static str) {
+        let route = match self.caught_up.load(Ordering::Relaxed) {
+            true => (&self.pool, REPLICA),
+            false => (primary, instrumentation::PRIMARY),
+        };
+        Metrics::get()
+            .database_read_only_queries
+            .with_label_values(&[route.1])
+            .inc();
+        route
+    }
+}
+
+async fn monitor_lag(
+    replica: PgPool,
+    primary: PgPool,
+    max_lag: Duration,
+    caught_up: Weak<AtomicBool>,
+) {
+    let mut interval = tokio::time::interval(LAG_CHECK_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let Some(caught_up) = caught_up.upgrade() else {
+            return;
+        };
+        instrumentation::update_pool_metrics(&replica, REPLICA);
+        caught_up.store(
+            is_caught_up(&replica, &primary, max_lag).await,
+            Ordering::Relaxed,
+        );
+    }
+}
+
+async fn is_caught_up(replica: &PgPool, primary: &PgPool, max_lag: Duration) -> bool {
+    match lag(replica, primary).await {
+        Ok(lag) => {
+            Metrics::get()
+                .database_replication_lag_seconds
+                .set(lag.as_secs_f64());
+            if lag > max_lag {
+                tracing::debug!(?lag, "read replica lags behind, using primary");
+            }
+            lag <= max_lag
+        }
+        Err(err) => {
+            tracing::warn!(?err, "failed to determine replication lag, using primary");
+            false
+        }
+    }
+}
+
+async fn lag(replica: &PgPool, primary: &PgPool) -> Result<Duration, sqlx::Error> {
+    let _timer = instrumentation::time_query("replication_lag");
+    let primary_lsn = {
+        let mut ex = instrumentation::acquire(primary).await?;
+        current_wal_lsn(&mut ex).await?
+    };
+    let mut ex = instrumentation::acquire_named(replica, REPLICA).await?;
+    replication_lag(&mut ex, &primary_lsn).await
+}
+
+/// Returns the position in the write-ahead log up to which the primary wrote
+/// changes.
+pub async fn current_wal_lsn(ex: &mut PgConnection) -> Result<String, sqlx::Error> {
+    const QUERY: &str = "SELECT pg_current_wal_lsn()::text;";
+    sqlx::query_scalar(QUERY).fetch_one(ex).await
+}
+
+/// Returns how far the database is behind its primary, whose write-ahead log
+/// is at `primary_lsn`. That's the age of the last replayed transaction unless
+/// all changes up to `primary_lsn` got replayed. Zero if the database isn't a
+/// replica.
+pub async fn replication_lag(
+    ex: &mut PgConnection,
+    primary_lsn: &str,
+) -> Result<Duration, sqlx::Error> {
+    // Only looking at the age of the last replayed transaction would make a
+    // replica of an idle primary look like it lags behind. Only comparing what
+    // the replica received with what it replayed would make a replica that
+    // lost its connection to the primary look caught up.
+    const QUERY: &str = r#"
+SELECT CASE
+    WHEN NOT pg_is_in_recovery() OR pg_last_wal_replay_lsn() >= $1::pg_lsn THEN 0
+    ELSE EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp())
+END::float8
+    ;"#;
+    let seconds: Option<f64> = sqlx::query_scalar(QUERY)
+        .bind(primary_lsn)
+        .fetch_one(ex)
+        .await?;
+    // Replicas that didn't replay any transaction yet have no timestamp.
+    Ok(seconds
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds.max(0.)).ok())
+        .unwrap_or(Duration::MAX))
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Number of read-only queries by the database they got routed to.
+    #[metric(labels("target"))]
+    database_read_only_queries: prometheus::IntCounterVec,
+
+    /// Last observed replication lag of the read replica.
+    database_replication_lag_seconds: prometheus::Gauge,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        sqlx::{postgres::PgPoolOptions, Connection, PgConnection},
+    };
+
+    fn unreachable_pool() -> PgPool {
+        PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgresql://localhost:1/")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn routes_to_replica_only_while_caught_up() {
+        let primary = unreachable_pool();
+        let replica = ReadReplica {
+            pool: unreachable_pool(),
+            caught_up: Default::default(),
+        };
+
+        let (pool, name) = replica.route(&primary);
+        assert!(std::ptr::eq(pool, &primary));
+        assert_eq!(name, instrumentation::PRIMARY);
+
+        replica.caught_up.store(true, Ordering::Relaxed);
+        let (pool, name) = replica.route(&primary);
+        assert!(std::ptr::eq(pool, &replica.pool));
+        assert_eq!(name, REPLICA);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_primary_if_lag_is_unknown() {
+        let (replica, primary) = (unreachable_pool(), unreachable_pool());
+        assert!(!is_caught_up(&replica, &primary, Duration::MAX).await);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_replication_lag_of_primary() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let lsn = current_wal_lsn(&mut db).await.unwrap();
+        assert_eq!(
+            replication_lag(&mut db, &lsn).await.unwrap(),
+            Duration::ZERO
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_replica_catches_up() {
+        let primary = PgPool::connect_lazy("postgresql://").unwrap();
+        let replica =
+            ReadReplica::connect_lazy("postgresql://", primary.clone(), Duration::ZERO).unwrap();
+        tokio::time::sleep(LAG_CHECK_INTERVAL * 2).await;
+        assert!(replica.caught_up.load(Ordering::Relaxed));
+    }
+}
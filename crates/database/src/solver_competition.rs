@@ -231,6 +231,25 @@ async fn save_jit_orders(
     Ok(())
 }
 
+/// Returns whether the solver won the auction or `None` if it did not propose
+/// any solution for it.
+pub async fn solver_won(
+    ex: &mut PgConnection,
+    auction_id: AuctionId,
+    solver: &Address,
+) -> Result<Option<bool>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT BOOL_OR(is_winner)
+FROM proposed_solutions
+WHERE auction_id = $1 AND solver = $2
+    ;"#;
+    sqlx::query_scalar(QUERY)
+        .bind(auction_id)
+        .bind(solver)
+        .fetch_one(ex)
+        .await
+}
+
 #[allow(clippy::type_complexity)]
 pub async fn fetch(
     ex: &mut PgConnection,
@@ -312,6 +331,236 @@ pub async fn fetch(
     Ok(solutions)
 }
 
+/// How a proposed solution fared in the competition.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The solution won and got settled on-chain.
+    Won,
+    /// The solution lost the competition.
+    Lost,
+    /// The solution won but no settlement of the solver showed up on-chain
+    /// for the auction, e.g. because it reverted or wasn't submitted in time.
+    /// Winners of auctions whose deadline didn't pass yet are reported as
+    /// reverted as well.
+    Reverted,
+}
+
+impl Outcome {
+    fn new(is_winner: bool, settled: bool) -> Self {
+        match (is_winner, settled) {
+            (false, _) => Self::Lost,
+            (true, true) => Self::Won,
+            (true, false) => Self::Reverted,
+        }
+    }
+
+    /// The `is_winner` and `settled` flags matching the outcome.
+    fn flags(outcome: Option<Self>) -> (Option<bool>, Option<bool>) {
+        match outcome {
+            None => (None, None),
+            Some(Self::Lost) => (Some(false), None),
+            Some(Self::Won) => (Some(true), Some(true)),
+            Some(Self::Reverted) => (Some(true), Some(false)),
+        }
+    }
+}
+
+/// A solution a solver proposed in a competition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Participation {
+    pub auction_id: AuctionId,
+    /// The block the auction was created at.
+    pub block: i64,
+    pub solver: Address,
+    pub solution_uid: i64,
+    pub score: BigDecimal,
+    pub outcome: Outcome,
+}
+
+/// Selects the solutions of competitions. Competitions don't store when they
+/// happened, so time ranges are given as the blocks the auctions were created
+/// at.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    pub solver: Option<Address>,
+    /// Inclusive lower bound of the auction blocks.
+    pub from_block: Option<i64>,
+    /// Exclusive upper bound of the auction blocks.
+    pub to_block: Option<i64>,
+    pub outcome: Option<Outcome>,
+}
+
+/// Solutions of all solvers and whether they got settled. Expects the filter
+/// parameters `$1` to `$5`.
+const PARTICIPATIONS: &str = r#"
+    SELECT
+        ps.auction_id, ca.block, ps.solver, ps.uid, ps.score, ps.is_winner,
+        EXISTS (
+            SELECT 1 FROM settlements s
+            WHERE s.auction_id = ps.auction_id AND s.solver = ps.solver
+        ) AS settled
+    FROM proposed_solutions ps
+    JOIN competition_auctions ca ON ca.id = ps.auction_id
+    WHERE
+        ($1::bytea IS NULL OR ps.solver = $1) AND
+        ($2::bigint IS NULL OR ca.block >= $2) AND
+        ($3::bigint IS NULL OR ca.block < $3)
+"#;
+
+/// Returns the most recent solutions matching the filter, newest first.
+pub async fn find_participations(
+    ex: &mut PgConnection,
+    filter: &Filter,
+    limit: i64,
+) -> Result<Vec<Participation>, sqlx::Error> {
+    const QUERY: &str = const_format::concatcp!(
+        "SELECT * FROM (",
+        PARTICIPATIONS,
+        ") p ",
+        "WHERE ($4::bool IS NULL OR is_winner = $4) AND ($5::bool IS NULL OR settled = $5) ",
+        "ORDER BY auction_id DESC, uid ",
+        "LIMIT $6",
+    );
+
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        auction_id: AuctionId,
+        block: i64,
+        solver: Address,
+        uid: i64,
+        score: BigDecimal,
+        is_winner: bool,
+        settled: bool,
+    }
+
+    let (is_winner, settled) = Outcome::flags(filter.outcome);
+    let rows: Vec<Row> = sqlx::query_as(QUERY)
+        .bind(filter.solver)
+        .bind(filter.from_block)
+        .bind(filter.to_block)
+        .bind(is_winner)
+        .bind(settled)
+        .bind(limit)
+        .fetch_all(ex)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| Participation {
+            auction_id: row.auction_id,
+            block: row.block,
+            solver: row.solver,
+            solution_uid: row.uid,
+            score: row.score,
+            outcome: Outcome::new(row.is_winner, row.settled),
+        })
+        .collect())
+}
+
+/// Aggregated results of the solutions matching a filter.
+#[derive(Clone, Debug, Default, PartialEq, sqlx::FromRow)]
+pub struct Stats {
+    /// Number of auctions with at least one matching solution.
+    pub auctions: i64,
+    pub solutions: i64,
+    pub won: i64,
+    pub lost: i64,
+    pub reverted: i64,
+    /// Average score of the solutions. `None` if there are none.
+    pub average_score: Option<BigDecimal>,
+}
+
+impl Stats {
+    /// Share of the solutions that won and got settled.
+    pub fn win_rate(&self) -> f64 {
+        if self.solutions == 0 {
+            return 0.;
+        }
+        self.won as f64 / self.solutions as f64
+    }
+}
+
+/// Aggregates the solutions matching the filter.
+pub async fn stats(ex: &mut PgConnection, filter: &Filter) -> Result<Stats, sqlx::Error> {
+    const QUERY: &str = const_format::concatcp!(
+        "SELECT ",
+        "COUNT(DISTINCT auction_id) AS auctions, ",
+        "COUNT(*) AS solutions, ",
+        "COUNT(*) FILTER (WHERE is_winner AND settled) AS won, ",
+        "COUNT(*) FILTER (WHERE NOT is_winner) AS lost, ",
+        "COUNT(*) FILTER (WHERE is_winner AND NOT settled) AS reverted, ",
+        "AVG(score) AS average_score ",
+        "FROM (",
+        PARTICIPATIONS,
+        ") p ",
+        "WHERE ($4::bool IS NULL OR is_winner = $4) AND ($5::bool IS NULL OR settled = $5)",
+    );
+
+    let (is_winner, settled) = Outcome::flags(filter.outcome);
+    sqlx::query_as(QUERY)
+        .bind(filter.solver)
+        .bind(filter.from_block)
+        .bind(filter.to_block)
+        .bind(is_winner)
+        .bind(settled)
+        .fetch_one(ex)
+        .await
+}
+
+/// An auction a solver won and whether it settled it.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct WonAuction {
+    pub auction_id: AuctionId,
+    pub solver: Address,
+    pub settled: bool,
+}
+
+/// Fetches the `window` most recent auctions every solver won, counting every
+/// winner of auctions with multiple winners. Only the `lookback` most recent
+/// auctions get scanned. Of those only auctions whose deadline passed before
+/// the most recent settlement that got linked to its auction are considered,
+/// so that settlements which weren't indexed yet don't count as failures.
+pub async fn won_auctions(
+    ex: &mut PgConnection,
+    window: i64,
+    lookback: i64,
+) -> Result<Vec<WonAuction>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT
+    recent.auction_id,
+    recent.solver,
+    EXISTS (
+        SELECT 1
+        FROM settlements s
+        WHERE s.auction_id = recent.auction_id AND s.solver = recent.solver
+    ) AS settled
+FROM (
+    SELECT
+        won.auction_id,
+        won.solver,
+        ROW_NUMBER() OVER (PARTITION BY won.solver ORDER BY won.auction_id DESC) AS recency
+    FROM (
+        SELECT DISTINCT ps.auction_id, ps.solver
+        FROM proposed_solutions ps
+        JOIN competition_auctions ca ON ca.id = ps.auction_id
+        WHERE ps.is_winner
+            AND ps.auction_id > (SELECT COALESCE(MAX(id), 0) FROM competition_auctions) - $2
+            AND ca.deadline < (
+                SELECT COALESCE(MAX(block_number), 0)
+                FROM settlements
+                WHERE auction_id IS NOT NULL
+            )
+    ) won
+) recent
+WHERE recency <= $1
+ORDER BY recent.auction_id
+    ;"#;
+    sqlx::query_as(QUERY)
+        .bind(window)
+        .bind(lookback)
+        .fetch_all(ex)
+        .await
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -533,4 +782,226 @@ mod tests {
         // inserted (2 fetched from "proposed_jit_orders" and 1 from "orders" table)
         assert!(fetched_solutions[2].orders.len() == 3);
     }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_solver_stats() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let solver_a = ByteArray([1u8; 20]);
+        let solver_b = ByteArray([2u8; 20]);
+        // Auction 1: `a` wins and settles. Auction 2: `b` wins but doesn't
+        // settle. Auction 3: `a` wins and settles, `b` loses.
+        let competitions = [
+            (1, [(true, 30), (false, 10)]),
+            (2, [(false, 20), (true, 40)]),
+            (3, [(true, 10), (false, 5)]),
+        ];
+        for (id, results) in competitions {
+            let solutions: Vec<_> = [solver_a, solver_b]
+                .into_iter()
+                .zip(results)
+                .enumerate()
+                .map(|(uid, (solver, (is_winner, score)))| Solution {
+                    uid: uid as i64,
+                    solver,
+                    is_winner,
+                    score: BigDecimal::from(score),
+                    orders: vec![Default::default()],
+                    ..Default::default()
+                })
+                .collect();
+            crate::auction::save(
+                &mut db,
+                crate::auction::Auction {
+                    id,
+                    block: id * 10,
+                    deadline: id * 10 + 5,
+                    order_uids: Default::default(),
+                    price_tokens: Default::default(),
+                    price_values: Default::default(),
+                    surplus_capturing_jit_order_owners: Default::default(),
+                },
+            )
+            .await
+            .unwrap();
+            save(&mut db, id, &solutions).await.unwrap();
+        }
+        for (log_index, auction_id) in [(0, 1), (1, 3)] {
+            crate::events::insert_settlement(
+                &mut db,
+                &EventIndex {
+                    block_number: 100,
+                    log_index,
+                },
+                &Settlement {
+                    solver: solver_a,
+                    transaction_hash: ByteArray([log_index as u8; 32]),
+                },
+            )
+            .await
+            .unwrap();
+            crate::settlements::update_settlement_auction(&mut db, 100, log_index, auction_id)
+                .await
+                .unwrap();
+        }
+
+        let of_a = Filter {
+            solver: Some(solver_a),
+            ..Default::default()
+        };
+        let stats_a = stats(&mut db, &of_a).await.unwrap();
+        assert_eq!(
+            stats_a,
+            Stats {
+                auctions: 3,
+                solutions: 3,
+                won: 2,
+                lost: 1,
+                reverted: 0,
+                average_score: Some(20.into()),
+            }
+        );
+        assert!((stats_a.win_rate() - 2. / 3.).abs() < 1e-9);
+
+        let reverted_of_b = Filter {
+            solver: Some(solver_b),
+            outcome: Some(Outcome::Reverted),
+            ..Default::default()
+        };
+        let participations = find_participations(&mut db, &reverted_of_b, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            participations,
+            [Participation {
+                auction_id: 2,
+                block: 20,
+                solver: solver_b,
+                solution_uid: 1,
+                score: 40.into(),
+                outcome: Outcome::Reverted,
+            }]
+        );
+
+        // Only auctions created in blocks [20, 30) are considered.
+        let recent = Filter {
+            from_block: Some(20),
+            to_block: Some(30),
+            ..Default::default()
+        };
+        let participations = find_participations(&mut db, &recent, 10).await.unwrap();
+        assert_eq!(participations.len(), 2);
+        assert!(participations.iter().all(|p| p.auction_id == 2));
+        assert_eq!(
+            stats(&mut db, &recent).await.unwrap().average_score,
+            Some(30.into())
+        );
+
+        assert_eq!(
+            stats(
+                &mut db,
+                &Filter {
+                    solver: Some(ByteArray([3u8; 20])),
+                    ..Default::default()
+                }
+            )
+            .await
+            .unwrap(),
+            Stats::default()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_won_auctions() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let solver_a = ByteArray([1; 20]);
+        let solver_b = ByteArray([2; 20]);
+        // Both solvers win auctions 1 to 4 under fair combinatorial winner
+        // selection, `a` with two solutions in auction 2.
+        for (auction_id, deadline) in [(1, 10), (2, 20), (3, 30), (4, 100)] {
+            crate::auction::save(
+                &mut db,
+                crate::auction::Auction {
+                    id: auction_id,
+                    block: deadline - 5,
+                    deadline,
+                    order_uids: Default::default(),
+                    price_tokens: Default::default(),
+                    price_values: Default::default(),
+                    surplus_capturing_jit_order_owners: Default::default(),
+                },
+            )
+            .await
+            .unwrap();
+            let solvers = match auction_id {
+                2 => vec![solver_a, solver_a, solver_b],
+                _ => vec![solver_a, solver_b],
+            };
+            let solutions: Vec<_> = solvers
+                .into_iter()
+                .enumerate()
+                .map(|(uid, solver)| Solution {
+                    uid: uid as i64,
+                    solver,
+                    is_winner: true,
+                    orders: vec![Default::default()],
+                    ..Default::default()
+                })
+                .collect();
+            save(&mut db, auction_id, &solutions).await.unwrap();
+        }
+        // `a` settled auctions 1 and 3, `b` only auction 3.
+        for (log_index, (auction_id, solver)) in [(1, solver_a), (3, solver_a), (3, solver_b)]
+            .into_iter()
+            .enumerate()
+        {
+            let log_index = log_index as i64;
+            crate::events::insert_settlement(
+                &mut db,
+                &EventIndex {
+                    block_number: 50,
+                    log_index,
+                },
+                &Settlement {
+                    solver,
+                    transaction_hash: ByteArray([log_index as u8; 32]),
+                },
+            )
+            .await
+            .unwrap();
+            crate::settlements::update_settlement_auction(&mut db, 50, log_index, auction_id)
+                .await
+                .unwrap();
+        }
+
+        let won = |auction_id, solver, settled| WonAuction {
+            auction_id,
+            solver,
+            settled,
+        };
+        // Auction 4 is still within its deadline and auction 1 is outside of
+        // the window.
+        assert_eq!(
+            won_auctions(&mut db, 2, 10).await.unwrap(),
+            [
+                won(2, solver_a, false),
+                won(2, solver_b, false),
+                won(3, solver_a, true),
+                won(3, solver_b, true),
+            ]
+        );
+        // Only the 2 most recent auctions get scanned, of which only auction 3
+        // passed its deadline.
+        assert_eq!(
+            won_auctions(&mut db, 10, 2).await.unwrap(),
+            [won(3, solver_a, true), won(3, solver_b, true)]
+        );
+    }
 }
@@ -0,0 +1,66 @@
+//! Solvers excluded from the competition for not settling the auctions they
+//! won.
+
+use {
+    crate::{auction::AuctionId, Address},
+    chrono::{DateTime, Utc},
+    sqlx::PgConnection,
+};
+
+#[derive(Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct Exclusion {
+    pub solver: Address,
+    pub excluded_until: DateTime<Utc>,
+    pub latest_auction: AuctionId,
+}
+
+/// Stores the latest exclusion of the solver, replacing the previous one.
+pub async fn upsert(ex: &mut PgConnection, exclusion: &Exclusion) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO solver_exclusions (solver, excluded_until, latest_auction)
+VALUES ($1, $2, $3)
+ON CONFLICT (solver) DO UPDATE
+SET excluded_until = EXCLUDED.excluded_until, latest_auction = EXCLUDED.latest_auction
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(exclusion.solver)
+        .bind(exclusion.excluded_until)
+        .bind(exclusion.latest_auction)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+/// Fetches the latest exclusion of every solver, including the ones that
+/// already ended.
+pub async fn fetch_all(ex: &mut PgConnection) -> Result<Vec<Exclusion>, sqlx::Error> {
+    const QUERY: &str = r#"SELECT * FROM solver_exclusions"#;
+    sqlx::query_as(QUERY).fetch_all(ex).await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::byte_array::ByteArray, chrono::SubsecRound, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let mut exclusion = Exclusion {
+            solver: ByteArray([1; 20]),
+            // Postgres stores timestamps with microsecond precision.
+            excluded_until: Utc::now().trunc_subsecs(6),
+            latest_auction: 1,
+        };
+        upsert(&mut db, &exclusion).await.unwrap();
+        assert_eq!(fetch_all(&mut db).await.unwrap(), [exclusion.clone()]);
+
+        exclusion.excluded_until += chrono::Duration::hours(1);
+        exclusion.latest_auction = 2;
+        upsert(&mut db, &exclusion).await.unwrap();
+        assert_eq!(fetch_all(&mut db).await.unwrap(), [exclusion]);
+    }
+}
@@ -1,8 +1,15 @@
 use {
-    crate::{auction::AuctionId, events::EventIndex, Address, OrderUid, TransactionHash},
+    crate::{
+        auction::AuctionId,
+        events::EventIndex,
+        filters::{self, Conditions},
+        Address,
+        OrderUid,
+        TransactionHash,
+    },
     bigdecimal::BigDecimal,
     futures::stream::BoxStream,
-    sqlx::PgConnection,
+    sqlx::{PgConnection, Postgres, QueryBuilder},
 };
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, sqlx::FromRow)]
@@ -20,12 +27,7 @@ pub struct TradesQueryRow {
     pub auction_id: Option<AuctionId>,
 }
 
-pub fn trades<'a>(
-    ex: &'a mut PgConnection,
-    owner_filter: Option<&'a Address>,
-    order_uid_filter: Option<&'a OrderUid>,
-) -> BoxStream<'a, Result<TradesQueryRow, sqlx::Error>> {
-    const COMMON_QUERY: &str = r#"
+const COMMON_QUERY: &str = r#"
 SELECT
     t.block_number,
     t.log_index,
@@ -47,29 +49,93 @@ LEFT OUTER JOIN LATERAL (
     LIMIT 1
 ) AS settlement ON true"#;
 
+pub fn trades<'a>(
+    ex: &'a mut PgConnection,
+    owner_filter: Option<&'a Address>,
+    order_uid_filter: Option<&'a OrderUid>,
+) -> BoxStream<'a, Result<TradesQueryRow, sqlx::Error>> {
+    let mut query = QueryBuilder::new("");
+    filtered_trades(&mut query, owner_filter, order_uid_filter);
+    filters::fetch(ex, query)
+}
+
+/// Fetches up to `limit` trades of the owner, most recent first. The page
+/// starts after the trade at `after`, usually the last trade of the previous
+/// page, or at the most recent trade if `None`.
+pub async fn owner_trades(
+    ex: &mut PgConnection,
+    owner: &Address,
+    after: Option<EventIndex>,
+    limit: i64,
+) -> Result<Vec<TradesQueryRow>, sqlx::Error> {
+    let mut query = QueryBuilder::new("SELECT * FROM (");
+    filtered_trades(&mut query, Some(owner), None);
+    query.push(") AS trades");
+    if let Some(after) = after {
+        query
+            .push(" WHERE (block_number, log_index) < (")
+            .push_bind(after.block_number)
+            .push(", ")
+            .push_bind(after.log_index)
+            .push(")");
+    }
+    query
+        .push(" ORDER BY block_number DESC, log_index DESC LIMIT ")
+        .push_bind(limit);
+    query.build_query_as().fetch_all(ex).await
+}
+
+/// Appends the query for the trades of regular, onchain placed and JIT orders
+/// matching the filters.
+fn filtered_trades<'a>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    owner_filter: Option<&'a Address>,
+    order_uid_filter: Option<&'a OrderUid>,
+) {
+    query
+        .push(COMMON_QUERY)
+        .push(" JOIN orders o ON o.uid = t.order_uid WHERE true")
+        .and_eq_opt(filters::orders::OWNER.of("o"), owner_filter)
+        .and_eq_opt(filters::orders::UID.of("o"), order_uid_filter);
+    // Orders placed onchain get also returned for the account that placed
+    // them.
+    if let Some(owner) = owner_filter {
+        query
+            .push(" UNION ")
+            .push(COMMON_QUERY)
+            .push(" JOIN orders o ON o.uid = t.order_uid")
+            .push(" LEFT OUTER JOIN onchain_placed_orders onchain_o")
+            .push(" ON onchain_o.uid = t.order_uid WHERE true")
+            .and_eq(
+                filters::onchain_placed_orders::SENDER.of("onchain_o"),
+                owner,
+            )
+            .and_eq_opt(filters::orders::UID.of("o"), order_uid_filter);
+    }
+    query
+        .push(" UNION ")
+        .push(COMMON_QUERY)
+        .push(" JOIN jit_orders o ON o.uid = t.order_uid WHERE true")
+        .and_eq_opt(filters::orders::OWNER.of("o"), owner_filter)
+        .and_eq_opt(filters::orders::UID.of("o"), order_uid_filter);
+}
+
+/// Fetches the trades of all the given orders at once.
+pub fn trades_for_orders<'a>(
+    ex: &'a mut PgConnection,
+    order_uids: &'a [OrderUid],
+) -> BoxStream<'a, Result<TradesQueryRow, sqlx::Error>> {
     const QUERY: &str = const_format::concatcp!(
         COMMON_QUERY,
         " JOIN orders o ON o.uid = t.order_uid",
-        " WHERE ($1 IS NULL OR o.owner = $1)",
-        " AND ($2 IS NULL OR o.uid = $2)",
-        " UNION ",
-        COMMON_QUERY,
-        " JOIN orders o ON o.uid = t.order_uid",
-        " LEFT OUTER JOIN onchain_placed_orders onchain_o",
-        " ON onchain_o.uid = t.order_uid",
-        " WHERE onchain_o.sender = $1",
-        " AND ($2 IS NULL OR o.uid = $2)",
+        " WHERE o.uid = ANY($1)",
         " UNION ",
         COMMON_QUERY,
         " JOIN jit_orders o ON o.uid = t.order_uid",
-        " WHERE ($1 IS NULL OR o.owner = $1)",
-        " AND ($2 IS NULL OR o.uid = $2)",
+        " WHERE o.uid = ANY($1)",
     );
 
-    sqlx::query_as(QUERY)
-        .bind(owner_filter)
-        .bind(order_uid_filter)
-        .fetch(ex)
+    sqlx::query_as(QUERY).bind(order_uids).fetch(ex)
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, sqlx::FromRow)]
@@ -106,6 +172,95 @@ AND t.log_index BETWEEN (SELECT * from previous_settlement) AND $2
         .await
 }
 
+/// A trade of a settlement transaction together with the fee charged for it
+/// and the solver that submitted the settlement.
+#[derive(Clone, Debug, Default, Eq, PartialEq, sqlx::FromRow)]
+pub struct SettledTrade {
+    pub order_uid: OrderUid,
+    pub sell_amount: BigDecimal,
+    pub buy_amount: BigDecimal,
+    pub fee_amount: BigDecimal,
+    /// The fee the order was charged in the auction, `None` if the
+    /// settlement wasn't associated with an auction (yet).
+    pub executed_fee: Option<BigDecimal>,
+    pub executed_fee_token: Option<Address>,
+    pub solver: Address,
+}
+
+/// Fetches the trades executed by the settlement in the given transaction
+/// ordered by their log index.
+pub fn trades_in_tx<'a>(
+    ex: &'a mut PgConnection,
+    tx_hash: &'a TransactionHash,
+) -> BoxStream<'a, Result<SettledTrade, sqlx::Error>> {
+    const QUERY: &str = r#"
+WITH
+    settlement AS (
+        SELECT block_number, log_index, solver, auction_id
+        FROM settlements
+        WHERE tx_hash = $1
+    ),
+    -- The log index in this query is the log index of the settlement event from the previous (lower log index) settlement in the same transaction or 0 if there is no previous settlement.
+    previous_settlement AS (
+        SELECT COALESCE(MAX(log_index), 0) AS low
+        FROM settlements
+        WHERE
+            block_number = (SELECT block_number FROM settlement) AND
+            log_index < (SELECT log_index FROM settlement)
+    )
+SELECT
+    t.order_uid,
+    t.sell_amount,
+    t.buy_amount,
+    t.fee_amount,
+    oe.executed_fee,
+    oe.executed_fee_token,
+    s.solver
+FROM trades t
+JOIN settlement s ON s.block_number = t.block_number
+LEFT OUTER JOIN order_execution oe
+    ON oe.order_uid = t.order_uid AND oe.auction_id = s.auction_id
+-- BETWEEN is inclusive
+WHERE t.log_index BETWEEN (SELECT low FROM previous_settlement) AND s.log_index
+ORDER BY t.log_index
+"#;
+    sqlx::query_as(QUERY).bind(tx_hash).fetch(ex)
+}
+
+/// Streams up to `limit` trade events in the order they were emitted,
+/// starting after the event at `after`, usually the last event of the previous
+/// page, or at the oldest event if `None`.
+///
+/// Uses keyset pagination on the primary key, so later pages are as fast to
+/// fetch as the first one.
+pub fn trades_after(
+    ex: &mut PgConnection,
+    after: Option<EventIndex>,
+    limit: i64,
+) -> BoxStream<'_, Result<TradeEvent, sqlx::Error>> {
+    const FIRST_PAGE: &str = r#"
+SELECT block_number, log_index, order_uid
+FROM trades
+ORDER BY block_number, log_index
+LIMIT $1
+"#;
+    const NEXT_PAGE: &str = r#"
+SELECT block_number, log_index, order_uid
+FROM trades
+WHERE (block_number, log_index) > ($2, $3)
+ORDER BY block_number, log_index
+LIMIT $1
+"#;
+    match after {
+        None => sqlx::query_as(FIRST_PAGE).bind(limit).fetch(ex),
+        Some(after) => sqlx::query_as(NEXT_PAGE)
+            .bind(limit)
+            .bind(after.block_number)
+            .bind(after.log_index)
+            .fetch(ex),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -316,6 +471,42 @@ mod tests {
         assert_trades(&mut db, Some(&owners[3]), None, &[trade_0]).await;
     }
 
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_owner_trades_pages() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let (owners, order_ids) = generate_owners_and_order_ids(2, 4).await;
+        let mut trades = Vec::new();
+        for (i, order_uid) in order_ids.iter().take(3).enumerate() {
+            let event_index = EventIndex {
+                block_number: i as i64 / 2,
+                log_index: i as i64 % 2,
+            };
+            trades.push(
+                add_order_and_trade(&mut db, owners[0], *order_uid, event_index, None, None).await,
+            );
+        }
+        let other = EventIndex {
+            block_number: 0,
+            log_index: 2,
+        };
+        add_order_and_trade(&mut db, owners[1], order_ids[3], other, None, None).await;
+
+        let first_page = owner_trades(&mut db, &owners[0], None, 2).await.unwrap();
+        assert_eq!(first_page, [trades[2].clone(), trades[1].clone()]);
+        let after = EventIndex {
+            block_number: first_page[1].block_number,
+            log_index: first_page[1].log_index,
+        };
+        let second_page = owner_trades(&mut db, &owners[0], Some(after), 2)
+            .await
+            .unwrap();
+        assert_eq!(second_page, [trades[0].clone()]);
+    }
+
     #[tokio::test]
     #[ignore]
     async fn postgres_trades_with_order_uid_filter() {
@@ -579,4 +770,131 @@ mod tests {
             }]
         );
     }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_trades_in_tx() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let (owners, order_ids) = generate_owners_and_order_ids(1, 3).await;
+        let tx_a = ByteArray([1; 32]);
+        let tx_b = ByteArray([2; 32]);
+        let settlements = [
+            (1, ByteArray([1; 20]), tx_a, 1),
+            (4, ByteArray([2; 20]), tx_b, 2),
+        ];
+        for (log_index, solver, tx_hash, auction_id) in settlements {
+            let event_index = EventIndex {
+                block_number: 0,
+                log_index,
+            };
+            add_settlement(&mut db, event_index, solver, tx_hash, auction_id).await;
+        }
+        // The first trade belongs to the first settlement, the others to the
+        // second settlement in the same block.
+        for (log_index, order_uid) in [0, 2, 3].into_iter().zip(&order_ids) {
+            let event_index = EventIndex {
+                block_number: 0,
+                log_index,
+            };
+            add_order_and_trade(&mut db, owners[0], *order_uid, event_index, None, None).await;
+        }
+        let executed_fee = crate::order_execution::Asset {
+            amount: 5.into(),
+            token: ByteArray([3; 20]),
+        };
+        crate::order_execution::save(&mut db, &order_ids[2], 2, 0, executed_fee, &[])
+            .await
+            .unwrap();
+
+        let trades: Vec<_> = trades_in_tx(&mut db, &tx_b).try_collect().await.unwrap();
+        assert_eq!(
+            trades,
+            [
+                SettledTrade {
+                    order_uid: order_ids[1],
+                    solver: ByteArray([2; 20]),
+                    ..Default::default()
+                },
+                SettledTrade {
+                    order_uid: order_ids[2],
+                    executed_fee: Some(5.into()),
+                    executed_fee_token: Some(ByteArray([3; 20])),
+                    solver: ByteArray([2; 20]),
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let trades: Vec<_> = trades_in_tx(&mut db, &tx_a).try_collect().await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].order_uid, order_ids[0]);
+        assert!(trades_in_tx(&mut db, &ByteArray([9; 32]))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_trades_for_orders() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let (owners, order_ids) = generate_owners_and_order_ids(2, 3).await;
+        let mut trades = Vec::new();
+        for (i, order_uid) in order_ids.iter().enumerate() {
+            let event_index = EventIndex {
+                block_number: 0,
+                log_index: i.try_into().unwrap(),
+            };
+            trades.push(
+                add_order_and_trade(&mut db, owners[i % 2], *order_uid, event_index, None, None)
+                    .await,
+            );
+        }
+
+        let mut filtered = trades_for_orders(&mut db, &[order_ids[0], order_ids[2]])
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        filtered.sort_by_key(|t| (t.block_number, t.log_index));
+        assert_eq!(filtered, [trades[0].clone(), trades[2].clone()]);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_trades_after() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let indices = [(1, 2), (1, 5), (2, 0)].map(|(block_number, log_index)| EventIndex {
+            block_number,
+            log_index,
+        });
+        let events: Vec<_> = indices
+            .iter()
+            .map(|index| (*index, Event::Trade(Default::default())))
+            .collect();
+        crate::events::append(&mut db, &events).await.unwrap();
+
+        let event_indices = |trades: Vec<TradeEvent>| {
+            trades
+                .iter()
+                .map(|trade| (trade.block_number, trade.log_index))
+                .collect::<Vec<_>>()
+        };
+        let first: Vec<_> = trades_after(&mut db, None, 2).try_collect().await.unwrap();
+        assert_eq!(event_indices(first), [(1, 2), (1, 5)]);
+        let second: Vec<_> = trades_after(&mut db, Some(indices[1]), 2)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(event_indices(second), [(2, 0)]);
+    }
 }
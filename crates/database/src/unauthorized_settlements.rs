@@ -0,0 +1,97 @@
+//! Settlements submitted by solvers that were not allowed to settle the
+//! auction they reference.
+
+use {
+    crate::{auction::AuctionId, Address, TransactionHash},
+    sqlx::PgConnection,
+};
+
+/// Why a settlement was not authorized by the competition.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "UnauthorizedSettlementReason")]
+#[sqlx(rename_all = "snake_case")]
+pub enum Reason {
+    /// The solver did not propose a solution for the auction.
+    NotParticipated,
+    /// The solver proposed a solution for the auction but did not win.
+    NotWinner,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, sqlx::FromRow)]
+pub struct UnauthorizedSettlement {
+    pub block_number: i64,
+    pub log_index: i64,
+    pub tx_hash: TransactionHash,
+    pub auction_id: AuctionId,
+    pub solver: Address,
+    pub reason: Reason,
+}
+
+pub async fn insert(
+    ex: &mut PgConnection,
+    settlement: &UnauthorizedSettlement,
+) -> Result<(), sqlx::Error> {
+    const QUERY: &str = r#"
+INSERT INTO unauthorized_settlements (block_number, log_index, tx_hash, auction_id, solver, reason)
+VALUES ($1, $2, $3, $4, $5, $6)
+ON CONFLICT DO NOTHING
+    ;"#;
+    sqlx::query(QUERY)
+        .bind(settlement.block_number)
+        .bind(settlement.log_index)
+        .bind(settlement.tx_hash)
+        .bind(settlement.auction_id)
+        .bind(settlement.solver)
+        .bind(settlement.reason)
+        .execute(ex)
+        .await?;
+    Ok(())
+}
+
+/// Fetches all unauthorized settlements of a solver, most recent first.
+pub async fn fetch_by_solver(
+    ex: &mut PgConnection,
+    solver: &Address,
+) -> Result<Vec<UnauthorizedSettlement>, sqlx::Error> {
+    const QUERY: &str = r#"
+SELECT * FROM unauthorized_settlements
+WHERE solver = $1
+ORDER BY block_number DESC, log_index DESC
+    ;"#;
+    sqlx::query_as(QUERY).bind(solver).fetch_all(ex).await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::byte_array::ByteArray, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_unauthorized_settlements_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let settlement = |block_number, solver, reason| UnauthorizedSettlement {
+            block_number,
+            log_index: 0,
+            tx_hash: ByteArray([block_number as u8; 32]),
+            auction_id: 1,
+            solver: ByteArray([solver; 20]),
+            reason,
+        };
+        let input = [
+            settlement(1, 1, Reason::NotParticipated),
+            settlement(2, 2, Reason::NotWinner),
+            settlement(3, 1, Reason::NotWinner),
+        ];
+        for settlement in &input {
+            insert(&mut db, settlement).await.unwrap();
+        }
+        // Inserting the same settlement again is a no-op.
+        insert(&mut db, &input[0]).await.unwrap();
+
+        let output = fetch_by_solver(&mut db, &ByteArray([1; 20])).await.unwrap();
+        assert_eq!(output, [input[2].clone(), input[0].clone()]);
+    }
+}
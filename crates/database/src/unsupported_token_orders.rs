@@ -0,0 +1,70 @@
+//! Tracks open orders that can not be settled because one of their tokens is
+//! not supported.
+
+use {
+    crate::{Address, OrderUid},
+    chrono::{DateTime, Utc},
+    sqlx::{PgConnection, QueryBuilder},
+};
+
+/// Marks the orders as unsupported because of the associated token. Orders
+/// that are already marked keep their original token and timestamp. Returns
+/// the UIDs of the orders that were not marked before.
+pub async fn insert(
+    ex: &mut PgConnection,
+    orders: &[(OrderUid, Address)],
+    timestamp: DateTime<Utc>,
+) -> Result<Vec<OrderUid>, sqlx::Error> {
+    if orders.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query_builder =
+        QueryBuilder::new("INSERT INTO unsupported_token_orders (order_uid, token, timestamp)");
+    query_builder.push_values(orders, |mut b, (uid, token)| {
+        b.push_bind(uid).push_bind(token).push_bind(timestamp);
+    });
+    query_builder.push(" ON CONFLICT (order_uid) DO NOTHING RETURNING order_uid");
+
+    query_builder.build_query_scalar().fetch_all(ex).await
+}
+
+/// Removes the mark from orders whose tokens are supported again.
+pub async fn delete(ex: &mut PgConnection, orders: &[OrderUid]) -> Result<(), sqlx::Error> {
+    const QUERY: &str = "DELETE FROM unsupported_token_orders WHERE order_uid = ANY($1)";
+    sqlx::query(QUERY).bind(orders).execute(ex).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::byte_array::ByteArray, sqlx::Connection};
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_unsupported_token_orders_roundtrip() {
+        let mut db = PgConnection::connect("postgresql://").await.unwrap();
+        let mut db = db.begin().await.unwrap();
+        crate::clear_DANGER_(&mut db).await.unwrap();
+
+        let order_a = ByteArray([1; 56]);
+        let order_b = ByteArray([2; 56]);
+        let token = ByteArray([3; 20]);
+        let now = Utc::now();
+
+        let inserted = insert(&mut db, &[(order_a, token)], now).await.unwrap();
+        assert_eq!(inserted, vec![order_a]);
+
+        // Already marked orders are not reported again.
+        let inserted = insert(&mut db, &[(order_a, token), (order_b, token)], now)
+            .await
+            .unwrap();
+        assert_eq!(inserted, vec![order_b]);
+
+        delete(&mut db, &[order_a]).await.unwrap();
+        let inserted = insert(&mut db, &[(order_a, token), (order_b, token)], now)
+            .await
+            .unwrap();
+        assert_eq!(inserted, vec![order_a]);
+    }
+}
@@ -20,6 +20,7 @@ use {
     ethrpc::block_stream::{BlockRetrieving, CurrentBlockWatcher},
     shared::{
         http_solver::model::TokenAmount,
+        recent_block_cache::CacheConfig,
         sources::balancer_v2::{
             pool_fetching::BalancerContracts,
             BalancerFactoryKind,
@@ -78,6 +79,7 @@ pub fn collector(
     eth: &Ethereum,
     block_stream: CurrentBlockWatcher,
     block_retriever: Arc<dyn BlockRetrieving>,
+    cache: CacheConfig,
     config: &infra::liquidity::config::BalancerV2,
 ) -> Box<dyn LiquidityCollecting> {
     let eth = Arc::new(eth.with_metric_label("balancerV2".into()));
@@ -87,7 +89,7 @@ pub fn collector(
         let block_stream = block_stream.clone();
         let block_retriever = block_retriever.clone();
         let config = config.clone();
-        async move { init_liquidity(&eth, &block_stream, block_retriever.clone(), &config).await }
+        async move { init_liquidity(&eth, &block_stream, block_retriever, cache, &config).await }
     };
     const TEN_MINUTES: std::time::Duration = std::time::Duration::from_secs(10 * 60);
     Box::new(BackgroundInitLiquiditySource::new(
@@ -101,6 +103,7 @@ async fn init_liquidity(
     eth: &Ethereum,
     block_stream: &CurrentBlockWatcher,
     block_retriever: Arc<dyn BlockRetrieving>,
+    cache: CacheConfig,
     config: &infra::liquidity::config::BalancerV2,
 ) -> Result<impl LiquidityCollecting> {
     let web3 = boundary::web3(eth);
@@ -174,6 +177,7 @@ async fn init_liquidity(
     };
     let token_info_fetcher = Arc::new(CachedTokenInfoFetcher::new(Arc::new(TokenInfoFetcher {
         web3: web3.clone(),
+        overrides: Default::default(),
     })));
 
     let balancer_pool_fetcher = Arc::new(
@@ -181,12 +185,13 @@ async fn init_liquidity(
             &config.graph_url,
             block_retriever.clone(),
             token_info_fetcher.clone(),
-            boundary::liquidity::cache_config(),
+            cache,
             block_stream.clone(),
-            boundary::liquidity::http_client(),
+            boundary::liquidity::http_client(eth),
             web3.clone(),
             &contracts,
             config.pool_deny_list.clone(),
+            eth.chain(),
         )
         .await
         .context("failed to create balancer pool fetcher")?,
@@ -11,7 +11,6 @@ use {
     shared::{
         baseline_solver::BaseTokens,
         current_block,
-        http_client::HttpClientFactory,
         recent_block_cache::{self, CacheConfig},
     },
     solver::{
@@ -34,21 +33,21 @@ pub mod zeroex;
 /// The default poll interval for the block stream updating task.
 const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
-/// The default pool caching configuration to use.
-fn cache_config() -> CacheConfig {
+/// The pool caching configuration to use.
+fn cache_config(config: &infra::liquidity::Config) -> CacheConfig {
     CacheConfig {
         number_of_blocks_to_cache: NonZeroU64::new(10).unwrap(),
         number_of_entries_to_auto_update: NonZeroUsize::new(1000).unwrap(),
         maximum_recent_block_age: 4,
         max_retries: 5,
         delay_between_retries: Duration::from_secs(1),
+        maximum_cached_keys: config.max_cached_pools,
     }
 }
 
 /// The default HTTP client to use for liquidity fetching.
-fn http_client() -> reqwest::Client {
-    // TODO: Should we allow `reqwest::Client` configuration here?
-    HttpClientFactory::default().create()
+fn http_client(eth: &Ethereum) -> reqwest::Client {
+    eth.http_client_factory().create()
 }
 
 pub struct Fetcher {
@@ -66,12 +65,13 @@ impl Fetcher {
 
         let block_stream = eth.current_block();
         let block_retriever = blocks.retriever(boundary::web3(eth));
+        let cache = cache_config(config);
 
         let uni_v2: Vec<_> = future::try_join_all(
             config
                 .uniswap_v2
                 .iter()
-                .map(|config| uniswap::v2::collector(eth, block_stream, config)),
+                .map(|config| uniswap::v2::collector(eth, block_stream, cache, config)),
         )
         .await?;
 
@@ -80,7 +80,7 @@ impl Fetcher {
             config
                 .swapr
                 .iter()
-                .map(|config| swapr::collector(eth, block_stream, config)),
+                .map(|config| swapr::collector(eth, block_stream, cache, config)),
         )
         .await?;
 
@@ -88,7 +88,13 @@ impl Fetcher {
             .balancer_v2
             .iter()
             .map(|config| {
-                balancer::v2::collector(eth, block_stream.clone(), block_retriever.clone(), config)
+                balancer::v2::collector(
+                    eth,
+                    block_stream.clone(),
+                    block_retriever.clone(),
+                    cache,
+                    config,
+                )
             })
             .collect();
 
@@ -5,7 +5,10 @@ use {
         infra::{self, blockchain::Ethereum},
     },
     ethrpc::block_stream::CurrentBlockWatcher,
-    shared::sources::{swapr::SwaprPoolReader, uniswap_v2::pool_fetching::DefaultPoolReader},
+    shared::{
+        recent_block_cache::CacheConfig,
+        sources::{swapr::SwaprPoolReader, uniswap_v2::pool_fetching::DefaultPoolReader},
+    },
     solver::{liquidity::ConstantProductOrder, liquidity_collector::LiquidityCollecting},
 };
 
@@ -38,16 +41,20 @@ pub fn to_domain(id: liquidity::Id, pool: ConstantProductOrder) -> Result<liquid
 pub async fn collector(
     eth: &Ethereum,
     blocks: &CurrentBlockWatcher,
+    cache: CacheConfig,
     config: &infra::liquidity::config::Swapr,
 ) -> Result<Box<dyn LiquidityCollecting>> {
     let eth = eth.with_metric_label("swapr".into());
     boundary::liquidity::uniswap::v2::collector_with_reader(
         &eth,
         blocks,
+        cache,
         &infra::liquidity::config::UniswapV2 {
             router: config.router,
             pool_code: config.pool_code,
             missing_pool_cache_time: config.missing_pool_cache_time,
+            // Swapr fees can change without emitting `Sync` events.
+            sync_events_reconciliation_blocks: None,
         },
         |web3, pair_provider| SwaprPoolReader(DefaultPoolReader::new(web3, pair_provider)),
     )
@@ -12,10 +12,12 @@ use {
     ethrpc::{block_stream::CurrentBlockWatcher, Web3},
     shared::{
         http_solver::model::TokenAmount,
+        recent_block_cache::CacheConfig,
         sources::uniswap_v2::{
             pair_provider::PairProvider,
             pool_cache::PoolCache,
-            pool_fetching::{DefaultPoolReader, PoolFetcher, PoolReading},
+            pool_fetching::{DefaultPoolReader, PoolFetcher, PoolFetching, PoolReading},
+            sync_events::SyncEventPoolFetcher,
         },
     },
     solver::{
@@ -113,15 +115,17 @@ pub fn to_interaction(
 pub async fn collector(
     eth: &Ethereum,
     blocks: &CurrentBlockWatcher,
+    cache: CacheConfig,
     config: &infra::liquidity::config::UniswapV2,
 ) -> Result<Box<dyn LiquidityCollecting>> {
     let eth = eth.with_metric_label("uniswapV2".into());
-    collector_with_reader(&eth, blocks, config, DefaultPoolReader::new).await
+    collector_with_reader(&eth, blocks, cache, config, DefaultPoolReader::new).await
 }
 
 pub(in crate::boundary::liquidity) async fn collector_with_reader<R, F>(
     eth: &Ethereum,
     blocks: &CurrentBlockWatcher,
+    cache: CacheConfig,
     config: &infra::liquidity::config::UniswapV2,
     reader: F,
 ) -> Result<Box<dyn LiquidityCollecting>>
@@ -139,17 +143,21 @@ where
             init_code_digest: config.pool_code.into(),
         };
 
-        let pool_fetcher = PoolFetcher::new(
+        let pool_fetcher: Arc<dyn PoolFetching> = Arc::new(PoolFetcher::new(
             reader(web3.clone(), pair_provider),
             web3.clone(),
             config.missing_pool_cache_time,
-        );
+        ));
+        let pool_fetcher = match config.sync_events_reconciliation_blocks {
+            Some(interval) => Arc::new(SyncEventPoolFetcher::new(
+                pool_fetcher,
+                web3.clone(),
+                interval,
+            )),
+            None => pool_fetcher,
+        };
 
-        Arc::new(PoolCache::new(
-            boundary::liquidity::cache_config(),
-            Arc::new(pool_fetcher),
-            blocks.clone(),
-        )?)
+        Arc::new(PoolCache::new(cache, pool_fetcher, blocks.clone())?)
     };
 
     Ok(Box::new(UniswapLikeLiquidity::with_allowances(
@@ -133,9 +133,10 @@ async fn init_liquidity(
         UniswapV3PoolFetcher::new(
             &config.graph_url,
             web3.clone(),
-            boundary::liquidity::http_client(),
+            boundary::liquidity::http_client(eth),
             block_retriever,
             config.max_pools_to_initialize,
+            eth.chain(),
         )
         .await
         .context("failed to initialise UniswapV3 liquidity")?,
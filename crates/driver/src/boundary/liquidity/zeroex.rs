@@ -8,11 +8,7 @@ use {
     },
     anyhow::anyhow,
     ethrpc::block_stream::CurrentBlockWatcher,
-    shared::{
-        http_client::HttpClientFactory,
-        price_estimation::gas::GAS_PER_ZEROEX_ORDER,
-        zeroex_api::DefaultZeroExApi,
-    },
+    shared::{price_estimation::gas::GAS_PER_ZEROEX_ORDER, zeroex_api::DefaultZeroExApi},
     solver::{
         liquidity::{zeroex::ZeroExLiquidity, LimitOrder},
         liquidity_collector::LiquidityCollecting,
@@ -86,15 +82,18 @@ pub async fn collector(
     let settlement = eth.contracts().settlement().clone();
     let web3 = settlement.raw_instance().web3().clone();
     let contract = contracts::IZeroEx::deployed(&web3).await?;
-    let http_client_factory = &HttpClientFactory::new(&shared::http_client::Arguments {
-        http_timeout: config.http_timeout,
-    });
-    let api = Arc::new(DefaultZeroExApi::new(
-        http_client_factory.builder(),
+    let mut api = DefaultZeroExApi::new(
+        eth.http_client_factory()
+            .builder()
+            .timeout(config.http_timeout),
         config.base_url.clone(),
         config.api_key.clone(),
         blocks.clone(),
-    )?);
+    )?;
+    if let Some(strategy) = &config.rate_limiter {
+        api = api.with_rate_limiter(strategy.clone())?;
+    }
+    let api = Arc::new(api);
     Ok(Box::new(
         ZeroExLiquidity::new(web3, api, contract, settlement, blocks).await,
     ))
@@ -32,7 +32,10 @@ pub use {
     anyhow::{Error, Result},
     contracts,
     model::order::OrderData,
-    shared::ethrpc::Web3,
+    shared::{
+        ethrpc::Web3,
+        http_client::{HttpClientFactory, ProxyRule},
+    },
 };
 
 /// Returns a Web3 instance with a trait object transport needed by various
@@ -43,26 +46,44 @@ fn web3(eth: &Ethereum) -> Web3 {
     eth.contracts().settlement().raw_instance().web3()
 }
 
+/// Builds the factory for all HTTP clients of the driver, so that all
+/// outgoing requests are routed through the configured proxies.
+pub fn http_client_factory(
+    proxy: Option<Url>,
+    proxy_overrides: Vec<ProxyRule>,
+) -> HttpClientFactory {
+    HttpClientFactory::new(&shared::http_client::Arguments {
+        http_timeout: std::time::Duration::from_secs(10),
+        http_proxy: proxy,
+        http_proxy_overrides: proxy_overrides,
+        http_cache_size: shared::http_client::DEFAULT_CACHE_SIZE,
+        http_cache_dir: None,
+    })
+}
+
 /// Builds a web3 client that buffers requests and sends them in a
-/// batch call.
-pub fn buffered_web3_client(ethrpc: &Url) -> Web3 {
-    web3_client(ethrpc, 20, 10)
+/// batch call. Fails over to the fallback nodes in order.
+pub fn buffered_web3_client(ethrpc: &Url, fallbacks: &[Url], http: &HttpClientFactory) -> Web3 {
+    web3_client(ethrpc, fallbacks, 20, 10, http)
 }
 
 /// Builds a web3 client that sends requests one by one.
-pub fn unbuffered_web3_client(ethrpc: &Url) -> Web3 {
-    web3_client(ethrpc, 0, 0)
+pub fn unbuffered_web3_client(ethrpc: &Url, http: &HttpClientFactory) -> Web3 {
+    web3_client(ethrpc, &[], 0, 0, http)
 }
 
-fn web3_client(ethrpc: &Url, max_batch_size: usize, max_concurrent_requests: usize) -> Web3 {
+fn web3_client(
+    ethrpc: &Url,
+    fallbacks: &[Url],
+    max_batch_size: usize,
+    max_concurrent_requests: usize,
+    http: &HttpClientFactory,
+) -> Web3 {
     let ethrpc_args = shared::ethrpc::Arguments {
         ethrpc_max_batch_size: max_batch_size,
         ethrpc_max_concurrent_requests: max_concurrent_requests,
         ethrpc_batch_delay: Default::default(),
+        budget: Default::default(),
     };
-    let http_factory =
-        shared::http_client::HttpClientFactory::new(&shared::http_client::Arguments {
-            http_timeout: std::time::Duration::from_secs(10),
-        });
-    shared::ethrpc::web3(&ethrpc_args, &http_factory, ethrpc, "base")
+    shared::ethrpc::web3_with_fallbacks(&ethrpc_args, http, ethrpc, fallbacks, "base")
 }
@@ -8,7 +8,7 @@ use {
             time,
         },
         infra::{self, blockchain, config::file::OrderPriorityStrategy, observe, Ethereum},
-        util::{self, Bytes},
+        util::Bytes,
     },
     chrono::{Duration, Utc},
     futures::future::{join_all, BoxFuture, FutureExt, Shared},
@@ -25,7 +25,7 @@ use {
 /// An auction is a set of orders that can be solved. The solvers calculate
 /// [`super::solution::Solution`]s by picking subsets of these orders and
 /// solving them.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Auction {
     /// See the [`Self::id`] method.
     id: Option<Id>,
@@ -262,8 +262,12 @@ impl AuctionProcessor {
             //    following: `available + (fee * available / sell) <= allocated_balance`
             if let order::Partial::Yes { available } = &mut order.partial {
                 *available = order::TargetAmount(
-                    util::math::mul_ratio(available.0, allocated_balance.0, max_sell.0)
-                        .unwrap_or_default(),
+                    number::conversions::mul_ratio_floor(
+                        available.0,
+                        allocated_balance.0,
+                        max_sell.0,
+                    )
+                    .unwrap_or_default(),
                 );
             }
             if order.available().is_zero() {
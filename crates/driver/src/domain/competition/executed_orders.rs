@@ -0,0 +1,374 @@
+//! Protection against executing orders again that were just executed.
+//!
+//! The autopilot only stops sending an order once it indexed its settlement.
+//! Until then, and whenever a settlement whose submission timed out still gets
+//! included or a settlement gets reorged, auctions can contain orders that
+//! were already executed. Including them again wastes gas on reverting
+//! settlements. So orders get tracked from the moment their settlement gets
+//! submitted and stay out of auctions until the onchain state confirms that
+//! the amounts the auction offers are still fillable.
+
+use {
+    crate::{
+        domain::{
+            competition::{order, Auction},
+            eth,
+            BlockNo,
+        },
+        infra::{observe::metrics, solver, Ethereum},
+        util::serialize,
+    },
+    futures::future::join_all,
+    serde::{Deserialize, Serialize},
+    serde_with::serde_as,
+    std::{
+        collections::HashMap,
+        future::Future,
+        path::PathBuf,
+        sync::Mutex,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// File the tracked orders get persisted to so that they survive
+    /// restarts. They are only kept in memory if not set.
+    pub path: Option<PathBuf>,
+    /// How long orders are tracked at most.
+    pub horizon: Duration,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Entry {
+    /// Last block in which the settlement may have been submitted.
+    submission_deadline: BlockNo,
+    /// Seconds since the unix epoch at which the settlement got submitted.
+    submitted_at: u64,
+    /// Whether the order was already counted as a prevented duplicate
+    /// execution.
+    counted: bool,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedEntry {
+    #[serde_as(as = "serialize::Hex")]
+    uid: [u8; order::UID_LEN],
+    submission_deadline: BlockNo,
+    submitted_at: u64,
+}
+
+/// Orders whose settlements were recently submitted.
+#[derive(Debug)]
+pub struct Tracker {
+    config: Config,
+    entries: Mutex<HashMap<order::Uid, Entry>>,
+    /// Serializes writes of the persisted file.
+    persisting: tokio::sync::Mutex<()>,
+}
+
+impl Tracker {
+    /// Creates a tracker with the orders persisted by a previous run.
+    pub async fn load(config: Config) -> Self {
+        let entries = match &config.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(content) => serde_json::from_slice::<Vec<PersistedEntry>>(&content)
+                    .inspect_err(|err| tracing::warn!(?err, ?path, "corrupt executed orders"))
+                    .unwrap_or_default(),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Default::default(),
+                Err(err) => {
+                    tracing::warn!(?err, ?path, "failed to read executed orders");
+                    Default::default()
+                }
+            },
+            None => Default::default(),
+        };
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                (
+                    order::Uid(entry.uid.into()),
+                    Entry {
+                        submission_deadline: entry.submission_deadline,
+                        submitted_at: entry.submitted_at,
+                        counted: false,
+                    },
+                )
+            })
+            .collect();
+        Self {
+            config,
+            entries: Mutex::new(entries),
+            persisting: Default::default(),
+        }
+    }
+
+    /// Starts tracking the orders of a settlement that is about to be
+    /// submitted.
+    pub async fn record(
+        &self,
+        uids: impl IntoIterator<Item = order::Uid>,
+        submission_deadline: BlockNo,
+    ) {
+        let entry = Entry {
+            submission_deadline,
+            submitted_at: now(),
+            counted: false,
+        };
+        {
+            let mut entries = self.entries.lock().unwrap();
+            for uid in uids {
+                entries.insert(uid, entry);
+            }
+        }
+        self.persist().await;
+    }
+
+    /// Removes the orders from the auction that were recently executed and
+    /// can't be confirmed to still be fillable.
+    pub async fn filter_auction(
+        &self,
+        mut auction: Auction,
+        eth: &Ethereum,
+        solver: &solver::Name,
+    ) -> Auction {
+        let orders = auction
+            .orders
+            .iter()
+            .map(|order| (order.uid, order.target(), order.partial));
+        let block = eth.current_block().borrow().number;
+        let blocked = self
+            .blocked(orders, block, |uid| eth.filled_amount(uid))
+            .await;
+        if !blocked.is_empty() {
+            auction.orders.retain(|order| !blocked.contains(&order.uid));
+            tracing::debug!(%solver, orders = ?blocked, "ignored recently executed orders");
+        }
+        auction
+    }
+
+    /// Returns the orders that have to stay out of the auction and stops
+    /// tracking the ones that are confirmed to be fillable again. Orders whose
+    /// filled amount can't be fetched stay blocked.
+    async fn blocked<F, E>(
+        &self,
+        orders: impl Iterator<Item = (order::Uid, order::TargetAmount, order::Partial)>,
+        block: BlockNo,
+        filled_amount: impl Fn(order::Uid) -> F,
+    ) -> Vec<order::Uid>
+    where
+        F: Future<Output = Result<eth::U256, E>>,
+        E: std::fmt::Debug,
+    {
+        let tracked: Vec<_> = {
+            let mut entries = self.entries.lock().unwrap();
+            let horizon = self.config.horizon.as_secs();
+            let now = now();
+            entries.retain(|_, entry| entry.submitted_at.saturating_add(horizon) > now);
+            orders
+                .filter_map(|(uid, target, partial)| {
+                    Some((uid, target, partial, *entries.get(&uid)?))
+                })
+                .collect()
+        };
+        if tracked.is_empty() {
+            return Default::default();
+        }
+
+        let checks = tracked.into_iter().map(|(uid, target, partial, entry)| {
+            let filled_amount = &filled_amount;
+            async move {
+                // The settlement can still get included.
+                if block <= entry.submission_deadline {
+                    return (uid, false);
+                }
+                match filled_amount(uid).await {
+                    Ok(filled) => (uid, is_fillable(target, partial, filled)),
+                    Err(err) => {
+                        tracing::warn!(?err, ?uid, "failed to fetch filled amount");
+                        (uid, false)
+                    }
+                }
+            }
+        });
+        let (released, blocked): (Vec<_>, Vec<_>) = join_all(checks)
+            .await
+            .into_iter()
+            .partition(|(_, fillable)| *fillable);
+
+        let newly_blocked = {
+            let mut entries = self.entries.lock().unwrap();
+            for (uid, _) in &released {
+                entries.remove(uid);
+            }
+            blocked
+                .iter()
+                .filter_map(|(uid, _)| entries.get_mut(uid))
+                .filter(|entry| !std::mem::replace(&mut entry.counted, true))
+                .count()
+        };
+        if !released.is_empty() {
+            self.persist().await;
+        }
+        metrics::get()
+            .prevented_duplicate_executions
+            .inc_by(newly_blocked.try_into().unwrap_or(u64::MAX));
+        blocked.into_iter().map(|(uid, _)| uid).collect()
+    }
+
+    async fn persist(&self) {
+        let Some(path) = &self.config.path else {
+            return;
+        };
+        let _guard = self.persisting.lock().await;
+        let entries: Vec<_> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(uid, entry)| PersistedEntry {
+                uid: uid.0 .0,
+                submission_deadline: entry.submission_deadline,
+                submitted_at: entry.submitted_at,
+            })
+            .collect();
+        let content = serde_json::to_vec(&entries).expect("entries serialize to json");
+        // Write to a temporary file first so that a crash doesn't leave a
+        // truncated file behind.
+        let tmp = path.with_extension("tmp");
+        let result = async {
+            tokio::fs::write(&tmp, content).await?;
+            tokio::fs::rename(&tmp, path).await
+        }
+        .await;
+        if let Err(err) = result {
+            tracing::warn!(?err, ?path, "failed to persist executed orders");
+        }
+    }
+}
+
+/// Whether the amounts the auction offers of an order are still fillable
+/// according to the amount that was filled onchain.
+fn is_fillable(target: order::TargetAmount, partial: order::Partial, filled: eth::U256) -> bool {
+    match partial {
+        order::Partial::No => filled.is_zero(),
+        order::Partial::Yes { available } => available.0 <= target.0.saturating_sub(filled),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_remaining_fillable_amounts() {
+        let target = order::TargetAmount(100.into());
+        let partial = |available: u64| order::Partial::Yes {
+            available: order::TargetAmount(available.into()),
+        };
+
+        // Fill-or-kill orders have to be unfilled.
+        assert!(is_fillable(target, order::Partial::No, 0.into()));
+        assert!(!is_fillable(target, order::Partial::No, 100.into()));
+        // Partially fillable orders must not offer more than what remains.
+        assert!(is_fillable(target, partial(60), 40.into()));
+        assert!(is_fillable(target, partial(50), 40.into()));
+        assert!(!is_fillable(target, partial(100), 40.into()));
+        assert!(!is_fillable(target, partial(1), 100.into()));
+    }
+
+    #[tokio::test]
+    async fn blocks_orders_until_confirmed_fillable() {
+        let tracker = Tracker::load(Config {
+            path: None,
+            horizon: Duration::from_secs(60),
+        })
+        .await;
+        let uid = |byte: u8| order::Uid([byte; order::UID_LEN].into());
+        let target = order::TargetAmount(100.into());
+        let orders = || {
+            [1, 2, 3, 4]
+                .map(|byte| (uid(byte), target, order::Partial::No))
+                .into_iter()
+        };
+        tracker.record([uid(1)], 10).await;
+        tracker.record([uid(2), uid(3)], 5).await;
+        let filled_amount = |filled: HashMap<order::Uid, u64>| {
+            move |uid: order::Uid| {
+                let filled = filled.get(&uid).copied();
+                async move { filled.map(eth::U256::from).ok_or("rpc failed") }
+            }
+        };
+
+        // Settlements that can still get included keep their orders blocked
+        // without checking the onchain state.
+        let blocked = tracker
+            .blocked(orders(), 5, |_| async { Err::<eth::U256, _>("unexpected") })
+            .await;
+        assert_eq!(blocked.len(), 3);
+
+        // Orders confirmed to be unfilled get released, the ones that were
+        // filled or can't be checked stay blocked.
+        let blocked = tracker
+            .blocked(
+                orders(),
+                11,
+                filled_amount(HashMap::from([(uid(1), 0), (uid(2), 100)])),
+            )
+            .await;
+        assert_eq!(blocked, vec![uid(2), uid(3)]);
+        let entries = tracker.entries.lock().unwrap();
+        assert!(!entries.contains_key(&uid(1)));
+        assert!(entries.contains_key(&uid(2)));
+        assert!(entries.contains_key(&uid(3)));
+        // Every order got counted once when it was first blocked.
+        assert!(entries.values().all(|entry| entry.counted));
+    }
+
+    #[tokio::test]
+    async fn stops_tracking_orders_after_horizon() {
+        let tracker = Tracker::load(Config {
+            path: None,
+            horizon: Duration::ZERO,
+        })
+        .await;
+        let uid = order::Uid([1; order::UID_LEN].into());
+        tracker.record([uid], 10).await;
+
+        let orders = [(uid, order::TargetAmount(1.into()), order::Partial::No)];
+        let blocked = tracker
+            .blocked(orders.into_iter(), 0, |_| async {
+                Err::<eth::U256, _>("unexpected")
+            })
+            .await;
+        assert!(blocked.is_empty());
+        assert!(tracker.entries.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn persists_tracked_orders() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            path: Some(dir.path().join("executed_orders.json")),
+            horizon: Duration::from_secs(60),
+        };
+        let uid = order::Uid([1; order::UID_LEN].into());
+
+        let tracker = Tracker::load(config.clone()).await;
+        tracker.record([uid], 10).await;
+
+        let reloaded = Tracker::load(config).await;
+        let entries = reloaded.entries.lock().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[&uid].submission_deadline, 10);
+    }
+}
@@ -31,7 +31,9 @@ use {
 
 pub mod auction;
 pub mod bad_tokens;
+pub mod executed_orders;
 pub mod order;
+pub mod shadow;
 pub mod solution;
 mod sorting;
 
@@ -58,6 +60,8 @@ pub struct Competition {
     /// Cached solutions with the most recent solutions at the front.
     pub settlements: Mutex<VecDeque<Settlement>>,
     pub bad_tokens: Arc<bad_tokens::Detector>,
+    /// Orders of recently submitted settlements, shared by all solvers.
+    pub executed_orders: Arc<executed_orders::Tracker>,
     settle_queue: mpsc::Sender<SettleRequest>,
 }
 
@@ -69,6 +73,7 @@ impl Competition {
         simulator: Simulator,
         mempools: Mempools,
         bad_tokens: Arc<bad_tokens::Detector>,
+        executed_orders: Arc<executed_orders::Tracker>,
     ) -> Arc<Self> {
         let (settle_sender, settle_receiver) = mpsc::channel(solver.settle_queue_size());
 
@@ -81,6 +86,7 @@ impl Competition {
             settlements: Default::default(),
             settle_queue: settle_sender,
             bad_tokens,
+            executed_orders,
         });
 
         let competition_clone = Arc::clone(&competition);
@@ -95,11 +101,16 @@ impl Competition {
 
     /// Solve an auction as part of this competition.
     pub async fn solve(&self, auction: Auction) -> Result<Option<Solved>, Error> {
-        let auction = &self
+        let auction = self
             .bad_tokens
             .filter_unsupported_orders_in_auction(auction)
             .await;
+        let auction = &self
+            .executed_orders
+            .filter_auction(auction, &self.eth, self.solver.name())
+            .await;
 
+        let stage = infra::memory::Stage::start("liquidity");
         let liquidity = match self.solver.liquidity() {
             solver::Liquidity::Fetch => {
                 self.liquidity
@@ -111,9 +122,34 @@ impl Competition {
             }
             solver::Liquidity::Skip => Default::default(),
         };
+        stage.finish(self.solver.name());
+
+        // Replay the auction to the shadow engine unless it is still busy with
+        // previous auctions. It only learns the score of the solver once the
+        // solver returned its solutions.
+        let solver_score = self.solver.shadow().and_then(|shadow| {
+            let Some(slot) = shadow.slots.try_reserve() else {
+                observe::shadow_skipped(self.solver.name(), auction.id());
+                return None;
+            };
+            let (sender, receiver) = oneshot::channel();
+            tokio::spawn(
+                shadow::compete(
+                    self.solver.name().clone(),
+                    shadow.solver.clone(),
+                    auction.clone(),
+                    liquidity.clone(),
+                    receiver,
+                    slot,
+                )
+                .in_current_span(),
+            );
+            Some(sender)
+        });
 
         // Fetch the solutions from the solver.
-        let solutions = self
+        let stage = infra::memory::Stage::start("solving");
+        let (solutions, timings) = self
             .solver
             .solve(auction, &liquidity)
             .await
@@ -122,8 +158,16 @@ impl Competition {
                     notify::solver_timeout(&self.solver, auction.id());
                 }
             })?;
+        stage.finish(self.solver.name());
+
+        // Compare the solutions as proposed by both engines, i.e. before merging
+        // and simulation which only the solver's solutions go through.
+        if let Some(sender) = solver_score {
+            let _ = sender.send(shadow::best_score(&solutions, auction));
+        }
 
         observe::postprocessing(&solutions, auction.deadline().driver());
+        let stage = infra::memory::Stage::start("postprocessing");
 
         // Discard solutions that don't have unique ID.
         let mut ids = HashSet::new();
@@ -166,6 +210,7 @@ impl Competition {
                         &self.eth,
                         &self.simulator,
                         self.solver.solver_native_token(),
+                        self.solver.interaction_optimization(),
                     )
                     .await;
                 (id, token_pairs, settlement)
@@ -246,6 +291,7 @@ impl Competition {
         for (score, settlement) in scores.iter() {
             observe::score(settlement, score);
         }
+        stage.finish(self.solver.name());
 
         // Pick the best-scoring settlement.
         let (mut score, settlement) = scores
@@ -259,6 +305,7 @@ impl Competition {
                         trades: settlement.orders(),
                         prices: settlement.prices(),
                         gas: Some(settlement.gas.estimate),
+                        timings,
                     },
                     settlement,
                 )
@@ -440,6 +487,11 @@ impl Competition {
                 .ok_or(Error::SolutionNotAvailable)?
         };
 
+        // Track the orders before submitting, as the settlement can get
+        // included even if the submission fails or times out.
+        self.executed_orders
+            .record(settlement.orders().into_keys(), submission_deadline)
+            .await;
         let executed = self
             .mempools
             .execute(&self.solver, &settlement, submission_deadline)
@@ -552,6 +604,8 @@ pub struct Solved {
     pub trades: HashMap<order::Uid, Amounts>,
     pub prices: HashMap<eth::TokenAddress, eth::TokenAmount>,
     pub gas: Option<eth::Gas>,
+    /// Phase timings reported by the solver engine.
+    pub timings: Option<solver::Timings>,
 }
 
 #[derive(Debug)]
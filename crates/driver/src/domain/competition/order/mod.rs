@@ -9,6 +9,7 @@ use {
     derive_more::{From, Into},
     model::order::{BuyTokenDestination, SellTokenSource},
     num::CheckedDiv,
+    number::conversions::{mul_ratio_ceil, mul_ratio_floor},
 };
 pub use {fees::FeePolicy, signature::Signature};
 
@@ -151,14 +152,13 @@ impl Order {
         };
         let target = self.target();
 
-        amounts.sell.amount = util::math::mul_ratio(amounts.sell.amount.0, available.0, target.0)
+        amounts.sell.amount = mul_ratio_floor(amounts.sell.amount.0, available.0, target.0)
             .unwrap_or_default()
             .into();
 
-        amounts.buy.amount =
-            util::math::mul_ratio_ceil(amounts.buy.amount.0, available.0, target.0)
-                .unwrap_or_default()
-                .into();
+        amounts.buy.amount = mul_ratio_ceil(amounts.buy.amount.0, available.0, target.0)
+            .unwrap_or_default()
+            .into();
 
         amounts
     }
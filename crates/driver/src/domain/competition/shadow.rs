@@ -0,0 +1,217 @@
+//! Shadow competitions between a solver and a candidate engine.
+//!
+//! The shadow engine receives the same auctions as the solver it shadows. Its
+//! solutions get scored like the ones of the solver but never get encoded,
+//! simulated or submitted. Comparing the best scores of both engines shows how
+//! often the candidate engine would have beaten the current one.
+
+use {
+    super::{Auction, Solution},
+    crate::{
+        domain::{eth, liquidity, time::Remaining},
+        infra::{
+            observe,
+            solver::{self, Solver},
+        },
+    },
+    std::{cmp::Ordering, future::Future, num::NonZeroUsize, sync::Arc},
+    tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore},
+};
+
+/// How the shadow engine did compared to the solver it shadows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The shadow engine found a better solution.
+    Won,
+    /// Both engines found equally good solutions.
+    Tied,
+    /// The solver found a better solution.
+    Lost,
+    /// The shadow engine didn't find any scorable solution.
+    NoSolution,
+}
+
+impl Outcome {
+    fn new(solver: Option<eth::Ether>, shadow: Option<eth::Ether>) -> Self {
+        match (solver, shadow) {
+            (_, None) => Self::NoSolution,
+            (None, Some(_)) => Self::Won,
+            (Some(solver), Some(shadow)) => match shadow.cmp(&solver) {
+                Ordering::Greater => Self::Won,
+                Ordering::Equal => Self::Tied,
+                Ordering::Less => Self::Lost,
+            },
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Won => "won",
+            Self::Tied => "tied",
+            Self::Lost => "lost",
+            Self::NoSolution => "no_solution",
+        }
+    }
+}
+
+/// Bounds how many auctions a shadow engine solves at once.
+#[derive(Debug, Clone)]
+pub struct Slots(Arc<Semaphore>);
+
+impl Slots {
+    pub fn new(max: NonZeroUsize) -> Self {
+        Self(Arc::new(Semaphore::new(max.get())))
+    }
+
+    /// Reserves a slot for replaying an auction. Returns `None` if the shadow
+    /// engine is already solving as many auctions as it may.
+    pub fn try_reserve(&self) -> Option<Slot> {
+        self.0
+            .clone()
+            .try_acquire_owned()
+            .ok()
+            .map(|permit| Slot { _permit: permit })
+    }
+}
+
+/// A reserved slot, freed once dropped.
+#[derive(Debug)]
+pub struct Slot {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// The best score of the solutions as proposed by the engine, i.e. before they
+/// get merged, encoded and simulated. Both engines get scored this way so that
+/// the comparison isn't skewed by the post-processing only the solver gets.
+pub fn best_score(solutions: &[Solution], auction: &Auction) -> Option<eth::Ether> {
+    solutions
+        .iter()
+        .filter_map(|solution| {
+            solution
+                .scoring(
+                    &auction.prices(),
+                    auction.surplus_capturing_jit_order_owners(),
+                )
+                .ok()
+        })
+        .max()
+}
+
+/// Solves the auction with the shadow engine and compares the best score of
+/// its solutions with the best score of the solver. The solver's score gets
+/// sent through `solver_score` once it is known; if the sender is dropped the
+/// solver is assumed to not have found a solution. The shadow engine has until
+/// the driver deadline to solve the auction, and `slot` stays reserved until
+/// it is done.
+pub async fn compete(
+    solver: solver::Name,
+    shadow: Solver,
+    auction: Auction,
+    liquidity: Vec<liquidity::Liquidity>,
+    solver_score: oneshot::Receiver<Option<eth::Ether>>,
+    slot: Slot,
+) {
+    let shadow_score = async {
+        let (solutions, _) = shadow.solve(&auction, &liquidity).await?;
+        Ok::<_, solver::Error>(best_score(&solutions, &auction))
+    };
+    let timeout = auction.deadline().driver().remaining().unwrap_or_default();
+    let result = tokio::time::timeout(timeout, compare(shadow_score, solver_score)).await;
+    drop(slot);
+
+    match result {
+        Ok(Ok((outcome, solver_score, shadow_score))) => {
+            observe::shadow_outcome(&solver, auction.id(), outcome, solver_score, shadow_score)
+        }
+        Ok(Err(err)) => observe::shadow_failed(&solver, auction.id(), &err),
+        Err(_) => observe::shadow_timed_out(&solver, auction.id()),
+    }
+}
+
+/// Waits for the scores of both engines and compares them.
+async fn compare<E>(
+    shadow_score: impl Future<Output = Result<Option<eth::Ether>, E>>,
+    solver_score: oneshot::Receiver<Option<eth::Ether>>,
+) -> Result<(Outcome, Option<eth::Ether>, Option<eth::Ether>), E> {
+    let shadow_score = shadow_score.await?;
+    let solver_score = solver_score.await.ok().flatten();
+    Ok((
+        Outcome::new(solver_score, shadow_score),
+        solver_score,
+        shadow_score,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcomes() {
+        let score = |score: u64| Some(eth::Ether(score.into()));
+        assert_eq!(Outcome::new(score(1), score(2)), Outcome::Won);
+        assert_eq!(Outcome::new(None, score(1)), Outcome::Won);
+        assert_eq!(Outcome::new(score(2), score(2)), Outcome::Tied);
+        assert_eq!(Outcome::new(score(2), score(1)), Outcome::Lost);
+        assert_eq!(Outcome::new(score(1), None), Outcome::NoSolution);
+        assert_eq!(Outcome::new(None, None), Outcome::NoSolution);
+    }
+
+    #[test]
+    fn slots_bound_concurrent_auctions() {
+        let slots = Slots::new(NonZeroUsize::new(2).unwrap());
+        let first = slots.try_reserve().unwrap();
+        let _second = slots.try_reserve().unwrap();
+        assert!(slots.try_reserve().is_none());
+
+        drop(first);
+        assert!(slots.try_reserve().is_some());
+    }
+
+    #[tokio::test]
+    async fn compares_scores_of_both_engines() {
+        let score = |score: u64| Some(eth::Ether(score.into()));
+
+        let (sender, receiver) = oneshot::channel();
+        sender.send(score(1)).unwrap();
+        let result = compare(async { Ok::<_, ()>(score(2)) }, receiver).await;
+        assert_eq!(result, Ok((Outcome::Won, score(1), score(2))));
+
+        let (sender, receiver) = oneshot::channel();
+        sender.send(score(3)).unwrap();
+        let result = compare(async { Ok::<_, ()>(score(2)) }, receiver).await;
+        assert_eq!(result, Ok((Outcome::Lost, score(3), score(2))));
+    }
+
+    #[tokio::test]
+    async fn dropped_solver_score_counts_as_no_solution() {
+        let score = Some(eth::Ether(1.into()));
+        let (sender, receiver) = oneshot::channel();
+        drop(sender);
+        let result = compare(async { Ok::<_, ()>(score) }, receiver).await;
+        assert_eq!(result, Ok((Outcome::Won, None, score)));
+    }
+
+    #[tokio::test]
+    async fn shadow_errors_are_reported() {
+        let (_sender, receiver) = oneshot::channel();
+        let result = compare(async { Err::<Option<eth::Ether>, _>("failed") }, receiver).await;
+        assert_eq!(result, Err("failed"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_solver_score() {
+        let score = |score: u64| Some(eth::Ether(score.into()));
+        let (sender, receiver) = oneshot::channel();
+        let comparison = tokio::spawn(compare(async { Ok::<_, ()>(score(2)) }, receiver));
+
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        assert!(!comparison.is_finished());
+
+        sender.send(score(2)).unwrap();
+        assert_eq!(
+            comparison.await.unwrap(),
+            Ok((Outcome::Tied, score(2), score(2)))
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! ERC-3009 transfer authorizations.
+//!
+//! Tokens implementing ERC-3009 (like USDC) let the recipient of a transfer
+//! that the token holder signed off-chain execute it. Solvers use this to move
+//! JIT liquidity into the settlement contract without the holder setting an
+//! allowance first. Authorizations get executed with `receiveWithAuthorization`
+//! as pre-interactions of the settlement. Unlike `transferWithAuthorization`,
+//! only the settlement contract can execute them, so they can't be front-run
+//! to make the settlement revert. The driver checks upfront that they can't
+//! make it revert otherwise.
+//!
+//! Every nonce can only be used once per token holder. Nonces that were
+//! already used or cancelled onchain and nonces used more than once within the
+//! same solution get rejected.
+//!
+//! https://eips.ethereum.org/EIPS/eip-3009
+
+use {
+    crate::{domain::eth, infra, util::Bytes},
+    ethabi::Token,
+    model::{
+        signature::{EcdsaSignature, EcdsaSigningScheme},
+        DomainSeparator,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        time::Duration,
+    },
+    web3::signing::keccak256,
+};
+
+/// How long an authorization has to stay valid after the solution was
+/// received, so the settlement has enough time to get mined.
+const MIN_REMAINING_VALIDITY: Duration = Duration::from_secs(120);
+
+/// Tokens whose authorizations solvers may use together with the EIP-712
+/// domain separators the authorizations are signed for.
+#[derive(Clone, Debug, Default)]
+pub struct Tokens(HashMap<eth::TokenAddress, eth::DomainSeparator>);
+
+/// A token supporting ERC-3009 and the EIP-712 domain it uses.
+#[derive(Clone, Debug)]
+pub struct TokenDomain {
+    pub address: eth::TokenAddress,
+    pub name: String,
+    pub version: String,
+}
+
+impl Tokens {
+    pub fn new(chain_id: u64, tokens: impl IntoIterator<Item = TokenDomain>) -> Self {
+        Self(
+            tokens
+                .into_iter()
+                .map(|token| {
+                    let domain_separator = domain_separator(chain_id, &token);
+                    (token.address, domain_separator)
+                })
+                .collect(),
+        )
+    }
+}
+
+fn domain_separator(chain_id: u64, token: &TokenDomain) -> eth::DomainSeparator {
+    let type_hash = keccak256(
+        b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+    );
+    eth::DomainSeparator(keccak256(&ethabi::encode(&[
+        Token::FixedBytes(type_hash.to_vec()),
+        Token::FixedBytes(keccak256(token.name.as_bytes()).to_vec()),
+        Token::FixedBytes(keccak256(token.version.as_bytes()).to_vec()),
+        Token::Uint(chain_id.into()),
+        Token::Address(token.address.0 .0),
+    ])))
+}
+
+/// A signed `receiveWithAuthorization` call.
+#[derive(Clone, Debug)]
+pub struct TransferAuthorization {
+    pub token: eth::TokenAddress,
+    pub from: eth::Address,
+    pub to: eth::Address,
+    pub value: eth::TokenAmount,
+    /// Unix timestamp after which the authorization can be used.
+    pub valid_after: u64,
+    /// Unix timestamp before which the authorization has to be used.
+    pub valid_before: u64,
+    pub nonce: eth::H256,
+    pub signature: EcdsaSignature,
+}
+
+impl TransferAuthorization {
+    fn struct_hash(&self) -> [u8; 32] {
+        let type_hash = keccak256(
+            b"ReceiveWithAuthorization(address from,address to,uint256 value,uint256 \
+              validAfter,uint256 validBefore,bytes32 nonce)",
+        );
+        keccak256(&ethabi::encode(&[
+            Token::FixedBytes(type_hash.to_vec()),
+            Token::Address(self.from.0),
+            Token::Address(self.to.0),
+            Token::Uint(self.value.0),
+            Token::Uint(self.valid_after.into()),
+            Token::Uint(self.valid_before.into()),
+            Token::FixedBytes(self.nonce.0.to_vec()),
+        ]))
+    }
+
+    /// Checks that the settlement contract can execute the authorization at
+    /// the unix timestamp `now`.
+    fn validate(
+        &self,
+        tokens: &Tokens,
+        settlement: eth::ContractAddress,
+        now: u64,
+    ) -> Result<(), Error> {
+        let domain_separator = tokens
+            .0
+            .get(&self.token)
+            .ok_or(Error::UnsupportedToken(self.token))?;
+        // Only the recipient can execute the authorization.
+        if self.to.0 != settlement.0 {
+            return Err(Error::InvalidRecipient);
+        }
+        if self.valid_after >= now {
+            return Err(Error::NotYetValid);
+        }
+        if self.valid_before < now.saturating_add(MIN_REMAINING_VALIDITY.as_secs()) {
+            return Err(Error::Expired);
+        }
+        let recovered = self
+            .signature
+            .recover(
+                EcdsaSigningScheme::Eip712,
+                &DomainSeparator(domain_separator.0),
+                &self.struct_hash(),
+            )
+            .map_err(|_| Error::InvalidSignature)?;
+        if recovered.signer != self.from.0 {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// The pre-interaction executing the authorization.
+    pub fn interaction(&self) -> eth::Interaction {
+        let selector = &keccak256(
+            b"receiveWithAuthorization(address,address,uint256,uint256,uint256,bytes32,uint8,\
+              bytes32,bytes32)",
+        )[..4];
+        let params = ethabi::encode(&[
+            Token::Address(self.from.0),
+            Token::Address(self.to.0),
+            Token::Uint(self.value.0),
+            Token::Uint(self.valid_after.into()),
+            Token::Uint(self.valid_before.into()),
+            Token::FixedBytes(self.nonce.0.to_vec()),
+            Token::Uint(self.signature.v.into()),
+            Token::FixedBytes(self.signature.r.0.to_vec()),
+            Token::FixedBytes(self.signature.s.0.to_vec()),
+        ]);
+        eth::Interaction {
+            target: self.token.0 .0.into(),
+            value: eth::Ether(0.into()),
+            call_data: Bytes([selector, &params].concat()),
+        }
+    }
+
+    /// Whether the nonce was already used or cancelled.
+    async fn is_used(&self, eth: &infra::Ethereum) -> Result<bool, Error> {
+        let selector = &keccak256(b"authorizationState(address,bytes32)")[..4];
+        let params = ethabi::encode(&[
+            Token::Address(self.from.0),
+            Token::FixedBytes(self.nonce.0.to_vec()),
+        ]);
+        let output = eth
+            .web3()
+            .eth()
+            .call(
+                web3::types::CallRequest {
+                    to: Some(self.token.0 .0),
+                    data: Some([selector, &params].concat().into()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .map_err(|err| Error::State(err.to_string()))?;
+        ethabi::decode(&[ethabi::ParamType::Bool], &output.0)
+            .ok()
+            .and_then(|tokens| tokens.into_iter().next()?.into_bool())
+            .ok_or_else(|| Error::State("invalid authorizationState output".to_owned()))
+    }
+}
+
+/// Checks that none of the authorizations were already used or cancelled by
+/// querying their `authorizationState` onchain.
+pub async fn check_unused(
+    authorizations: &[TransferAuthorization],
+    eth: &infra::Ethereum,
+) -> Result<(), Error> {
+    let states = futures::future::try_join_all(
+        authorizations
+            .iter()
+            .map(|authorization| authorization.is_used(eth)),
+    )
+    .await?;
+    match authorizations.iter().zip(states).find(|(_, used)| *used) {
+        Some((authorization, _)) => Err(Error::NonceUsed(authorization.nonce)),
+        None => Ok(()),
+    }
+}
+
+/// Checks that all authorizations of a solution can be executed by the
+/// settlement contract at the unix timestamp `now`.
+pub fn validate(
+    authorizations: &[TransferAuthorization],
+    tokens: &Tokens,
+    settlement: eth::ContractAddress,
+    now: u64,
+) -> Result<(), Error> {
+    let mut nonces = HashSet::new();
+    for authorization in authorizations {
+        authorization.validate(tokens, settlement, now)?;
+        if !nonces.insert((authorization.token, authorization.from, authorization.nonce)) {
+            return Err(Error::DuplicateNonce(authorization.nonce));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("ERC-3009 authorizations are not enabled for token {0:?}")]
+    UnsupportedToken(eth::TokenAddress),
+    #[error("authorization does not transfer to the settlement contract")]
+    InvalidRecipient,
+    #[error("authorization is not valid yet")]
+    NotYetValid,
+    #[error("authorization expires before the settlement can be mined")]
+    Expired,
+    #[error("authorization is not signed by the token holder")]
+    InvalidSignature,
+    #[error("authorization nonce {0:?} is used more than once")]
+    DuplicateNonce(eth::H256),
+    #[error("authorization nonce {0:?} was already used or cancelled")]
+    NonceUsed(eth::H256),
+    #[error("could not fetch the authorization state: {0}")]
+    State(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex, secp256k1::SecretKey, web3::signing::SecretKeyRef};
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn settlement() -> eth::ContractAddress {
+        eth::H160([0x90; 20]).into()
+    }
+
+    fn usdc() -> TokenDomain {
+        TokenDomain {
+            address: eth::TokenAddress(eth::H160([0xa0; 20]).into()),
+            name: "USD Coin".to_string(),
+            version: "2".to_string(),
+        }
+    }
+
+    fn signed(key: &SecretKey, tokens: &Tokens, nonce: u8) -> TransferAuthorization {
+        let mut authorization = TransferAuthorization {
+            token: usdc().address,
+            from: web3::signing::Key::address(&SecretKeyRef::new(key)).into(),
+            to: settlement().0.into(),
+            value: eth::TokenAmount(1_000_000.into()),
+            valid_after: NOW - 60,
+            valid_before: NOW + 600,
+            nonce: eth::H256([nonce; 32]),
+            signature: Default::default(),
+        };
+        authorization.signature = EcdsaSignature::sign(
+            EcdsaSigningScheme::Eip712,
+            &DomainSeparator(tokens.0[&usdc().address].0),
+            &authorization.struct_hash(),
+            SecretKeyRef::new(key),
+        );
+        authorization
+    }
+
+    #[test]
+    fn validates_authorizations() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let tokens = Tokens::new(1, [usdc()]);
+        let authorization = signed(&key, &tokens, 1);
+        assert!(validate(
+            &[authorization.clone(), signed(&key, &tokens, 2)],
+            &tokens,
+            settlement(),
+            NOW
+        )
+        .is_ok());
+
+        assert!(matches!(
+            validate(
+                &[authorization.clone(), authorization.clone()],
+                &tokens,
+                settlement(),
+                NOW
+            ),
+            Err(Error::DuplicateNonce(_))
+        ));
+        assert!(matches!(
+            authorization.validate(&Tokens::default(), settlement(), NOW),
+            Err(Error::UnsupportedToken(_))
+        ));
+        assert!(matches!(
+            authorization.validate(&tokens, eth::H160([1; 20]).into(), NOW),
+            Err(Error::InvalidRecipient)
+        ));
+        assert!(matches!(
+            authorization.validate(&tokens, settlement(), NOW - 60),
+            Err(Error::NotYetValid)
+        ));
+        assert!(matches!(
+            authorization.validate(&tokens, settlement(), NOW + 500),
+            Err(Error::Expired)
+        ));
+
+        // Authorizations signed for another chain or by somebody else than
+        // the token holder are rejected.
+        assert!(matches!(
+            authorization.validate(&Tokens::new(100, [usdc()]), settlement(), NOW),
+            Err(Error::InvalidSignature)
+        ));
+        let tampered = TransferAuthorization {
+            value: eth::TokenAmount(2_000_000.into()),
+            ..authorization
+        };
+        assert!(matches!(
+            tampered.validate(&tokens, settlement(), NOW),
+            Err(Error::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn encodes_interaction() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let authorization = signed(&key, &Tokens::new(1, [usdc()]), 1);
+        let interaction = authorization.interaction();
+        assert_eq!(interaction.target.0, usdc().address.0 .0);
+        assert_eq!(interaction.value, eth::Ether(0.into()));
+        assert_eq!(interaction.call_data.0[..4], hex!("ef55bec6"));
+        assert_eq!(interaction.call_data.0.len(), 4 + 9 * 32);
+    }
+
+    #[test]
+    fn computes_usdc_domain_separator() {
+        let usdc = TokenDomain {
+            address: eth::TokenAddress(
+                eth::H160(hex!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")).into(),
+            ),
+            ..usdc()
+        };
+        assert_eq!(
+            domain_separator(1, &usdc).0,
+            hex!("06c37168a7db5138defc7866392bb87a741f9b3d104deb5094588ce041cae335")
+        );
+    }
+}
@@ -14,6 +14,7 @@ use {
     },
     allowance::Allowance,
     itertools::Itertools,
+    std::collections::HashSet,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -34,6 +35,7 @@ pub fn tx(
     contracts: &infra::blockchain::Contracts,
     approvals: impl Iterator<Item = eth::allowance::Approval>,
     internalization: settlement::Internalization,
+    optimization: settlement::InteractionOptimization,
     solver_native_token: ManageNativeToken,
 ) -> Result<eth::Tx, Error> {
     let mut tokens = Vec::with_capacity(solution.prices.len() + (solution.trades().len() * 2));
@@ -197,6 +199,10 @@ pub fn tx(
         interactions.push(unwrap(native_unwrap, contracts.weth()));
     }
 
+    if optimization == settlement::InteractionOptimization::Enable {
+        interactions = optimize(interactions);
+    }
+
     let tx = contracts
         .settlement()
         .settle(
@@ -257,14 +263,13 @@ pub fn liquidity_interaction(
 
 pub fn approve(allowance: &Allowance) -> eth::Interaction {
     let mut amount = [0u8; 32];
-    let selector = hex_literal::hex!("095ea7b3");
     allowance.amount.to_big_endian(&mut amount);
     eth::Interaction {
         target: allowance.token.0.into(),
         value: eth::U256::zero().into(),
         // selector (4 bytes) + spender (20 byte address padded to 32 bytes) + amount (32 bytes)
         call_data: [
-            selector.as_slice(),
+            APPROVE_SELECTOR.as_slice(),
             [0; 12].as_slice(),
             allowance.spender.0.as_bytes(),
             &amount,
@@ -274,6 +279,78 @@ pub fn approve(allowance: &Allowance) -> eth::Interaction {
     }
 }
 
+/// Removes redundant interactions without changing the outcome of the
+/// settlement for standard ERC20 tokens:
+/// - approvals identical to an earlier approval get dropped unless an
+///   interaction in between calls the token or the spender, which might have
+///   changed the allowance (e.g. by spending it or resetting it to 0)
+/// - consecutive transfers of the same token to the same receiver get merged
+///   into a single transfer
+///
+/// The result still needs to be verified with a simulation since
+/// non-standard tokens may behave differently.
+pub fn optimize(interactions: Vec<eth::Interaction>) -> Vec<eth::Interaction> {
+    let mut approvals = HashSet::new();
+    let mut optimized: Vec<eth::Interaction> = Vec::with_capacity(interactions.len());
+    for interaction in interactions {
+        let is_approval = is_call(&interaction, APPROVE_SELECTOR);
+        if is_approval && approvals.contains(&(interaction.target, interaction.call_data.clone())) {
+            continue;
+        }
+        // Approvals are only known to be redundant as long as neither the
+        // token nor the spender got called in the meantime.
+        approvals.retain(|(token, call_data)| {
+            *token != interaction.target && &call_data.0[16..36] != interaction.target.0.as_bytes()
+        });
+        if is_approval {
+            approvals.insert((interaction.target, interaction.call_data.clone()));
+        }
+        if let Some(previous) = optimized.last_mut() {
+            if let Some(merged) = merge_transfers(previous, &interaction) {
+                *previous = merged;
+                continue;
+            }
+        }
+        optimized.push(interaction);
+    }
+    optimized
+}
+
+const APPROVE_SELECTOR: [u8; 4] = hex_literal::hex!("095ea7b3");
+const TRANSFER_SELECTOR: [u8; 4] = hex_literal::hex!("a9059cbb");
+
+/// Whether the interaction is an ERC20 call with the given selector, an
+/// address and an amount argument and no Ether value.
+fn is_call(interaction: &eth::Interaction, selector: [u8; 4]) -> bool {
+    let call_data = &interaction.call_data.0;
+    interaction.value.0.is_zero()
+        && call_data.len() == 68
+        && call_data[..4] == selector
+        && call_data[4..16].iter().all(|byte| *byte == 0)
+}
+
+/// Merges two transfers of the same token to the same receiver.
+fn merge_transfers(
+    first: &eth::Interaction,
+    second: &eth::Interaction,
+) -> Option<eth::Interaction> {
+    if first.target != second.target
+        || !is_call(first, TRANSFER_SELECTOR)
+        || !is_call(second, TRANSFER_SELECTOR)
+        || first.call_data.0[..36] != second.call_data.0[..36]
+    {
+        return None;
+    }
+    let amount = eth::U256::from_big_endian(&first.call_data.0[36..])
+        .checked_add(eth::U256::from_big_endian(&second.call_data.0[36..]))?;
+    let mut call_data = first.call_data.0.clone();
+    amount.to_big_endian(&mut call_data[36..]);
+    Some(eth::Interaction {
+        call_data: call_data.into(),
+        ..first.clone()
+    })
+}
+
 fn unwrap(amount: eth::TokenAmount, weth: &contracts::WETH9) -> eth::Interaction {
     let tx = weth.withdraw(amount.into()).into_inner();
     eth::Interaction {
@@ -426,4 +503,91 @@ mod test {
         );
         assert_eq!(interaction.call_data.0.as_slice(), hex!("095ea7b3000000000000000000000000000000000022d473030f116ddee9f6b43ac78ba3ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"));
     }
+
+    #[test]
+    fn optimizes_interactions() {
+        let approval = approve(&Allowance {
+            token: eth::H160([1; 20]).into(),
+            spender: eth::H160([3; 20]).into(),
+            amount: eth::U256::max_value(),
+        });
+        let transfer = |target: u8, receiver: u8, amount: u8| eth::Interaction {
+            target: eth::H160([target; 20]).into(),
+            value: eth::U256::zero().into(),
+            call_data: [
+                TRANSFER_SELECTOR.as_slice(),
+                [0; 12].as_slice(),
+                [receiver; 20].as_slice(),
+                [0; 31].as_slice(),
+                [amount].as_slice(),
+            ]
+            .concat()
+            .into(),
+        };
+        let swap = eth::Interaction {
+            target: eth::H160([4; 20]).into(),
+            value: eth::U256::zero().into(),
+            call_data: vec![1, 2, 3].into(),
+        };
+
+        let optimized = optimize(vec![
+            approval.clone(),
+            swap.clone(),
+            approval.clone(),
+            transfer(1, 5, 1),
+            transfer(1, 5, 2),
+            transfer(1, 6, 3),
+            swap.clone(),
+            transfer(1, 6, 4),
+        ]);
+        assert_eq!(
+            optimized,
+            vec![
+                approval,
+                swap.clone(),
+                transfer(1, 5, 3),
+                transfer(1, 6, 3),
+                swap,
+                transfer(1, 6, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_approvals_after_allowance_changes() {
+        let approval = |amount: eth::U256| {
+            approve(&Allowance {
+                token: eth::H160([1; 20]).into(),
+                spender: eth::H160([3; 20]).into(),
+                amount,
+            })
+        };
+        let swap = eth::Interaction {
+            target: eth::H160([3; 20]).into(),
+            value: eth::U256::zero().into(),
+            call_data: vec![1, 2, 3].into(),
+        };
+
+        // Resetting the allowance to 0 makes the repeated approval necessary.
+        let interactions = vec![
+            approval(eth::U256::max_value()),
+            approval(eth::U256::zero()),
+            approval(eth::U256::max_value()),
+        ];
+        assert_eq!(optimize(interactions.clone()), interactions);
+
+        // The spender might have used up the allowance in between.
+        let interactions = vec![approval(100.into()), swap.clone(), approval(100.into())];
+        assert_eq!(optimize(interactions.clone()), interactions);
+
+        // Adjacent duplicates still get dropped.
+        assert_eq!(
+            optimize(vec![
+                approval(100.into()),
+                approval(100.into()),
+                swap.clone()
+            ]),
+            vec![approval(100.into()), swap]
+        );
+    }
 }
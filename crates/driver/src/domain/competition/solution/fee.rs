@@ -37,6 +37,7 @@ use {
         eth::{self},
     },
     bigdecimal::Zero,
+    number::{conversions::mul_ratio_floor, ratio::Ratio256},
 };
 
 impl Fulfillment {
@@ -163,10 +164,10 @@ impl Fulfillment {
         factor: f64,
     ) -> Result<eth::TokenAmount, Error> {
         let surplus = self.surplus_over_reference_price(sell_amount, buy_amount, prices)?;
-        surplus
-            .apply_factor(factor)
-            .ok_or(Math::Overflow)
-            .map_err(Into::into)
+        Ok((Ratio256::from(surplus.0) * &Ratio256::from_factor(factor))
+            .floor()
+            .map_err(Math::from)?
+            .into())
     }
 
     /// Computes the volume based fee in surplus token
@@ -182,10 +183,10 @@ impl Fulfillment {
             Side::Buy => self.sell_amount(&prices)?,
             Side::Sell => self.buy_amount(&prices)?,
         };
-        volume
-            .apply_factor(factor)
-            .ok_or(Math::Overflow)
-            .map_err(Into::into)
+        Ok((Ratio256::from(volume.0) * &Ratio256::from_factor(factor))
+            .floor()
+            .map_err(Math::from)?
+            .into())
     }
 
     /// Returns the protocol fee denominated in the sell token.
@@ -196,14 +197,13 @@ impl Fulfillment {
     ) -> Result<eth::TokenAmount, Error> {
         let fee_in_sell_token = match self.order().side {
             Side::Buy => self.protocol_fee(prices, protocol_fee)?,
-            Side::Sell => self
-                .protocol_fee(prices, protocol_fee)?
-                .0
-                .checked_mul(prices.buy)
-                .ok_or(Math::Overflow)?
-                .checked_div(prices.sell)
-                .ok_or(Math::DivisionByZero)?
-                .into(),
+            Side::Sell => mul_ratio_floor(
+                self.protocol_fee(prices, protocol_fee)?.0,
+                prices.buy,
+                prices.sell,
+            )
+            .map_err(Math::from)?
+            .into(),
         };
         Ok(fee_in_sell_token)
     }
@@ -247,20 +247,14 @@ pub fn adjust_quote_to_order_limits(order: Order, quote: Quote) -> Result<PriceL
         Side::Sell => {
             let quote_buy_amount = quote
                 .buy_amount
-                .checked_sub(
-                    quote
-                        .fee_amount
-                        .checked_mul(quote.buy_amount)
-                        .ok_or(Math::Overflow)?
-                        .checked_div(quote.sell_amount)
-                        .ok_or(Math::DivisionByZero)?,
-                )
+                .checked_sub(mul_ratio_floor(
+                    quote.fee_amount,
+                    quote.buy_amount,
+                    quote.sell_amount,
+                )?)
                 .ok_or(Math::Negative)?;
-            let scaled_buy_amount = quote_buy_amount
-                .checked_mul(order.sell_amount)
-                .ok_or(Math::Overflow)?
-                .checked_div(quote.sell_amount)
-                .ok_or(Math::DivisionByZero)?;
+            let scaled_buy_amount =
+                mul_ratio_floor(quote_buy_amount, order.sell_amount, quote.sell_amount)?;
             let buy_amount = order.buy_amount.max(scaled_buy_amount);
             Ok(PriceLimits {
                 sell: order.sell_amount.into(),
@@ -272,11 +266,8 @@ pub fn adjust_quote_to_order_limits(order: Order, quote: Quote) -> Result<PriceL
                 .sell_amount
                 .checked_add(quote.fee_amount)
                 .ok_or(Math::Overflow)?;
-            let scaled_sell_amount = quote_sell_amount
-                .checked_mul(order.buy_amount)
-                .ok_or(Math::Overflow)?
-                .checked_div(quote.buy_amount)
-                .ok_or(Math::DivisionByZero)?;
+            let scaled_sell_amount =
+                mul_ratio_floor(quote_sell_amount, order.buy_amount, quote.buy_amount)?;
             let sell_amount = order.sell_amount.min(scaled_sell_amount);
             Ok(PriceLimits {
                 sell: sell_amount.into(),
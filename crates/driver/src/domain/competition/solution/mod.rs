@@ -10,6 +10,7 @@ use {
         infra::{
             blockchain::{self, Ethereum},
             config::file::FeeHandler,
+            observe,
             simulator,
             solver::{ManageNativeToken, Solver},
             Simulator,
@@ -18,7 +19,7 @@ use {
     chrono::Utc,
     futures::future::try_join_all,
     itertools::Itertools,
-    num::{BigRational, One},
+    number::ratio::Ratio256,
     std::{
         collections::{hash_map::Entry, BTreeSet, HashMap, HashSet},
         sync::atomic::{AtomicU64, Ordering},
@@ -26,6 +27,7 @@ use {
     thiserror::Error,
 };
 
+pub mod authorization;
 pub mod encoding;
 pub mod fee;
 pub mod interaction;
@@ -254,7 +256,11 @@ impl Solution {
         }
 
         let scoring = scoring::Scoring::new(trades);
-        scoring.score(prices).map_err(error::Scoring::from)
+        scoring
+            .score_with(prices, self.solver.scoring(), |divergence| {
+                observe::scoring_divergence(self.solver.name(), &self.id, divergence)
+            })
+            .map_err(error::Scoring::from)
     }
 
     /// Approval interactions necessary for encoding the settlement.
@@ -306,17 +312,16 @@ impl Solution {
             scaling_factor(&self.prices, &other.prices).ok_or(error::Merge::IncongruentPrices)?;
 
         // To avoid precision issues, make sure we always scale up settlements
-        if factor < BigRational::one() {
+        if factor < Ratio256::one() {
             return other.merge(self);
         }
 
         // Scale prices
         let mut prices = self.prices.clone();
         for (token, price) in other.prices.iter() {
-            let scaled = number::conversions::big_rational_to_u256(
-                &(number::conversions::u256_to_big_rational(price) * &factor),
-            )
-            .map_err(error::Merge::Math)?;
+            let scaled = (Ratio256::from(*price) * &factor)
+                .floor()
+                .map_err(|err| error::Merge::Math(err.into()))?;
             match prices.entry(*token) {
                 Entry::Occupied(entry) => {
                     // This shouldn't fail unless there are rounding errors given that the scaling
@@ -412,8 +417,17 @@ impl Solution {
         eth: &Ethereum,
         simulator: &Simulator,
         solver_native_token: ManageNativeToken,
+        optimization: settlement::InteractionOptimization,
     ) -> Result<Settlement, Error> {
-        Settlement::encode(self, auction, eth, simulator, solver_native_token).await
+        Settlement::encode(
+            self,
+            auction,
+            eth,
+            simulator,
+            solver_native_token,
+            optimization,
+        )
+        .await
     }
 
     /// Token prices settled by this solution, expressed using an arbitrary
@@ -498,23 +512,16 @@ impl std::fmt::Debug for Solution {
 /// given token would have the same price in both solutions.
 /// If the solutions have no prices in common any scaling factor is valid (we
 /// return 1). Returns None if the solutions have more than one price in common
-/// and the scaling factor is not unique.
-fn scaling_factor(first: &Prices, second: &Prices) -> Option<BigRational> {
+/// and the scaling factor is not unique or if a common price is zero.
+fn scaling_factor(first: &Prices, second: &Prices) -> Option<Ratio256> {
     let factors: HashSet<_> = first
         .keys()
         .collect::<HashSet<_>>()
         .intersection(&second.keys().collect::<HashSet<_>>())
-        .map(|&token| {
-            let first_price = first[token];
-            let second_price = second[token];
-            BigRational::new(
-                number::conversions::u256_to_big_int(&first_price),
-                number::conversions::u256_to_big_int(&second_price),
-            )
-        })
-        .collect();
+        .map(|&token| Ratio256::new(first[token], second[token]).ok())
+        .collect::<Option<_>>()?;
     match factors.len() {
-        0 => Some(BigRational::one()),
+        0 => Some(Ratio256::one()),
         1 => factors.into_iter().next(),
         _ => None,
     }
@@ -600,6 +607,8 @@ pub mod error {
              trusted"
         )]
         NonBufferableTokensUsed(BTreeSet<TokenAddress>),
+        #[error("buffer limit exceeded: {0:?}")]
+        BufferLimitExceeded(BufferLimit),
         #[error("invalid internalization: uninternalized solution fails to simulate")]
         FailingInternalization,
         #[error("Gas estimate of {0:?} exceeded the per settlement limit of {1:?}")]
@@ -612,6 +621,20 @@ pub mod error {
         Encoding(#[from] encoding::Error),
     }
 
+    /// A buffer limit a solution exceeded. See [`super::settlement::BufferLimits`].
+    #[derive(Debug, Clone)]
+    pub enum BufferLimit {
+        Token {
+            token: TokenAddress,
+            used: eth::TokenAmount,
+            limit: eth::TokenAmount,
+        },
+        Settlement {
+            used: eth::Ether,
+            limit: eth::Ether,
+        },
+    }
+
     #[derive(Debug, thiserror::Error)]
     pub enum Math {
         #[error("overflow")]
@@ -622,6 +645,15 @@ pub mod error {
         Negative,
     }
 
+    impl From<number::conversions::RatioError> for Math {
+        fn from(err: number::conversions::RatioError) -> Self {
+            match err {
+                number::conversions::RatioError::Overflow => Self::Overflow,
+                number::conversions::RatioError::DivisionByZero => Self::DivisionByZero,
+            }
+        }
+    }
+
     #[derive(Debug, thiserror::Error)]
     pub enum Solution {
         #[error("invalid clearing prices")]
@@ -12,23 +12,24 @@ use {
         order::{self, Side},
         trade::CustomClearingPrices,
     },
-    crate::{
-        domain::{
-            competition::{
-                auction,
-                order::FeePolicy,
-                solution::{
-                    error,
-                    fee::{self, adjust_quote_to_order_limits},
-                },
-                PriceLimits,
+    crate::domain::{
+        competition::{
+            auction,
+            order::FeePolicy,
+            solution::{
+                error,
+                fee::{self, adjust_quote_to_order_limits},
             },
-            eth,
+            PriceLimits,
         },
-        util::conv::u256::U256Ext,
+        eth,
     },
     bigdecimal::Zero,
     num::{CheckedAdd, CheckedSub},
+    number::{
+        conversions::{mul_ratio_ceil, mul_ratio_floor, safe_mul_ceil_div, safe_mul_div},
+        ratio::Ratio256,
+    },
 };
 
 /// Scoring contains trades with values as they are expected by the settlement
@@ -58,6 +59,132 @@ impl Scoring {
     pub fn score(&self, prices: &auction::Prices) -> Result<eth::Ether, Error> {
         self.trades.iter().map(|trade| trade.score(prices)).sum()
     }
+
+    /// Candidate replacement of [`Scoring::score`].
+    ///
+    /// Instead of adding up surplus and protocol fees it computes the score of
+    /// a trade directly as its surplus over the limit price before any protocol
+    /// fees were applied. Both implementations are expected to agree up to
+    /// rounding, see [`Scoring::rounding_tolerance`].
+    ///
+    /// Denominated in NATIVE token
+    pub fn score_candidate(&self, prices: &auction::Prices) -> Result<eth::Ether, Error> {
+        self.trades
+            .iter()
+            .map(|trade| trade.score_candidate(prices))
+            .sum()
+    }
+
+    /// Upper bound of the rounding difference between [`Scoring::score`] and
+    /// [`Scoring::score_candidate`]. The surplus and every protocol fee of a
+    /// trade may be off by one atom of the surplus token and by one wei when
+    /// converted to the native token.
+    pub fn rounding_tolerance(&self, prices: &auction::Prices) -> eth::Ether {
+        self.trades
+            .iter()
+            .map(|trade| {
+                let steps = eth::U256::from(trade.policies.len() + 1);
+                let atom = prices
+                    .get(&trade.surplus_token())
+                    .map(|price| price.in_eth(eth::U256::one().into()).0)
+                    .unwrap_or_default();
+                eth::Ether(atom.saturating_add(2.into()).saturating_mul(steps))
+            })
+            .fold(eth::Ether(0.into()), |total, tolerance| {
+                eth::Ether(total.0.saturating_add(tolerance.0))
+            })
+    }
+
+    /// Computes the score with the configured implementation. In differential
+    /// mode the score is additionally computed with the other implementation
+    /// and any divergence between the two beyond the
+    /// [rounding tolerance](Scoring::rounding_tolerance) is reported.
+    pub fn score_with(
+        &self,
+        prices: &auction::Prices,
+        config: &Config,
+        report: impl FnOnce(&Divergence),
+    ) -> Result<eth::Ether, Error> {
+        let score = |implementation| match implementation {
+            Implementation::Current => self.score(prices),
+            Implementation::Candidate => self.score_candidate(prices),
+        };
+        let result = score(config.implementation);
+        if config.differential {
+            let other = score(config.implementation.other());
+            let (current, candidate) = match config.implementation {
+                Implementation::Current => (&result, &other),
+                Implementation::Candidate => (&other, &result),
+            };
+            if diverged(current, candidate, self.rounding_tolerance(prices)) {
+                report(&Divergence {
+                    trades: &self.trades,
+                    current,
+                    candidate,
+                });
+            }
+        }
+        result
+    }
+}
+
+/// Whether the scores differ by more than the tolerance or only one of the
+/// implementations failed.
+fn diverged(
+    current: &Result<eth::Ether, Error>,
+    candidate: &Result<eth::Ether, Error>,
+    tolerance: eth::Ether,
+) -> bool {
+    match (current, candidate) {
+        (Ok(current), Ok(candidate)) => {
+            let difference = match current.0.checked_sub(candidate.0) {
+                Some(difference) => difference,
+                None => candidate.0 - current.0,
+            };
+            difference > tolerance.0
+        }
+        (Err(_), Err(_)) => false,
+        _ => true,
+    }
+}
+
+/// Which implementation computes the scores of solutions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Implementation {
+    /// [`Scoring::score`]
+    #[default]
+    Current,
+    /// [`Scoring::score_candidate`]
+    Candidate,
+}
+
+impl Implementation {
+    fn other(self) -> Self {
+        match self {
+            Self::Current => Self::Candidate,
+            Self::Candidate => Self::Current,
+        }
+    }
+}
+
+/// Configures how scores get computed. Differential mode allows validating a
+/// new scoring implementation on production traffic while the scores of the
+/// configured implementation keep getting used.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    /// The implementation whose scores get used.
+    pub implementation: Implementation,
+    /// Whether to also compute scores with the other implementation and report
+    /// divergences.
+    pub differential: bool,
+}
+
+/// Diverging results of the scoring implementations for the same solution.
+#[derive(Debug)]
+pub struct Divergence<'a> {
+    pub trades: &'a [Trade],
+    pub current: &'a Result<eth::Ether, Error>,
+    pub candidate: &'a Result<eth::Ether, Error>,
 }
 
 // Trade represents a single trade in a settlement.
@@ -102,6 +229,29 @@ impl Trade {
         Ok(self.native_surplus(prices)? + self.native_protocol_fee(prices)?)
     }
 
+    /// CIP38 score computed as the surplus the trade would have had without
+    /// any protocol fees
+    ///
+    /// Denominated in NATIVE token
+    fn score_candidate(&self, prices: &auction::Prices) -> Result<eth::Ether, Error> {
+        let protocol_fee = self
+            .protocol_fees()?
+            .into_iter()
+            .try_fold(eth::TokenAmount::default(), |total, fee| {
+                total.checked_add(&fee.amount).ok_or(Math::Overflow)
+            })?;
+        let without_protocol_fees = Self {
+            custom_price: self.calculate_custom_prices(protocol_fee)?,
+            ..self.clone()
+        };
+        let surplus = without_protocol_fees.surplus_over_limit_price()?;
+        let price = prices
+            .get(&surplus.token)
+            .ok_or(Error::MissingPrice(surplus.token))?;
+
+        Ok(price.in_eth(surplus.amount))
+    }
+
     /// Surplus based on custom clearing prices returns the surplus after all
     /// fees have been applied and calculated over the price limits.
     ///
@@ -110,43 +260,32 @@ impl Trade {
         match self.side {
             Side::Buy => {
                 // scale limit sell to support partially fillable orders
-                let limit_sell = price_limits
-                    .sell
-                    .0
-                    .checked_mul(self.executed.into())
-                    .ok_or(Math::Overflow)?
-                    .checked_div(price_limits.buy.0)
-                    .ok_or(Math::DivisionByZero)?;
-                let sold = self
-                    .executed
-                    .0
-                    .checked_mul(self.custom_price.buy)
-                    .ok_or(Math::Overflow)?
-                    .checked_div(self.custom_price.sell)
-                    .ok_or(Math::DivisionByZero)?;
+                let limit_sell = mul_ratio_floor(
+                    price_limits.sell.0,
+                    self.executed.into(),
+                    price_limits.buy.0,
+                )?;
+                let sold = mul_ratio_floor(
+                    self.executed.0,
+                    self.custom_price.buy,
+                    self.custom_price.sell,
+                )?;
                 limit_sell.checked_sub(sold).ok_or(Math::Negative)
             }
             Side::Sell => {
                 // scale limit buy to support partially fillable orders
 
-                // `checked_ceil_div`` to be consistent with how settlement contract calculates
+                // `mul_ratio_ceil` to be consistent with how settlement contract calculates
                 // traded buy amounts
                 // smallest allowed executed_buy_amount per settlement contract is
                 // executed_sell_amount * ceil(price_limits.buy / price_limits.sell)
-                let limit_buy = self
-                    .executed
-                    .0
-                    .checked_mul(price_limits.buy.0)
-                    .ok_or(Math::Overflow)?
-                    .checked_ceil_div(&price_limits.sell.0)
-                    .ok_or(Math::DivisionByZero)?;
-                let bought = self
-                    .executed
-                    .0
-                    .checked_mul(self.custom_price.sell)
-                    .ok_or(Math::Overflow)?
-                    .checked_ceil_div(&self.custom_price.buy)
-                    .ok_or(Math::DivisionByZero)?;
+                let limit_buy =
+                    mul_ratio_ceil(self.executed.0, price_limits.buy.0, price_limits.sell.0)?;
+                let bought = mul_ratio_ceil(
+                    self.executed.0,
+                    self.custom_price.sell,
+                    self.custom_price.buy,
+                )?;
                 bought.checked_sub(limit_buy).ok_or(Math::Negative)
             }
         }
@@ -197,13 +336,11 @@ impl Trade {
     fn sell_amount(&self) -> Result<eth::TokenAmount, error::Math> {
         Ok(match self.side {
             order::Side::Sell => self.executed.0,
-            order::Side::Buy => self
-                .executed
-                .0
-                .checked_mul(self.custom_price.buy)
-                .ok_or(Math::Overflow)?
-                .checked_div(self.custom_price.sell)
-                .ok_or(Math::DivisionByZero)?,
+            order::Side::Buy => safe_mul_div(
+                self.executed.0,
+                self.custom_price.buy,
+                self.custom_price.sell,
+            )?,
         }
         .into())
     }
@@ -215,13 +352,11 @@ impl Trade {
     /// Settlement contract uses `ceil` division for buy amount calculation.
     fn buy_amount(&self) -> Result<eth::TokenAmount, error::Math> {
         Ok(match self.side {
-            order::Side::Sell => self
-                .executed
-                .0
-                .checked_mul(self.custom_price.sell)
-                .ok_or(Math::Overflow)?
-                .checked_ceil_div(&self.custom_price.buy)
-                .ok_or(Math::DivisionByZero)?,
+            order::Side::Sell => safe_mul_ceil_div(
+                self.executed.0,
+                self.custom_price.sell,
+                self.custom_price.buy,
+            )?,
             order::Side::Buy => self.executed.0,
         }
         .into())
@@ -341,10 +476,13 @@ impl Trade {
         //
         // Finally:
         //     fee = surplus_after_fee * factor / (1 - factor)
-        let fee = surplus
-            .amount
-            .apply_factor(factor / (1.0 - factor))
-            .ok_or(Math::Overflow)?;
+        let factor = Ratio256::from_factor(factor);
+        let remainder = Ratio256::one().checked_sub(&factor).ok_or(Math::Negative)?;
+        let factor = (&factor / &remainder).map_err(Math::from)?;
+        let fee = (Ratio256::from(surplus.amount.0) * &factor)
+            .floor()
+            .map_err(Math::from)?
+            .into();
 
         Ok(eth::Asset {
             token: surplus.token,
@@ -386,18 +524,19 @@ impl Trade {
             order::Side::Buy => self.sell_amount()?,
             order::Side::Sell => self.buy_amount()?,
         };
-        let factor = match self.side {
-            Side::Sell => factor / (1.0 - factor),
-            Side::Buy => factor / (1.0 + factor),
+        let factor = Ratio256::from_factor(factor);
+        let denominator = match self.side {
+            Side::Sell => Ratio256::one().checked_sub(&factor).ok_or(Math::Negative)?,
+            Side::Buy => &Ratio256::one() + &factor,
         };
+        let factor = (&factor / &denominator).map_err(Math::from)?;
 
         Ok(eth::Asset {
             token: self.surplus_token(),
-            amount: {
-                executed_in_surplus_token
-                    .apply_factor(factor)
-                    .ok_or(Math::Overflow)?
-            },
+            amount: (Ratio256::from(executed_in_surplus_token.0) * &factor)
+                .floor()
+                .map_err(Math::from)?
+                .into(),
         })
     }
 
@@ -433,3 +572,97 @@ pub enum Error {
     #[error("scoring: failed to calculate custom price for the applied fee policy {0:?}")]
     Scoring(#[source] error::Scoring),
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::HashMap};
+
+    fn token(byte: u8) -> eth::TokenAddress {
+        eth::H160::repeat_byte(byte).into()
+    }
+
+    /// Sells 1 token for 1010 atoms of a 6 decimals token with a limit of 1000
+    /// atoms, i.e. with a surplus of 10 atoms.
+    fn trade(policies: Vec<FeePolicy>) -> Trade {
+        Trade::new(
+            eth::Asset {
+                token: token(1),
+                amount: eth::U256::exp10(18).into(),
+            },
+            eth::Asset {
+                token: token(2),
+                amount: 1_000.into(),
+            },
+            Side::Sell,
+            eth::U256::exp10(18).into(),
+            CustomClearingPrices {
+                sell: 1_010.into(),
+                buy: eth::U256::exp10(18),
+            },
+            policies,
+        )
+    }
+
+    fn prices() -> auction::Prices {
+        // 1 atom of the 6 decimals token is worth 0.0000000005 ETH.
+        HashMap::from([
+            (token(1), eth::U256::exp10(18).into()),
+            (token(2), (eth::U256::exp10(18) * 500_000_000).into()),
+        ])
+    }
+
+    #[test]
+    fn scoring_implementations_agree_up_to_rounding() {
+        let scoring = Scoring::new(vec![
+            trade(vec![]),
+            trade(vec![FeePolicy::Surplus {
+                factor: 0.33,
+                max_volume_factor: 0.01,
+            }]),
+        ]);
+        let prices = prices();
+        assert_eq!(
+            scoring.rounding_tolerance(&prices),
+            eth::Ether((500_000_002 * 3).into())
+        );
+
+        for implementation in [Implementation::Current, Implementation::Candidate] {
+            let config = Config {
+                implementation,
+                differential: true,
+            };
+            let score = scoring
+                .score_with(&prices, &config, |divergence| {
+                    panic!("unexpected divergence {divergence:?}")
+                })
+                .unwrap();
+            let expected = match implementation {
+                Implementation::Current => scoring.score(&prices),
+                Implementation::Candidate => scoring.score_candidate(&prices),
+            };
+            assert_eq!(score, expected.unwrap());
+        }
+    }
+
+    #[test]
+    fn detects_divergences() {
+        let ether = |wei: u64| Ok(eth::Ether(wei.into()));
+        let tolerance = eth::Ether(10.into());
+
+        assert!(!diverged(&ether(100), &ether(100), tolerance));
+        assert!(!diverged(&ether(100), &ether(110), tolerance));
+        assert!(!diverged(&ether(110), &ether(100), tolerance));
+        assert!(diverged(&ether(100), &ether(111), tolerance));
+        assert!(diverged(&ether(111), &ether(100), tolerance));
+        assert!(diverged(
+            &ether(100),
+            &Err(Error::MissingPrice(token(1))),
+            tolerance
+        ));
+        assert!(!diverged(
+            &Err(Error::MissingPrice(token(1))),
+            &Err(Error::MissingPrice(token(2))),
+            tolerance
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use {
-    super::{encoding, trade::ClearingPrices, Error, Solution},
+    super::{encoding, slippage, trade::ClearingPrices, Error, Interaction, Solution},
     crate::{
         domain::{
             competition::{
@@ -10,12 +10,168 @@ use {
             },
             eth,
         },
-        infra::{blockchain::Ethereum, observe, solver::ManageNativeToken, Simulator},
+        infra::{
+            blockchain::Ethereum,
+            observe,
+            solver::{self, ManageNativeToken},
+            Simulator,
+        },
     },
     futures::future::try_join_all,
     std::collections::{BTreeSet, HashMap, HashSet},
 };
 
+/// Upper bound of the value a sandwich attack can extract from the
+/// internalized settlement: the slippage tolerance granted to the liquidity
+/// interactions that get executed on chain, on their input as well as their
+/// output side. `None` if there is no such bound because the settlement
+/// executes custom solver interactions on chain or the tolerance is in a token
+/// without a native price.
+fn extractable_value(
+    interactions: &[Interaction],
+    slippage: &slippage::Parameters,
+) -> Option<eth::Ether> {
+    let in_eth = |token: eth::TokenAddress, amount: eth::U256| {
+        if amount.is_zero() {
+            return Some(eth::Ether::from(0));
+        }
+        Some(slippage.prices.get(&token)?.in_eth(amount.into()))
+    };
+    interactions
+        .iter()
+        .filter(|interaction| !interaction.internalize())
+        .map(|interaction| match interaction {
+            Interaction::Custom(_) => None,
+            Interaction::Liquidity(liquidity) => {
+                let (max_input, min_output) = slippage
+                    .apply_to(&slippage::Interaction {
+                        input: liquidity.input,
+                        output: liquidity.output,
+                    })
+                    .ok()?;
+                // An attacker can move the price until the interaction pays
+                // the maximum input or receives the minimum output.
+                let input = max_input
+                    .0
+                    .amount
+                    .0
+                    .saturating_sub(liquidity.input.amount.0);
+                let output = liquidity
+                    .output
+                    .amount
+                    .0
+                    .saturating_sub(min_output.0.amount.0);
+                // Tolerance in an unpriced input token is valued at the
+                // interaction's exchange rate.
+                let input = in_eth(liquidity.input.token, input).or_else(|| {
+                    let input = input
+                        .checked_mul(liquidity.output.amount.0)?
+                        .checked_div(liquidity.input.amount.0)?;
+                    in_eth(liquidity.output.token, input)
+                })?;
+                Some(input + in_eth(liquidity.output.token, output)?)
+            }
+        })
+        .sum()
+}
+
+/// Whether sandwiching a transaction costs more gas than the value it can
+/// extract. Transactions without a bound of the extractable value are never
+/// safe.
+fn sandwich_unprofitable(
+    extractable_value: Option<eth::Ether>,
+    sandwich_gas: eth::Gas,
+    gas_price: eth::FeePerGas,
+) -> bool {
+    extractable_value.is_some_and(|value| value <= sandwich_gas * gas_price)
+}
+
+/// Net amounts of tokens the internalized interactions take out of the
+/// settlement contract's buffers. Internalized interactions don't get executed
+/// so their outputs are paid from the buffers while their inputs stay in them.
+fn buffer_usage(interactions: &[Interaction]) -> HashMap<eth::TokenAddress, eth::TokenAmount> {
+    let (mut taken, mut added) = (HashMap::<_, eth::U256>::new(), HashMap::new());
+    for interaction in interactions
+        .iter()
+        .filter(|interaction| interaction.internalize())
+    {
+        for (assets, balances) in [
+            (interaction.outputs(), &mut taken),
+            (interaction.inputs(), &mut added),
+        ] {
+            for asset in assets {
+                let balance = balances.entry(asset.token).or_default();
+                *balance = balance.saturating_add(asset.amount.0);
+            }
+        }
+    }
+    taken
+        .into_iter()
+        .filter_map(|(token, taken)| {
+            let used = taken.saturating_sub(added.get(&token).copied().unwrap_or_default());
+            (!used.is_zero()).then_some((token, used.into()))
+        })
+        .collect()
+}
+
+/// Caps on how much of the settlement contract's buffers a solution may use
+/// through internalized interactions.
+#[derive(Debug, Clone, Default)]
+pub struct BufferLimits {
+    /// The maximum net amount of a token a solution may take from the buffers.
+    pub tokens: HashMap<eth::TokenAddress, eth::TokenAmount>,
+    /// The maximum value of all tokens a solution may take from the buffers.
+    /// Tokens without a native price only count towards their token limit.
+    pub settlement: Option<eth::Ether>,
+}
+
+impl BufferLimits {
+    fn check(&self, solution: &Solution, auction: &competition::Auction) -> Result<(), Error> {
+        if self.tokens.is_empty() && self.settlement.is_none() {
+            return Ok(());
+        }
+        self.check_usage(
+            &buffer_usage(solution.interactions()),
+            &auction.prices(),
+            solution.solver().name(),
+        )
+        .map_err(Error::BufferLimitExceeded)
+    }
+
+    /// Checks the net amounts the solution takes from the buffers against the
+    /// limits.
+    fn check_usage(
+        &self,
+        usage: &HashMap<eth::TokenAddress, eth::TokenAmount>,
+        prices: &auction::Prices,
+        solver: &solver::Name,
+    ) -> Result<(), error::BufferLimit> {
+        for (token, used) in usage {
+            if let Some(limit) = self.tokens.get(token) {
+                observe::buffer_usage(solver, "token", used.0, limit.0);
+                if used > limit {
+                    return Err(error::BufferLimit::Token {
+                        token: *token,
+                        used: *used,
+                        limit: *limit,
+                    });
+                }
+            }
+        }
+        if let Some(limit) = self.settlement {
+            let used: eth::Ether = usage
+                .iter()
+                .filter_map(|(token, used)| Some(prices.get(token)?.in_eth(*used)))
+                .sum();
+            observe::buffer_usage(solver, "settlement", used.0, limit.0);
+            if used > limit {
+                return Err(error::BufferLimit::Settlement { used, limit });
+            }
+        }
+        Ok(())
+    }
+}
+
 /// A transaction calling into our settlement contract on the blockchain, ready
 /// to be published to the blockchain.
 ///
@@ -30,7 +186,8 @@ use {
 /// - Simulation: the settlement has been simulated without reverting, including
 ///   the case where no interactions were internalized. Additionally the solver
 ///   account is known to have sufficient Ether to execute the transaction.
-/// - Internalization: internalized interactions only use trusted tokens.
+/// - Internalization: internalized interactions only use trusted tokens and
+///   stay within the buffer limits of the solver.
 ///
 /// Publishing a settlement which violates these rules would result in slashing
 /// for the solver (earning reduced rewards). Enforcing these rules ensures that
@@ -54,6 +211,9 @@ struct SettlementTx {
     uninternalized: eth::Tx,
     /// Whether this settlement has interactions that could make it revert
     may_revert: bool,
+    /// Upper bound of the value a sandwich attack can extract from the
+    /// internalized transaction. See [`extractable_value`].
+    extractable_value: Option<eth::Ether>,
 }
 
 impl SettlementTx {
@@ -74,6 +234,7 @@ impl Settlement {
         eth: &Ethereum,
         simulator: &Simulator,
         solver_native_token: ManageNativeToken,
+        optimization: InteractionOptimization,
     ) -> Result<Self, Error> {
         // For a settlement to be valid, the solution has to respect some rules which
         // would otherwise lead to slashing. Check those rules first.
@@ -91,27 +252,93 @@ impl Settlement {
         if !untrusted_tokens.is_empty() {
             return Err(Error::NonBufferableTokensUsed(untrusted_tokens));
         }
+        solution
+            .solver()
+            .buffer_limits()
+            .check(&solution, auction)?;
 
         // Encode the solution into a settlement.
-        let tx = SettlementTx {
-            internalized: encoding::tx(
-                auction,
-                &solution,
-                eth.contracts(),
-                solution.approvals(eth, Internalization::Enable).await?,
-                Internalization::Enable,
-                solver_native_token,
-            )?,
-            uninternalized: encoding::tx(
-                auction,
-                &solution,
-                eth.contracts(),
-                solution.approvals(eth, Internalization::Disable).await?,
-                Internalization::Disable,
-                solver_native_token,
-            )?,
-            may_revert: solution.revertable(),
+        let approvals = (
+            solution
+                .approvals(eth, Internalization::Enable)
+                .await?
+                .collect::<Vec<_>>(),
+            solution
+                .approvals(eth, Internalization::Disable)
+                .await?
+                .collect::<Vec<_>>(),
+        );
+        let slippage = slippage::Parameters {
+            relative: solution.solver().slippage().relative.clone(),
+            max: solution.solver().slippage().absolute.map(eth::Ether::into),
+            min: None,
+            prices: auction.prices().clone(),
+        };
+        let encode = |optimization| -> Result<SettlementTx, Error> {
+            Ok(SettlementTx {
+                internalized: encoding::tx(
+                    auction,
+                    &solution,
+                    eth.contracts(),
+                    approvals.0.iter().cloned(),
+                    Internalization::Enable,
+                    optimization,
+                    solver_native_token,
+                )?,
+                uninternalized: encoding::tx(
+                    auction,
+                    &solution,
+                    eth.contracts(),
+                    approvals.1.iter().cloned(),
+                    Internalization::Disable,
+                    optimization,
+                    solver_native_token,
+                )?,
+                may_revert: solution.revertable(),
+                extractable_value: extractable_value(solution.interactions(), &slippage),
+            })
         };
+        let tx = encode(InteractionOptimization::Disable)?;
+
+        // Only use the optimized interactions if the optimized settlement still
+        // simulates successfully. Otherwise fall back to the original ones.
+        if optimization == InteractionOptimization::Enable {
+            let optimized = encode(InteractionOptimization::Enable)?;
+            if optimized.internalized.input != tx.internalized.input
+                || optimized.uninternalized.input != tx.uninternalized.input
+            {
+                match Self::new(
+                    auction.id().unwrap(),
+                    solution.clone(),
+                    optimized,
+                    eth,
+                    simulator,
+                )
+                .await
+                {
+                    Ok(settlement) => {
+                        let original = tx.internalized.set_access_list(
+                            settlement.transaction.internalized.access_list.clone(),
+                        );
+                        let original_gas = simulator.gas(&original).await.ok();
+                        observe::interactions_optimized(
+                            solution.solver().name(),
+                            &original,
+                            &settlement.transaction.internalized,
+                            original_gas,
+                            settlement.gas.estimate,
+                        );
+                        return Ok(settlement);
+                    }
+                    Err(err) => observe::interaction_optimization_failed(
+                        solution.solver().name(),
+                        solution.id(),
+                        &err,
+                    ),
+                }
+            }
+        }
+
         Self::new(auction.id().unwrap(), solution, tx, eth, simulator).await
     }
 
@@ -237,6 +464,17 @@ impl Settlement {
         self.transaction.may_revert
     }
 
+    /// Whether sandwiching the internalized transaction would cost an attacker
+    /// more gas than it could extract, making it safe to submit to the
+    /// public mempool.
+    pub fn sandwich_unprofitable(&self, sandwich_gas: eth::Gas) -> bool {
+        sandwich_unprofitable(
+            self.transaction.extractable_value,
+            sandwich_gas,
+            eth::U256::from(self.gas.price.effective()).into(),
+        )
+    }
+
     /// Score as defined per CIP38. Equal to surplus + protocol fees.
     pub fn score(
         &self,
@@ -324,6 +562,16 @@ pub enum Internalization {
     Disable,
 }
 
+/// Should redundant interactions be removed from the settlement?
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionOptimization {
+    /// Deduplicate approvals and merge consecutive transfers, see
+    /// [`encoding::optimize`].
+    Enable,
+    /// Encode the interactions as provided.
+    Disable,
+}
+
 /// Gas parameters associated with a settlement.
 #[derive(Clone, Copy, Debug)]
 pub struct Gas {
@@ -384,3 +632,172 @@ impl Gas {
         self.limit * self.price.max()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::domain::{competition::solution::interaction, liquidity},
+        number::ratio::Ratio256,
+    };
+
+    fn asset((token, amount): (u8, u64)) -> eth::Asset {
+        eth::Asset {
+            token: eth::H160::repeat_byte(token).into(),
+            amount: eth::U256::from(amount).into(),
+        }
+    }
+
+    fn custom(internalize: bool, input: (u8, u64), output: (u8, u64)) -> Interaction {
+        Interaction::Custom(interaction::Custom {
+            target: eth::H160::zero().into(),
+            value: eth::U256::zero().into(),
+            call_data: Default::default(),
+            allowances: Default::default(),
+            inputs: vec![asset(input)],
+            outputs: vec![asset(output)],
+            internalize,
+        })
+    }
+
+    #[test]
+    fn computes_net_buffer_usage_of_internalized_interactions() {
+        let usage = buffer_usage(&[
+            custom(true, (1, 100), (2, 50)),
+            custom(true, (2, 20), (3, 10)),
+            custom(true, (3, 30), (1, 40)),
+            // Executed on chain so it doesn't use the buffers.
+            custom(false, (1, 1000), (2, 1000)),
+        ]);
+        assert_eq!(
+            usage,
+            HashMap::from([(eth::H160::repeat_byte(2).into(), eth::U256::from(30).into())])
+        );
+    }
+
+    #[test]
+    fn checks_buffer_limits() {
+        let token = |byte: u8| -> eth::TokenAddress { eth::H160::repeat_byte(byte).into() };
+        let amount = |amount: u64| -> eth::TokenAmount { eth::U256::from(amount).into() };
+        let ether = |amount: u64| -> eth::Ether { eth::U256::from(amount).into() };
+        let solver = solver::Name("solver".to_string());
+        // Token 1 is worth 2 wei per atom, token 2 has no price.
+        let prices = HashMap::from([(
+            token(1),
+            auction::Price::try_new(eth::U256::from(2 * 10u64.pow(18)).into()).unwrap(),
+        )]);
+        let usage = HashMap::from([(token(1), amount(100)), (token(2), amount(1_000))]);
+        let check = |limits: BufferLimits| limits.check_usage(&usage, &prices, &solver);
+
+        // Unlimited by default.
+        assert!(check(BufferLimits::default()).is_ok());
+
+        // Token limits only apply to their token.
+        let limits = |limit: u64| BufferLimits {
+            tokens: HashMap::from([(token(1), amount(limit)), (token(3), amount(0))]),
+            settlement: None,
+        };
+        assert!(check(limits(100)).is_ok());
+        assert!(matches!(
+            check(limits(99)),
+            Err(error::BufferLimit::Token { token: t, used, limit })
+                if t == token(1) && used == amount(100) && limit == amount(99)
+        ));
+
+        // Only priced tokens count towards the settlement limit.
+        let limits = |limit: u64| BufferLimits {
+            tokens: Default::default(),
+            settlement: Some(ether(limit)),
+        };
+        assert!(check(limits(200)).is_ok());
+        assert!(matches!(
+            check(limits(199)),
+            Err(error::BufferLimit::Settlement { used, limit })
+                if used == ether(200) && limit == ether(199)
+        ));
+    }
+
+    fn swap(internalize: bool, input: (u8, u64), output: (u8, u64)) -> Interaction {
+        Interaction::Liquidity(interaction::Liquidity {
+            liquidity: liquidity::Liquidity {
+                id: 0.into(),
+                gas: eth::U256::from(100_000).into(),
+                kind: liquidity::Kind::UniswapV2(liquidity::uniswap::v2::Pool {
+                    address: eth::H160::repeat_byte(0xfe).into(),
+                    router: eth::H160::repeat_byte(0xff).into(),
+                    reserves: liquidity::uniswap::v2::Reserves::try_new(
+                        asset((input.0, 1_000_000)),
+                        asset((output.0, 1_000_000)),
+                    )
+                    .unwrap(),
+                }),
+            },
+            input: asset(input),
+            output: asset(output),
+            internalize,
+        })
+    }
+
+    #[test]
+    fn bounds_extractable_value_by_slippage_tolerance() {
+        // Only token 1 has a native price, 1 token atom is worth 1 wei.
+        let slippage = |max: Option<u64>| slippage::Parameters {
+            relative: Ratio256::from_bps(1_000),
+            max: max.map(Into::into),
+            min: None,
+            prices: HashMap::from([(
+                eth::H160::repeat_byte(1).into(),
+                eth::U256::exp10(18).into(),
+            )]),
+        };
+        let value = |interactions: &[Interaction], max| {
+            extractable_value(interactions, &slippage(max)).map(|value| value.0.as_u64())
+        };
+
+        // 10% of the priced input.
+        assert_eq!(value(&[swap(false, (1, 1000), (2, 500))], None), Some(100));
+        // Capped by the absolute slippage.
+        assert_eq!(
+            value(&[swap(false, (1, 1000), (2, 500))], Some(50)),
+            Some(50)
+        );
+        // A tolerance in an unpriced input token is valued at the exchange
+        // rate of the interaction: 10% of 2000 output tokens.
+        assert_eq!(value(&[swap(false, (3, 1000), (1, 2000))], None), Some(200));
+        // Tolerances of all interactions executed on chain add up, internalized
+        // interactions can't be sandwiched.
+        assert_eq!(
+            value(
+                &[
+                    swap(false, (1, 1000), (2, 500)),
+                    swap(false, (3, 1000), (1, 2000)),
+                    swap(true, (1, 1_000_000), (2, 500)),
+                    custom(true, (1, 1_000_000), (2, 500)),
+                ],
+                None
+            ),
+            Some(300)
+        );
+        // No bound for custom interactions executed on chain or tolerances
+        // that can't be priced.
+        assert_eq!(value(&[custom(false, (1, 1000), (2, 500))], None), None);
+        assert_eq!(value(&[swap(false, (2, 1000), (3, 500))], None), None);
+        assert_eq!(value(&[], None), Some(0));
+    }
+
+    #[test]
+    fn sandwiches_are_unprofitable_if_they_cost_more_gas_than_they_extract() {
+        let unprofitable = |value: Option<u64>, gas_price: u64| {
+            sandwich_unprofitable(
+                value.map(|value| eth::U256::from(value).into()),
+                eth::U256::from(100).into(),
+                eth::U256::from(gas_price).into(),
+            )
+        };
+
+        assert!(unprofitable(Some(300), 3));
+        assert!(unprofitable(Some(300), 4));
+        assert!(!unprofitable(Some(300), 2));
+        assert!(!unprofitable(None, 1_000));
+    }
+}
@@ -5,15 +5,14 @@ use {
         liquidity::{ExactOutput, MaxInput},
     },
     ethcontract::U256,
-    num::{BigRational, CheckedDiv, CheckedMul},
-    number::conversions::big_rational_to_u256,
-    shared::conversions::U256Ext,
+    num::{CheckedDiv, CheckedMul},
+    number::ratio::Ratio256,
 };
 
 #[derive(Clone)]
 pub struct Parameters {
     /// The maximum relative slippage factor.
-    pub relative: BigRational,
+    pub relative: Ratio256,
     /// The maximum absolute slippage in native tokens.
     pub max: Option<eth::U256>,
     /// The minimum absolute slippage in native tokens.
@@ -43,9 +42,7 @@ impl Parameters {
         // 3. Fall back to using the default relative slippage without capping
         let slippage = if let Some(price) = self.prices.get(&interaction.input.token) {
             let amount = price.in_eth(interaction.input.amount);
-            let relative = amount.0.to_big_rational() * &self.relative;
-            let relative =
-                big_rational_to_u256(&relative).map_err(|_| super::error::Math::DivisionByZero)?;
+            let relative = (Ratio256::from(amount.0) * &self.relative).floor()?;
 
             // Final slippage considers min/max caps
             let slippage = num::clamp(
@@ -65,9 +62,7 @@ impl Parameters {
             price.from_eth(eth::Ether(slippage))
         } else if let Some(price) = self.prices.get(&interaction.output.token) {
             let amount = price.in_eth(interaction.output.amount);
-            let relative = amount.0.to_big_rational() * &self.relative;
-            let relative =
-                big_rational_to_u256(&relative).map_err(|_| super::error::Math::DivisionByZero)?;
+            let relative = (Ratio256::from(amount.0) * &self.relative).floor()?;
 
             // Final slippage considers min/max caps
             let slippage = num::clamp(
@@ -96,9 +91,8 @@ impl Parameters {
                 output_token = ?interaction.output.token,
                 "unable to compute capped slippage; falling back to relative slippage",
             );
-            let relative = interaction.input.amount.0.to_big_rational() * &self.relative;
-            big_rational_to_u256(&relative)
-                .map_err(|_| super::error::Math::DivisionByZero)?
+            (Ratio256::from(interaction.input.amount.0) * &self.relative)
+                .floor()?
                 .into()
         };
 
@@ -115,7 +109,7 @@ impl Parameters {
 
 #[cfg(test)]
 mod tests {
-    use {super::*, crate::domain::eth::Asset, num::rational::Ratio};
+    use {super::*, crate::domain::eth::Asset};
 
     const GNO: eth::H160 = eth::H160(hex_literal::hex!(
         "6810e776880c02933d47db1b9fc05908e5386b96"
@@ -146,7 +140,7 @@ mod tests {
 
         // no cap
         let slippage = Parameters {
-            relative: Ratio::from_float(0.1).unwrap(),
+            relative: Ratio256::from_bps(1_000),
             max: None,
             min: None,
             prices,
@@ -206,7 +200,7 @@ mod tests {
 
         // no cap
         let slippage = Parameters {
-            relative: Ratio::from_float(0.1).unwrap(),
+            relative: Ratio256::from_bps(1_000),
             max: None,
             min: None,
             prices,
@@ -248,7 +242,7 @@ mod tests {
     #[test]
     fn test_no_price() {
         let slippage = Parameters {
-            relative: Ratio::from_float(1.).unwrap(),
+            relative: Ratio256::one(),
             max: Some(eth::U256::exp10(16)),
             min: Some(eth::U256::exp10(18)),
             prices: Default::default(),
@@ -1,5 +1,5 @@
-use crate::{
-    domain::{
+use {
+    crate::domain::{
         competition::{
             self,
             order::{self, FeePolicy, SellAmount, Side, TargetAmount, Uid},
@@ -7,7 +7,7 @@ use crate::{
         },
         eth::{self, Asset},
     },
-    util::conv::u256::U256Ext,
+    number::conversions::{mul_ratio_ceil, mul_ratio_floor, safe_mul_ceil_div, safe_mul_div},
 };
 
 /// A trade which executes an order as part of this solution.
@@ -75,13 +75,7 @@ impl Trade {
     ) -> Result<eth::TokenAmount, error::Math> {
         let before_fee = match self.side() {
             order::Side::Sell => self.executed().0,
-            order::Side::Buy => self
-                .executed()
-                .0
-                .checked_mul(prices.buy)
-                .ok_or(Math::Overflow)?
-                .checked_div(prices.sell)
-                .ok_or(Math::DivisionByZero)?,
+            order::Side::Buy => safe_mul_div(self.executed().0, prices.buy, prices.sell)?,
         };
         Ok(eth::TokenAmount(
             before_fee.checked_add(self.fee().0).ok_or(Math::Overflow)?,
@@ -97,13 +91,7 @@ impl Trade {
     ) -> Result<eth::TokenAmount, error::Math> {
         let amount = match self.side() {
             order::Side::Buy => self.executed().0,
-            order::Side::Sell => self
-                .executed()
-                .0
-                .checked_mul(prices.sell)
-                .ok_or(Math::Overflow)?
-                .checked_ceil_div(&prices.buy)
-                .ok_or(Math::DivisionByZero)?,
+            order::Side::Sell => safe_mul_ceil_div(self.executed().0, prices.sell, prices.buy)?,
         };
         Ok(eth::TokenAmount(amount))
     }
@@ -212,13 +200,7 @@ impl Fulfillment {
     pub fn sell_amount(&self, prices: &ClearingPrices) -> Result<eth::TokenAmount, error::Math> {
         let before_fee = match self.order.side {
             order::Side::Sell => self.executed.0,
-            order::Side::Buy => self
-                .executed
-                .0
-                .checked_mul(prices.buy)
-                .ok_or(Math::Overflow)?
-                .checked_div(prices.sell)
-                .ok_or(Math::DivisionByZero)?,
+            order::Side::Buy => safe_mul_div(self.executed.0, prices.buy, prices.sell)?,
         };
         Ok(eth::TokenAmount(
             before_fee.checked_add(self.fee().0).ok_or(Math::Overflow)?,
@@ -231,13 +213,7 @@ impl Fulfillment {
     pub fn buy_amount(&self, prices: &ClearingPrices) -> Result<eth::TokenAmount, error::Math> {
         let amount = match self.order.side {
             order::Side::Buy => self.executed.0,
-            order::Side::Sell => self
-                .executed
-                .0
-                .checked_mul(prices.sell)
-                .ok_or(Math::Overflow)?
-                .checked_ceil_div(&prices.buy)
-                .ok_or(Math::DivisionByZero)?,
+            order::Side::Sell => safe_mul_ceil_div(self.executed.0, prices.sell, prices.buy)?,
         };
         Ok(eth::TokenAmount(amount))
     }
@@ -266,11 +242,7 @@ impl Fulfillment {
         let executed_sell_amount = match self.order().side {
             Side::Buy => {
                 // How much `sell_token` we need to sell to buy `executed` amount of `buy_token`
-                executed
-                    .checked_mul(prices.buy)
-                    .ok_or(Math::Overflow)?
-                    .checked_div(prices.sell)
-                    .ok_or(Math::DivisionByZero)?
+                mul_ratio_floor(executed, prices.buy, prices.sell).map_err(Math::from)?
             }
             Side::Sell => executed,
         };
@@ -286,11 +258,8 @@ impl Fulfillment {
         let surplus = match self.order().side {
             Side::Buy => {
                 // Scale to support partially fillable orders
-                let limit_sell_amount = limit_sell
-                    .checked_mul(executed)
-                    .ok_or(Math::Overflow)?
-                    .checked_div(limit_buy)
-                    .ok_or(Math::DivisionByZero)?;
+                let limit_sell_amount =
+                    mul_ratio_floor(limit_sell, executed, limit_buy).map_err(Math::from)?;
                 // Remaining surplus after fees
                 // Do not return error if `checked_sub` fails because violated limit prices will
                 // be caught by simulation
@@ -301,21 +270,16 @@ impl Fulfillment {
             Side::Sell => {
                 // Scale to support partially fillable orders
 
-                // `checked_ceil_div`` to be consistent with how settlement contract calculates
+                // `mul_ratio_ceil` to be consistent with how settlement contract calculates
                 // traded buy amounts
                 // smallest allowed executed_buy_amount per settlement contract is
                 // executed_sell_amount * ceil(price_limits.buy / price_limits.sell)
-                let limit_buy_amount = limit_buy
-                    .checked_mul(executed_sell_amount_with_fee)
-                    .ok_or(Math::Overflow)?
-                    .checked_ceil_div(&limit_sell)
-                    .ok_or(Math::DivisionByZero)?;
+                let limit_buy_amount =
+                    mul_ratio_ceil(limit_buy, executed_sell_amount_with_fee, limit_sell)
+                        .map_err(Math::from)?;
                 // How much `buy_token` we get for `executed` amount of `sell_token`
-                let executed_buy_amount = executed
-                    .checked_mul(prices.sell)
-                    .ok_or(Math::Overflow)?
-                    .checked_ceil_div(&prices.buy)
-                    .ok_or(Math::DivisionByZero)?;
+                let executed_buy_amount =
+                    mul_ratio_ceil(executed, prices.sell, prices.buy).map_err(Math::from)?;
                 // Remaining surplus after fees
                 // Do not return error if `checked_sub` fails because violated limit prices will
                 // be caught by simulation
@@ -421,29 +385,26 @@ impl Jit {
     pub fn executed_buy(&self) -> Result<eth::TokenAmount, Math> {
         Ok(match self.order().side {
             Side::Buy => self.executed().into(),
-            Side::Sell => (self
-                .executed()
-                .0
-                .checked_add(self.fee().0)
-                .ok_or(Math::Overflow)?)
-            .checked_mul(self.order.buy.amount.0)
-            .ok_or(Math::Overflow)?
-            .checked_ceil_div(&self.order.sell.amount.0)
-            .ok_or(Math::DivisionByZero)?
+            Side::Sell => mul_ratio_ceil(
+                self.executed()
+                    .0
+                    .checked_add(self.fee().0)
+                    .ok_or(Math::Overflow)?,
+                self.order.buy.amount.0,
+                self.order.sell.amount.0,
+            )?
             .into(),
         })
     }
 
     pub fn executed_sell(&self) -> Result<eth::TokenAmount, Math> {
         Ok(match self.order().side {
-            Side::Buy => self
-                .executed()
-                .0
-                .checked_mul(self.order.sell.amount.0)
-                .ok_or(Math::Overflow)?
-                .checked_div(self.order.buy.amount.0)
-                .ok_or(Math::DivisionByZero)?
-                .into(),
+            Side::Buy => mul_ratio_floor(
+                self.executed().0,
+                self.order.sell.amount.0,
+                self.order.buy.amount.0,
+            )?
+            .into(),
             Side::Sell => self
                 .executed()
                 .0
@@ -13,7 +13,12 @@ use {
     },
     anyhow::Context,
     ethrpc::block_stream::into_stream,
-    futures::{future::select_ok, FutureExt, StreamExt},
+    futures::{
+        future::{select, select_ok, Either},
+        FutureExt,
+        StreamExt,
+    },
+    std::time::Duration,
     thiserror::Error,
     tracing::Instrument,
 };
@@ -29,15 +34,39 @@ const CANCELLATION_GAS_AMOUNT: u64 = 21000;
 #[derive(Debug, Clone)]
 pub struct Mempools {
     mempools: Vec<infra::Mempool>,
+    public_fallback: Option<PublicFallback>,
     ethereum: Ethereum,
 }
 
+/// Re-submission to the public mempool for settlements that did not get
+/// included via the configured (private) mempools in time.
+#[derive(Debug, Clone)]
+pub struct PublicFallback {
+    pub mempool: infra::Mempool,
+    /// How long to wait for inclusion before falling back.
+    pub delay: Duration,
+    /// The gas an attacker has to spend on the front- and back-running
+    /// transactions of a sandwich attack.
+    pub sandwich_gas: eth::Gas,
+}
+
 impl Mempools {
     pub fn try_new(mempools: Vec<infra::Mempool>, ethereum: Ethereum) -> Result<Self, NoMempools> {
         if mempools.is_empty() {
             Err(NoMempools)
         } else {
-            Ok(Self { mempools, ethereum })
+            Ok(Self {
+                mempools,
+                public_fallback: None,
+                ethereum,
+            })
+        }
+    }
+
+    pub fn with_public_fallback(self, public_fallback: Option<PublicFallback>) -> Self {
+        Self {
+            public_fallback,
+            ..self
         }
     }
 
@@ -48,20 +77,51 @@ impl Mempools {
         settlement: &Settlement,
         submission_deadline: BlockNo,
     ) -> Result<eth::TxId, Error> {
-        let (tx_hash, _remaining_futures) =
-            select_ok(self.mempools.iter().cloned().map(|mempool| {
-                async move {
-                    let result = self
-                        .submit(&mempool, solver, settlement, submission_deadline)
+        let submissions = select_ok(self.mempools.iter().cloned().map(|mempool| {
+            async move {
+                // Don't submit risky transactions if revert protection is
+                // enabled and the settlement may revert in this mempool.
+                let result = if settlement.may_revert()
+                    && matches!(self.revert_protection(), RevertProtection::Enabled)
+                    && mempool.may_revert()
+                {
+                    Err(Error::Disabled)
+                } else {
+                    self.submit(&mempool, solver, settlement, submission_deadline)
                         .instrument(tracing::info_span!("mempool", kind = mempool.to_string()))
-                        .await;
-                    observe::mempool_executed(&mempool, settlement, &result);
-                    result
-                }
-                .boxed()
-            }))
-            .await?;
+                        .await
+                };
+                observe::mempool_executed(&mempool, settlement, &result);
+                result
+            }
+            .boxed()
+        }))
+        .map(|result| result.map(|(tx_hash, _remaining_futures)| tx_hash))
+        .boxed();
 
+        let Some(fallback) = &self.public_fallback else {
+            return submissions.await;
+        };
+        let submissions =
+            match select(submissions, tokio::time::sleep(fallback.delay).boxed()).await {
+                Either::Left((result, _)) => return result,
+                Either::Right((_, submissions)) => submissions,
+            };
+        let safe = settlement.sandwich_unprofitable(fallback.sandwich_gas);
+        observe::public_fallback(solver.name(), fallback.delay, safe);
+        if !safe {
+            return submissions.await;
+        }
+        let public = async {
+            let result = self
+                .submit(&fallback.mempool, solver, settlement, submission_deadline)
+                .instrument(tracing::info_span!("mempool", kind = "PublicFallback"))
+                .await;
+            observe::mempool_executed(&fallback.mempool, settlement, &result);
+            result
+        }
+        .boxed();
+        let (tx_hash, _remaining_futures) = select_ok([submissions, public]).await?;
         Ok(tx_hash)
     }
 
@@ -90,15 +150,6 @@ impl Mempools {
         settlement: &Settlement,
         submission_deadline: BlockNo,
     ) -> Result<eth::TxId, Error> {
-        // Don't submit risky transactions if revert protection is
-        // enabled and the settlement may revert in this mempool.
-        if settlement.may_revert()
-            && matches!(self.revert_protection(), RevertProtection::Enabled)
-            && mempool.may_revert()
-        {
-            return Err(Error::Disabled);
-        }
-
         let tx = settlement.transaction(settlement::Internalization::Enable);
 
         // Instantiate block stream and skip the current block before we submit the
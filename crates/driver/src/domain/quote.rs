@@ -100,7 +100,7 @@ impl Order {
         let auction = self
             .fake_auction(eth, tokens, solver.quote_using_limit_orders())
             .await?;
-        let solutions = solver.solve(&auction, &liquidity).await?;
+        let (solutions, _) = solver.solve(&auction, &liquidity).await?;
         Quote::try_new(
             eth,
             // TODO(#1468): choose the best solution in the future, but for now just pick the
@@ -268,7 +268,7 @@ mod encode {
                 allowance::{Approval, Required},
             },
         },
-        num::rational::Ratio,
+        number::ratio::Ratio256,
     };
 
     const DEFAULT_QUOTE_SLIPPAGE_BPS: u32 = 100;
@@ -278,7 +278,7 @@ mod encode {
         settlement: &contracts::GPv2Settlement,
     ) -> Result<Vec<eth::Interaction>, solution::encoding::Error> {
         let slippage = solution::slippage::Parameters {
-            relative: Ratio::new_raw(DEFAULT_QUOTE_SLIPPAGE_BPS.into(), 10_000.into()),
+            relative: Ratio256::from_bps(DEFAULT_QUOTE_SLIPPAGE_BPS),
             max: None,
             min: None,
             prices: Default::default(),
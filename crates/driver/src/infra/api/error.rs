@@ -8,7 +8,7 @@ use {
 
 #[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "PascalCase")]
-enum Kind {
+pub(super) enum Kind {
     QuotingFailed,
     SolverFailed,
     TooManyPendingSettlements,
@@ -21,6 +21,11 @@ enum Kind {
     InvalidAmounts,
     QuoteSameTokens,
     FailedToSubmit,
+    Unauthorized,
+    RequestTooLarge,
+    InvalidRequestBody,
+    RequestTimeout,
+    QuoteOnly,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,9 +58,21 @@ impl From<Kind> for (hyper::StatusCode, axum::Json<Error>) {
             }
             Kind::FailedToSubmit => "Could not submit the solution to the blockchain",
             Kind::TooManyPendingSettlements => "Settlement queue is full",
+            Kind::Unauthorized => "Missing or invalid authentication",
+            Kind::RequestTooLarge => "Request body exceeds the size limit",
+            Kind::InvalidRequestBody => "Request body could not be read",
+            Kind::RequestTimeout => "Request took too long to process",
+            Kind::QuoteOnly => "The driver only serves quotes",
+        };
+        let status = match value {
+            Kind::Unauthorized => hyper::StatusCode::UNAUTHORIZED,
+            Kind::RequestTooLarge => hyper::StatusCode::PAYLOAD_TOO_LARGE,
+            Kind::RequestTimeout => hyper::StatusCode::REQUEST_TIMEOUT,
+            Kind::QuoteOnly => hyper::StatusCode::NOT_FOUND,
+            _ => hyper::StatusCode::BAD_REQUEST,
         };
         (
-            hyper::StatusCode::BAD_REQUEST,
+            status,
             axum::Json(Error {
                 kind: value,
                 description,
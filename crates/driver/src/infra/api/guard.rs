@@ -0,0 +1,238 @@
+//! Middleware protecting the solver endpoints from oversized, unauthenticated
+//! and long running requests.
+
+use {
+    super::{error::Kind, Auth, Config},
+    crate::infra::{observe, solver},
+    axum::{
+        body::{Body, Bytes},
+        http::Request,
+        middleware::Next,
+        response::{IntoResponse, Response},
+    },
+    hmac::{Hmac, Mac},
+    hyper::body::HttpBody,
+    sha2::Sha256,
+    std::{
+        sync::Arc,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
+
+const TIMESTAMP_HEADER: &str = "X-Timestamp";
+const SIGNATURE_HEADER: &str = "X-Signature";
+
+pub(super) struct Guard {
+    pub solver: solver::Name,
+    pub config: Arc<Config>,
+}
+
+pub(super) async fn guard(
+    axum::extract::State(guard): axum::extract::State<Arc<Guard>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let endpoint = endpoint(request.uri().path());
+    let reject = |kind: Kind, reason: &str| {
+        observe::rejected_request(&guard.solver, endpoint, reason);
+        <(hyper::StatusCode, axum::Json<super::error::Error>)>::from(kind).into_response()
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match read_body(body, guard.config.request_body_limit).await {
+        Ok(body) => body,
+        Err(BodyError::TooLarge) => return reject(Kind::RequestTooLarge, "too_large"),
+        Err(BodyError::Invalid) => return reject(Kind::InvalidRequestBody, "invalid_body"),
+    };
+
+    if let Some(auth) = &guard.config.auth {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let authorized = match auth {
+            Auth::Bearer(token) => header(axum::http::header::AUTHORIZATION.as_str())
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes())),
+            Auth::Hmac {
+                secret,
+                max_clock_skew,
+            } => match (header(TIMESTAMP_HEADER), header(SIGNATURE_HEADER)) {
+                (Some(timestamp), Some(signature)) => verify_signature(
+                    secret,
+                    *max_clock_skew,
+                    timestamp,
+                    signature,
+                    &body,
+                    SystemTime::now(),
+                ),
+                _ => false,
+            },
+        };
+        if !authorized {
+            return reject(Kind::Unauthorized, "unauthorized");
+        }
+    }
+
+    let request = Request::from_parts(parts, Body::from(body));
+    match guard.config.timeouts.get(endpoint) {
+        Some(timeout) => match tokio::time::timeout(timeout, next.run(request)).await {
+            Ok(response) => response,
+            Err(_) => reject(Kind::RequestTimeout, "timeout"),
+        },
+        None => next.run(request).await,
+    }
+}
+
+/// The name of the endpoint used for metrics and timeouts. Solver routers
+/// are nested, so the path only contains the endpoint itself.
+fn endpoint(path: &str) -> &str {
+    match path.trim_matches('/') {
+        "" => "info",
+        endpoint => endpoint,
+    }
+}
+
+enum BodyError {
+    TooLarge,
+    Invalid,
+}
+
+/// Reads the whole body while making sure that it does not exceed the limit.
+async fn read_body(mut body: Body, limit: usize) -> Result<Bytes, BodyError> {
+    // Reject bodies with a known size early without reading them.
+    if body.size_hint().lower() > limit as u64 {
+        return Err(BodyError::TooLarge);
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| BodyError::Invalid)?;
+        if bytes.len() + chunk.len() > limit {
+            return Err(BodyError::TooLarge);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes.into())
+}
+
+/// Verifies that the signature is the hex encoded HMAC-SHA256 of
+/// `<timestamp>.<body>` and that the timestamp (in seconds since the unix
+/// epoch) is close enough to the current time.
+fn verify_signature(
+    secret: &[u8],
+    max_clock_skew: Duration,
+    timestamp: &str,
+    signature: &str,
+    body: &[u8],
+    now: SystemTime,
+) -> bool {
+    let Ok(seconds) = timestamp.parse::<u64>() else {
+        return false;
+    };
+    let Ok(now) = now.duration_since(UNIX_EPOCH) else {
+        return false;
+    };
+    if now.as_secs().abs_diff(seconds) > max_clock_skew.as_secs() {
+        return false;
+    }
+    let Ok(signature) = hex::decode(signature.trim_start_matches("0x")) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Compares the values without short circuiting to not leak the position of
+/// the first mismatch through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], timestamp: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(format!("{timestamp}.").as_bytes());
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn verifies_signatures() {
+        let secret = b"secret";
+        let skew = Duration::from_secs(30);
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let body = br#"{"id":"1"}"#;
+        let signature = sign(secret, "1000", body);
+
+        assert!(verify_signature(
+            secret, skew, "1000", &signature, body, now
+        ));
+        assert!(verify_signature(
+            secret,
+            skew,
+            "1000",
+            &format!("0x{signature}"),
+            body,
+            now + skew,
+        ));
+        // Replayed too late.
+        assert!(!verify_signature(
+            secret,
+            skew,
+            "1000",
+            &signature,
+            body,
+            now + skew + Duration::from_secs(1),
+        ));
+        // Tampered body.
+        assert!(!verify_signature(
+            secret,
+            skew,
+            "1000",
+            &signature,
+            br#"{"id":"2"}"#,
+            now
+        ));
+        // Wrong secret.
+        assert!(!verify_signature(
+            b"other", skew, "1000", &signature, body, now
+        ));
+        // Malformed headers.
+        assert!(!verify_signature(
+            secret, skew, "now", &signature, body, now
+        ));
+        assert!(!verify_signature(secret, skew, "1000", "zz", body, now));
+    }
+
+    #[test]
+    fn compares_in_constant_time() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokem"));
+        assert!(!constant_time_eq(b"token", b"token2"));
+    }
+
+    #[test]
+    fn endpoints() {
+        assert_eq!(endpoint("/"), "info");
+        assert_eq!(endpoint(""), "info");
+        assert_eq!(endpoint("/solve"), "solve");
+        assert_eq!(endpoint("/settle/"), "settle");
+    }
+
+    #[tokio::test]
+    async fn limits_body_size() {
+        assert!(read_body(Body::from("1234"), 4).await.is_ok());
+        assert!(matches!(
+            read_body(Body::from("12345"), 4).await,
+            Err(BodyError::TooLarge)
+        ));
+    }
+}
@@ -1,6 +1,10 @@
 use {
     crate::{
-        domain::{self, competition::bad_tokens, Mempools},
+        domain::{
+            self,
+            competition::{bad_tokens, executed_orders},
+            Mempools,
+        },
         infra::{
             self,
             config::file::OrderPriorityStrategy,
@@ -13,23 +17,85 @@ use {
     },
     error::Error,
     futures::Future,
-    std::{net::SocketAddr, sync::Arc},
+    std::{net::SocketAddr, sync::Arc, time::Duration},
     tokio::sync::oneshot,
 };
 
 mod error;
+mod guard;
 mod routes;
 
-const REQUEST_BODY_LIMIT: usize = 10 * 1024 * 1024;
+/// Limits and authentication applied to the requests of every solver.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Maximum size in bytes of request bodies.
+    pub request_body_limit: usize,
+    pub timeouts: Timeouts,
+    pub auth: Option<Auth>,
+    /// Memory in bytes the driver may allocate. See [`infra::memory`].
+    pub memory_budget: Option<usize>,
+}
+
+/// Maximum time requests to the individual endpoints may take. Endpoints
+/// without a timeout are only limited by the deadlines of the requests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timeouts {
+    pub quote: Option<Duration>,
+    pub solve: Option<Duration>,
+    pub reveal: Option<Duration>,
+    pub settle: Option<Duration>,
+}
+
+impl Timeouts {
+    fn get(&self, endpoint: &str) -> Option<Duration> {
+        match endpoint {
+            "quote" => self.quote,
+            "solve" => self.solve,
+            "reveal" => self.reveal,
+            "settle" => self.settle,
+            _ => None,
+        }
+    }
+}
+
+/// Authentication required from the clients of the API.
+#[derive(Clone)]
+pub enum Auth {
+    /// Requests need to contain an `Authorization: Bearer <token>` header.
+    Bearer(String),
+    /// Requests need to be signed with the HMAC-SHA256 of a shared secret.
+    Hmac {
+        secret: Vec<u8>,
+        max_clock_skew: Duration,
+    },
+}
+
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Don't leak the secrets into the logs.
+        match self {
+            Self::Bearer(_) => f.debug_tuple("Bearer").field(&"REDACTED").finish(),
+            Self::Hmac { max_clock_skew, .. } => f
+                .debug_struct("Hmac")
+                .field("secret", &"REDACTED")
+                .field("max_clock_skew", max_clock_skew)
+                .finish(),
+        }
+    }
+}
 
 pub struct Api {
     pub solvers: Vec<Solver>,
     pub liquidity: liquidity::Fetcher,
     pub simulator: Simulator,
     pub eth: Ethereum,
-    pub mempools: Mempools,
+    /// Where settlements get submitted to. The driver runs in quote-only mode
+    /// and only serves the `/info` and `/quote` endpoints if not set.
+    pub mempools: Option<Mempools>,
     pub addr: SocketAddr,
+    pub config: Config,
     pub bad_token_detector: bad_tokens::simulation::Detector,
+    pub executed_orders: Arc<executed_orders::Tracker>,
     /// If this channel is specified, the bound address will be sent to it. This
     /// allows the driver to bind to 0.0.0.0:0 during testing.
     pub addr_sender: Option<oneshot::Sender<SocketAddr>>,
@@ -43,20 +109,19 @@ impl Api {
     ) -> Result<(), hyper::Error> {
         // Add middleware.
         let mut app = axum::Router::new().layer(
-            tower::ServiceBuilder::new()
-                .layer(tower_http::limit::RequestBodyLimitLayer::new(
-                    REQUEST_BODY_LIMIT,
-                ))
-                .layer(tower_http::trace::TraceLayer::new_for_http()),
+            tower::ServiceBuilder::new().layer(tower_http::trace::TraceLayer::new_for_http()),
         );
+        let config = Arc::new(self.config);
+        infra::memory::start_sampling();
 
         let tokens = tokens::Fetcher::new(&self.eth);
         let pre_processor =
             domain::competition::AuctionProcessor::new(&self.eth, order_priority_strategies);
 
-        // Add the metrics and healthz endpoints.
+        // Add the metrics, healthz and ready endpoints.
         app = routes::metrics(app);
         app = routes::healthz(app);
+        app = routes::ready(app);
 
         // Multiplex each solver as part of the API. Multiple solvers are multiplexed
         // on the same driver so only one liquidity collector collects the liquidity
@@ -67,9 +132,21 @@ impl Api {
             let router = axum::Router::new();
             let router = routes::info(router);
             let router = routes::quote(router);
-            let router = routes::solve(router);
-            let router = routes::reveal(router);
-            let router = routes::settle(router);
+            let router = match self.mempools {
+                Some(_) => {
+                    let router = routes::solve(router);
+                    let router = routes::reveal(router);
+                    routes::settle(router)
+                }
+                None => router,
+            };
+            let router = router.route_layer(axum::middleware::from_fn_with_state(
+                Arc::new(guard::Guard {
+                    solver: name.clone(),
+                    config: config.clone(),
+                }),
+                guard::guard,
+            ));
 
             let bad_token_config = solver.bad_token_detection();
             let mut bad_tokens =
@@ -90,23 +167,28 @@ impl Api {
             let router = router.with_state(State(Arc::new(Inner {
                 eth: self.eth.clone(),
                 solver: solver.clone(),
-                competition: domain::Competition::new(
-                    solver,
-                    self.eth.clone(),
-                    self.liquidity.clone(),
-                    self.simulator.clone(),
-                    self.mempools.clone(),
-                    Arc::new(bad_tokens),
-                ),
+                competition: self.mempools.clone().map(|mempools| {
+                    domain::Competition::new(
+                        solver,
+                        self.eth.clone(),
+                        self.liquidity.clone(),
+                        self.simulator.clone(),
+                        mempools,
+                        Arc::new(bad_tokens),
+                        self.executed_orders.clone(),
+                    )
+                }),
                 liquidity: self.liquidity.clone(),
                 tokens: tokens.clone(),
                 pre_processor: pre_processor.clone(),
+                memory_budget: config.memory_budget.map(infra::memory::Budget::new),
             })));
             let path = format!("/{name}");
             infra::observe::mounting_solver(&name, &path);
             app = app
                 .nest(&path, router)
-                // axum's default body limit needs to be disabled to not have the default limit on top of our custom limit
+                // axum's default body limit needs to be disabled to not have the default limit on
+                // top of the configured limit enforced by the guard
                 .layer(axum::extract::DefaultBodyLimit::disable());
         }
 
@@ -134,8 +216,14 @@ impl State {
         &self.0.solver
     }
 
-    fn competition(&self) -> &domain::Competition {
-        &self.0.competition
+    /// The competition of the solver, which doesn't exist in quote-only mode.
+    fn competition(
+        &self,
+    ) -> Result<&Arc<domain::Competition>, (hyper::StatusCode, axum::Json<Error>)> {
+        self.0
+            .competition
+            .as_ref()
+            .ok_or_else(|| error::Kind::QuoteOnly.into())
     }
 
     fn liquidity(&self) -> &liquidity::Fetcher {
@@ -153,13 +241,20 @@ impl State {
     fn timeouts(&self) -> Timeouts {
         self.0.solver.timeouts()
     }
+
+    fn memory_budget(&self) -> Option<&infra::memory::Budget> {
+        self.0.memory_budget.as_ref()
+    }
 }
 
 struct Inner {
     eth: Ethereum,
     solver: Solver,
-    competition: Arc<domain::Competition>,
+    /// Not set in quote-only mode.
+    competition: Option<Arc<domain::Competition>>,
     liquidity: liquidity::Fetcher,
     tokens: tokens::Fetcher,
     pre_processor: domain::competition::AuctionProcessor,
+    /// Not set if the memory of the driver is not limited.
+    memory_budget: Option<infra::memory::Budget>,
 }
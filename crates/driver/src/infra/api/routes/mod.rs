@@ -2,6 +2,7 @@ mod healthz;
 mod info;
 mod metrics;
 mod quote;
+mod ready;
 mod reveal;
 mod settle;
 mod solve;
@@ -11,6 +12,7 @@ pub(super) use {
     info::info,
     metrics::metrics,
     quote::{quote, OrderError},
+    ready::ready,
     reveal::reveal,
     settle::settle,
     solve::{solve, AuctionError},
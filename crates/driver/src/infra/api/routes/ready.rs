@@ -0,0 +1,17 @@
+use axum::{http::StatusCode, response::IntoResponse, routing::get};
+
+pub(in crate::infra::api) fn ready(app: axum::Router<()>) -> axum::Router<()> {
+    app.route("/ready", get(route))
+}
+
+/// Reports whether the warm-up conditions of the driver, like initialised
+/// liquidity sources, are met.
+async fn route() -> impl IntoResponse {
+    let report = observe::readiness::gate().report();
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, axum::Json(report))
+}
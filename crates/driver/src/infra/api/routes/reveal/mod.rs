@@ -19,7 +19,7 @@ async fn route(
     let handle_request = async {
         observe::revealing();
         let result = state
-            .competition()
+            .competition()?
             .reveal(req.solution_id, req.auction_id)
             .await;
         observe::revealed(state.solver().name(), &result);
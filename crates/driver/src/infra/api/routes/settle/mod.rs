@@ -25,11 +25,11 @@ async fn route(
         .transpose()
         .map_err(Into::<api::routes::AuctionError>::into)?;
     let solver = state.solver().name().to_string();
+    let competition = state.competition()?.clone();
 
     let handle_request = async move {
         observe::settling();
-        let result = state
-            .competition()
+        let result = competition
             .settle(
                 auction_id,
                 req.solution_id,
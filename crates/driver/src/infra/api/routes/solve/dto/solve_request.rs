@@ -19,7 +19,9 @@ impl SolveRequest {
         eth: &Ethereum,
         tokens: &tokens::Fetcher,
         timeouts: Timeouts,
+        received_at: chrono::DateTime<chrono::Utc>,
     ) -> Result<competition::Auction, Error> {
+        let deadline = self.deadline(received_at);
         let token_addresses: Vec<_> = self
             .tokens
             .iter()
@@ -153,7 +155,7 @@ impl SolveRequest {
                     trusted: token.trusted,
                 }
             }),
-            time::Deadline::new(self.deadline, timeouts),
+            time::Deadline::new(deadline, timeouts),
             eth,
             self.surplus_capturing_jit_order_owners
                 .into_iter()
@@ -204,6 +206,9 @@ pub struct SolveRequest {
     tokens: Vec<Token>,
     orders: Vec<Order>,
     deadline: chrono::DateTime<chrono::Utc>,
+    /// When the autopilot sent the request according to its clock.
+    #[serde(default)]
+    sent_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(default)]
     surplus_capturing_jit_order_owners: Vec<eth::H160>,
 }
@@ -212,6 +217,30 @@ impl SolveRequest {
     pub fn id(&self) -> i64 {
         self.id
     }
+
+    /// How far the clock of the driver is ahead of the autopilot's, including
+    /// the time the request took to arrive.
+    pub fn clock_offset(
+        &self,
+        received_at: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::Duration> {
+        Some(received_at - self.sent_at?)
+    }
+
+    /// The deadline according to the clock of the driver. If the autopilot
+    /// told when it sent the request, this is the earlier of the deadline and
+    /// the time until the deadline counted from receiving the request. The
+    /// latter keeps a clock behind the autopilot's from delaying the deadline,
+    /// the former keeps the time the request took to arrive from doing so.
+    fn deadline(
+        &self,
+        received_at: chrono::DateTime<chrono::Utc>,
+    ) -> chrono::DateTime<chrono::Utc> {
+        match self.sent_at {
+            Some(sent_at) => self.deadline.min(received_at + (self.deadline - sent_at)),
+            None => self.deadline,
+        }
+    }
 }
 
 #[serde_as]
@@ -359,3 +388,44 @@ impl Quote {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interprets_deadline_relative_to_sending_if_earlier() {
+        let request = |sent_at: &str| {
+            serde_json::from_value::<SolveRequest>(serde_json::json!({
+                "id": "1",
+                "tokens": [],
+                "orders": [],
+                "deadline": "2024-01-01T00:00:10Z",
+                "sentAt": sent_at,
+            }))
+            .unwrap()
+        };
+        let time = |time: &str| time.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+
+        // The clock of the driver is 2s ahead of the autopilot's and the
+        // request took 100ms to arrive.
+        let received_at = time("2024-01-01T00:00:02.1Z");
+        let request = request("2024-01-01T00:00:00Z");
+        assert_eq!(request.deadline(received_at), time("2024-01-01T00:00:10Z"));
+        assert_eq!(
+            request.clock_offset(received_at),
+            Some(chrono::Duration::milliseconds(2_100))
+        );
+
+        // The clock of the driver is 2s behind the autopilot's.
+        let received_at = time("2023-12-31T23:59:58.1Z");
+        assert_eq!(
+            request.deadline(received_at),
+            time("2024-01-01T00:00:08.1Z")
+        );
+        assert_eq!(
+            request.clock_offset(received_at),
+            Some(chrono::Duration::milliseconds(-1_900))
+        );
+    }
+}
@@ -1,21 +1,50 @@
 use {
     crate::{
         domain::{competition, competition::order, eth},
-        infra::Solver,
+        infra::{self, Solver},
         util::serialize,
     },
     serde::Serialize,
     serde_with::serde_as,
-    std::collections::HashMap,
+    std::{collections::HashMap, num::NonZeroUsize, time::Duration},
 };
 
 impl SolveResponse {
-    pub fn new(solved: Option<competition::Solved>, solver: &Solver) -> Self {
+    pub fn new(
+        solved: Option<competition::Solved>,
+        solver: &Solver,
+        max_orders: Option<NonZeroUsize>,
+        received_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
         let solutions = solved
             .into_iter()
             .map(|solved| Solution::new(solved.id.get(), solved, solver))
             .collect();
-        Self { solutions }
+        Self {
+            solutions,
+            max_orders,
+            deadline_extension: solver.deadline_extension(),
+            received_at,
+        }
+    }
+}
+
+impl axum::response::IntoResponse for SolveResponse {
+    fn into_response(self) -> axum::response::Response {
+        // The time of sending gets stamped after encoding the rest of the
+        // response, so that the encoding doesn't count as network delay when
+        // the autopilot estimates the clock offset.
+        let mut body = serde_json::to_vec(&self).expect("responses are serializable");
+        let closing_brace = body.pop();
+        debug_assert_eq!(closing_brace, Some(b'}'));
+        body.extend_from_slice(br#","sentAt":"#);
+        serde_json::to_writer(&mut body, &infra::time::now()).expect("timestamps are serializable");
+        body.push(b'}');
+        (
+            [(axum::http::header::CONTENT_TYPE, "application/json")],
+            body,
+        )
+            .into_response()
     }
 }
 
@@ -24,6 +53,19 @@ impl SolveResponse {
 #[serde(rename_all = "camelCase")]
 pub struct SolveResponse {
     solutions: Vec<Solution>,
+    /// The maximum number of orders future auctions should have to stay
+    /// within the memory budget of the driver.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_orders: Option<NonZeroUsize>,
+    /// How much longer than the regular deadline future auctions should give
+    /// the solver.
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deadline_extension: Option<Duration>,
+    /// When the driver received the request according to its clock. Together
+    /// with the time of sending the response, which gets stamped when
+    /// responding, the autopilot can estimate the clock offset.
+    received_at: chrono::DateTime<chrono::Utc>,
 }
 
 impl Solution {
@@ -58,6 +100,12 @@ impl Solution {
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
                 .collect(),
+            gas: solved.gas.and_then(|gas| u64::try_from(gas.0).ok()),
+            timings: solved.timings.map(|timings| Timings {
+                liquidity_processing: timings.liquidity_processing,
+                optimization: timings.optimization,
+                encoding: timings.encoding,
+            }),
         }
     }
 }
@@ -78,6 +126,29 @@ pub struct Solution {
     orders: HashMap<OrderId, TradedOrder>,
     #[serde_as(as = "HashMap<_, serialize::U256>")]
     clearing_prices: HashMap<eth::H160, eth::U256>,
+    /// Gas the settlement used in the driver's simulation. Omitted if it
+    /// doesn't fit into 64 bits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas: Option<u64>,
+    /// Milliseconds the solver engine spent in the different phases of solving
+    /// the auction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<Timings>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timings {
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    liquidity_processing: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    optimization: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<Duration>,
 }
 
 #[serde_as]
@@ -3,7 +3,9 @@ mod dto;
 pub use dto::AuctionError;
 use {
     crate::infra::{
+        self,
         api::{Error, State},
+        memory,
         observe,
     },
     std::time::Instant,
@@ -18,31 +20,45 @@ pub(in crate::infra::api) fn solve(router: axum::Router<State>) -> axum::Router<
 async fn route(
     state: axum::extract::State<State>,
     req: axum::Json<dto::SolveRequest>,
-) -> Result<axum::Json<dto::SolveResponse>, (hyper::StatusCode, axum::Json<Error>)> {
+) -> Result<dto::SolveResponse, (hyper::StatusCode, axum::Json<Error>)> {
     let auction_id = req.id();
     let handle_request = async {
+        let received_at = infra::time::now();
         observe::auction(auction_id);
+        if let Some(offset) = req.clock_offset(received_at) {
+            observe::autopilot_clock_offset(state.solver().name(), offset);
+        }
         let start = Instant::now();
+        let request_stage = memory::Stage::start("request");
+        let preprocessing = memory::Stage::start("preprocessing");
         let auction = req
             .0
-            .into_domain(state.eth(), state.tokens(), state.timeouts())
+            .into_domain(state.eth(), state.tokens(), state.timeouts(), received_at)
             .await
             .tap_err(|err| {
                 observe::invalid_dto(err, "auction");
             })?;
         tracing::debug!(elapsed = ?start.elapsed(), "auction task execution time");
-        let competition = state.competition();
+        let competition = state.competition()?;
         let auction = state
             .pre_processor()
             .prioritize(auction, &competition.solver.account().address())
             .await;
+        preprocessing.finish(state.solver().name());
+        let orders = auction.orders().len();
         let result = competition.solve(auction).await;
+        let peak = request_stage.finish(state.solver().name());
+        let max_orders = state
+            .memory_budget()
+            .and_then(|budget| budget.record(orders, peak));
         competition.ensure_settle_queue_capacity()?;
         observe::solved(state.solver().name(), &result);
-        Ok(axum::Json(dto::SolveResponse::new(
+        Ok(dto::SolveResponse::new(
             result?,
             &competition.solver,
-        )))
+            max_orders,
+            received_at,
+        ))
     };
 
     handle_request
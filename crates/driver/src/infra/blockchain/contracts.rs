@@ -33,6 +33,7 @@ impl Contracts {
         addresses: Addresses,
         block_stream: CurrentBlockWatcher,
         archive_node_url: Option<&Url>,
+        http: &boundary::HttpClientFactory,
     ) -> Result<Self, Error> {
         let address_for = |contract: &ethcontract::Contract,
                            address: Option<eth::ContractAddress>| {
@@ -55,7 +56,12 @@ impl Contracts {
 
         let weth = contracts::WETH9::at(
             web3,
-            address_for(contracts::WETH9::raw_contract(), addresses.weth),
+            address_for(
+                contracts::WETH9::raw_contract(),
+                addresses
+                    .weth
+                    .or(chain.custom().map(|config| config.native_token.into())),
+            ),
         );
 
         let settlement_domain_separator = eth::DomainSeparator(
@@ -67,9 +73,9 @@ impl Contracts {
                 .0,
         );
 
-        let archive_node_web3 = archive_node_url
-            .as_ref()
-            .map_or(web3.clone(), |url| boundary::buffered_web3_client(url));
+        let archive_node_web3 = archive_node_url.as_ref().map_or(web3.clone(), |url| {
+            boundary::buffered_web3_client(url, &[], http)
+        });
         let mut cow_amm_registry = cow_amm::Registry::new(archive_node_web3);
         for config in addresses.cow_amms {
             cow_amm_registry
@@ -52,19 +52,20 @@ impl GasPriceEstimator {
                 } => (max_additional_tip, additional_tip_percentage),
             })
             .next();
-        // Use the lowest max_fee_per_gas of all mempools as the max_fee_per_gas
+        // Use the lowest max_fee_per_gas of all mempools as the max_fee_per_gas.
+        // Without mempools (quote-only mode) nothing caps the gas price.
         let max_fee_per_gas = mempools
             .iter()
             .map(|mempool| mempool.gas_price_cap)
             .min()
-            .expect("at least one mempool");
+            .unwrap_or(eth::U256::MAX);
 
         // Use the highest min_priority_fee of all mempools as the min_priority_fee
         let min_priority_fee = mempools
             .iter()
             .map(|mempool| mempool.min_priority_fee)
             .max()
-            .expect("at least one mempool");
+            .unwrap_or_default();
         Ok(Self {
             gas,
             additional_tip,
@@ -1,6 +1,9 @@
 use {
     self::contracts::ContractAt,
-    crate::{boundary, domain::eth},
+    crate::{
+        boundary::{self, HttpClientFactory},
+        domain::{competition::order, eth},
+    },
     chain::Chain,
     ethcontract::{dyns::DynWeb3, errors::ExecutionError},
     ethrpc::block_stream::CurrentBlockWatcher,
@@ -21,19 +24,26 @@ pub struct Rpc {
     web3: DynWeb3,
     chain: Chain,
     url: Url,
+    http: HttpClientFactory,
 }
 
 impl Rpc {
     /// Instantiate an RPC client to an Ethereum (or Ethereum-compatible) node
-    /// at the specifed URL.
-    pub async fn try_new(url: &url::Url) -> Result<Self, RpcError> {
-        let web3 = boundary::buffered_web3_client(url);
+    /// at the specifed URL which fails over to the fallback nodes in order.
+    /// All HTTP clients of the driver get created by the `http` factory.
+    pub async fn try_new(
+        url: &url::Url,
+        fallbacks: &[Url],
+        http: HttpClientFactory,
+    ) -> Result<Self, RpcError> {
+        let web3 = boundary::buffered_web3_client(url, fallbacks, &http);
         let chain = Chain::try_from(web3.eth().chain_id().await?)?;
 
         Ok(Self {
             web3,
             chain,
             url: url.clone(),
+            http,
         })
     }
 
@@ -68,6 +78,7 @@ struct Inner {
     contracts: Contracts,
     gas: Arc<GasPriceEstimator>,
     current_block: CurrentBlockWatcher,
+    http: HttpClientFactory,
 }
 
 impl Ethereum {
@@ -83,7 +94,12 @@ impl Ethereum {
         gas: Arc<GasPriceEstimator>,
         archive_node_url: Option<&Url>,
     ) -> Self {
-        let Rpc { web3, chain, url } = rpc;
+        let Rpc {
+            web3,
+            chain,
+            url,
+            http,
+        } = rpc;
 
         let current_block_stream =
             ethrpc::block_stream::current_block_stream(url, std::time::Duration::from_millis(500))
@@ -96,6 +112,7 @@ impl Ethereum {
             addresses,
             current_block_stream.clone(),
             archive_node_url,
+            &http,
         )
         .await
         .expect("could not initialize important smart contracts");
@@ -106,6 +123,7 @@ impl Ethereum {
                 chain,
                 contracts,
                 gas,
+                http,
             }),
             web3,
         }
@@ -129,6 +147,12 @@ impl Ethereum {
         &self.inner.contracts
     }
 
+    /// Factory for HTTP clients that route requests through the configured
+    /// proxies.
+    pub fn http_client_factory(&self) -> &HttpClientFactory {
+        &self.inner.http
+    }
+
     /// Create a contract instance at the specified address.
     pub fn contract_at<T: ContractAt>(&self, address: eth::ContractAddress) -> T {
         T::at(self, address)
@@ -173,6 +197,7 @@ impl Ethereum {
                 Chain::Sepolia => self.block_gas_limit().0,
                 Chain::Base => self.block_gas_limit().0,
                 Chain::Hardhat => self.block_gas_limit().0,
+                Chain::Custom(_) => self.block_gas_limit().0,
             }),
             gas_price: self.simulation_gas_price().await,
             ..Default::default()
@@ -232,6 +257,17 @@ impl Ethereum {
             .map_err(Into::into)
     }
 
+    /// Returns how much of the order was filled onchain, denominated in the
+    /// sell token for sell orders and in the buy token for buy orders.
+    pub async fn filled_amount(&self, uid: order::Uid) -> Result<eth::U256, Error> {
+        Ok(self
+            .contracts()
+            .settlement()
+            .filled_amount(ethcontract::Bytes(uid.0 .0.to_vec()))
+            .call()
+            .await?)
+    }
+
     /// Returns a [`token::Erc20`] for the specified address.
     pub fn erc20(&self, address: eth::TokenAddress) -> token::Erc20 {
         token::Erc20::new(self, address)
@@ -1,29 +1,58 @@
 use {
     reqwest::Url,
+    shared::{http_client::ProxyRule, logging_args_with_default_filter},
     std::{net::SocketAddr, path::PathBuf},
+    tracing::level_filters::LevelFilter,
 };
 
+logging_args_with_default_filter!(
+    LoggingArguments,
+    "warn,driver=debug,driver::infra::solver=trace,shared=debug,solver=debug"
+);
+
 #[derive(Debug, clap::Parser)]
 pub struct Args {
     /// The address to bind the driver to.
     #[clap(long, env, default_value = "0.0.0.0:11088")]
     pub addr: SocketAddr,
 
-    /// The log filter.
-    #[clap(
-        long,
-        env,
-        default_value = "warn,driver=debug,driver::infra::solver=trace,shared=debug,solver=debug"
-    )]
-    pub log: String,
+    #[clap(flatten)]
+    pub logging: LoggingArguments,
 
     /// The node RPC API endpoint.
     #[clap(long, env)]
     pub ethrpc: Url,
 
+    /// Node RPC API endpoints to fail over to, in order, when the node at
+    /// `ethrpc` returns errors or times out.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub ethrpc_fallbacks: Vec<Url>,
+
+    #[clap(flatten)]
+    pub ethrpc_budget: shared::ethrpc::BudgetArguments,
+
     /// Path to the driver configuration file. This file should be in TOML
     /// format. For an example see
     /// https://github.com/cowprotocol/services/blob/main/crates/driver/example.toml.
     #[clap(long, env)]
     pub config: PathBuf,
+
+    /// Chains that are not built into the driver. Supplied in the form of:
+    /// "<ID>|<NAME>|<BLOCK_TIME_MS>|<NATIVE_TOKEN>|<NATIVE_PRICE_ESTIMATION_AMOUNT>"
+    /// where the native token is the address of the wrapped native token and
+    /// the amount is in native token atoms. The finality depth of the chain in
+    /// blocks can be appended as "|<FINALITY_DEPTH>".
+    #[clap(long, env, use_value_delimiter = true)]
+    pub custom_chains: Vec<chain::ChainConfig>,
+
+    /// Proxy all outgoing HTTP requests are routed through unless overridden,
+    /// including the ones to the node, solvers, simulators and liquidity
+    /// sources. Supports `http`, `https`, `socks5` and `socks5h` URLs.
+    #[clap(long, env, value_parser = shared::http_client::parse_proxy)]
+    pub http_proxy: Option<Url>,
+
+    /// Per-destination overrides of the default proxy in the form of
+    /// "<HOST>|<PROXY>,..." where the proxy can be "direct" to bypass proxies.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub http_proxy_overrides: Vec<ProxyRule>,
 }
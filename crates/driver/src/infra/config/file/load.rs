@@ -1,8 +1,16 @@
 use {
     crate::{
-        domain::{competition::bad_tokens, eth},
+        domain::{
+            competition::{
+                bad_tokens,
+                executed_orders,
+                solution::{authorization, scoring, settlement},
+            },
+            eth,
+        },
         infra::{
             self,
+            api,
             blockchain,
             config::file,
             liquidity,
@@ -13,7 +21,6 @@ use {
     },
     chain::Chain,
     futures::future::join_all,
-    number::conversions::big_decimal_to_big_rational,
     std::path::Path,
     tokio::fs,
 };
@@ -48,14 +55,36 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
         chain,
         "The configured chain ID does not match the connected Ethereum node"
     );
+    let quote_only = config.quote_only;
+    let erc3009_tokens = &authorization::Tokens::new(
+        chain.id(),
+        config
+            .erc3009_tokens
+            .into_iter()
+            .map(|token| authorization::TokenDomain {
+                address: token.address.into(),
+                name: token.name,
+                version: token.version,
+            }),
+    );
     infra::Config {
+        quote_only,
         solvers: join_all(config.solvers.into_iter().map(|config| async move {
             let account = match config.account {
-                file::Account::PrivateKey(private_key) => ethcontract::Account::Offline(
+                // Quotes never get signed so any address works as the solver
+                // account.
+                None if quote_only => {
+                    ethcontract::Account::Local(config.quote_tx_origin.unwrap_or_default(), None)
+                }
+                None => panic!(
+                    "solver {} requires an account unless the driver runs in quote-only mode",
+                    config.name
+                ),
+                Some(file::Account::PrivateKey(private_key)) => ethcontract::Account::Offline(
                     ethcontract::PrivateKey::from_raw(private_key.0).unwrap(),
                     None,
                 ),
-                file::Account::Kms(key_id) => {
+                Some(file::Account::Kms(key_id)) => {
                     let config = ethcontract::aws_config::load_from_env().await;
                     let account =
                         ethcontract::transaction::kms::Account::new((&config).into(), &key_id.0)
@@ -63,13 +92,13 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                             .unwrap_or_else(|_| panic!("Unable to load KMS account {:?}", key_id));
                     ethcontract::Account::Kms(account, None)
                 }
-                file::Account::Address(address) => ethcontract::Account::Local(address, None),
+                Some(file::Account::Address(address)) => ethcontract::Account::Local(address, None),
             };
             solver::Config {
                 endpoint: config.endpoint,
                 name: config.name.into(),
                 slippage: solver::Slippage {
-                    relative: big_decimal_to_big_rational(&config.slippage.relative),
+                    relative: config.slippage.relative,
                     absolute: config.slippage.absolute.map(eth::Ether),
                 },
                 liquidity: if config.skip_liquidity {
@@ -129,6 +158,35 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                         .metrics_strategy_token_freeze_time,
                 },
                 settle_queue_size: config.settle_queue_size,
+                scoring: scoring::Config {
+                    implementation: match config.scoring.implementation {
+                        file::ScoringImplementation::Current => scoring::Implementation::Current,
+                        file::ScoringImplementation::Candidate => {
+                            scoring::Implementation::Candidate
+                        }
+                    },
+                    differential: config.scoring.differential,
+                },
+                interaction_optimization: match config.optimize_interactions {
+                    true => settlement::InteractionOptimization::Enable,
+                    false => settlement::InteractionOptimization::Disable,
+                },
+                buffer_limits: settlement::BufferLimits {
+                    tokens: config
+                        .buffer_limits
+                        .tokens
+                        .into_iter()
+                        .map(|(token, limit)| (token.into(), limit.into()))
+                        .collect(),
+                    settlement: config.buffer_limits.settlement.map(eth::Ether),
+                },
+                shadow: config.shadow.map(|shadow| solver::Shadow {
+                    endpoint: shadow.endpoint,
+                    request_headers: shadow.request_headers,
+                    max_concurrent_auctions: shadow.max_concurrent_auctions,
+                }),
+                erc3009_tokens: erc3009_tokens.clone(),
+                deadline_extension: config.deadline_extension,
             }
         }))
         .await,
@@ -146,35 +204,43 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                 .iter()
                 .cloned()
                 .map(|config| match config {
-                    file::UniswapV2Config::Preset { preset } => match preset {
-                        file::UniswapV2Preset::UniswapV2 => {
-                            liquidity::config::UniswapV2::uniswap_v2(chain)
-                        }
-                        file::UniswapV2Preset::SushiSwap => {
-                            liquidity::config::UniswapV2::sushi_swap(chain)
-                        }
-                        file::UniswapV2Preset::Honeyswap => {
-                            liquidity::config::UniswapV2::honeyswap(chain)
-                        }
-                        file::UniswapV2Preset::Baoswap => {
-                            liquidity::config::UniswapV2::baoswap(chain)
-                        }
-                        file::UniswapV2Preset::PancakeSwap => {
-                            liquidity::config::UniswapV2::pancake_swap(chain)
-                        }
-                        file::UniswapV2Preset::TestnetUniswapV2 => {
-                            liquidity::config::UniswapV2::testnet_uniswapv2(chain)
+                    file::UniswapV2Config::Preset {
+                        preset,
+                        sync_events_reconciliation_blocks,
+                    } => liquidity::config::UniswapV2 {
+                        sync_events_reconciliation_blocks,
+                        ..match preset {
+                            file::UniswapV2Preset::UniswapV2 => {
+                                liquidity::config::UniswapV2::uniswap_v2(chain)
+                            }
+                            file::UniswapV2Preset::SushiSwap => {
+                                liquidity::config::UniswapV2::sushi_swap(chain)
+                            }
+                            file::UniswapV2Preset::Honeyswap => {
+                                liquidity::config::UniswapV2::honeyswap(chain)
+                            }
+                            file::UniswapV2Preset::Baoswap => {
+                                liquidity::config::UniswapV2::baoswap(chain)
+                            }
+                            file::UniswapV2Preset::PancakeSwap => {
+                                liquidity::config::UniswapV2::pancake_swap(chain)
+                            }
+                            file::UniswapV2Preset::TestnetUniswapV2 => {
+                                liquidity::config::UniswapV2::testnet_uniswapv2(chain)
+                            }
                         }
-                    }
-                    .expect("no Uniswap V2 preset for current network"),
+                        .expect("no Uniswap V2 preset for current network")
+                    },
                     file::UniswapV2Config::Manual {
                         router,
                         pool_code,
                         missing_pool_cache_time,
+                        sync_events_reconciliation_blocks,
                     } => liquidity::config::UniswapV2 {
                         router: router.into(),
                         pool_code: pool_code.into(),
                         missing_pool_cache_time,
+                        sync_events_reconciliation_blocks,
                     },
                 })
                 .collect(),
@@ -288,7 +354,9 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                     base_url: config.base_url,
                     api_key: config.api_key,
                     http_timeout: config.http_timeout,
+                    rate_limiter: config.rate_limiter,
                 }),
+            max_cached_pools: config.liquidity.max_cached_pools,
         },
         mempools: config
             .submission
@@ -338,6 +406,24 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
                 },
             })
             .collect(),
+        public_mempool_fallback: config.submission.public_mempool_fallback.map(|fallback| {
+            mempool::FallbackConfig {
+                mempool: mempool::Config {
+                    min_priority_fee: config.submission.min_priority_fee,
+                    gas_price_cap: config.submission.gas_price_cap,
+                    target_confirm_time: config.submission.target_confirm_time,
+                    retry_interval: config.submission.retry_interval,
+                    kind: mempool::Kind::Public {
+                        max_additional_tip: fallback.max_additional_tip,
+                        additional_tip_percentage: fallback.additional_tip_percentage,
+                        // The fallback explicitly accepts the revert risk.
+                        revert_protection: mempool::RevertProtection::Disabled,
+                    },
+                },
+                delay: fallback.delay,
+                sandwich_gas: fallback.sandwich_gas.into(),
+            }
+        }),
         simulator: match (config.tenderly, config.enso) {
             (Some(config), None) => {
                 Some(simulator::Config::Tenderly(simulator::tenderly::Config {
@@ -376,5 +462,29 @@ pub async fn load(chain: Chain, path: &Path) -> infra::Config {
         order_priority_strategies: config.order_priority_strategies,
         archive_node_url: config.archive_node_url,
         simulation_bad_token_max_age: config.simulation_bad_token_max_age,
+        executed_orders: executed_orders::Config {
+            path: config.executed_orders.path,
+            horizon: config.executed_orders.horizon,
+        },
+        api: api::Config {
+            request_body_limit: config.api.request_body_limit,
+            timeouts: api::Timeouts {
+                quote: config.api.timeouts.quote,
+                solve: config.api.timeouts.solve,
+                reveal: config.api.timeouts.reveal,
+                settle: config.api.timeouts.settle,
+            },
+            auth: config.api.auth.map(|auth| match auth {
+                file::ApiAuthConfig::Bearer { token } => api::Auth::Bearer(token),
+                file::ApiAuthConfig::Hmac {
+                    secret,
+                    max_clock_skew,
+                } => api::Auth::Hmac {
+                    secret: secret.into_bytes(),
+                    max_clock_skew,
+                },
+            }),
+            memory_budget: config.api.memory_budget,
+        },
     }
 }
@@ -1,11 +1,17 @@
 pub use load::load;
 use {
     crate::{domain::eth, infra, util::serialize},
+    number::{conversions::big_decimal_to_big_rational, ratio::Ratio256},
     reqwest::Url,
-    serde::{Deserialize, Serialize},
-    serde_with::serde_as,
+    serde::{Deserialize, Deserializer, Serialize},
+    serde_with::{serde_as, DeserializeAs},
     solver::solver::Arn,
-    std::{collections::HashMap, time::Duration},
+    std::{
+        collections::HashMap,
+        num::{NonZeroU64, NonZeroUsize},
+        path::PathBuf,
+        time::Duration,
+    },
 };
 
 mod load;
@@ -20,6 +26,12 @@ struct Config {
     /// value.
     chain_id: Option<u64>,
 
+    /// Only serve quotes. Disables settlement submission and the `/solve`,
+    /// `/reveal` and `/settle` endpoints, so neither mempools nor solver
+    /// accounts need to be configured.
+    #[serde(default)]
+    quote_only: bool,
+
     /// Disable access list simulation, useful for environments that don't
     /// support this, such as less popular blockchains.
     #[serde(default)]
@@ -73,6 +85,129 @@ struct Config {
         default = "default_simulation_bad_token_max_age"
     )]
     simulation_bad_token_max_age: Duration,
+
+    /// Hardening of the API exposed to the autopilot.
+    #[serde(default)]
+    api: ApiConfig,
+
+    /// Tokens supporting ERC-3009 whose transfer authorizations solvers may
+    /// execute as pre-interactions.
+    #[serde(default, rename = "erc3009-token")]
+    erc3009_tokens: Vec<Erc3009TokenConfig>,
+
+    /// Tracking of the orders of recently submitted settlements, which keeps
+    /// them out of auctions until they are confirmed to be fillable again.
+    #[serde(default)]
+    executed_orders: ExecutedOrdersConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ExecutedOrdersConfig {
+    /// File the tracked orders get persisted to so that they survive
+    /// restarts. They are only kept in memory if not specified.
+    path: Option<PathBuf>,
+
+    /// How long orders are tracked at most.
+    #[serde(with = "humantime_serde", default = "default_executed_orders_horizon")]
+    horizon: Duration,
+}
+
+impl Default for ExecutedOrdersConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            horizon: default_executed_orders_horizon(),
+        }
+    }
+}
+
+fn default_executed_orders_horizon() -> Duration {
+    Duration::from_secs(300)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Erc3009TokenConfig {
+    address: eth::H160,
+
+    /// The name of the token's EIP-712 domain.
+    name: String,
+
+    /// The version of the token's EIP-712 domain.
+    version: String,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ApiConfig {
+    /// Maximum size in bytes of request bodies.
+    #[serde(default = "default_request_body_limit")]
+    request_body_limit: usize,
+
+    /// Maximum time requests to the individual endpoints may take.
+    #[serde(default)]
+    timeouts: ApiTimeoutsConfig,
+
+    /// Authentication required from clients. The API is unauthenticated if
+    /// not specified.
+    auth: Option<ApiAuthConfig>,
+
+    /// Memory in bytes the driver process may use. Once the peak usage of the
+    /// process while handling auctions approaches it, solvers ask the
+    /// autopilot for auctions with fewer orders. Auctions are not limited if not specified.
+    memory_budget: Option<usize>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            request_body_limit: default_request_body_limit(),
+            timeouts: Default::default(),
+            auth: None,
+            memory_budget: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ApiTimeoutsConfig {
+    #[serde(default, with = "humantime_serde")]
+    quote: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    solve: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    reveal: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    settle: Option<Duration>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+enum ApiAuthConfig {
+    /// Requests need to contain an `Authorization: Bearer <token>` header.
+    Bearer { token: String },
+    /// Requests need to be signed with a shared secret. The `X-Signature`
+    /// header contains the hex encoded HMAC-SHA256 of
+    /// `<X-Timestamp header>.<request body>`.
+    #[serde(rename_all = "kebab-case")]
+    Hmac {
+        secret: String,
+        /// Maximum difference between the `X-Timestamp` header and the
+        /// current time to prevent replaying requests.
+        #[serde(with = "humantime_serde", default = "default_max_clock_skew")]
+        max_clock_skew: Duration,
+    },
+}
+
+fn default_request_body_limit() -> usize {
+    10 * 1024 * 1024
+}
+
+fn default_max_clock_skew() -> Duration {
+    Duration::from_secs(30)
 }
 
 #[serde_as]
@@ -105,6 +240,44 @@ struct SubmissionConfig {
     /// mempool of a node or the private MEVBlocker mempool.
     #[serde(rename = "mempool", default)]
     mempools: Vec<Mempool>,
+
+    /// Additionally submits settlements to the public mempool of the node if
+    /// they did not get included via the configured mempools in time. Only
+    /// settlements that can't be sandwiched profitably get submitted.
+    #[serde(default)]
+    public_mempool_fallback: Option<PublicMempoolFallback>,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct PublicMempoolFallback {
+    /// How long to wait for the settlement to get included before also
+    /// submitting it to the public mempool.
+    #[serde(with = "humantime_serde")]
+    delay: Duration,
+
+    /// The gas an attacker spends on the front- and back-running transactions
+    /// of a sandwich attack. A settlement is only submitted publicly if the
+    /// slippage its on-chain interactions tolerate is worth less than this gas.
+    #[serde(default = "default_sandwich_gas")]
+    sandwich_gas: u64,
+
+    /// Maximum additional tip in Gwei that we are willing to pay above regular
+    /// gas price estimation.
+    #[serde(default = "default_max_additional_tip")]
+    #[serde_as(as = "serialize::U256")]
+    max_additional_tip: eth::U256,
+
+    /// Additional tip in percentage of max_fee_per_gas we are willing to pay
+    /// above regular gas price estimation. Expects a floating point value
+    /// between 0 and 1.
+    #[serde(default = "default_additional_tip_percentage")]
+    additional_tip_percentage: f64,
+}
+
+fn default_sandwich_gas() -> u64 {
+    300_000
 }
 
 #[serde_as]
@@ -230,7 +403,8 @@ struct SolverConfig {
     skip_liquidity: bool,
 
     /// The account which should be used to sign settlements for this solver.
-    account: Account,
+    /// Only optional in quote-only mode.
+    account: Option<Account>,
 
     /// Timeout configuration for the solver.
     #[serde(default, flatten)]
@@ -277,6 +451,93 @@ struct SolverConfig {
     /// before the driver starts dropping new `/solve` requests.
     #[serde(default = "default_settle_queue_size")]
     settle_queue_size: usize,
+
+    /// How the scores of the solutions get computed.
+    #[serde(default)]
+    scoring: ScoringConfig,
+
+    /// Remove redundant interactions (repeated approvals and consecutive
+    /// transfers to the same receiver) from settlements if the optimized
+    /// settlement still simulates successfully.
+    #[serde(default)]
+    optimize_interactions: bool,
+
+    /// Caps on how much of the settlement contract's buffers the solutions
+    /// of this solver may use through internalized interactions.
+    #[serde(default)]
+    buffer_limits: BufferLimitsConfig,
+
+    /// A candidate engine that receives the same auctions as this solver
+    /// without participating in the competition. Used to evaluate new engine
+    /// versions on production traffic before promoting them.
+    #[serde(default)]
+    shadow: Option<ShadowConfig>,
+
+    /// How much longer than the regular solve deadline the solver needs to
+    /// optimize its solutions, e.g. because it runs a combinatorial engine.
+    /// The driver asks the autopilot for this extension, which only grants it
+    /// to drivers operators allowed to get one and up to their cap.
+    #[serde(default, with = "humantime_serde")]
+    deadline_extension: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ShadowConfig {
+    /// The endpoint of the shadow engine.
+    endpoint: url::Url,
+
+    /// HTTP headers added to every request to the shadow engine.
+    #[serde(default)]
+    request_headers: HashMap<String, String>,
+
+    /// How many auctions the shadow engine solves at once. Auctions arriving
+    /// while it is busy don't get replayed.
+    #[serde(default = "default_shadow_max_concurrent_auctions")]
+    max_concurrent_auctions: NonZeroUsize,
+}
+
+fn default_shadow_max_concurrent_auctions() -> NonZeroUsize {
+    NonZeroUsize::new(1).unwrap()
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct BufferLimitsConfig {
+    /// The maximum net amount of a token in atoms a solution may take from
+    /// the buffers.
+    #[serde(default)]
+    #[serde_as(as = "HashMap<_, serialize::U256>")]
+    tokens: HashMap<eth::H160, eth::U256>,
+
+    /// The maximum value in wei of all tokens a solution may take from the
+    /// buffers.
+    #[serde(default)]
+    #[serde_as(as = "Option<serialize::U256>")]
+    settlement: Option<eth::U256>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ScoringConfig {
+    /// The scoring implementation whose scores get used.
+    #[serde(default)]
+    implementation: ScoringImplementation,
+
+    /// Also compute the scores with the other implementation and log and count
+    /// any divergences. Used to validate a new scoring implementation on
+    /// production traffic.
+    #[serde(default)]
+    differential: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum ScoringImplementation {
+    #[default]
+    Current,
+    Candidate,
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
@@ -290,12 +551,29 @@ pub enum FeeHandler {
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct S3 {
-    /// Name of the AWS S3 bucket in which the auctions will be stored
+    /// Name of the bucket in which the auctions will be stored. For the local
+    /// backend this is the directory the auctions get written to.
     pub bucket: String,
 
     /// Prepended to the auction id to form the final instance filename on AWS
     /// S3 bucket. Something like "staging/mainnet/"
     pub prefix: String,
+
+    /// Which object storage the auctions get stored in.
+    #[serde(default)]
+    pub backend: StorageBackend,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StorageBackend {
+    /// AWS S3
+    #[default]
+    S3,
+    /// Google Cloud Storage
+    Gcs,
+    /// The local filesystem
+    Local,
 }
 
 #[serde_as]
@@ -335,9 +613,11 @@ struct Timeouts {
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 struct Slippage {
     /// The relative slippage factor allowed by the solver.
-    #[serde(rename = "relative-slippage")]
-    #[serde_as(as = "serde_with::DisplayFromStr")]
-    relative: bigdecimal::BigDecimal,
+    #[serde(
+        rename = "relative-slippage",
+        deserialize_with = "deserialize_relative_slippage"
+    )]
+    relative: Ratio256,
 
     /// The absolute slippage allowed by the solver.
     #[serde(rename = "absolute-slippage")]
@@ -345,6 +625,16 @@ struct Slippage {
     absolute: Option<eth::U256>,
 }
 
+fn deserialize_relative_slippage<'de, D>(deserializer: D) -> Result<Ratio256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let decimal: bigdecimal::BigDecimal =
+        serde_with::DisplayFromStr::deserialize_as(deserializer)?;
+    Ratio256::try_from(big_decimal_to_big_rational(&decimal))
+        .map_err(|_| serde::de::Error::custom("relative slippage must not be negative"))
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 struct ContractsConfig {
@@ -405,7 +695,7 @@ struct EnsoConfig {
     network_block_interval: Option<Duration>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 struct LiquidityConfig {
     /// Additional tokens for which liquidity is always fetched, regardless of
@@ -432,13 +722,44 @@ struct LiquidityConfig {
     /// Liquidity provided by 0x API.
     #[serde(default)]
     zeroex: Option<ZeroExConfig>,
+
+    /// How many pools every Uniswap V2 like and Balancer V2 liquidity source
+    /// keeps cached at most. The least recently used pools get evicted first.
+    #[serde(default = "default_max_cached_pools")]
+    max_cached_pools: NonZeroUsize,
+}
+
+impl Default for LiquidityConfig {
+    fn default() -> Self {
+        Self {
+            base_tokens: Default::default(),
+            uniswap_v2: Default::default(),
+            swapr: Default::default(),
+            uniswap_v3: Default::default(),
+            balancer_v2: Default::default(),
+            zeroex: Default::default(),
+            max_cached_pools: default_max_cached_pools(),
+        }
+    }
+}
+
+fn default_max_cached_pools() -> NonZeroUsize {
+    NonZeroUsize::new(20_000).unwrap()
 }
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(untagged, deny_unknown_fields)]
 enum UniswapV2Config {
     #[serde(rename_all = "kebab-case")]
-    Preset { preset: UniswapV2Preset },
+    Preset {
+        preset: UniswapV2Preset,
+
+        /// Update pool reserves from `Sync` events instead of re-reading them
+        /// every block and reconcile them with the on-chain state after this
+        /// many blocks.
+        #[serde(default)]
+        sync_events_reconciliation_blocks: Option<NonZeroU64>,
+    },
 
     #[serde(rename_all = "kebab-case")]
     Manual {
@@ -453,6 +774,12 @@ enum UniswapV2Config {
         /// again.
         #[serde(with = "humantime_serde")]
         missing_pool_cache_time: Duration,
+
+        /// Update pool reserves from `Sync` events instead of re-reading them
+        /// every block and reconcile them with the on-chain state after this
+        /// many blocks.
+        #[serde(default)]
+        sync_events_reconciliation_blocks: Option<NonZeroU64>,
     },
 }
 
@@ -593,6 +920,7 @@ enum BalancerV2Preset {
     BalancerV2,
 }
 
+#[serde_as]
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 struct ZeroExConfig {
@@ -601,6 +929,11 @@ struct ZeroExConfig {
     pub api_key: Option<String>,
     #[serde(with = "humantime_serde", default = "default_http_timeout")]
     pub http_timeout: Duration,
+    /// Rate limiting strategy for requests to the 0x API, e.g. "10rps,burst=20"
+    /// to stay below the API key's quota.
+    #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+    #[serde(default)]
+    pub rate_limiter: Option<rate_limit::Strategy>,
 }
 
 fn default_zeroex_base_url() -> String {
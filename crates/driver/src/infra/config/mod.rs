@@ -1,7 +1,8 @@
 use {
     crate::{
-        domain::eth,
+        domain::{competition::executed_orders, eth},
         infra::{
+            api,
             blockchain,
             config::file::{GasEstimatorType, OrderPriorityStrategy},
             liquidity,
@@ -19,6 +20,8 @@ pub mod file;
 /// Configuration of infrastructural components.
 #[derive(Debug)]
 pub struct Config {
+    /// Whether the driver only serves quotes and never settles.
+    pub quote_only: bool,
     pub disable_access_list_simulation: bool,
     pub disable_gas_simulation: Option<eth::Gas>,
     pub solvers: Vec<solver::Config>,
@@ -26,8 +29,11 @@ pub struct Config {
     pub simulator: Option<simulator::Config>,
     pub gas_estimator: GasEstimatorType,
     pub mempools: Vec<mempool::Config>,
+    pub public_mempool_fallback: Option<mempool::FallbackConfig>,
     pub contracts: blockchain::contracts::Addresses,
     pub order_priority_strategies: Vec<OrderPriorityStrategy>,
     pub archive_node_url: Option<Url>,
     pub simulation_bad_token_max_age: Duration,
+    pub executed_orders: executed_orders::Config,
+    pub api: api::Config,
 }
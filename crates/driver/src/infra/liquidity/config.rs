@@ -4,7 +4,11 @@ use {
     derive_more::Debug,
     hex_literal::hex,
     reqwest::Url,
-    std::{collections::HashSet, time::Duration},
+    std::{
+        collections::HashSet,
+        num::{NonZeroU64, NonZeroUsize},
+        time::Duration,
+    },
 };
 
 /// Configuration options for liquidity fetching.
@@ -32,6 +36,9 @@ pub struct Config {
 
     /// 0x liquidity fetcher.
     pub zeroex: Option<ZeroEx>,
+
+    /// How many pools every cached liquidity source keeps cached at most.
+    pub max_cached_pools: NonZeroUsize,
 }
 
 /// Uniswap V2 (and Uniswap V2 clone) liquidity fetching options.
@@ -45,6 +52,10 @@ pub struct UniswapV2 {
     /// How long liquidity should not be fetched for a token pair that didn't
     /// return useful liquidity before allowing to fetch it again.
     pub missing_pool_cache_time: Duration,
+    /// If set, pool reserves get updated from `Sync` events instead of being
+    /// re-read every block and get reconciled with the on-chain state after
+    /// this many blocks.
+    pub sync_events_reconciliation_blocks: Option<NonZeroU64>,
 }
 
 impl UniswapV2 {
@@ -56,6 +67,7 @@ impl UniswapV2 {
             pool_code: hex!("96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f")
                 .into(),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
+            sync_events_reconciliation_blocks: None,
         })
     }
 
@@ -66,6 +78,7 @@ impl UniswapV2 {
             pool_code: hex!("e18a34eb0e04b04f7a0ac29a6e80748dca96319b42c54d679cb821dca90c6303")
                 .into(),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
+            sync_events_reconciliation_blocks: None,
         })
     }
 
@@ -76,6 +89,7 @@ impl UniswapV2 {
             pool_code: hex!("3f88503e8580ab941773b59034fb4b2a63e86dbc031b3633a925533ad3ed2b93")
                 .into(),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
+            sync_events_reconciliation_blocks: None,
         })
     }
 
@@ -86,6 +100,7 @@ impl UniswapV2 {
             pool_code: hex!("0bae3ead48c325ce433426d2e8e6b07dac10835baec21e163760682ea3d3520d")
                 .into(),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
+            sync_events_reconciliation_blocks: None,
         })
     }
 
@@ -96,6 +111,7 @@ impl UniswapV2 {
             pool_code: hex!("57224589c67f3f30a6b0d7a1b54cf3153ab84563bc609ef41dfb34f8b2974d2d")
                 .into(),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
+            sync_events_reconciliation_blocks: None,
         })
     }
 
@@ -107,6 +123,7 @@ impl UniswapV2 {
             pool_code: hex!("0efd7612822d579e24a8851501d8c2ad854264a1050e3dfcee8afcca08f80a86")
                 .into(),
             missing_pool_cache_time: Duration::from_secs(60 * 60),
+            sync_events_reconciliation_blocks: None,
         })
     }
 }
@@ -242,4 +259,5 @@ pub struct ZeroEx {
     #[debug(ignore)]
     pub api_key: Option<String>,
     pub http_timeout: Duration,
+    pub rate_limiter: Option<rate_limit::Strategy>,
 }
@@ -0,0 +1,231 @@
+//! Accounting of the memory used by the driver process.
+//!
+//! A background task frequently samples the memory mimalloc, the global
+//! allocator of the driver binary, has committed, so the peak usage of the
+//! process during a stage of handling an auction can be determined once the
+//! stage finished. The measurement is per process, not per auction: it
+//! includes the memory of requests handled concurrently, e.g. of other
+//! solvers working on the same auction, and memory the allocator hasn't
+//! returned to the OS yet.
+//!
+//! Solvers with a memory budget ask the autopilot for smaller auctions once
+//! the peak usage of their auctions approaches the budget.
+
+use {
+    crate::infra::{observe::metrics, solver},
+    std::{
+        collections::VecDeque,
+        num::NonZeroUsize,
+        ptr,
+        sync::{Mutex, Once},
+        time::{Duration, Instant},
+    },
+};
+
+/// How often the committed memory gets sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long samples are kept. Stages taking longer only take the samples of
+/// this period into account.
+const SAMPLE_RETENTION: Duration = Duration::from_secs(60);
+
+/// Share of the budget above which auctions get downscaled.
+const HIGH_WATERMARK: f64 = 0.9;
+
+/// Share of the budget auctions get downscaled to.
+const TARGET: f64 = 0.75;
+
+/// Share of the budget below which auctions are allowed to grow again.
+const LOW_WATERMARK: f64 = 0.6;
+
+/// Factor by which the number of orders grows per auction that stayed below
+/// the low watermark.
+const GROWTH: f64 = 1.1;
+
+static SAMPLES: Mutex<VecDeque<(Instant, usize)>> = Mutex::new(VecDeque::new());
+
+/// Bytes mimalloc currently has committed for the process. Only covers the
+/// whole process if mimalloc is the global allocator.
+pub fn current() -> usize {
+    let mut commit = 0;
+    // SAFETY: mimalloc accepts null pointers for the statistics that aren't
+    // needed.
+    unsafe {
+        libmimalloc_sys::mi_process_info(
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut commit,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        );
+    }
+    commit
+}
+
+/// Starts sampling the allocated memory in a background task. Only the first
+/// call has an effect.
+pub fn start_sampling() {
+    static STARTED: Once = Once::new();
+    STARTED.call_once(|| {
+        tokio::spawn(async {
+            let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                let now = interval.tick().await.into_std();
+                record_sample(now, current());
+            }
+        });
+    });
+}
+
+fn record_sample(now: Instant, bytes: usize) {
+    let mut samples = SAMPLES.lock().unwrap();
+    while samples
+        .front()
+        .is_some_and(|(sampled, _)| now.duration_since(*sampled) > SAMPLE_RETENTION)
+    {
+        samples.pop_front();
+    }
+    samples.push_back((now, bytes));
+}
+
+/// Peak of the committed memory since the instant.
+fn peak_since(start: Instant) -> usize {
+    let samples = SAMPLES.lock().unwrap();
+    samples
+        .iter()
+        .filter(|(sampled, _)| *sampled >= start)
+        .map(|(_, bytes)| *bytes)
+        .fold(current(), usize::max)
+}
+
+/// Measures the peak memory usage of the process during a stage of handling
+/// an auction.
+#[derive(Debug)]
+pub struct Stage {
+    name: &'static str,
+    start: Instant,
+}
+
+impl Stage {
+    pub fn start(name: &'static str) -> Self {
+        Self {
+            name,
+            start: Instant::now(),
+        }
+    }
+
+    /// Reports the peak memory usage of the process during the stage and
+    /// returns it.
+    pub fn finish(self, solver: &solver::Name) -> usize {
+        let peak = peak_since(self.start);
+        metrics::get()
+            .process_memory_peak_bytes
+            .with_label_values(&[solver.as_str(), self.name])
+            .set(peak.try_into().unwrap_or(i64::MAX));
+        peak
+    }
+}
+
+/// Adapts the number of orders a solver asks the autopilot for to the memory
+/// budget of the driver.
+#[derive(Debug)]
+pub struct Budget {
+    bytes: usize,
+    max_orders: Mutex<Option<NonZeroUsize>>,
+}
+
+impl Budget {
+    pub fn new(bytes: usize) -> Self {
+        Self {
+            bytes,
+            max_orders: Default::default(),
+        }
+    }
+
+    /// Records the peak memory usage while handling an auction with the given
+    /// number of orders. Returns the maximum number of orders future auctions
+    /// should have, if they should be limited.
+    pub fn record(&self, orders: usize, peak: usize) -> Option<NonZeroUsize> {
+        let mut max_orders = self.max_orders.lock().unwrap();
+        let adapted = adapt(*max_orders, self.bytes, orders, peak);
+        if adapted != *max_orders {
+            tracing::info!(
+                ?adapted,
+                previous = ?*max_orders,
+                orders,
+                peak,
+                budget = self.bytes,
+                "adapted auction size to memory budget"
+            );
+        }
+        *max_orders = adapted;
+        adapted
+    }
+}
+
+/// Scales the number of orders down proportionally once the peak usage
+/// exceeds the high watermark and lets it grow gradually while the peak usage
+/// stays below the low watermark. The limit gets lifted once auctions are
+/// smaller than it anyway.
+fn adapt(
+    max_orders: Option<NonZeroUsize>,
+    budget: usize,
+    orders: usize,
+    peak: usize,
+) -> Option<NonZeroUsize> {
+    let usage = peak as f64 / budget as f64;
+    if usage >= HIGH_WATERMARK {
+        let scaled = (orders as f64 * TARGET / usage) as usize;
+        let limited = max_orders.map_or(scaled, |max| max.get().min(scaled));
+        return Some(NonZeroUsize::new(limited).unwrap_or(NonZeroUsize::MIN));
+    }
+    match max_orders {
+        Some(max) if usage < LOW_WATERMARK => {
+            if orders < max.get() {
+                return None;
+            }
+            NonZeroUsize::new((max.get() as f64 * GROWTH).ceil() as usize)
+        }
+        max_orders => max_orders,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max(orders: usize) -> Option<NonZeroUsize> {
+        NonZeroUsize::new(orders)
+    }
+
+    #[test]
+    fn adapts_auction_size() {
+        // Auctions aren't limited while the usage is fine.
+        assert_eq!(adapt(None, 1000, 100, 800), None);
+        // Approaching the budget scales the auctions down to the target.
+        assert_eq!(adapt(None, 1000, 100, 950), max(78));
+        assert_eq!(adapt(max(50), 1000, 50, 2000), max(18));
+        assert_eq!(adapt(max(1), 1000, 1, 2000), max(1));
+        // The limit is kept while the usage is close to the budget.
+        assert_eq!(adapt(max(78), 1000, 78, 700), max(78));
+        // Auctions grow again if the usage is low.
+        assert_eq!(adapt(max(78), 1000, 78, 500), max(86));
+        // The limit gets lifted once auctions are smaller than it anyway.
+        assert_eq!(adapt(max(78), 1000, 60, 500), None);
+    }
+
+    #[test]
+    fn finds_peak_in_samples() {
+        let start = Instant::now();
+        record_sample(start - Duration::from_secs(1), usize::MAX);
+        record_sample(start, 300);
+        record_sample(start + Duration::from_millis(10), 500);
+        record_sample(start + Duration::from_millis(20), 400);
+        assert_eq!(peak_since(start), 500);
+        assert_eq!(peak_since(start + Duration::from_millis(20)), 400);
+    }
+}
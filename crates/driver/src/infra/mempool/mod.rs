@@ -1,6 +1,6 @@
 use {
     crate::{
-        boundary::unbuffered_web3_client,
+        boundary::{unbuffered_web3_client, HttpClientFactory},
         domain::{competition, eth, mempools},
         infra,
     },
@@ -16,6 +16,14 @@ pub struct Config {
     pub kind: Kind,
 }
 
+/// See [`mempools::PublicFallback`].
+#[derive(Debug, Clone)]
+pub struct FallbackConfig {
+    pub mempool: Config,
+    pub delay: std::time::Duration,
+    pub sandwich_gas: eth::Gas,
+}
+
 #[derive(Debug, Clone)]
 pub enum Kind {
     /// The public mempool of the [`Ethereum`] node.
@@ -67,11 +75,11 @@ impl std::fmt::Display for Mempool {
 }
 
 impl Mempool {
-    pub fn new(config: Config, transport: DynWeb3) -> Self {
+    pub fn new(config: Config, transport: DynWeb3, http: &HttpClientFactory) -> Self {
         let transport = match &config.kind {
             Kind::Public { .. } => transport,
             // Flashbots Protect RPC fallback doesn't support buffered transport
-            Kind::MEVBlocker { url, .. } => unbuffered_web3_client(url),
+            Kind::MEVBlocker { url, .. } => unbuffered_web3_client(url, http),
         };
         Self { config, transport }
     }
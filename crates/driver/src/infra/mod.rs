@@ -3,6 +3,7 @@ pub mod blockchain;
 pub mod cli;
 pub mod config;
 pub mod liquidity;
+pub mod memory;
 pub mod mempool;
 pub mod notify;
 pub mod observe;
@@ -58,6 +58,9 @@ pub fn encoding_failed(
         solution::Error::NonBufferableTokensUsed(tokens) => {
             notification::Kind::NonBufferableTokensUsed(tokens.clone())
         }
+        solution::Error::BufferLimitExceeded(limit) => {
+            notification::Kind::BufferLimitExceeded(limit.clone())
+        }
         solution::Error::SolverAccountInsufficientBalance(required) => {
             notification::Kind::SolverAccountInsufficientBalance(*required)
         }
@@ -36,6 +36,9 @@ pub enum Kind {
     /// Solution aimed to internalize tokens that are not considered safe to
     /// keep in the settlement contract.
     NonBufferableTokensUsed(TokensUsed),
+    /// Solution internalized interactions taking more from the settlement
+    /// contract's buffers than allowed.
+    BufferLimitExceeded(solution::error::BufferLimit),
     /// Solver don't have enough balance to submit the solution onchain.
     SolverAccountInsufficientBalance(RequiredEther),
     /// Result of winning solver trying to settle the transaction onchain.
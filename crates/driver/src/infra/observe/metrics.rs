@@ -19,6 +19,58 @@ pub struct Metrics {
     /// The results of the mempool submission.
     #[metric(labels("mempool", "result"))]
     pub mempool_submission: prometheus::IntCounterVec,
+    /// Settlements that did not get included in time and whether they were
+    /// submitted to the public mempool as a fallback.
+    #[metric(labels("solver", "result"))]
+    pub public_mempool_fallbacks: prometheus::IntCounterVec,
+    /// Requests rejected by the API before reaching the handlers.
+    #[metric(labels("solver", "endpoint", "reason"))]
+    pub rejected_requests: prometheus::IntCounterVec,
+    /// Solutions for which the scoring implementations computed different
+    /// scores in differential mode.
+    #[metric(labels("solver"))]
+    pub scoring_divergences: prometheus::IntCounterVec,
+    /// Calldata bytes saved by optimizing settlement interactions.
+    #[metric(labels("solver"))]
+    pub interaction_optimization_calldata_saved: prometheus::IntCounterVec,
+    /// Gas saved by optimizing settlement interactions.
+    #[metric(labels("solver"))]
+    pub interaction_optimization_gas_saved: prometheus::IntCounterVec,
+    /// Optimized settlements that failed to simulate.
+    #[metric(labels("solver"))]
+    pub interaction_optimization_failures: prometheus::IntCounterVec,
+    /// Time solver engines reported to have spent per solving phase.
+    #[metric(
+        labels("solver", "phase"),
+        buckets(0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10., 20., 40.)
+    )]
+    pub solver_phase_seconds: prometheus::HistogramVec,
+    /// Share of a buffer limit used by solutions, where 1 means that the limit
+    /// is fully used.
+    #[metric(
+        labels("solver", "limit"),
+        buckets(0.1, 0.25, 0.5, 0.75, 0.9, 0.95, 1., 1.5, 2.)
+    )]
+    pub buffer_limit_usage: prometheus::HistogramVec,
+    /// Auctions replayed to the shadow engine of a solver by whether the
+    /// shadow engine would have beaten the solver, or why the comparison
+    /// didn't happen.
+    #[metric(labels("solver", "outcome"))]
+    pub shadow_competitions: prometheus::IntCounterVec,
+    /// Peak memory committed by the whole driver process during the latest
+    /// run of a stage of handling an auction. Includes the memory of other
+    /// requests handled at the same time.
+    #[metric(labels("solver", "stage"))]
+    pub process_memory_peak_bytes: prometheus::IntGaugeVec,
+    /// Recently executed orders that were kept out of auctions because they
+    /// could not be confirmed to be fillable again. Every order is counted
+    /// once no matter how many auctions it was kept out of.
+    pub prevented_duplicate_executions: prometheus::IntCounter,
+    /// How far the clock of the driver was ahead of the autopilot's when
+    /// receiving the latest auction, including the time the request took to
+    /// arrive.
+    #[metric(labels("solver"))]
+    pub autopilot_clock_offset_seconds: prometheus::GaugeVec,
 }
 
 /// Setup the metrics registry.
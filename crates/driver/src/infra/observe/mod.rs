@@ -24,16 +24,19 @@ use {
         util::http,
     },
     ethrpc::block_stream::BlockInfo,
-    std::collections::{HashMap, HashSet},
+    std::{
+        collections::{HashMap, HashSet},
+        time::Duration,
+    },
     url::Url,
 };
 
 mod metrics;
 
-/// Setup the observability. The log argument configures the tokio tracing
+/// Setup the observability. The config configures the tokio tracing
 /// framework.
-pub fn init(log: &str) {
-    observe::tracing::initialize_reentrant(log);
+pub fn init(config: &::observe::config::Config) {
+    observe::tracing::initialize_reentrant_with_config(config);
     metrics::init();
 }
 
@@ -42,6 +45,15 @@ pub fn auction(auction_id: i64) {
     tracing::debug!(id=?auction_id, "received auction");
 }
 
+/// Observe how far the clock of the driver is ahead of the autopilot's.
+pub fn autopilot_clock_offset(solver: &solver::Name, offset: chrono::Duration) {
+    tracing::debug!(?offset, "autopilot clock offset");
+    metrics::get()
+        .autopilot_clock_offset_seconds
+        .with_label_values(&[solver.as_str()])
+        .set(offset.num_milliseconds() as f64 / 1000.);
+}
+
 /// Observe that liquidity fetching is about to start.
 pub fn fetching_liquidity() {
     tracing::trace!("fetching liquidity");
@@ -125,6 +137,61 @@ pub fn encoding_failed(solver: &solver::Name, id: &solution::Id, err: &solution:
         .inc();
 }
 
+/// Observe that the interactions of a settlement were optimized.
+pub fn interactions_optimized(
+    solver: &solver::Name,
+    original: &eth::Tx,
+    optimized: &eth::Tx,
+    original_gas: Option<Gas>,
+    optimized_gas: Gas,
+) {
+    let calldata_saved = original
+        .input
+        .0
+        .len()
+        .saturating_sub(optimized.input.0.len());
+    let gas_saved = original_gas.map(|gas| gas.0.saturating_sub(optimized_gas.0));
+    tracing::debug!(%solver, calldata_saved, ?gas_saved, "optimized settlement interactions");
+    let metrics = metrics::get();
+    metrics
+        .interaction_optimization_calldata_saved
+        .with_label_values(&[solver.as_str()])
+        .inc_by(calldata_saved as u64);
+    if let Some(gas_saved) = gas_saved {
+        metrics
+            .interaction_optimization_gas_saved
+            .with_label_values(&[solver.as_str()])
+            .inc_by(gas_saved.as_u64());
+    }
+}
+
+/// Observe that the optimized interactions of a settlement failed to simulate
+/// and the original interactions are used instead.
+pub fn interaction_optimization_failed(
+    solver: &solver::Name,
+    id: &solution::Id,
+    err: &solution::Error,
+) {
+    tracing::debug!(%solver, ?id, ?err, "optimized settlement interactions failed");
+    metrics::get()
+        .interaction_optimization_failures
+        .with_label_values(&[solver.as_str()])
+        .inc();
+}
+
+/// Observe how much of a buffer limit a solution uses.
+pub fn buffer_usage(solver: &solver::Name, limit: &str, used: eth::U256, maximum: eth::U256) {
+    let ratio = match maximum.is_zero() {
+        true if used.is_zero() => 0.,
+        true => f64::INFINITY,
+        false => used.to_f64_lossy() / maximum.to_f64_lossy(),
+    };
+    metrics::get()
+        .buffer_limit_usage
+        .with_label_values(&[solver.as_str(), limit])
+        .observe(ratio);
+}
+
 /// Observe that two solutions were merged.
 pub fn merged(first: &Solution, other: &Solution, result: &Solution) {
     tracing::debug!(?first, ?other, ?result, "merged solutions");
@@ -153,6 +220,27 @@ pub fn scoring_failed(solver: &solver::Name, err: &solution::error::Scoring) {
         .inc();
 }
 
+/// Observe that the scoring implementations disagree about the score of a
+/// solution.
+pub fn scoring_divergence(
+    solver: &solver::Name,
+    solution: &solution::Id,
+    divergence: &solution::scoring::Divergence,
+) {
+    tracing::warn!(
+        %solver,
+        ?solution,
+        current = ?divergence.current,
+        candidate = ?divergence.candidate,
+        trades = ?divergence.trades,
+        "scoring implementations diverged"
+    );
+    metrics::get()
+        .scoring_divergences
+        .with_label_values(&[solver.as_str()])
+        .inc();
+}
+
 /// Observe the settlement score.
 pub fn score(settlement: &Settlement, score: &eth::Ether) {
     tracing::info!(
@@ -216,6 +304,79 @@ pub fn settled(solver: &solver::Name, result: &Result<competition::Settled, comp
     }
 }
 
+/// Observe the phase timings reported by a solver engine.
+pub fn solver_timings(solver: &solver::Name, timings: &solver::Timings) {
+    tracing::debug!(?timings, "solver phase timings");
+    let phases = [
+        ("liquidity_processing", timings.liquidity_processing),
+        ("optimization", timings.optimization),
+        ("encoding", timings.encoding),
+    ];
+    for (phase, duration) in phases {
+        if let Some(duration) = duration {
+            metrics::get()
+                .solver_phase_seconds
+                .with_label_values(&[solver.as_str(), phase])
+                .observe(duration.as_secs_f64());
+        }
+    }
+}
+
+/// Observe that the shadow engine of a solver failed to solve an auction.
+pub fn shadow_failed(
+    solver: &solver::Name,
+    auction_id: Option<competition::auction::Id>,
+    err: &solver::Error,
+) {
+    tracing::debug!(%solver, ?auction_id, ?err, "shadow engine failed to solve auction");
+    metrics::get()
+        .shadow_competitions
+        .with_label_values(&[solver.as_str(), "failed"])
+        .inc();
+}
+
+/// Observe that an auction didn't get replayed because the shadow engine of
+/// a solver was still busy with previous auctions.
+pub fn shadow_skipped(solver: &solver::Name, auction_id: Option<competition::auction::Id>) {
+    tracing::debug!(%solver, ?auction_id, "shadow engine busy, skipping auction");
+    metrics::get()
+        .shadow_competitions
+        .with_label_values(&[solver.as_str(), "skipped"])
+        .inc();
+}
+
+/// Observe that the shadow engine of a solver didn't solve an auction before
+/// the deadline.
+pub fn shadow_timed_out(solver: &solver::Name, auction_id: Option<competition::auction::Id>) {
+    tracing::debug!(%solver, ?auction_id, "shadow engine timed out");
+    metrics::get()
+        .shadow_competitions
+        .with_label_values(&[solver.as_str(), "timed_out"])
+        .inc();
+}
+
+/// Observe how the shadow engine of a solver did compared to the solver.
+pub fn shadow_outcome(
+    solver: &solver::Name,
+    auction_id: Option<competition::auction::Id>,
+    outcome: competition::shadow::Outcome,
+    solver_score: Option<eth::Ether>,
+    shadow_score: Option<eth::Ether>,
+) {
+    tracing::info!(
+        %solver,
+        ?auction_id,
+        ?outcome,
+        ?solver_score,
+        ?shadow_score,
+        "shadow competition"
+    );
+    metrics::get()
+        .shadow_competitions
+        .with_label_values(&[solver.as_str(), outcome.as_str()])
+        .inc();
+}
+
 /// Observe the result of solving an auction.
 pub fn solved(solver: &solver::Name, result: &Result<Option<Solved>, competition::Error>) {
     match result {
@@ -290,6 +451,15 @@ pub fn mounting_solver(solver: &solver::Name, path: &str) {
     tracing::debug!(%solver, path, "mounting solver");
 }
 
+/// Observe that a request to the API got rejected.
+pub fn rejected_request(solver: &solver::Name, endpoint: &str, reason: &str) {
+    tracing::warn!(%solver, endpoint, reason, "rejected request");
+    metrics::get()
+        .rejected_requests
+        .with_label_values(&[solver.as_str(), endpoint, reason])
+        .inc();
+}
+
 /// Observe that a request is about to be sent to the solver.
 pub fn solver_request(endpoint: &Url, req: &str) {
     tracing::trace!(%endpoint, %req, "sending request to solver");
@@ -350,6 +520,26 @@ pub fn mempool_executed(
         .inc();
 }
 
+/// Observe that a settlement did not get included in time and whether it was
+/// safe to fall back to the public mempool.
+pub fn public_fallback(solver: &solver::Name, delay: Duration, safe: bool) {
+    if safe {
+        tracing::info!(
+            ?delay,
+            "settlement not included yet, submitting to public mempool"
+        );
+    } else {
+        tracing::info!(
+            ?delay,
+            "settlement not included yet but could be sandwiched, not submitting to public mempool"
+        );
+    }
+    metrics::get()
+        .public_mempool_fallbacks
+        .with_label_values(&[solver.as_str(), if safe { "Submitted" } else { "Unsafe" }])
+        .inc();
+}
+
 /// Observe that an invalid DTO was received.
 pub fn invalid_dto(err: &impl std::error::Error, dto: &str) {
     tracing::warn!(?err, ?dto, "received invalid dto");
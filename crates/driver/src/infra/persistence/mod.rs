@@ -17,6 +17,9 @@ pub struct S3 {
     /// Prepended to the auction id to form the final instance filename on AWS
     /// S3 bucket. Something like "staging/mainnet/"
     pub prefix: String,
+
+    /// Which object storage the auctions get stored in.
+    pub backend: file::StorageBackend,
 }
 
 impl From<file::S3> for S3 {
@@ -24,6 +27,7 @@ impl From<file::S3> for S3 {
         Self {
             bucket: value.bucket,
             prefix: value.prefix,
+            backend: value.backend,
         }
     }
 }
@@ -31,8 +35,19 @@ impl From<file::S3> for S3 {
 impl From<S3> for s3::Config {
     fn from(value: S3) -> Self {
         Self {
-            bucket: value.bucket,
+            backend: match value.backend {
+                file::StorageBackend::S3 => s3::Backend::S3 {
+                    bucket: value.bucket,
+                },
+                file::StorageBackend::Gcs => s3::Backend::Gcs {
+                    bucket: value.bucket,
+                },
+                file::StorageBackend::Local => s3::Backend::Local {
+                    path: value.bucket.into(),
+                },
+            },
             filename_prefix: value.prefix,
+            ..Default::default()
         }
     }
 }
@@ -44,12 +59,17 @@ pub struct Persistence {
 
 impl Persistence {
     pub async fn build(config: &Config) -> Self {
-        if let Some(s3) = &config.s3 {
-            Self {
-                s3: Some(Arc::new(s3::Uploader::new(s3.clone().into()).await)),
-            }
-        } else {
-            Self { s3: None }
+        let Some(s3) = &config.s3 else {
+            return Self { s3: None };
+        };
+        // Archiving auctions is not essential so don't refuse to start if the
+        // storage is not usable.
+        let uploader = s3::Uploader::new(s3.clone().into())
+            .await
+            .inspect_err(|err| tracing::error!(?err, "auction archiving disabled"))
+            .ok();
+        Self {
+            s3: uploader.map(Arc::new),
         }
     }
 
@@ -68,7 +88,10 @@ impl Persistence {
         };
         tokio::spawn(
             async move {
-                match uploader.upload(auction_id.to_string(), body).await {
+                match uploader
+                    .upload_stream(auction_id.to_string(), s3::json_reader(body))
+                    .await
+                {
                     Ok(key) => {
                         tracing::debug!(?key, "uploaded auction with liquidity to s3");
                     }
@@ -2,7 +2,6 @@ use {
     crate::domain::eth,
     chain::Chain,
     ethrpc::block_stream::CurrentBlockWatcher,
-    reqwest::ClientBuilder,
     std::time::Duration,
     thiserror::Error,
 };
@@ -14,6 +13,7 @@ const GAS_LIMIT: u64 = 30_000_000;
 #[derive(Debug, Clone)]
 pub(super) struct Enso {
     url: reqwest::Url,
+    client: reqwest::Client,
     chain: Chain,
     current_block: CurrentBlockWatcher,
     network_block_interval: Option<Duration>,
@@ -28,9 +28,15 @@ pub struct Config {
 }
 
 impl Enso {
-    pub(super) fn new(config: Config, chain: Chain, current_block: CurrentBlockWatcher) -> Self {
+    pub(super) fn new(
+        config: Config,
+        chain: Chain,
+        current_block: CurrentBlockWatcher,
+        client: reqwest::Client,
+    ) -> Self {
         Self {
             url: reqwest::Url::parse(&format!("{}api/v1/simulate", config.url)).unwrap(),
+            client,
             chain,
             current_block,
             network_block_interval: config.network_block_interval,
@@ -53,9 +59,8 @@ impl Enso {
             }
         };
 
-        let res: dto::Response = ClientBuilder::new()
-            .build()
-            .unwrap()
+        let res: dto::Response = self
+            .client
             .post(self.url.clone())
             .json(&dto::Request {
                 chain_id: self.chain.id(),
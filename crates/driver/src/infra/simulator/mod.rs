@@ -3,6 +3,7 @@ use {
         domain::eth,
         infra::blockchain::{self, Ethereum},
     },
+    ethrpc::buffered::{with_priority, Priority},
     observe::future::Measure,
 };
 
@@ -59,6 +60,10 @@ impl Simulator {
                 config,
                 eth.chain(),
                 eth.current_block().clone(),
+                eth.http_client_factory()
+                    .builder_without_timeout()
+                    .build()
+                    .unwrap(),
             )),
             eth,
             disable_access_lists: false,
@@ -94,16 +99,11 @@ impl Simulator {
                     .map_err(with(tx.clone(), block))?
                     .access_list
             }
-            Inner::Ethereum => self
-                .eth
-                .create_access_list(tx.clone())
-                .await
-                .map_err(with(tx.clone(), block))?,
-            Inner::Enso(_) => self
-                .eth
-                .create_access_list(tx.clone())
-                .await
-                .map_err(with(tx.clone(), block))?,
+            Inner::Ethereum | Inner::Enso(_) => {
+                with_priority(Priority::High, self.eth.create_access_list(tx.clone()))
+                    .await
+                    .map_err(with(tx.clone(), block))?
+            }
         };
         Ok(tx.access_list.clone().merge(access_list))
     }
@@ -123,9 +123,7 @@ impl Simulator {
                     .map_err(with(tx.clone(), block))?
                     .gas
             }
-            Inner::Ethereum => self
-                .eth
-                .estimate_gas(tx)
+            Inner::Ethereum => with_priority(Priority::High, self.eth.estimate_gas(tx))
                 .await
                 .map_err(with(tx.clone(), block))?,
             Inner::Enso(enso) => enso
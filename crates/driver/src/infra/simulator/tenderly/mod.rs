@@ -55,7 +55,9 @@ impl Tenderly {
                 config.project
             ))
             .unwrap(),
-            client: reqwest::ClientBuilder::new()
+            client: eth
+                .http_client_factory()
+                .builder_without_timeout()
                 .default_headers(headers)
                 .build()
                 .unwrap(),
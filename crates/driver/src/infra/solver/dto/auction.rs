@@ -10,10 +10,7 @@ use {
             liquidity,
         },
         infra::{config::file::FeeHandler, solver::ManageNativeToken},
-        util::{
-            conv::{rational_to_big_decimal, u256::U256Ext},
-            serialize,
-        },
+        util::{conv::u256::U256Ext, serialize},
     },
     app_data::AppDataHash,
     indexmap::IndexMap,
@@ -21,6 +18,13 @@ use {
         interaction::InteractionData,
         order::{BuyTokenDestination, SellTokenSource},
     },
+    number::conversions::{
+        factor_scale,
+        factor_to_fixed,
+        rational_to_big_decimal,
+        saturating_mul_ratio_floor,
+        to_base_units,
+    },
     serde::Serialize,
     serde_with::serde_as,
     std::collections::{BTreeMap, HashMap},
@@ -99,19 +103,28 @@ impl Auction {
                                 match order.side {
                                     Side::Buy => {
                                         // reduce sell amount by factor
-                                        available.sell.amount = available
-                                            .sell
-                                            .amount
-                                            .apply_factor(1.0 / (1.0 + factor))
-                                            .unwrap_or_default();
+                                        available.sell.amount = saturating_mul_ratio_floor(
+                                            available.sell.amount.0,
+                                            factor_scale(),
+                                            factor_scale().saturating_add(factor_to_fixed(*factor)),
+                                        )
+                                        .unwrap_or_default()
+                                        .into();
                                     }
                                     Side::Sell => {
-                                        // increase buy amount by factor
-                                        available.buy.amount = available
-                                            .buy
-                                            .amount
-                                            .apply_factor(1.0 / (1.0 - factor))
-                                            .unwrap_or_default();
+                                        // increase buy amount by factor, a factor of 100% or
+                                        // more makes the order unfillable
+                                        available.buy.amount = factor_scale()
+                                            .checked_sub(factor_to_fixed(*factor))
+                                            .and_then(|denominator| {
+                                                saturating_mul_ratio_floor(
+                                                    available.buy.amount.0,
+                                                    factor_scale(),
+                                                    denominator,
+                                                )
+                                            })
+                                            .unwrap_or(eth::U256::MAX)
+                                            .into();
                                     }
                                 }
                             }
@@ -286,7 +299,7 @@ impl Auction {
                                     )
                                 })
                                 .collect(),
-                            fee: bigdecimal::BigDecimal::new(pool.fee.bps().into(), 4),
+                            fee: to_base_units(pool.fee.bps().into(), 4),
                         })
                     }
                     liquidity::Kind::ZeroEx(limit_order) => {
@@ -600,15 +613,15 @@ struct ForeignLimitOrder {
 }
 
 fn fee_to_decimal(fee: liquidity::balancer::v2::Fee) -> bigdecimal::BigDecimal {
-    bigdecimal::BigDecimal::new(fee.as_raw().to_big_int(), 18)
+    to_base_units(fee.as_raw(), 18)
 }
 
 fn weight_to_decimal(weight: liquidity::balancer::v2::weighted::Weight) -> bigdecimal::BigDecimal {
-    bigdecimal::BigDecimal::new(weight.as_raw().to_big_int(), 18)
+    to_base_units(weight.as_raw(), 18)
 }
 
 fn scaling_factor_to_decimal(
     scale: liquidity::balancer::v2::ScalingFactor,
 ) -> bigdecimal::BigDecimal {
-    bigdecimal::BigDecimal::new(scale.as_raw().to_big_int(), 18)
+    to_base_units(scale.as_raw(), 18)
 }
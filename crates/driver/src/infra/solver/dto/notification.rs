@@ -42,6 +42,22 @@ impl Notification {
                 notify::Kind::NonBufferableTokensUsed(tokens) => Kind::NonBufferableTokensUsed {
                     tokens: tokens.into_iter().map(|token| token.0 .0).collect(),
                 },
+                notify::Kind::BufferLimitExceeded(limit) => match limit {
+                    solution::error::BufferLimit::Token { token, used, limit } => {
+                        Kind::BufferLimitExceeded {
+                            token: Some(token.0 .0),
+                            used: used.0,
+                            limit: limit.0,
+                        }
+                    }
+                    solution::error::BufferLimit::Settlement { used, limit } => {
+                        Kind::BufferLimitExceeded {
+                            token: None,
+                            used: used.0,
+                            limit: limit.0,
+                        }
+                    }
+                },
                 notify::Kind::SolverAccountInsufficientBalance(required) => {
                     Kind::SolverAccountInsufficientBalance {
                         required: required.0,
@@ -127,6 +143,15 @@ pub enum Kind {
     NonBufferableTokensUsed {
         tokens: BTreeSet<eth::H160>,
     },
+    /// The token is absent if the limit of the whole settlement was exceeded
+    /// in which case the amounts are denominated in wei.
+    BufferLimitExceeded {
+        token: Option<eth::H160>,
+        #[serde_as(as = "serialize::U256")]
+        used: eth::U256,
+        #[serde_as(as = "serialize::U256")]
+        limit: eth::U256,
+    },
     SolverAccountInsufficientBalance {
         #[serde_as(as = "serialize::U256")]
         required: eth::U256,
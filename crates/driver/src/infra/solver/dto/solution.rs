@@ -1,7 +1,16 @@
 use {
     crate::{
-        domain::{competition, competition::order, eth, liquidity},
-        infra::{solver::Config, Solver},
+        domain::{
+            competition,
+            competition::{order, solution::authorization},
+            eth,
+            liquidity,
+        },
+        infra::{
+            self,
+            solver::{self, Config},
+            Solver,
+        },
         util::{serialize, Bytes},
     },
     app_data::AppDataHash,
@@ -9,14 +18,70 @@ use {
     model::{
         interaction::InteractionData,
         order::{BuyTokenDestination, OrderData, OrderKind, SellTokenSource},
+        signature::{EcdsaSignature, Signature},
         DomainSeparator,
     },
     serde::Deserialize,
     serde_with::serde_as,
-    std::collections::HashMap,
+    std::{collections::HashMap, time::Duration},
+    tap::TapFallible,
 };
 
 impl Solutions {
+    /// Replaces all CoW AMM trades with JIT orders generated by the helper
+    /// contract of the respective CoW AMM. This allows solvers to trade with
+    /// CoW AMMs without encoding the orders and signatures themselves.
+    /// Solutions with CoW AMM trades that can't be resolved get dropped.
+    pub async fn resolve_cow_amm_trades(mut self, eth: &infra::Ethereum) -> Self {
+        let results = futures::future::join_all(
+            self.solutions
+                .iter_mut()
+                .map(|solution| solution.resolve_cow_amm_trades(eth)),
+        )
+        .await;
+        self.solutions = self
+            .solutions
+            .into_iter()
+            .zip(results)
+            .filter_map(|(solution, result)| drop_invalid(solution.id, result.map(|()| solution)))
+            .collect();
+        self
+    }
+
+    /// Drops solutions with ERC-3009 authorizations whose nonces were already
+    /// used or cancelled, since executing them would revert the settlement.
+    pub async fn drop_used_authorizations(mut self, eth: &infra::Ethereum) -> Self {
+        let results = futures::future::join_all(self.solutions.iter().map(|solution| async {
+            let authorizations: Vec<_> = solution
+                .transfer_authorizations
+                .iter()
+                .map(TransferAuthorization::to_domain)
+                .try_collect()?;
+            authorization::check_unused(&authorizations, eth)
+                .await
+                .map_err(|err| super::Error(format!("invalid transfer authorization: {err}")))
+        }))
+        .await;
+        self.solutions = self
+            .solutions
+            .into_iter()
+            .zip(results)
+            .filter_map(|(solution, result)| drop_invalid(solution.id, result.map(|()| solution)))
+            .collect();
+        self
+    }
+
+    /// The phase timings reported by the solver engine, if any.
+    pub fn timings(&self) -> Option<solver::Timings> {
+        self.timings.as_ref().map(|timings| solver::Timings {
+            liquidity_processing: timings.liquidity_processing,
+            optimization: timings.optimization,
+            encoding: timings.encoding,
+        })
+    }
+
+    /// Converts the solutions into the domain. Invalid solutions get dropped
+    /// without affecting the other solutions of the response.
     pub fn into_domain(
         self,
         auction: &competition::Auction,
@@ -24,209 +89,297 @@ impl Solutions {
         weth: eth::WethAddress,
         solver: Solver,
         solver_config: &Config,
-    ) -> Result<Vec<competition::Solution>, super::Error> {
+    ) -> Vec<competition::Solution> {
         self.solutions
             .into_iter()
-            .map(|solution| {
-                competition::Solution::new(
-                    competition::solution::Id::new(solution.id),
-                    solution
-                        .trades
-                        .into_iter()
-                        .map(|trade| match trade {
-                            Trade::Fulfillment(fulfillment) => {
-                                let order = auction
-                                    .orders()
-                                    .iter()
-                                    .find(|order| order.uid == fulfillment.order)
-                                    // TODO this error should reference the UID
-                                    .ok_or(super::Error(
-                                        "invalid order UID specified in fulfillment".to_owned()
-                                    ))?
-                                    .clone();
-
-                                competition::solution::trade::Fulfillment::new(
-                                    order,
-                                    fulfillment.executed_amount.into(),
-                                    match fulfillment.fee {
-                                        Some(fee) => competition::solution::trade::Fee::Dynamic(
-                                            competition::order::SellAmount(fee),
-                                        ),
-                                        None => competition::solution::trade::Fee::Static,
-                                    },
-                                )
-                                .map(competition::solution::Trade::Fulfillment)
-                                .map_err(|err| super::Error(format!("invalid fulfillment: {err}")))
-                            }
-                            Trade::Jit(jit) => Ok(competition::solution::Trade::Jit(
-                                competition::solution::trade::Jit::new(
-                                    competition::order::Jit {
-                                        uid: jit.order.uid(
-                                            solver.eth.contracts().settlement_domain_separator(),
-                                        )?,
-                                        sell: eth::Asset {
-                                            amount: jit.order.sell_amount.into(),
-                                            token: jit.order.sell_token.into(),
-                                        },
-                                        buy: eth::Asset {
-                                            amount: jit.order.buy_amount.into(),
-                                            token: jit.order.buy_token.into(),
-                                        },
-                                        receiver: jit.order.receiver.into(),
-                                        partially_fillable: jit.order.partially_fillable,
-                                        valid_to: jit.order.valid_to.into(),
-                                        app_data: jit.order.app_data.into(),
-                                        side: match jit.order.kind {
-                                            Kind::Sell => competition::order::Side::Sell,
-                                            Kind::Buy => competition::order::Side::Buy,
-                                        },
-                                        sell_token_balance: match jit.order.sell_token_balance {
-                                            SellTokenBalance::Erc20 => {
-                                                competition::order::SellTokenBalance::Erc20
-                                            }
-                                            SellTokenBalance::Internal => {
-                                                competition::order::SellTokenBalance::Internal
-                                            }
-                                            SellTokenBalance::External => {
-                                                competition::order::SellTokenBalance::External
-                                            }
-                                        },
-                                        buy_token_balance: match jit.order.buy_token_balance {
-                                            BuyTokenBalance::Erc20 => {
-                                                competition::order::BuyTokenBalance::Erc20
-                                            }
-                                            BuyTokenBalance::Internal => {
-                                                competition::order::BuyTokenBalance::Internal
-                                            }
-                                        },
-                                        signature: jit.order.signature(
-                                            solver.eth.contracts().settlement_domain_separator(),
-                                        )?,
-                                    },
-                                    jit.executed_amount.into(),
-                                    jit.fee.into(),
-                                )
-                                .map_err(|err| super::Error(format!("invalid JIT trade: {err}")))?,
-                            )),
-                        })
-                        .try_collect()?,
-                    solution
-                        .prices
-                        .into_iter()
-                        .map(|(address, price)| (address.into(), price))
-                        .collect(),
-                    solution
-                        .pre_interactions
-                        .into_iter()
-                        .map(|interaction| eth::Interaction {
-                            target: interaction.target.into(),
-                            value: interaction.value.into(),
-                            call_data: Bytes(interaction.call_data),
-                        })
-                        .collect(),
-                    solution
-                        .interactions
-                        .into_iter()
-                        .map(|interaction| match interaction {
-                            Interaction::Custom(interaction) => {
-                                Ok(competition::solution::Interaction::Custom(
-                                    competition::solution::interaction::Custom {
-                                        target: interaction.target.into(),
-                                        value: interaction.value.into(),
-                                        call_data: interaction.call_data.into(),
-                                        allowances: interaction
-                                            .allowances
-                                            .into_iter()
-                                            .map(|allowance| {
-                                                eth::Allowance {
-                                                    token: allowance.token.into(),
-                                                    spender: allowance.spender.into(),
-                                                    amount: allowance.amount,
-                                                }
-                                                .into()
-                                            })
-                                            .collect(),
-                                        inputs: interaction
-                                            .inputs
-                                            .into_iter()
-                                            .map(|input| eth::Asset {
-                                                amount: input.amount.into(),
-                                                token: input.token.into(),
-                                            })
-                                            .collect(),
-                                        outputs: interaction
-                                            .outputs
-                                            .into_iter()
-                                            .map(|input| eth::Asset {
-                                                amount: input.amount.into(),
-                                                token: input.token.into(),
-                                            })
-                                            .collect(),
-                                        internalize: interaction.internalize,
-                                    },
-                                ))
-                            }
-                            Interaction::Liquidity(interaction) => {
-                                let liquidity = liquidity
-                                    .iter()
-                                    .find(|liquidity| liquidity.id == interaction.id)
-                                    .ok_or(super::Error(
-                                        "invalid liquidity ID specified in interaction".to_owned(),
-                                    ))?
-                                    .to_owned();
-                                Ok(competition::solution::Interaction::Liquidity(
-                                    competition::solution::interaction::Liquidity {
-                                        liquidity,
-                                        input: eth::Asset {
-                                            amount: interaction.input_amount.into(),
-                                            token: interaction.input_token.into(),
-                                        },
-                                        output: eth::Asset {
-                                            amount: interaction.output_amount.into(),
-                                            token: interaction.output_token.into(),
-                                        },
-                                        internalize: interaction.internalize,
-                                    },
-                                ))
-                            }
-                        })
-                        .try_collect()?,
-                    solution
-                        .post_interactions
+            .filter_map(|solution| {
+                let id = solution.id;
+                let result = solution.into_domain(auction, liquidity, weth, &solver, solver_config);
+                drop_invalid(id, result)
+            })
+            .collect()
+    }
+}
+
+impl Solution {
+    fn into_domain(
+        self,
+        auction: &competition::Auction,
+        liquidity: &[liquidity::Liquidity],
+        weth: eth::WethAddress,
+        solver: &Solver,
+        solver_config: &Config,
+    ) -> Result<competition::Solution, super::Error> {
+        let authorizations: Vec<_> = self
+            .transfer_authorizations
+            .iter()
+            .map(TransferAuthorization::to_domain)
+            .try_collect()?;
+        authorization::validate(
+            &authorizations,
+            &solver_config.erc3009_tokens,
+            solver.eth.contracts().settlement().address().into(),
+            u64::try_from(chrono::Utc::now().timestamp()).unwrap_or_default(),
+        )
+        .map_err(|err| super::Error(format!("invalid transfer authorization: {err}")))?;
+
+        competition::Solution::new(
+            competition::solution::Id::new(self.id),
+            self.trades
+                .into_iter()
+                .map(|trade| match trade {
+                    Trade::Fulfillment(fulfillment) => {
+                        let order = auction
+                            .orders()
+                            .iter()
+                            .find(|order| order.uid == fulfillment.order)
+                            // TODO this error should reference the UID
+                            .ok_or(super::Error(
+                                "invalid order UID specified in fulfillment".to_owned()
+                            ))?
+                            .clone();
+
+                        competition::solution::trade::Fulfillment::new(
+                            order,
+                            fulfillment.executed_amount.into(),
+                            match fulfillment.fee {
+                                Some(fee) => competition::solution::trade::Fee::Dynamic(
+                                    competition::order::SellAmount(fee),
+                                ),
+                                None => competition::solution::trade::Fee::Static,
+                            },
+                        )
+                        .map(competition::solution::Trade::Fulfillment)
+                        .map_err(|err| super::Error(format!("invalid fulfillment: {err}")))
+                    }
+                    Trade::CowAmm(_) => Err(super::Error(
+                        "CoW AMM trades have to be resolved into JIT trades first".to_owned(),
+                    )),
+                    Trade::Jit(jit) => Ok(competition::solution::Trade::Jit(
+                        competition::solution::trade::Jit::new(
+                            competition::order::Jit {
+                                uid: jit
+                                    .order
+                                    .uid(solver.eth.contracts().settlement_domain_separator())?,
+                                sell: eth::Asset {
+                                    amount: jit.order.sell_amount.into(),
+                                    token: jit.order.sell_token.into(),
+                                },
+                                buy: eth::Asset {
+                                    amount: jit.order.buy_amount.into(),
+                                    token: jit.order.buy_token.into(),
+                                },
+                                receiver: jit.order.receiver.into(),
+                                partially_fillable: jit.order.partially_fillable,
+                                valid_to: jit.order.valid_to.into(),
+                                app_data: jit.order.app_data.into(),
+                                side: match jit.order.kind {
+                                    Kind::Sell => competition::order::Side::Sell,
+                                    Kind::Buy => competition::order::Side::Buy,
+                                },
+                                sell_token_balance: match jit.order.sell_token_balance {
+                                    SellTokenBalance::Erc20 => {
+                                        competition::order::SellTokenBalance::Erc20
+                                    }
+                                    SellTokenBalance::Internal => {
+                                        competition::order::SellTokenBalance::Internal
+                                    }
+                                    SellTokenBalance::External => {
+                                        competition::order::SellTokenBalance::External
+                                    }
+                                },
+                                buy_token_balance: match jit.order.buy_token_balance {
+                                    BuyTokenBalance::Erc20 => {
+                                        competition::order::BuyTokenBalance::Erc20
+                                    }
+                                    BuyTokenBalance::Internal => {
+                                        competition::order::BuyTokenBalance::Internal
+                                    }
+                                },
+                                signature: jit.order.signature(
+                                    solver.eth.contracts().settlement_domain_separator(),
+                                )?,
+                            },
+                            jit.executed_amount.into(),
+                            jit.fee.into(),
+                        )
+                        .map_err(|err| super::Error(format!("invalid JIT trade: {err}")))?,
+                    )),
+                })
+                .try_collect()?,
+            self.prices
+                .into_iter()
+                .map(|(address, price)| (address.into(), price))
+                .collect(),
+            // Authorizations get executed first, so the solver's own
+            // pre-interactions can already use the transferred tokens.
+            authorizations
+                .iter()
+                .map(authorization::TransferAuthorization::interaction)
+                .chain(
+                    self.pre_interactions
                         .into_iter()
                         .map(|interaction| eth::Interaction {
                             target: interaction.target.into(),
                             value: interaction.value.into(),
                             call_data: Bytes(interaction.call_data),
-                        })
-                        .collect(),
-                    solver.clone(),
-                    weth,
-                    solution.gas.map(|gas| eth::Gas(gas.into())),
-                    solver_config.fee_handler,
-                    auction.surplus_capturing_jit_order_owners(),
+                        }),
                 )
-                .map_err(|err| match err {
-                    competition::solution::error::Solution::InvalidClearingPrices => {
-                        super::Error("invalid clearing prices".to_owned())
+                .collect(),
+            self.interactions
+                .into_iter()
+                .map(|interaction| match interaction {
+                    Interaction::Custom(interaction) => {
+                        Ok(competition::solution::Interaction::Custom(
+                            competition::solution::interaction::Custom {
+                                target: interaction.target.into(),
+                                value: interaction.value.into(),
+                                call_data: interaction.call_data.into(),
+                                allowances: interaction
+                                    .allowances
+                                    .into_iter()
+                                    .map(|allowance| {
+                                        eth::Allowance {
+                                            token: allowance.token.into(),
+                                            spender: allowance.spender.into(),
+                                            amount: allowance.amount,
+                                        }
+                                        .into()
+                                    })
+                                    .collect(),
+                                inputs: interaction
+                                    .inputs
+                                    .into_iter()
+                                    .map(|input| eth::Asset {
+                                        amount: input.amount.into(),
+                                        token: input.token.into(),
+                                    })
+                                    .collect(),
+                                outputs: interaction
+                                    .outputs
+                                    .into_iter()
+                                    .map(|input| eth::Asset {
+                                        amount: input.amount.into(),
+                                        token: input.token.into(),
+                                    })
+                                    .collect(),
+                                internalize: interaction.internalize,
+                            },
+                        ))
                     }
-                    competition::solution::error::Solution::ProtocolFee(err) => {
-                        super::Error(format!("could not incorporate protocol fee: {err}"))
-                    }
-                    competition::solution::error::Solution::InvalidJitTrade(err) => {
-                        super::Error(format!("invalid jit trade: {err}"))
+                    Interaction::Liquidity(interaction) => {
+                        let liquidity = liquidity
+                            .iter()
+                            .find(|liquidity| liquidity.id == interaction.id)
+                            .ok_or(super::Error(
+                                "invalid liquidity ID specified in interaction".to_owned(),
+                            ))?
+                            .to_owned();
+                        Ok(competition::solution::Interaction::Liquidity(
+                            competition::solution::interaction::Liquidity {
+                                liquidity,
+                                input: eth::Asset {
+                                    amount: interaction.input_amount.into(),
+                                    token: interaction.input_token.into(),
+                                },
+                                output: eth::Asset {
+                                    amount: interaction.output_amount.into(),
+                                    token: interaction.output_token.into(),
+                                },
+                                internalize: interaction.internalize,
+                            },
+                        ))
                     }
                 })
-            })
-            .collect()
+                .try_collect()?,
+            self.post_interactions
+                .into_iter()
+                .map(|interaction| eth::Interaction {
+                    target: interaction.target.into(),
+                    value: interaction.value.into(),
+                    call_data: Bytes(interaction.call_data),
+                })
+                .collect(),
+            solver.clone(),
+            weth,
+            self.gas.map(|gas| eth::Gas(gas.into())),
+            solver_config.fee_handler,
+            auction.surplus_capturing_jit_order_owners(),
+        )
+        .map_err(|err| match err {
+            competition::solution::error::Solution::InvalidClearingPrices => {
+                super::Error("invalid clearing prices".to_owned())
+            }
+            competition::solution::error::Solution::ProtocolFee(err) => {
+                super::Error(format!("could not incorporate protocol fee: {err}"))
+            }
+            competition::solution::error::Solution::InvalidJitTrade(err) => {
+                super::Error(format!("invalid jit trade: {err}"))
+            }
+        })
+    }
+
+    async fn resolve_cow_amm_trades(&mut self, eth: &infra::Ethereum) -> Result<(), super::Error> {
+        for trade in self.trades.iter_mut() {
+            let Trade::CowAmm(cow_amm) = trade else {
+                continue;
+            };
+            let amm = eth
+                .contracts()
+                .cow_amm_registry()
+                .amm(cow_amm.amm)
+                .await
+                .ok_or_else(|| super::Error(format!("unknown CoW AMM {:?}", cow_amm.amm)))?;
+            let template = amm
+                .jit_order(
+                    cow_amm.sell_token,
+                    cow_amm.buy_token,
+                    cow_amm.sell_amount,
+                    cow_amm.buy_amount,
+                )
+                .await
+                .map_err(|err| super::Error(format!("invalid CoW AMM trade: {err:#}")))?;
+            self.pre_interactions
+                .extend(template.pre_interactions.iter().cloned());
+            self.post_interactions
+                .extend(template.post_interactions.iter().cloned());
+            *trade = Trade::Jit(JitTrade::from_cow_amm_template(cow_amm.amm, template)?);
+        }
+        Ok(())
     }
 }
 
+/// Logs why a solution is invalid so it can be dropped on its own.
+fn drop_invalid<T>(id: u64, result: Result<T, super::Error>) -> Option<T> {
+    result
+        .tap_err(|err| tracing::warn!(id, ?err, "dropping invalid solution"))
+        .ok()
+}
+
 #[serde_as]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Solutions {
     solutions: Vec<Solution>,
+    #[serde(default)]
+    timings: Option<Timings>,
+}
+
+/// Milliseconds the solver engine spent in the different phases of solving
+/// the auction.
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Timings {
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(default)]
+    liquidity_processing: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(default)]
+    optimization: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationMilliSeconds<u64>>")]
+    #[serde(default)]
+    encoding: Option<Duration>,
 }
 
 #[serde_as]
@@ -243,6 +396,44 @@ pub struct Solution {
     #[serde(default)]
     post_interactions: Vec<InteractionData>,
     gas: Option<u64>,
+    #[serde(default)]
+    transfer_authorizations: Vec<TransferAuthorization>,
+}
+
+/// A signed ERC-3009 `receiveWithAuthorization` call that gets executed
+/// before the pre-interactions.
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferAuthorization {
+    token: eth::H160,
+    from: eth::H160,
+    to: eth::H160,
+    #[serde_as(as = "serialize::U256")]
+    value: eth::U256,
+    valid_after: u64,
+    valid_before: u64,
+    nonce: eth::H256,
+    #[serde_as(as = "serialize::Hex")]
+    signature: Vec<u8>,
+}
+
+impl TransferAuthorization {
+    fn to_domain(&self) -> Result<authorization::TransferAuthorization, super::Error> {
+        let signature: &[u8; 65] = self.signature.as_slice().try_into().map_err(|_| {
+            super::Error("transfer authorization signature must be 65 bytes".to_owned())
+        })?;
+        Ok(authorization::TransferAuthorization {
+            token: self.token.into(),
+            from: self.from.into(),
+            to: self.to.into(),
+            value: self.value.into(),
+            valid_after: self.valid_after,
+            valid_before: self.valid_before,
+            nonce: self.nonce,
+            signature: EcdsaSignature::from_bytes(signature),
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -250,6 +441,7 @@ pub struct Solution {
 enum Trade {
     Fulfillment(Fulfillment),
     Jit(JitTrade),
+    CowAmm(CowAmmTrade),
 }
 
 #[serde_as]
@@ -276,6 +468,75 @@ struct JitTrade {
     fee: eth::U256,
 }
 
+impl JitTrade {
+    /// Fully executes the order generated by a CoW AMM helper contract.
+    fn from_cow_amm_template(
+        amm: eth::H160,
+        template: cow_amm::TemplateOrder,
+    ) -> Result<Self, super::Error> {
+        let Signature::Eip1271(signature) = template.signature else {
+            return Err(super::Error(
+                "CoW AMM helper returned non EIP-1271 signature".to_owned(),
+            ));
+        };
+        let order = template.order;
+        Ok(Self {
+            executed_amount: match order.kind {
+                OrderKind::Sell => order.sell_amount,
+                OrderKind::Buy => order.buy_amount,
+            },
+            fee: Default::default(),
+            order: JitOrder {
+                sell_token: order.sell_token,
+                buy_token: order.buy_token,
+                receiver: order.receiver.unwrap_or_default(),
+                sell_amount: order.sell_amount,
+                buy_amount: order.buy_amount,
+                partially_fillable: order.partially_fillable,
+                valid_to: order.valid_to,
+                app_data: order.app_data.0,
+                kind: match order.kind {
+                    OrderKind::Sell => Kind::Sell,
+                    OrderKind::Buy => Kind::Buy,
+                },
+                sell_token_balance: match order.sell_token_balance {
+                    SellTokenSource::Erc20 => SellTokenBalance::Erc20,
+                    SellTokenSource::Internal => SellTokenBalance::Internal,
+                    SellTokenSource::External => SellTokenBalance::External,
+                },
+                buy_token_balance: match order.buy_token_balance {
+                    BuyTokenDestination::Erc20 => BuyTokenBalance::Erc20,
+                    BuyTokenDestination::Internal => BuyTokenBalance::Internal,
+                },
+                signing_scheme: SigningScheme::Eip1271,
+                // The EIP-1271 signature of JIT orders is expected to be prefixed
+                // with the signer.
+                signature: amm.as_bytes().iter().copied().chain(signature).collect(),
+            },
+        })
+    }
+}
+
+/// Trade with a CoW AMM. The driver generates the JIT order including the
+/// signature and required interactions for it.
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CowAmmTrade {
+    /// The address of the CoW AMM.
+    amm: eth::H160,
+    /// The token sold by the CoW AMM.
+    sell_token: eth::H160,
+    /// The token bought by the CoW AMM.
+    buy_token: eth::H160,
+    /// Together with `buy_amount` defines the exchange rate the CoW AMM
+    /// should trade at.
+    #[serde_as(as = "serialize::U256")]
+    sell_amount: eth::U256,
+    #[serde_as(as = "serialize::U256")]
+    buy_amount: eth::U256,
+}
+
 #[serde_as]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -1,11 +1,13 @@
 use {
     super::notify,
     crate::{
+        boundary,
         domain::{
             competition::{
                 auction::{self, Auction},
                 bad_tokens,
-                solution::{self, Solution},
+                shadow,
+                solution::{self, authorization, scoring, settlement, Solution},
             },
             eth,
             liquidity,
@@ -20,9 +22,10 @@ use {
     },
     anyhow::Result,
     derive_more::{From, Into},
-    num::BigRational,
+    number::ratio::Ratio256,
+    observe::distributed_tracing::RequestBuilderExt,
     reqwest::header::HeaderName,
-    std::{collections::HashMap, time::Duration},
+    std::{collections::HashMap, num::NonZeroUsize, time::Duration},
     tap::TapFallible,
     thiserror::Error,
     tracing::Instrument,
@@ -52,7 +55,7 @@ impl std::fmt::Display for Name {
 
 #[derive(Debug, Clone)]
 pub struct Slippage {
-    pub relative: BigRational,
+    pub relative: Ratio256,
     pub absolute: Option<eth::Ether>,
 }
 
@@ -93,6 +96,8 @@ pub struct Solver {
     config: Config,
     eth: Ethereum,
     persistence: Persistence,
+    /// The engine that gets the same auctions without competing.
+    shadow: Option<Box<ShadowEngine>>,
 }
 
 #[derive(Debug, Clone)]
@@ -127,31 +132,75 @@ pub struct Config {
     pub bad_token_detection: BadTokenDetection,
     /// Max size of the pending settlements queue.
     pub settle_queue_size: usize,
+    /// How the scores of the solutions get computed.
+    pub scoring: scoring::Config,
+    /// Whether redundant interactions get removed from settlements.
+    pub interaction_optimization: settlement::InteractionOptimization,
+    /// Caps on the usage of the settlement contract's buffers.
+    pub buffer_limits: settlement::BufferLimits,
+    /// Candidate engine that solves the same auctions without competing.
+    pub shadow: Option<Shadow>,
+    /// Tokens whose ERC-3009 transfer authorizations solutions may use.
+    pub erc3009_tokens: authorization::Tokens,
+    /// Extension of the solve deadline the solver asks the autopilot for.
+    pub deadline_extension: Option<Duration>,
+}
+
+/// A candidate engine replaying the auctions of a solver. Its solutions get
+/// scored and compared with the solutions of the solver but never
+/// participate in the competition.
+#[derive(Debug, Clone)]
+pub struct Shadow {
+    pub endpoint: url::Url,
+    pub request_headers: HashMap<String, String>,
+    /// How many auctions the shadow engine solves at once. Further auctions
+    /// are not replayed until one of them finished.
+    pub max_concurrent_auctions: NonZeroUsize,
+}
+
+/// The engine replaying the auctions of a solver.
+#[derive(Debug, Clone)]
+pub struct ShadowEngine {
+    pub solver: Solver,
+    /// Bounds how many auctions the engine solves at once.
+    pub slots: shadow::Slots,
 }
 
 impl Solver {
     pub async fn try_new(config: Config, eth: Ethereum) -> Result<Self> {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            reqwest::header::CONTENT_TYPE,
-            "application/json".parse().unwrap(),
-        );
-        headers.insert(reqwest::header::ACCEPT, "application/json".parse().unwrap());
-
-        for (key, val) in config.request_headers.iter() {
-            let header_name = HeaderName::try_from(key)?;
-            headers.insert(header_name, val.parse()?);
-        }
+        let shadow = match &config.shadow {
+            Some(shadow) => {
+                let config = Config {
+                    endpoint: shadow.endpoint.clone(),
+                    name: format!("{}-shadow", config.name).into(),
+                    request_headers: shadow.request_headers.clone(),
+                    // Only the auctions sent to the solver itself get archived.
+                    s3: None,
+                    shadow: None,
+                    ..config.clone()
+                };
+                Some(Box::new(ShadowEngine {
+                    solver: Self {
+                        client: client(eth.http_client_factory(), &config.request_headers)?,
+                        persistence: Persistence::build(&config).await,
+                        config,
+                        eth: eth.clone(),
+                        shadow: None,
+                    },
+                    slots: shadow::Slots::new(shadow.max_concurrent_auctions),
+                }))
+            }
+            None => None,
+        };
 
         let persistence = Persistence::build(&config).await;
 
         Ok(Self {
-            client: reqwest::ClientBuilder::new()
-                .default_headers(headers)
-                .build()?,
+            client: client(eth.http_client_factory(), &config.request_headers)?,
             config,
             eth,
             persistence,
+            shadow,
         })
     }
 
@@ -205,6 +254,10 @@ impl Solver {
         self.config.solver_native_token
     }
 
+    pub fn interaction_optimization(&self) -> settlement::InteractionOptimization {
+        self.config.interaction_optimization
+    }
+
     pub fn quote_tx_origin(&self) -> &Option<eth::Address> {
         &self.config.quote_tx_origin
     }
@@ -213,13 +266,32 @@ impl Solver {
         self.config.settle_queue_size
     }
 
+    pub fn scoring(&self) -> &scoring::Config {
+        &self.config.scoring
+    }
+
+    pub fn buffer_limits(&self) -> &settlement::BufferLimits {
+        &self.config.buffer_limits
+    }
+
+    pub fn deadline_extension(&self) -> Option<Duration> {
+        self.config.deadline_extension
+    }
+
+    /// The engine that solves the same auctions as this solver without
+    /// competing.
+    pub fn shadow(&self) -> Option<&ShadowEngine> {
+        self.shadow.as_deref()
+    }
+
     /// Make a POST request instructing the solver to solve an auction.
-    /// Allocates at most `timeout` time for the solving.
+    /// Allocates at most `timeout` time for the solving. Returns the
+    /// solutions together with the phase timings reported by the solver.
     pub async fn solve(
         &self,
         auction: &Auction,
         liquidity: &[liquidity::Liquidity],
-    ) -> Result<Vec<Solution>, Error> {
+    ) -> Result<(Vec<Solution>, Option<Timings>), Error> {
         // Fetch the solutions from the solver.
         let weth = self.eth.contracts().weth_address();
         let auction_dto = dto::Auction::new(
@@ -237,23 +309,30 @@ impl Solver {
         let body = serde_json::to_string(&auction_dto).unwrap();
         let url = shared::url::join(&self.config.endpoint, "solve");
         super::observe::solver_request(&url, &body);
-        let mut req = self
+        let req = self
             .client
             .post(url.clone())
             .body(body)
-            .timeout(auction.deadline().solvers().remaining().unwrap_or_default());
-        if let Some(id) = observe::request_id::get_task_local_storage() {
-            req = req.header("X-REQUEST-ID", id);
-        }
+            .timeout(auction.deadline().solvers().remaining().unwrap_or_default())
+            .with_tracing_headers();
         let res = util::http::send(self.config.response_size_limit_max_bytes, req).await;
         super::observe::solver_response(&url, res.as_deref());
         let res = res?;
         let res: dto::Solutions = serde_json::from_str(&res)
             .tap_err(|err| tracing::warn!(res, ?err, "failed to parse solver response"))?;
-        let solutions = res.into_domain(auction, liquidity, weth, self.clone(), &self.config)?;
+        let res = res
+            .resolve_cow_amm_trades(&self.eth)
+            .await
+            .drop_used_authorizations(&self.eth)
+            .await;
+        let timings = res.timings();
+        if let Some(timings) = &timings {
+            super::observe::solver_timings(self.name(), timings);
+        }
+        let solutions = res.into_domain(auction, liquidity, weth, self.clone(), &self.config);
 
         super::observe::solutions(&solutions, auction.surplus_capturing_jit_order_owners());
-        Ok(solutions)
+        Ok((solutions, timings))
     }
 
     /// Make a fire and forget POST request to notify the solver about an event.
@@ -267,10 +346,7 @@ impl Solver {
             serde_json::to_string(&dto::Notification::new(auction_id, solution_id, kind)).unwrap();
         let url = shared::url::join(&self.config.endpoint, "notify");
         super::observe::solver_request(&url, &body);
-        let mut req = self.client.post(url).body(body);
-        if let Some(id) = observe::request_id::get_task_local_storage() {
-            req = req.header("X-REQUEST-ID", id);
-        }
+        let req = self.client.post(url).body(body).with_tracing_headers();
         let response_size = self.config.response_size_limit_max_bytes;
         let future = async move {
             if let Err(error) = util::http::send(response_size, req).await {
@@ -281,6 +357,38 @@ impl Solver {
     }
 }
 
+fn client(
+    http: &boundary::HttpClientFactory,
+    request_headers: &HashMap<String, String>,
+) -> Result<reqwest::Client> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        reqwest::header::CONTENT_TYPE,
+        "application/json".parse().unwrap(),
+    );
+    headers.insert(reqwest::header::ACCEPT, "application/json".parse().unwrap());
+
+    for (key, val) in request_headers.iter() {
+        let header_name = HeaderName::try_from(key)?;
+        headers.insert(header_name, val.parse()?);
+    }
+
+    // Solver requests are bounded by the deadline of the auction instead.
+    Ok(http
+        .builder_without_timeout()
+        .default_headers(headers)
+        .build()?)
+}
+
+/// Time the solver engine reported to have spent in the different phases of
+/// solving an auction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timings {
+    pub liquidity_processing: Option<Duration>,
+    pub optimization: Option<Duration>,
+    pub encoding: Option<Duration>,
+}
+
 /// Controls whether or not the driver is allowed to merge multiple solutions
 /// of the same solver to produce an overall better solution.
 #[derive(Debug, Clone, Copy)]
@@ -1,6 +1,11 @@
 use {
     crate::{
-        domain::{competition::bad_tokens, Mempools},
+        boundary,
+        domain::{
+            competition::{bad_tokens, executed_orders},
+            mempools::PublicFallback,
+            Mempools,
+        },
         infra::{
             self,
             blockchain::{self, Ethereum},
@@ -13,6 +18,7 @@ use {
         },
     },
     clap::Parser,
+    ethcontract::dyns::DynWeb3,
     futures::future::join_all,
     std::{net::SocketAddr, sync::Arc, time::Duration},
     tokio::sync::oneshot,
@@ -42,8 +48,12 @@ pub async fn run(
 /// Run the driver. This function exists to avoid multiple monomorphizations of
 /// the `run` code, which bloats the binaries and increases compile times.
 async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAddr>>) {
-    crate::infra::observe::init(&args.log);
+    crate::infra::observe::init(&args.logging.observe_config());
+    args.ethrpc_budget.configure(None);
 
+    for config in &args.custom_chains {
+        chain::register(config.clone()).expect("invalid custom chain");
+    }
     let ethrpc = ethrpc(&args).await;
     let web3 = ethrpc.web3().clone();
     let config = config::file::load(ethrpc.chain(), &args.config).await;
@@ -55,23 +65,17 @@ async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAdd
         solvers: solvers(&config, &eth).await,
         liquidity: liquidity(&config, &eth).await,
         simulator: simulator(&config, &eth),
-        mempools: Mempools::try_new(
-            config
-                .mempools
-                .iter()
-                .map(|mempool| {
-                    crate::infra::mempool::Mempool::new(mempool.to_owned(), web3.clone())
-                })
-                .collect(),
-            eth.clone(),
-        )
-        .unwrap(),
+        mempools: mempools(&config, &eth, &web3),
         bad_token_detector: bad_tokens::simulation::Detector::new(
             config.simulation_bad_token_max_age,
             &eth,
         ),
+        executed_orders: Arc::new(
+            executed_orders::Tracker::load(config.executed_orders.clone()).await,
+        ),
         eth,
         addr: args.addr,
+        config: config.api.clone(),
         addr_sender,
     }
     .serve(
@@ -96,6 +100,29 @@ async fn run_with(args: cli::Args, addr_sender: Option<oneshot::Sender<SocketAdd
     };
 }
 
+/// The mempools settlements get submitted to. Returns `None` in quote-only
+/// mode.
+fn mempools(config: &infra::Config, eth: &Ethereum, web3: &DynWeb3) -> Option<Mempools> {
+    if config.quote_only {
+        tracing::info!("running in quote-only mode");
+        return None;
+    }
+    let http = eth.http_client_factory();
+    let mempool = |config: &infra::mempool::Config| {
+        crate::infra::mempool::Mempool::new(config.to_owned(), web3.clone(), http)
+    };
+    let mempools = Mempools::try_new(config.mempools.iter().map(mempool).collect(), eth.clone())
+        .unwrap()
+        .with_public_fallback(config.public_mempool_fallback.as_ref().map(|fallback| {
+            PublicFallback {
+                mempool: mempool(&fallback.mempool),
+                delay: fallback.delay,
+                sandwich_gas: fallback.sandwich_gas,
+            }
+        }));
+    Some(mempools)
+}
+
 fn simulator(config: &infra::Config, eth: &Ethereum) -> Simulator {
     let mut simulator = match &config.simulator {
         Some(infra::simulator::Config::Tenderly(tenderly)) => Simulator::tenderly(
@@ -128,7 +155,9 @@ fn simulator(config: &infra::Config, eth: &Ethereum) -> Simulator {
 }
 
 async fn ethrpc(args: &cli::Args) -> blockchain::Rpc {
-    blockchain::Rpc::try_new(&args.ethrpc)
+    let http =
+        boundary::http_client_factory(args.http_proxy.clone(), args.http_proxy_overrides.clone());
+    blockchain::Rpc::try_new(&args.ethrpc, &args.ethrpc_fallbacks, http)
         .await
         .expect("connect ethereum RPC")
 }
@@ -64,3 +64,20 @@ async fn with_jit_order() {
     // Check whether the returned data aligns with the expected.
     quote.ok().amount().interactions().jit_order();
 }
+
+/// Test that a quote-only driver serves quotes without solver accounts and
+/// doesn't serve the competition endpoints.
+#[tokio::test]
+#[ignore]
+async fn quote_only() {
+    let test = tests::setup()
+        .pool(ab_pool())
+        .order(ab_order())
+        .solution(ab_solution())
+        .quote_only()
+        .done()
+        .await;
+
+    test.quote().await.ok().amount().interactions();
+    test.solve().await.err();
+}
@@ -18,6 +18,8 @@ pub struct Config {
     pub enable_simulation: bool,
     pub mempools: Vec<Mempool>,
     pub order_priority_strategies: Vec<OrderPriorityStrategy>,
+    /// Only serve quotes, without solver accounts or mempools.
+    pub quote_only: bool,
 }
 
 pub struct Driver {
@@ -196,6 +198,9 @@ async fn create_config_file(
     blockchain: &Blockchain,
 ) -> tempfile::TempPath {
     let mut file = tempfile::NamedTempFile::new().unwrap();
+    if config.quote_only {
+        writeln!(file, "quote-only = true").unwrap();
+    }
     let simulation = if config.enable_simulation {
         ""
     } else {
@@ -293,7 +298,7 @@ async fn create_config_file(
                endpoint = "http://{}"
                absolute-slippage = "{}"
                relative-slippage = "{}"
-               account = "0x{}"
+               {}
                solving-share-of-deadline = {}
                http-time-buffer = "{}ms"
                fee-handler = {}
@@ -307,7 +312,14 @@ async fn create_config_file(
                 .map(|abs| abs.0)
                 .unwrap_or_default(),
             solver.slippage.relative,
-            hex::encode(solver.private_key.secret_bytes()),
+            if config.quote_only {
+                String::new()
+            } else {
+                format!(
+                    "account = \"0x{}\"",
+                    hex::encode(solver.private_key.secret_bytes())
+                )
+            },
             solver.timeouts.solving_share_of_deadline.get(),
             solver.timeouts.http_delay.num_milliseconds(),
             serde_json::to_string(&solver.fee_handler).unwrap(),
@@ -37,7 +37,7 @@ use {
     futures::future::join_all,
     hyper::StatusCode,
     model::order::{BuyTokenDestination, SellTokenSource},
-    number::serialization::HexOrDecimalU256,
+    number::{conversions::mul_ratio_ceil, serialization::HexOrDecimalU256},
     primitive_types::H160,
     secp256k1::SecretKey,
     serde_with::serde_as,
@@ -431,12 +431,12 @@ impl Pool {
         } else {
             (quote.buy_amount, quote.sell_amount)
         };
-        let reserve_a_min = ceil_div(
-            eth::U256::from(997)
-                * quote_sell_amount
-                * (self.amount_b - quote_buy_amount - eth::U256::from(1)),
+        let reserve_a_min = mul_ratio_ceil(
+            eth::U256::from(997) * quote_sell_amount,
+            self.amount_b - quote_buy_amount - eth::U256::from(1),
             eth::U256::from(1000) * quote_buy_amount,
-        );
+        )
+        .unwrap();
         let reserve_a_max =
             (eth::U256::from(997) * quote_sell_amount * (self.amount_b - quote_buy_amount))
                 / (eth::U256::from(1000) * quote_buy_amount);
@@ -461,12 +461,12 @@ impl Pool {
         } else {
             (quote.buy_amount, quote.sell_amount)
         };
-        let reserve_b_min = ceil_div(
-            quote_buy_amount
-                * (eth::U256::from(1000) * self.amount_a
-                    + eth::U256::from(997) * quote_sell_amount),
+        let reserve_b_min = mul_ratio_ceil(
+            quote_buy_amount,
+            eth::U256::from(1000) * self.amount_a + eth::U256::from(997) * quote_sell_amount,
             eth::U256::from(997) * quote_sell_amount,
-        );
+        )
+        .unwrap();
         let reserve_b_max = ((quote_buy_amount + eth::U256::from(1))
             * (eth::U256::from(1000) * self.amount_a + eth::U256::from(997) * quote_sell_amount)
             - eth::U256::from(1))
@@ -484,10 +484,6 @@ impl Pool {
     }
 }
 
-fn ceil_div(x: eth::U256, y: eth::U256) -> eth::U256 {
-    (x + y - eth::U256::from(1)) / y
-}
-
 #[derive(Debug)]
 pub enum Mempool {
     Public,
@@ -543,6 +539,8 @@ pub struct Setup {
     /// The maximum number of blocks to wait for a settlement to appear on
     /// chain.
     settle_submission_deadline: u64,
+    /// Should the driver only serve quotes?
+    quote_only: bool,
 }
 
 /// The validity of a solution.
@@ -963,6 +961,7 @@ impl Setup {
                 enable_simulation: self.enable_simulation,
                 mempools: self.mempools,
                 order_priority_strategies: self.order_priority_strategies,
+                quote_only: self.quote_only,
             },
             &solvers_with_address,
             &blockchain,
@@ -993,6 +992,16 @@ impl Setup {
         }
     }
 
+    /// Run the driver in quote-only mode. Implies that this is a test for the
+    /// /quote endpoint.
+    pub fn quote_only(self) -> Self {
+        Self {
+            quote: true,
+            quote_only: true,
+            ..self
+        }
+    }
+
     /// Solver send the solution as JIT order
     pub fn jit_order(mut self, jit_order: JitOrder) -> Self {
         self.jit_orders.push(jit_order);
@@ -1249,8 +1258,8 @@ impl SolveOk<'_> {
         assert_eq!(solutions.len(), 1);
         let solution = solutions[0].clone();
         assert!(solution.is_object());
-        // response contains 1 optional field
-        assert!((5..=6).contains(&solution.as_object().unwrap().len()));
+        // response contains 2 optional fields
+        assert!((5..=7).contains(&solution.as_object().unwrap().len()));
         solution
     }
 
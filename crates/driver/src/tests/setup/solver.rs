@@ -15,6 +15,7 @@ use {
     },
     ethereum_types::H160,
     itertools::Itertools,
+    number::conversions::{factor_scale, factor_to_fixed, mul_ratio_floor},
     serde_json::json,
     std::{
         collections::{HashMap, HashSet},
@@ -79,10 +80,12 @@ impl Solver {
                             fee::Policy::Volume { factor }
                                 if config.fee_handler == FeeHandler::Driver =>
                             {
-                                current_sell_amount = eth::TokenAmount(current_sell_amount)
-                                    .apply_factor(1.0 / (1.0 + factor))
-                                    .unwrap()
-                                    .0;
+                                current_sell_amount = mul_ratio_floor(
+                                    current_sell_amount,
+                                    factor_scale(),
+                                    factor_scale() + factor_to_fixed(*factor),
+                                )
+                                .unwrap();
                             }
                             _ => {}
                         }
@@ -105,10 +108,12 @@ impl Solver {
                             fee::Policy::Volume { factor }
                                 if config.fee_handler == FeeHandler::Driver =>
                             {
-                                current_buy_amount = eth::TokenAmount(current_buy_amount)
-                                    .apply_factor(1.0 / (1.0 - factor))
-                                    .unwrap()
-                                    .0;
+                                current_buy_amount = mul_ratio_floor(
+                                    current_buy_amount,
+                                    factor_scale(),
+                                    factor_scale() - factor_to_fixed(*factor),
+                                )
+                                .unwrap();
                             }
                             _ => {}
                         }
@@ -408,7 +413,9 @@ impl Solver {
             .collect::<HashMap<_, _>>();
 
         let url = config.blockchain.web3_url.parse().unwrap();
-        let rpc = infra::blockchain::Rpc::try_new(&url).await.unwrap();
+        let rpc = infra::blockchain::Rpc::try_new(&url, &[], Default::default())
+            .await
+            .unwrap();
         let gas = Arc::new(
             infra::blockchain::GasPriceEstimator::new(
                 rpc.web3(),
@@ -5,9 +5,6 @@ pub trait U256Ext: Sized {
     fn to_big_uint(&self) -> num::BigUint;
     fn to_big_rational(&self) -> num::BigRational;
 
-    fn checked_ceil_div(&self, other: &Self) -> Option<Self>;
-    fn ceil_div(&self, other: &Self) -> Self;
-
     fn from_big_int(input: &num::BigInt) -> Result<Self>;
     fn from_big_uint(input: &num::BigUint) -> Result<Self>;
     fn from_big_rational(value: &num::BigRational) -> Result<Self>;
@@ -28,16 +25,6 @@ impl U256Ext for eth::U256 {
         num::BigRational::new(self.to_big_int(), 1.into())
     }
 
-    fn checked_ceil_div(&self, other: &Self) -> Option<Self> {
-        self.checked_add(other.checked_sub(1.into())?)?
-            .checked_div(*other)
-    }
-
-    fn ceil_div(&self, other: &Self) -> Self {
-        self.checked_ceil_div(other)
-            .expect("ceiling division arithmetic error")
-    }
-
     fn from_big_int(input: &num::BigInt) -> Result<eth::U256> {
         anyhow::ensure!(input.sign() != num::bigint::Sign::Minus, "negative");
         Self::from_big_uint(input.magnitude())
@@ -1,7 +1,6 @@
 mod bytes;
 pub mod conv;
 pub mod http;
-pub mod math;
 mod percent;
 pub mod serialize;
 mod time;
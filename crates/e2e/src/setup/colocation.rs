@@ -98,6 +98,18 @@ pub fn start_driver(
     solvers: Vec<SolverEngine>,
     liquidity: LiquidityProvider,
     quote_using_limit_orders: bool,
+) -> JoinHandle<()> {
+    start_driver_with_api(contracts, solvers, liquidity, quote_using_limit_orders, "")
+}
+
+/// Like [`start_driver`] but with additional configuration of the `[api]`
+/// section, e.g. to require authentication.
+pub fn start_driver_with_api(
+    contracts: &Contracts,
+    solvers: Vec<SolverEngine>,
+    liquidity: LiquidityProvider,
+    quote_using_limit_orders: bool,
+    api: &str,
 ) -> JoinHandle<()> {
     let base_tokens: HashSet<_> = solvers
         .iter()
@@ -179,6 +191,8 @@ gas-price-cap = "1000000000000"
 
 [[submission.mempool]]
 mempool = "public"
+
+{api}
 "#,
         contracts.gp_settlement.address(),
         contracts.weth.address(),
@@ -0,0 +1,250 @@
+//! Golden tests for API responses.
+//!
+//! Responses get canonicalized and compared with the approved responses in
+//! `tests/golden`. Canonicalization sorts object keys and replaces values
+//! that change between runs (timestamps, order UIDs, signatures, ...) with
+//! placeholders, so only changes to the shape or the deterministic content of
+//! a response make a test fail. Addresses of the accounts and contracts of a
+//! scenario get replaced with their names, so that golden files read like the
+//! scenario that produced them.
+//!
+//! When a response changes intentionally, run the failing test again with
+//! `BLESS_GOLDEN=1` to record the new response and commit the updated golden
+//! file together with the change. New golden files get recorded the same way.
+
+use {
+    ethcontract::H160,
+    serde_json::{Map, Value},
+    std::{fs, path::PathBuf},
+};
+
+/// Environment variable that makes golden tests record the current responses
+/// instead of comparing them with the approved ones.
+pub const BLESS_ENV: &str = "BLESS_GOLDEN";
+
+/// Fields whose values change between runs of the same scenario. They get
+/// redacted wherever they occur in a response.
+pub const VOLATILE_FIELDS: &[&str] = &[
+    "auctionId",
+    "auctionStartBlock",
+    "block",
+    "blockNumber",
+    "competitionSimulationBlock",
+    "created",
+    "creationDate",
+    "deadline",
+    "expiration",
+    "id",
+    "latestSettlementBlock",
+    "quoteId",
+    "signature",
+    "timings",
+    "transactionHash",
+    "txHash",
+    "validTo",
+];
+
+/// Sorts all object keys and replaces the values of the [`VOLATILE_FIELDS`]
+/// and the `extra_redactions` with placeholders. Order UIDs depend on the
+/// validity of the order, so they get replaced wherever they occur. The
+/// `addresses` get replaced with `<name>` wherever they occur, including in
+/// object keys.
+pub fn canonicalize(value: Value, extra_redactions: &[&str], addresses: &[(H160, &str)]) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut fields: Vec<_> = object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = if is_redacted(&key, extra_redactions) {
+                        Value::String(format!("<{key}>"))
+                    } else {
+                        canonicalize(value, extra_redactions, addresses)
+                    };
+                    (name_address(&key, addresses).unwrap_or(key), value)
+                })
+                .collect();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(fields.into_iter().collect::<Map<_, _>>())
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| canonicalize(value, extra_redactions, addresses))
+                .collect(),
+        ),
+        Value::String(string) if is_order_uid(&string) => Value::String("<orderUid>".to_owned()),
+        Value::String(string) => Value::String(name_address(&string, addresses).unwrap_or(string)),
+        value => value,
+    }
+}
+
+fn name_address(string: &str, addresses: &[(H160, &str)]) -> Option<String> {
+    let hex = string
+        .strip_prefix("0x")
+        .filter(|hex| hex.len() == 2 * 20)?;
+    let address: H160 = hex.parse().ok()?;
+    addresses
+        .iter()
+        .find(|(known, _)| *known == address)
+        .map(|(_, name)| format!("<{name}>"))
+}
+
+fn is_order_uid(string: &str) -> bool {
+    string
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 2 * 56 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn is_redacted(key: &str, extra_redactions: &[&str]) -> bool {
+    VOLATILE_FIELDS.contains(&key) || extra_redactions.contains(&key)
+}
+
+/// Asserts that the canonicalized response matches the approved golden file
+/// `tests/golden/<name>.json`. With [`BLESS_ENV`] set the response gets
+/// recorded as the new golden file instead.
+pub fn assert_golden(
+    name: &str,
+    response: Value,
+    extra_redactions: &[&str],
+    addresses: &[(H160, &str)],
+) {
+    let actual = canonicalize(response, extra_redactions, addresses);
+    let path = golden_path(name);
+
+    if std::env::var(BLESS_ENV).is_ok_and(|bless| bless == "1") {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!("{:#}\n", actual)).unwrap();
+        tracing::info!(?path, "recorded golden file");
+        return;
+    }
+
+    let expected: Value = match fs::read_to_string(&path) {
+        Ok(expected) => serde_json::from_str(&expected)
+            .unwrap_or_else(|err| panic!("golden file {path:?} is not valid JSON: {err}")),
+        Err(err) => panic!(
+            "no golden file {path:?} ({err}). Run the test with {BLESS_ENV}=1 to record it.\nactual \
+             response: {actual:#}"
+        ),
+    };
+
+    let mut differences = Vec::new();
+    diff("", &expected, &actual, &mut differences);
+    assert!(
+        differences.is_empty(),
+        "response {name} differs from the golden file {path:?}:\n{}\nRun the test with \
+         {BLESS_ENV}=1 to accept the new response if the change is intended.",
+        differences.join("\n")
+    );
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.json"))
+}
+
+/// Collects human readable descriptions of all differences between the two
+/// values, identified by their JSON pointer.
+fn diff(pointer: &str, expected: &Value, actual: &Value, differences: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                let pointer = format!("{pointer}/{key}");
+                match actual.get(key) {
+                    Some(actual) => diff(&pointer, expected, actual, differences),
+                    None => differences.push(format!("{pointer}: missing, expected {expected}")),
+                }
+            }
+            for (key, actual) in actual {
+                if !expected.contains_key(key) {
+                    differences.push(format!("{pointer}/{key}: unexpected {actual}"));
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                diff(&format!("{pointer}/{i}"), expected, actual, differences);
+            }
+        }
+        (expected, actual) if expected != actual => {
+            differences.push(format!("{pointer}: expected {expected}, got {actual}"));
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn canonicalize_redacts_and_names_values() {
+        let trader = H160([1; 20]);
+        let token = H160([2; 20]);
+        let response = json!({
+            "validTo": 1700000000,
+            "owner": format!("{trader:?}"),
+            "uid": format!("0x{}", "ab".repeat(56)),
+            "prices": { format!("{token:?}"): "1000" },
+            "orders": [{ "feeAmount": "42", "kind": "sell" }],
+            "other": "0x0303030303030303030303030303030303030303",
+        });
+
+        let canonical = canonicalize(
+            response,
+            &["feeAmount"],
+            &[(trader, "trader"), (token, "token")],
+        );
+
+        assert_eq!(
+            canonical,
+            json!({
+                "orders": [{ "feeAmount": "<feeAmount>", "kind": "sell" }],
+                "other": "0x0303030303030303030303030303030303030303",
+                "owner": "<trader>",
+                "prices": { "<token>": "1000" },
+                "uid": "<orderUid>",
+                "validTo": "<validTo>",
+            })
+        );
+    }
+
+    #[test]
+    fn diff_describes_differences() {
+        let expected = json!({
+            "missing": 1,
+            "changed": "a",
+            "same": [1, 2],
+            "nested": { "array": [1, 2] },
+        });
+        let actual = json!({
+            "unexpected": true,
+            "changed": "b",
+            "same": [1, 2],
+            "nested": { "array": [1] },
+        });
+
+        let mut differences = Vec::new();
+        diff("", &expected, &actual, &mut differences);
+        differences.sort();
+
+        assert_eq!(
+            differences,
+            [
+                r#"/changed: expected "a", got "b""#,
+                "/missing: missing, expected 1",
+                "/nested/array: expected [1,2], got [1]",
+                "/unexpected: unexpected true",
+            ],
+        );
+    }
+
+    #[test]
+    fn diff_of_equal_values_is_empty() {
+        let value = json!({ "a": [{ "b": null }] });
+        let mut differences = Vec::new();
+        diff("", &value, &value, &mut differences);
+        assert!(differences.is_empty());
+    }
+}
@@ -3,6 +3,7 @@ mod deploy;
 #[macro_use]
 pub mod onchain_components;
 pub mod fee;
+pub mod golden;
 mod services;
 mod solver;
 
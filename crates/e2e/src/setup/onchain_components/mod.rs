@@ -5,6 +5,7 @@ use {
     ethcontract::{transaction::TransactionBuilder, Account, Bytes, PrivateKey, H160, U256},
     hex_literal::hex,
     model::{
+        order::OrderBuilder,
         signature::{EcdsaSignature, EcdsaSigningScheme},
         DomainSeparator,
         TokenPair,
@@ -94,6 +95,17 @@ impl TestAccount {
         )
     }
 
+    /// Returns a builder for an order of this account. Unless specified
+    /// otherwise, the order gets signed with EIP-712 and is valid for 5
+    /// minutes.
+    pub fn order(&self, domain: &DomainSeparator) -> OrderBuilder {
+        OrderBuilder::default().with_validity(300).sign_with(
+            EcdsaSigningScheme::Eip712,
+            domain,
+            SecretKeyRef::from(&SecretKey::from_slice(self.private_key()).unwrap()),
+        )
+    }
+
     pub async fn nonce(&self, web3: &Web3) -> U256 {
         web3.eth()
             .transaction_count(self.address(), None)
@@ -42,8 +42,11 @@ fn orders_for_tx_endpoint(tx_hash: &H256) -> String {
     format!("/api/v1/transactions/{tx_hash:?}/orders")
 }
 
-fn orders_for_owner(owner: &H160, offset: u64, limit: u64) -> String {
-    format!("{ACCOUNT_ENDPOINT}/{owner:?}/orders?offset={offset}&limit={limit}")
+fn orders_for_owner(owner: &H160, after: Option<&OrderUid>, limit: u64) -> String {
+    match after {
+        Some(after) => format!("{ACCOUNT_ENDPOINT}/{owner:?}/orders?after={after}&limit={limit}"),
+        None => format!("{ACCOUNT_ENDPOINT}/{owner:?}/orders?limit={limit}"),
+    }
 }
 
 pub struct ServicesBuilder {
@@ -523,14 +526,14 @@ impl<'a> Services<'a> {
     pub async fn get_orders_for_owner(
         &self,
         owner: &H160,
-        offset: u64,
+        after: Option<&OrderUid>,
         limit: u64,
     ) -> Result<Vec<Order>, (StatusCode, String)> {
         let response = self
             .http
             .get(format!(
                 "{API_HOST}{}",
-                orders_for_owner(owner, offset, limit)
+                orders_for_owner(owner, after, limit)
             ))
             .send()
             .await
@@ -629,6 +632,10 @@ impl<'a> Services<'a> {
 }
 
 pub async fn clear_database() {
+    tracing::info!("Setting up database schema.");
+    let pool = sqlx::PgPool::connect(LOCAL_DB_URL).await.unwrap();
+    database::migrate(&pool).await.unwrap();
+
     tracing::info!("Clearing database.");
 
     async fn truncate_tables() -> Result<(), sqlx::Error> {
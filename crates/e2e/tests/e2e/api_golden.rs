@@ -0,0 +1,169 @@
+use {
+    driver::domain::eth::NonZeroU256,
+    e2e::{setup::*, tx, tx_value},
+    ethcontract::U256,
+    model::{
+        order::OrderKind,
+        quote::{OrderQuoteRequest, OrderQuoteResponse, OrderQuoteSide, SellAmount},
+    },
+    reqwest::StatusCode,
+    serde_json::Value,
+    shared::ethrpc::Web3,
+};
+
+/// Values that depend on the gas price at the time of quoting.
+const GAS_DEPENDENT: &[&str] = &[
+    "buyAmount",
+    "feeAmount",
+    "fullFeeAmount",
+    "gasAmount",
+    "gasPrice",
+    "prices",
+    "sellTokenPrice",
+    "solverFee",
+];
+
+/// The quoted sell amount is the sell amount before fee minus the fee, which
+/// depends on the gas price.
+const QUOTE_DEPENDENT: &[&str] = &["sellAmount"];
+
+/// Values of the competition that depend on the gas used by the settlement.
+const SETTLEMENT_DEPENDENT: &[&str] = &[
+    "clearingPrices",
+    "executedAmount",
+    "gasPrice",
+    "referenceScore",
+    "score",
+    "sellAmount",
+    "transactionHashes",
+];
+
+#[tokio::test]
+#[ignore]
+async fn local_node_api_golden() {
+    run_test(api_golden).await;
+}
+
+async fn get(services: &Services<'_>, path: &str) -> Value {
+    let response = services
+        .client()
+        .get(format!("{API_HOST}{path}"))
+        .send()
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.text().await.unwrap();
+    assert_eq!(status, StatusCode::OK, "{path}: {body}");
+    serde_json::from_str(&body).unwrap()
+}
+
+async fn api_golden(web3: Web3) {
+    let mut onchain = OnchainComponents::deploy(web3).await;
+
+    let [solver] = onchain.make_solvers(to_wei(10)).await;
+    let [trader] = onchain.make_accounts(to_wei(10)).await;
+    let [token] = onchain
+        .deploy_tokens_with_weth_uni_v2_pools(to_wei(1_000), to_wei(1_000))
+        .await;
+
+    tx!(
+        trader.account(),
+        onchain
+            .contracts()
+            .weth
+            .approve(onchain.contracts().allowance, to_wei(3))
+    );
+    tx_value!(
+        trader.account(),
+        to_wei(3),
+        onchain.contracts().weth.deposit()
+    );
+
+    let addresses = [
+        (trader.address(), "trader"),
+        (solver.address(), "solver"),
+        (onchain.contracts().weth.address(), "weth"),
+        (token.address(), "token"),
+        (onchain.contracts().gp_settlement.address(), "settlement"),
+    ];
+
+    let services = Services::new(&onchain).await;
+    services.start_protocol(solver).await;
+
+    let quote_request = OrderQuoteRequest {
+        from: trader.address(),
+        sell_token: onchain.contracts().weth.address(),
+        buy_token: token.address(),
+        side: OrderQuoteSide::Sell {
+            sell_amount: SellAmount::BeforeFee {
+                value: NonZeroU256::try_from(to_wei(1)).unwrap(),
+            },
+        },
+        ..Default::default()
+    };
+    let quote = services
+        .client()
+        .post(format!("{API_HOST}{QUOTING_ENDPOINT}"))
+        .json(&quote_request)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let quote: Value = serde_json::from_str(&quote).unwrap();
+    golden::assert_golden(
+        "quote",
+        quote.clone(),
+        &[GAS_DEPENDENT, QUOTE_DEPENDENT].concat(),
+        &addresses,
+    );
+
+    let quote: OrderQuoteResponse = serde_json::from_value(quote).unwrap();
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_quote_id(quote.id.unwrap())
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(to_wei(1))
+        .with_buy_token(token.address())
+        .with_buy_amount(quote.quote.buy_amount)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
+    let uid = services.create_order(&order).await.unwrap();
+    golden::assert_golden(
+        "order",
+        get(&services, &format!("{ORDERS_ENDPOINT}/{uid}")).await,
+        GAS_DEPENDENT,
+        &addresses,
+    );
+
+    let order_in_auction = || async { services.get_auction().await.auction.orders.len() == 1 };
+    wait_for_condition(TIMEOUT, order_in_auction).await.unwrap();
+    // The quote of the order in the auction is redacted as a whole, its fee
+    // dependent amounts are covered by the quote above.
+    golden::assert_golden(
+        "auction",
+        get(&services, AUCTION_ENDPOINT).await,
+        &[GAS_DEPENDENT, &["quote"]].concat(),
+        &addresses,
+    );
+
+    onchain.mint_block().await;
+    let trade_happened =
+        || async { token.balance_of(trader.address()).call().await.unwrap() != U256::zero() };
+    wait_for_condition(TIMEOUT, trade_happened).await.unwrap();
+    let competition_indexed = || async {
+        onchain.mint_block().await;
+        services.get_latest_solver_competition().await.is_ok()
+    };
+    wait_for_condition(TIMEOUT, competition_indexed)
+        .await
+        .unwrap();
+    golden::assert_golden(
+        "solver_competition",
+        get(&services, &format!("{SOLVER_COMPETITION_ENDPOINT}/latest")).await,
+        &[GAS_DEPENDENT, SETTLEMENT_DEPENDENT].concat(),
+        &addresses,
+    );
+}
@@ -3,15 +3,12 @@ use {
     e2e::{setup::*, tx},
     ethcontract::prelude::U256,
     model::{
-        order::{OrderCreation, OrderCreationAppData, OrderKind},
+        order::{OrderCreationAppData, OrderKind},
         quote::{OrderQuoteRequest, OrderQuoteSide, SellAmount},
-        signature::EcdsaSigningScheme,
     },
     reqwest::StatusCode,
-    secp256k1::SecretKey,
     shared::ethrpc::Web3,
     std::str::FromStr,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -37,21 +34,17 @@ async fn app_data(web3: Web3) {
 
     let mut valid_to: u32 = model::time::now_in_epoch_seconds() + 300;
     let mut create_order = |app_data| {
-        let order = OrderCreation {
-            app_data,
-            sell_token: token_a.address(),
-            sell_amount: to_wei(2),
-            buy_token: token_b.address(),
-            buy_amount: to_wei(1),
-            valid_to,
-            kind: OrderKind::Sell,
-            ..Default::default()
-        }
-        .sign(
-            EcdsaSigningScheme::Eip712,
-            &onchain.contracts().domain_separator,
-            SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-        );
+        let order = trader
+            .order(&onchain.contracts().domain_separator)
+            .with_creation_app_data(app_data)
+            .with_sell_token(token_a.address())
+            .with_sell_amount(to_wei(2))
+            .with_buy_token(token_b.address())
+            .with_buy_amount(to_wei(1))
+            .with_valid_to(valid_to)
+            .with_kind(OrderKind::Sell)
+            .build_creation()
+            .unwrap();
         // Adjust valid to make sure we get unique UIDs.
         valid_to += 1;
         order
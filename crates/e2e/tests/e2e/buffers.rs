@@ -80,20 +80,16 @@ async fn onchain_settlement_without_liquidity(web3: Web3) {
         .await;
 
     // Place Order
-    let order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(9),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Buy,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(9))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Buy)
+        .build_creation()
+        .unwrap();
     services.create_order(&order).await.unwrap();
 
     tracing::info!("waiting for first trade");
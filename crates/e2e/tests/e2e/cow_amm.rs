@@ -10,11 +10,10 @@ use {
     },
     ethcontract::{web3::ethabi::Token, BlockId, BlockNumber, H160, U256},
     model::{
-        order::{OrderClass, OrderCreation, OrderData, OrderKind, OrderUid},
+        order::{OrderClass, OrderData, OrderKind, OrderUid},
         quote::{OrderQuoteRequest, OrderQuoteSide, SellAmount},
-        signature::{hashed_eip712_message, EcdsaSigningScheme},
+        signature::hashed_eip712_message,
     },
-    secp256k1::SecretKey,
     shared::{addr, ethrpc::Web3},
     solvers_dto::solution::{
         BuyTokenBalance,
@@ -25,7 +24,6 @@ use {
         Solution,
     },
     std::collections::{HashMap, HashSet},
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -282,20 +280,16 @@ async fn cow_amm_jit(web3: Web3) {
     );
 
     // place user order with the same limit price as the CoW AMM order
-    let user_order = OrderCreation {
-        sell_token: onchain.contracts().weth.address(),
-        sell_amount: U256::exp10(17), // 0.1 WETH
-        buy_token: dai.address(),
-        buy_amount: to_wei(230), // 230 DAI
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(bob.private_key()).unwrap()),
-    );
+    let user_order = bob
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(U256::exp10(17)) // 0.1 WETH
+        .with_buy_token(dai.address())
+        .with_buy_amount(to_wei(230)) // 230 DAI
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let user_order_id = services.create_order(&user_order).await.unwrap();
 
     let amm_balance_before = dai.balance_of(cow_amm.address()).call().await.unwrap();
@@ -500,20 +494,16 @@ async fn cow_amm_driver_support(web3: Web3) {
     onchain.mint_block().await;
 
     // Place Orders
-    let order = OrderCreation {
-        sell_token: usdc.address(),
-        sell_amount: to_wei_with_exp(1000, 6),
-        buy_token: usdt.address(),
-        buy_amount: to_wei_with_exp(2000, 6),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(usdc.address())
+        .with_sell_amount(to_wei_with_exp(1000, 6))
+        .with_buy_token(usdt.address())
+        .with_buy_amount(to_wei_with_exp(2000, 6))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
 
     // Warm up co-located driver by quoting the order (otherwise placing an order
     // may time out)
@@ -939,20 +929,16 @@ async fn cow_amm_opposite_direction(web3: Web3) {
     assert_eq!(quote_response.quote.buy_amount, U256::exp10(17));
 
     // Place user order where bob sells DAI to buy WETH (opposite direction)
-    let user_order = OrderCreation {
-        sell_token: dai.address(),
-        sell_amount: executed_amount, // 230 DAI
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: U256::from(90000000000000000u64), // 0.09 WETH to generate some surplus
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(bob.private_key()).unwrap()),
-    );
+    let user_order = bob
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(dai.address())
+        .with_sell_amount(executed_amount) // 230 DAI
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(U256::from(90000000000000000u64)) // 0.09 WETH to generate some surplus
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let user_order_id = services.create_order(&user_order).await.unwrap();
 
     // Configure the mocked `/solve` solver's solution
@@ -0,0 +1,124 @@
+use {
+    e2e::{setup::*, tx},
+    ethcontract::prelude::U256,
+    model::{
+        order::OrderKind,
+        quote::{OrderQuoteRequest, OrderQuoteSide, SellAmount},
+    },
+    number::nonzero::U256 as NonZeroU256,
+    reqwest::StatusCode,
+    shared::ethrpc::Web3,
+};
+
+#[tokio::test]
+#[ignore]
+async fn local_node_driver_auth() {
+    run_test(driver_auth).await;
+}
+
+/// Tests that the autopilot and the orderbook authenticate with drivers that
+/// require it.
+async fn driver_auth(web3: Web3) {
+    let mut onchain = OnchainComponents::deploy(web3).await;
+
+    let [solver] = onchain.make_solvers(to_wei(1)).await;
+    let [trader] = onchain.make_accounts(to_wei(1)).await;
+    let [token] = onchain
+        .deploy_tokens_with_weth_uni_v2_pools(to_wei(1_000), to_wei(1_000))
+        .await;
+
+    token.mint(trader.address(), to_wei(10)).await;
+    tx!(
+        trader.account(),
+        token.approve(onchain.contracts().allowance, to_wei(10))
+    );
+
+    colocation::start_driver_with_api(
+        onchain.contracts(),
+        vec![
+            colocation::start_baseline_solver(
+                "test_solver".into(),
+                solver,
+                onchain.contracts().weth.address(),
+                vec![],
+                1,
+                true,
+            )
+            .await,
+        ],
+        colocation::LiquidityProvider::UniswapV2,
+        false,
+        r#"
+[api.auth]
+kind = "bearer"
+token = "driver-token"
+"#,
+    );
+
+    tracing::info!("Waiting for driver to reject unauthenticated requests");
+    let rejects_unauthenticated = || async {
+        reqwest::get("http://localhost:11088/test_solver/")
+            .await
+            .is_ok_and(|response| response.status() == StatusCode::UNAUTHORIZED)
+    };
+    wait_for_condition(TIMEOUT, rejects_unauthenticated)
+        .await
+        .unwrap();
+
+    let services = Services::new(&onchain).await;
+    let credentials =
+        "--driver-credentials=test_solver|driver-token,test_quoter|driver-token".to_string();
+    services
+        .start_autopilot(
+            None,
+            vec![
+                "--drivers=test_solver|http://localhost:11088/test_solver".to_string(),
+                "--price-estimation-drivers=test_quoter|http://localhost:11088/test_solver"
+                    .to_string(),
+                credentials.clone(),
+            ],
+        )
+        .await;
+    services
+        .start_api(vec![
+            "--price-estimation-drivers=test_quoter|http://localhost:11088/test_solver".to_string(),
+            credentials,
+        ])
+        .await;
+
+    tracing::info!("Quoting order");
+    let quote = services
+        .submit_quote(&OrderQuoteRequest {
+            from: trader.address(),
+            sell_token: token.address(),
+            buy_token: onchain.contracts().weth.address(),
+            side: OrderQuoteSide::Sell {
+                sell_amount: SellAmount::BeforeFee {
+                    value: NonZeroU256::try_from(to_wei(10)).unwrap(),
+                },
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(!quote.quote.buy_amount.is_zero());
+
+    tracing::info!("Placing order");
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
+    services.create_order(&order).await.unwrap();
+    onchain.mint_block().await;
+
+    tracing::info!("Waiting for trade");
+    let trade_happened =
+        || async { token.balance_of(trader.address()).call().await.unwrap() == U256::zero() };
+    wait_for_condition(TIMEOUT, trade_happened).await.unwrap();
+}
@@ -10,14 +10,12 @@ use {
     model::{
         order::{OrderCreation, OrderCreationAppData, OrderKind},
         quote::{OrderQuoteRequest, OrderQuoteSide, SellAmount},
-        signature::{hashed_eip712_message, EcdsaSigningScheme, Signature},
+        signature::{hashed_eip712_message, Signature},
     },
     number::nonzero::U256 as NonZeroU256,
     reqwest::StatusCode,
-    secp256k1::SecretKey,
     serde_json::json,
     shared::ethrpc::Web3,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -69,14 +67,15 @@ async fn gas_limit(web3: Web3) {
     let services = Services::new(&onchain).await;
     services.start_protocol(solver).await;
 
-    let order = OrderCreation {
-        sell_token: cow.address(),
-        sell_amount: to_wei(4),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(3),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        app_data: OrderCreationAppData::Full {
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(cow.address())
+        .with_sell_amount(to_wei(4))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(3))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .with_creation_app_data(OrderCreationAppData::Full {
             full: json!({
                 "metadata": {
                     "hooks": {
@@ -90,14 +89,9 @@ async fn gas_limit(web3: Web3) {
                 },
             })
             .to_string(),
-        },
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+        })
+        .build_creation()
+        .unwrap();
     let error = services.create_order(&order).await.unwrap_err();
     assert_eq!(error.0, StatusCode::BAD_REQUEST);
     assert!(error.1.contains("TooMuchGas"));
@@ -140,14 +134,15 @@ async fn allowance(web3: Web3) {
     let services = Services::new(&onchain).await;
     services.start_protocol(solver).await;
 
-    let order = OrderCreation {
-        sell_token: cow.address(),
-        sell_amount: to_wei(5),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(3),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        app_data: OrderCreationAppData::Full {
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(cow.address())
+        .with_sell_amount(to_wei(5))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(3))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .with_creation_app_data(OrderCreationAppData::Full {
             full: json!({
                 "metadata": {
                     "hooks": {
@@ -157,14 +152,9 @@ async fn allowance(web3: Web3) {
                 },
             })
             .to_string(),
-        },
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+        })
+        .build_creation()
+        .unwrap();
     services.create_order(&order).await.unwrap();
     onchain.mint_block().await;
 
@@ -415,15 +405,16 @@ async fn partial_fills(web3: Web3) {
     services.start_protocol(solver).await;
 
     tracing::info!("Placing order");
-    let order = OrderCreation {
-        sell_token: onchain.contracts().weth.address(),
-        sell_amount: to_wei(2),
-        buy_token: token.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        partially_fillable: true,
-        app_data: OrderCreationAppData::Full {
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(to_wei(2))
+        .with_buy_token(token.address())
+        .with_buy_amount(to_wei(1))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .with_partially_fillable(true)
+        .with_creation_app_data(OrderCreationAppData::Full {
             full: json!({
                 "metadata": {
                     "hooks": {
@@ -433,14 +424,9 @@ async fn partial_fills(web3: Web3) {
                 },
             })
             .to_string(),
-        },
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+        })
+        .build_creation()
+        .unwrap();
     services.create_order(&order).await.unwrap();
     onchain.mint_block().await;
 
@@ -6,7 +6,7 @@ use {
     },
     ethcontract::prelude::U256,
     model::{
-        order::{OrderClass, OrderCreation, OrderKind},
+        order::{OrderClass, OrderKind},
         signature::EcdsaSigningScheme,
     },
     secp256k1::SecretKey,
@@ -98,20 +98,16 @@ async fn single_limit_order_test(web3: Web3) {
         .await;
 
     // Place order
-    let order = OrderCreation {
-        sell_token: onchain.contracts().weth.address(),
-        sell_amount: to_wei(10),
-        buy_token: token.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(token.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
 
     let trader_balance_before = token.balance_of(trader.address()).call().await.unwrap();
     let solver_balance_before = token.balance_of(solver.address()).call().await.unwrap();
@@ -202,7 +198,7 @@ async fn single_limit_order_test(web3: Web3) {
 
         // jit order can be found on /api/v1/account/{owner}/orders
         let orders_by_owner = services
-            .get_orders_for_owner(&jit_order_uid.parts().1, 0, 10)
+            .get_orders_for_owner(&jit_order_uid.parts().1, None, 10)
             .await
             .ok()?;
         let jit_order_by_owner = orders_by_owner
@@ -214,9 +210,9 @@ async fn single_limit_order_test(web3: Web3) {
     .await
     .unwrap();
 
-    // make sure the offset works
+    // make sure the pagination works
     let orders_by_owner = services
-        .get_orders_for_owner(&jit_order_uid.parts().1, 1, 1)
+        .get_orders_for_owner(&jit_order_uid.parts().1, Some(&jit_order_uid), 1)
         .await
         .unwrap();
     assert!(orders_by_owner.is_empty());
@@ -5,7 +5,7 @@ use {
     ethcontract::{prelude::U256, H160},
     fee::{FeePolicyOrderClass, ProtocolFee, ProtocolFeesConfig},
     model::{
-        order::{OrderClass, OrderCreation, OrderKind},
+        order::{OrderClass, OrderKind},
         quote::{OrderQuoteRequest, OrderQuoteSide, SellAmount},
         signature::EcdsaSigningScheme,
     },
@@ -146,20 +146,15 @@ async fn single_limit_order_test(web3: Web3) {
     let services = Services::new(&onchain).await;
     services.start_protocol(solver).await;
 
-    let order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
+    let order = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(5))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let balance_before = token_b.balance_of(trader_a.address()).call().await.unwrap();
     let order_id = services.create_order(&order).await.unwrap();
     onchain.mint_block().await;
@@ -241,20 +236,15 @@ async fn two_limit_orders_test(web3: Web3) {
     let services = Services::new(&onchain).await;
     services.start_protocol(solver).await;
 
-    let order_a = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
+    let order_a = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(5))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
 
     let balance_before_a = token_b.balance_of(trader_a.address()).call().await.unwrap();
     let balance_before_b = token_a.balance_of(trader_b.address()).call().await.unwrap();
@@ -265,20 +255,20 @@ async fn two_limit_orders_test(web3: Web3) {
     let limit_order = services.get_order(&order_id).await.unwrap();
     assert!(limit_order.metadata.class.is_limit());
 
-    let order_b = OrderCreation {
-        sell_token: token_b.address(),
-        sell_amount: to_wei(5),
-        buy_token: token_a.address(),
-        buy_amount: to_wei(2),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::EthSign,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_b.private_key()).unwrap()),
-    );
+    let order_b = trader_b
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_b.address())
+        .with_sell_amount(to_wei(5))
+        .with_buy_token(token_a.address())
+        .with_buy_amount(to_wei(2))
+        .with_kind(OrderKind::Sell)
+        .sign_with(
+            EcdsaSigningScheme::EthSign,
+            &onchain.contracts().domain_separator,
+            SecretKeyRef::from(&SecretKey::from_slice(trader_b.private_key()).unwrap()),
+        )
+        .build_creation()
+        .unwrap();
     let order_id = services.create_order(&order_b).await.unwrap();
 
     let limit_order = services.get_order(&order_id).await.unwrap();
@@ -369,36 +359,26 @@ async fn two_limit_orders_multiple_winners_test(web3: Web3) {
         .await;
 
     // Place Orders
-    let order_a = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: token_c.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
+    let order_a = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(token_c.address())
+        .with_buy_amount(to_wei(5))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let uid_a = services.create_order(&order_a).await.unwrap();
 
-    let order_b = OrderCreation {
-        sell_token: token_b.address(),
-        sell_amount: to_wei(10),
-        buy_token: token_d.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_b.private_key()).unwrap()),
-    );
+    let order_b = trader_b
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_b.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(token_d.address())
+        .with_buy_amount(to_wei(5))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let uid_b = services.create_order(&order_b).await.unwrap();
 
     // Start autopilot only once all the orders are created.
@@ -491,38 +471,28 @@ async fn too_many_limit_orders_test(web3: Web3) {
         ])
         .await;
 
-    let order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(1),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(1))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(1))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     services.create_order(&order).await.unwrap();
 
     // Attempt to place another order, but the orderbook is configured to allow only
     // one limit order per user.
-    let order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(1),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(2),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(1))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(2))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
 
     let (status, body) = services.create_order(&order).await.unwrap_err();
     assert_eq!(status, 400);
@@ -584,73 +554,53 @@ async fn limit_does_not_apply_to_in_market_orders_test(web3: Web3) {
     let quote = services.submit_quote(&quote_request).await.unwrap();
 
     // Place "in-market" order
-    let order = OrderCreation {
-        sell_token: token.address(),
-        sell_amount: quote.quote.sell_amount,
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: quote.quote.buy_amount.saturating_sub(to_wei(4)),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token.address())
+        .with_sell_amount(quote.quote.sell_amount)
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(quote.quote.buy_amount.saturating_sub(to_wei(4)))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     assert!(services.create_order(&order).await.is_ok());
 
     // Place a "limit" order
-    let order = OrderCreation {
-        sell_token: token.address(),
-        sell_amount: to_wei(1),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(3),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token.address())
+        .with_sell_amount(to_wei(1))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(3))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let order_id = services.create_order(&order).await.unwrap();
     let limit_order = services.get_order(&order_id).await.unwrap();
     assert!(limit_order.metadata.class.is_limit());
 
     // Place another "in-market" order in order to check it is not limited
-    let order = OrderCreation {
-        sell_token: token.address(),
-        sell_amount: quote.quote.sell_amount,
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: quote.quote.buy_amount.saturating_sub(to_wei(2)),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token.address())
+        .with_sell_amount(quote.quote.sell_amount)
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(quote.quote.buy_amount.saturating_sub(to_wei(2)))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     assert!(services.create_order(&order).await.is_ok());
 
     // Place a "limit" order in order to see if fails
-    let order = OrderCreation {
-        sell_token: token.address(),
-        sell_amount: to_wei(1),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(2),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token.address())
+        .with_sell_amount(to_wei(1))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(2))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
 
     let (status, body) = services.create_order(&order).await.unwrap_err();
     assert_eq!(status, 400);
@@ -701,20 +651,15 @@ async fn forked_mainnet_single_limit_order_test(web3: Web3) {
 
     onchain.mint_block().await;
 
-    let order = OrderCreation {
-        sell_token: token_usdc.address(),
-        sell_amount: to_wei_with_exp(1000, 6),
-        buy_token: token_usdt.address(),
-        buy_amount: to_wei_with_exp(500, 6),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_usdc.address())
+        .with_sell_amount(to_wei_with_exp(1000, 6))
+        .with_buy_token(token_usdt.address())
+        .with_buy_amount(to_wei_with_exp(500, 6))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
 
     // Warm up co-located driver by quoting the order (otherwise placing an order
     // may time out)
@@ -810,20 +755,15 @@ async fn forked_gnosis_single_limit_order_test(web3: Web3) {
     let services = Services::new(&onchain).await;
     services.start_protocol(solver).await;
 
-    let order = OrderCreation {
-        sell_token: token_usdc.address(),
-        sell_amount: to_wei_with_exp(1000, 6),
-        buy_token: token_wxdai.address(),
-        buy_amount: to_wei(500),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_usdc.address())
+        .with_sell_amount(to_wei_with_exp(1000, 6))
+        .with_buy_token(token_wxdai.address())
+        .with_buy_amount(to_wei(500))
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let sell_token_balance_before = token_usdc
         .balance_of(trader.address())
         .call()
@@ -910,35 +850,27 @@ async fn no_liquidity_limit_order(web3: Web3) {
         .await;
 
     // Place order
-    let mut order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
-    let order_id = services.create_order(&order).await.unwrap();
+    let order = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(1))
+        .with_kind(OrderKind::Sell);
+    let order_id = services
+        .create_order(&order.clone().build_creation().unwrap())
+        .await
+        .unwrap();
     onchain.mint_block().await;
     let limit_order = services.get_order(&order_id).await.unwrap();
     assert_eq!(limit_order.metadata.class, OrderClass::Limit);
 
     // Cannot place orders with unsupported tokens
-    order.sell_token = unsupported.address();
-    services
-        .create_order(&order.sign(
-            EcdsaSigningScheme::Eip712,
-            &onchain.contracts().domain_separator,
-            SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-        ))
-        .await
-        .unwrap_err();
+    let order = order
+        .with_sell_token(unsupported.address())
+        .build_creation()
+        .unwrap();
+    services.create_order(&order).await.unwrap_err();
 
     let balance_before = onchain
         .contracts()
@@ -29,12 +29,7 @@ use {
     },
     ethrpc::Web3,
     hex_literal::hex,
-    model::{
-        order::{OrderCreation, OrderKind},
-        signature::EcdsaSigningScheme,
-    },
-    secp256k1::SecretKey,
-    web3::signing::SecretKeyRef,
+    model::order::{OrderCreation, OrderKind},
 };
 
 /// The block number from which we will fetch state for the forked tests.
@@ -106,20 +101,16 @@ async fn zero_ex_liquidity(web3: Web3) {
         token_usdc.approve(zeroex.address(), amount)
     );
 
-    let order = OrderCreation {
-        sell_token: token_usdc.address(),
-        sell_amount: amount,
-        buy_token: token_usdt.address(),
-        buy_amount: amount,
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_usdc.address())
+        .with_sell_amount(amount)
+        .with_buy_token(token_usdt.address())
+        .with_buy_amount(amount)
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
 
     let chain_id = web3.eth().chain_id().await.unwrap().as_u64();
     let zeroex_liquidity_orders = create_zeroex_liquidity_orders(
@@ -4,12 +4,14 @@
 // tests we want to run.
 
 // Each of the following modules contains tests.
+mod api_golden;
 mod app_data;
 mod app_data_signer;
 mod banned_users;
 mod buffers;
 mod cow_amm;
 mod database;
+mod driver_auth;
 mod eth_integration;
 mod eth_safe;
 mod ethflow;
@@ -7,7 +7,6 @@ use {
             CancellationPayload,
             OrderCancellation,
             OrderCancellations,
-            OrderCreation,
             OrderCreationAppData,
             OrderStatus,
             OrderUid,
@@ -102,22 +101,18 @@ async fn order_cancellation(web3: Web3) {
         async move {
             let quote = services.submit_quote(&request).await.unwrap().quote;
 
-            let order = OrderCreation {
-                kind: quote.kind,
-                sell_token: quote.sell_token,
-                sell_amount: quote.sell_amount,
-                fee_amount: 0.into(),
-                buy_token: quote.buy_token,
-                buy_amount: (quote.buy_amount * 99) / 100,
-                valid_to: quote.valid_to,
-                app_data: quote.app_data,
-                ..Default::default()
-            }
-            .sign(
-                EcdsaSigningScheme::Eip712,
-                &onchain.contracts().domain_separator,
-                SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-            );
+            let order = trader
+                .order(&onchain.contracts().domain_separator)
+                .with_kind(quote.kind)
+                .with_sell_token(quote.sell_token)
+                .with_sell_amount(quote.sell_amount)
+                .with_fee_amount(0.into())
+                .with_buy_token(quote.buy_token)
+                .with_buy_amount((quote.buy_amount * 99) / 100)
+                .with_valid_to(quote.valid_to)
+                .with_creation_app_data(quote.app_data)
+                .build_creation()
+                .unwrap();
             services.create_order(&order).await.unwrap()
         }
     };
@@ -1,13 +1,8 @@
 use {
     e2e::{setup::*, tx},
     ethcontract::prelude::U256,
-    model::{
-        order::{OrderCreation, OrderKind},
-        signature::EcdsaSigningScheme,
-    },
-    secp256k1::SecretKey,
+    model::order::OrderKind,
     shared::ethrpc::Web3,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -73,21 +68,17 @@ async fn test(web3: Web3) {
     services.start_protocol(solver).await;
     onchain.mint_block().await;
 
-    let order_a = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(500),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(390),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        partially_fillable: true,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
+    let order_a = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(500))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(390))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .with_partially_fillable(true)
+        .build_creation()
+        .unwrap();
     let uid = services.create_order(&order_a).await.unwrap();
     let order = services.get_order(&uid).await.unwrap();
     assert!(order.is_limit_order());
@@ -3,14 +3,11 @@ use {
     e2e::{nodes::local_node::TestNodeApi, setup::*, tx, tx_value},
     ethcontract::U256,
     model::{
-        order::{OrderCreation, OrderKind},
+        order::OrderKind,
         quote::{OrderQuoteRequest, OrderQuoteSide, SellAmount},
-        signature::EcdsaSigningScheme,
     },
-    secp256k1::SecretKey,
     shared::ethrpc::Web3,
     std::ops::DerefMut,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -76,21 +73,17 @@ async fn place_order_with_quote(web3: Web3) {
     tracing::info!("Placing order");
     let balance = token.balance_of(trader.address()).call().await.unwrap();
     assert_eq!(balance, 0.into());
-    let order = OrderCreation {
-        quote_id: quote_response.id,
-        sell_token: onchain.contracts().weth.address(),
-        sell_amount: quote_sell_amount,
-        buy_token: token.address(),
-        buy_amount: quote_response.quote.buy_amount,
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_quote_id(quote_response.id.unwrap())
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(quote_sell_amount)
+        .with_buy_token(token.address())
+        .with_buy_amount(quote_response.quote.buy_amount)
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let order_uid = services.create_order(&order).await.unwrap();
 
     tracing::info!("Order quote verification");
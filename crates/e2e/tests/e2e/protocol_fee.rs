@@ -8,7 +8,7 @@ use {
     },
     ethcontract::{prelude::U256, Address},
     model::{
-        order::{Order, OrderCreation, OrderCreationAppData, OrderKind},
+        order::{Order, OrderBuilder, OrderCreationAppData, OrderKind},
         quote::{
             OrderQuote,
             OrderQuoteRequest,
@@ -158,40 +158,40 @@ async fn combined_protocol_fees(web3: Web3) {
         .try_into()
         .expect("Expected exactly four elements");
 
-    let market_price_improvement_order = OrderCreation {
-        sell_amount,
+    let market_price_improvement_order = sell_order_from_quote(&market_quote_before)
+        .with_sell_amount(sell_amount)
         // to make sure the order is in-market
-        buy_amount: market_quote_before.quote.buy_amount * 2 / 3,
-        ..sell_order_from_quote(&market_quote_before)
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
-    let limit_surplus_order = OrderCreation {
-        sell_amount,
+        .with_buy_amount(market_quote_before.quote.buy_amount * 2 / 3)
+        .sign_with(
+            EcdsaSigningScheme::Eip712,
+            &onchain.contracts().domain_separator,
+            SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
+        )
+        .build_creation()
+        .unwrap();
+    let limit_surplus_order = sell_order_from_quote(&limit_quote_before)
+        .with_sell_amount(sell_amount)
         // to make sure the order is out-of-market
-        buy_amount: limit_quote_before.quote.buy_amount * 3 / 2,
-        ..sell_order_from_quote(&limit_quote_before)
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
-    let partner_fee_order = OrderCreation {
-        sell_amount,
+        .with_buy_amount(limit_quote_before.quote.buy_amount * 3 / 2)
+        .sign_with(
+            EcdsaSigningScheme::Eip712,
+            &onchain.contracts().domain_separator,
+            SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
+        )
+        .build_creation()
+        .unwrap();
+    let partner_fee_order = sell_order_from_quote(&partner_fee_quote)
+        .with_sell_amount(sell_amount)
         // to make sure the order is out-of-market
-        buy_amount: partner_fee_quote.quote.buy_amount * 3 / 2,
-        app_data: partner_fee_app_data.clone(),
-        ..sell_order_from_quote(&partner_fee_quote)
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+        .with_buy_amount(partner_fee_quote.quote.buy_amount * 3 / 2)
+        .with_creation_app_data(partner_fee_app_data.clone())
+        .sign_with(
+            EcdsaSigningScheme::Eip712,
+            &onchain.contracts().domain_separator,
+            SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
+        )
+        .build_creation()
+        .unwrap();
 
     tracing::info!("Rebalancing AMM pools for market & limit order.");
     onchain
@@ -384,16 +384,17 @@ fn fee_in_buy_token(order: &Order, quote: &OrderQuote) -> U256 {
     order.metadata.executed_fee * quote.buy_amount / quote.sell_amount
 }
 
-fn sell_order_from_quote(quote: &OrderQuoteResponse) -> OrderCreation {
-    OrderCreation {
-        sell_token: quote.quote.sell_token,
-        sell_amount: quote.quote.sell_amount,
-        buy_token: quote.quote.buy_token,
-        buy_amount: quote.quote.buy_amount,
-        valid_to: quote.quote.valid_to,
-        kind: OrderKind::Sell,
-        quote_id: quote.id,
-        ..Default::default()
+fn sell_order_from_quote(quote: &OrderQuoteResponse) -> OrderBuilder {
+    let order = OrderBuilder::default()
+        .with_sell_token(quote.quote.sell_token)
+        .with_sell_amount(quote.quote.sell_amount)
+        .with_buy_token(quote.quote.buy_token)
+        .with_buy_amount(quote.quote.buy_amount)
+        .with_valid_to(quote.quote.valid_to)
+        .with_kind(OrderKind::Sell);
+    match quote.id {
+        Some(id) => order.with_quote_id(id),
+        None => order,
     }
 }
 
@@ -489,20 +490,16 @@ async fn volume_fee_buy_order_test(web3: Web3) {
     .unwrap()
     .quote;
 
-    let order = OrderCreation {
-        sell_token: token_gno.address(),
-        sell_amount: quote.sell_amount * 3 / 2,
-        buy_token: token_dai.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Buy,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_gno.address())
+        .with_sell_amount(quote.sell_amount * 3 / 2)
+        .with_buy_token(token_dai.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Buy)
+        .build_creation()
+        .unwrap();
     let uid = services.create_order(&order).await.unwrap();
 
     // Drive solution
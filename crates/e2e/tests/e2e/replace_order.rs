@@ -1,14 +1,9 @@
 use {
     e2e::{setup::*, tx},
     ethcontract::prelude::U256,
-    model::{
-        order::{OrderCreation, OrderCreationAppData, OrderKind, OrderStatus},
-        signature::EcdsaSigningScheme,
-    },
+    model::order::{OrderCreationAppData, OrderKind, OrderStatus},
     reqwest::StatusCode,
-    secp256k1::SecretKey,
     shared::ethrpc::Web3,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -90,45 +85,37 @@ async fn try_replace_someone_else_order_test(web3: Web3) {
 
     onchain.mint_block().await;
 
-    let order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        partially_fillable: false,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
+    let order = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_partially_fillable(false)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let order_id = services.create_order(&order).await.unwrap();
 
     // Replace order
-    let new_order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(3),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        partially_fillable: false,
-        app_data: OrderCreationAppData::Full {
+    let new_order = trader_b
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(3))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(1))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .with_partially_fillable(false)
+        .with_creation_app_data(OrderCreationAppData::Full {
             full: format!(
                 r#"{{"version":"1.1.0","metadata":{{"replacedOrder":{{"uid":"{}"}}}}}}"#,
                 order_id
             ),
-        },
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_b.private_key()).unwrap()),
-    );
+        })
+        .build_creation()
+        .unwrap();
     let balance_before = token_a.balance_of(trader_a.address()).call().await.unwrap();
     let response = services.create_order(&new_order).await;
     let (error_code, _) = response.err().unwrap();
@@ -204,20 +191,16 @@ async fn single_replace_order_test(web3: Web3) {
     let services = Services::new(&onchain).await;
     services.start_protocol(solver).await;
 
-    let order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     onchain.mint_block().await;
     let order_id = services.create_order(&order).await.unwrap();
 
@@ -235,24 +218,20 @@ async fn single_replace_order_test(web3: Web3) {
     );
 
     // Replace order
-    let new_order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(3),
-        buy_token: token_b.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        partially_fillable: false,
-        app_data: OrderCreationAppData::Full {
+    let new_order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(3))
+        .with_buy_token(token_b.address())
+        .with_buy_amount(to_wei(1))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .with_partially_fillable(false)
+        .with_creation_app_data(OrderCreationAppData::Full {
             full: app_data.clone(),
-        },
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+        })
+        .build_creation()
+        .unwrap();
     let balance_before = token_a.balance_of(trader.address()).call().await.unwrap();
     let new_order_uid = services.create_order(&new_order).await.unwrap();
 
@@ -1,13 +1,8 @@
 use {
     e2e::{setup::*, tx},
     ethcontract::prelude::U256,
-    model::{
-        order::{OrderCreation, OrderKind},
-        signature::EcdsaSigningScheme,
-    },
-    secp256k1::SecretKey,
+    model::order::OrderKind,
     shared::ethrpc::Web3,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -82,20 +77,16 @@ async fn solver_competition(web3: Web3) {
     ]).await;
 
     // Place Order
-    let order = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let uid = services.create_order(&order).await.unwrap();
     onchain.mint_block().await;
 
@@ -213,38 +204,30 @@ async fn fairness_check(web3: Web3) {
         .await;
 
     // Place Orders
-    let order_a = OrderCreation {
-        sell_token: token_a.address(),
-        sell_amount: to_wei(10),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
+    let order_a = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_a.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     let uid_a = services.create_order(&order_a).await.unwrap();
 
     onchain.mint_block().await;
 
-    let order_b = OrderCreation {
-        sell_token: token_b.address(),
-        sell_amount: to_wei(10),
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(5),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_b.private_key()).unwrap()),
-    );
+    let order_b = trader_b
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(token_b.address())
+        .with_sell_amount(to_wei(10))
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(5))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .build_creation()
+        .unwrap();
     services.create_order(&order_b).await.unwrap();
 
     // Wait for trade
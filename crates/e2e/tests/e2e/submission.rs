@@ -2,14 +2,9 @@ use {
     e2e::{nodes::local_node::TestNodeApi, setup::*, tx, tx_value},
     ethcontract::{BlockId, H160, H256, U256},
     futures::{Stream, StreamExt},
-    model::{
-        order::{OrderCreation, OrderKind},
-        signature::EcdsaSigningScheme,
-    },
-    secp256k1::SecretKey,
+    model::order::OrderKind,
     shared::ethrpc::Web3,
     std::time::Duration,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -54,20 +49,16 @@ async fn test_cancel_on_expiry(web3: Web3) {
     tracing::info!("Placing order");
     let balance = token.balance_of(trader.address()).call().await.unwrap();
     assert_eq!(balance, 0.into());
-    let order = OrderCreation {
-        sell_token: onchain.contracts().weth.address(),
-        sell_amount: to_wei(2),
-        buy_token: token.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Buy,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(to_wei(2))
+        .with_buy_token(token.address())
+        .with_buy_amount(to_wei(1))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Buy)
+        .build_creation()
+        .unwrap();
     services.create_order(&order).await.unwrap();
     onchain.mint_block().await;
 
@@ -2,13 +2,8 @@ use {
     database::order_events::{OrderEvent, OrderEventLabel},
     e2e::{setup::*, tx, tx_value},
     ethcontract::U256,
-    model::{
-        order::{OrderCreation, OrderKind},
-        signature::EcdsaSigningScheme,
-    },
-    secp256k1::SecretKey,
+    model::order::OrderKind,
     shared::ethrpc::Web3,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -57,34 +52,26 @@ async fn test(web3: Web3) {
     services.start_protocol(solver).await;
 
     tracing::info!("Placing order");
-    let order_a = OrderCreation {
-        sell_token: onchain.contracts().weth.address(),
-        sell_amount: to_wei(2),
-        buy_token: token.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Buy,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_a.private_key()).unwrap()),
-    );
-    let order_b = OrderCreation {
-        sell_token: onchain.contracts().weth.address(),
-        sell_amount: to_wei(2),
-        buy_token: token.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Buy,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader_b.private_key()).unwrap()),
-    );
+    let order_a = trader_a
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(to_wei(2))
+        .with_buy_token(token.address())
+        .with_buy_amount(to_wei(1))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Buy)
+        .build_creation()
+        .unwrap();
+    let order_b = trader_b
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(onchain.contracts().weth.address())
+        .with_sell_amount(to_wei(2))
+        .with_buy_token(token.address())
+        .with_buy_amount(to_wei(1))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Buy)
+        .build_creation()
+        .unwrap();
     let uid_a = services.create_order(&order_a).await.unwrap();
     let uid_b = services.create_order(&order_b).await.unwrap();
 
@@ -1,13 +1,8 @@
 use {
     e2e::{setup::*, tx, tx_value},
     ethcontract::U256,
-    model::{
-        order::{OrderCreation, OrderKind},
-        signature::EcdsaSigningScheme,
-    },
-    secp256k1::SecretKey,
+    model::order::OrderKind,
     shared::ethrpc::Web3,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -40,22 +35,18 @@ async fn test(web3: Web3) {
     services.start_protocol(solver).await;
 
     tracing::info!("Placing order with 0 sell tokens");
-    let order = OrderCreation {
-        sell_token: weth.address(),
-        sell_amount: to_wei(2),
-        fee_amount: 0.into(),
-        buy_token: token.address(),
-        buy_amount: to_wei(1),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        kind: OrderKind::Sell,
-        partially_fillable: false,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_sell_token(weth.address())
+        .with_sell_amount(to_wei(2))
+        .with_fee_amount(0.into())
+        .with_buy_token(token.address())
+        .with_buy_amount(to_wei(1))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .with_kind(OrderKind::Sell)
+        .with_partially_fillable(false)
+        .build_creation()
+        .unwrap();
     // This order can't be created because we require the trader
     // to have at least 1 wei of sell tokens.
     services.create_order(&order).await.unwrap_err();
@@ -1,13 +1,8 @@
 use {
     e2e::{setup::*, tx},
     ethcontract::prelude::U256,
-    model::{
-        order::{OrderCreation, OrderKind, SellTokenSource},
-        signature::EcdsaSigningScheme,
-    },
-    secp256k1::SecretKey,
+    model::order::{OrderKind, SellTokenSource},
     shared::ethrpc::Web3,
-    web3::signing::SecretKeyRef,
 };
 
 #[tokio::test]
@@ -45,21 +40,17 @@ async fn vault_balances(web3: Web3) {
     services.start_protocol(solver).await;
 
     // Place Orders
-    let order = OrderCreation {
-        kind: OrderKind::Sell,
-        sell_token: token.address(),
-        sell_amount: to_wei(10),
-        sell_token_balance: SellTokenSource::External,
-        buy_token: onchain.contracts().weth.address(),
-        buy_amount: to_wei(8),
-        valid_to: model::time::now_in_epoch_seconds() + 300,
-        ..Default::default()
-    }
-    .sign(
-        EcdsaSigningScheme::Eip712,
-        &onchain.contracts().domain_separator,
-        SecretKeyRef::from(&SecretKey::from_slice(trader.private_key()).unwrap()),
-    );
+    let order = trader
+        .order(&onchain.contracts().domain_separator)
+        .with_kind(OrderKind::Sell)
+        .with_sell_token(token.address())
+        .with_sell_amount(to_wei(10))
+        .with_sell_token_balance(SellTokenSource::External)
+        .with_buy_token(onchain.contracts().weth.address())
+        .with_buy_amount(to_wei(8))
+        .with_valid_to(model::time::now_in_epoch_seconds() + 300)
+        .build_creation()
+        .unwrap();
     services.create_order(&order).await.unwrap();
     onchain.mint_block().await;
     let balance_before = onchain
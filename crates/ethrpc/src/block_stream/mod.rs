@@ -167,7 +167,9 @@ pub async fn current_block_stream(
         }
     };
 
-    tokio::task::spawn(update_future.instrument(tracing::info_span!("current_block_stream")));
+    observe::metrics::spawn_in_current_scope(
+        update_future.instrument(tracing::info_span!("current_block_stream")),
+    );
     Ok(receiver)
 }
 
@@ -204,7 +206,7 @@ pub fn throttle(blocks: CurrentBlockWatcher, updates_to_skip: NonZeroU64) -> Cur
             }
         }
     };
-    tokio::task::spawn(
+    observe::metrics::spawn_in_current_scope(
         update_future.instrument(tracing::info_span!("current_block_stream_throttled")),
     );
     receiver
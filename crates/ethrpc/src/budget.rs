@@ -0,0 +1,348 @@
+//! Daily RPC usage accounting and budgets.
+//!
+//! Every request sent through an [`InstrumentedTransport`] gets accounted to
+//! the component it was sent for (the label of the transport). Usage is
+//! aggregated per UTC day, so it can be compared with what node providers
+//! bill. If a daily budget is configured, requests of background components
+//! get delayed once the usage gets close to the budget, so latency sensitive
+//! components keep their share of it.
+//!
+//! Services with a database persist the usage (see [`take_unsynced`]) so that
+//! the budget survives restarts and gets shared by all services using it.
+//!
+//! [`InstrumentedTransport`]: crate::instrumented::InstrumentedTransport
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+        OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Daily budget of RPC requests.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Budget {
+    /// Number of requests all components together may send per UTC day.
+    pub daily_requests: u64,
+    /// Share of the daily budget after which background components get
+    /// throttled.
+    pub throttle_threshold: f64,
+    /// Components whose requests can wait when the budget runs low. A
+    /// component matches if its label is the name or ends with `_<name>`,
+    /// like the labels added with
+    /// [`instrument_with_label`](crate::instrumented::instrument_with_label).
+    pub background_components: Vec<String>,
+    /// How long requests of background components get delayed when they are
+    /// throttled.
+    pub throttle_delay: Duration,
+}
+
+impl Budget {
+    fn is_background(&self, component: &str) -> bool {
+        self.background_components.iter().any(|name| {
+            component == name
+                || component
+                    .strip_suffix(name.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('_'))
+        })
+    }
+}
+
+static BUDGET: OnceLock<Budget> = OnceLock::new();
+
+/// Configures the daily budget. Without a budget, usage still gets accounted
+/// but no requests get throttled.
+///
+/// Should be called once at startup. Later calls are ignored.
+pub fn configure(budget: Budget) {
+    tracing::info!(?budget, "configured daily RPC budget");
+    if BUDGET.set(budget).is_err() {
+        tracing::warn!("RPC budget was already configured");
+    }
+}
+
+/// Requests sent during the current UTC day. Every request updates it, so it
+/// only uses atomics; the mutex is only taken when a new day starts or the
+/// usage gets synced.
+#[derive(Debug)]
+struct Usage {
+    /// Days since the unix epoch.
+    day: AtomicU64,
+    /// Requests sent on `day` by all processes as of the last sync.
+    synced: AtomicU64,
+    /// Requests sent on `day` by this process since the last sync.
+    pending: AtomicU64,
+    /// Requests of the previous day that have not been synced yet.
+    carry_over: Mutex<Option<(u64, u64)>>,
+}
+
+impl Usage {
+    const fn new() -> Self {
+        Self {
+            day: AtomicU64::new(0),
+            synced: AtomicU64::new(0),
+            pending: AtomicU64::new(0),
+            carry_over: Mutex::new(None),
+        }
+    }
+
+    /// Starts accounting requests for `today`. Returns whether a new day
+    /// started.
+    fn roll_over(&self, today: u64) -> bool {
+        let day = self.day.load(Ordering::Relaxed);
+        if day == today
+            || self
+                .day
+                .compare_exchange(day, today, Ordering::AcqRel, Ordering::Relaxed)
+                .is_err()
+        {
+            return false;
+        }
+        self.synced.store(0, Ordering::Relaxed);
+        let pending = self.pending.swap(0, Ordering::AcqRel);
+        if pending > 0 {
+            *self.carry_over.lock().unwrap() = Some((day, pending));
+        }
+        true
+    }
+
+    /// Accounts the requests and returns whether a new day started since the
+    /// last requests were accounted.
+    fn record(&self, today: u64, requests: u64) -> bool {
+        let new_day = self.roll_over(today);
+        self.pending.fetch_add(requests, Ordering::Relaxed);
+        new_day
+    }
+
+    fn share_of(&self, budget: &Budget) -> f64 {
+        if budget.daily_requests == 0 {
+            return 1.;
+        }
+        let total = self.synced.load(Ordering::Relaxed) + self.pending.load(Ordering::Relaxed);
+        total as f64 / budget.daily_requests as f64
+    }
+
+    fn is_throttled(&self, budget: &Budget, component: &str, today: u64) -> bool {
+        budget.is_background(component)
+            && self.day.load(Ordering::Relaxed) == today
+            && self.share_of(budget) >= budget.throttle_threshold
+    }
+
+    fn take_unsynced(&self, today: u64) -> Unsynced {
+        self.roll_over(today);
+        Unsynced {
+            day: self.day.load(Ordering::Acquire),
+            requests: self.pending.swap(0, Ordering::AcqRel),
+            carry_over: self.carry_over.lock().unwrap().take(),
+        }
+    }
+
+    fn synced(&self, unsynced: Unsynced, total: Option<u64>) {
+        let same_day = self.day.load(Ordering::Acquire) == unsynced.day;
+        match total {
+            Some(total) if same_day => self.synced.store(total, Ordering::Relaxed),
+            Some(_) => (),
+            // Retry persisting the requests with the next sync.
+            None => {
+                let mut carry_over = self.carry_over.lock().unwrap();
+                if same_day {
+                    self.pending.fetch_add(unsynced.requests, Ordering::Relaxed);
+                } else if unsynced.requests > 0 {
+                    *carry_over = Some((unsynced.day, unsynced.requests));
+                }
+                if let Some(unsynced) = unsynced.carry_over {
+                    carry_over.get_or_insert(unsynced);
+                }
+            }
+        }
+    }
+}
+
+static USAGE: Usage = Usage::new();
+
+fn today() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    now.as_secs() / (24 * 60 * 60)
+}
+
+/// Accounts `requests` requests of the method to the component.
+pub(crate) fn record(component: &str, method: &str, requests: u64) {
+    let metrics = Metrics::get();
+    if USAGE.record(today(), requests) {
+        metrics.daily_requests.reset();
+    }
+    metrics
+        .daily_requests
+        .with_label_values(&[component, method])
+        .add(requests.try_into().unwrap_or(i64::MAX));
+    if let Some(budget) = BUDGET.get() {
+        metrics.daily_budget_used.set(USAGE.share_of(budget));
+    }
+}
+
+/// How long the next request of the component should be delayed.
+pub(crate) fn throttle_delay(component: &str) -> Option<Duration> {
+    let budget = BUDGET.get()?;
+    if !USAGE.is_throttled(budget, component, today()) {
+        return None;
+    }
+    Metrics::get()
+        .throttled_requests
+        .with_label_values(&[component])
+        .inc();
+    Some(budget.throttle_delay)
+}
+
+/// Requests sent by this process that still have to be persisted so that
+/// the budget is shared with other processes and survives restarts.
+#[derive(Debug, PartialEq)]
+pub struct Unsynced {
+    /// Days since the unix epoch.
+    pub day: u64,
+    pub requests: u64,
+    /// Requests of the previous day as `(day, requests)`.
+    pub carry_over: Option<(u64, u64)>,
+}
+
+/// Takes the requests sent since the last sync. The result of persisting them
+/// has to be reported with [`synced`].
+pub fn take_unsynced() -> Unsynced {
+    USAGE.take_unsynced(today())
+}
+
+/// Reports the total requests of the day after persisting the unsynced
+/// requests, or `None` if persisting them failed so they get retried.
+pub fn synced(unsynced: Unsynced, total: Option<u64>) {
+    USAGE.synced(unsynced, total);
+    if let Some(budget) = BUDGET.get() {
+        Metrics::get().daily_budget_used.set(USAGE.share_of(budget));
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "rpc")]
+struct Metrics {
+    /// Number of RPC requests sent during the current UTC day. Requests within
+    /// batches are counted individually.
+    #[metric(labels("component", "method"))]
+    daily_requests: prometheus::IntGaugeVec,
+
+    /// Share of the daily RPC budget used during the current UTC day.
+    daily_budget_used: prometheus::Gauge,
+
+    /// Number of RPC requests of background components that got delayed
+    /// because the daily budget is running low.
+    #[metric(labels("component"))]
+    throttled_requests: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_background_components() {
+        let budget = Budget {
+            daily_requests: 100,
+            throttle_threshold: 0.8,
+            background_components: vec!["tokenOwners".to_string(), "trace".to_string()],
+            throttle_delay: Duration::from_secs(1),
+        };
+        assert!(budget.is_background("trace"));
+        assert!(budget.is_background("base_tokenOwners"));
+        assert!(!budget.is_background("base"));
+        assert!(!budget.is_background("base_othertokenOwners"));
+        assert!(!budget.is_background("trace_base"));
+    }
+
+    fn budget() -> Budget {
+        Budget {
+            daily_requests: 100,
+            throttle_threshold: 0.8,
+            background_components: vec!["trace".to_string()],
+            throttle_delay: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn usage_resets_every_day() {
+        let budget = budget();
+        let usage = Usage::new();
+        assert!(usage.record(1, 50));
+        assert!(!usage.record(1, 30));
+        assert_eq!(usage.share_of(&budget), 0.8);
+        assert!(usage.record(2, 10));
+        assert_eq!(usage.share_of(&budget), 0.1);
+        assert_eq!(*usage.carry_over.lock().unwrap(), Some((1, 80)));
+    }
+
+    #[test]
+    fn throttles_background_components_near_budget() {
+        let budget = budget();
+        let usage = Usage::new();
+        usage.record(1, 79);
+        assert!(!usage.is_throttled(&budget, "trace", 1));
+
+        usage.record(1, 1);
+        assert!(usage.is_throttled(&budget, "trace", 1));
+        assert!(usage.is_throttled(&budget, "base_trace", 1));
+        assert!(!usage.is_throttled(&budget, "base", 1));
+        // The budget is fresh on the next day.
+        assert!(!usage.is_throttled(&budget, "trace", 2));
+    }
+
+    #[test]
+    fn synced_usage_counts_towards_budget() {
+        let budget = budget();
+        let usage = Usage::new();
+        usage.record(1, 10);
+
+        let unsynced = usage.take_unsynced(1);
+        assert_eq!(
+            unsynced,
+            Unsynced {
+                day: 1,
+                requests: 10,
+                carry_over: None
+            }
+        );
+        // Other processes sent requests too.
+        usage.synced(unsynced, Some(75));
+        usage.record(1, 5);
+        assert!(usage.is_throttled(&budget, "trace", 1));
+    }
+
+    #[test]
+    fn failed_syncs_get_retried() {
+        let usage = Usage::new();
+        usage.record(1, 10);
+        let unsynced = usage.take_unsynced(1);
+        usage.synced(unsynced, None);
+        assert_eq!(usage.take_unsynced(1).requests, 10);
+
+        // Requests of a day that ended before they got persisted are carried
+        // over.
+        usage.record(1, 5);
+        let unsynced = usage.take_unsynced(1);
+        usage.record(2, 1);
+        usage.synced(unsynced, None);
+        assert_eq!(
+            usage.take_unsynced(2),
+            Unsynced {
+                day: 2,
+                requests: 1,
+                carry_over: Some((1, 5))
+            }
+        );
+    }
+}
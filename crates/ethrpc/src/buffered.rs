@@ -1,5 +1,8 @@
 //! A buffered `Transport` implementation that automatically groups JSON RPC
 //! requests into batches.
+//!
+//! Latency sensitive requests can opt out of batching by running with
+//! [`Priority::High`], see [`with_priority`].
 
 use {
     super::MAX_BATCH_SIZE,
@@ -25,6 +28,42 @@ use {
     tracing::Instrument as _,
 };
 
+/// Methods which always get sent with [`Priority::High`].
+const HIGH_PRIORITY_METHODS: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+
+/// How urgently a request has to be sent to the node.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Priority {
+    /// The request gets batched with other requests.
+    #[default]
+    Normal,
+    /// The request gets sent right away without waiting for the batch delay
+    /// or for other batches to complete.
+    High,
+}
+
+tokio::task_local! {
+    static PRIORITY: Priority;
+}
+
+/// Sends all requests issued while executing the future with the priority.
+///
+/// For example solvers use this for the simulations that have to finish
+/// before the solving deadline.
+pub fn with_priority<F: Future>(priority: Priority, future: F) -> impl Future<Output = F::Output> {
+    PRIORITY.scope(priority, future)
+}
+
+/// The priority of the call based on its method and the current task.
+fn priority(call: &Call) -> Priority {
+    match call {
+        Call::MethodCall(call) if HIGH_PRIORITY_METHODS.contains(&call.method.as_str()) => {
+            Priority::High
+        }
+        _ => PRIORITY.try_with(|priority| *priority).unwrap_or_default(),
+    }
+}
+
 /// Buffered transport configuration.
 pub struct Configuration {
     /// The maximum amount of concurrent batches to send to the node.
@@ -88,7 +127,7 @@ where
         config: Configuration,
         calls: mpsc::UnboundedReceiver<CallContext>,
     ) -> JoinHandle<()> {
-        tokio::task::spawn(batched_for_each(config, calls, move |batch| {
+        observe::metrics::spawn_in_current_scope(batched_for_each(config, calls, move |batch| {
             let inner = inner.clone();
             async move {
                 let (mut requests, mut trace_ids, mut senders): (Vec<_>, Vec<_>, Vec<_>) =
@@ -156,17 +195,24 @@ where
         receiver
     }
 
-    /// Executes a call.
-    async fn execute_call(&self, id: RequestId, request: Call) -> RpcResult {
+    /// Executes a call. High priority calls bypass the queue.
+    async fn execute_call(&self, id: RequestId, request: Call, priority: Priority) -> RpcResult {
         let method = match &request {
             Call::MethodCall(call) => call.method.as_str(),
             _ => "none",
         };
 
-        tracing::trace!(%id, %method, "queueing call");
-
-        let response = self.queue_call(id, request);
-        let result = response.await.expect("worker task unexpectedly dropped");
+        let result = match priority {
+            Priority::High => {
+                tracing::trace!(%id, %method, "sending high priority call");
+                self.inner.send(id, request).await
+            }
+            Priority::Normal => {
+                tracing::trace!(%id, %method, "queueing call");
+                let response = self.queue_call(id, request);
+                response.await.expect("worker task unexpectedly dropped")
+            }
+        };
 
         tracing::trace!(%id, ok = %result.is_ok(), "received response");
 
@@ -188,8 +234,9 @@ where
 
     fn send(&self, id: RequestId, request: Call) -> Self::Out {
         let this = self.clone();
+        let priority = priority(&request);
 
-        async move { this.execute_call(id, request).await }
+        async move { this.execute_call(id, request, priority).await }
             .in_current_span()
             .boxed()
     }
@@ -208,12 +255,18 @@ where
         T: IntoIterator<Item = (RequestId, Call)>,
     {
         let this = self.clone();
-        let requests = requests.into_iter().collect::<Vec<_>>();
+        let requests = requests
+            .into_iter()
+            .map(|(id, request)| {
+                let priority = priority(&request);
+                (id, request, priority)
+            })
+            .collect::<Vec<_>>();
 
         async move {
             let responses = requests
                 .into_iter()
-                .map(|(id, request)| this.execute_call(id, request));
+                .map(|(id, request, priority)| this.execute_call(id, request, priority));
             Ok(future::join_all(responses).await)
         }
         .in_current_span()
@@ -517,6 +570,47 @@ mod tests {
         drop(unpolled);
     }
 
+    #[tokio::test]
+    async fn high_priority_calls_bypass_batching() {
+        let transport = MockTransport::new();
+        transport
+            .mock()
+            .expect_execute_batch()
+            .with(predicate::eq(vec![
+                ("foo".to_owned(), vec![]),
+                ("bar".to_owned(), vec![]),
+            ]))
+            .returning(|_| Ok(vec![Ok(json!("foo")), Ok(json!("bar"))]));
+        transport
+            .mock()
+            .expect_execute()
+            .with(
+                predicate::eq("eth_sendRawTransaction".to_owned()),
+                predicate::eq(vec![json!("0x00")]),
+            )
+            .returning(|_, _| Ok(json!("hash")));
+        transport
+            .mock()
+            .expect_execute()
+            .with(predicate::eq("eth_call".to_owned()), predicate::eq(vec![]))
+            .returning(|_, _| Ok(json!("call")));
+
+        let transport = BufferedTransport::new(transport);
+
+        let (foo, send, call, bar) = futures::join!(
+            transport.execute("foo", vec![]),
+            transport.execute("eth_sendRawTransaction", vec![json!("0x00")]),
+            with_priority(Priority::High, async {
+                transport.execute("eth_call", vec![]).await
+            }),
+            transport.execute("bar", vec![]),
+        );
+        assert_eq!(foo.unwrap(), json!("foo"));
+        assert_eq!(send.unwrap(), json!("hash"));
+        assert_eq!(call.unwrap(), json!("call"));
+        assert_eq!(bar.unwrap(), json!("bar"));
+    }
+
     #[test]
     fn test_format_indices_as_ranges() {
         // empty string
@@ -0,0 +1,410 @@
+//! A `Transport` implementation that sends requests to an ordered list of
+//! nodes and fails over to the next one when a node errors or times out.
+
+use {
+    ethcontract::{
+        jsonrpc::{Call, Value},
+        transport::DynTransport,
+        web3::{
+            error::{Error as Web3Error, TransportError},
+            helpers,
+            BatchTransport,
+            RequestId,
+            Transport,
+        },
+    },
+    futures::{
+        future::{BoxFuture, FutureExt},
+        Future,
+    },
+    std::{
+        fmt::{self, Debug, Formatter},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+            Mutex,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// Fallback transport configuration.
+#[derive(Clone, Debug)]
+pub struct Configuration {
+    /// How long to wait for a node to respond before trying the next one.
+    pub request_timeout: Duration,
+    /// After how many consecutive failures a node is considered unhealthy.
+    pub failure_threshold: usize,
+    /// For how long an unhealthy node only gets used once all other nodes
+    /// failed as well.
+    pub unhealthy_cooldown: Duration,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(10),
+            failure_threshold: 3,
+            unhealthy_cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage, Debug)]
+#[metric(subsystem = "rpc_fallback")]
+struct Metrics {
+    /// Number of requests sent to each node by result.
+    #[metric(labels("component", "node", "result"))]
+    requests: prometheus::IntCounterVec,
+
+    /// Whether a node is currently considered healthy.
+    #[metric(labels("component", "node"))]
+    node_healthy: prometheus::IntGaugeVec,
+}
+
+/// `Transport` implementation sending requests to the first healthy node of
+/// an ordered list of nodes.
+///
+/// Requests failing on the transport level (e.g. connection errors, invalid
+/// responses or timeouts) get retried on the next node. JSON RPC errors are
+/// regular responses of a working node and get returned as is. Nodes failing
+/// repeatedly get marked as unhealthy and are only tried after all healthy
+/// nodes until they recover.
+#[derive(Clone)]
+pub struct FallbackTransport(Arc<Inner>);
+
+struct Inner {
+    nodes: Vec<Node>,
+    config: Configuration,
+    id: AtomicUsize,
+    label: String,
+    metrics: &'static Metrics,
+}
+
+struct Node {
+    name: String,
+    transport: DynTransport,
+    consecutive_failures: AtomicUsize,
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl FallbackTransport {
+    /// Creates a transport over the nodes in the order they should be used.
+    /// The names identify the nodes in logs and metrics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no nodes are specified.
+    pub fn new(
+        label: String,
+        nodes: impl IntoIterator<Item = (String, DynTransport)>,
+        config: Configuration,
+    ) -> Self {
+        let metrics = Metrics::instance(observe::metrics::get_storage_registry()).unwrap();
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .map(|(name, transport)| {
+                metrics
+                    .node_healthy
+                    .with_label_values(&[&label, &name])
+                    .set(1);
+                Node {
+                    name,
+                    transport,
+                    consecutive_failures: AtomicUsize::new(0),
+                    unhealthy_until: Mutex::new(None),
+                }
+            })
+            .collect();
+        assert!(!nodes.is_empty(), "fallback transport without nodes");
+
+        Self(Arc::new(Inner {
+            nodes,
+            config,
+            id: AtomicUsize::new(0),
+            label,
+            metrics,
+        }))
+    }
+}
+
+impl Debug for FallbackTransport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackTransport")
+            .field(
+                "nodes",
+                &self
+                    .0
+                    .nodes
+                    .iter()
+                    .map(|node| &node.name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Inner {
+    /// The nodes in the order they should be tried. Unhealthy nodes come last
+    /// so they still get used if all healthy ones fail.
+    fn candidates(&self) -> impl Iterator<Item = &Node> {
+        let now = Instant::now();
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) =
+            self.nodes.iter().partition(|node| node.is_healthy(now));
+        healthy.into_iter().chain(unhealthy)
+    }
+
+    async fn execute<T, F, Fut>(&self, request: F) -> Result<T, Web3Error>
+    where
+        F: Fn(&DynTransport) -> Fut,
+        Fut: Future<Output = Result<T, Web3Error>>,
+    {
+        let mut last_error = None;
+        for node in self.candidates() {
+            let result =
+                tokio::time::timeout(self.config.request_timeout, request(&node.transport))
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(Web3Error::Transport(TransportError::Message(
+                            "request timed out".to_string(),
+                        )))
+                    });
+            match result {
+                Err(err) if is_node_failure(&err) => {
+                    tracing::warn!(label = %self.label, node = %node.name, ?err, "node failed");
+                    self.on_failure(node);
+                    last_error = Some(err);
+                }
+                result => {
+                    self.on_success(node);
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("fallback transport without nodes"))
+    }
+
+    fn on_success(&self, node: &Node) {
+        self.metrics
+            .requests
+            .with_label_values(&[&self.label, &node.name, "success"])
+            .inc();
+        if node.consecutive_failures.swap(0, Ordering::Relaxed) >= self.config.failure_threshold {
+            tracing::info!(label = %self.label, node = %node.name, "node recovered");
+        }
+        *node.unhealthy_until.lock().unwrap() = None;
+        self.metrics
+            .node_healthy
+            .with_label_values(&[&self.label, &node.name])
+            .set(1);
+    }
+
+    fn on_failure(&self, node: &Node) {
+        self.metrics
+            .requests
+            .with_label_values(&[&self.label, &node.name, "failure"])
+            .inc();
+        let failures = node.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= self.config.failure_threshold {
+            *node.unhealthy_until.lock().unwrap() =
+                Some(Instant::now() + self.config.unhealthy_cooldown);
+            self.metrics
+                .node_healthy
+                .with_label_values(&[&self.label, &node.name])
+                .set(0);
+        }
+    }
+}
+
+impl Node {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap()
+            .map_or(true, |until| until <= now)
+    }
+}
+
+/// Whether the error indicates a problem with the node rather than with the
+/// request itself.
+fn is_node_failure(err: &Web3Error) -> bool {
+    matches!(
+        err,
+        Web3Error::Unreachable
+            | Web3Error::Transport(_)
+            | Web3Error::InvalidResponse(_)
+            | Web3Error::Decoder(_)
+            | Web3Error::Io(_)
+    )
+}
+
+type RpcResult = Result<Value, Web3Error>;
+
+impl Transport for FallbackTransport {
+    type Out = BoxFuture<'static, RpcResult>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        let id = self.0.id.fetch_add(1, Ordering::SeqCst);
+        let request = helpers::build_request(id, method, params);
+        (id, request)
+    }
+
+    fn send(&self, id: RequestId, call: Call) -> Self::Out {
+        let inner = self.0.clone();
+
+        async move {
+            inner
+                .execute(|transport| transport.send(id, call.clone()))
+                .await
+        }
+        .boxed()
+    }
+}
+
+impl BatchTransport for FallbackTransport {
+    type Batch = BoxFuture<'static, Result<Vec<RpcResult>, Web3Error>>;
+
+    fn send_batch<T>(&self, requests: T) -> Self::Batch
+    where
+        T: IntoIterator<Item = (RequestId, Call)>,
+    {
+        let inner = self.0.clone();
+        let requests: Vec<_> = requests.into_iter().collect();
+
+        async move {
+            inner
+                .execute(|transport| transport.send_batch(requests.clone()))
+                .await
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::mock::MockTransport,
+        ethcontract::jsonrpc::Error as RpcError,
+        serde_json::json,
+    };
+
+    fn node(name: &str, mock: &MockTransport) -> (String, DynTransport) {
+        (name.to_string(), DynTransport::new(mock.clone()))
+    }
+
+    fn unreachable() -> Web3Error {
+        Web3Error::Transport(TransportError::Message("connection refused".to_string()))
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_next_node() {
+        let primary = MockTransport::new();
+        primary
+            .mock()
+            .expect_execute()
+            .returning(|_, _| Err(unreachable()));
+        let secondary = MockTransport::new();
+        secondary
+            .mock()
+            .expect_execute()
+            .returning(|_, _| Ok(json!("secondary")));
+
+        let transport = FallbackTransport::new(
+            "test".to_string(),
+            [node("primary", &primary), node("secondary", &secondary)],
+            Default::default(),
+        );
+        assert_eq!(
+            transport.execute("foo", vec![]).await.unwrap(),
+            json!("secondary")
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_rpc_errors_without_failing_over() {
+        let primary = MockTransport::new();
+        primary
+            .mock()
+            .expect_execute()
+            .returning(|_, _| Err(Web3Error::Rpc(RpcError::internal_error())));
+        let secondary = MockTransport::new();
+        secondary.mock().expect_execute().never();
+
+        let transport = FallbackTransport::new(
+            "test".to_string(),
+            [node("primary", &primary), node("secondary", &secondary)],
+            Default::default(),
+        );
+        assert!(matches!(
+            transport.execute("foo", vec![]).await,
+            Err(Web3Error::Rpc(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn skips_unhealthy_nodes() {
+        let primary = MockTransport::new();
+        primary
+            .mock()
+            .expect_execute()
+            .times(2)
+            .returning(|_, _| Err(unreachable()));
+        let secondary = MockTransport::new();
+        secondary
+            .mock()
+            .expect_execute()
+            .times(3)
+            .returning(|_, _| Ok(json!("secondary")));
+
+        let transport = FallbackTransport::new(
+            "test".to_string(),
+            [node("primary", &primary), node("secondary", &secondary)],
+            Configuration {
+                failure_threshold: 2,
+                ..Default::default()
+            },
+        );
+        // The primary node gets skipped after failing twice.
+        for _ in 0..3 {
+            assert_eq!(
+                transport.execute("foo", vec![]).await.unwrap(),
+                json!("secondary")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn uses_unhealthy_nodes_as_last_resort() {
+        let primary = MockTransport::new();
+        let mut calls = 0;
+        primary.mock().expect_execute().returning(move |_, _| {
+            calls += 1;
+            match calls {
+                1 => Err(unreachable()),
+                _ => Ok(json!("primary")),
+            }
+        });
+        let secondary = MockTransport::new();
+        secondary
+            .mock()
+            .expect_execute()
+            .returning(|_, _| Err(unreachable()));
+
+        let transport = FallbackTransport::new(
+            "test".to_string(),
+            [node("primary", &primary), node("secondary", &secondary)],
+            Configuration {
+                failure_threshold: 1,
+                ..Default::default()
+            },
+        );
+        // Both nodes fail and get marked as unhealthy.
+        assert!(transport.execute("foo", vec![]).await.is_err());
+        // They still get tried instead of failing right away.
+        assert_eq!(
+            transport.execute("foo", vec![]).await.unwrap(),
+            json!("primary")
+        );
+    }
+}
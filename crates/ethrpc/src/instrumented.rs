@@ -1,14 +1,27 @@
 use {
+    crate::budget,
     ethcontract::{
         dyns::DynWeb3,
         jsonrpc::types::{Call, Value},
         transport::DynTransport,
     },
     futures::{future::BoxFuture, FutureExt},
-    std::sync::Arc,
-    web3::{error::Error as Web3Error, BatchTransport, RequestId, Transport},
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+    web3::{
+        error::{Error as Web3Error, TransportError},
+        BatchTransport,
+        RequestId,
+        Transport,
+    },
 };
 
+/// Number of most recent requests a [`NodeHealth`] snapshot is based on.
+const HEALTH_WINDOW: usize = 100;
+
 #[derive(prometheus_metric_storage::MetricStorage, Clone, Debug)]
 #[metric(subsystem = "rpc")]
 struct Metrics {
@@ -28,6 +41,15 @@ struct Metrics {
     /// Number of RPC requests initiated within a batch request
     #[metric(labels("component", "method"))]
     inner_batch_requests_initiated: prometheus::IntCounterVec,
+
+    /// Execution time of RPC requests within a batch request, i.e. the time
+    /// until the whole batch completed.
+    #[metric(labels("component", "method"))]
+    inner_batch_requests_duration_seconds: prometheus::HistogramVec,
+
+    /// Number of failed RPC requests by kind of error (see [`ErrorKind`]).
+    #[metric(labels("component", "method", "kind"))]
+    requests_errors: prometheus::IntCounterVec,
 }
 
 impl Metrics {
@@ -48,6 +70,167 @@ impl Metrics {
             timer.stop_and_record();
         })
     }
+
+    fn on_error(&self, label: &str, method: &str, kind: ErrorKind) {
+        self.requests_errors
+            .with_label_values(&[label, method, kind.as_str()])
+            .inc();
+    }
+}
+
+/// Classification of failed RPC requests.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorKind {
+    /// The node executed the request but the call reverted.
+    Reverted,
+    /// The node or its provider rejected the request because of rate limits.
+    RateLimited,
+    /// Any other JSON RPC error returned by the node.
+    Rpc,
+    /// The node responded with an HTTP server error status like 503.
+    Unavailable,
+    /// The node responded with an HTTP client error status.
+    Http,
+    /// The request did not complete in time.
+    Timeout,
+    /// The request could not be sent or the response not be received.
+    Transport,
+    /// The node responded with something that isn't a valid JSON RPC
+    /// response.
+    InvalidResponse,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn classify(err: &Web3Error) -> Self {
+        match err {
+            Web3Error::Rpc(err) => {
+                let message = err.message.to_lowercase();
+                // Geth and most other nodes report reverts with code 3.
+                if err.code.code() == 3 || message.contains("revert") {
+                    Self::Reverted
+                } else if err.code.code() == -32005
+                    || message.contains("rate limit")
+                    || message.contains("too many requests")
+                {
+                    Self::RateLimited
+                } else {
+                    Self::Rpc
+                }
+            }
+            Web3Error::Transport(TransportError::Code(code)) => Self::from_status(*code),
+            Web3Error::Transport(TransportError::Message(message)) => {
+                match message
+                    .strip_prefix("HTTP error ")
+                    .and_then(|status| status.get(..3)?.parse().ok())
+                {
+                    Some(status) => Self::from_status(status),
+                    None if message.contains("timed out") => Self::Timeout,
+                    None => Self::Transport,
+                }
+            }
+            Web3Error::InvalidResponse(_) | Web3Error::Decoder(_) => Self::InvalidResponse,
+            Web3Error::Unreachable | Web3Error::Io(_) => Self::Transport,
+            _ => Self::Other,
+        }
+    }
+
+    fn from_status(status: u16) -> Self {
+        match status {
+            429 => Self::RateLimited,
+            500.. => Self::Unavailable,
+            _ => Self::Http,
+        }
+    }
+
+    /// Whether the error indicates a problem with the node rather than with
+    /// the request.
+    pub fn is_node_failure(&self) -> bool {
+        !matches!(self, Self::Reverted | Self::Rpc | Self::Http)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reverted => "reverted",
+            Self::RateLimited => "rate_limited",
+            Self::Rpc => "rpc",
+            Self::Unavailable => "unavailable",
+            Self::Http => "http",
+            Self::Timeout => "timeout",
+            Self::Transport => "transport",
+            Self::InvalidResponse => "invalid_response",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Snapshot of how the node behind a transport performed for the most recent
+/// requests. Lets components like circuit breakers react to node problems
+/// without going through the metrics.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeHealth {
+    /// Number of recent requests the snapshot is based on.
+    pub requests: usize,
+    /// Number of the recent requests that failed by kind of error.
+    pub errors: HashMap<ErrorKind, usize>,
+    pub mean_latency: Duration,
+    pub max_latency: Duration,
+    /// When a request last succeeded.
+    pub last_success: Option<Instant>,
+}
+
+impl NodeHealth {
+    /// Share of the recent requests that failed because of the node. Reverts
+    /// and other errors caused by the request itself don't count.
+    pub fn node_failure_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.;
+        }
+        let failures: usize = self
+            .errors
+            .iter()
+            .filter(|(kind, _)| kind.is_node_failure())
+            .map(|(_, count)| count)
+            .sum();
+        failures as f64 / self.requests as f64
+    }
+}
+
+/// Outcomes of the most recent requests sent through a transport.
+#[derive(Debug, Default)]
+struct HealthTracker {
+    samples: VecDeque<(Duration, Option<ErrorKind>)>,
+    last_success: Option<Instant>,
+}
+
+impl HealthTracker {
+    fn record(&mut self, latency: Duration, error: Option<ErrorKind>) {
+        if self.samples.len() == HEALTH_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((latency, error));
+        if error.is_none() {
+            self.last_success = Some(Instant::now());
+        }
+    }
+
+    fn snapshot(&self) -> NodeHealth {
+        let mut errors = HashMap::new();
+        for kind in self.samples.iter().filter_map(|(_, error)| *error) {
+            *errors.entry(kind).or_default() += 1;
+        }
+        let latencies = self.samples.iter().map(|(latency, _)| *latency);
+        let total: Duration = latencies.clone().sum();
+        NodeHealth {
+            requests: self.samples.len(),
+            errors,
+            mean_latency: total
+                .checked_div(self.samples.len().try_into().unwrap_or(u32::MAX))
+                .unwrap_or_default(),
+            max_latency: latencies.max().unwrap_or_default(),
+            last_success: self.last_success,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +242,7 @@ impl InstrumentedTransport {
             metrics: Metrics::instance(observe::metrics::get_storage_registry()).unwrap(),
             transport,
             label,
+            health: Default::default(),
         }))
     }
 
@@ -67,8 +251,15 @@ impl InstrumentedTransport {
             label: format!("{}_{label}", self.0.label),
             transport: self.0.transport.clone(),
             metrics: self.0.metrics,
+            // Additional labels still send requests to the same node.
+            health: self.0.health.clone(),
         }))
     }
+
+    /// Returns how the node performed for the most recent requests.
+    pub fn health(&self) -> NodeHealth {
+        self.0.health.lock().unwrap().snapshot()
+    }
 }
 
 /// Adds metrics for RPC requests using the provided label.
@@ -82,11 +273,30 @@ pub fn instrument_with_label(web3: &DynWeb3, label: String) -> DynWeb3 {
     web3::Web3::new(DynTransport::new(instrumented))
 }
 
+/// Returns the health of the node `web3` sends requests to if its transport
+/// is instrumented.
+pub fn node_health(web3: &DynWeb3) -> Option<NodeHealth> {
+    web3.transport()
+        .downcast::<InstrumentedTransport>()
+        .map(InstrumentedTransport::health)
+}
+
 #[derive(Debug)]
 struct Inner {
     metrics: &'static Metrics,
     transport: DynTransport,
     label: String,
+    health: Arc<Mutex<HealthTracker>>,
+}
+
+impl Inner {
+    fn record(&self, method: &str, latency: Duration, error: Option<&Web3Error>) {
+        let kind = error.map(ErrorKind::classify);
+        if let Some(kind) = kind {
+            self.metrics.on_error(&self.label, method, kind);
+        }
+        self.health.lock().unwrap().record(latency, kind);
+    }
 }
 
 type RpcResult = Result<Value, Web3Error>;
@@ -102,10 +312,16 @@ impl Transport for InstrumentedTransport {
         let inner = self.0.clone();
 
         async move {
-            let _guard = inner
-                .metrics
-                .on_request_start(&inner.label, method_name(&call));
-            inner.transport.send(id, call).await
+            let method = method_name(&call).to_string();
+            if let Some(delay) = budget::throttle_delay(&inner.label) {
+                tokio::time::sleep(delay).await;
+            }
+            budget::record(&inner.label, &method, 1);
+            let _guard = inner.metrics.on_request_start(&inner.label, &method);
+            let start = Instant::now();
+            let result = inner.transport.send(id, call).await;
+            inner.record(&method, start.elapsed(), result.as_ref().err());
+            result
         }
         .boxed()
     }
@@ -122,18 +338,40 @@ impl BatchTransport for InstrumentedTransport {
         let requests: Vec<_> = requests.into_iter().collect();
 
         async move {
+            if let Some(delay) = budget::throttle_delay(&inner.label) {
+                tokio::time::sleep(delay).await;
+            }
             let _guard = inner.metrics.on_request_start(&inner.label, "batch");
             let metrics = inner.metrics;
             let label = &inner.label;
-
-            let requests = requests.into_iter().inspect(move |(_id, call)| {
+            let methods: Vec<_> = requests
+                .iter()
+                .map(|(_, call)| method_name(call).to_string())
+                .collect();
+            for method in &methods {
                 metrics
                     .inner_batch_requests_initiated
-                    .with_label_values(&[label, method_name(call)])
-                    .inc()
-            });
+                    .with_label_values(&[label, method])
+                    .inc();
+                budget::record(label, method, 1);
+            }
 
-            inner.transport.send_batch(requests).await
+            let start = Instant::now();
+            let result = inner.transport.send_batch(requests).await;
+            let latency = start.elapsed();
+            match &result {
+                Ok(results) => {
+                    for (method, result) in methods.iter().zip(results) {
+                        metrics
+                            .inner_batch_requests_duration_seconds
+                            .with_label_values(&[label, method])
+                            .observe(latency.as_secs_f64());
+                        inner.record(method, latency, result.as_ref().err());
+                    }
+                }
+                Err(err) => inner.record("batch", latency, Some(err)),
+            }
+            result
         }
         .boxed()
     }
@@ -146,3 +384,91 @@ fn method_name(call: &Call) -> &str {
         Call::Invalid { .. } => "invalid",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::mock::MockTransport,
+        ethcontract::jsonrpc::{Error as RpcError, ErrorCode},
+        serde_json::json,
+    };
+
+    fn rpc_error(code: i64, message: &str) -> Web3Error {
+        Web3Error::Rpc(RpcError {
+            code: ErrorCode::ServerError(code),
+            message: message.to_string(),
+            data: None,
+        })
+    }
+
+    fn transport_error(message: &str) -> Web3Error {
+        Web3Error::Transport(TransportError::Message(message.to_string()))
+    }
+
+    #[test]
+    fn classifies_errors() {
+        for (err, kind) in [
+            (rpc_error(3, "execution reverted"), ErrorKind::Reverted),
+            (
+                rpc_error(-32000, "execution reverted: foo"),
+                ErrorKind::Reverted,
+            ),
+            (rpc_error(-32005, "limit exceeded"), ErrorKind::RateLimited),
+            (rpc_error(-32000, "nonce too low"), ErrorKind::Rpc),
+            (
+                transport_error("HTTP error 503 Service Unavailable"),
+                ErrorKind::Unavailable,
+            ),
+            (
+                transport_error("HTTP error 429 Too Many Requests"),
+                ErrorKind::RateLimited,
+            ),
+            (
+                transport_error("HTTP error 400 Bad Request"),
+                ErrorKind::Http,
+            ),
+            (transport_error("request timed out"), ErrorKind::Timeout),
+            (transport_error("connection refused"), ErrorKind::Transport),
+            (
+                Web3Error::Decoder("invalid json".to_string()),
+                ErrorKind::InvalidResponse,
+            ),
+        ] {
+            assert_eq!(ErrorKind::classify(&err), kind, "{err:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn tracks_node_health() {
+        let mock = MockTransport::new();
+        let mut calls = 0;
+        mock.mock().expect_execute().returning(move |_, _| {
+            calls += 1;
+            match calls {
+                1 => Ok(json!(true)),
+                2 => Err(rpc_error(3, "execution reverted")),
+                _ => Err(transport_error("HTTP error 503 Service Unavailable")),
+            }
+        });
+        let transport = InstrumentedTransport::new("test".into(), DynTransport::new(mock));
+        let labeled = transport.with_additional_label("other".into());
+
+        assert_eq!(transport.health(), NodeHealth::default());
+        transport.execute("foo", vec![]).await.unwrap();
+        transport.execute("foo", vec![]).await.unwrap_err();
+        // Requests with additional labels go to the same node.
+        labeled.execute("foo", vec![]).await.unwrap_err();
+        labeled.execute("foo", vec![]).await.unwrap_err();
+
+        let health = transport.health();
+        assert_eq!(health.requests, 4);
+        assert_eq!(
+            health.errors,
+            HashMap::from([(ErrorKind::Reverted, 1), (ErrorKind::Unavailable, 2)])
+        );
+        assert!(health.last_success.is_some());
+        assert_eq!(health.node_failure_rate(), 0.5);
+        assert_eq!(labeled.health(), health);
+    }
+}
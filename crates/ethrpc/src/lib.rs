@@ -1,14 +1,16 @@
 pub mod block_stream;
+pub mod budget;
 pub mod buffered;
 pub mod dummy;
 pub mod extensions;
+pub mod fallback;
 pub mod http;
 pub mod instrumented;
 pub mod mock;
 pub mod multicall;
 
 use {
-    self::{buffered::BufferedTransport, http::HttpTransport},
+    self::{buffered::BufferedTransport, fallback::FallbackTransport, http::HttpTransport},
     ethcontract::{batch::CallBatch, dyns::DynWeb3, transport::DynTransport},
     reqwest::{Client, Url},
     std::{num::NonZeroUsize, time::Duration},
@@ -69,12 +71,40 @@ pub fn web3(
     http_factory: reqwest::ClientBuilder,
     url: &Url,
     name: impl ToString,
+) -> Web3 {
+    web3_with_fallbacks(args, http_factory, url, &[], name)
+}
+
+/// Create a Web3 instance that fails over to the fallback nodes in order when
+/// the node at `url` errors or times out.
+pub fn web3_with_fallbacks(
+    args: Config,
+    http_factory: reqwest::ClientBuilder,
+    url: &Url,
+    fallbacks: &[Url],
+    name: impl ToString,
 ) -> Web3 {
     let http = http_factory.cookie_store(true).build().unwrap();
-    let http = HttpTransport::new(http, url.clone(), name.to_string());
+    let node = if fallbacks.is_empty() {
+        Web3Transport::new(HttpTransport::new(http, url.clone(), name.to_string()))
+    } else {
+        let nodes = std::iter::once(url)
+            .chain(fallbacks)
+            .enumerate()
+            .map(|(i, url)| {
+                let name = format!("{}_{i}", name.to_string());
+                let http = HttpTransport::new(http.clone(), url.clone(), name.clone());
+                (name, Web3Transport::new(http))
+            });
+        Web3Transport::new(FallbackTransport::new(
+            name.to_string(),
+            nodes,
+            Default::default(),
+        ))
+    };
     let transport = match args.into_buffered_configuration() {
-        Some(config) => Web3Transport::new(BufferedTransport::with_config(http, config)),
-        None => Web3Transport::new(http),
+        Some(config) => Web3Transport::new(BufferedTransport::with_config(node, config)),
+        None => node,
     };
     let instrumented = instrumented::InstrumentedTransport::new(name.to_string(), transport);
     Web3::new(Web3Transport::new(instrumented))
@@ -0,0 +1,559 @@
+//! Typed full app data documents.
+//!
+//! The schema of app data documents is versioned, see
+//! <https://github.com/cowprotocol/app-data>. Fields whose format changed
+//! between versions get normalized, so callers don't need to care about the
+//! version a document was created with. Informational fields that don't match
+//! the typed schema, e.g. because they were created with an unknown schema
+//! version, are kept as opaque values instead of failing the whole document.
+//! Only the fields relevant to the protocol have to match the schema. Fields
+//! that aren't part of the typed schema are kept too, so documents can be
+//! serialized again without losing data.
+
+use {
+    crate::order::OrderUid,
+    app_data::{hash_full_app_data, AppDataHash, Hooks, PartnerFee, ReplacedOrder},
+    bytes_hex::BytesHex,
+    cached::{Cached, SizedCache},
+    primitive_types::H160,
+    serde::{Deserialize, Serialize},
+    serde_json::{Map, Value},
+    serde_with::{serde_as, DeserializeFromStr, DisplayFromStr, PickFirst, SerializeDisplay},
+    std::{
+        fmt,
+        str::FromStr,
+        sync::{Mutex, OnceLock},
+    },
+};
+
+/// A full app data document.
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDataDocument {
+    /// Version of the app data schema. The minimal app data document `{}`
+    /// doesn't specify any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Field<Version>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+    /// DEPRECATED. Predecessor of `metadata` that only supported hooks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<Backend>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<Field<Referrer>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<Field<Quote>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_class: Option<Field<OrderClassMetadata>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Hooks>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<H160>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_order: Option<ReplacedOrder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partner_fee: Option<PartnerFee>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bridging: Option<Bridging>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// An informational field that keeps its raw value if it doesn't match the
+/// typed schema.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Field<T> {
+    Typed(T),
+    Opaque(Value),
+}
+
+impl<T> Field<T> {
+    /// The value if it matches the typed schema.
+    pub fn typed(&self) -> Option<&T> {
+        match self {
+            Self::Typed(value) => Some(value),
+            Self::Opaque(_) => None,
+        }
+    }
+}
+
+/// Where the bought tokens get bridged to after the order settled. The order
+/// has to pay out to the bridge adapter, which forwards the tokens to the
+/// recipient on the target chain.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Bridging {
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub target_chain_id: u64,
+    pub adapter: H160,
+    /// Address of the recipient on the target chain. Its length depends on
+    /// the target chain.
+    #[serde_as(as = "BytesHex")]
+    pub recipient: Vec<u8>,
+}
+
+impl Bridging {
+    /// Shortest and longest recipient addresses, e.g. of EVM chains and of
+    /// chains using 32 byte public keys.
+    const RECIPIENT_LEN: std::ops::RangeInclusive<usize> = 20..=32;
+
+    /// Checks that the bridging metadata is consistent with an order of the
+    /// given chain paying out to `receiver`.
+    pub fn validate(&self, chain_id: u64, receiver: H160) -> Result<(), BridgingError> {
+        if self.target_chain_id == chain_id {
+            return Err(BridgingError::SameChain);
+        }
+        if receiver != self.adapter {
+            return Err(BridgingError::ReceiverNotAdapter);
+        }
+        if !Self::RECIPIENT_LEN.contains(&self.recipient.len())
+            || self.recipient.iter().all(|byte| *byte == 0)
+        {
+            return Err(BridgingError::InvalidRecipient);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum BridgingError {
+    #[error("bridging is not supported")]
+    Unsupported,
+    #[error("bridging target chain is the chain the order is placed on")]
+    SameChain,
+    #[error("order receiver is not the bridge adapter")]
+    ReceiverNotAdapter,
+    #[error("bridging recipient is not a valid address")]
+    InvalidRecipient,
+}
+
+/// Who referred the user. Older schema versions identify referrers by address,
+/// newer ones by a referral code.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Referrer {
+    Address { address: H160 },
+    Code { code: String },
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    /// The slippage the user accepted. Older schema versions encode it as a
+    /// string.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub slippage_bips: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_slippage: Option<bool>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderClassMetadata {
+    pub order_class: OrderClass,
+}
+
+/// The class of an order as intended by the user interface that created it.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderClass {
+    Market,
+    Limit,
+    Liquidity,
+    Twap,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Backend {
+    #[serde(default)]
+    pub hooks: Hooks,
+}
+
+impl AppDataDocument {
+    /// Parses a full app data document. Fails if it isn't a JSON object or
+    /// one of the fields relevant to the protocol doesn't match the schema.
+    pub fn parse(full_app_data: &str) -> Result<Self, Error> {
+        serde_json::from_str(full_app_data).map_err(Error::Json)
+    }
+
+    /// Parses a full app data document and checks that it matches the app data
+    /// hash of an order.
+    pub fn parse_with_hash(full_app_data: &str, expected: &AppDataHash) -> Result<Self, Error> {
+        let actual = AppDataHash(hash_full_app_data(full_app_data.as_bytes()));
+        if actual != *expected {
+            return Err(Error::HashMismatch {
+                expected: *expected,
+                actual,
+            });
+        }
+        Self::parse(full_app_data)
+    }
+
+    pub fn version(&self) -> Option<Version> {
+        self.version.as_ref()?.typed().copied()
+    }
+
+    pub fn referrer(&self) -> Option<&Referrer> {
+        self.metadata.as_ref()?.referrer.as_ref()?.typed()
+    }
+
+    pub fn slippage_bips(&self) -> Option<u32> {
+        Some(
+            self.metadata
+                .as_ref()?
+                .quote
+                .as_ref()?
+                .typed()?
+                .slippage_bips,
+        )
+    }
+
+    pub fn order_class(&self) -> Option<OrderClass> {
+        Some(
+            self.metadata
+                .as_ref()?
+                .order_class
+                .as_ref()?
+                .typed()?
+                .order_class,
+        )
+    }
+
+    /// The hooks of the order. Hooks in the deprecated `backend` object are
+    /// only considered if the document has no `metadata`.
+    pub fn hooks(&self) -> Option<&Hooks> {
+        match &self.metadata {
+            Some(metadata) => metadata.hooks.as_ref(),
+            None => self.backend.as_ref().map(|backend| &backend.hooks),
+        }
+    }
+
+    pub fn signer(&self) -> Option<H160> {
+        self.metadata.as_ref()?.signer
+    }
+
+    pub fn replaced_order(&self) -> Option<OrderUid> {
+        Some(OrderUid(
+            self.metadata.as_ref()?.replaced_order.as_ref()?.uid.0,
+        ))
+    }
+
+    pub fn partner_fee(&self) -> Option<&PartnerFee> {
+        self.metadata.as_ref()?.partner_fee.as_ref()
+    }
+
+    pub fn bridging(&self) -> Option<&Bridging> {
+        self.metadata.as_ref()?.bridging.as_ref()
+    }
+}
+
+/// How many app data documents [`bridging`] remembers the bridging metadata
+/// of.
+const BRIDGING_CACHE_SIZE: usize = 10_000;
+
+/// The bridging metadata of the full app data document with the given hash.
+/// Documents that fail to parse have none.
+///
+/// Orders get read far more often than new app data gets stored, so the
+/// result is cached by the hash to not parse the same documents over and over.
+pub fn bridging(hash: &AppDataHash, full_app_data: &str) -> Option<Bridging> {
+    static CACHE: OnceLock<Mutex<SizedCache<AppDataHash, Option<Bridging>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(SizedCache::with_size(BRIDGING_CACHE_SIZE)));
+    if let Some(bridging) = cache.lock().unwrap().cache_get(hash) {
+        return bridging.clone();
+    }
+    let bridging = AppDataDocument::parse(full_app_data)
+        .ok()
+        .and_then(|document| document.bridging().cloned());
+    cache.lock().unwrap().cache_set(*hash, bridging.clone());
+    bridging
+}
+
+/// A semantic version of the app data schema.
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, DeserializeFromStr, SerializeDisplay,
+)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    /// Parses versions like `1.2.3`. Some documents omit the patch version.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let mut next = |optional: bool| match parts.next() {
+            Some(part) => part.parse::<u32>().map_err(anyhow::Error::from),
+            None if optional => Ok(0),
+            None => Err(anyhow::anyhow!("missing version component in {s:?}")),
+        };
+        let version = Self {
+            major: next(false)?,
+            minor: next(false)?,
+            patch: next(true)?,
+        };
+        anyhow::ensure!(
+            parts.next().is_none(),
+            "too many version components in {s:?}"
+        );
+        Ok(version)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid app data document: {0}")]
+    Json(serde_json::Error),
+    #[error("app data hashes to {actual:?} instead of {expected:?}")]
+    HashMismatch {
+        expected: AppDataHash,
+        actual: AppDataHash,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn parses_documents_of_all_versions() {
+        let v0_4 = AppDataDocument::parse(
+            r#"{
+                "version": "0.4.0",
+                "appCode": "CowSwap",
+                "metadata": {
+                    "referrer": {
+                        "version": "0.1.0",
+                        "address": "0x1111111111111111111111111111111111111111"
+                    },
+                    "quote": { "version": "0.2.0", "slippageBips": "50" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            v0_4.referrer(),
+            Some(&Referrer::Address {
+                address: H160([0x11; 20])
+            })
+        );
+        assert_eq!(v0_4.slippage_bips(), Some(50));
+        assert_eq!(v0_4.order_class(), None);
+
+        let latest = AppDataDocument::parse(
+            r#"{
+                "version": "1.1.0",
+                "appCode": "CoW Swap",
+                "environment": "production",
+                "metadata": {
+                    "referrer": { "code": "COWRANGERS" },
+                    "quote": { "slippageBips": 50, "smartSlippage": true },
+                    "orderClass": { "orderClass": "limit" },
+                    "partnerFee": {
+                        "bps": 10,
+                        "recipient": "0x2222222222222222222222222222222222222222"
+                    },
+                    "widget": { "appCode": "partner" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            latest.referrer(),
+            Some(&Referrer::Code {
+                code: "COWRANGERS".to_string()
+            })
+        );
+        assert_eq!(latest.slippage_bips(), Some(50));
+        assert_eq!(latest.order_class(), Some(OrderClass::Limit));
+        assert_eq!(latest.partner_fee().unwrap().bps, 10);
+        assert_eq!(
+            latest.metadata.as_ref().unwrap().other["widget"],
+            json!({ "appCode": "partner" })
+        );
+
+        assert_eq!(
+            AppDataDocument::parse("{}").unwrap(),
+            AppDataDocument::default()
+        );
+        assert_eq!(
+            AppDataDocument::parse(r#"{"version": "0.3.0"}"#)
+                .unwrap()
+                .version(),
+            Some(Version {
+                major: 0,
+                minor: 3,
+                patch: 0
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_unexpected_informational_fields() {
+        let full = json!({
+            "version": "v2",
+            "metadata": {
+                "referrer": { "kind": "partner", "id": 7 },
+                "quote": { "slippageBips": "a lot" },
+                "orderClass": { "orderClass": "dca" }
+            }
+        });
+        let document = AppDataDocument::parse(&full.to_string()).unwrap();
+        assert_eq!(document.version(), None);
+        assert_eq!(document.referrer(), None);
+        assert_eq!(document.slippage_bips(), None);
+        assert_eq!(document.order_class(), None);
+        assert_eq!(
+            document.metadata.as_ref().unwrap().order_class,
+            Some(Field::Opaque(json!({ "orderClass": "dca" })))
+        );
+        assert_eq!(serde_json::to_value(&document).unwrap(), full);
+
+        // Fields relevant to the protocol still have to match the schema.
+        assert!(matches!(
+            AppDataDocument::parse(r#"{"metadata": {"partnerFee": {"bps": "a lot"}}}"#),
+            Err(Error::Json(_))
+        ));
+        assert!(matches!(AppDataDocument::parse("[]"), Err(Error::Json(_))));
+    }
+
+    #[test]
+    fn legacy_backend_hooks() {
+        let hooks = json!({
+            "pre": [{
+                "target": "0x3333333333333333333333333333333333333333",
+                "callData": "0x",
+                "gasLimit": "21000"
+            }]
+        });
+        let backend =
+            AppDataDocument::parse(&json!({ "backend": { "hooks": hooks } }).to_string()).unwrap();
+        assert_eq!(backend.hooks().unwrap().gas_limit(), 21000);
+
+        // Hooks in `metadata` take precedence over the legacy ones.
+        let both = AppDataDocument::parse(
+            &json!({ "metadata": {}, "backend": { "hooks": hooks } }).to_string(),
+        )
+        .unwrap();
+        assert_eq!(both.hooks(), None);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let full = json!({
+            "version": "1.1.0",
+            "appCode": "CoW Swap",
+            "metadata": {
+                "quote": { "slippageBips": 50 },
+                "replacedOrder": { "uid": format!("0x{}", "01".repeat(56)) },
+                "utm": { "utmSource": "cowmunity" }
+            },
+            "custom": [1, 2, 3]
+        });
+        let document = AppDataDocument::parse(&full.to_string()).unwrap();
+        assert_eq!(document.replaced_order(), Some(OrderUid([1; 56])));
+        assert_eq!(serde_json::to_value(&document).unwrap(), full);
+    }
+
+    #[test]
+    fn validates_bridging() {
+        let document = AppDataDocument::parse(
+            r#"{
+                "version": "1.1.0",
+                "metadata": {
+                    "bridging": {
+                        "targetChainId": "42161",
+                        "adapter": "0x4444444444444444444444444444444444444444",
+                        "recipient": "0x5555555555555555555555555555555555555555"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let bridging = document.bridging().unwrap();
+        assert_eq!(bridging.target_chain_id, 42161);
+        assert_eq!(bridging.recipient, vec![0x55; 20]);
+
+        let adapter = H160([0x44; 20]);
+        assert_eq!(bridging.validate(1, adapter), Ok(()));
+        assert_eq!(
+            bridging.validate(42161, adapter),
+            Err(BridgingError::SameChain)
+        );
+        assert_eq!(
+            bridging.validate(1, H160([0x55; 20])),
+            Err(BridgingError::ReceiverNotAdapter)
+        );
+        let recipient = |recipient: Vec<u8>| Bridging {
+            recipient,
+            ..bridging.clone()
+        };
+        assert_eq!(recipient(vec![0x55; 32]).validate(1, adapter), Ok(()));
+        assert_eq!(
+            recipient(vec![0x55; 19]).validate(1, adapter),
+            Err(BridgingError::InvalidRecipient)
+        );
+        assert_eq!(
+            recipient(vec![0; 20]).validate(1, adapter),
+            Err(BridgingError::InvalidRecipient)
+        );
+    }
+
+    #[test]
+    fn caches_bridging_by_hash() {
+        let full = json!({
+            "metadata": {
+                "bridging": {
+                    "targetChainId": "42161",
+                    "adapter": "0x4444444444444444444444444444444444444444",
+                    "recipient": "0x5555555555555555555555555555555555555555"
+                }
+            }
+        })
+        .to_string();
+        let hash = AppDataHash(hash_full_app_data(full.as_bytes()));
+        let expected = AppDataDocument::parse(&full).unwrap().bridging().cloned();
+        assert!(expected.is_some());
+        assert_eq!(bridging(&hash, &full), expected);
+        // The cached result is returned without parsing the document again.
+        assert_eq!(bridging(&hash, "invalid"), expected);
+
+        let hash = AppDataHash(hash_full_app_data(b"invalid"));
+        assert_eq!(bridging(&hash, "invalid"), None);
+    }
+
+    #[test]
+    fn validates_hash() {
+        let full = r#"{"version":"1.1.0"}"#;
+        let hash = AppDataHash(hash_full_app_data(full.as_bytes()));
+        assert!(AppDataDocument::parse_with_hash(full, &hash).is_ok());
+        assert!(matches!(
+            AppDataDocument::parse_with_hash(full, &AppDataHash([1; 32])),
+            Err(Error::HashMismatch { .. })
+        ));
+    }
+}
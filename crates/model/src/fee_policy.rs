@@ -1,7 +1,8 @@
 use {
+    chrono::{DateTime, Utc},
     number::serialization::HexOrDecimalU256,
     primitive_types::{H160, U256},
-    serde::Serialize,
+    serde::{Deserialize, Serialize},
     serde_with::serde_as,
 };
 
@@ -45,3 +46,102 @@ pub struct ExecutedProtocolFee {
     pub amount: U256,
     pub token: H160,
 }
+
+/// The protocol fee configuration the autopilot applies to orders.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeePolicyConfiguration {
+    /// Policies that get applied to every order of the respective class.
+    pub policies: Vec<ConfiguredFeePolicy>,
+    /// Cap of the partner fees orders specify in their app data as a factor of
+    /// the order's volume.
+    pub max_partner_fee: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfiguredFeePolicy {
+    pub order_class: FeePolicyOrderClass,
+    #[serde(flatten)]
+    pub kind: FeePolicyKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FeePolicyKind {
+    #[serde(rename_all = "camelCase")]
+    Surplus { factor: f64, max_volume_factor: f64 },
+    #[serde(rename_all = "camelCase")]
+    PriceImprovement { factor: f64, max_volume_factor: f64 },
+    #[serde(rename_all = "camelCase")]
+    Volume { factor: f64 },
+}
+
+/// Which orders a fee policy applies to. Limit orders are orders whose limit
+/// price is outside of the market price at the time of the auction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeePolicyOrderClass {
+    Market,
+    Limit,
+    Any,
+}
+
+/// A version of the fee policy configuration.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionedFeePolicyConfiguration {
+    /// Increases whenever the configuration changes.
+    pub version: u64,
+    pub active_since: DateTime<Utc>,
+    #[serde(flatten)]
+    pub configuration: FeePolicyConfiguration,
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn serialize_configuration() {
+        let configuration = VersionedFeePolicyConfiguration {
+            version: 3,
+            active_since: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            configuration: FeePolicyConfiguration {
+                policies: vec![
+                    ConfiguredFeePolicy {
+                        order_class: FeePolicyOrderClass::Limit,
+                        kind: FeePolicyKind::PriceImprovement {
+                            factor: 0.5,
+                            max_volume_factor: 0.01,
+                        },
+                    },
+                    ConfiguredFeePolicy {
+                        order_class: FeePolicyOrderClass::Any,
+                        kind: FeePolicyKind::Volume { factor: 0.0002 },
+                    },
+                ],
+                max_partner_fee: 0.01,
+            },
+        };
+        let expected = json!({
+            "version": 3,
+            "activeSince": "2023-11-14T22:13:20Z",
+            "policies": [
+                {
+                    "orderClass": "limit",
+                    "kind": "priceImprovement",
+                    "factor": 0.5,
+                    "maxVolumeFactor": 0.01,
+                },
+                { "orderClass": "any", "kind": "volume", "factor": 0.0002 },
+            ],
+            "maxPartnerFee": 0.01,
+        });
+        assert_eq!(serde_json::to_value(&configuration).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<VersionedFeePolicyConfiguration>(expected).unwrap(),
+            configuration
+        );
+    }
+}
@@ -1,8 +1,10 @@
 //! Contains models that are shared between the orderbook and the solver.
 
+pub mod app_data;
 pub mod auction;
 pub mod fee_policy;
 pub mod interaction;
+pub mod notification;
 pub mod order;
 pub mod quote;
 pub mod signature;
@@ -0,0 +1,167 @@
+//! Notification preferences accounts register to receive digests of their
+//! order activity.
+
+use {
+    crate::{
+        signature::{EcdsaSignature, EcdsaSigningScheme},
+        DomainSeparator,
+    },
+    anyhow::Result,
+    hex_literal::hex,
+    primitive_types::H160,
+    serde::{Deserialize, Serialize},
+    web3::signing::{self, SecretKeyRef},
+};
+
+/// Where and about what an account wants to be notified.
+///
+/// Preferences without any channel or event opt the account out of all
+/// notifications.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreferences {
+    /// URL digests get posted to.
+    pub webhook: Option<String>,
+    /// Email address digests get sent to.
+    pub email: Option<String>,
+    /// Whether digests include filled orders.
+    pub fills: bool,
+    /// Whether digests include orders that expired without being filled.
+    pub expiries: bool,
+    /// Unix timestamp of when the preferences were signed. Preferences only
+    /// replace previously registered ones that were issued earlier.
+    pub issued_at: u32,
+}
+
+impl NotificationPreferences {
+    /// The EIP-712 type hash for notification preferences. Computed with:
+    /// `keccak256("NotificationPreferences(string webhook,string email,bool
+    /// fills,bool expiries,uint32 issuedAt)")`.
+    const TYPE_HASH: [u8; 32] =
+        hex!("02cf50bf4efb274a94fc88a77444405a1092034bf666d43b61573102c6a06432");
+
+    /// Whether the preferences opt the account out of all notifications.
+    pub fn is_opt_out(&self) -> bool {
+        (self.webhook.is_none() && self.email.is_none()) || (!self.fills && !self.expiries)
+    }
+
+    /// Missing channels are hashed as empty strings.
+    pub fn hash_struct(&self) -> [u8; 32] {
+        let mut hash_data = [0u8; 192];
+        hash_data[0..32].copy_from_slice(&Self::TYPE_HASH);
+        hash_data[32..64].copy_from_slice(&signing::keccak256(
+            self.webhook.as_deref().unwrap_or_default().as_bytes(),
+        ));
+        hash_data[64..96].copy_from_slice(&signing::keccak256(
+            self.email.as_deref().unwrap_or_default().as_bytes(),
+        ));
+        hash_data[127] = self.fills as u8;
+        hash_data[159] = self.expiries as u8;
+        hash_data[188..192].copy_from_slice(&self.issued_at.to_be_bytes());
+        signing::keccak256(&hash_data)
+    }
+
+    pub fn sign(
+        self,
+        signing_scheme: EcdsaSigningScheme,
+        domain_separator: &DomainSeparator,
+        key: SecretKeyRef,
+    ) -> SignedNotificationPreferences {
+        let signature =
+            EcdsaSignature::sign(signing_scheme, domain_separator, &self.hash_struct(), key);
+        SignedNotificationPreferences {
+            data: self,
+            signature,
+            signing_scheme,
+        }
+    }
+}
+
+/// Notification preferences signed by the account they belong to.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedNotificationPreferences {
+    #[serde(flatten)]
+    pub data: NotificationPreferences,
+    pub signature: EcdsaSignature,
+    pub signing_scheme: EcdsaSigningScheme,
+}
+
+impl SignedNotificationPreferences {
+    /// Returns the account that signed the preferences.
+    pub fn validate(&self, domain_separator: &DomainSeparator) -> Result<H160> {
+        Ok(self
+            .signature
+            .recover(
+                self.signing_scheme,
+                domain_separator,
+                &self.data.hash_struct(),
+            )?
+            .signer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, secp256k1::SecretKey, serde_json::json, web3::signing::Key};
+
+    #[test]
+    fn type_hash_matches_type() {
+        assert_eq!(
+            NotificationPreferences::TYPE_HASH,
+            signing::keccak256(
+                b"NotificationPreferences(string webhook,string email,bool fills,bool \
+                  expiries,uint32 issuedAt)"
+            )
+        );
+    }
+
+    #[test]
+    fn recovers_signer() {
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let key = SecretKeyRef::new(&key);
+        let domain_separator = DomainSeparator([2; 32]);
+        let preferences = NotificationPreferences {
+            webhook: Some("https://example.com/hook".to_string()),
+            email: None,
+            fills: true,
+            expiries: false,
+            issued_at: 1_700_000_000,
+        };
+
+        for signing_scheme in [EcdsaSigningScheme::Eip712, EcdsaSigningScheme::EthSign] {
+            let mut signed = preferences
+                .clone()
+                .sign(signing_scheme, &domain_separator, key);
+            assert_eq!(signed.validate(&domain_separator).unwrap(), key.address());
+
+            signed.data.expiries = true;
+            assert_ne!(signed.validate(&domain_separator).unwrap(), key.address());
+        }
+    }
+
+    #[test]
+    fn deserializes_signed_preferences() {
+        let signed: SignedNotificationPreferences = serde_json::from_value(json!({
+            "webhook": "https://example.com/hook",
+            "email": null,
+            "fills": true,
+            "expiries": true,
+            "issuedAt": 1700000000,
+            "signature": format!("0x{}", "00".repeat(65)),
+            "signingScheme": "eip712",
+        }))
+        .unwrap();
+        assert_eq!(
+            signed.data,
+            NotificationPreferences {
+                webhook: Some("https://example.com/hook".to_string()),
+                email: None,
+                fills: true,
+                expiries: true,
+                issued_at: 1_700_000_000,
+            }
+        );
+        assert!(!signed.data.is_opt_out());
+    }
+}
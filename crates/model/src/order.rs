@@ -3,8 +3,9 @@
 
 use {
     crate::{
+        app_data::Bridging,
         interaction::InteractionData,
-        quote::QuoteId,
+        quote::{QuoteId, SignedQuoteCommitment},
         signature::{self, EcdsaSignature, EcdsaSigningScheme, Signature},
         DomainSeparator,
         TokenPair,
@@ -17,6 +18,7 @@ use {
     num::BigUint,
     number::serialization::HexOrDecimalU256,
     primitive_types::{H160, H256, U256},
+    secp256k1::SecretKey,
     serde::{de, Deserialize, Deserializer, Serialize, Serializer},
     serde_with::{serde_as, DisplayFromStr},
     std::{
@@ -83,109 +85,288 @@ impl Order {
     }
 }
 
+/// Builds [`Order`]s and [`OrderCreation`]s for tests and integrations.
+///
+/// The order only gets signed when it is built, so the setters can be called
+/// in any order. [`OrderBuilder::try_build`] and
+/// [`OrderBuilder::build_creation`] check the order for mistakes the orderbook
+/// would reject it for.
 #[derive(Clone, Default, Debug)]
-pub struct OrderBuilder(Order);
+pub struct OrderBuilder {
+    order: Order,
+    app_data: Option<OrderCreationAppData>,
+    quote_id: Option<QuoteId>,
+    signer: Option<Signer>,
+}
+
+#[derive(Clone, Debug)]
+enum Signer {
+    Ecdsa {
+        scheme: EcdsaSigningScheme,
+        domain: DomainSeparator,
+        key: SecretKey,
+    },
+    Eip1271 {
+        owner: H160,
+        signature: Vec<u8>,
+    },
+    PreSign {
+        owner: H160,
+    },
+}
 
 impl OrderBuilder {
     pub fn with_sell_token(mut self, sell_token: H160) -> Self {
-        self.0.data.sell_token = sell_token;
+        self.order.data.sell_token = sell_token;
         self
     }
 
     pub fn with_buy_token(mut self, buy_token: H160) -> Self {
-        self.0.data.buy_token = buy_token;
+        self.order.data.buy_token = buy_token;
         self
     }
 
     pub fn with_sell_amount(mut self, sell_amount: U256) -> Self {
-        self.0.data.sell_amount = sell_amount;
+        self.order.data.sell_amount = sell_amount;
         self
     }
 
     pub fn with_buy_amount(mut self, buy_amount: U256) -> Self {
-        self.0.data.buy_amount = buy_amount;
+        self.order.data.buy_amount = buy_amount;
         self
     }
 
     pub fn with_valid_to(mut self, valid_to: u32) -> Self {
-        self.0.data.valid_to = valid_to;
+        self.order.data.valid_to = valid_to;
         self
     }
 
+    /// Makes the order valid for the specified number of seconds from now.
+    pub fn with_validity(self, seconds: u32) -> Self {
+        self.with_valid_to(crate::time::now_in_epoch_seconds() + seconds)
+    }
+
     pub fn with_app_data(mut self, app_data: [u8; 32]) -> Self {
-        self.0.data.app_data = AppDataHash(app_data);
+        self.order.data.app_data = AppDataHash(app_data);
+        self
+    }
+
+    /// Sets the app data the way it gets submitted to the orderbook. The
+    /// signed app data hash is derived from it.
+    pub fn with_creation_app_data(mut self, app_data: OrderCreationAppData) -> Self {
+        self.order.data.app_data = app_data.hash();
+        self.order.metadata.full_app_data = match &app_data {
+            OrderCreationAppData::Hash { .. } => None,
+            OrderCreationAppData::Full { full } | OrderCreationAppData::Both { full, .. } => {
+                Some(full.clone())
+            }
+        };
+        self.app_data = Some(app_data);
         self
     }
 
     pub fn with_receiver(mut self, receiver: Option<H160>) -> Self {
-        self.0.data.receiver = receiver;
+        self.order.data.receiver = receiver;
         self
     }
 
     pub fn with_fee_amount(mut self, fee_amount: U256) -> Self {
-        self.0.data.fee_amount = fee_amount;
+        self.order.data.fee_amount = fee_amount;
         self
     }
 
     pub fn with_kind(mut self, kind: OrderKind) -> Self {
-        self.0.data.kind = kind;
+        self.order.data.kind = kind;
         self
     }
 
     pub fn with_partially_fillable(mut self, partially_fillable: bool) -> Self {
-        self.0.data.partially_fillable = partially_fillable;
+        self.order.data.partially_fillable = partially_fillable;
         self
     }
 
     pub fn with_sell_token_balance(mut self, balance: SellTokenSource) -> Self {
-        self.0.data.sell_token_balance = balance;
+        self.order.data.sell_token_balance = balance;
         self
     }
 
     pub fn with_buy_token_balance(mut self, balance: BuyTokenDestination) -> Self {
-        self.0.data.buy_token_balance = balance;
+        self.order.data.buy_token_balance = balance;
         self
     }
 
     pub fn with_creation_date(mut self, creation_date: DateTime<Utc>) -> Self {
-        self.0.metadata.creation_date = creation_date;
+        self.order.metadata.creation_date = creation_date;
+        self
+    }
+
+    pub fn with_quote_id(mut self, quote_id: QuoteId) -> Self {
+        self.quote_id = Some(quote_id);
         self
     }
 
-    /// Sets owner, uid, signature.
+    /// Signs the order with the key once it is built, which also sets its
+    /// owner and uid.
     pub fn sign_with(
         mut self,
         signing_scheme: EcdsaSigningScheme,
         domain: &DomainSeparator,
         key: SecretKeyRef,
     ) -> Self {
-        self.0.metadata.owner = key.address();
-        self.0.metadata.uid = self.0.data.uid(domain, &key.address());
-        self.0.signature =
-            EcdsaSignature::sign(signing_scheme, domain, &self.0.data.hash_struct(), key)
-                .to_signature(signing_scheme);
+        self.signer = Some(Signer::Ecdsa {
+            scheme: signing_scheme,
+            domain: *domain,
+            key: *key,
+        });
         self
     }
 
     pub fn with_eip1271(mut self, owner: H160, signature: Vec<u8>) -> Self {
-        self.0.metadata.owner = owner;
-        self.0.signature = Signature::Eip1271(signature);
+        self.signer = Some(Signer::Eip1271 { owner, signature });
         self
     }
 
     pub fn with_presign(mut self, owner: H160) -> Self {
-        self.0.metadata.owner = owner;
-        self.0.signature = Signature::PreSign;
+        self.signer = Some(Signer::PreSign { owner });
         self
     }
 
     pub fn with_class(mut self, class: OrderClass) -> Self {
-        self.0.metadata.class = class;
+        self.order.metadata.class = class;
         self
     }
 
     pub fn build(self) -> Order {
-        self.0
+        self.signed().order
+    }
+
+    /// Builds the order after checking that its data is valid and that its
+    /// uid and ECDSA signature match the owner for the specified domain.
+    pub fn try_build(self, domain: &DomainSeparator) -> Result<Order, OrderBuildError> {
+        let order = self.validated()?.signed().order;
+        verify_signer(&order.signature, domain, &order.data, order.metadata.owner)?;
+        if order.metadata.uid != order.data.uid(domain, &order.metadata.owner) {
+            return Err(OrderBuildError::UidMismatch);
+        }
+        Ok(order)
+    }
+
+    /// Validates and signs the order the way it gets submitted to the
+    /// orderbook.
+    pub fn build_creation(self) -> Result<OrderCreation, OrderBuildError> {
+        if self.signer.is_none() {
+            return Err(OrderBuildError::MissingSignature);
+        }
+        let builder = self.validated()?.signed();
+        let Order {
+            data,
+            metadata,
+            signature,
+            ..
+        } = builder.order;
+        Ok(OrderCreation {
+            sell_token: data.sell_token,
+            buy_token: data.buy_token,
+            receiver: data.receiver,
+            sell_amount: data.sell_amount,
+            buy_amount: data.buy_amount,
+            valid_to: data.valid_to,
+            fee_amount: data.fee_amount,
+            kind: data.kind,
+            partially_fillable: data.partially_fillable,
+            sell_token_balance: data.sell_token_balance,
+            buy_token_balance: data.buy_token_balance,
+            from: Some(metadata.owner),
+            signature,
+            quote_id: builder.quote_id,
+            app_data: builder.app_data.unwrap_or_else(|| data.app_data.into()),
+            ..Default::default()
+        })
+    }
+
+    fn validated(self) -> Result<Self, OrderBuildError> {
+        let data = &self.order.data;
+        data.check()?;
+        let now = crate::time::now_in_epoch_seconds();
+        if data.valid_to <= now {
+            return Err(OrderBuildError::Expired {
+                valid_to: data.valid_to,
+                now,
+            });
+        }
+        if let Some(OrderCreationAppData::Both { expected, .. }) = &self.app_data {
+            if *expected != data.app_data {
+                return Err(OrderBuildError::AppDataHashMismatch);
+            }
+        }
+        Ok(self)
+    }
+
+    fn signed(mut self) -> Self {
+        let order = &mut self.order;
+        match &self.signer {
+            Some(Signer::Ecdsa {
+                scheme,
+                domain,
+                key,
+            }) => {
+                let key = SecretKeyRef::new(key);
+                order.metadata.owner = key.address();
+                order.metadata.uid = order.data.uid(domain, &key.address());
+                order.signature =
+                    EcdsaSignature::sign(*scheme, domain, &order.data.hash_struct(), key)
+                        .to_signature(*scheme);
+            }
+            Some(Signer::Eip1271 { owner, signature }) => {
+                order.metadata.owner = *owner;
+                order.signature = Signature::Eip1271(signature.clone());
+            }
+            Some(Signer::PreSign { owner }) => {
+                order.metadata.owner = *owner;
+                order.signature = Signature::PreSign;
+            }
+            None => (),
+        }
+        self
+    }
+}
+
+/// Reasons why an order can't be built.
+#[derive(Debug, thiserror::Error)]
+pub enum OrderBuildError {
+    #[error(transparent)]
+    InvalidData(#[from] OrderDataError),
+    #[error("order expired at {valid_to} (now {now})")]
+    Expired { valid_to: u32, now: u32 },
+    #[error("app data does not match the expected app data hash")]
+    AppDataHashMismatch,
+    #[error("order is not signed")]
+    MissingSignature,
+    #[error("invalid signature: {0}")]
+    InvalidSignature(anyhow::Error),
+    #[error("signature recovers to {recovered:?} instead of the owner {expected:?}")]
+    UnexpectedSigner { expected: H160, recovered: H160 },
+    #[error("uid does not match the order data and owner")]
+    UidMismatch,
+}
+
+/// Checks that an ECDSA signature recovers to the owner. Other signatures can
+/// only be verified on-chain.
+fn verify_signer(
+    signature: &Signature,
+    domain: &DomainSeparator,
+    data: &OrderData,
+    owner: H160,
+) -> Result<(), OrderBuildError> {
+    let recovered = signature
+        .recover(domain, &data.hash_struct())
+        .map_err(OrderBuildError::InvalidSignature)?;
+    match recovered {
+        Some(recovered) if recovered.signer != owner => Err(OrderBuildError::UnexpectedSigner {
+            expected: owner,
+            recovered: recovered.signer,
+        }),
+        _ => Ok(()),
     }
 }
 
@@ -272,6 +453,14 @@ impl OrderData {
         )
     }
 
+    /// Checks the rules the orderbook enforces for all orders regardless of
+    /// its configuration.
+    pub fn check(&self) -> Result<(), OrderDataError> {
+        check_token_balances(self.sell_token_balance, self.buy_token_balance)?;
+        check_tokens(self.sell_token, self.buy_token)?;
+        check_amounts(self.sell_amount, self.buy_amount)
+    }
+
     /// Checks if the order is a market order.
     pub fn within_market(&self, quote: QuoteAmounts) -> bool {
         (self.sell_amount + self.fee_amount).full_mul(quote.buy)
@@ -318,6 +507,13 @@ pub struct OrderCreation {
     pub quote_id: Option<QuoteId>,
     #[serde(flatten)]
     pub app_data: OrderCreationAppData,
+    /// Signed commitment of the backend to honor the referenced quote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_commitment: Option<SignedQuoteCommitment>,
+    /// Hide the order from the public order APIs until the next auction gets
+    /// cut.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub embargo: bool,
 }
 
 impl OrderCreation {
@@ -399,6 +595,69 @@ impl OrderCreation {
 
         Ok(verified_owner)
     }
+
+    /// The uid of the order if its owner is specified.
+    pub fn uid(&self, domain: &DomainSeparator) -> Option<OrderUid> {
+        self.from.map(|owner| self.data().uid(domain, &owner))
+    }
+}
+
+/// Reasons why the orderbook rejects order data regardless of its
+/// configuration.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum OrderDataError {
+    #[error("sell and buy token are the same")]
+    SameBuyAndSellToken,
+    #[error("the native token can't be sold")]
+    InvalidNativeSellToken,
+    #[error("sell or buy amount is zero")]
+    ZeroAmount,
+    #[error("unsupported buy token destination {0:?}")]
+    UnsupportedBuyTokenDestination(BuyTokenDestination),
+    #[error("unsupported sell token source {0:?}")]
+    UnsupportedSellTokenSource(SellTokenSource),
+}
+
+/// Checks that the orderbook supports where the sold tokens come from and
+/// where the bought tokens go to.
+pub fn check_token_balances(
+    sell_token_balance: SellTokenSource,
+    buy_token_balance: BuyTokenDestination,
+) -> Result<(), OrderDataError> {
+    if buy_token_balance != BuyTokenDestination::Erc20 {
+        return Err(OrderDataError::UnsupportedBuyTokenDestination(
+            buy_token_balance,
+        ));
+    }
+    if !matches!(
+        sell_token_balance,
+        SellTokenSource::Erc20 | SellTokenSource::External
+    ) {
+        return Err(OrderDataError::UnsupportedSellTokenSource(
+            sell_token_balance,
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that an order trades two different tokens and doesn't try to sell
+/// the native token marker.
+pub fn check_tokens(sell_token: H160, buy_token: H160) -> Result<(), OrderDataError> {
+    if sell_token == buy_token {
+        return Err(OrderDataError::SameBuyAndSellToken);
+    }
+    if sell_token == BUY_ETH_ADDRESS {
+        return Err(OrderDataError::InvalidNativeSellToken);
+    }
+    Ok(())
+}
+
+/// Checks that an order trades non-zero amounts.
+pub fn check_amounts(sell_amount: U256, buy_amount: U256) -> Result<(), OrderDataError> {
+    if sell_amount.is_zero() || buy_amount.is_zero() {
+        return Err(OrderDataError::ZeroAmount);
+    }
+    Ok(())
 }
 
 // Note that the order of the variants is important for deserialization.
@@ -654,6 +913,16 @@ pub struct OnchainOrderData {
     pub placement_error: Option<OnchainOrderPlacementError>,
 }
 
+/// Reason why an open order can currently not be settled.
+#[derive(Eq, PartialEq, Clone, Copy, Deserialize, Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum InvalidationReason {
+    /// One of the order's tokens is not supported. The order will not be part
+    /// of any auction until the token becomes supported again.
+    #[serde(rename_all = "camelCase")]
+    UnsupportedToken { token: H160 },
+}
+
 /// An order as provided to the orderbook by the frontend.
 #[serde_as]
 #[derive(Eq, PartialEq, Clone, Default, Deserialize, Serialize, DeriveDebug)]
@@ -716,6 +985,16 @@ pub struct OrderMetadata {
     /// Full app data that `OrderData::app_data` is a hash of. Can be None if
     /// the backend doesn't know about the full app data.
     pub full_app_data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invalidation_reason: Option<InvalidationReason>,
+    /// Set if the order was submitted with an embargo. The order was hidden
+    /// from the public order APIs until the first auction cut after its
+    /// creation, but at most until this time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embargo_until: Option<DateTime<Utc>>,
+    /// Where the bought tokens get bridged to according to the full app data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bridging: Option<Bridging>,
 }
 
 // uid as 56 bytes: 32 for orderDigest, 20 for ownerAddress and 4 for validTo
@@ -1045,6 +1324,10 @@ mod tests {
                     "post": [],
             },
             "fullAppData": "123",
+            "invalidationReason": {
+                "kind": "unsupportedToken",
+                "token": "0x000000000000000000000000000000000000000a",
+            },
         });
         let signing_scheme = EcdsaSigningScheme::Eip712;
         let expected = Order {
@@ -1067,6 +1350,9 @@ mod tests {
                 full_fee_amount: U256::MAX,
                 solver_fee: U256::MAX,
                 full_app_data: Some("123".to_string()),
+                invalidation_reason: Some(InvalidationReason::UnsupportedToken {
+                    token: H160::from_low_u64_be(10),
+                }),
                 ..Default::default()
             },
             data: OrderData {
@@ -1145,6 +1431,8 @@ mod tests {
                 from,
                 signature,
                 quote_id: Some(42),
+                quote_commitment: None,
+                embargo: false,
             };
             let order_json = json!({
                 "sellToken": "0x1111111111111111111111111111111111111111",
@@ -1417,4 +1705,149 @@ mod tests {
             assert_eq!(cancellations.hash_struct(), struct_hash);
         }
     }
+
+    #[test]
+    fn order_builder_creation() {
+        let domain = DomainSeparator([7; 32]);
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let owner = SecretKeyRef::new(&key).address();
+        let builder = OrderBuilder::default()
+            .with_sell_token(H160([1; 20]))
+            .with_buy_token(H160([2; 20]))
+            .with_sell_amount(10.into())
+            .with_buy_amount(5.into())
+            .with_validity(300)
+            .sign_with(EcdsaSigningScheme::Eip712, &domain, SecretKeyRef::new(&key));
+
+        // Setters called after the signing key still end up in the signed data.
+        let order = builder
+            .clone()
+            .with_kind(OrderKind::Buy)
+            .build_creation()
+            .unwrap();
+        assert_eq!(order.kind, OrderKind::Buy);
+        assert_eq!(order.verify_owner(&domain, None).unwrap(), owner);
+        assert_eq!(order.uid(&domain), Some(order.data().uid(&domain, &owner)));
+
+        let presign = builder
+            .clone()
+            .with_presign(H160([3; 20]))
+            .build_creation()
+            .unwrap();
+        assert_eq!(presign.from, Some(H160([3; 20])));
+        assert_eq!(presign.signature, Signature::PreSign);
+
+        let full = "{}".to_string();
+        let order = builder
+            .clone()
+            .with_creation_app_data(OrderCreationAppData::Full { full: full.clone() })
+            .build_creation()
+            .unwrap();
+        assert_eq!(order.app_data, OrderCreationAppData::Full { full });
+        assert_eq!(order.verify_owner(&domain, None).unwrap(), owner);
+
+        assert!(matches!(
+            builder
+                .clone()
+                .with_buy_token(H160([1; 20]))
+                .build_creation(),
+            Err(OrderBuildError::InvalidData(
+                OrderDataError::SameBuyAndSellToken
+            ))
+        ));
+        assert!(matches!(
+            builder.clone().with_sell_amount(0.into()).build_creation(),
+            Err(OrderBuildError::InvalidData(OrderDataError::ZeroAmount))
+        ));
+        assert!(matches!(
+            builder
+                .clone()
+                .with_buy_token_balance(BuyTokenDestination::Internal)
+                .build_creation(),
+            Err(OrderBuildError::InvalidData(
+                OrderDataError::UnsupportedBuyTokenDestination(_)
+            ))
+        ));
+        assert!(matches!(
+            builder.clone().with_valid_to(1).build_creation(),
+            Err(OrderBuildError::Expired { valid_to: 1, .. })
+        ));
+        assert!(matches!(
+            builder
+                .clone()
+                .with_creation_app_data(OrderCreationAppData::Both {
+                    full: "{}".to_string(),
+                    expected: AppDataHash([1; 32]),
+                })
+                .build_creation(),
+            Err(OrderBuildError::AppDataHashMismatch)
+        ));
+        assert!(matches!(
+            OrderBuilder {
+                signer: None,
+                ..builder
+            }
+            .build_creation(),
+            Err(OrderBuildError::MissingSignature)
+        ));
+    }
+
+    #[test]
+    fn order_builder_try_build() {
+        let domain = DomainSeparator([7; 32]);
+        let key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let builder = OrderBuilder::default()
+            .with_sell_token(H160([1; 20]))
+            .with_buy_token(H160([2; 20]))
+            .with_sell_amount(10.into())
+            .with_buy_amount(5.into())
+            .with_valid_to(u32::MAX)
+            .sign_with(EcdsaSigningScheme::Eip712, &domain, SecretKeyRef::new(&key));
+
+        let order = builder.clone().try_build(&domain).unwrap();
+        assert_eq!(order.metadata.owner, SecretKeyRef::new(&key).address());
+        // The signature only recovers to the owner for the signing domain.
+        assert!(matches!(
+            builder.try_build(&DomainSeparator([8; 32])),
+            Err(OrderBuildError::UnexpectedSigner { .. })
+        ));
+    }
+
+    #[test]
+    fn order_data_rules() {
+        let data = OrderData {
+            sell_token: H160([1; 20]),
+            buy_token: H160([2; 20]),
+            sell_amount: 1.into(),
+            buy_amount: 1.into(),
+            ..Default::default()
+        };
+        assert_eq!(data.check(), Ok(()));
+        assert_eq!(
+            OrderData {
+                sell_token: BUY_ETH_ADDRESS,
+                ..data
+            }
+            .check(),
+            Err(OrderDataError::InvalidNativeSellToken)
+        );
+        assert_eq!(
+            OrderData {
+                buy_amount: 0.into(),
+                ..data
+            }
+            .check(),
+            Err(OrderDataError::ZeroAmount)
+        );
+        assert_eq!(
+            OrderData {
+                sell_token_balance: SellTokenSource::Internal,
+                ..data
+            }
+            .check(),
+            Err(OrderDataError::UnsupportedSellTokenSource(
+                SellTokenSource::Internal
+            ))
+        );
+    }
 }
@@ -1,16 +1,18 @@
 use {
     crate::{
         order::{BuyTokenDestination, OrderCreationAppData, OrderKind, SellTokenSource},
-        signature::SigningScheme,
+        signature::{EcdsaSignature, EcdsaSigningScheme, SigningScheme},
         time,
+        DomainSeparator,
     },
-    anyhow::bail,
+    anyhow::{bail, Result},
     app_data::AppDataHash,
     chrono::{DateTime, Utc},
     number::{nonzero::U256 as NonZeroU256, serialization::HexOrDecimalU256},
     primitive_types::{H160, U256},
     serde::{de, ser::SerializeStruct as _, Deserialize, Deserializer, Serialize, Serializer},
     serde_with::serde_as,
+    web3::signing::{self, SecretKeyRef},
 };
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
@@ -293,6 +295,96 @@ pub struct OrderQuoteResponse {
     pub expiration: DateTime<Utc>,
     pub id: Option<QuoteId>,
     pub verified: bool,
+    /// Commitment of the backend to honor the quote for orders placed before
+    /// the commitment expires. Only returned if quote commitments are enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<SignedQuoteCommitment>,
+}
+
+/// The terms of a quote the backend commits to honor until `expiry`.
+#[serde_as]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteCommitment {
+    pub quote_id: QuoteId,
+    pub sell_token: H160,
+    pub buy_token: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub sell_amount: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub buy_amount: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub fee_amount: U256,
+    /// Unix timestamp in seconds after which the commitment is no longer
+    /// honored.
+    pub expiry: u32,
+}
+
+impl QuoteCommitment {
+    /// Returns the value of hashStruct() over the commitment as defined by
+    /// EIP-712 for the type
+    /// `QuoteCommitment(int64 quoteId,address sellToken,address buyToken,
+    /// uint256 sellAmount,uint256 buyAmount,uint256 feeAmount,uint32 expiry)`.
+    ///
+    /// https://eips.ethereum.org/EIPS/eip-712#definition-of-hashstruct
+    pub fn hash_struct(&self) -> [u8; 32] {
+        lazy_static::lazy_static! {
+            static ref TYPE_HASH: [u8; 32] = signing::keccak256(
+                b"QuoteCommitment(int64 quoteId,address sellToken,address buyToken,uint256 \
+                  sellAmount,uint256 buyAmount,uint256 feeAmount,uint32 expiry)",
+            );
+        }
+
+        let mut hash_data = [0u8; 256];
+        hash_data[0..32].copy_from_slice(&*TYPE_HASH);
+        // Signed integers are sign extended to 256 bits.
+        if self.quote_id < 0 {
+            hash_data[32..56].fill(0xff);
+        }
+        hash_data[56..64].copy_from_slice(&self.quote_id.to_be_bytes());
+        hash_data[76..96].copy_from_slice(self.sell_token.as_fixed_bytes());
+        hash_data[108..128].copy_from_slice(self.buy_token.as_fixed_bytes());
+        self.sell_amount.to_big_endian(&mut hash_data[128..160]);
+        self.buy_amount.to_big_endian(&mut hash_data[160..192]);
+        self.fee_amount.to_big_endian(&mut hash_data[192..224]);
+        hash_data[252..256].copy_from_slice(&self.expiry.to_be_bytes());
+        signing::keccak256(&hash_data)
+    }
+
+    pub fn sign(self, domain: &DomainSeparator, key: SecretKeyRef) -> SignedQuoteCommitment {
+        SignedQuoteCommitment {
+            commitment: self,
+            signature: EcdsaSignature::sign(
+                EcdsaSigningScheme::Eip712,
+                domain,
+                &self.hash_struct(),
+                key,
+            ),
+        }
+    }
+}
+
+/// A quote commitment with the EIP-712 signature of the backend.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedQuoteCommitment {
+    #[serde(flatten)]
+    pub commitment: QuoteCommitment,
+    pub signature: EcdsaSignature,
+}
+
+impl SignedQuoteCommitment {
+    /// Recovers the address of the key that signed the commitment.
+    pub fn signer(&self, domain: &DomainSeparator) -> Result<H160> {
+        Ok(self
+            .signature
+            .recover(
+                EcdsaSigningScheme::Eip712,
+                domain,
+                &self.commitment.hash_struct(),
+            )?
+            .signer)
+    }
 }
 
 #[cfg(test)]
@@ -526,4 +618,39 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn quote_commitment_signature_roundtrip() {
+        let key = web3::signing::SecretKey::from_slice(&[1; 32]).unwrap();
+        let signer = web3::signing::Key::address(&SecretKeyRef::new(&key));
+        let domain = DomainSeparator::default();
+        let commitment = QuoteCommitment {
+            quote_id: 42,
+            sell_token: H160([1; 20]),
+            buy_token: H160([2; 20]),
+            sell_amount: 100.into(),
+            buy_amount: 200.into(),
+            fee_amount: 3.into(),
+            expiry: 1337,
+        }
+        .sign(&domain, SecretKeyRef::new(&key));
+        assert_eq!(commitment.signer(&domain).unwrap(), signer);
+
+        let json = serde_json::to_value(commitment).unwrap();
+        assert_eq!(json["quoteId"], 42);
+        assert_eq!(json["feeAmount"], "3");
+        assert_eq!(
+            serde_json::from_value::<SignedQuoteCommitment>(json).unwrap(),
+            commitment
+        );
+
+        let tampered = SignedQuoteCommitment {
+            commitment: QuoteCommitment {
+                fee_amount: 0.into(),
+                ..commitment.commitment
+            },
+            ..commitment
+        };
+        assert_ne!(tampered.signer(&domain).ok(), Some(signer));
+    }
 }
@@ -16,6 +16,18 @@ pub struct SolverCompetitionDB {
     pub competition_simulation_block: u64,
     pub auction: CompetitionAuction,
     pub solutions: Vec<SolverSettlement>,
+    /// How the winners were selected. Not recorded for older competitions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winner_selection: Option<WinnerSelection>,
+}
+
+/// The strategies the winners of a competition can be selected with.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum WinnerSelection {
+    Score,
+    SurplusPerGas,
+    FairCombinatorial,
 }
 
 /// Returned by the `/solver_competition` endpoint.
@@ -54,6 +66,21 @@ pub struct SolverSettlement {
     pub orders: Vec<Order>,
     #[serde(default)]
     pub is_winner: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<SolverTimings>,
+}
+
+/// Milliseconds the solver engine reported to have spent in the different
+/// phases of solving the auction.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SolverTimings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidity_processing: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimization: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<u64>,
 }
 
 #[serde_as]
@@ -168,6 +195,9 @@ mod tests {
                         }
                     ],
                     "isWinner": true,
+                    "timings": {
+                        "optimization": 250,
+                    },
                 },
             ],
         });
@@ -210,7 +240,12 @@ mod tests {
                         },
                     ],
                     is_winner: true,
+                    timings: Some(SolverTimings {
+                        optimization: Some(250),
+                        ..Default::default()
+                    }),
                 }],
+                winner_selection: None,
             },
         };
 
@@ -32,6 +32,29 @@ pub struct Trade {
     pub executed_protocol_fees: Vec<ExecutedProtocolFee>,
 }
 
+/// A trade executed by a settlement transaction together with the solver that
+/// submitted it.
+#[serde_as]
+#[derive(PartialEq, Clone, Debug, Default, Serialize)]
+#[cfg_attr(any(test, feature = "e2e"), derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub struct SettledTrade {
+    pub order_uid: OrderUid,
+    #[serde_as(as = "DisplayFromStr")]
+    pub sell_amount: BigUint,
+    #[serde_as(as = "DisplayFromStr")]
+    pub buy_amount: BigUint,
+    /// The fee taken by the settlement contract in the sell token.
+    #[serde_as(as = "DisplayFromStr")]
+    pub fee_amount: BigUint,
+    /// The fee charged for the order in the auction, if the settlement was
+    /// already associated with its auction.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub executed_fee: Option<BigUint>,
+    pub executed_fee_token: Option<H160>,
+    pub solver: H160,
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -1,8 +1,9 @@
 use {
     anyhow::{ensure, Result},
     bigdecimal::{num_bigint::ToBigInt, BigDecimal},
-    num::{bigint::Sign, rational::Ratio, BigInt, BigRational, BigUint, Zero},
-    primitive_types::U256,
+    num::{bigint::Sign, rational::Ratio, BigInt, BigRational, BigUint, Integer, Zero},
+    primitive_types::{U256, U512},
+    std::fmt,
 };
 
 pub fn u256_to_big_uint(input: &U256) -> BigUint {
@@ -80,9 +81,161 @@ pub fn big_decimal_to_big_rational(value: &BigDecimal) -> BigRational {
     BigRational::new(adjusted_numer, denom)
 }
 
+/// Converts an amount of the smallest unit of a token (e.g. wei) into a
+/// decimal amount of whole tokens (e.g. ether). The conversion is exact.
+pub fn to_base_units(amount: U256, decimals: u8) -> BigDecimal {
+    BigDecimal::new(u256_to_big_int(&amount), decimals.into())
+}
+
+/// Converts a decimal amount of whole tokens into the smallest unit of the
+/// token. Fractions of the smallest unit get rounded down, so the result never
+/// exceeds the decimal amount.
+pub fn from_base_units(amount: BigDecimal, decimals: u8) -> Result<U256> {
+    scale_to_smallest_unit(&amount, decimals, false)
+}
+
+/// Like [`from_base_units`] but rounds fractions of the smallest unit up, so
+/// the result is never less than the decimal amount.
+pub fn from_base_units_ceil(amount: BigDecimal, decimals: u8) -> Result<U256> {
+    scale_to_smallest_unit(&amount, decimals, true)
+}
+
+fn scale_to_smallest_unit(amount: &BigDecimal, decimals: u8, round_up: bool) -> Result<U256> {
+    // `amount == digits * 10^-exponent`
+    let (digits, exponent) = amount.as_bigint_and_exponent();
+    ensure!(digits.sign() != Sign::Minus, "negative");
+    let shift = i64::from(decimals) - exponent;
+    let units = if shift >= 0 {
+        digits * BigInt::from(10).pow(u32::try_from(shift)?)
+    } else {
+        let divisor = BigInt::from(10).pow(u32::try_from(-shift)?);
+        let (quotient, remainder) = digits.div_rem(&divisor);
+        if round_up && !remainder.is_zero() {
+            quotient + 1
+        } else {
+            quotient
+        }
+    };
+    big_int_to_u256(&units)
+}
+
+/// The number of basis points that make up 100%.
+pub const BPS_BASE: u32 = 10_000;
+
+/// Error of overflow-checked ratio scaling.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RatioError {
+    /// The scaled value doesn't fit into a `U256`.
+    Overflow,
+    DivisionByZero,
+}
+
+impl fmt::Display for RatioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("overflow"),
+            Self::DivisionByZero => f.write_str("division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for RatioError {}
+
+/// Computes `value * numerator / denominator` rounded down.
+///
+/// The intermediate product is computed with 512 bits, so only the result has
+/// to fit into a `U256`.
+pub fn mul_ratio_floor(
+    value: U256,
+    numerator: U256,
+    denominator: U256,
+) -> Result<U256, RatioError> {
+    if denominator.is_zero() {
+        return Err(RatioError::DivisionByZero);
+    }
+    let scaled = value.full_mul(numerator) / U512::from(denominator);
+    U256::try_from(scaled).map_err(|_| RatioError::Overflow)
+}
+
+/// Computes `value * numerator / denominator` rounded up. This is how the
+/// settlement contract computes the buy amounts of sell orders.
+///
+/// The intermediate product is computed with 512 bits, so only the result has
+/// to fit into a `U256`.
+pub fn mul_ratio_ceil(value: U256, numerator: U256, denominator: U256) -> Result<U256, RatioError> {
+    if denominator.is_zero() {
+        return Err(RatioError::DivisionByZero);
+    }
+    let denominator = U512::from(denominator);
+    // Can't overflow because the product of two `U256`s is smaller than
+    // `U512::MAX - U256::MAX`.
+    let scaled = (value.full_mul(numerator) + denominator - U512::one()) / denominator;
+    U256::try_from(scaled).map_err(|_| RatioError::Overflow)
+}
+
+/// Like [`mul_ratio_floor`] but returns `U256::MAX` if the result overflows.
+/// Returns `None` if the denominator is zero.
+pub fn saturating_mul_ratio_floor(value: U256, numerator: U256, denominator: U256) -> Option<U256> {
+    saturate(mul_ratio_floor(value, numerator, denominator))
+}
+
+/// Like [`mul_ratio_ceil`] but returns `U256::MAX` if the result overflows.
+/// Returns `None` if the denominator is zero.
+pub fn saturating_mul_ratio_ceil(value: U256, numerator: U256, denominator: U256) -> Option<U256> {
+    saturate(mul_ratio_ceil(value, numerator, denominator))
+}
+
+fn saturate(result: Result<U256, RatioError>) -> Option<U256> {
+    match result {
+        Ok(value) => Some(value),
+        Err(RatioError::Overflow) => Some(U256::MAX),
+        Err(RatioError::DivisionByZero) => None,
+    }
+}
+
+/// Computes `value * numerator / denominator` rounded down exactly like the
+/// settlement contract's `SafeMath`: unlike [`mul_ratio_floor`] the
+/// intermediate product has to fit into a `U256` too.
+pub fn safe_mul_div(value: U256, numerator: U256, denominator: U256) -> Result<U256, RatioError> {
+    let product = value.checked_mul(numerator).ok_or(RatioError::Overflow)?;
+    product
+        .checked_div(denominator)
+        .ok_or(RatioError::DivisionByZero)
+}
+
+/// Computes `value * numerator / denominator` rounded up exactly like the
+/// settlement contract's `SafeMath` (`mul` followed by `ceilDiv`): unlike
+/// [`mul_ratio_ceil`] the intermediate product has to fit into a `U256` too.
+pub fn safe_mul_ceil_div(
+    value: U256,
+    numerator: U256,
+    denominator: U256,
+) -> Result<U256, RatioError> {
+    let product = value.checked_mul(numerator).ok_or(RatioError::Overflow)?;
+    if denominator.is_zero() {
+        return Err(RatioError::DivisionByZero);
+    }
+    let (quotient, remainder) = product.div_mod(denominator);
+    // Can't overflow because the quotient is smaller than `U256::MAX` if
+    // there is a remainder.
+    Ok(quotient + U256::from(u8::from(!remainder.is_zero())))
+}
+
+/// The scale fee factors get converted to before amounts get multiplied with
+/// them, i.e. factors are applied with 18 decimals of precision.
+pub fn factor_scale() -> U256 {
+    U256::exp10(18)
+}
+
+/// Converts a factor into a fixed point number with 18 decimals, see
+/// [`factor_scale`]. Negative factors and NaN become zero.
+pub fn factor_to_fixed(factor: f64) -> U256 {
+    U256::from_f64_lossy(factor * 1e18)
+}
+
 #[cfg(test)]
 mod tests {
-    use {super::*, num::One, std::str::FromStr};
+    use {super::*, num::One, proptest::prelude::*, std::str::FromStr};
 
     #[test]
     fn big_integer_to_u256() {
@@ -215,4 +368,158 @@ mod tests {
         );
         assert!(big_decimal_to_u256(&(max_u256_as_big_decimal + BigDecimal::one())).is_none());
     }
+
+    #[test]
+    fn mul_ratio_rounding() {
+        let (value, numerator, denominator) = (U256::from(10), U256::from(2), U256::from(3));
+        assert_eq!(mul_ratio_floor(value, numerator, denominator), Ok(6.into()));
+        assert_eq!(mul_ratio_ceil(value, numerator, denominator), Ok(7.into()));
+
+        // Exact results are the same no matter the rounding.
+        assert_eq!(mul_ratio_floor(value, 3.into(), 3.into()), Ok(value));
+        assert_eq!(mul_ratio_ceil(value, 3.into(), 3.into()), Ok(value));
+        assert_eq!(
+            mul_ratio_ceil(0.into(), numerator, denominator),
+            Ok(0.into())
+        );
+    }
+
+    #[test]
+    fn mul_ratio_overflow() {
+        // The intermediate product overflows 256 bits but the result doesn't.
+        assert_eq!(
+            mul_ratio_floor(U256::MAX, U256::MAX, U256::MAX),
+            Ok(U256::MAX)
+        );
+        assert_eq!(
+            mul_ratio_ceil(U256::MAX, U256::MAX, U256::MAX),
+            Ok(U256::MAX)
+        );
+        assert_eq!(
+            mul_ratio_floor(U256::MAX, 2.into(), 3.into()),
+            Ok(U256::MAX / 3 * 2)
+        );
+
+        assert_eq!(
+            mul_ratio_floor(U256::MAX, 2.into(), 1.into()),
+            Err(RatioError::Overflow)
+        );
+        assert_eq!(
+            mul_ratio_ceil(U256::MAX, U256::MAX, U256::MAX - 1),
+            Err(RatioError::Overflow)
+        );
+        assert_eq!(
+            mul_ratio_floor(1.into(), 1.into(), 0.into()),
+            Err(RatioError::DivisionByZero)
+        );
+        assert_eq!(
+            saturating_mul_ratio_floor(U256::MAX, 2.into(), 1.into()),
+            Some(U256::MAX)
+        );
+        assert_eq!(
+            saturating_mul_ratio_ceil(1.into(), 1.into(), 0.into()),
+            None
+        );
+        assert_eq!(
+            saturating_mul_ratio_ceil(5.into(), 1.into(), 2.into()),
+            Some(3.into())
+        );
+    }
+
+    #[test]
+    fn safe_math_requires_product_to_fit() {
+        assert_eq!(safe_mul_div(10.into(), 2.into(), 3.into()), Ok(6.into()));
+        assert_eq!(
+            safe_mul_ceil_div(10.into(), 2.into(), 3.into()),
+            Ok(7.into())
+        );
+        assert_eq!(
+            safe_mul_ceil_div(9.into(), 2.into(), 3.into()),
+            Ok(6.into())
+        );
+        assert_eq!(
+            safe_mul_ceil_div(U256::MAX, 1.into(), 1.into()),
+            Ok(U256::MAX)
+        );
+
+        // The result fits but the intermediate product doesn't, which makes
+        // the settlement contract revert.
+        assert_eq!(
+            mul_ratio_floor(U256::MAX, 2.into(), 2.into()),
+            Ok(U256::MAX)
+        );
+        assert_eq!(
+            safe_mul_div(U256::MAX, 2.into(), 2.into()),
+            Err(RatioError::Overflow)
+        );
+        assert_eq!(
+            safe_mul_ceil_div(U256::MAX, 2.into(), 2.into()),
+            Err(RatioError::Overflow)
+        );
+        assert_eq!(
+            safe_mul_ceil_div(1.into(), 1.into(), 0.into()),
+            Err(RatioError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn factors() {
+        assert_eq!(factor_to_fixed(0.5), U256::exp10(17) * 5);
+        assert_eq!(factor_to_fixed(0.0001), U256::exp10(14));
+        assert_eq!(factor_to_fixed(0.), U256::zero());
+        assert_eq!(factor_to_fixed(-1.), U256::zero());
+        assert_eq!(factor_to_fixed(1.), factor_scale());
+    }
+
+    /// Amounts covering the whole `U256` range. Shifting the random words
+    /// varies the magnitude so small amounts get covered too.
+    fn amount() -> impl Strategy<Value = U256> {
+        (any::<[u64; 4]>(), 0..256_usize).prop_map(|(words, shift)| U256(words) >> shift)
+    }
+
+    fn decimals() -> impl Strategy<Value = u8> {
+        prop_oneof![Just(0), Just(6), Just(18), 0..=77_u8]
+    }
+
+    proptest! {
+        #[test]
+        fn base_units_roundtrip(amount in amount(), decimals in decimals()) {
+            let units = to_base_units(amount, decimals);
+            prop_assert_eq!(from_base_units(units.clone(), decimals).unwrap(), amount);
+            prop_assert_eq!(from_base_units_ceil(units, decimals).unwrap(), amount);
+        }
+
+        #[test]
+        fn base_units_rounding(
+            amount in amount().prop_filter("room to round up", |a| *a < U256::MAX),
+            decimals in decimals(),
+            fraction in 1..1000_u32,
+        ) {
+            // Add a fraction of the smallest unit.
+            let units = to_base_units(amount, decimals)
+                + BigDecimal::new(fraction.into(), i64::from(decimals) + 3);
+            let floor = from_base_units(units.clone(), decimals).unwrap();
+            let ceil = from_base_units_ceil(units.clone(), decimals).unwrap();
+            prop_assert_eq!(floor, amount);
+            prop_assert_eq!(ceil, amount + U256::one());
+            prop_assert!(to_base_units(floor, decimals) <= units);
+            prop_assert!(to_base_units(ceil, decimals) >= units);
+        }
+    }
+
+    #[test]
+    fn base_units_examples() {
+        let decimal = |s: &str| BigDecimal::from_str(s).unwrap();
+        assert_eq!(to_base_units(1_500_000.into(), 6), decimal("1.5"));
+        assert_eq!(to_base_units(U256::exp10(18), 18), BigDecimal::one());
+        assert_eq!(
+            from_base_units(decimal("1.5"), 6).unwrap(),
+            1_500_000.into()
+        );
+        assert_eq!(from_base_units(decimal("1.5"), 0).unwrap(), 1.into());
+        assert_eq!(from_base_units_ceil(decimal("1.5"), 0).unwrap(), 2.into());
+        assert_eq!(from_base_units(decimal("1e3"), 2).unwrap(), 100_000.into());
+        assert!(from_base_units(decimal("-1"), 18).is_err());
+        assert!(from_base_units(to_base_units(U256::MAX, 18) * BigDecimal::from(10), 18).is_err());
+    }
 }
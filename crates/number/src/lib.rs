@@ -1,3 +1,4 @@
 pub mod conversions;
 pub mod nonzero;
+pub mod ratio;
 pub mod serialization;
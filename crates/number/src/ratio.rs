@@ -0,0 +1,271 @@
+//! Exact non-negative rational numbers with explicit conversions to `U256`.
+//!
+//! Computations on token amounts and prices are exact as long as they stay
+//! within [`Ratio256`]. Converting back to a `U256` always requires choosing a
+//! rounding direction, so it becomes visible where precision gets lost.
+
+use {
+    crate::conversions::{
+        big_int_to_u256,
+        factor_scale,
+        factor_to_fixed,
+        u256_to_big_int,
+        u256_to_big_rational,
+        RatioError,
+        BPS_BASE,
+    },
+    anyhow::ensure,
+    num::{BigInt, BigRational, One, Signed, Zero},
+    primitive_types::U256,
+    std::{
+        fmt,
+        ops::{Add, Div, Mul},
+    },
+};
+
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Ratio256(BigRational);
+
+impl Ratio256 {
+    /// Creates the ratio `numerator / denominator`.
+    pub fn new(numerator: U256, denominator: U256) -> Result<Self, RatioError> {
+        if denominator.is_zero() {
+            return Err(RatioError::DivisionByZero);
+        }
+        Ok(Self(BigRational::new(
+            u256_to_big_int(&numerator),
+            u256_to_big_int(&denominator),
+        )))
+    }
+
+    /// Creates the ratio of `bps` basis points.
+    pub fn from_bps(bps: u32) -> Self {
+        Self(BigRational::new(bps.into(), BPS_BASE.into()))
+    }
+
+    /// Creates the ratio of a fee factor, applied with 18 decimals of
+    /// precision. Negative factors and NaN become zero.
+    pub fn from_factor(factor: f64) -> Self {
+        Self(BigRational::new(
+            u256_to_big_int(&factor_to_fixed(factor)),
+            u256_to_big_int(&factor_scale()),
+        ))
+    }
+
+    pub fn zero() -> Self {
+        Self(BigRational::zero())
+    }
+
+    pub fn one() -> Self {
+        Self(BigRational::one())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Divides by `other`.
+    pub fn checked_div(&self, other: &Self) -> Result<Self, RatioError> {
+        if other.is_zero() {
+            return Err(RatioError::DivisionByZero);
+        }
+        Ok(Self(&self.0 / &other.0))
+    }
+
+    /// Subtracts `other` unless the result would be negative.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        (self >= other).then(|| Self(&self.0 - &other.0))
+    }
+
+    /// The largest `U256` not greater than the ratio.
+    pub fn floor(&self) -> Result<U256, RatioError> {
+        to_u256(self.0.floor().to_integer())
+    }
+
+    /// The smallest `U256` not less than the ratio.
+    pub fn ceil(&self) -> Result<U256, RatioError> {
+        to_u256(self.0.ceil().to_integer())
+    }
+
+    /// The `U256` closest to the ratio. Halves get rounded up.
+    pub fn round(&self) -> Result<U256, RatioError> {
+        to_u256(self.0.round().to_integer())
+    }
+
+    /// Like [`Self::floor`] but returns `U256::MAX` if the ratio is too large.
+    pub fn saturating_floor(&self) -> U256 {
+        self.floor().unwrap_or(U256::MAX)
+    }
+
+    /// Like [`Self::ceil`] but returns `U256::MAX` if the ratio is too large.
+    pub fn saturating_ceil(&self) -> U256 {
+        self.ceil().unwrap_or(U256::MAX)
+    }
+
+    /// Like [`Self::round`] but returns `U256::MAX` if the ratio is too large.
+    pub fn saturating_round(&self) -> U256 {
+        self.round().unwrap_or(U256::MAX)
+    }
+
+    pub fn as_big_rational(&self) -> &BigRational {
+        &self.0
+    }
+
+    pub fn into_big_rational(self) -> BigRational {
+        self.0
+    }
+}
+
+fn to_u256(value: BigInt) -> Result<U256, RatioError> {
+    // Ratios are never negative, so the conversion can only fail because the
+    // value is too large.
+    big_int_to_u256(&value).map_err(|_| RatioError::Overflow)
+}
+
+impl From<U256> for Ratio256 {
+    fn from(value: U256) -> Self {
+        Self(u256_to_big_rational(&value))
+    }
+}
+
+impl TryFrom<BigRational> for Ratio256 {
+    type Error = anyhow::Error;
+
+    fn try_from(value: BigRational) -> Result<Self, Self::Error> {
+        ensure!(!value.is_negative(), "negative ratio");
+        Ok(Self(value))
+    }
+}
+
+impl Add for Ratio256 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Add<&Ratio256> for &Ratio256 {
+    type Output = Ratio256;
+
+    fn add(self, rhs: &Ratio256) -> Ratio256 {
+        Ratio256(&self.0 + &rhs.0)
+    }
+}
+
+impl Mul for Ratio256 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl Mul<&Ratio256> for Ratio256 {
+    type Output = Self;
+
+    fn mul(self, rhs: &Ratio256) -> Self {
+        Self(self.0 * &rhs.0)
+    }
+}
+
+impl Mul<&Ratio256> for &Ratio256 {
+    type Output = Ratio256;
+
+    fn mul(self, rhs: &Ratio256) -> Ratio256 {
+        Ratio256(&self.0 * &rhs.0)
+    }
+}
+
+/// Division fails instead of panicking if the divisor is zero.
+impl Div for Ratio256 {
+    type Output = Result<Self, RatioError>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(&rhs)
+    }
+}
+
+impl Div<&Ratio256> for &Ratio256 {
+    type Output = Result<Ratio256, RatioError>;
+
+    fn div(self, rhs: &Ratio256) -> Self::Output {
+        self.checked_div(rhs)
+    }
+}
+
+impl fmt::Display for Ratio256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(numerator: u64, denominator: u64) -> Ratio256 {
+        Ratio256::new(numerator.into(), denominator.into()).unwrap()
+    }
+
+    #[test]
+    fn rounding() {
+        for (ratio, floor, ceil, round) in [
+            (ratio(0, 1), 0, 0, 0),
+            (ratio(6, 3), 2, 2, 2),
+            (ratio(7, 3), 2, 3, 2),
+            (ratio(5, 2), 2, 3, 3),
+            (ratio(8, 3), 2, 3, 3),
+        ] {
+            assert_eq!(ratio.floor(), Ok(floor.into()), "{ratio}");
+            assert_eq!(ratio.ceil(), Ok(ceil.into()), "{ratio}");
+            assert_eq!(ratio.round(), Ok(round.into()), "{ratio}");
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let max = Ratio256::from(U256::MAX);
+        assert_eq!(max.floor(), Ok(U256::MAX));
+        assert_eq!(max.ceil(), Ok(U256::MAX));
+
+        let above_max = &max + &ratio(1, 2);
+        assert_eq!(above_max.floor(), Ok(U256::MAX));
+        assert_eq!(above_max.ceil(), Err(RatioError::Overflow));
+        assert_eq!(above_max.round(), Err(RatioError::Overflow));
+        assert_eq!(above_max.saturating_ceil(), U256::MAX);
+        assert_eq!((max.clone() * max).saturating_floor(), U256::MAX);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(ratio(1, 3) + ratio(1, 6), ratio(1, 2));
+        assert_eq!(ratio(2, 3) * ratio(3, 4), ratio(1, 2));
+        assert_eq!(ratio(1, 2) / ratio(1, 4), Ok(ratio(2, 1)));
+        assert_eq!(
+            ratio(1, 2) / Ratio256::zero(),
+            Err(RatioError::DivisionByZero)
+        );
+        assert_eq!(ratio(1, 2).checked_sub(&ratio(1, 3)), Some(ratio(1, 6)));
+        assert_eq!(ratio(1, 3).checked_sub(&ratio(1, 2)), None);
+        assert_eq!(Ratio256::from_bps(250), ratio(1, 40));
+        assert_eq!(Ratio256::from_factor(0.25), ratio(1, 4));
+        assert_eq!(Ratio256::from_factor(-1.), Ratio256::zero());
+        assert_eq!(
+            Ratio256::new(1.into(), 0.into()),
+            Err(RatioError::DivisionByZero)
+        );
+        assert!(Ratio256::try_from(BigRational::new((-1).into(), 2.into())).is_err());
+    }
+
+    #[test]
+    fn scaling_is_exact() {
+        // Scaling an amount by a price and back again loses precision if the
+        // intermediate result gets rounded.
+        let amount = Ratio256::from(U256::from(1_000_000_007u64));
+        let price = ratio(3, 7);
+        let scaled = &amount * &price;
+        assert_eq!(scaled.floor(), Ok(428_571_431.into()));
+        assert_eq!((&scaled / &price).unwrap(), amount);
+    }
+}
@@ -0,0 +1,51 @@
+//! Procedural macros for the `observe` crate. Use them through their re-exports
+//! in `observe` instead of depending on this crate directly.
+
+use {
+    proc_macro::TokenStream,
+    quote::quote,
+    syn::{parse_macro_input, spanned::Spanned, ItemFn, LitStr},
+};
+
+/// Records the duration and the outcome of every call of an `async fn`
+/// returning a `Result` with `observe::future::timed`.
+///
+/// The operation is named after the function unless a name is passed
+/// explicitly:
+///
+/// ```ignore
+/// #[observe::timed]
+/// async fn fetch_prices() -> Result<Prices> { .. }
+///
+/// #[observe::timed("fetch_orders")]
+/// async fn fetch() -> Result<Vec<Order>> { .. }
+/// ```
+#[proc_macro_attribute]
+pub fn timed(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let name = if attr.is_empty() {
+        None
+    } else {
+        Some(parse_macro_input!(attr as LitStr))
+    };
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = parse_macro_input!(item as ItemFn);
+
+    if sig.asyncness.is_none() {
+        return syn::Error::new(sig.fn_token.span(), "`timed` only supports `async fn`")
+            .to_compile_error()
+            .into();
+    }
+    let name = name.unwrap_or_else(|| LitStr::new(&sig.ident.to_string(), sig.ident.span()));
+
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            ::observe::future::timed(#name, &[], async move #block).await
+        }
+    }
+    .into()
+}
@@ -0,0 +1,196 @@
+//! Guardrails against metric label values with unbounded cardinality.
+//!
+//! Label values derived from user input or chain data (token addresses, app
+//! codes, ...) can create an unbounded number of time series and degrade
+//! Prometheus. Passing such values through [`bounded`] before using them as
+//! label values caps how many distinct values a label can take. Once the cap
+//! is reached new values get reported as [`OVERFLOW_VALUE`] instead and a
+//! warning is logged.
+//!
+//! The current state of all guarded labels and the number of series per
+//! metric are served as JSON on `/metrics/cardinality`.
+
+use {
+    once_cell::sync::OnceCell,
+    serde::Serialize,
+    std::{
+        collections::{BTreeMap, HashSet},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+    },
+    warp::{Filter, Rejection, Reply},
+};
+
+/// How many distinct values a guarded label may take unless configured
+/// otherwise.
+pub const DEFAULT_MAX_VALUES: usize = 100;
+
+/// Label value reported instead of values exceeding the limit.
+pub const OVERFLOW_VALUE: &str = "other";
+
+static GUARDS: OnceCell<Guards> = OnceCell::new();
+
+fn guards() -> &'static Guards {
+    GUARDS.get_or_init(|| Guards {
+        default_max_values: AtomicUsize::new(DEFAULT_MAX_VALUES),
+        labels: Default::default(),
+    })
+}
+
+struct Guards {
+    default_max_values: AtomicUsize,
+    labels: Mutex<BTreeMap<(String, String), Guard>>,
+}
+
+#[derive(Default)]
+struct Guard {
+    /// Explicitly configured limit, overriding the default one.
+    max_values: Option<usize>,
+    values: HashSet<String>,
+    overflows: u64,
+}
+
+/// Sets how many distinct values guarded labels may take unless a limit was
+/// configured for the specific label.
+pub fn set_default_max_values(max_values: usize) {
+    guards()
+        .default_max_values
+        .store(max_values, Ordering::Relaxed);
+}
+
+/// Sets how many distinct values the label `label` of the metric `metric` may
+/// take.
+pub fn set_max_values(metric: &str, label: &str, max_values: usize) {
+    guards()
+        .labels
+        .lock()
+        .unwrap()
+        .entry((metric.to_string(), label.to_string()))
+        .or_default()
+        .max_values = Some(max_values);
+}
+
+/// Returns `value` if it is already known for the label or the label has not
+/// reached its limit of distinct values yet and [`OVERFLOW_VALUE`] otherwise.
+pub fn bounded<'a>(metric: &str, label: &str, value: &'a str) -> &'a str {
+    let guards = guards();
+    let default_max_values = guards.default_max_values.load(Ordering::Relaxed);
+    let mut labels = guards.labels.lock().unwrap();
+    let guard = labels
+        .entry((metric.to_string(), label.to_string()))
+        .or_default();
+    if guard.values.contains(value) {
+        return value;
+    }
+    let max_values = guard.max_values.unwrap_or(default_max_values);
+    if guard.values.len() < max_values {
+        guard.values.insert(value.to_string());
+        return value;
+    }
+
+    if guard.overflows == 0 {
+        tracing::warn!(
+            metric,
+            label,
+            max_values,
+            "metric label reached its cardinality limit; reporting new values as \"other\""
+        );
+    }
+    guard.overflows += 1;
+    Metrics::get()
+        .metric_label_overflows
+        .with_label_values(&[metric, label])
+        .inc();
+    OVERFLOW_VALUE
+}
+
+/// Body of the `/metrics/cardinality` endpoint.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Guarded labels keyed by `<metric>.<label>`.
+    pub labels: BTreeMap<String, LabelReport>,
+    /// Number of series of every metric in the global registry.
+    pub series: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelReport {
+    pub distinct_values: usize,
+    pub max_values: usize,
+    /// How often a value got replaced with [`OVERFLOW_VALUE`].
+    pub overflows: u64,
+}
+
+/// Reports the state of all guarded labels and the number of series per
+/// metric.
+pub fn report() -> Report {
+    let guards = guards();
+    let default_max_values = guards.default_max_values.load(Ordering::Relaxed);
+    let labels = guards
+        .labels
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|((metric, label), guard)| {
+            (
+                format!("{metric}.{label}"),
+                LabelReport {
+                    distinct_values: guard.values.len(),
+                    max_values: guard.max_values.unwrap_or(default_max_values),
+                    overflows: guard.overflows,
+                },
+            )
+        })
+        .collect();
+    let series = crate::metrics::gather()
+        .iter()
+        .map(|family| (family.get_name().to_string(), family.get_metric().len()))
+        .collect();
+    Report { labels, series }
+}
+
+// `/metrics/cardinality` route exposing the cardinality report
+pub fn handle_cardinality() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("metrics" / "cardinality").map(|| warp::reply::json(&report()))
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// How often a label value got replaced because the label reached its
+    /// cardinality limit.
+    #[metric(labels("metric", "label"))]
+    metric_label_overflows: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(crate::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_values_exceeding_limit() {
+        set_max_values("test_metric", "token", 2);
+
+        assert_eq!(bounded("test_metric", "token", "a"), "a");
+        assert_eq!(bounded("test_metric", "token", "b"), "b");
+        assert_eq!(bounded("test_metric", "token", "c"), OVERFLOW_VALUE);
+        // Known values keep being reported as is.
+        assert_eq!(bounded("test_metric", "token", "a"), "a");
+        // Limits are tracked per label.
+        assert_eq!(bounded("test_metric", "pool", "c"), "c");
+
+        let report = report();
+        let token = &report.labels["test_metric.token"];
+        assert_eq!(token.distinct_values, 2);
+        assert_eq!(token.max_values, 2);
+        assert_eq!(token.overflows, 1);
+    }
+}
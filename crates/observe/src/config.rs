@@ -0,0 +1,223 @@
+//! Observability settings shared by all binaries.
+//!
+//! The settings can be provided by a TOML file, e.g. mounted into the
+//! container, as well as by command line arguments and environment variables.
+//! Arguments and environment variables take precedence over the file, so a
+//! single setting can be changed for one deployment without touching the
+//! shared file:
+//!
+//! ```toml
+//! log-filter = "warn,orderbook=debug"
+//! log-stderr-threshold = "error"
+//! log-format = "json"
+//! tokio-console = false
+//! otlp-endpoint = "http://otel-collector:4317"
+//! metrics-port = 9586
+//! ```
+
+use {
+    anyhow::{Context, Result},
+    serde::{Deserialize, Serialize},
+    std::{path::Path, str::FromStr},
+    tracing::level_filters::LevelFilter,
+};
+
+/// How log lines get formatted.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// Human readable lines.
+    #[default]
+    Plain,
+    /// One JSON object per line, for log aggregators.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "json" => Ok(Self::Json),
+            _ => anyhow::bail!("unknown log format {s:?}, expected \"plain\" or \"json\""),
+        }
+    }
+}
+
+impl std::fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Plain => "plain",
+            Self::Json => "json",
+        })
+    }
+}
+
+/// The effective observability settings.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// Filter for the logs with the syntax of `env_logger`.
+    pub log_filter: String,
+    /// Logs at or above this level get written to stderr instead of stdout.
+    #[serde(with = "level_filter")]
+    pub log_stderr_threshold: LevelFilter,
+    /// Format of the log lines written to stdout and stderr.
+    pub log_format: LogFormat,
+    /// Exposes the tokio runtime to `tokio-console`. Only has an effect in
+    /// binaries built with `--cfg tokio_unstable`.
+    pub tokio_console: bool,
+    /// gRPC endpoint of an OpenTelemetry collector to export spans to. The
+    /// service name is taken from `OTEL_SERVICE_NAME`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otlp_endpoint: Option<String>,
+    /// Port serving the metrics and health probes. Every binary has its own
+    /// default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_port: Option<u16>,
+}
+
+impl Config {
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("config serializes to TOML")
+    }
+}
+
+/// Observability settings of a single source, e.g. the config file. Settings
+/// that are not set fall back to the next source.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Partial {
+    pub log_filter: Option<String>,
+    #[serde(default, with = "level_filter::option")]
+    pub log_stderr_threshold: Option<LevelFilter>,
+    pub log_format: Option<LogFormat>,
+    pub tokio_console: Option<bool>,
+    pub otlp_endpoint: Option<String>,
+    pub metrics_port: Option<u16>,
+}
+
+impl Partial {
+    /// Reads the settings from a TOML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("reading config file {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("parsing config file {}", path.display()))
+    }
+
+    /// Reads the settings from the TOML file if there is one.
+    pub fn load_optional(path: Option<&Path>) -> Result<Self> {
+        path.map(Self::load)
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Falls back to the settings of `other` for all settings that are not
+    /// set.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            log_filter: self.log_filter.or(other.log_filter),
+            log_stderr_threshold: self.log_stderr_threshold.or(other.log_stderr_threshold),
+            log_format: self.log_format.or(other.log_format),
+            tokio_console: self.tokio_console.or(other.tokio_console),
+            otlp_endpoint: self.otlp_endpoint.or(other.otlp_endpoint),
+            metrics_port: self.metrics_port.or(other.metrics_port),
+        }
+    }
+
+    /// Falls back to the defaults for all settings that are not set.
+    pub fn resolve(self, default_log_filter: &str) -> Config {
+        Config {
+            log_filter: self
+                .log_filter
+                .unwrap_or_else(|| default_log_filter.to_string()),
+            log_stderr_threshold: self.log_stderr_threshold.unwrap_or(LevelFilter::ERROR),
+            log_format: self.log_format.unwrap_or_default(),
+            tokio_console: self.tokio_console.unwrap_or_default(),
+            otlp_endpoint: self.otlp_endpoint,
+            metrics_port: self.metrics_port,
+        }
+    }
+}
+
+/// `LevelFilter` only implements `FromStr` and `Display`.
+mod level_filter {
+    use {
+        serde::{de::Error, Deserialize, Deserializer, Serializer},
+        tracing::level_filters::LevelFilter,
+    };
+
+    pub fn serialize<S: Serializer>(level: &LevelFilter, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(level)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<LevelFilter, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<LevelFilter>, D::Error> {
+            super::deserialize(deserializer).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_take_precedence_over_file() {
+        let file: Partial = toml::from_str(
+            r#"
+            log-filter = "info"
+            log-stderr-threshold = "warn"
+            log-format = "json"
+            tokio-console = true
+            metrics-port = 9000
+            "#,
+        )
+        .unwrap();
+        let arguments = Partial {
+            log_filter: Some("debug".to_string()),
+            ..Default::default()
+        };
+
+        let config = arguments.or(file).resolve("warn");
+        assert_eq!(
+            config,
+            Config {
+                log_filter: "debug".to_string(),
+                log_stderr_threshold: LevelFilter::WARN,
+                log_format: LogFormat::Json,
+                tokio_console: true,
+                otlp_endpoint: None,
+                metrics_port: Some(9000),
+            }
+        );
+        assert_eq!(
+            config.to_toml(),
+            "log-filter = \"debug\"\nlog-stderr-threshold = \"warn\"\nlog-format = \"json\"\ntokio-console = true\nmetrics-port = 9000\n"
+        );
+        assert_eq!(
+            Partial::load_optional(None)
+                .unwrap()
+                .resolve("warn")
+                .log_format,
+            LogFormat::Plain
+        );
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("yaml".parse::<LogFormat>().is_err());
+        assert_eq!(Partial::default().resolve("warn").log_filter, "warn");
+        assert!(toml::from_str::<Partial>("log-level = \"info\"").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Propagates the identifiers that tie together logs of related work across
+//! process boundaries. Outgoing HTTP requests carry the request id and the
+//! auction id of the current task as headers and incoming requests restore
+//! them into the task local storage and the tracing span of their handler.
+//! That way the logs of an auction can be correlated between the autopilot,
+//! the drivers, the solver engines and external price estimators without
+//! passing the identifiers through the code explicitly.
+
+use {
+    crate::request_id,
+    reqwest::{header::HeaderMap, RequestBuilder},
+    std::future::Future,
+    tracing::Instrument,
+};
+
+pub const REQUEST_ID_HEADER: &str = "X-REQUEST-ID";
+pub const AUCTION_ID_HEADER: &str = "X-AUCTION-ID";
+
+tokio::task_local! {
+    static AUCTION_ID: i64;
+}
+
+/// Returns the auction id associated with the current task.
+pub fn auction_id() -> Option<i64> {
+    AUCTION_ID.try_with(|id| *id).ok()
+}
+
+/// Associates the auction id with the given future so it gets attached to all
+/// outgoing requests it makes.
+pub async fn with_auction_id<F>(id: i64, scope: F) -> F::Output
+where
+    F: Future,
+{
+    AUCTION_ID.scope(id, scope).await
+}
+
+/// The identifiers of the current task that get propagated to other
+/// processes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Context {
+    pub request_id: Option<String>,
+    pub auction_id: Option<i64>,
+}
+
+impl Context {
+    /// Captures the identifiers of the current task. Useful when the request
+    /// gets sent from a different task (e.g. because it is shared).
+    pub fn current() -> Self {
+        Self {
+            request_id: request_id::get_task_local_storage(),
+            auction_id: auction_id(),
+        }
+    }
+
+    /// Reads the identifiers from the headers of an incoming request.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            request_id: headers
+                .get(REQUEST_ID_HEADER)
+                .map(|header| String::from_utf8_lossy(header.as_bytes()).to_string()),
+            auction_id: headers
+                .get(AUCTION_ID_HEADER)
+                .and_then(|header| header.to_str().ok()?.parse().ok()),
+        }
+    }
+}
+
+/// Attaches the propagated identifiers to outgoing requests.
+pub trait RequestBuilderExt {
+    /// Adds the identifiers of the current task as headers.
+    fn with_tracing_headers(self) -> Self;
+
+    /// Adds the identifiers of a previously captured [`Context`] as headers.
+    fn with_tracing_context(self, context: &Context) -> Self;
+}
+
+impl RequestBuilderExt for RequestBuilder {
+    fn with_tracing_headers(self) -> Self {
+        self.with_tracing_context(&Context::current())
+    }
+
+    fn with_tracing_context(mut self, context: &Context) -> Self {
+        if let Some(id) = &context.request_id {
+            self = self.header(REQUEST_ID_HEADER, id);
+        }
+        if let Some(id) = context.auction_id {
+            self = self.header(AUCTION_ID_HEADER, id.to_string());
+        }
+        self
+    }
+}
+
+/// Runs the handler of an incoming request with the identifiers propagated in
+/// its headers (see [`Context::from_headers`]). Requests without a request id
+/// get the fallback id assigned.
+pub fn instrument_request<F>(
+    context: Context,
+    fallback_id: impl FnOnce() -> String,
+    handler: F,
+) -> impl Future<Output = F::Output>
+where
+    F: Future,
+{
+    let id = context.request_id.unwrap_or_else(fallback_id);
+    let span = tracing::info_span!("request", id, auction_id = context.auction_id);
+    let handler = request_id::REQUEST_ID.scope(id, handler);
+    async move {
+        match context.auction_id {
+            Some(auction_id) => AUCTION_ID.scope(auction_id, handler).await,
+            None => handler.await,
+        }
+    }
+    .instrument(span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn propagates_identifiers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, "foo".parse().unwrap());
+        headers.insert(AUCTION_ID_HEADER, "42".parse().unwrap());
+
+        let context =
+            instrument_request(Context::from_headers(&headers), || unreachable!(), async {
+                Context::current()
+            })
+            .await;
+        assert_eq!(
+            context,
+            Context {
+                request_id: Some("foo".to_string()),
+                auction_id: Some(42),
+            }
+        );
+
+        let request = reqwest::Client::new()
+            .get("http://localhost")
+            .with_tracing_context(&context)
+            .build()
+            .unwrap();
+        assert_eq!(Context::from_headers(request.headers()), context);
+
+        let context = instrument_request(Context::default(), || "1".to_string(), async {
+            Context::current()
+        })
+        .await;
+        assert_eq!(context.request_id.as_deref(), Some("1"));
+        assert_eq!(context.auction_id, None);
+    }
+}
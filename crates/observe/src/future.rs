@@ -5,9 +5,53 @@ use {
         future::Future,
         pin::Pin,
         task::{Context, Poll},
+        time::Instant,
     },
 };
 
+/// Runs the future and records how long it took in the
+/// `timed_operation_seconds` histogram. Observations are labeled with the
+/// operation name, the given labels (joined with `,`) and the outcome:
+/// `success` or `error` depending on the result, or `cancelled` if the future
+/// got dropped before it completed.
+///
+/// Async functions can be timed as a whole with [`crate::timed`].
+pub async fn timed<T, E>(
+    name: &str,
+    labels: &[&str],
+    fut: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let mut timer = OperationTimer {
+        name,
+        labels: labels.join(","),
+        outcome: "cancelled",
+        start: Instant::now(),
+    };
+    let result = fut.await;
+    timer.outcome = match result {
+        Ok(_) => "success",
+        Err(_) => "error",
+    };
+    result
+}
+
+/// Records the operation when dropped so cancelled futures get recorded too.
+struct OperationTimer<'a> {
+    name: &'a str,
+    labels: String,
+    outcome: &'static str,
+    start: Instant,
+}
+
+impl Drop for OperationTimer<'_> {
+    fn drop(&mut self) {
+        Metrics::get()
+            .timed_operation_seconds
+            .with_label_values(&[self.name, &self.labels, self.outcome])
+            .observe(self.start.elapsed().as_secs_f64());
+    }
+}
+
 pub trait Measure: Sized {
     fn measure(self, label: &str) -> Measurable<Self> {
         Measurable {
@@ -74,6 +118,10 @@ struct Metrics {
     /// Timing of measured futures.
     #[metric(labels("label"))]
     future_execution_times: prometheus::HistogramVec,
+
+    /// Timing of operations measured with [`timed`] by outcome.
+    #[metric(labels("operation", "labels", "outcome"))]
+    timed_operation_seconds: prometheus::HistogramVec,
 }
 
 impl Metrics {
@@ -81,3 +129,34 @@ impl Metrics {
         Metrics::instance(super::metrics::get_storage_registry()).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(outcome: &str) -> u64 {
+        Metrics::get()
+            .timed_operation_seconds
+            .with_label_values(&["test_operation", "a,b", outcome])
+            .get_sample_count()
+    }
+
+    #[tokio::test]
+    async fn timed_records_outcome() {
+        timed("test_operation", &["a", "b"], async { Ok::<_, ()>(()) })
+            .await
+            .unwrap();
+        timed("test_operation", &["a", "b"], async { Err::<(), _>(()) })
+            .await
+            .unwrap_err();
+        let pending = timed("test_operation", &["a", "b"], async {
+            futures::future::pending::<Result<(), ()>>().await
+        });
+        // Poll once so the timer gets started before dropping the future.
+        assert!(futures::poll!(Box::pin(pending)).is_pending());
+
+        assert_eq!(count("success"), 1);
+        assert_eq!(count("error"), 1);
+        assert_eq!(count("cancelled"), 1);
+    }
+}
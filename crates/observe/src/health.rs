@@ -0,0 +1,211 @@
+//! Standardized liveness and readiness probes.
+//!
+//! Binaries register the components whose liveness matters on a
+//! [`HealthServer`] which serves them next to the metrics:
+//!
+//! - `/health/live` reports whether all registered components are alive.
+//! - `/health/ready` additionally requires all warmup conditions of the global
+//!   [`crate::readiness`] gate to be met.
+//!
+//! Both endpoints respond with `200 OK` or `503 Service Unavailable` and a
+//! JSON body listing the status of every component, so orchestrators and
+//! humans alike can see which part of a service is unhealthy.
+//!
+//! Additionally binaries can register named reports of internal state which
+//! get served as JSON at `/metrics/<name>`.
+
+use {
+    crate::{metrics::LivenessChecking, readiness},
+    serde::Serialize,
+    std::{collections::BTreeMap, convert::Infallible, net::SocketAddr, sync::Arc},
+    tokio::task::{self, JoinHandle},
+    warp::{http::StatusCode, Filter, Rejection, Reply},
+};
+
+/// Produces the JSON body of a report endpoint.
+type Report = Arc<dyn Fn() -> warp::reply::Json + Send + Sync>;
+
+/// Serves metrics and health probes for a set of named components.
+#[derive(Clone, Default)]
+pub struct HealthServer {
+    components: BTreeMap<String, Arc<dyn LivenessChecking>>,
+    reports: BTreeMap<String, Report>,
+}
+
+impl HealthServer {
+    /// Registers a component that has to be alive for the process to be
+    /// considered alive. Registering a component with an existing name
+    /// replaces the previous one.
+    pub fn with(mut self, name: &str, component: Arc<dyn LivenessChecking>) -> Self {
+        self.components.insert(name.to_string(), component);
+        self
+    }
+
+    /// Registers a report served at `/metrics/<name>`. Registering a report
+    /// with an existing name replaces the previous one.
+    pub fn with_report(
+        mut self,
+        name: &str,
+        report: impl Fn() -> warp::reply::Json + Send + Sync + 'static,
+    ) -> Self {
+        self.reports.insert(name.to_string(), Arc::new(report));
+        self
+    }
+
+    /// Checks the liveness of all registered components concurrently.
+    pub async fn liveness(&self) -> LivenessReport {
+        let statuses = futures::future::join_all(
+            self.components
+                .values()
+                .map(|component| component.is_alive()),
+        )
+        .await;
+        let components: BTreeMap<_, _> = self.components.keys().cloned().zip(statuses).collect();
+        LivenessReport {
+            alive: components.values().all(|alive| *alive),
+            components,
+        }
+    }
+
+    /// Checks the liveness of all registered components and the warmup
+    /// conditions of the global readiness gate.
+    pub async fn readiness(&self) -> ReadinessReport {
+        let liveness = self.liveness().await;
+        let gate = readiness::gate().report();
+        ReadinessReport {
+            ready: liveness.alive && gate.ready,
+            components: liveness.components,
+            conditions: gate.conditions,
+        }
+    }
+
+    /// Spawns a task serving `/metrics`, `/metrics/cardinality`, the
+    /// registered reports, the health probes, the legacy `/liveness` and
+    /// `/ready` routes and the log filter API on the given address.
+    pub fn serve(self, address: SocketAddr) -> JoinHandle<()> {
+        let filter = crate::cardinality::handle_cardinality()
+            .or(crate::metrics::handle_metrics())
+            .or(self.routes())
+            .or(crate::log_filter::handle_log_filter());
+        tracing::info!(%address, "serving metrics and health probes");
+        task::spawn(warp::serve(filter).bind(address))
+    }
+
+    /// The probe and report routes, for binaries that want to mount them into
+    /// their own server.
+    pub fn routes(self) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        let server = Arc::new(self);
+        let live = {
+            let server = server.clone();
+            warp::path!("health" / "live")
+                .or(warp::path!("liveness"))
+                .unify()
+                .and_then(move || {
+                    let server = server.clone();
+                    async move {
+                        let report = server.liveness().await;
+                        Result::<_, Infallible>::Ok(json_with_status(&report, report.alive))
+                    }
+                })
+        };
+        let ready = {
+            let server = server.clone();
+            warp::path!("health" / "ready")
+                .or(warp::path!("ready"))
+                .unify()
+                .and_then(move || {
+                    let server = server.clone();
+                    async move {
+                        let report = server.readiness().await;
+                        Result::<_, Infallible>::Ok(json_with_status(&report, report.ready))
+                    }
+                })
+        };
+        let reports = warp::path!("metrics" / String).and_then(move |name: String| {
+            let report = server.reports.get(&name).cloned();
+            async move {
+                match report {
+                    Some(report) => Ok(report()),
+                    None => Err(warp::reject::not_found()),
+                }
+            }
+        });
+        live.or(ready).or(reports)
+    }
+}
+
+fn json_with_status(
+    report: &impl Serialize,
+    healthy: bool,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    warp::reply::with_status(warp::reply::json(report), status)
+}
+
+/// Body of the `/health/live` endpoint.
+#[derive(Debug, Serialize)]
+pub struct LivenessReport {
+    pub alive: bool,
+    pub components: BTreeMap<String, bool>,
+}
+
+/// Body of the `/health/ready` endpoint.
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub components: BTreeMap<String, bool>,
+    pub conditions: BTreeMap<String, readiness::ConditionReport>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Static(bool);
+
+    #[async_trait::async_trait]
+    impl LivenessChecking for Static {
+        async fn is_alive(&self) -> bool {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_status_per_component() {
+        let server = HealthServer::default()
+            .with("api", Arc::new(Static(true)))
+            .with("db", Arc::new(Static(false)));
+
+        let report = server.liveness().await;
+        assert!(!report.alive);
+        assert!(report.components["api"]);
+        assert!(!report.components["db"]);
+
+        let server = server.with("db", Arc::new(Static(true)));
+        assert!(server.liveness().await.alive);
+    }
+
+    #[tokio::test]
+    async fn serves_reports() {
+        let routes = HealthServer::default()
+            .with_report("answer", || warp::reply::json(&42))
+            .routes();
+
+        let response = warp::test::request()
+            .path("/metrics/answer")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_ref(), b"42");
+
+        let response = warp::test::request()
+            .path("/metrics/unknown")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
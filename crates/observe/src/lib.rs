@@ -1,11 +1,19 @@
 //! This crate is intended to contain code that is required to provide or
 //! improve the observability of a system. That includes initialization logic
 //! for metrics and logging as well as logging helper functions.
+pub mod cardinality;
+pub mod config;
+pub mod distributed_tracing;
 pub mod future;
+pub mod health;
+pub mod log_filter;
 pub mod metrics;
 pub mod panic_hook;
+pub mod readiness;
 pub mod request_id;
 pub mod tracing;
 
+pub use observe_macros::timed;
+
 #[cfg(unix)]
 mod tracing_reload_handler;
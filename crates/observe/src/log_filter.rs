@@ -0,0 +1,99 @@
+//! Changing the log filter of a running process without restarting it.
+
+use {
+    once_cell::sync::OnceCell,
+    std::sync::Mutex,
+    tracing_subscriber::{reload, EnvFilter},
+    warp::{http::StatusCode, hyper::body::Bytes, Filter, Rejection, Reply},
+};
+
+/// Environment variable holding the key that requests to the log filter API
+/// have to carry in the `X-API-Key` header. The API is disabled if it is not
+/// set.
+pub const API_KEY_ENV: &str = "LOG_FILTER_API_KEY";
+
+static LOG_FILTER: OnceCell<LogFilter> = OnceCell::new();
+
+struct LogFilter {
+    initial: String,
+    current: Mutex<String>,
+    reload: Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>,
+}
+
+/// Makes the reloadable filter of the global tracing subscriber available to
+/// [`set`] and [`get`].
+pub(crate) fn register<S: 'static>(initial: String, handle: reload::Handle<EnvFilter, S>) {
+    let _ = LOG_FILTER.set(LogFilter {
+        current: Mutex::new(initial.clone()),
+        initial,
+        reload: Box::new(move |filter| handle.reload(filter)),
+    });
+}
+
+/// The currently applied log filter.
+pub fn get() -> Option<String> {
+    Some(LOG_FILTER.get()?.current.lock().unwrap().clone())
+}
+
+/// The log filter the process was started with.
+pub fn initial() -> Option<String> {
+    Some(LOG_FILTER.get()?.initial.clone())
+}
+
+/// Replaces the log filter. The filter "reset" restores the filter the process
+/// was started with. Returns the applied filter.
+pub fn set(filter: &str) -> Result<String, String> {
+    let log_filter = LOG_FILTER
+        .get()
+        .ok_or_else(|| "tracing is not initialized".to_owned())?;
+    let filter = match filter.trim() {
+        "reset" => log_filter.initial.as_str(),
+        filter => filter,
+    };
+    let env_filter =
+        EnvFilter::try_new(filter).map_err(|err| format!("failed to parse filter: {err}"))?;
+    let mut current = log_filter.current.lock().unwrap();
+    (log_filter.reload)(env_filter).map_err(|err| format!("failed to apply filter: {err}"))?;
+    *current = filter.to_owned();
+    // Use a fairly high log level to improve chances that this actually gets
+    // logged when somebody messed with the log filter.
+    tracing::warn!(filter, "applied new log filter");
+    Ok(filter.to_owned())
+}
+
+/// `/internal/log_filter` route to read (`GET`) or replace (`PUT` with the
+/// filter as the plain text body) the log filter. Every request has to carry
+/// the key from [`API_KEY_ENV`] in the `X-API-Key` header.
+pub fn handle_log_filter() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let api_key = std::env::var(API_KEY_ENV).ok().filter(|key| !key.is_empty());
+    let authorized = warp::path!("internal" / "log_filter")
+        .and(warp::header::optional::<String>("x-api-key"))
+        .map(move |key: Option<String>| api_key.is_some() && key == api_key);
+
+    let read = authorized
+        .clone()
+        .and(warp::get())
+        .map(|authorized| match authorized {
+            true => reply(get().ok_or_else(|| "tracing is not initialized".to_owned())),
+            false => unauthorized(),
+        });
+    let write = authorized
+        .and(warp::put())
+        .and(warp::body::bytes())
+        .map(|authorized, body: Bytes| match authorized {
+            true => reply(set(&String::from_utf8_lossy(&body))),
+            false => unauthorized(),
+        });
+    read.or(write).unify()
+}
+
+fn reply(result: Result<String, String>) -> warp::reply::WithStatus<String> {
+    match result {
+        Ok(filter) => warp::reply::with_status(filter, StatusCode::OK),
+        Err(err) => warp::reply::with_status(err, StatusCode::BAD_REQUEST),
+    }
+}
+
+fn unauthorized() -> warp::reply::WithStatus<String> {
+    warp::reply::with_status("unauthorized".to_owned(), StatusCode::UNAUTHORIZED)
+}
@@ -1,13 +1,30 @@
 use {
     once_cell::sync::OnceCell,
-    prometheus::Encoder,
-    std::{collections::HashMap, convert::Infallible, net::SocketAddr, sync::Arc},
-    tokio::task::{self, JoinHandle},
+    prometheus::{proto::MetricFamily, Encoder},
+    prometheus_metric_storage::StorageRegistry,
+    std::{
+        collections::{BTreeMap, HashMap},
+        future::Future,
+        sync::Mutex,
+    },
+    tokio::task::JoinHandle,
     warp::{Filter, Rejection, Reply},
 };
 
 /// Global metrics registry used by all components.
-static REGISTRY: OnceCell<prometheus_metric_storage::StorageRegistry> = OnceCell::new();
+static REGISTRY: OnceCell<StorageRegistry> = OnceCell::new();
+
+/// Prefix and labels the global registry got configured with. Labelled
+/// registries get created with the same configuration.
+static CONFIG: OnceCell<(Option<String>, Option<HashMap<String, String>>)> = OnceCell::new();
+
+/// All registries created with [`labelled_registry`].
+static LABELLED_REGISTRIES: Mutex<Vec<&'static StorageRegistry>> = Mutex::new(Vec::new());
+
+tokio::task_local! {
+    /// Registry overriding the global one for the current task, see [`scope`].
+    static SCOPED_REGISTRY: &'static StorageRegistry;
+}
 
 /// Configure global metrics registry.
 ///
@@ -24,9 +41,10 @@ static REGISTRY: OnceCell<prometheus_metric_storage::StorageRegistry> = OnceCell
 /// any call to [`get_registry`]. This function also panics if registry
 /// configuration is invalid.
 pub fn setup_registry(prefix: Option<String>, labels: Option<HashMap<String, String>>) {
-    let registry = prometheus::Registry::new_custom(prefix, labels).unwrap();
-    let storage_registry = prometheus_metric_storage::StorageRegistry::new(registry);
+    let registry = prometheus::Registry::new_custom(prefix.clone(), labels.clone()).unwrap();
+    let storage_registry = StorageRegistry::new(registry);
     REGISTRY.set(storage_registry).unwrap();
+    CONFIG.set((prefix, labels)).unwrap();
 }
 
 /// Like [`setup_registry`], but can be called multiple times in a row.
@@ -34,17 +52,77 @@ pub fn setup_registry(prefix: Option<String>, labels: Option<HashMap<String, Str
 ///
 /// Useful for tests.
 pub fn setup_registry_reentrant(prefix: Option<String>, labels: Option<HashMap<String, String>>) {
-    let registry = prometheus::Registry::new_custom(prefix, labels).unwrap();
-    let storage_registry = prometheus_metric_storage::StorageRegistry::new(registry);
-    REGISTRY.set(storage_registry).ok();
+    let registry = prometheus::Registry::new_custom(prefix.clone(), labels.clone()).unwrap();
+    let storage_registry = StorageRegistry::new(registry);
+    if REGISTRY.set(storage_registry).is_ok() {
+        CONFIG.set((prefix, labels)).ok();
+    }
 }
 
-/// Get the global instance of the metrics registry.
+/// Creates a registry that adds the given labels to all its metrics on top of
+/// the prefix and labels of the global registry. Components running inside of
+/// [`scope`] with this registry report their metrics to it instead of the
+/// global registry. The metrics of all labelled registries get served
+/// together with the global ones.
+///
+/// Useful to serve the same components multiple times from one process, e.g.
+/// once per chain.
+///
+/// # Panics
+///
+/// This function panics if the labels clash with the labels of the global
+/// registry.
+pub fn labelled_registry(labels: HashMap<String, String>) -> &'static StorageRegistry {
+    let (prefix, global_labels) = CONFIG.get().cloned().unwrap_or_default();
+    let mut all_labels = global_labels.unwrap_or_default();
+    for (name, value) in labels {
+        assert!(
+            all_labels.insert(name.clone(), value).is_none(),
+            "label {name} is already set on the global registry"
+        );
+    }
+    let registry = prometheus::Registry::new_custom(prefix, Some(all_labels)).unwrap();
+    let registry: &'static StorageRegistry = Box::leak(Box::new(StorageRegistry::new(registry)));
+    LABELLED_REGISTRIES.lock().unwrap().push(registry);
+    registry
+}
+
+/// Runs the future with the given registry replacing the global one. This
+/// only affects the current task, tasks spawned from the future report to the
+/// global registry unless they get spawned with [`spawn_in_current_scope`].
+pub async fn scope<F: Future>(registry: &'static StorageRegistry, future: F) -> F::Output {
+    SCOPED_REGISTRY.scope(registry, future).await
+}
+
+/// Like [`scope`] but for synchronous code.
+pub fn sync_scope<R>(registry: &'static StorageRegistry, f: impl FnOnce() -> R) -> R {
+    SCOPED_REGISTRY.sync_scope(registry, f)
+}
+
+/// Spawns a new task and ensures it uses the same registry as the current task
+/// (if scoped). Background tasks of components that can run in a [`scope`]
+/// should be spawned with this so their metrics end up in the same registry.
+pub fn spawn_in_current_scope<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let registry = SCOPED_REGISTRY.try_with(|registry| *registry).ok();
+    tokio::task::spawn(async move {
+        match registry {
+            Some(registry) => SCOPED_REGISTRY.scope(registry, future).await,
+            None => future.await,
+        }
+    })
+}
+
+/// Get the metrics registry of the current scope or the global one.
 pub fn get_registry() -> &'static prometheus::Registry {
     get_storage_registry().registry()
 }
 
-/// Get the global instance of the metric storage registry.
+/// Get the metric storage registry of the current scope (see [`scope`]) or
+/// the global one.
 ///
 /// # Implementation notice
 ///
@@ -54,14 +132,43 @@ pub fn get_registry() -> &'static prometheus::Registry {
 /// a hook that will call [`setup_registry`] before each test, so we'll
 /// have to initialize it manually before every test, which is tedious
 /// to say the least.
-pub fn get_storage_registry() -> &'static prometheus_metric_storage::StorageRegistry {
-    REGISTRY.get_or_init(prometheus_metric_storage::StorageRegistry::default)
+pub fn get_storage_registry() -> &'static StorageRegistry {
+    SCOPED_REGISTRY
+        .try_with(|registry| *registry)
+        .unwrap_or_else(|_| REGISTRY.get_or_init(StorageRegistry::default))
+}
+
+/// Gathers the metrics of the global and all labelled registries. Metric
+/// families registered in multiple registries get merged into one.
+pub fn gather() -> Vec<MetricFamily> {
+    let global = REGISTRY.get_or_init(StorageRegistry::default);
+    let labelled = LABELLED_REGISTRIES.lock().unwrap().clone();
+    let mut families = BTreeMap::<String, MetricFamily>::new();
+    for registry in std::iter::once(global).chain(labelled) {
+        for mut family in registry.registry().gather() {
+            match families.get_mut(family.get_name()) {
+                Some(existing) => {
+                    for metric in family.take_metric().into_vec() {
+                        existing.mut_metric().push(metric);
+                    }
+                }
+                None => {
+                    families.insert(family.get_name().to_string(), family);
+                }
+            }
+        }
+    }
+    families.into_values().collect()
 }
 
 pub fn encode(registry: &prometheus::Registry) -> String {
+    encode_families(&registry.gather())
+}
+
+fn encode_families(families: &[MetricFamily]) -> String {
     let encoder = prometheus::TextEncoder::new();
     let mut buffer = Vec::new();
-    encoder.encode(&registry.gather(), &mut buffer).unwrap();
+    encoder.encode(families, &mut buffer).unwrap();
     String::from_utf8(buffer).unwrap()
 }
 
@@ -72,30 +179,46 @@ pub trait LivenessChecking: Send + Sync {
     async fn is_alive(&self) -> bool;
 }
 
-pub fn serve_metrics(liveness: Arc<dyn LivenessChecking>, address: SocketAddr) -> JoinHandle<()> {
-    let filter = handle_metrics().or(handle_liveness(liveness));
-    tracing::info!(%address, "serving metrics");
-    task::spawn(warp::serve(filter).bind(address))
-}
-
 // `/metrics` route exposing encoded prometheus data to monitoring system
 pub fn handle_metrics() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    let registry = get_registry();
-    warp::path("metrics").map(move || encode(registry))
+    warp::path("metrics").map(|| encode_families(&gather()))
 }
 
-fn handle_liveness(
-    liveness_checker: Arc<dyn LivenessChecking>,
-) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    warp::path("liveness").and_then(move || {
-        let liveness_checker = liveness_checker.clone();
-        async move {
-            let status = if liveness_checker.is_alive().await {
-                warp::http::StatusCode::OK
-            } else {
-                warp::http::StatusCode::SERVICE_UNAVAILABLE
-            };
-            Result::<_, Infallible>::Ok(warp::reply::with_status(warp::reply(), status))
-        }
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scoped_metrics_end_up_in_labelled_registry() {
+        let registry = labelled_registry(HashMap::from([("chain".to_string(), "1".to_string())]));
+        let register = || {
+            let counter = prometheus::IntCounter::new("scoped_requests", "test").unwrap();
+            get_registry().register(Box::new(counter.clone())).unwrap();
+            counter.inc();
+        };
+        register();
+        scope(registry, async {
+            spawn_in_current_scope(async move { register() })
+                .await
+                .unwrap()
+        })
+        .await;
+
+        let family = gather()
+            .into_iter()
+            .find(|family| family.get_name().ends_with("scoped_requests"))
+            .unwrap();
+        let chains: Vec<_> = family
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .find(|label| label.get_name() == "chain")
+                    .map(|label| label.get_value().to_string())
+            })
+            .collect();
+        assert_eq!(chains, [None, Some("1".to_string())]);
+    }
 }
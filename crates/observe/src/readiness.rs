@@ -0,0 +1,239 @@
+//! Readiness gate that holds back traffic until the caches of a service are
+//! warm.
+//!
+//! Components register warmup conditions on the global [`gate`] when they get
+//! created and mark them as met once their caches are populated. The `/ready`
+//! endpoint served next to the metrics only reports the service as ready once
+//! all registered conditions are met and lists the status of every condition,
+//! so orchestrators don't route traffic to a freshly deployed instance too
+//! early.
+
+use {
+    once_cell::sync::OnceCell,
+    serde::Serialize,
+    std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+    tokio::sync::Notify,
+};
+
+static GATE: OnceCell<Gate> = OnceCell::new();
+
+/// Get the global readiness gate of the process.
+pub fn gate() -> &'static Gate {
+    GATE.get_or_init(Gate::default)
+}
+
+/// Registers a warmup condition on the global readiness gate.
+pub fn register(name: &str) -> Condition {
+    gate().register(name)
+}
+
+/// A set of warmup conditions that all need to be met for a service to be
+/// ready.
+#[derive(Clone, Default)]
+pub struct Gate {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    conditions: Mutex<BTreeMap<String, State>>,
+    changed: Notify,
+}
+
+#[derive(Clone, Copy)]
+struct State {
+    ready: bool,
+    /// When the condition was registered or last changed its status.
+    since: Instant,
+}
+
+impl Gate {
+    /// Registers a condition that is not met yet. Registering a condition
+    /// with the name of an existing condition returns a handle to the existing
+    /// one.
+    pub fn register(&self, name: &str) -> Condition {
+        let ready = self
+            .inner
+            .conditions
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| State {
+                ready: false,
+                since: Instant::now(),
+            })
+            .ready;
+        Metrics::get()
+            .readiness_condition
+            .with_label_values(&[name])
+            .set(ready.into());
+        Condition {
+            name: name.into(),
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Whether all registered conditions are met.
+    pub fn is_ready(&self) -> bool {
+        self.inner
+            .conditions
+            .lock()
+            .unwrap()
+            .values()
+            .all(|state| state.ready)
+    }
+
+    /// Waits until all registered conditions are met. Useful to delay work
+    /// that depends on warm caches, like serving requests.
+    pub async fn wait(&self) {
+        loop {
+            // Create the notification future before checking the conditions
+            // so updates in between don't get lost.
+            let changed = self.inner.changed.notified();
+            if self.is_ready() {
+                return;
+            }
+            changed.await;
+        }
+    }
+
+    /// Reports the status of all registered conditions.
+    pub fn report(&self) -> Report {
+        let conditions = self.inner.conditions.lock().unwrap();
+        Report {
+            ready: conditions.values().all(|state| state.ready),
+            conditions: conditions
+                .iter()
+                .map(|(name, state)| {
+                    (
+                        name.clone(),
+                        ConditionReport {
+                            ready: state.ready,
+                            since: state.since.elapsed(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Handle to mark a registered warmup condition as met.
+#[derive(Clone)]
+pub struct Condition {
+    name: String,
+    inner: Arc<Inner>,
+}
+
+impl Condition {
+    /// Marks the condition as met.
+    pub fn ready(&self) {
+        self.set(true);
+    }
+
+    /// Marks the condition as no longer met, for example because a cache had
+    /// to be reset.
+    pub fn unready(&self) {
+        self.set(false);
+    }
+
+    fn set(&self, ready: bool) {
+        {
+            let mut conditions = self.inner.conditions.lock().unwrap();
+            let state = conditions
+                .get_mut(&self.name)
+                .expect("registered condition");
+            if state.ready == ready {
+                return;
+            }
+            *state = State {
+                ready,
+                since: Instant::now(),
+            };
+        }
+        tracing::info!(condition = %self.name, ready, "readiness condition changed");
+        Metrics::get()
+            .readiness_condition
+            .with_label_values(&[&self.name])
+            .set(ready.into());
+        self.inner.changed.notify_waiters();
+    }
+}
+
+/// Status of the readiness gate as reported by the `/ready` endpoint.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub ready: bool,
+    pub conditions: BTreeMap<String, ConditionReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConditionReport {
+    pub ready: bool,
+    /// For how long the condition has had its current status.
+    #[serde(serialize_with = "serialize_seconds")]
+    pub since: Duration,
+}
+
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_secs())
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Whether a readiness condition is met (1) or not (0).
+    #[metric(labels("condition"))]
+    readiness_condition: prometheus::IntGaugeVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(crate::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn ready_once_all_conditions_are_met() {
+        let gate = Gate::default();
+        assert!(gate.is_ready());
+
+        let orders = gate.register("orders");
+        let tokens = gate.register("tokens");
+        assert!(!gate.is_ready());
+
+        orders.ready();
+        let report = gate.report();
+        assert!(!report.ready);
+        assert!(report.conditions["orders"].ready);
+        assert!(!report.conditions["tokens"].ready);
+
+        let wait = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.wait().await }
+        });
+        tokens.ready();
+        tokio::time::timeout(Duration::from_secs(1), wait)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(gate.is_ready());
+
+        // Registering an existing condition keeps its status.
+        gate.register("orders");
+        assert!(gate.is_ready());
+
+        tokens.unready();
+        assert!(!gate.is_ready());
+    }
+}
@@ -16,7 +16,7 @@
 //! And when we issue requests to another process we can simply fetch the
 //! current identifier specific to our task and send that along with the
 //! request.
-use {std::future::Future, tokio::task::JoinHandle};
+use {crate::distributed_tracing, std::future::Future, tokio::task::JoinHandle};
 
 tokio::task_local! {
     pub static REQUEST_ID: String;
@@ -40,18 +40,28 @@ where
     REQUEST_ID.scope(id, scope).await
 }
 
-/// Spawns a new task and ensures it uses the same request id as the current
-/// task (if present). This allows for tracing requests across task boundaries.
+/// Spawns a new task and ensures it uses the same request id and auction id as
+/// the current task (if present). This allows for tracing requests across task
+/// boundaries.
 pub fn spawn_task_with_current_request_id<F>(future: F) -> JoinHandle<F::Output>
 where
     F: Future + Send + 'static,
     F::Output: Send + 'static,
 {
-    if let Some(id) = get_task_local_storage() {
-        tokio::task::spawn(REQUEST_ID.scope(id, future))
-    } else {
-        tokio::task::spawn(future)
-    }
+    let request_id = get_task_local_storage();
+    let auction_id = distributed_tracing::auction_id();
+    tokio::task::spawn(async move {
+        let future = async move {
+            match auction_id {
+                Some(id) => distributed_tracing::with_auction_id(id, future).await,
+                None => future.await,
+            }
+        };
+        match request_id {
+            Some(id) => REQUEST_ID.scope(id, future).await,
+            None => future.await,
+        }
+    })
 }
 
 /// Takes a `tower::Service` and embeds it in a `make_service` function that
@@ -59,7 +69,9 @@ where
 /// But crucially before spawning that service task local storage will be
 /// initialized with some request id.
 /// Either that gets taken from the requests `X-REQUEST-ID` header of if that's
-/// missing a globally unique request number will be generated.
+/// missing a globally unique request number will be generated. The auction id
+/// gets restored from the `X-AUCTION-ID` header if present, see
+/// [`crate::distributed_tracing`].
 #[macro_export]
 macro_rules! make_service_with_task_local_storage {
     ($service:expr) => {{
@@ -72,19 +84,17 @@ macro_rules! make_service_with_task_local_storage {
                     let svc =
                         hyper::service::service_fn(move |req: hyper::Request<hyper::Body>| {
                             let mut warp_svc = warp_svc.clone();
-                            let id = if let Some(header) = req.headers().get("X-Request-ID") {
-                                String::from_utf8_lossy(header.as_bytes()).to_string()
-                            } else {
-                                format!(
-                                    "{}",
-                                    internal_request_id
-                                        .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
-                                )
-                            };
-                            let span = tracing::info_span!("request", id);
-                            let handle_request = observe::request_id::REQUEST_ID
-                                .scope(id, hyper::service::Service::call(&mut warp_svc, req));
-                            tracing::Instrument::instrument(handle_request, span)
+                            observe::distributed_tracing::instrument_request(
+                                observe::distributed_tracing::Context::from_headers(req.headers()),
+                                || {
+                                    format!(
+                                        "{}",
+                                        internal_request_id
+                                            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                                    )
+                                },
+                                hyper::service::Service::call(&mut warp_svc, req),
+                            )
                         });
                     Ok::<_, std::convert::Infallible>(svc)
                 }
@@ -1,11 +1,16 @@
 use {
-    crate::tracing_reload_handler::spawn_reload_handler,
+    crate::{
+        config::{Config, LogFormat},
+        tracing_reload_handler::spawn_reload_handler,
+    },
+    opentelemetry::trace::TracerProvider as _,
     std::{panic::PanicHookInfo, sync::Once},
     time::macros::format_description,
-    tracing::level_filters::LevelFilter,
+    tracing::{level_filters::LevelFilter, Subscriber},
     tracing_subscriber::{
         fmt::{time::UtcTime, writer::MakeWriterExt as _},
         prelude::*,
+        registry::LookupSpan,
         util::SubscriberInitExt,
         EnvFilter,
         Layer,
@@ -16,7 +21,15 @@ use {
 /// `env_filter` has similar syntax to env_logger. It is documented at
 /// https://docs.rs/tracing-subscriber/0.2.15/tracing_subscriber/filter/struct.EnvFilter.html
 pub fn initialize(env_filter: &str, stderr_threshold: LevelFilter) {
-    set_tracing_subscriber(env_filter, stderr_threshold);
+    initialize_with_config(&Config {
+        log_stderr_threshold: stderr_threshold,
+        ..config_from_env(env_filter)
+    });
+}
+
+/// Like [`initialize`], but with all settings taken from the config.
+pub fn initialize_with_config(config: &Config) {
+    set_tracing_subscriber(config);
     std::panic::set_hook(Box::new(tracing_panic_hook));
 }
 
@@ -25,17 +38,41 @@ pub fn initialize(env_filter: &str, stderr_threshold: LevelFilter) {
 ///
 /// Useful for tests.
 pub fn initialize_reentrant(env_filter: &str) {
+    initialize_reentrant_with_config(&config_from_env(env_filter));
+}
+
+/// Like [`initialize_with_config`], but can be called multiple times in a row.
+/// Later calls are ignored.
+///
+/// Useful for binaries that also get started multiple times by tests.
+pub fn initialize_reentrant_with_config(config: &Config) {
     // The tracing subscriber below is global object so initializing it again in the
     // same process by a different thread would fail.
     static ONCE: Once = Once::new();
-    ONCE.call_once(|| {
-        set_tracing_subscriber(env_filter, LevelFilter::ERROR);
-        std::panic::set_hook(Box::new(tracing_panic_hook));
-    });
+    ONCE.call_once(|| initialize_with_config(config));
 }
 
-fn set_tracing_subscriber(env_filter: &str, stderr_threshold: LevelFilter) {
-    let initial_filter = env_filter.to_string();
+fn config_from_env(env_filter: &str) -> Config {
+    Config {
+        log_filter: env_filter.to_string(),
+        log_stderr_threshold: LevelFilter::ERROR,
+        log_format: LogFormat::Plain,
+        tokio_console: tokio_console_from_env(),
+        otlp_endpoint: None,
+        metrics_port: None,
+    }
+}
+
+fn tokio_console_from_env() -> bool {
+    std::env::var("TOKIO_CONSOLE")
+        .unwrap_or("false".to_string())
+        .parse()
+        .unwrap()
+}
+
+fn set_tracing_subscriber(config: &Config) {
+    let initial_filter = config.log_filter.clone();
+    let stderr_threshold = config.log_stderr_threshold;
 
     // The `tracing` APIs are heavily generic to enable zero overhead. Unfortunately
     // this leads to very annoying type constraints which can only be satisfied
@@ -52,7 +89,7 @@ fn set_tracing_subscriber(env_filter: &str, stderr_threshold: LevelFilter) {
     //    work correctly.
     macro_rules! fmt_layer {
         ($env_filter:expr, $stderr_threshold:expr) => {{
-            tracing_subscriber::fmt::layer()
+            let layer = tracing_subscriber::fmt::layer()
                 .with_writer(
                     std::io::stdout
                         .with_min_level(
@@ -64,29 +101,29 @@ fn set_tracing_subscriber(env_filter: &str, stderr_threshold: LevelFilter) {
                 )
                 .with_timer(UtcTime::new(format_description!(
                     "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
-                )))
-                .with_ansi(atty::is(atty::Stream::Stdout))
-                .with_filter($env_filter)
+                )));
+            let layer = match config.log_format {
+                LogFormat::Plain => layer.with_ansi(atty::is(atty::Stream::Stdout)).boxed(),
+                LogFormat::Json => layer.json().boxed(),
+            };
+            layer.with_filter($env_filter)
         }};
     }
 
-    let enable_tokio_console: bool = std::env::var("TOKIO_CONSOLE")
-        .unwrap_or("false".to_string())
-        .parse()
-        .unwrap();
-
-    if cfg!(tokio_unstable) && enable_tokio_console {
+    if cfg!(tokio_unstable) && config.tokio_console {
         let (env_filter, reload_handle) =
             tracing_subscriber::reload::Layer::new(EnvFilter::new(&initial_filter));
 
         tracing_subscriber::registry()
             .with(console_subscriber::spawn())
             .with(fmt_layer!(env_filter, stderr_threshold))
+            .with(otlp_layer(config))
             .init();
         tracing::info!("started programm with support for tokio-console");
 
+        crate::log_filter::register(initial_filter.clone(), reload_handle);
         if cfg!(unix) {
-            spawn_reload_handler(initial_filter, reload_handle);
+            spawn_reload_handler(initial_filter);
         }
     } else {
         let (env_filter, reload_handle) =
@@ -97,15 +134,48 @@ fn set_tracing_subscriber(env_filter: &str, stderr_threshold: LevelFilter) {
             // `sqlx` uses under the hood.
             .with(tracing::level_filters::LevelFilter::TRACE)
             .with(fmt_layer!(env_filter, stderr_threshold))
+            .with(otlp_layer(config))
             .init();
         tracing::info!("started programm without support for tokio-console");
 
+        crate::log_filter::register(initial_filter.clone(), reload_handle);
         if cfg!(unix) {
-            spawn_reload_handler(initial_filter, reload_handle);
+            spawn_reload_handler(initial_filter);
         }
     }
 }
 
+/// Exports spans to the configured OpenTelemetry collector. Spans get filtered
+/// by the initial log filter, which doesn't change with log filter overrides.
+fn otlp_layer<S>(config: &Config) -> Option<impl Layer<S>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let endpoint = config.otlp_endpoint.as_ref()?;
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(endpoint);
+    let provider = match opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+    {
+        Ok(provider) => provider,
+        Err(err) => {
+            // Tracing isn't set up yet, so the error can't be logged.
+            eprintln!("failed to set up exporting spans to {endpoint}: {err}");
+            return None;
+        }
+    };
+    let tracer = provider.tracer("cowprotocol-services");
+    opentelemetry::global::set_tracer_provider(provider);
+    Some(
+        tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .with_filter(EnvFilter::new(&config.log_filter)),
+    )
+}
+
 /// Panic hook that prints roughly the same message as the default panic hook
 /// but uses tracing:error instead of stderr.
 ///
@@ -1,9 +1,6 @@
-use {
-    tokio::{
-        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
-        net::{UnixListener, UnixStream},
-    },
-    tracing_subscriber::{reload, EnvFilter},
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
 };
 
 /// Spawns a new thread that listens for connections to a UNIX socket
@@ -11,10 +8,7 @@ use {
 /// Whenever a line gets written to that socket the reload handler
 /// uses it as the new log filter.
 /// To reset to the original log filter send the message "reset".
-pub(crate) fn spawn_reload_handler<T: 'static>(
-    initial_filter: String,
-    reload_handle: reload::Handle<EnvFilter, T>,
-) {
+pub(crate) fn spawn_reload_handler(initial_filter: String) {
     tokio::spawn(async move {
         let id = std::process::id();
         let name = binary_name().unwrap_or_default();
@@ -28,7 +22,7 @@ pub(crate) fn spawn_reload_handler<T: 'static>(
         };
 
         loop {
-            handle_connection(&handle.listener, &initial_filter, &reload_handle).await;
+            handle_connection(&handle.listener, &initial_filter).await;
         }
     });
 }
@@ -54,11 +48,7 @@ fn binary_name() -> Option<String> {
     )
 }
 
-async fn handle_connection<T>(
-    listener: &UnixListener,
-    initial_filter: &str,
-    reload_handle: &reload::Handle<EnvFilter, T>,
-) {
+async fn handle_connection(listener: &UnixListener, initial_filter: &str) {
     let Ok((mut socket, _addr)) = listener.accept().await else {
         tracing::warn!("failed to accept UNIX socket connection");
         return;
@@ -80,18 +70,12 @@ async fn handle_connection<T>(
                 log(&mut socket, "failed to read message from socket".into()).await;
                 continue;
             }
-            Some("reset") => initial_filter,
             Some(message) => message,
         };
 
-        let Ok(env_filter) = EnvFilter::try_new(filter) else {
-            log(&mut socket, format!("failed to parse filter: {filter:?}")).await;
-            continue;
-        };
-
-        match reload_handle.reload(env_filter) {
-            Ok(_) => log(&mut socket, format!("applied new filter: {filter:?}")).await,
-            Err(err) => log(&mut socket, format!("failed to apply filter: {err:?}")).await,
+        match crate::log_filter::set(filter) {
+            Ok(filter) => log(&mut socket, format!("applied new filter: {filter:?}")).await,
+            Err(err) => log(&mut socket, err).await,
         }
     }
 }
@@ -1,5 +1,16 @@
 use {
-    crate::{app_data, database::Postgres, orderbook::Orderbook, quoter::QuoteHandler},
+    crate::{
+        app_data,
+        arguments::PartnerApiKey,
+        current_auction::CurrentAuction,
+        database::Postgres,
+        ethflow::EthFlow,
+        graphql::GraphQl,
+        notifications::Notifications,
+        order_events::OrderEvents,
+        orderbook::Orderbook,
+        quoter::QuoteHandler,
+    },
     anyhow::Result,
     serde::{de::DeserializeOwned, Serialize},
     shared::price_estimation::{native::NativePriceEstimating, PriceEstimationError},
@@ -16,8 +27,13 @@ use {
 
 mod cancel_order;
 mod cancel_orders;
+mod get_app_code_report;
 mod get_app_data;
 mod get_auction;
+mod get_auction_filtered_orders;
+mod get_ethflow_order_placement;
+mod get_events;
+mod get_fee_policies;
 mod get_native_price;
 mod get_order_by_uid;
 mod get_order_status;
@@ -25,28 +41,73 @@ mod get_orders_by_tx;
 mod get_solver_competition;
 mod get_total_surplus;
 mod get_trades;
+mod get_trades_by_tx;
 mod get_user_orders;
+mod post_ethflow_order;
+mod post_graphql;
 mod post_order;
+mod post_orders_batch;
 mod post_quote;
 mod put_app_data;
+mod put_notification_preferences;
+mod put_order;
+pub mod rate_limit;
 mod version;
 
+/// Everything needed to serve the API of a single chain.
+pub struct Api {
+    pub database: Postgres,
+    pub orderbook: Arc<Orderbook>,
+    pub quotes: Arc<QuoteHandler>,
+    pub app_data: Arc<app_data::Registry>,
+    pub native_price_estimator: Arc<dyn NativePriceEstimating>,
+    pub partner_api_keys: Vec<PartnerApiKey>,
+    pub graphql: Option<Arc<GraphQl>>,
+    pub notifications: Option<Arc<Notifications>>,
+    pub order_events: Option<Arc<OrderEvents>>,
+    pub ethflow: Option<Arc<EthFlow>>,
+    pub current_auction: Arc<CurrentAuction>,
+    pub ip_rate_limit: Option<Arc<rate_limit::IpRateLimit>>,
+    /// Rate limiters of the external endpoints used by this chain.
+    pub rate_limiters: Arc<::rate_limit::RateLimiterRegistry>,
+}
+
 pub fn handle_all_routes(
-    database: Postgres,
-    orderbook: Arc<Orderbook>,
-    quotes: Arc<QuoteHandler>,
-    app_data: Arc<app_data::Registry>,
-    native_price_estimator: Arc<dyn NativePriceEstimating>,
+    api: Api,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let Api {
+        database,
+        orderbook,
+        quotes,
+        app_data,
+        native_price_estimator,
+        partner_api_keys,
+        graphql,
+        notifications,
+        order_events,
+        ethflow,
+        current_auction,
+        ip_rate_limit,
+        rate_limiters: _,
+    } = api;
+
     // Note that we add a string with endpoint's name to all responses.
     // This string will be used later to report metrics.
     // It is not used to form the actual server response.
 
-    let routes = vec![
+    let mut routes = vec![
         (
             "v1/create_order",
             box_filter(post_order::post_order(orderbook.clone())),
         ),
+        (
+            "v1/create_orders_batch",
+            box_filter(post_orders_batch::post_orders_batch(orderbook.clone())),
+        ),
+        (
+            "v1/replace_order",
+            box_filter(put_order::put_order(orderbook.clone())),
+        ),
         (
             "v1/get_order",
             box_filter(get_order_by_uid::get_order_by_uid(orderbook.clone())),
@@ -75,10 +136,18 @@ pub fn handle_all_routes(
             "v1/get_orders_by_tx",
             box_filter(get_orders_by_tx::get_orders_by_tx(orderbook.clone())),
         ),
+        (
+            "v1/get_trades_by_tx",
+            box_filter(get_trades_by_tx::get_trades_by_tx(database.clone())),
+        ),
         ("v1/post_quote", box_filter(post_quote::post_quote(quotes))),
         (
             "v1/auction",
-            box_filter(get_auction::get_auction(orderbook.clone())),
+            box_filter(get_auction::get_auction(current_auction)),
+        ),
+        (
+            "v1/get_auction_filtered_orders",
+            box_filter(get_auction_filtered_orders::get(database.clone())),
         ),
         (
             "v1/solver_competition",
@@ -103,11 +172,58 @@ pub fn handle_all_routes(
             "v1/put_app_data",
             box_filter(put_app_data::filter(app_data)),
         ),
+        (
+            "v1/get_fee_policies",
+            get_fee_policies::get(database.clone()).boxed(),
+        ),
         (
             "v1/get_total_surplus",
-            box_filter(get_total_surplus::get(database)),
+            box_filter(get_total_surplus::get(database.clone())),
+        ),
+        (
+            "v1/get_app_code_report",
+            box_filter(get_app_code_report::get(database, partner_api_keys)),
         ),
     ];
+    if let Some(graphql) = graphql {
+        routes.push((
+            "v1/graphql",
+            box_filter(post_graphql::post_graphql(graphql)),
+        ));
+    }
+    if let Some(notifications) = notifications {
+        routes.push((
+            "v1/put_notification_preferences",
+            box_filter(put_notification_preferences::filter(notifications)),
+        ));
+    }
+    if let Some(order_events) = order_events {
+        routes.push((
+            "v1/get_events",
+            get_events::get_events(order_events).boxed(),
+        ));
+    }
+    if let Some(ethflow) = ethflow {
+        routes.push((
+            "v1/post_ethflow_order",
+            box_filter(post_ethflow_order::filter(ethflow.clone())),
+        ));
+        routes.push((
+            "v1/get_ethflow_order_placement",
+            box_filter(get_ethflow_order_placement::filter(ethflow)),
+        ));
+    }
+    if let Some(ip_rate_limit) = ip_rate_limit {
+        // Has to come first to answer requests of rate limited clients before
+        // they get handled.
+        routes.insert(
+            0,
+            (
+                "v1/rate_limited",
+                box_filter(rate_limit::ip_rate_limit(ip_rate_limit)),
+            ),
+        );
+    }
 
     finalize_router(routes, "orderbook::api::request_summary")
 }
@@ -150,10 +266,12 @@ impl ApiMetrics {
     const INITIAL_STATUSES: &'static [StatusCode] = &[
         StatusCode::OK,
         StatusCode::CREATED,
+        StatusCode::NOT_MODIFIED,
         StatusCode::BAD_REQUEST,
         StatusCode::UNAUTHORIZED,
         StatusCode::FORBIDDEN,
         StatusCode::NOT_FOUND,
+        StatusCode::TOO_MANY_REQUESTS,
         StatusCode::INTERNAL_SERVER_ERROR,
         StatusCode::SERVICE_UNAVAILABLE,
     ];
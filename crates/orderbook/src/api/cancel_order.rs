@@ -1,12 +1,22 @@
 use {
     crate::{
-        api::{convert_json_response, extract_payload, IntoWarpReply},
+        api::{
+            convert_json_response,
+            extract_payload,
+            rate_limit::with_retry_after,
+            IntoWarpReply,
+        },
         orderbook::{OrderCancellationError, Orderbook},
     },
     anyhow::Result,
     model::order::{CancellationPayload, OrderCancellation, OrderUid},
     std::{convert::Infallible, sync::Arc},
-    warp::{hyper::StatusCode, reply::with_status, Filter, Rejection},
+    warp::{
+        hyper::StatusCode,
+        reply::{with_status, Response},
+        Filter,
+        Rejection,
+    },
 };
 
 pub fn cancel_order_request(
@@ -52,9 +62,15 @@ impl IntoWarpReply for OrderCancellationError {
                 StatusCode::UNAUTHORIZED,
             ),
             Self::OnChainOrder => with_status(
-                super::error("OnChainOrder", "On-chain orders must be cancelled on-chain"),
+                super::error(
+                    "OnChainOrder",
+                    "Orders without an ECDSA signature can't be cancelled off-chain. Cancel them \
+                     by calling `invalidateOrder(orderUid)` on the settlement contract (or on the \
+                     ethflow contract for ethflow orders) instead.",
+                ),
                 StatusCode::BAD_REQUEST,
             ),
+            Self::RateLimited(retry_after) => super::rate_limit::rate_limited_reply(retry_after),
             Self::Other(err) => {
                 tracing::error!(?err, "cancel_order");
                 crate::api::internal_error_reply()
@@ -69,12 +85,19 @@ pub fn cancel_order_response(result: Result<(), OrderCancellationError>) -> supe
 
 pub fn cancel_order(
     orderbook: Arc<Orderbook>,
-) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
     cancel_order_request().and_then(move |order| {
         let orderbook = orderbook.clone();
         async move {
             let result = orderbook.cancel_order(order).await;
-            Result::<_, Infallible>::Ok(cancel_order_response(result))
+            let retry_after = result
+                .as_ref()
+                .err()
+                .and_then(OrderCancellationError::retry_after);
+            Result::<_, Infallible>::Ok(with_retry_after(
+                cancel_order_response(result),
+                retry_after,
+            ))
         }
     })
 }
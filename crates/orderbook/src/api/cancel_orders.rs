@@ -1,12 +1,12 @@
 use {
     crate::{
-        api::{convert_json_response, extract_payload},
+        api::{convert_json_response, extract_payload, rate_limit::with_retry_after},
         orderbook::{OrderCancellationError, Orderbook},
     },
     anyhow::Result,
     model::order::SignedOrderCancellations,
     std::{convert::Infallible, sync::Arc},
-    warp::{Filter, Rejection},
+    warp::{reply::Response, Filter, Rejection},
 };
 
 pub fn request() -> impl Filter<Extract = (SignedOrderCancellations,), Error = Rejection> + Clone {
@@ -21,12 +21,16 @@ pub fn response(result: Result<(), OrderCancellationError>) -> super::ApiReply {
 
 pub fn filter(
     orderbook: Arc<Orderbook>,
-) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
     request().and_then(move |cancellations| {
         let orderbook = orderbook.clone();
         async move {
             let result = orderbook.cancel_orders(cancellations).await;
-            Result::<_, Infallible>::Ok(response(result))
+            let retry_after = result
+                .as_ref()
+                .err()
+                .and_then(OrderCancellationError::retry_after);
+            Result::<_, Infallible>::Ok(with_retry_after(response(result), retry_after))
         }
     })
 }
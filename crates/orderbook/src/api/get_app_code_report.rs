@@ -0,0 +1,134 @@
+use {
+    crate::{api::ApiReply, arguments::PartnerApiKey, database::Postgres},
+    chrono::NaiveDate,
+    primitive_types::H160,
+    serde::Deserialize,
+    std::{collections::HashMap, convert::Infallible, sync::Arc},
+    warp::{hyper::StatusCode, reply::with_status, Filter, Rejection},
+};
+
+/// Longest time range that can be requested in a single report.
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Query {
+    app_code: String,
+    from: NaiveDate,
+    to: NaiveDate,
+    after: Option<H160>,
+    limit: Option<u64>,
+}
+
+fn request() -> impl Filter<Extract = (Option<String>, Query), Error = Rejection> + Clone {
+    warp::path!("v1" / "app_code_report")
+        .and(warp::get())
+        .and(warp::header::optional::<String>("x-api-key"))
+        .and(warp::query::<Query>())
+}
+
+pub fn get(
+    db: Postgres,
+    api_keys: Vec<PartnerApiKey>,
+) -> impl Filter<Extract = (ApiReply,), Error = Rejection> + Clone {
+    // Maps API keys to the app code they grant access to.
+    let api_keys: Arc<HashMap<String, String>> = Arc::new(
+        api_keys
+            .into_iter()
+            .map(|api_key| (api_key.key, api_key.app_code))
+            .collect(),
+    );
+    request().and_then(move |api_key: Option<String>, query: Query| {
+        let db = db.clone();
+        let api_keys = api_keys.clone();
+        async move {
+            const DEFAULT_LIMIT: u64 = 100;
+            const MIN_LIMIT: u64 = 1;
+            const MAX_LIMIT: u64 = 1000;
+
+            let authorized = api_key
+                .and_then(|key| api_keys.get(&key))
+                .is_some_and(|authorized| *authorized == query.app_code);
+            if !authorized {
+                return Ok(with_status(
+                    super::error(
+                        "Unauthorized",
+                        "missing or invalid API key for this app code",
+                    ),
+                    StatusCode::UNAUTHORIZED,
+                ));
+            }
+
+            let days = (query.to - query.from).num_days();
+            if !(0..MAX_RANGE_DAYS).contains(&days) {
+                return Ok(with_status(
+                    super::error(
+                        "InvalidTimeRange",
+                        format!(
+                            "`from` must not be after `to` and the range must not exceed \
+                             {MAX_RANGE_DAYS} days"
+                        ),
+                    ),
+                    StatusCode::BAD_REQUEST,
+                ));
+            }
+
+            let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+            if !(MIN_LIMIT..=MAX_LIMIT).contains(&limit) {
+                return Ok(with_status(
+                    super::error(
+                        "LIMIT_OUT_OF_BOUNDS",
+                        format!("The pagination limit is [{MIN_LIMIT},{MAX_LIMIT}]."),
+                    ),
+                    StatusCode::BAD_REQUEST,
+                ));
+            }
+
+            let result = db
+                .app_code_report(&query.app_code, query.from, query.to, query.after, limit)
+                .await;
+            Result::<_, Infallible>::Ok(match result {
+                Ok(report) => with_status(warp::reply::json(&report), StatusCode::OK),
+                Err(err) => {
+                    tracing::error!(?err, ?query, "failed to fetch app code report");
+                    crate::api::internal_error_reply()
+                }
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn request_() {
+        let path = "/v1/app_code_report?appCode=CoW%20Swap&from=2024-01-01&to=2024-01-31";
+        let result = warp::test::request()
+            .path(path)
+            .method("GET")
+            .header("X-API-Key", "secret")
+            .filter(&request())
+            .await
+            .unwrap();
+        assert_eq!(result.0.as_deref(), Some("secret"));
+        assert_eq!(result.1.app_code, "CoW Swap");
+        assert_eq!(result.1.from, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(result.1.to, NaiveDate::from_ymd_opt(2024, 1, 31).unwrap());
+        assert_eq!(result.1.after, None);
+        assert_eq!(result.1.limit, None);
+
+        let path = "/v1/app_code_report?appCode=partner&from=2024-01-01&to=2024-01-31&after=\
+                    0x0101010101010101010101010101010101010101&limit=2";
+        let result = warp::test::request()
+            .path(path)
+            .method("GET")
+            .filter(&request())
+            .await
+            .unwrap();
+        assert_eq!(result.0, None);
+        assert_eq!(result.1.after, Some(H160([1; 20])));
+        assert_eq!(result.1.limit, Some(2));
+    }
+}
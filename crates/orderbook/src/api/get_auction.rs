@@ -1,31 +1,48 @@
 use {
-    crate::{api::ApiReply, orderbook::Orderbook},
+    crate::current_auction::{CurrentAuction, Snapshot},
     anyhow::Result,
     reqwest::StatusCode,
     std::{convert::Infallible, sync::Arc},
-    warp::{reply::with_status, Filter, Rejection},
+    warp::{
+        http::header::CONTENT_TYPE,
+        hyper::Body,
+        reply::{with_status, Response},
+        Filter,
+        Rejection,
+        Reply,
+    },
 };
 
 fn get_auction_request() -> impl Filter<Extract = (), Error = Rejection> + Clone {
     warp::path!("v1" / "auction").and(warp::get())
 }
 
+/// Responds with the already serialized auction.
+fn auction_response(snapshot: &Snapshot) -> Response {
+    let mut response = Response::new(Body::from(snapshot.json.clone()));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, "application/json".parse().unwrap());
+    response
+}
+
 pub fn get_auction(
-    orderbook: Arc<Orderbook>,
-) -> impl Filter<Extract = (ApiReply,), Error = Rejection> + Clone {
+    current_auction: Arc<CurrentAuction>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
     get_auction_request().and_then(move || {
-        let orderbook = orderbook.clone();
+        let current_auction = current_auction.clone();
         async move {
-            let result = orderbook.get_auction().await;
+            let result = current_auction.get().await;
             let reply = match result {
-                Ok(Some(auction)) => with_status(warp::reply::json(&auction), StatusCode::OK),
+                Ok(Some(snapshot)) => auction_response(&snapshot),
                 Ok(None) => with_status(
                     super::error("NotFound", "There is no active auction"),
                     StatusCode::NOT_FOUND,
-                ),
+                )
+                .into_response(),
                 Err(err) => {
                     tracing::error!(?err, "/api/v1/get_auction");
-                    crate::api::internal_error_reply()
+                    crate::api::internal_error_reply().into_response()
                 }
             };
             Result::<_, Infallible>::Ok(reply)
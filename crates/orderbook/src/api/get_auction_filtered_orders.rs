@@ -0,0 +1,64 @@
+use {
+    crate::database::Postgres,
+    model::{auction::AuctionId, order::OrderUid},
+    serde::Deserialize,
+    std::convert::Infallible,
+    warp::{hyper::StatusCode, reply::with_status, Filter, Rejection},
+};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Query {
+    order_uid: Option<OrderUid>,
+}
+
+fn request() -> impl Filter<Extract = (AuctionId, Query), Error = Rejection> + Clone {
+    warp::path!("v1" / "auctions" / AuctionId / "filtered_orders")
+        .and(warp::get())
+        .and(warp::query::<Query>())
+}
+
+/// Internal endpoint listing the candidate orders that got filtered out of an
+/// auction and why.
+pub fn get(db: Postgres) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+    request().and_then(move |auction_id: AuctionId, query: Query| {
+        let db = db.clone();
+        async move {
+            let result = db
+                .auction_filtered_orders(auction_id, query.order_uid.as_ref())
+                .await;
+            Result::<_, Infallible>::Ok(match result {
+                Ok(orders) => with_status(warp::reply::json(&orders), StatusCode::OK),
+                Err(err) => {
+                    tracing::error!(?err, ?auction_id, "failed to fetch filtered orders");
+                    crate::api::internal_error_reply()
+                }
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parses_request() {
+        let filter = request();
+        let (auction_id, query) = warp::test::request()
+            .path("/v1/auctions/42/filtered_orders")
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(auction_id, 42);
+        assert_eq!(query.order_uid, None);
+
+        let uid = OrderUid([1; 56]);
+        let (_, query) = warp::test::request()
+            .path(&format!("/v1/auctions/42/filtered_orders?orderUid={uid}"))
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(query.order_uid, Some(uid));
+    }
+}
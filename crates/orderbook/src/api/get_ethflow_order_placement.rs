@@ -0,0 +1,100 @@
+use {
+    crate::{
+        api::ApiReply,
+        ethflow::{EthFlow, Placement},
+    },
+    anyhow::Result,
+    model::order::OrderUid,
+    std::{convert::Infallible, sync::Arc},
+    warp::{hyper::StatusCode, reply::with_status, Filter, Rejection},
+};
+
+fn request() -> impl Filter<Extract = (OrderUid,), Error = Rejection> + Clone {
+    warp::path!("v1" / "ethflow_orders" / OrderUid / "placement").and(warp::get())
+}
+
+fn response(result: Result<Option<Placement>>) -> ApiReply {
+    match result {
+        Ok(Some(placement)) => with_status(warp::reply::json(&placement), StatusCode::OK),
+        Ok(None) => with_status(
+            super::error(
+                "PlacementNotFound",
+                "No placement of this eth-flow order was indexed (yet)",
+            ),
+            StatusCode::NOT_FOUND,
+        ),
+        Err(err) => {
+            tracing::error!(?err, "get_ethflow_order_placement");
+            crate::api::internal_error_reply()
+        }
+    }
+}
+
+pub fn filter(
+    ethflow: Arc<EthFlow>,
+) -> impl Filter<Extract = (ApiReply,), Error = Rejection> + Clone {
+    request().and_then(move |uid: OrderUid| {
+        let ethflow = ethflow.clone();
+        async move {
+            let result = ethflow.placement(&uid).await;
+            Result::<_, Infallible>::Ok(response(result))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::api::response_body,
+        model::order::OnchainOrderPlacementError,
+        primitive_types::H160,
+        serde_json::json,
+        warp::{test::request as test_request, Reply},
+    };
+
+    #[tokio::test]
+    async fn request_ok() {
+        let uid = OrderUid([1; 56]);
+        let result = test_request()
+            .path(&format!("/v1/ethflow_orders/{uid}/placement"))
+            .method("GET")
+            .filter(&request())
+            .await
+            .unwrap();
+        assert_eq!(result, uid);
+    }
+
+    #[tokio::test]
+    async fn response_ok() {
+        let placement = Placement {
+            sender: H160([1; 20]),
+            block_number: 42,
+            placement_error: Some(OnchainOrderPlacementError::NonZeroFee),
+        };
+        let response = response(Ok(Some(placement))).into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            serde_json::from_slice(&response_body(response).await).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "sender": "0x0101010101010101010101010101010101010101",
+                "blockNumber": 42,
+                "placementError": "nonZeroFee",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn response_not_indexed() {
+        let response = response(Ok(None)).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn response_error() {
+        let response = response(Err(anyhow::anyhow!("database down"))).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
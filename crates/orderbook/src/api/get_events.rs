@@ -0,0 +1,151 @@
+use {
+    crate::{
+        api::error,
+        order_events::{
+            OrderEvents,
+            SubscribeError,
+            Subscription,
+            Update,
+            MAX_SUBSCRIPTION_SIZE,
+        },
+    },
+    futures::StreamExt,
+    serde::Deserialize,
+    std::{collections::HashSet, convert::Infallible, hash::Hash, str::FromStr, sync::Arc},
+    warp::{hyper::StatusCode, reply::with_status, sse, Filter, Rejection, Reply},
+};
+
+/// Owners and order uids are passed as comma separated lists.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Query {
+    owners: Option<String>,
+    order_uids: Option<String>,
+}
+
+impl Query {
+    fn subscription(&self) -> Result<Subscription, String> {
+        let subscription = Subscription {
+            owners: parse_list(self.owners.as_deref(), "owner")?,
+            order_uids: parse_list(self.order_uids.as_deref(), "order uid")?,
+        };
+        if subscription.is_empty() {
+            return Err("Must subscribe to at least one owner or order uid.".to_owned());
+        }
+        if subscription.len() > MAX_SUBSCRIPTION_SIZE {
+            return Err(format!(
+                "Cannot subscribe to more than {MAX_SUBSCRIPTION_SIZE} owners and order uids."
+            ));
+        }
+        Ok(subscription)
+    }
+}
+
+fn parse_list<T: FromStr + Eq + Hash>(
+    list: Option<&str>,
+    name: &str,
+) -> Result<HashSet<T>, String> {
+    list.into_iter()
+        .flat_map(|list| list.split(','))
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.parse()
+                .map_err(|_| format!("Invalid {name} {item:?}."))
+        })
+        .collect()
+}
+
+fn get_events_request(
+) -> impl Filter<Extract = (Result<Subscription, String>,), Error = Rejection> + Clone {
+    warp::path!("v1" / "events")
+        .and(warp::get())
+        .and(warp::query::<Query>())
+        .map(|query: Query| query.subscription())
+}
+
+pub fn get_events(
+    events: Arc<OrderEvents>,
+) -> impl Filter<Extract = (Box<dyn Reply>,), Error = Rejection> + Clone {
+    get_events_request().map(move |subscription: Result<Subscription, String>| {
+        let subscription = match subscription {
+            Ok(subscription) => subscription,
+            Err(msg) => {
+                let err = error("InvalidSubscription", msg);
+                return Box::new(with_status(err, StatusCode::BAD_REQUEST)) as Box<dyn Reply>;
+            }
+        };
+        match events.subscribe(subscription) {
+            Ok(updates) => {
+                let updates = updates.map(|update| Ok::<_, Infallible>(sse_event(update)));
+                Box::new(sse::reply(sse::keep_alive().stream(updates)))
+            }
+            Err(SubscribeError::TooManySubscribers) => Box::new(with_status(
+                error("TooManySubscribers", "Too many clients are subscribed."),
+                StatusCode::SERVICE_UNAVAILABLE,
+            )),
+        }
+    })
+}
+
+fn sse_event(update: Update) -> sse::Event {
+    match update {
+        Update::Event(event) => sse::Event::default()
+            .event(event.kind.as_str())
+            .json_data(event.as_ref())
+            .expect("order events serialize"),
+        Update::Lagged(missed) => sse::Event::default()
+            .event("lagged")
+            .data(missed.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        model::order::OrderUid,
+        primitive_types::H160,
+        warp::test::request,
+    };
+
+    #[tokio::test]
+    async fn get_events_request_ok() {
+        let filter = get_events_request();
+        let owner = H160([1; 20]);
+        let uids = [OrderUid([2; 56]), OrderUid([3; 56])];
+        let path = format!(
+            "/v1/events?owners=0x{owner:x}&orderUids={},{}",
+            uids[0], uids[1]
+        );
+        let result = request()
+            .path(&path)
+            .method("GET")
+            .filter(&filter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.owners, [owner].into());
+        assert_eq!(result.order_uids, uids.into());
+    }
+
+    #[tokio::test]
+    async fn get_events_request_err() {
+        let filter = get_events_request();
+        let subscription = |path: &'static str| {
+            let filter = filter.clone();
+            async move {
+                request()
+                    .path(path)
+                    .method("GET")
+                    .filter(&filter)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert!(subscription("/v1/events").await.is_err());
+        assert!(subscription("/v1/events?owners=0x01").await.is_err());
+        assert!(subscription("/v1/events?orderUids=").await.is_err());
+    }
+}
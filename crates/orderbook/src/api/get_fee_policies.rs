@@ -0,0 +1,98 @@
+use {
+    crate::database::Postgres,
+    reqwest::StatusCode,
+    std::convert::Infallible,
+    warp::{reply, Filter, Rejection, Reply},
+};
+
+/// How long clients may cache the configuration. The configuration only
+/// changes when the autopilot gets restarted with different arguments.
+const CACHE_CONTROL: &str = "public, max-age=60";
+
+fn request() -> impl Filter<Extract = (Option<String>,), Error = Rejection> + Clone {
+    warp::path!("v1" / "fee_policies")
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+}
+
+fn etag(version: u64) -> String {
+    format!("\"{version}\"")
+}
+
+/// Whether the `If-None-Match` header of the request matches the entity tag.
+fn matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+pub fn get(
+    database: Postgres,
+) -> impl Filter<Extract = (Box<dyn Reply>,), Error = Rejection> + Clone {
+    request().and_then(move |if_none_match: Option<String>| {
+        let database = database.clone();
+        async move {
+            let result = database.active_fee_policy_configuration().await;
+            Result::<_, Infallible>::Ok(match result {
+                Ok(Some(configuration)) => {
+                    let etag = etag(configuration.version);
+                    let response: Box<dyn Reply> = match if_none_match {
+                        Some(if_none_match) if matches(&if_none_match, &etag) => {
+                            Box::new(StatusCode::NOT_MODIFIED)
+                        }
+                        _ => Box::new(reply::json(&configuration)),
+                    };
+                    Box::new(reply::with_header(
+                        reply::with_header(response, "etag", etag),
+                        "cache-control",
+                        CACHE_CONTROL,
+                    ))
+                }
+                Ok(None) => Box::new(reply::with_status(
+                    "no fee policy configuration found",
+                    StatusCode::NOT_FOUND,
+                )),
+                Err(err) => {
+                    tracing::error!(?err, "get_fee_policies");
+                    Box::new(crate::api::internal_error_reply())
+                }
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, warp::test::request as test_request};
+
+    #[tokio::test]
+    async fn get_fee_policies_request() {
+        let filter = request();
+        let if_none_match = test_request()
+            .path("/v1/fee_policies")
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(if_none_match, None);
+
+        let if_none_match = test_request()
+            .path("/v1/fee_policies")
+            .header("If-None-Match", "\"3\"")
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(if_none_match.as_deref(), Some("\"3\""));
+    }
+
+    #[test]
+    fn matches_entity_tags() {
+        let etag = etag(3);
+        assert!(matches("\"3\"", &etag));
+        assert!(matches("W/\"3\"", &etag));
+        assert!(matches("\"2\", \"3\"", &etag));
+        assert!(matches("*", &etag));
+        assert!(!matches("\"2\"", &etag));
+        assert!(!matches("3", &etag));
+    }
+}
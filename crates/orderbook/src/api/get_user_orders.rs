@@ -1,6 +1,7 @@
 use {
     crate::{api::ApiReply, orderbook::Orderbook},
     anyhow::Result,
+    model::order::OrderUid,
     primitive_types::H160,
     serde::Deserialize,
     std::{convert::Infallible, sync::Arc},
@@ -9,8 +10,11 @@ use {
 
 #[derive(Clone, Copy, Debug, Deserialize)]
 struct Query {
-    offset: Option<u64>,
+    after: Option<OrderUid>,
     limit: Option<u64>,
+    /// Replaced by `after`. Only parsed to tell clients still using it how to
+    /// migrate instead of silently returning the first page.
+    offset: Option<u64>,
 }
 
 fn request() -> impl Filter<Extract = (H160, Query), Error = Rejection> + Clone {
@@ -25,11 +29,19 @@ pub fn get_user_orders(
     request().and_then(move |owner: H160, query: Query| {
         let orderbook = orderbook.clone();
         async move {
-            const DEFAULT_OFFSET: u64 = 0;
             const DEFAULT_LIMIT: u64 = 10;
             const MIN_LIMIT: u64 = 1;
             const MAX_LIMIT: u64 = 1000;
-            let offset = query.offset.unwrap_or(DEFAULT_OFFSET);
+            if query.offset.is_some() {
+                return Ok(with_status(
+                    super::error(
+                        "OFFSET_NOT_SUPPORTED",
+                        "Pagination by offset is no longer supported. Set `after` to the UID of \
+                         the last order of the previous page instead.",
+                    ),
+                    StatusCode::BAD_REQUEST,
+                ));
+            }
             let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
             if !(MIN_LIMIT..=MAX_LIMIT).contains(&limit) {
                 return Ok(with_status(
@@ -40,7 +52,9 @@ pub fn get_user_orders(
                     StatusCode::BAD_REQUEST,
                 ));
             }
-            let result = orderbook.get_user_orders(&owner, offset, limit).await;
+            let result = orderbook
+                .get_user_orders(&owner, query.after.as_ref(), limit)
+                .await;
             Result::<_, Infallible>::Ok(match result {
                 Ok(reply) => with_status(warp::reply::json(&reply), StatusCode::OK),
                 Err(err) => {
@@ -66,17 +80,30 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(result.0, addr!("0000000000000000000000000000000000000001"));
-        assert_eq!(result.1.offset, None);
+        assert_eq!(result.1.after, None);
         assert_eq!(result.1.limit, None);
 
-        let path = "/v1/account/0x0000000000000000000000000000000000000001/orders?offset=1&limit=2";
+        let uid = OrderUid([1; 56]);
+        let path = format!(
+            "/v1/account/0x0000000000000000000000000000000000000001/orders?after={uid}&limit=2"
+        );
         let result = warp::test::request()
-            .path(path)
+            .path(&path)
             .method("GET")
             .filter(&request())
             .await
             .unwrap();
-        assert_eq!(result.1.offset, Some(1));
+        assert_eq!(result.1.after, Some(uid));
         assert_eq!(result.1.limit, Some(2));
+        assert_eq!(result.1.offset, None);
+
+        let path = "/v1/account/0x0000000000000000000000000000000000000001/orders?offset=10";
+        let result = warp::test::request()
+            .path(path)
+            .method("GET")
+            .filter(&request())
+            .await
+            .unwrap();
+        assert_eq!(result.1.offset, Some(10));
     }
 }
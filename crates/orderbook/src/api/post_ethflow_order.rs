@@ -0,0 +1,104 @@
+use {
+    super::post_order::{PartialValidationErrorWrapper, ValidationErrorWrapper},
+    crate::{
+        api::{convert_json_response, extract_payload, IntoWarpReply},
+        ethflow::{EthFlow, EthFlowOrderCreation, PlacementError},
+    },
+    std::{convert::Infallible, sync::Arc},
+    warp::{hyper::StatusCode, reply::with_status, Filter, Rejection},
+};
+
+fn request() -> impl Filter<Extract = (EthFlowOrderCreation,), Error = Rejection> + Clone {
+    warp::path!("v1" / "ethflow_orders")
+        .and(warp::post())
+        .and(extract_payload())
+}
+
+impl IntoWarpReply for PlacementError {
+    fn into_warp_reply(self) -> super::ApiReply {
+        match self {
+            Self::NonNativeSellToken => with_status(
+                super::error(
+                    "NonNativeSellToken",
+                    "Eth-flow orders have to sell the chain's native token \
+                     (0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee)",
+                ),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::MissingReceiver => with_status(
+                super::error("MissingReceiver", "Eth-flow orders need a receiver"),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::ZeroAmount => with_status(
+                super::error("ZeroAmount", "Buy or sell amount is zero."),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::Validation(err) => PartialValidationErrorWrapper(err).into_warp_reply(),
+            Self::Quote(err) => ValidationErrorWrapper(err).into_warp_reply(),
+        }
+    }
+}
+
+pub fn filter(
+    ethflow: Arc<EthFlow>,
+) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+    request().and_then(move |order: EthFlowOrderCreation| {
+        let ethflow = ethflow.clone();
+        async move {
+            let result = ethflow.prepare_order(&order).await;
+            if let Err(err) = &result {
+                tracing::debug!(?err, ?order, "post_ethflow_order error");
+            }
+            Result::<_, Infallible>::Ok(convert_json_response(result))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        app_data::AppDataHash,
+        model::order::BUY_ETH_ADDRESS,
+        primitive_types::H160,
+        serde_json::json,
+        warp::test::request as test_request,
+    };
+
+    #[tokio::test]
+    async fn post_ethflow_order_request_ok() {
+        let order = test_request()
+            .path("/v1/ethflow_orders")
+            .method("POST")
+            .header("content-type", "application/json")
+            .json(&json!({
+                "from": "0x0101010101010101010101010101010101010101",
+                "sellToken": "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee",
+                "buyToken": "0x0202020202020202020202020202020202020202",
+                "receiver": "0x0303030303030303030303030303030303030303",
+                "sellAmount": "1000",
+                "buyAmount": "2000",
+                "validTo": 1700000000,
+                "appData": format!("0x{}", "04".repeat(32)),
+                "quoteId": 42,
+            }))
+            .filter(&request())
+            .await
+            .unwrap();
+        assert_eq!(
+            order,
+            EthFlowOrderCreation {
+                from: H160([1; 20]),
+                sell_token: BUY_ETH_ADDRESS,
+                buy_token: H160([2; 20]),
+                receiver: H160([3; 20]),
+                sell_amount: 1_000.into(),
+                buy_amount: 2_000.into(),
+                valid_to: 1_700_000_000,
+                app_data: AppDataHash([4; 32]),
+                partially_fillable: false,
+                quote_id: 42,
+            }
+        );
+    }
+}
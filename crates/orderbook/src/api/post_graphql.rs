@@ -0,0 +1,51 @@
+use {
+    crate::{api, graphql::GraphQl},
+    std::{convert::Infallible, sync::Arc},
+    warp::{hyper::StatusCode, reply::with_status, Filter, Rejection},
+};
+
+/// Queries are small but may contain many fields and fragments.
+const MAX_QUERY_SIZE: u64 = 1024 * 64;
+
+fn post_graphql_request(
+) -> impl Filter<Extract = (async_graphql::Request,), Error = Rejection> + Clone {
+    warp::path!("v1" / "graphql")
+        .and(warp::post())
+        .and(api::extract_payload_with_max_size(MAX_QUERY_SIZE))
+}
+
+pub fn post_graphql(
+    graphql: Arc<GraphQl>,
+) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+    post_graphql_request().and_then(move |request: async_graphql::Request| {
+        let graphql = graphql.clone();
+        async move {
+            // As usual for GraphQL, errors are reported in the response body
+            // instead of the status code.
+            let response = graphql.execute(request).await;
+            Result::<_, Infallible>::Ok(with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, warp::test::request};
+
+    #[tokio::test]
+    async fn post_graphql_request_ok() {
+        let filter = post_graphql_request();
+        let request = request()
+            .path("/v1/graphql")
+            .method("POST")
+            .header("content-type", "application/json")
+            .json(&serde_json::json!({
+                "query": "query Order($uid: OrderUid!) { order(uid: $uid) { status } }",
+                "operationName": "Order",
+                "variables": { "uid": "0x01" },
+            }));
+        let result = request.filter(&filter).await.unwrap();
+        assert_eq!(result.operation_name.as_deref(), Some("Order"));
+        assert!(result.query.contains("order(uid: $uid)"));
+    }
+}
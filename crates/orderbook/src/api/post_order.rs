@@ -1,6 +1,13 @@
 use {
     crate::{
-        api::{error, extract_payload, ApiReply, IntoWarpReply},
+        api::{
+            error,
+            extract_payload,
+            rate_limit::with_retry_after,
+            rich_error,
+            ApiReply,
+            IntoWarpReply,
+        },
         orderbook::{AddOrderError, Orderbook},
     },
     anyhow::Result,
@@ -9,16 +16,18 @@ use {
         quote::QuoteId,
         signature,
     },
+    serde_json::json,
     shared::order_validation::{
         AppDataValidationError,
         OrderValidToError,
         PartialValidationError,
+        QuoteVerificationError,
         ValidationError,
     },
     std::{convert::Infallible, sync::Arc},
     warp::{
         hyper::StatusCode,
-        reply::{self, with_status},
+        reply::{self, with_status, Response},
         Filter,
         Rejection,
     },
@@ -116,7 +125,7 @@ impl IntoWarpReply for AppDataValidationErrorWrapper {
     }
 }
 
-pub struct ValidationErrorWrapper(ValidationError);
+pub struct ValidationErrorWrapper(pub ValidationError);
 impl IntoWarpReply for ValidationErrorWrapper {
     fn into_warp_reply(self) -> ApiReply {
         match self.0 {
@@ -204,6 +213,26 @@ impl IntoWarpReply for ValidationErrorWrapper {
                 ),
                 StatusCode::BAD_REQUEST,
             ),
+            ValidationError::InvalidQuoteCommitment(err) => with_status(
+                error("InvalidQuoteCommitment", err.to_string()),
+                StatusCode::BAD_REQUEST,
+            ),
+            ValidationError::QuoteVerification(err) => with_status(
+                rich_error(
+                    match err {
+                        QuoteVerificationError::MissingQuoteId
+                        | QuoteVerificationError::NotFound => "QuoteNotFound",
+                        _ => "InvalidQuote",
+                    },
+                    err.to_string(),
+                    json!({ "reason": err.reason() }),
+                ),
+                StatusCode::BAD_REQUEST,
+            ),
+            ValidationError::Bridging(err) => with_status(
+                error("InvalidBridging", err.to_string()),
+                StatusCode::BAD_REQUEST,
+            ),
             ValidationError::ZeroAmount => with_status(
                 error("ZeroAmount", "Buy or sell amount is zero."),
                 StatusCode::BAD_REQUEST,
@@ -266,6 +295,21 @@ impl IntoWarpReply for AddOrderError {
                 super::error("MetadataSerializationFailed", err.to_string()),
                 StatusCode::INTERNAL_SERVER_ERROR,
             ),
+            err @ AddOrderError::EmbargoNotSupported => reply::with_status(
+                super::error("EmbargoNotSupported", err.to_string()),
+                StatusCode::BAD_REQUEST,
+            ),
+            err @ AddOrderError::ReplacementInBatch => reply::with_status(
+                super::error("ReplacementInBatch", err.to_string()),
+                StatusCode::BAD_REQUEST,
+            ),
+            err @ AddOrderError::ReplacedOrderMismatch => reply::with_status(
+                super::error("ReplacedOrderMismatch", err.to_string()),
+                StatusCode::BAD_REQUEST,
+            ),
+            AddOrderError::RateLimited(retry_after) => {
+                super::rate_limit::rate_limited_reply(retry_after)
+            }
         }
     }
 }
@@ -281,7 +325,7 @@ pub fn create_order_response(
 
 pub fn post_order(
     orderbook: Arc<Orderbook>,
-) -> impl Filter<Extract = (ApiReply,), Error = Rejection> + Clone {
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
     create_order_request().and_then(move |order: OrderCreation| {
         let orderbook = orderbook.clone();
         async move {
@@ -293,7 +337,11 @@ pub fn post_order(
                 Err(err) => tracing::debug!(?order, ?err, "error creating order"),
             }
 
-            Result::<_, Infallible>::Ok(create_order_response(result))
+            let retry_after = result.as_ref().err().and_then(AddOrderError::retry_after);
+            Result::<_, Infallible>::Ok(with_retry_after(
+                create_order_response(result),
+                retry_after,
+            ))
         }
     })
 }
@@ -0,0 +1,128 @@
+use {
+    crate::{
+        api::{error, extract_payload_with_max_size, response_body, IntoWarpReply},
+        orderbook::{AddOrderError, Orderbook},
+    },
+    model::{
+        order::{OrderCreation, OrderUid},
+        quote::QuoteId,
+    },
+    serde_json::{json, Value},
+    std::{convert::Infallible, sync::Arc},
+    warp::{
+        hyper::StatusCode,
+        reply::{json, with_status, WithStatus},
+        Filter,
+        Rejection,
+        Reply,
+    },
+};
+
+fn create_orders_batch_request(
+    max_orders: usize,
+) -> impl Filter<Extract = (Vec<OrderCreation>,), Error = Rejection> + Clone {
+    let max_size = super::MAX_JSON_BODY_PAYLOAD * u64::try_from(max_orders).unwrap_or(u64::MAX);
+    warp::path!("v1" / "orders" / "batch")
+        .and(warp::post())
+        .and(extract_payload_with_max_size(max_size))
+}
+
+/// The result of a single order of the batch. Failures are described by the
+/// same error the order would have been rejected with by `/api/v1/orders`.
+async fn order_result(result: Result<(OrderUid, Option<QuoteId>), AddOrderError>) -> Value {
+    match result {
+        Ok((uid, _)) => json!({
+            "status": StatusCode::CREATED.as_u16(),
+            "uid": uid,
+        }),
+        Err(err) => {
+            let response = err.into_warp_reply().into_response();
+            let status = response.status();
+            let body = response_body(response).await;
+            json!({
+                "status": status.as_u16(),
+                "error": serde_json::from_slice::<Value>(&body).unwrap_or_default(),
+            })
+        }
+    }
+}
+
+pub fn post_orders_batch(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (WithStatus<warp::reply::Json>,), Error = Rejection> + Clone {
+    let max_orders = orderbook.max_orders_per_batch();
+    create_orders_batch_request(max_orders.map_or(0, |max| max.get())).and_then(
+        move |orders: Vec<OrderCreation>| {
+            let orderbook = orderbook.clone();
+            async move {
+                let Some(max_orders) = max_orders else {
+                    return Result::<_, Infallible>::Ok(with_status(
+                        error("BatchesNotSupported", "order batches are not enabled"),
+                        StatusCode::BAD_REQUEST,
+                    ));
+                };
+                if orders.is_empty() || orders.len() > max_orders.get() {
+                    return Ok(with_status(
+                        error(
+                            "InvalidBatchSize",
+                            format!("batches must contain between 1 and {max_orders} orders"),
+                        ),
+                        StatusCode::BAD_REQUEST,
+                    ));
+                }
+
+                let results = orderbook.add_orders(orders).await;
+                let mut body = Vec::with_capacity(results.len());
+                for result in results {
+                    match &result {
+                        Ok((order_uid, quote_id)) => {
+                            tracing::debug!(%order_uid, ?quote_id, "order created in batch")
+                        }
+                        Err(err) => tracing::debug!(?err, "error creating order in batch"),
+                    }
+                    body.push(order_result(result).await);
+                }
+                Ok(with_status(json(&body), StatusCode::OK))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, warp::test::request};
+
+    #[tokio::test]
+    async fn create_orders_batch_request_ok() {
+        let filter = create_orders_batch_request(10);
+        let orders = vec![OrderCreation::default(), OrderCreation::default()];
+        let result = request()
+            .path("/v1/orders/batch")
+            .method("POST")
+            .header("content-type", "application/json")
+            .json(&orders)
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(result, orders);
+    }
+
+    #[tokio::test]
+    async fn order_results() {
+        let uid = OrderUid([1; 56]);
+        assert_eq!(
+            order_result(Ok((uid, Some(42)))).await,
+            json!({ "status": 201, "uid": uid }),
+        );
+        assert_eq!(
+            order_result(Err(AddOrderError::DuplicatedOrder)).await,
+            json!({
+                "status": 400,
+                "error": {
+                    "errorType": "DuplicatedOrder",
+                    "description": "order already exists",
+                },
+            }),
+        );
+    }
+}
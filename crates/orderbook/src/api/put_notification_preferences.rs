@@ -0,0 +1,116 @@
+use {
+    crate::{
+        api::{convert_json_response, extract_payload, IntoWarpReply},
+        notifications::{Notifications, RegistrationError},
+    },
+    model::notification::SignedNotificationPreferences,
+    primitive_types::H160,
+    std::{convert::Infallible, sync::Arc},
+    warp::{hyper::StatusCode, reply::with_status, Filter, Rejection},
+};
+
+fn request(
+) -> impl Filter<Extract = (H160, SignedNotificationPreferences), Error = Rejection> + Clone {
+    warp::path!("v1" / "account" / H160 / "notifications")
+        .and(warp::put())
+        .and(extract_payload())
+}
+
+impl IntoWarpReply for RegistrationError {
+    fn into_warp_reply(self) -> super::ApiReply {
+        match self {
+            Self::InvalidSignature => with_status(
+                super::error("InvalidSignature", "Malformed signature"),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::WrongOwner => with_status(
+                super::error(
+                    "WrongOwner",
+                    "Signature recovery's owner doesn't match the account",
+                ),
+                StatusCode::UNAUTHORIZED,
+            ),
+            Self::InvalidIssuedAt => with_status(
+                super::error(
+                    "InvalidIssuedAt",
+                    "Preferences must be registered within 10 minutes of being issued",
+                ),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::Outdated => with_status(
+                super::error(
+                    "OutdatedPreferences",
+                    "Preferences issued at the same time or later are already registered",
+                ),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::TooManyUpdates => with_status(
+                super::error(
+                    "TooManyUpdates",
+                    "Preferences can only be changed once per minute",
+                ),
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            Self::InvalidWebhook => with_status(
+                super::error("InvalidWebhook", "Webhook must be an HTTPS URL"),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::InvalidEmail => with_status(
+                super::error("InvalidEmail", "Malformed email address"),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::EmailUnsupported => with_status(
+                super::error("EmailUnsupported", "Email notifications are not supported"),
+                StatusCode::BAD_REQUEST,
+            ),
+            Self::Other(err) => {
+                tracing::error!(?err, "put_notification_preferences");
+                crate::api::internal_error_reply()
+            }
+        }
+    }
+}
+
+pub fn filter(
+    notifications: Arc<Notifications>,
+) -> impl Filter<Extract = (super::ApiReply,), Error = Rejection> + Clone {
+    request().and_then(move |owner, preferences| {
+        let notifications = notifications.clone();
+        async move {
+            let result = notifications.register(owner, preferences).await;
+            Result::<_, Infallible>::Ok(convert_json_response(result.map(|_| "Registered")))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json, warp::test::request as test_request};
+
+    #[tokio::test]
+    async fn put_notification_preferences_request_ok() {
+        let filter = request();
+        let (owner, preferences) = test_request()
+            .path("/v1/account/0x0101010101010101010101010101010101010101/notifications")
+            .method("PUT")
+            .header("content-type", "application/json")
+            .json(&json!({
+                "webhook": "https://example.com/hook",
+                "fills": true,
+                "expiries": false,
+                "issuedAt": 1700000000,
+                "signature": format!("0x{}", "01".repeat(65)),
+                "signingScheme": "eip712",
+            }))
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(owner, H160([1; 20]));
+        assert_eq!(
+            preferences.data.webhook.as_deref(),
+            Some("https://example.com/hook")
+        );
+        assert_eq!(preferences.data.email, None);
+        assert!(preferences.data.fills);
+    }
+}
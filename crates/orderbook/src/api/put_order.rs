@@ -0,0 +1,114 @@
+use {
+    crate::{
+        api::{extract_payload, rate_limit::with_retry_after, ApiReply, IntoWarpReply},
+        orderbook::{AddOrderError, Orderbook},
+    },
+    model::{
+        order::{OrderCreation, OrderUid},
+        quote::QuoteId,
+    },
+    serde_json::json,
+    std::{convert::Infallible, sync::Arc},
+    warp::{
+        hyper::StatusCode,
+        reply::{self, with_status, Response},
+        Filter,
+        Rejection,
+    },
+};
+
+fn replace_order_request(
+) -> impl Filter<Extract = (OrderUid, OrderCreation), Error = Rejection> + Clone {
+    warp::path!("v1" / "orders" / OrderUid)
+        .and(warp::put())
+        .and(extract_payload())
+}
+
+fn replace_order_response(
+    old_order_uid: OrderUid,
+    result: Result<(OrderUid, Option<QuoteId>), AddOrderError>,
+) -> ApiReply {
+    match result {
+        Ok((uid, _)) => with_status(
+            reply::json(&json!({
+                "uid": uid,
+                "replacedUid": old_order_uid,
+            })),
+            StatusCode::CREATED,
+        ),
+        Err(err) => err.into_warp_reply(),
+    }
+}
+
+pub fn put_order(
+    orderbook: Arc<Orderbook>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    replace_order_request().and_then(move |old_order_uid: OrderUid, order: OrderCreation| {
+        let orderbook = orderbook.clone();
+        async move {
+            let result = orderbook
+                .replace_order_by_uid(&old_order_uid, order.clone())
+                .await;
+            match &result {
+                Ok((order_uid, quote_id)) => {
+                    tracing::debug!(%order_uid, %old_order_uid, ?quote_id, "order replaced")
+                }
+                Err(err) => {
+                    tracing::debug!(?order, %old_order_uid, ?err, "error replacing order")
+                }
+            }
+
+            let retry_after = result.as_ref().err().and_then(AddOrderError::retry_after);
+            Result::<_, Infallible>::Ok(with_retry_after(
+                replace_order_response(old_order_uid, result),
+                retry_after,
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::api::response_body,
+        warp::{test::request, Reply},
+    };
+
+    #[tokio::test]
+    async fn replace_order_request_ok() {
+        let filter = replace_order_request();
+        let uid = OrderUid([1; 56]);
+        let order = OrderCreation::default();
+        let result = request()
+            .path(&format!("/v1/orders/{uid}"))
+            .method("PUT")
+            .header("content-type", "application/json")
+            .json(&order)
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(result, (uid, order));
+    }
+
+    #[tokio::test]
+    async fn replace_order_response_created() {
+        let old = OrderUid([1; 56]);
+        let new = OrderUid([2; 56]);
+        let response = replace_order_response(old, Ok((new, None))).into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response_body(response).await;
+        let body: serde_json::Value = serde_json::from_slice(body.as_slice()).unwrap();
+        assert_eq!(body, json!({ "uid": new, "replacedUid": old }));
+    }
+
+    #[tokio::test]
+    async fn replace_order_response_mismatch() {
+        let response = replace_order_response(
+            OrderUid::default(),
+            Err(AddOrderError::ReplacedOrderMismatch),
+        )
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,171 @@
+use {
+    crate::api::{rich_error, ApiReply},
+    rate_limit::Quota,
+    serde_json::json,
+    std::{
+        net::{IpAddr, SocketAddr},
+        sync::Arc,
+        time::Duration,
+    },
+    warp::{
+        http::{header::RETRY_AFTER, Method},
+        hyper::StatusCode,
+        reply::{with_header, with_status, Response},
+        Filter,
+        Rejection,
+        Reply,
+    },
+};
+
+/// Limits how many requests every client IP address can send.
+pub struct IpRateLimit {
+    pub quota: Quota<IpAddr>,
+    /// How many proxies in front of the orderbook append the address they
+    /// received the request from to the `X-Forwarded-For` header. Clients get
+    /// identified by the address the outermost of them saw. Zero identifies
+    /// clients by the address of the connection.
+    pub trusted_proxies: usize,
+}
+
+fn client_ip(
+    trusted_proxies: usize,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .map(
+            move |remote: Option<SocketAddr>, forwarded_for: Option<String>| {
+                // Clients can put anything into the header, so only the
+                // entries appended by trusted proxies can be relied on.
+                let forwarded = trusted_proxies
+                    .checked_sub(1)
+                    .zip(forwarded_for)
+                    .and_then(|(hops, header)| header.rsplit(',').nth(hops)?.trim().parse().ok());
+                forwarded.or(remote.map(|remote| remote.ip()))
+            },
+        )
+}
+
+/// Answers requests placing, replacing or cancelling orders of clients that
+/// exhausted their quota with 429 and rejects all other requests, so that they
+/// get handled by the actual routes. Every such request uses up one request
+/// of its client's quota. Other endpoints like quotes don't count against the
+/// quota, so requesting a quote for every order doesn't halve it.
+pub fn ip_rate_limit(
+    limit: Arc<IpRateLimit>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path("v1")
+        .and(warp::path("orders"))
+        .and(warp::method())
+        .and(client_ip(limit.trusted_proxies))
+        .and_then(move |method: Method, ip: Option<IpAddr>| {
+            let limit = limit.clone();
+            async move {
+                match ip.filter(|_| !method.is_safe()) {
+                    Some(ip) => match limit.quota.try_acquire(ip) {
+                        Ok(()) => Err(warp::reject::not_found()),
+                        Err(retry_after) => Ok(rate_limited_response(retry_after)),
+                    },
+                    None => Err(warp::reject::not_found()),
+                }
+            }
+        })
+}
+
+/// 429 reply telling the client when to retry. Use [`with_retry_after`] to
+/// also set the `Retry-After` header.
+pub fn rate_limited_reply(retry_after: Duration) -> ApiReply {
+    with_status(
+        rich_error(
+            "TooManyRequests",
+            "Too many requests, retry later.",
+            json!({ "retryAfter": retry_after_secs(retry_after) }),
+        ),
+        StatusCode::TOO_MANY_REQUESTS,
+    )
+}
+
+/// Sets the `Retry-After` header of the reply if the request was rate
+/// limited.
+pub fn with_retry_after(reply: ApiReply, retry_after: Option<Duration>) -> Response {
+    match retry_after {
+        Some(retry_after) => {
+            with_header(reply, RETRY_AFTER, retry_after_secs(retry_after)).into_response()
+        }
+        None => reply.into_response(),
+    }
+}
+
+pub fn rate_limited_response(retry_after: Duration) -> Response {
+    with_retry_after(rate_limited_reply(retry_after), Some(retry_after))
+}
+
+/// `Retry-After` only supports whole seconds, so round up to not have clients
+/// retry too early.
+fn retry_after_secs(retry_after: Duration) -> u64 {
+    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::api::response_body, warp::test::request};
+
+    #[tokio::test]
+    async fn identifies_clients() {
+        let remote: SocketAddr = "1.1.1.1:80".parse().unwrap();
+        let ip = |trusted_proxies: usize, forwarded_for: Option<&str>| {
+            let mut request = request().remote_addr(remote);
+            if let Some(forwarded_for) = forwarded_for {
+                request = request.header("x-forwarded-for", forwarded_for);
+            }
+            async move {
+                request
+                    .filter(&client_ip(trusted_proxies))
+                    .await
+                    .unwrap()
+                    .unwrap()
+            }
+        };
+
+        let forwarded = Some("2.2.2.2, 3.3.3.3");
+        assert_eq!(ip(0, forwarded).await, remote.ip());
+        assert_eq!(ip(1, forwarded).await, "3.3.3.3".parse::<IpAddr>().unwrap());
+        assert_eq!(ip(2, forwarded).await, "2.2.2.2".parse::<IpAddr>().unwrap());
+        // Requests that didn't pass all proxies.
+        assert_eq!(ip(3, forwarded).await, remote.ip());
+        assert_eq!(ip(1, None).await, remote.ip());
+        assert_eq!(ip(1, Some("garbage")).await, remote.ip());
+    }
+
+    #[tokio::test]
+    async fn limits_order_mutations() {
+        let limit = Arc::new(IpRateLimit {
+            quota: Quota::try_new("test_api_ip".into(), "1rps,burst=1".parse().unwrap()).unwrap(),
+            trusted_proxies: 0,
+        });
+        let filter = ip_rate_limit(limit);
+        let remote: SocketAddr = "1.1.1.1:80".parse().unwrap();
+        let send = |method: &str, path: &str| {
+            request()
+                .method(method)
+                .path(path)
+                .remote_addr(remote)
+                .filter(&filter)
+        };
+
+        assert!(send("POST", "/v1/orders").await.is_err());
+        let response = send("DELETE", "/v1/orders/0x01").await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(send("GET", "/v1/orders/0x01").await.is_err());
+        assert!(send("POST", "/v1/quote").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_has_retry_after() {
+        let response = rate_limited_response(Duration::from_millis(1500));
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "2");
+        let body: serde_json::Value =
+            serde_json::from_slice(&response_body(response).await).unwrap();
+        assert_eq!(body["data"]["retryAfter"], 2);
+    }
+}
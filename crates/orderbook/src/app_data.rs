@@ -1,10 +1,15 @@
 use {
     crate::{
         database::{app_data::InsertError, Postgres},
-        ipfs_app_data::IpfsAppData,
+        ipfs_app_data::{AppDataPinning, IpfsAppData},
     },
     anyhow::{Context, Result},
     app_data::AppDataHash,
+    std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    },
+    tokio::{sync::Semaphore, task::JoinHandle},
 };
 
 /// CoW Protocol API app-data registry.
@@ -12,6 +17,7 @@ pub struct Registry {
     validator: app_data::Validator,
     database: Postgres,
     ipfs: Option<IpfsAppData>,
+    pinning: Option<Pinner>,
 }
 
 impl Registry {
@@ -25,9 +31,17 @@ impl Registry {
             validator,
             database,
             ipfs,
+            pinning: None,
         }
     }
 
+    /// Pins registered app data documents on IPFS, at most `max_concurrent`
+    /// at a time.
+    pub fn with_pinning(mut self, pinning: Arc<dyn AppDataPinning>, max_concurrent: usize) -> Self {
+        self.pinning = Some(Pinner::new(pinning, max_concurrent));
+        self
+    }
+
     /// Returns the size limit, in bytes, of an app-data document.
     pub fn size_limit(&self) -> usize {
         self.validator.size_limit()
@@ -55,16 +69,24 @@ impl Registry {
             });
         }
 
-        match self
+        let registered = match self
             .database
             .insert_full_app_data(&validated.hash, &validated.document)
             .await
         {
-            Ok(()) => Ok((Registered::New, validated.hash)),
-            Err(InsertError::Duplicate) => Ok((Registered::AlreadyExisted, validated.hash)),
-            Err(InsertError::Mismatch(existing)) => Err(RegisterError::DataMismatch { existing }),
-            Err(InsertError::Other(err)) => Err(RegisterError::Other(err)),
+            Ok(()) => Registered::New,
+            Err(InsertError::Duplicate) => Registered::AlreadyExisted,
+            Err(InsertError::Mismatch(existing)) => {
+                return Err(RegisterError::DataMismatch { existing })
+            }
+            Err(InsertError::Other(err)) => return Err(RegisterError::Other(err)),
+        };
+        // Documents that already existed get pinned too in case they were
+        // registered before pinning was enabled or pinning them failed.
+        if let Some(pinning) = &self.pinning {
+            pinning.pin(validated.hash, validated.document);
         }
+        Ok((registered, validated.hash))
     }
 
     /// Finds full app data for an order that only has the contract app data
@@ -94,6 +116,56 @@ impl Registry {
     }
 }
 
+/// Pins documents in the background so that uploads don't wait for IPFS.
+/// Failures only get logged because the document is registered either way.
+struct Pinner {
+    pinning: Arc<dyn AppDataPinning>,
+    /// Documents that got pinned or are being pinned. Uploading them again
+    /// doesn't pin them again.
+    pinned: Arc<Mutex<HashSet<AppDataHash>>>,
+    slots: Arc<Semaphore>,
+}
+
+impl Pinner {
+    fn new(pinning: Arc<dyn AppDataPinning>, max_concurrent: usize) -> Self {
+        Self {
+            pinning,
+            pinned: Default::default(),
+            slots: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Starts pinning the document unless it already got pinned. Documents
+    /// uploaded while too many others are being pinned are skipped; they get
+    /// pinned when they are uploaded again.
+    fn pin(&self, hash: AppDataHash, document: String) -> Option<JoinHandle<()>> {
+        let slot = {
+            let mut pinned = self.pinned.lock().unwrap();
+            if pinned.contains(&hash) {
+                return None;
+            }
+            let Ok(slot) = self.slots.clone().try_acquire_owned() else {
+                tracing::debug!(?hash, "too many pending pins, skipping app data");
+                return None;
+            };
+            pinned.insert(hash);
+            slot
+        };
+        let pinning = self.pinning.clone();
+        let pinned = self.pinned.clone();
+        Some(observe::metrics::spawn_in_current_scope(async move {
+            match pinning.pin(&hash, document).await {
+                Ok(()) => tracing::debug!(?hash, "pinned app data"),
+                Err(err) => {
+                    tracing::warn!(?hash, ?err, "failed to pin app data");
+                    pinned.lock().unwrap().remove(&hash);
+                }
+            }
+            drop(slot);
+        }))
+    }
+}
+
 #[derive(Debug)]
 pub enum Registered {
     /// The app data was newly added to the registry.
@@ -116,3 +188,87 @@ pub enum RegisterError {
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::time::Duration};
+
+    /// Pinning that takes a second and fails for the hashes in `failing`.
+    #[derive(Default)]
+    struct FakePinning {
+        pins: Mutex<Vec<AppDataHash>>,
+        failing: Mutex<HashSet<AppDataHash>>,
+    }
+
+    #[async_trait::async_trait]
+    impl AppDataPinning for FakePinning {
+        async fn pin(&self, app_data: &AppDataHash, _: String) -> Result<()> {
+            self.pins.lock().unwrap().push(*app_data);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            anyhow::ensure!(
+                !self.failing.lock().unwrap().contains(app_data),
+                "pinning failed"
+            );
+            Ok(())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pins_documents_once() {
+        let fake = Arc::new(FakePinning::default());
+        let pinner = Pinner::new(fake.clone(), 10);
+        let hash = AppDataHash([1; 32]);
+
+        let pin = pinner.pin(hash, "{}".to_string()).unwrap();
+        // Uploads while the document is being pinned don't pin it again.
+        assert!(pinner.pin(hash, "{}".to_string()).is_none());
+        pin.await.unwrap();
+        assert!(pinner.pin(hash, "{}".to_string()).is_none());
+        assert_eq!(*fake.pins.lock().unwrap(), vec![hash]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_failed_pins() {
+        let fake = Arc::new(FakePinning::default());
+        let pinner = Pinner::new(fake.clone(), 10);
+        let hash = AppDataHash([1; 32]);
+        fake.failing.lock().unwrap().insert(hash);
+
+        pinner.pin(hash, "{}".to_string()).unwrap().await.unwrap();
+        fake.failing.lock().unwrap().clear();
+        pinner.pin(hash, "{}".to_string()).unwrap().await.unwrap();
+        assert!(pinner.pin(hash, "{}".to_string()).is_none());
+        assert_eq!(*fake.pins.lock().unwrap(), vec![hash, hash]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bounds_concurrent_pins() {
+        let fake = Arc::new(FakePinning::default());
+        let pinner = Pinner::new(fake.clone(), 1);
+
+        let pin = pinner.pin(AppDataHash([1; 32]), "{}".to_string()).unwrap();
+        assert!(pinner.pin(AppDataHash([2; 32]), "{}".to_string()).is_none());
+        pin.await.unwrap();
+        assert!(pinner.pin(AppDataHash([2; 32]), "{}".to_string()).is_some());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_register_pins_documents_once() {
+        let db = Postgres::try_new("postgresql://").unwrap();
+        database::clear_DANGER(&db.pool).await.unwrap();
+        let fake = Arc::new(FakePinning::default());
+        let registry =
+            Registry::new(app_data::Validator::new(1000), db, None).with_pinning(fake.clone(), 10);
+        let document = br#"{"appCode":"test"}"#;
+
+        let (registered, hash) = registry.register(None, document).await.unwrap();
+        assert!(matches!(registered, Registered::New));
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let (registered, _) = registry.register(Some(hash), document).await.unwrap();
+        assert!(matches!(registered, Registered::AlreadyExisted));
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        assert_eq!(*fake.pins.lock().unwrap(), vec![hash]);
+    }
+}
@@ -1,5 +1,9 @@
 use {
+    anyhow::{ensure, Context},
+    clap::Parser,
+    model::order::OrderClass,
     primitive_types::H160,
+    rate_limit::QuotaConfig,
     reqwest::Url,
     shared::{
         arguments::{display_option, display_secret_option},
@@ -7,7 +11,7 @@ use {
         http_client,
         price_estimation::{self, NativePriceEstimators},
     },
-    std::{net::SocketAddr, num::NonZeroUsize, time::Duration},
+    std::{net::SocketAddr, num::NonZeroUsize, path::PathBuf, str::FromStr, time::Duration},
 };
 
 #[derive(clap::Parser)]
@@ -35,6 +39,12 @@ pub struct Arguments {
     #[clap(long, env, default_value = "0.0.0.0:8080")]
     pub bind_address: SocketAddr,
 
+    /// Port serving the metrics and health probes on the interface of
+    /// `--bind-address`. Defaults to the `metrics-port` of the observability
+    /// config, or 9586.
+    #[clap(long, env)]
+    pub metrics_port: Option<u16>,
+
     /// Url of the Postgres database. By default connects to locally running
     /// postgres.
     #[clap(long, env, default_value = "postgresql://")]
@@ -45,7 +55,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "1m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub min_order_validity_period: Duration,
 
@@ -56,7 +66,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "3h",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub max_order_validity_period: Duration,
 
@@ -66,7 +76,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "1y",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub max_limit_order_validity_period: Duration,
 
@@ -124,6 +134,21 @@ pub struct Arguments {
     #[clap(long, env)]
     pub ipfs_pinata_auth: Option<String>,
 
+    /// If set, app data documents uploaded to the API get stored and pinned
+    /// through the RPC API of this IPFS node (or pinning service), so that the
+    /// CID derived from their app data hash resolves publicly.
+    #[clap(long, env)]
+    pub ipfs_pinning_api: Option<Url>,
+
+    /// Bearer token authenticating requests to the IPFS pinning API.
+    #[clap(long, env)]
+    pub ipfs_pinning_auth: Option<String>,
+
+    /// How many app data documents get pinned at once. Documents uploaded
+    /// while this many are being pinned are not pinned until uploaded again.
+    #[clap(long, env, default_value = "10")]
+    pub ipfs_pinning_max_concurrent: usize,
+
     /// Override the address of the `HooksTrampoline` contract used for
     /// trampolining custom order interactions. If not specified, the default
     /// contract deployment for the current network will be used.
@@ -137,6 +162,222 @@ pub struct Arguments {
     /// The maximum gas amount a single order can use for getting settled.
     #[clap(long, env, default_value = "8000000")]
     pub max_gas_per_order: u64,
+
+    /// API keys of partners allowed to query the fee and surplus reports of
+    /// their app code. Supplied in the form of
+    /// "<appCode1>|<key1>,<appCode2>|<key2>".
+    #[clap(long, env, use_value_delimiter = true)]
+    pub partner_api_keys: Vec<PartnerApiKey>,
+
+    /// Private key used to sign quote commitments. If set, quotes get
+    /// returned with a signed commitment and orders including it are
+    /// validated against the committed quote.
+    #[clap(long, env, hide_env_values = true)]
+    pub quote_commitment_signing_key: Option<String>,
+
+    /// For how long signed quote commitments get honored. Commitments never
+    /// outlive the quote they commit to.
+    #[clap(
+        long,
+        env,
+        default_value = "30s",
+        value_parser = shared::arguments::parse_duration,
+    )]
+    pub quote_commitment_validity: Duration,
+
+    /// Addresses of previous quote commitment signing keys whose commitments
+    /// are still accepted. Allows rotating the signing key without
+    /// invalidating commitments issued right before the rotation.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub quote_commitment_previous_signers: Vec<H160>,
+
+    /// Order classes that get rejected unless they reference an existing,
+    /// unexpired quote requested by the order owner that matches the order
+    /// amounts.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub mandatory_quote_verification: Vec<OrderClass>,
+
+    /// How much the limit price of an order may deviate from the price of the
+    /// referenced quote in basis points when mandatory quote verification
+    /// applies.
+    #[clap(long, env, default_value = "500")]
+    pub mandatory_quote_verification_tolerance_bps: u32,
+
+    /// Enables submitting orders with an embargo. Embargoed orders are hidden
+    /// from the public order APIs until the autopilot cuts the next auction,
+    /// but at most for this duration. Orders requesting an embargo get
+    /// rejected if this is not set.
+    #[clap(long, env, value_parser = shared::arguments::parse_duration)]
+    pub order_embargo_max_duration: Option<Duration>,
+
+    /// Most orders that can be placed with a single request to
+    /// "/api/v1/orders/batch". Setting this to 0 disables batches.
+    #[clap(long, env, default_value = "100")]
+    pub max_orders_per_batch: usize,
+
+    /// Limits how many requests placing, replacing or cancelling orders every
+    /// client IP address can send, e.g. "2rps,burst=10". Not limited if not
+    /// set.
+    #[clap(long, env)]
+    pub api_rate_limit_per_ip: Option<QuotaConfig>,
+
+    /// Limits how many orders every owner can place, replace and cancel, e.g.
+    /// "1rps,burst=20". Not limited if not set.
+    #[clap(long, env)]
+    pub api_rate_limit_per_owner: Option<QuotaConfig>,
+
+    /// How many proxies in front of the orderbook append to the
+    /// "X-Forwarded-For" header. The IP rate limit identifies clients by the
+    /// address the outermost of them received the request from. With 0,
+    /// clients get identified by the address of the connection.
+    #[clap(long, env, default_value = "0")]
+    pub api_rate_limit_trusted_proxies: usize,
+
+    /// Serves a GraphQL API over orders, trades, quotes and solver
+    /// competitions at "/api/v1/graphql".
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub graphql_enabled: bool,
+
+    /// Maximum nesting depth of GraphQL queries.
+    #[clap(long, env, default_value = "10")]
+    pub graphql_max_depth: usize,
+
+    /// Maximum complexity of GraphQL queries. Every requested field adds 1
+    /// and lists of orders get multiplied by their requested limit.
+    #[clap(long, env, default_value = "2000")]
+    pub graphql_max_complexity: usize,
+
+    /// Lets accounts register notification preferences at
+    /// "/api/v1/account/{owner}/notifications" and sends them periodic
+    /// digests of their order activity.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub notifications_enabled: bool,
+
+    /// How often an account gets sent a digest at most.
+    #[clap(
+        long,
+        env,
+        default_value = "1h",
+        value_parser = shared::arguments::parse_duration,
+    )]
+    pub notification_digest_interval: Duration,
+
+    /// Maximum number of digests sent per minute across all accounts.
+    /// Accounts that have been waiting the longest get served first.
+    #[clap(long, env, default_value = "100")]
+    pub notification_max_digests_per_minute: usize,
+
+    /// Maximum number of fills and expiries each reported in a single digest.
+    #[clap(long, env, default_value = "100")]
+    pub notification_max_orders_per_digest: usize,
+
+    /// How long to wait for a webhook to accept a digest.
+    #[clap(
+        long,
+        env,
+        default_value = "10s",
+        value_parser = shared::arguments::parse_duration,
+    )]
+    pub notification_webhook_timeout: Duration,
+
+    /// Streams the lifecycle events of orders to clients subscribed at
+    /// "/api/v1/events" as server-sent events.
+    #[clap(long, env, action = clap::ArgAction::Set, default_value = "false")]
+    pub order_events_enabled: bool,
+
+    /// Address of the eth-flow contract. If specified, orders selling the
+    /// native token can be prepared at "/api/v1/ethflow_orders". Has to match
+    /// the contract the autopilot indexes.
+    #[clap(long, env)]
+    pub ethflow_contract: Option<H160>,
+
+    /// Chains to serve from this process instead of a single one. Supplied in
+    /// the form of "<prefix1>=<file1>,<prefix2>=<file2>". Each file contains
+    /// the arguments of the chain, one per line (e.g.
+    /// "--db-url=postgresql://..."), and its API gets served under
+    /// "/<prefix>/api/...". The metrics of each chain carry a "chain" label
+    /// with its prefix. Arguments of the process itself are then only used
+    /// for the bind address, logging and as defaults through environment
+    /// variables.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub chains: Vec<ChainConfig>,
+}
+
+/// A chain served by a multi-chain orderbook.
+#[derive(Clone, Debug)]
+pub struct ChainConfig {
+    pub prefix: String,
+    pub arguments: PathBuf,
+}
+
+impl ChainConfig {
+    /// Reads the arguments of the chain.
+    pub fn load(&self) -> anyhow::Result<Arguments> {
+        let content = std::fs::read_to_string(&self.arguments)
+            .with_context(|| format!("reading {}", self.arguments.display()))?;
+        let args = Arguments::try_parse_from(
+            std::iter::once("orderbook").chain(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#')),
+            ),
+        )
+        .with_context(|| format!("parsing arguments of chain {}", self.prefix))?;
+        ensure!(args.chains.is_empty(), "chains can't be nested");
+        Ok(args)
+    }
+}
+
+impl FromStr for ChainConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, arguments) = s
+            .split_once('=')
+            .context("chain must be in the form <prefix>=<arguments file>")?;
+        ensure!(
+            !prefix.is_empty() && !prefix.contains('/'),
+            "chain prefix must be a single path segment"
+        );
+        ensure!(prefix != "api", "chain prefix must not be \"api\"");
+        Ok(Self {
+            prefix: prefix.to_string(),
+            arguments: arguments.into(),
+        })
+    }
+}
+
+/// API key granting access to the reports of a single app code.
+#[derive(Clone)]
+pub struct PartnerApiKey {
+    pub app_code: String,
+    pub key: String,
+}
+
+impl std::fmt::Debug for PartnerApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PartnerApiKey")
+            .field("app_code", &self.app_code)
+            .field("key", &"SECRET")
+            .finish()
+    }
+}
+
+impl FromStr for PartnerApiKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (app_code, key) = s
+            .split_once('|')
+            .context("partner API key must be in the form <appCode>|<key>")?;
+        ensure!(!app_code.is_empty(), "app code must not be empty");
+        ensure!(!key.is_empty(), "API key must not be empty");
+        Ok(Self {
+            app_code: app_code.to_string(),
+            key: key.to_string(),
+        })
+    }
 }
 
 impl std::fmt::Display for Arguments {
@@ -149,6 +390,7 @@ impl std::fmt::Display for Arguments {
             price_estimation,
             tracing_node_url,
             bind_address,
+            metrics_port,
             min_order_validity_period,
             max_order_validity_period,
             max_limit_order_validity_period,
@@ -163,10 +405,35 @@ impl std::fmt::Display for Arguments {
             max_limit_orders_per_user,
             ipfs_gateway,
             ipfs_pinata_auth,
+            ipfs_pinning_api,
+            ipfs_pinning_auth,
+            ipfs_pinning_max_concurrent,
             hooks_contract_address,
             app_data_size_limit,
             db_url,
             max_gas_per_order,
+            partner_api_keys,
+            quote_commitment_signing_key,
+            quote_commitment_validity,
+            quote_commitment_previous_signers,
+            mandatory_quote_verification,
+            mandatory_quote_verification_tolerance_bps,
+            order_embargo_max_duration,
+            max_orders_per_batch,
+            api_rate_limit_per_ip,
+            api_rate_limit_per_owner,
+            api_rate_limit_trusted_proxies,
+            graphql_enabled,
+            graphql_max_depth,
+            graphql_max_complexity,
+            notifications_enabled,
+            notification_digest_interval,
+            notification_max_digests_per_minute,
+            notification_max_orders_per_digest,
+            notification_webhook_timeout,
+            order_events_enabled,
+            ethflow_contract,
+            chains,
         } = self;
 
         write!(f, "{}", shared)?;
@@ -176,6 +443,7 @@ impl std::fmt::Display for Arguments {
         write!(f, "{}", price_estimation)?;
         display_option(f, "tracing_node_url", tracing_node_url)?;
         writeln!(f, "bind_address: {}", bind_address)?;
+        display_option(f, "metrics_port", metrics_port)?;
         let _intentionally_ignored = db_url;
         writeln!(f, "db_url: SECRET")?;
         writeln!(
@@ -220,6 +488,9 @@ impl std::fmt::Display for Arguments {
         )?;
         writeln!(f, "ipfs_gateway: {:?}", ipfs_gateway)?;
         display_secret_option(f, "ipfs_pinata_auth", ipfs_pinata_auth.as_ref())?;
+        display_option(f, "ipfs_pinning_api", ipfs_pinning_api)?;
+        display_secret_option(f, "ipfs_pinning_auth", ipfs_pinning_auth.as_ref())?;
+        writeln!(f, "ipfs_pinning_max_concurrent: {}", ipfs_pinning_max_concurrent)?;
         display_option(
             f,
             "hooks_contract_address",
@@ -227,6 +498,76 @@ impl std::fmt::Display for Arguments {
         )?;
         writeln!(f, "app_data_size_limit: {}", app_data_size_limit)?;
         writeln!(f, "max_gas_per_order: {}", max_gas_per_order)?;
+        writeln!(f, "partner_api_keys: {:?}", partner_api_keys)?;
+        display_secret_option(
+            f,
+            "quote_commitment_signing_key",
+            quote_commitment_signing_key.as_ref(),
+        )?;
+        writeln!(
+            f,
+            "quote_commitment_validity: {:?}",
+            quote_commitment_validity
+        )?;
+        writeln!(
+            f,
+            "quote_commitment_previous_signers: {:?}",
+            quote_commitment_previous_signers
+        )?;
+        writeln!(
+            f,
+            "mandatory_quote_verification: {:?}",
+            mandatory_quote_verification
+        )?;
+        writeln!(
+            f,
+            "mandatory_quote_verification_tolerance_bps: {}",
+            mandatory_quote_verification_tolerance_bps
+        )?;
+        writeln!(
+            f,
+            "order_embargo_max_duration: {:?}",
+            order_embargo_max_duration
+        )?;
+        writeln!(f, "max_orders_per_batch: {}", max_orders_per_batch)?;
+        display_option(f, "api_rate_limit_per_ip", api_rate_limit_per_ip)?;
+        display_option(f, "api_rate_limit_per_owner", api_rate_limit_per_owner)?;
+        writeln!(
+            f,
+            "api_rate_limit_trusted_proxies: {}",
+            api_rate_limit_trusted_proxies
+        )?;
+        writeln!(f, "graphql_enabled: {}", graphql_enabled)?;
+        writeln!(f, "graphql_max_depth: {}", graphql_max_depth)?;
+        writeln!(f, "graphql_max_complexity: {}", graphql_max_complexity)?;
+        writeln!(f, "notifications_enabled: {}", notifications_enabled)?;
+        writeln!(
+            f,
+            "notification_digest_interval: {:?}",
+            notification_digest_interval
+        )?;
+        writeln!(
+            f,
+            "notification_max_digests_per_minute: {}",
+            notification_max_digests_per_minute
+        )?;
+        writeln!(
+            f,
+            "notification_max_orders_per_digest: {}",
+            notification_max_orders_per_digest
+        )?;
+        writeln!(
+            f,
+            "notification_webhook_timeout: {:?}",
+            notification_webhook_timeout
+        )?;
+        writeln!(f, "order_events_enabled: {}", order_events_enabled)?;
+        display_option(
+            f,
+            "ethflow_contract",
+            &ethflow_contract.map(|a| format!("{a:?}")),
+        )?;
+        writeln!(f, "chains: {:?}", chains)?;
 
         Ok(())
     }
@@ -0,0 +1,153 @@
+//! In-memory read model of the current auction.
+//!
+//! Answering `/api/v1/auction` by loading the auction from the database,
+//! deserializing it and serializing it again dominates the latency of the
+//! endpoint. Instead, a background task keeps the serialized response of the
+//! current auction in memory, so requests get answered without touching the
+//! database. The auction only gets loaded and serialized again after the
+//! autopilot replaced it, which is cheap to detect by its id.
+
+use {
+    crate::{database::Postgres, dto},
+    anyhow::Result,
+    std::{
+        sync::{Arc, RwLock},
+        time::{Duration, Instant},
+    },
+    warp::hyper::body::Bytes,
+};
+
+/// How often the read model checks for a new auction.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An auction together with its serialized API response.
+#[derive(Debug)]
+pub struct Snapshot {
+    pub id: dto::AuctionId,
+    pub json: Bytes,
+}
+
+impl Snapshot {
+    fn new(auction: &dto::AuctionWithId) -> Result<Self> {
+        Ok(Self {
+            id: auction.id,
+            json: serde_json::to_vec(auction)?.into(),
+        })
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "current_auction")]
+struct Metrics {
+    /// Seconds since the read model was last synchronized with the database.
+    staleness_seconds: prometheus::Gauge,
+
+    /// Number of auctions that got loaded into the read model.
+    updates: prometheus::IntCounter,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    snapshot: Option<Arc<Snapshot>>,
+    synchronized_at: Option<Instant>,
+}
+
+pub struct CurrentAuction {
+    database: Postgres,
+    state: RwLock<State>,
+}
+
+impl CurrentAuction {
+    pub fn new(database: Postgres) -> Self {
+        Self {
+            database,
+            state: Default::default(),
+        }
+    }
+
+    /// The current auction, if there is one. Synchronizes with the database
+    /// first if that didn't happen yet, e.g. right after startup.
+    pub async fn get(&self) -> Result<Option<Arc<Snapshot>>> {
+        if self.state.read().unwrap().synchronized_at.is_none() {
+            self.update().await?;
+        }
+        Ok(self.state.read().unwrap().snapshot.clone())
+    }
+
+    /// Periodically synchronizes the read model with the database.
+    pub async fn run_forever(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(UPDATE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.update().await {
+                tracing::warn!(?err, "failed to update current auction");
+            }
+            if let Some(synchronized_at) = self.state.read().unwrap().synchronized_at {
+                Metrics::get()
+                    .staleness_seconds
+                    .set(synchronized_at.elapsed().as_secs_f64());
+            }
+        }
+    }
+
+    /// Loads the most recent auction if it differs from the one in memory.
+    async fn update(&self) -> Result<()> {
+        let id = self.database.most_recent_auction_id().await?;
+        let current = self.state.read().unwrap().snapshot.as_ref().map(|s| s.id);
+        let snapshot = if id != current {
+            // The auction might have been replaced again in the meantime, so
+            // the loaded auction can be even newer.
+            let auction = self.database.most_recent_auction().await?;
+            let snapshot = auction.as_ref().map(Snapshot::new).transpose()?;
+            tracing::debug!(id = ?snapshot.as_ref().map(|s| s.id), "updated current auction");
+            Metrics::get().updates.inc();
+            Some(snapshot.map(Arc::new))
+        } else {
+            None
+        };
+
+        let mut state = self.state.write().unwrap();
+        if let Some(snapshot) = snapshot {
+            state.snapshot = snapshot;
+        }
+        state.synchronized_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, primitive_types::H160};
+
+    #[test]
+    fn snapshot_contains_api_response() {
+        let auction = dto::AuctionWithId {
+            id: 42,
+            auction: dto::Auction {
+                block: 1,
+                orders: vec![],
+                prices: [(H160([1; 20]), 2.into())].into(),
+                surplus_capturing_jit_order_owners: vec![],
+            },
+        };
+        let snapshot = Snapshot::new(&auction).unwrap();
+        assert_eq!(snapshot.id, 42);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&snapshot.json).unwrap(),
+            serde_json::json!({
+                "id": 42,
+                "block": 1,
+                "orders": [],
+                "prices": { "0x0101010101010101010101010101010101010101": "2" },
+                "surplusCapturingJitOrderOwners": [],
+            })
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use {
+    crate::database::Postgres,
+    anyhow::Result,
+    bigdecimal::BigDecimal,
+    chrono::NaiveDate,
+    database::{app_code_reports, byte_array::ByteArray},
+    primitive_types::H160,
+    serde::Serialize,
+};
+
+/// Fees paid and surplus received by the users of an app code over a range
+/// of days.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppCodeReport {
+    pub app_code: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub totals: ReportTotals,
+    pub tokens: Vec<TokenReport>,
+}
+
+/// Amounts of the report denominated in the native token (wei).
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportTotals {
+    pub trades: i64,
+    pub fee_in_wei: String,
+    pub surplus_in_wei: String,
+}
+
+/// Fees and surplus denominated in a single token.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenReport {
+    pub token: H160,
+    pub trades: i64,
+    pub fee: String,
+    pub fee_in_wei: String,
+    pub surplus: String,
+    pub surplus_in_wei: String,
+}
+
+/// Formats an amount as an integer string. Amounts converted to wei can have
+/// a fractional part which is not meaningful for reports.
+fn amount(value: BigDecimal) -> String {
+    value.with_scale(0).to_string()
+}
+
+impl Postgres {
+    pub async fn app_code_report(
+        &self,
+        app_code: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        after: Option<H160>,
+        limit: u64,
+    ) -> Result<AppCodeReport> {
+        let _timer = database::instrumentation::time_query("app_code_report");
+
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        let totals = app_code_reports::totals(&mut ex, app_code, from, to).await?;
+        let tokens = app_code_reports::token_totals(
+            &mut ex,
+            app_code,
+            from,
+            to,
+            after.map(|token| ByteArray(token.0)).as_ref(),
+            limit.try_into()?,
+        )
+        .await?;
+
+        Ok(AppCodeReport {
+            app_code: app_code.to_string(),
+            from,
+            to,
+            totals: ReportTotals {
+                trades: totals.trades,
+                fee_in_wei: amount(totals.fee_in_wei),
+                surplus_in_wei: amount(totals.surplus_in_wei),
+            },
+            tokens: tokens
+                .into_iter()
+                .map(|token| TokenReport {
+                    token: H160(token.token.0),
+                    trades: token.trades,
+                    fee: amount(token.fee),
+                    fee_in_wei: amount(token.fee_in_wei),
+                    surplus: amount(token.surplus),
+                    surplus_in_wei: amount(token.surplus_in_wei),
+                })
+                .collect(),
+        })
+    }
+}
@@ -10,12 +10,9 @@ impl super::Postgres {
         &self,
         contract_app_data: &AppDataHash,
     ) -> Result<Option<String>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["get_full_app_data"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("get_full_app_data");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let full_app_data =
             match database::app_data::fetch(&mut ex, &ByteArray(contract_app_data.0)).await? {
                 Some(inner) => inner,
@@ -30,12 +27,9 @@ impl super::Postgres {
         contract_app_data: &AppDataHash,
         full_app_data: &str,
     ) -> Result<(), InsertError> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["insert_full_app_data"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("insert_full_app_data");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         if let Some(existing) = database::app_data::insert(
             &mut ex,
             &ByteArray(contract_app_data.0),
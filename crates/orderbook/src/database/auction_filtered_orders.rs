@@ -0,0 +1,46 @@
+use {
+    crate::database::Postgres,
+    anyhow::{Context, Result},
+    database::byte_array::ByteArray,
+    model::{auction::AuctionId, order::OrderUid},
+    serde::Serialize,
+};
+
+/// A candidate order that got filtered out while building an auction.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredOrder {
+    pub uid: OrderUid,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl Postgres {
+    /// Returns the orders that got filtered out of the auction, optionally
+    /// only the given order.
+    pub async fn auction_filtered_orders(
+        &self,
+        auction_id: AuctionId,
+        order_uid: Option<&OrderUid>,
+    ) -> Result<Vec<FilteredOrder>> {
+        let _timer = database::instrumentation::time_query("auction_filtered_orders");
+
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        let orders = database::auction_filtered_orders::fetch(
+            &mut ex,
+            auction_id,
+            order_uid.map(|uid| ByteArray(uid.0)).as_ref(),
+        )
+        .await
+        .context("auction_filtered_orders::fetch")?;
+        Ok(orders
+            .into_iter()
+            .map(|order| FilteredOrder {
+                uid: OrderUid(order.order_uid.0),
+                reason: order.reason,
+                details: order.details,
+            })
+            .collect())
+    }
+}
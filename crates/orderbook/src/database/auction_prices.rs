@@ -8,12 +8,9 @@ use {
 
 impl Postgres {
     pub async fn fetch_latest_prices(&self) -> Result<HashMap<H160, BigDecimal>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["fetch_latest_prices"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("fetch_latest_prices");
 
-        let mut ex = self.pool.begin().await?;
+        let mut ex = database::instrumentation::begin(&self.pool).await?;
         Ok(database::auction_prices::fetch_latest_prices(&mut ex)
             .await?
             .into_iter()
@@ -2,12 +2,9 @@ use {crate::dto, anyhow::Result};
 
 impl super::Postgres {
     pub async fn most_recent_auction(&self) -> Result<Option<dto::AuctionWithId>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["load_most_recent_auction"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("load_most_recent_auction");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let (id, json) = match database::auction::load_most_recent(&mut ex).await? {
             Some(inner) => inner,
             None => return Ok(None),
@@ -16,4 +13,11 @@ impl super::Postgres {
         let auction = dto::AuctionWithId { id, auction };
         Ok(Some(auction))
     }
+
+    pub async fn most_recent_auction_id(&self) -> Result<Option<dto::AuctionId>> {
+        let _timer = database::instrumentation::time_query("load_most_recent_auction_id");
+
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(database::auction::load_most_recent_id(&mut ex).await?)
+    }
 }
@@ -18,19 +18,13 @@ impl super::Postgres {
         &self,
         keys_filter: &[Execution],
     ) -> anyhow::Result<HashMap<Execution, Vec<ExecutedProtocolFee>>> {
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
 
-        let timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["fee_policies"])
-            .start_timer();
+        let timer = database::instrumentation::time_query("fee_policies");
         let fee_policies = database::fee_policies::fetch_all(&mut ex, keys_filter).await?;
         timer.stop_and_record();
 
-        let timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["executed_protocol_fees"])
-            .start_timer();
+        let timer = database::instrumentation::time_query("executed_protocol_fees");
         let executed_protocol_fees =
             database::order_execution::executed_protocol_fees(&mut ex, keys_filter).await?;
         timer.stop_and_record();
@@ -50,10 +44,7 @@ impl super::Postgres {
             })
             .collect::<Vec<_>>();
 
-        let timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["order_quotes"])
-            .start_timer();
+        let timer = database::instrumentation::time_query("order_quotes");
         let quotes = database::orders::read_quotes(&mut ex, quote_order_uids.as_slice())
             .await?
             .into_iter()
@@ -0,0 +1,24 @@
+use {
+    anyhow::{Context, Result},
+    model::fee_policy::VersionedFeePolicyConfiguration,
+};
+
+impl super::Postgres {
+    /// Returns the fee policy configuration the autopilot currently applies.
+    pub async fn active_fee_policy_configuration(
+        &self,
+    ) -> Result<Option<VersionedFeePolicyConfiguration>> {
+        let _timer = database::instrumentation::time_query("active_fee_policy_configuration");
+
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        let Some(row) = database::fee_policy_configurations::latest(&mut ex).await? else {
+            return Ok(None);
+        };
+        Ok(Some(VersionedFeePolicyConfiguration {
+            version: row.version.try_into().context("negative version")?,
+            active_since: row.created_at,
+            configuration: serde_json::from_value(row.configuration)
+                .context("invalid fee policy configuration")?,
+        }))
+    }
+}
@@ -1,7 +1,12 @@
+pub mod app_code_reports;
 pub mod app_data;
+pub mod auction_filtered_orders;
 pub mod auction_prices;
 pub mod auctions;
 mod fee_policies;
+pub mod fee_policy_configurations;
+pub mod notifications;
+pub mod onchain_orders;
 pub mod orders;
 pub mod quotes;
 pub mod solver_competition;
@@ -11,9 +16,9 @@ pub mod trades;
 use {
     crate::database::orders::InsertionError,
     anyhow::Result,
-    database::byte_array::ByteArray,
+    database::{byte_array::ByteArray, replica::ReadReplica},
     model::order::Order,
-    sqlx::{PgConnection, PgPool},
+    sqlx::{pool::PoolConnection, PgConnection, PgPool},
 };
 
 // TODO: There is remaining optimization potential by implementing sqlx encoding
@@ -24,6 +29,7 @@ use {
 #[derive(Clone)]
 pub struct Postgres {
     pub pool: PgPool,
+    replica: Option<ReadReplica>,
 }
 
 // The implementation is split up into several modules which contain more public
@@ -33,9 +39,28 @@ impl Postgres {
     pub fn try_new(uri: &str) -> Result<Self> {
         Ok(Self {
             pool: PgPool::connect_lazy(uri)?,
+            replica: None,
         })
     }
 
+    /// Routes read-only queries that tolerate slightly outdated data to the
+    /// replica.
+    pub fn with_replica(self, replica: ReadReplica) -> Self {
+        Self {
+            replica: Some(replica),
+            ..self
+        }
+    }
+
+    /// Acquires a connection for read-only queries that tolerate slightly
+    /// outdated data. See [`ReadReplica`].
+    pub async fn acquire_read(&self) -> sqlx::Result<PoolConnection<sqlx::Postgres>> {
+        match &self.replica {
+            Some(replica) => replica.acquire(&self.pool).await,
+            None => database::instrumentation::acquire(&self.pool).await,
+        }
+    }
+
     async fn insert_order_app_data(
         order: &Order,
         ex: &mut PgConnection,
@@ -54,16 +79,3 @@ impl Postgres {
         Ok(())
     }
 }
-
-#[derive(prometheus_metric_storage::MetricStorage)]
-struct Metrics {
-    /// Timing of db queries.
-    #[metric(name = "orderbook_database_queries", labels("type"))]
-    database_queries: prometheus::HistogramVec,
-}
-
-impl Metrics {
-    fn get() -> &'static Self {
-        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
-    }
-}
@@ -0,0 +1,80 @@
+use {
+    crate::database::Postgres,
+    anyhow::Result,
+    chrono::{DateTime, Utc},
+    database::{
+        byte_array::ByteArray,
+        notification_preferences::{self, Expiry, Fill, Preferences},
+    },
+    primitive_types::H160,
+};
+
+impl Postgres {
+    pub async fn notification_preferences(&self, owner: &H160) -> Result<Option<Preferences>> {
+        let _timer = database::instrumentation::time_query("notification_preferences");
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(notification_preferences::fetch(&mut ex, &ByteArray(owner.0)).await?)
+    }
+
+    pub async fn upsert_notification_preferences(&self, preferences: &Preferences) -> Result<bool> {
+        let _timer = database::instrumentation::time_query("upsert_notification_preferences");
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(notification_preferences::upsert(&mut ex, preferences).await?)
+    }
+
+    pub async fn claim_due_notification_preferences(
+        &self,
+        digested_before: DateTime<Utc>,
+        now: DateTime<Utc>,
+        claimed_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Preferences>> {
+        let _timer = database::instrumentation::time_query("claim_due_notification_preferences");
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(notification_preferences::claim_due(
+            &mut ex,
+            digested_before,
+            now,
+            claimed_until,
+            limit.try_into()?,
+        )
+        .await?)
+    }
+
+    /// Fetches the fills and expiries of the owner's orders in `(from, to]`.
+    pub async fn order_activity(
+        &self,
+        owner: &H160,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<(Vec<Fill>, Vec<Expiry>)> {
+        let _timer = database::instrumentation::time_query("order_activity");
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        let owner = ByteArray(owner.0);
+        let fills = notification_preferences::fills(&mut ex, &owner, from, to).await?;
+        let expiries = notification_preferences::expiries(&mut ex, &owner, from, to).await?;
+        Ok((fills, expiries))
+    }
+
+    pub async fn mark_notifications_digested(
+        &self,
+        owner: &H160,
+        until: DateTime<Utc>,
+    ) -> Result<()> {
+        let _timer = database::instrumentation::time_query("mark_notifications_digested");
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(notification_preferences::mark_digested(&mut ex, &ByteArray(owner.0), until).await?)
+    }
+
+    pub async fn remove_notification_webhook(&self, owner: &H160, webhook: &str) -> Result<()> {
+        let _timer = database::instrumentation::time_query("remove_notification_webhook");
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(notification_preferences::remove_webhook(&mut ex, &ByteArray(owner.0), webhook).await?)
+    }
+
+    pub async fn remove_notification_email(&self, owner: &H160, email: &str) -> Result<()> {
+        let _timer = database::instrumentation::time_query("remove_notification_email");
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(notification_preferences::remove_email(&mut ex, &ByteArray(owner.0), email).await?)
+    }
+}
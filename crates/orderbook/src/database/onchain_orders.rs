@@ -0,0 +1,17 @@
+use {
+    anyhow::Result,
+    database::{byte_array::ByteArray, onchain_broadcasted_orders::OnchainOrderPlacementRow},
+    model::order::OrderUid,
+};
+
+impl super::Postgres {
+    pub async fn onchain_order_placement(
+        &self,
+        uid: &OrderUid,
+    ) -> Result<Option<OnchainOrderPlacementRow>> {
+        let _timer = database::instrumentation::time_query("onchain_order_placement");
+
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        Ok(database::onchain_broadcasted_orders::read_order(&mut ex, &ByteArray(uid.0)).await?)
+    }
+}
@@ -16,6 +16,7 @@ use {
         order::{
             EthflowData,
             Interactions,
+            InvalidationReason,
             OnchainOrderData,
             Order,
             OrderClass,
@@ -58,6 +59,13 @@ use {
 pub trait OrderStoring: Send + Sync {
     async fn insert_order(&self, order: &Order, quote: Option<Quote>)
         -> Result<(), InsertionError>;
+    /// Inserts the orders in a single transaction. Orders that can't be
+    /// inserted are skipped without affecting the others. The outer error
+    /// means that none of the orders got inserted.
+    async fn insert_orders(
+        &self,
+        orders: &[(Order, Option<Quote>)],
+    ) -> Result<Vec<Result<(), InsertionError>>, InsertionError>;
     async fn cancel_orders(&self, order_uids: Vec<OrderUid>, now: DateTime<Utc>) -> Result<()>;
     async fn cancel_order(&self, order_uid: &OrderUid, now: DateTime<Utc>) -> Result<()>;
     async fn replace_order(
@@ -67,13 +75,16 @@ pub trait OrderStoring: Send + Sync {
         new_quote: Option<Quote>,
     ) -> Result<(), InsertionError>;
     async fn orders_for_tx(&self, tx_hash: &H256) -> Result<Vec<Order>>;
+    /// Looks up an order by its uid. Orders under an active embargo are not
+    /// returned.
     async fn single_order(&self, uid: &OrderUid) -> Result<Option<Order>>;
     /// All orders of a single user ordered by creation date descending (newest
-    /// orders first).
+    /// orders first), starting after the order `after`. Orders under an active
+    /// embargo are skipped.
     async fn user_orders(
         &self,
         owner: &H160,
-        offset: u64,
+        after: Option<&OrderUid>,
         limit: Option<u64>,
     ) -> Result<Vec<Order>>;
     async fn latest_order_event(&self, order_uid: &OrderUid) -> Result<Option<OrderEvent>>;
@@ -149,6 +160,7 @@ async fn cancel_order(
 
 async fn insert_order(order: &Order, ex: &mut PgConnection) -> Result<(), InsertionError> {
     let order_uid = ByteArray(order.metadata.uid.0);
+    let embargo_until = order.metadata.embargo_until;
     insert_order_event(
         ex,
         &OrderEvent {
@@ -211,6 +223,10 @@ async fn insert_order(order: &Order, ex: &mut PgConnection) -> Result<(), Insert
         cancellation_timestamp: None,
     };
 
+    if let Some(until) = embargo_until {
+        database::order_embargoes::insert(ex, &order_uid, until).await?;
+    }
+
     database::orders::insert_order(ex, &order)
         .await
         .map_err(|err| {
@@ -260,13 +276,10 @@ impl OrderStoring for Postgres {
         order: &Order,
         quote: Option<Quote>,
     ) -> Result<(), InsertionError> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["insert_order"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("insert_order");
 
         let order = order.clone();
-        let mut connection = self.pool.acquire().await?;
+        let mut connection = database::instrumentation::acquire(&self.pool).await?;
         let mut ex = connection.begin().await?;
 
         insert_order(&order, &mut ex).await?;
@@ -279,13 +292,43 @@ impl OrderStoring for Postgres {
         Ok(())
     }
 
+    async fn insert_orders(
+        &self,
+        orders: &[(Order, Option<Quote>)],
+    ) -> Result<Vec<Result<(), InsertionError>>, InsertionError> {
+        let _timer = database::instrumentation::time_query("insert_orders");
+
+        let mut connection = database::instrumentation::acquire(&self.pool).await?;
+        let mut ex = connection.begin().await?;
+
+        let mut results = Vec::with_capacity(orders.len());
+        for (order, quote) in orders {
+            // Every order gets its own savepoint so that a failing order only
+            // rolls back its own changes.
+            let mut savepoint = ex.begin().await?;
+            let result = async {
+                insert_order(order, &mut savepoint).await?;
+                if let Some(quote) = quote {
+                    insert_quote(&order.metadata.uid, quote, &mut savepoint).await?;
+                }
+                Self::insert_order_app_data(order, &mut savepoint).await
+            }
+            .await;
+            match result {
+                Ok(()) => savepoint.commit().await?,
+                Err(_) => savepoint.rollback().await?,
+            }
+            results.push(result);
+        }
+
+        ex.commit().await?;
+        Ok(results)
+    }
+
     async fn cancel_orders(&self, order_uids: Vec<OrderUid>, now: DateTime<Utc>) -> Result<()> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["cancel_orders"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("cancel_orders");
 
-        let mut connection = self.pool.begin().await?;
+        let mut connection = database::instrumentation::begin(&self.pool).await?;
         for order_uid in order_uids {
             cancel_order(&mut connection, &order_uid, now).await?;
         }
@@ -296,12 +339,9 @@ impl OrderStoring for Postgres {
     }
 
     async fn cancel_order(&self, order_uid: &OrderUid, now: DateTime<Utc>) -> Result<()> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["cancel_order"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("cancel_order");
 
-        let mut ex = self.pool.begin().await?;
+        let mut ex = database::instrumentation::begin(&self.pool).await?;
         cancel_order(&mut ex, order_uid, now).await?;
         ex.commit().await.context("commit cancel single order")
     }
@@ -312,14 +352,11 @@ impl OrderStoring for Postgres {
         new_order: &model::order::Order,
         new_quote: Option<Quote>,
     ) -> anyhow::Result<(), super::orders::InsertionError> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["replace_order"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("replace_order");
 
         let old_order = *old_order;
         let new_order = new_order.clone();
-        let mut connection = self.pool.acquire().await?;
+        let mut connection = database::instrumentation::acquire(&self.pool).await?;
         connection
             .transaction(move |ex| {
                 async move {
@@ -343,13 +380,11 @@ impl OrderStoring for Postgres {
     }
 
     async fn single_order(&self, uid: &OrderUid) -> Result<Option<Order>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["single_order"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("single_order");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let order = match database::orders::single_full_order(&mut ex, &ByteArray(uid.0)).await? {
+            Some(order) if order.embargoed => None,
             Some(order) => Some(order),
             None => {
                 // try to find the order in the JIT orders table
@@ -360,12 +395,9 @@ impl OrderStoring for Postgres {
     }
 
     async fn single_order_with_quote(&self, uid: &OrderUid) -> Result<Option<OrderWithQuote>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["single_order_with_quote"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("single_order_with_quote");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let order = orders::single_full_order_with_quote(&mut ex, &ByteArray(uid.0)).await?;
         order
             .map(|order_with_quote| {
@@ -424,19 +456,16 @@ impl OrderStoring for Postgres {
     async fn user_orders(
         &self,
         owner: &H160,
-        offset: u64,
+        after: Option<&OrderUid>,
         limit: Option<u64>,
     ) -> Result<Vec<Order>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["user_orders"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("user_orders");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = self.acquire_read().await?;
         database::order_history::user_orders(
             &mut ex,
             &ByteArray(owner.0),
-            i64::try_from(offset).unwrap_or(i64::MAX),
+            after.map(|uid| ByteArray(uid.0)).as_ref(),
             limit.map(|l| i64::try_from(l).unwrap_or(i64::MAX)),
         )
         .map(|result| match result {
@@ -448,11 +477,10 @@ impl OrderStoring for Postgres {
     }
 
     async fn latest_order_event(&self, order_uid: &OrderUid) -> Result<Option<OrderEvent>> {
-        let mut ex = self.pool.begin().await.context("could not init tx")?;
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["latest_order_event"])
-            .start_timer();
+        let mut ex = database::instrumentation::begin(&self.pool)
+            .await
+            .context("could not init tx")?;
+        let _timer = database::instrumentation::time_query("latest_order_event");
 
         database::order_events::get_latest(&mut ex, &ByteArray(order_uid.0))
             .await
@@ -463,12 +491,9 @@ impl OrderStoring for Postgres {
 impl Postgres {
     /// Retrieve all user posted orders for a given transaction.
     pub async fn user_order_for_tx(&self, tx_hash: &H256) -> Result<Vec<Order>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["user_order_for_tx"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("user_order_for_tx");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         database::orders::full_orders_in_tx(&mut ex, &ByteArray(tx_hash.0))
             .map(|result| match result {
                 Ok(order) => full_order_into_model_order(order),
@@ -478,14 +503,28 @@ impl Postgres {
             .await
     }
 
+    /// Retrieves all orders with the given uids at once. Embargoed orders and
+    /// unknown uids are skipped.
+    pub async fn orders_by_uids(&self, uids: &[OrderUid]) -> Result<Vec<Order>> {
+        let _timer = database::instrumentation::time_query("orders_by_uids");
+
+        let uids: Vec<_> = uids.iter().map(|uid| ByteArray(uid.0)).collect();
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        database::orders::many_full_orders(&mut ex, &uids)
+            .try_filter(|order| futures::future::ready(!order.embargoed))
+            .map(|result| match result {
+                Ok(order) => full_order_into_model_order(order),
+                Err(err) => Err(anyhow::Error::from(err)),
+            })
+            .try_collect()
+            .await
+    }
+
     /// Retrieve all JIT orders for a given transaction.
     pub async fn jit_orders_for_tx(&self, tx_hash: &H256) -> Result<Vec<Order>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["jit_orders_for_tx"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("jit_orders_for_tx");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         database::jit_orders::get_by_tx(&mut ex, &ByteArray(tx_hash.0))
             .await?
             .into_iter()
@@ -497,12 +536,9 @@ impl Postgres {
 #[async_trait]
 impl LimitOrderCounting for Postgres {
     async fn count(&self, owner: H160) -> Result<u64> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["count_limit_orders_by_owner"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("count_limit_orders_by_owner");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         Ok(database::orders::user_orders_with_quote(
             &mut ex,
             now_in_epoch_seconds().into(),
@@ -583,6 +619,11 @@ fn full_order_into_model_order(order: FullOrder) -> Result<Order> {
         sender: onchain_user,
         placement_error: onchain_placement_error,
     });
+    let full_app_data = order
+        .full_app_data
+        .map(String::from_utf8)
+        .transpose()
+        .context("full app data isn't utf-8")?;
     let metadata = OrderMetadata {
         creation_date: order.creation_timestamp,
         owner: H160(order.owner.0),
@@ -619,11 +660,16 @@ fn full_order_into_model_order(order: FullOrder) -> Result<Order> {
         ethflow_data,
         onchain_user,
         onchain_order_data,
-        full_app_data: order
-            .full_app_data
-            .map(String::from_utf8)
-            .transpose()
-            .context("full app data isn't utf-8")?,
+        invalidation_reason: order.unsupported_token.map(|token| {
+            InvalidationReason::UnsupportedToken {
+                token: H160(token.0),
+            }
+        }),
+        embargo_until: order.embargo_until,
+        bridging: full_app_data
+            .as_deref()
+            .and_then(|full| model::app_data::bridging(&AppDataHash(order.app_data.0), full)),
+        full_app_data,
     };
     let data = OrderData {
         sell_token: H160(order.sell_token.0),
@@ -732,6 +778,9 @@ mod tests {
             executed_fee: Default::default(),
             executed_fee_token: ByteArray([1; 20]), // TODO surplus token
             full_app_data: Default::default(),
+            unsupported_token: None,
+            embargo_until: None,
+            embargoed: false,
         };
 
         // Open - sell (filled - 0%)
@@ -948,7 +997,7 @@ mod tests {
             .unwrap();
 
         let order_statuses = db
-            .user_orders(&owner, 0, None)
+            .user_orders(&owner, None, None)
             .await
             .unwrap()
             .iter()
@@ -1142,6 +1191,39 @@ mod tests {
         assert_eq!(order_status(3).await, OrderStatus::Open);
     }
 
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_insert_orders_in_batch() {
+        let db = Postgres::try_new("postgresql://").unwrap();
+        database::clear_DANGER(&db.pool).await.unwrap();
+
+        let order = |byte: u8| Order {
+            data: OrderData {
+                valid_to: u32::MAX,
+                ..Default::default()
+            },
+            metadata: OrderMetadata {
+                uid: OrderUid([byte; 56]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The duplicate gets skipped without affecting the other orders.
+        let results = db
+            .insert_orders(&[(order(1), None), (order(1), None), (order(2), None)])
+            .await
+            .unwrap();
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(InsertionError::DuplicatedRecord)));
+        assert!(results[2].is_ok());
+
+        for byte in [1, 2] {
+            let stored = db.single_order(&OrderUid([byte; 56])).await.unwrap();
+            assert_eq!(stored, Some(order(byte)));
+        }
+    }
+
     #[tokio::test]
     #[ignore]
     async fn postgres_insert_orders_with_interactions() {
@@ -1239,6 +1321,7 @@ mod tests {
                         call_data: vec![3, 20],
                     }],
                     jit_orders: vec![],
+                    owner: None,
                 }
                 .into(),
                 ..Default::default()
@@ -12,24 +12,18 @@ use {
 #[async_trait::async_trait]
 impl QuoteStoring for Postgres {
     async fn save(&self, data: QuoteData) -> Result<QuoteId> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["save_quote"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("save_quote");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let row = create_quote_row(data)?;
         let id = database::quotes::save(&mut ex, &row).await?;
         Ok(id)
     }
 
     async fn get(&self, id: QuoteId) -> Result<Option<QuoteData>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["get_quote"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("get_quote");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let quote = database::quotes::get(&mut ex, id).await?;
         quote.map(TryFrom::try_from).transpose()
     }
@@ -39,12 +33,9 @@ impl QuoteStoring for Postgres {
         params: QuoteSearchParameters,
         expiration: DateTime<Utc>,
     ) -> Result<Option<(QuoteId, QuoteData)>> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["find_quote"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("find_quote");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let params = create_db_search_parameters(params, expiration);
         let quote = database::quotes::find(&mut ex, &params)
             .await
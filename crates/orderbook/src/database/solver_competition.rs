@@ -31,12 +31,9 @@ impl SolverCompetitionStoring for Postgres {
         &self,
         id: Identifier,
     ) -> Result<SolverCompetitionAPI, LoadSolverCompetitionError> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["load_solver_competition"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("load_solver_competition");
 
-        let mut ex = self.pool.acquire().await.map_err(anyhow::Error::from)?;
+        let mut ex = self.acquire_read().await.map_err(anyhow::Error::from)?;
         match id {
             Identifier::Id(id) => database::solver_competition::load_by_id(&mut ex, id)
                 .await
@@ -67,12 +64,9 @@ impl SolverCompetitionStoring for Postgres {
     async fn load_latest_competition(
         &self,
     ) -> Result<SolverCompetitionAPI, LoadSolverCompetitionError> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["load_latest_solver_competition"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("load_latest_solver_competition");
 
-        let mut ex = self.pool.acquire().await.map_err(anyhow::Error::from)?;
+        let mut ex = self.acquire_read().await.map_err(anyhow::Error::from)?;
         database::solver_competition::load_latest_competition(&mut ex)
             .await
             .context("solver_competition::load_latest")?
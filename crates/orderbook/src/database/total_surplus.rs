@@ -89,12 +89,9 @@ FROM trade_surplus
 
 impl super::Postgres {
     pub async fn total_surplus(&self, user: &H160) -> Result<U256> {
-        let _timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["get_total_surplus"])
-            .start_timer();
+        let _timer = database::instrumentation::time_query("get_total_surplus");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let surplus = fetch_total_surplus(&mut ex, &ByteArray(user.0)).await?;
         Ok(U256::from_f64_lossy(surplus))
     }
@@ -1,10 +1,18 @@
 use {
     crate::database::Postgres,
     anyhow::{Context, Result},
-    database::{byte_array::ByteArray, trades::TradesQueryRow},
+    database::{
+        byte_array::ByteArray,
+        events::EventIndex,
+        trades::{SettledTrade as SettledTradeRow, TradesQueryRow},
+    },
     ethcontract::H160,
     futures::stream::TryStreamExt,
-    model::{fee_policy::ExecutedProtocolFee, order::OrderUid, trade::Trade},
+    model::{
+        fee_policy::ExecutedProtocolFee,
+        order::OrderUid,
+        trade::{SettledTrade, Trade},
+    },
     number::conversions::big_decimal_to_big_uint,
     primitive_types::H256,
     std::convert::TryInto,
@@ -25,22 +33,82 @@ pub struct TradeFilter {
 #[async_trait::async_trait]
 impl TradeRetrieving for Postgres {
     async fn trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
-        let timer = super::Metrics::get()
-            .database_queries
-            .with_label_values(&["trades"])
-            .start_timer();
+        let timer = database::instrumentation::time_query("trades");
 
-        let mut ex = self.pool.acquire().await?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
         let trades = database::trades::trades(
             &mut ex,
             filter.owner.map(|owner| ByteArray(owner.0)).as_ref(),
             filter.order_uid.map(|uid| ByteArray(uid.0)).as_ref(),
         )
-        .map_err(anyhow::Error::from)
         .try_collect::<Vec<TradesQueryRow>>()
         .await?;
         timer.stop_and_record();
 
+        self.trades_with_protocol_fees(trades).await
+    }
+}
+
+impl Postgres {
+    /// Retrieves up to `limit` trades of the owner, most recent first. The
+    /// page starts after the trade with the given block number and log index,
+    /// usually the last trade of the previous page.
+    pub async fn owner_trades(
+        &self,
+        owner: &H160,
+        after: Option<(u64, u64)>,
+        limit: u64,
+    ) -> Result<Vec<Trade>> {
+        let timer = database::instrumentation::time_query("owner_trades");
+
+        let after = after
+            .map(|(block_number, log_index)| {
+                anyhow::Ok(EventIndex {
+                    block_number: block_number.try_into().context("block_number is not i64")?,
+                    log_index: log_index.try_into().context("log_index is not i64")?,
+                })
+            })
+            .transpose()?;
+        let limit = limit.try_into().context("limit is not i64")?;
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        let trades =
+            database::trades::owner_trades(&mut ex, &ByteArray(owner.0), after, limit).await?;
+        timer.stop_and_record();
+
+        self.trades_with_protocol_fees(trades).await
+    }
+
+    /// Retrieves the trades of all the given orders at once.
+    pub async fn trades_for_orders(&self, order_uids: &[OrderUid]) -> Result<Vec<Trade>> {
+        let timer = database::instrumentation::time_query("trades_for_orders");
+
+        let order_uids: Vec<_> = order_uids.iter().map(|uid| ByteArray(uid.0)).collect();
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        let trades = database::trades::trades_for_orders(&mut ex, &order_uids)
+            .map_err(anyhow::Error::from)
+            .try_collect::<Vec<TradesQueryRow>>()
+            .await?;
+        timer.stop_and_record();
+
+        self.trades_with_protocol_fees(trades).await
+    }
+
+    /// Retrieves the trades executed by the settlement in the given
+    /// transaction.
+    pub async fn trades_in_tx(&self, tx_hash: &H256) -> Result<Vec<SettledTrade>> {
+        let timer = database::instrumentation::time_query("trades_in_tx");
+
+        let mut ex = database::instrumentation::acquire(&self.pool).await?;
+        let trades = database::trades::trades_in_tx(&mut ex, &ByteArray(tx_hash.0))
+            .map_err(anyhow::Error::from)
+            .try_collect::<Vec<SettledTradeRow>>()
+            .await?;
+        timer.stop_and_record();
+
+        trades.into_iter().map(settled_trade_from).collect()
+    }
+
+    async fn trades_with_protocol_fees(&self, trades: Vec<TradesQueryRow>) -> Result<Vec<Trade>> {
         let auction_order_uids = trades
             .iter()
             .filter_map(|t| t.auction_id.map(|auction_id| (auction_id, t.order_uid)))
@@ -102,6 +170,26 @@ fn trade_from(
     })
 }
 
+fn settled_trade_from(row: SettledTradeRow) -> Result<SettledTrade> {
+    Ok(SettledTrade {
+        order_uid: OrderUid(row.order_uid.0),
+        sell_amount: big_decimal_to_big_uint(&row.sell_amount)
+            .context("sell_amount is not an unsigned integer")?,
+        buy_amount: big_decimal_to_big_uint(&row.buy_amount)
+            .context("buy_amount is not an unsigned integer")?,
+        fee_amount: big_decimal_to_big_uint(&row.fee_amount)
+            .context("fee_amount is not an unsigned integer")?,
+        executed_fee: row
+            .executed_fee
+            .map(|fee| {
+                big_decimal_to_big_uint(&fee).context("executed_fee is not an unsigned integer")
+            })
+            .transpose()?,
+        executed_fee_token: row.executed_fee_token.map(|token| H160(token.0)),
+        solver: H160(row.solver.0),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,4 +198,9 @@ mod tests {
     fn convert_trade() {
         trade_from(TradesQueryRow::default(), vec![]).unwrap();
     }
+
+    #[test]
+    fn convert_settled_trade() {
+        settled_trade_from(SettledTradeRow::default()).unwrap();
+    }
 }
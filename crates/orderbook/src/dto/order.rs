@@ -118,4 +118,7 @@ pub enum Status {
     Traded(Vec<SolutionInclusion>),
     /// The user cancelled the order. It will no longer show up in any auctions.
     Cancelled,
+    /// One of the order's tokens is not supported. The order will not show up
+    /// in any auctions until the token becomes supported again.
+    Unsupported,
 }
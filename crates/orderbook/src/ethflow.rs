@@ -0,0 +1,426 @@
+//! Placement of orders selling the chain's native token (e.g. ETH).
+//!
+//! The native token can't be approved, so orders selling it can't be signed
+//! like regular orders. Instead users send the native token to the eth-flow
+//! contract which wraps it and places an order selling the wrapped token on
+//! their behalf with itself as the EIP-1271 owner. This lets API clients place
+//! such orders without knowing the contract: they get the transaction to send
+//! and the uid of the order it will create.
+//!
+//! The order has to reference a quote which gets checked the same way the
+//! autopilot checks it when indexing the placement, so that orders which
+//! would get indexed with a placement error are rejected up front.
+//!
+//! The autopilot indexes the placement events of the contract, so the status
+//! of the placement gets read from the `onchain_placed_orders` table and the
+//! order itself can be queried like every other order once it was indexed.
+
+use {
+    crate::database::Postgres,
+    anyhow::Result,
+    app_data::AppDataHash,
+    ethcontract::{tokens::Tokenize, Bytes},
+    model::{
+        interaction::InteractionData,
+        order::{
+            BuyTokenDestination,
+            OnchainOrderPlacementError,
+            OrderData,
+            OrderKind,
+            OrderUid,
+            SellTokenSource,
+            BUY_ETH_ADDRESS,
+        },
+        quote::QuoteId,
+        signature::SigningScheme,
+        DomainSeparator,
+    },
+    number::serialization::HexOrDecimalU256,
+    primitive_types::{H160, U256},
+    serde::{Deserialize, Serialize},
+    serde_with::serde_as,
+    shared::{
+        db_order_conversions::onchain_placement_error_from,
+        order_quoting::{OrderQuoting, QuoteSearchParameters},
+        order_validation::{
+            convert_signing_scheme_into_quote_signing_scheme,
+            get_quote_and_check_fee,
+            OrderValidating,
+            PartialValidationError,
+            PreOrderData,
+            ValidationError,
+        },
+    },
+    std::sync::Arc,
+};
+
+/// An order selling the native token for which the transaction placing it
+/// should be prepared.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EthFlowOrderCreation {
+    /// The account that will send the transaction placing the order.
+    pub from: H160,
+    /// Has to be [`BUY_ETH_ADDRESS`].
+    pub sell_token: H160,
+    pub buy_token: H160,
+    pub receiver: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub sell_amount: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub buy_amount: U256,
+    pub valid_to: u32,
+    pub app_data: AppDataHash,
+    #[serde(default)]
+    pub partially_fillable: bool,
+    pub quote_id: QuoteId,
+}
+
+/// The transaction placing an eth-flow order and the uid of that order.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthFlowOrderPlacement {
+    pub order_uid: OrderUid,
+    pub transaction: InteractionData,
+}
+
+/// How the placement of an eth-flow order got indexed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Placement {
+    pub sender: H160,
+    pub block_number: u64,
+    /// Set if the order got placed onchain but won't be settled.
+    pub placement_error: Option<OnchainOrderPlacementError>,
+}
+
+#[derive(Debug)]
+pub enum PlacementError {
+    NonNativeSellToken,
+    MissingReceiver,
+    ZeroAmount,
+    Validation(PartialValidationError),
+    Quote(ValidationError),
+}
+
+pub struct EthFlow {
+    contract: H160,
+    native_token: H160,
+    domain_separator: DomainSeparator,
+    order_validator: Arc<dyn OrderValidating>,
+    quoter: Arc<dyn OrderQuoting>,
+    database: Postgres,
+}
+
+impl EthFlow {
+    pub fn new(
+        contract: H160,
+        native_token: H160,
+        domain_separator: DomainSeparator,
+        order_validator: Arc<dyn OrderValidating>,
+        quoter: Arc<dyn OrderQuoting>,
+        database: Postgres,
+    ) -> Self {
+        Self {
+            contract,
+            native_token,
+            domain_separator,
+            order_validator,
+            quoter,
+            database,
+        }
+    }
+
+    /// Validates the order and returns the transaction placing it.
+    pub async fn prepare_order(
+        &self,
+        order: &EthFlowOrderCreation,
+    ) -> Result<EthFlowOrderPlacement, PlacementError> {
+        if order.sell_token != BUY_ETH_ADDRESS {
+            return Err(PlacementError::NonNativeSellToken);
+        }
+        // The contract reverts if no receiver is set.
+        if order.receiver.is_zero() {
+            return Err(PlacementError::MissingReceiver);
+        }
+        if order.sell_amount.is_zero() || order.buy_amount.is_zero() {
+            return Err(PlacementError::ZeroAmount);
+        }
+
+        let data = self.order_data(order);
+        self.order_validator
+            .partial_validate(PreOrderData {
+                // The contract checks the validity of the user's order while
+                // the order it places is valid forever.
+                valid_to: order.valid_to,
+                ..PreOrderData::from_order_creation(order.from, &data, SigningScheme::Eip1271)
+            })
+            .await
+            .map_err(PlacementError::Validation)?;
+        self.check_quote(order, &data).await?;
+
+        Ok(EthFlowOrderPlacement {
+            order_uid: data.uid(&self.domain_separator, &self.contract),
+            transaction: InteractionData {
+                target: self.contract,
+                value: order.sell_amount,
+                call_data: encode_create_order(order),
+            },
+        })
+    }
+
+    /// Finds the referenced quote (or computes a new one) and checks the fee
+    /// like the autopilot does when it indexes the placement.
+    async fn check_quote(
+        &self,
+        order: &EthFlowOrderCreation,
+        data: &OrderData,
+    ) -> Result<(), PlacementError> {
+        let parameters = QuoteSearchParameters {
+            sell_token: data.sell_token,
+            buy_token: data.buy_token,
+            sell_amount: data.sell_amount,
+            buy_amount: data.buy_amount,
+            fee_amount: data.fee_amount,
+            kind: data.kind,
+            signing_scheme: convert_signing_scheme_into_quote_signing_scheme(
+                SigningScheme::Eip1271,
+                false,
+                0,
+            )
+            .expect("eip-1271 is a valid onchain signing scheme"),
+            additional_gas: 0,
+            // The autopilot doesn't require a verified quote for eth-flow
+            // orders either.
+            verification: Default::default(),
+        };
+        get_quote_and_check_fee(
+            self.quoter.as_ref(),
+            &parameters,
+            Some(order.quote_id),
+            Some(data.fee_amount),
+        )
+        .await
+        .map_err(PlacementError::Quote)?;
+        Ok(())
+    }
+
+    /// Returns how the placement of the order got indexed or `None` if it
+    /// wasn't indexed (yet).
+    pub async fn placement(&self, uid: &OrderUid) -> Result<Option<Placement>> {
+        let placement = self.database.onchain_order_placement(uid).await?;
+        Ok(placement
+            .filter(|placement| !placement.is_reorged)
+            .map(|placement| Placement {
+                sender: H160(placement.sender.0),
+                block_number: placement.block_number.try_into().unwrap_or_default(),
+                placement_error: onchain_placement_error_from(placement.placement_error.as_ref()),
+            }))
+    }
+
+    /// The order the contract places on behalf of the user.
+    fn order_data(&self, order: &EthFlowOrderCreation) -> OrderData {
+        OrderData {
+            sell_token: self.native_token,
+            buy_token: order.buy_token,
+            receiver: Some(order.receiver),
+            sell_amount: order.sell_amount,
+            buy_amount: order.buy_amount,
+            valid_to: u32::MAX,
+            app_data: order.app_data,
+            fee_amount: U256::zero(),
+            kind: OrderKind::Sell,
+            partially_fillable: order.partially_fillable,
+            sell_token_balance: SellTokenSource::Erc20,
+            buy_token_balance: BuyTokenDestination::Erc20,
+        }
+    }
+}
+
+/// Encodes the `CoWSwapEthFlow.createOrder()` calldata placing the order.
+fn encode_create_order(order: &EthFlowOrderCreation) -> Vec<u8> {
+    let order = (
+        order.buy_token,
+        order.receiver,
+        order.sell_amount,
+        order.buy_amount,
+        Bytes(order.app_data.0),
+        U256::zero(), // feeAmount
+        order.valid_to,
+        order.partially_fillable,
+        order.quote_id,
+    );
+    contracts::CoWSwapEthFlow::raw_contract()
+        .interface
+        .abi
+        .function("createOrder")
+        .unwrap()
+        .encode_input(&[order.into_token()])
+        .expect("tokens match the createOrder() signature")
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        shared::{
+            order_quoting::{FindQuoteError, MockOrderQuoting, Quote},
+            order_validation::MockOrderValidating,
+        },
+    };
+
+    fn order() -> EthFlowOrderCreation {
+        EthFlowOrderCreation {
+            from: H160([1; 20]),
+            sell_token: BUY_ETH_ADDRESS,
+            buy_token: H160([2; 20]),
+            receiver: H160([3; 20]),
+            sell_amount: 1_000.into(),
+            buy_amount: 2_000.into(),
+            valid_to: 1_700_000_000,
+            app_data: AppDataHash([4; 32]),
+            partially_fillable: false,
+            quote_id: 42,
+        }
+    }
+
+    fn ethflow(order_validator: MockOrderValidating, quoter: MockOrderQuoting) -> EthFlow {
+        EthFlow::new(
+            H160([5; 20]),
+            H160([6; 20]),
+            DomainSeparator([7; 32]),
+            Arc::new(order_validator),
+            Arc::new(quoter),
+            Postgres::try_new("postgresql://").unwrap(),
+        )
+    }
+
+    fn quoter_finding_quote() -> MockOrderQuoting {
+        let mut quoter = MockOrderQuoting::new();
+        quoter
+            .expect_find_quote()
+            .withf(|id, parameters| {
+                *id == Some(42)
+                    && parameters.sell_token == H160([6; 20])
+                    && parameters.sell_amount == 1_000.into()
+                    && parameters.fee_amount.is_zero()
+                    && parameters.kind == OrderKind::Sell
+            })
+            .returning(|id, _| {
+                Ok(Quote {
+                    id,
+                    ..Default::default()
+                })
+            });
+        quoter
+    }
+
+    #[tokio::test]
+    async fn prepares_order_placement() {
+        let mut order_validator = MockOrderValidating::new();
+        order_validator
+            .expect_partial_validate()
+            .withf(|order| {
+                order.owner == H160([1; 20])
+                    && order.sell_token == H160([6; 20])
+                    && order.valid_to == 1_700_000_000
+                    && order.signing_scheme == SigningScheme::Eip1271
+            })
+            .returning(|_| Ok(()));
+        let ethflow = ethflow(order_validator, quoter_finding_quote());
+
+        let placement = ethflow.prepare_order(&order()).await.unwrap();
+        let expected_uid = OrderData {
+            sell_token: H160([6; 20]),
+            buy_token: H160([2; 20]),
+            receiver: Some(H160([3; 20])),
+            sell_amount: 1_000.into(),
+            buy_amount: 2_000.into(),
+            valid_to: u32::MAX,
+            app_data: AppDataHash([4; 32]),
+            kind: OrderKind::Sell,
+            ..Default::default()
+        }
+        .uid(&DomainSeparator([7; 32]), &H160([5; 20]));
+        assert_eq!(placement.order_uid, expected_uid);
+        assert_eq!(placement.transaction.target, H160([5; 20]));
+        assert_eq!(placement.transaction.value, 1_000.into());
+
+        let function = contracts::CoWSwapEthFlow::raw_contract()
+            .interface
+            .abi
+            .function("createOrder")
+            .unwrap();
+        let call_data = &placement.transaction.call_data;
+        assert_eq!(call_data[..4], function.short_signature());
+        let tokens = function.decode_input(&call_data[4..]).unwrap();
+        let order = order();
+        let expected = (
+            order.buy_token,
+            order.receiver,
+            order.sell_amount,
+            order.buy_amount,
+            Bytes(order.app_data.0),
+            U256::zero(),
+            order.valid_to,
+            order.partially_fillable,
+            order.quote_id,
+        );
+        assert_eq!(tokens, vec![expected.into_token()]);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_orders() {
+        let ethflow = &ethflow(MockOrderValidating::new(), MockOrderQuoting::new());
+        let prepare = |order: EthFlowOrderCreation| async move {
+            ethflow.prepare_order(&order).await.unwrap_err()
+        };
+
+        assert!(matches!(
+            prepare(EthFlowOrderCreation {
+                sell_token: H160([6; 20]),
+                ..order()
+            })
+            .await,
+            PlacementError::NonNativeSellToken
+        ));
+        assert!(matches!(
+            prepare(EthFlowOrderCreation {
+                receiver: H160::zero(),
+                ..order()
+            })
+            .await,
+            PlacementError::MissingReceiver
+        ));
+        assert!(matches!(
+            prepare(EthFlowOrderCreation {
+                buy_amount: U256::zero(),
+                ..order()
+            })
+            .await,
+            PlacementError::ZeroAmount
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_orders_without_quote() {
+        let mut order_validator = MockOrderValidating::new();
+        order_validator
+            .expect_partial_validate()
+            .returning(|_| Ok(()));
+        let mut quoter = MockOrderQuoting::new();
+        quoter
+            .expect_find_quote()
+            .returning(|_, _| Err(FindQuoteError::NotFound(None)));
+        quoter
+            .expect_calculate_quote()
+            .returning(|_| Err(anyhow::anyhow!("no liquidity").into()));
+        let ethflow = ethflow(order_validator, quoter);
+
+        assert!(matches!(
+            ethflow.prepare_order(&order()).await.unwrap_err(),
+            PlacementError::Quote(_)
+        ));
+    }
+}
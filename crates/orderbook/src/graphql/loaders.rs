@@ -0,0 +1,44 @@
+//! Batches the lookups of nested fields across all items of a response.
+
+use {
+    crate::database::Postgres,
+    async_graphql::dataloader::Loader,
+    model::{
+        order::{Order, OrderUid},
+        trade::Trade,
+    },
+    std::{collections::HashMap, sync::Arc},
+};
+
+/// Loads orders by their uid.
+pub struct Orders(pub Postgres);
+
+impl Loader<OrderUid> for Orders {
+    type Error = Arc<anyhow::Error>;
+    type Value = Order;
+
+    async fn load(&self, uids: &[OrderUid]) -> Result<HashMap<OrderUid, Order>, Self::Error> {
+        let orders = self.0.orders_by_uids(uids).await.map_err(Arc::new)?;
+        Ok(orders
+            .into_iter()
+            .map(|order| (order.metadata.uid, order))
+            .collect())
+    }
+}
+
+/// Loads all trades of orders by the order uid.
+pub struct Trades(pub Postgres);
+
+impl Loader<OrderUid> for Trades {
+    type Error = Arc<anyhow::Error>;
+    type Value = Vec<Trade>;
+
+    async fn load(&self, uids: &[OrderUid]) -> Result<HashMap<OrderUid, Vec<Trade>>, Self::Error> {
+        let trades = self.0.trades_for_orders(uids).await.map_err(Arc::new)?;
+        let mut by_order = HashMap::<_, Vec<_>>::new();
+        for trade in trades {
+            by_order.entry(trade.order_uid).or_default().push(trade);
+        }
+        Ok(by_order)
+    }
+}
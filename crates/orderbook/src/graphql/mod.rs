@@ -0,0 +1,183 @@
+//! GraphQL facade over orders, trades, quotes and solver competitions.
+//!
+//! Lets integrators fetch related data, like orders together with their
+//! trades, with a single request instead of combining several REST endpoints.
+//! Nested lookups go through data loaders which batch them into one database
+//! query per level of the response instead of one query per returned item.
+
+mod loaders;
+mod types;
+
+use {
+    crate::database::Postgres,
+    async_graphql::{
+        dataloader::DataLoader,
+        extensions::{
+            Extension,
+            ExtensionContext,
+            ExtensionFactory,
+            NextRequest,
+            NextResolve,
+            ResolveInfo,
+        },
+        EmptyMutation,
+        EmptySubscription,
+        Request,
+        Response,
+        ServerResult,
+        Value,
+    },
+    std::{sync::Arc, time::Instant},
+};
+
+type Schema = async_graphql::Schema<types::Query, EmptyMutation, EmptySubscription>;
+
+/// Limits protecting the database from overly expensive queries.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub max_depth: usize,
+    pub max_complexity: usize,
+}
+
+pub struct GraphQl {
+    schema: Schema,
+    database: Postgres,
+}
+
+impl GraphQl {
+    pub fn new(database: Postgres, limits: Limits) -> Self {
+        let schema = Schema::build(types::Query, EmptyMutation, EmptySubscription)
+            .data(database.clone())
+            .limit_depth(limits.max_depth)
+            .limit_complexity(limits.max_complexity)
+            .extension(Instrumentation)
+            .finish();
+        Self { schema, database }
+    }
+
+    pub async fn execute(&self, request: Request) -> Response {
+        // Data loaders cache what they loaded so every request gets its own
+        // to never serve stale data.
+        let request = request
+            .data(DataLoader::new(
+                loaders::Orders(self.database.clone()),
+                tokio::spawn,
+            ))
+            .data(DataLoader::new(
+                loaders::Trades(self.database.clone()),
+                tokio::spawn,
+            ));
+        self.schema.execute(request).await
+    }
+}
+
+/// Converts an internal error into a GraphQL error without exposing its
+/// details to the client.
+fn internal_error(err: impl std::fmt::Debug) -> async_graphql::Error {
+    tracing::error!(?err, "graphql");
+    async_graphql::Error::new("internal error")
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "graphql")]
+struct Metrics {
+    /// Number of GraphQL requests that failed without returning any data,
+    /// e.g. for being malformed or exceeding the depth or complexity limits.
+    failed_requests: prometheus::IntCounter,
+
+    /// Number of resolved top level query fields.
+    #[metric(labels("field", "result"))]
+    queries: prometheus::IntCounterVec,
+
+    /// Time it took to resolve top level query fields including all their
+    /// nested fields.
+    #[metric(labels("field"))]
+    query_duration_seconds: prometheus::HistogramVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+/// Records [`Metrics`] for every executed request.
+struct Instrumentation;
+
+impl ExtensionFactory for Instrumentation {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(Instrumentation)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for Instrumentation {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let response = next.run(ctx).await;
+        if response.data == Value::Null && response.is_err() {
+            Metrics::get().failed_requests.inc();
+        }
+        response
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.path_node.parent.is_some() {
+            return next.run(ctx, info).await;
+        }
+        // Label by the field name of the schema instead of client provided
+        // operation names or aliases to keep the cardinality bounded.
+        let field = info.name.to_string();
+        let timer = Instant::now();
+        let result = next.run(ctx, info).await;
+        let metrics = Metrics::get();
+        metrics
+            .query_duration_seconds
+            .with_label_values(&[&field])
+            .observe(timer.elapsed().as_secs_f64());
+        metrics
+            .queries
+            .with_label_values(&[&field, if result.is_ok() { "ok" } else { "error" }])
+            .inc();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rejects_queries_exceeding_limits() {
+        // Rejected queries never reach the database.
+        let database = Postgres::try_new("postgresql://").unwrap();
+        let graphql = GraphQl::new(
+            database,
+            Limits {
+                max_depth: 3,
+                max_complexity: 50,
+            },
+        );
+        let owner = "0x0000000000000000000000000000000000000001";
+
+        let too_deep =
+            format!(r#"{{ orders(owner: "{owner}") {{ trades {{ order {{ uid }} }} }} }}"#);
+        let response = graphql.execute(Request::new(too_deep)).await;
+        assert!(response.is_err());
+        assert_eq!(response.data, Value::Null);
+
+        let too_complex = format!(r#"{{ orders(owner: "{owner}", limit: 100) {{ uid }} }}"#);
+        let response = graphql.execute(Request::new(too_complex)).await;
+        assert!(response.is_err());
+        assert_eq!(response.data, Value::Null);
+
+        let too_complex = format!(r#"{{ trades(owner: "{owner}", limit: 100) {{ logIndex }} }}"#);
+        let response = graphql.execute(Request::new(too_complex)).await;
+        assert!(response.is_err());
+        assert_eq!(response.data, Value::Null);
+    }
+}
@@ -0,0 +1,494 @@
+//! The GraphQL schema. Objects wrap the types of the REST API and expose their
+//! fields with GraphQL naming conventions. Token amounts are decimal strings
+//! because they don't fit into GraphQL integers.
+
+use {
+    super::{internal_error, loaders},
+    crate::{
+        database::{orders::OrderStoring, Postgres},
+        solver_competition::{
+            Identifier,
+            LoadSolverCompetitionError,
+            SolverCompetitionStoring,
+        },
+    },
+    async_graphql::{dataloader::DataLoader, scalar, Context, Enum, InputObject, Object, Result},
+    chrono::{DateTime, Utc},
+    model::{order, solver_competition, trade},
+    primitive_types::{H160, H256},
+    serde::{Deserialize, Serialize},
+    shared::order_quoting::{QuoteData, QuoteStoring},
+};
+
+/// Default number of items returned by list queries.
+const DEFAULT_LIMIT: u64 = 10;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Address(H160);
+scalar!(Address, "Address", "Hex encoded 20 byte address.");
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct OrderUid(order::OrderUid);
+scalar!(OrderUid, "OrderUid", "Hex encoded 56 byte order uid.");
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct TransactionHash(H256);
+scalar!(
+    TransactionHash,
+    "TransactionHash",
+    "Hex encoded 32 byte transaction hash."
+);
+
+/// Identifies a trade by the position of its event on chain.
+#[derive(InputObject)]
+pub struct TradeIndex {
+    block_number: u64,
+    log_index: u64,
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Looks up an order by its uid.
+    async fn order(&self, ctx: &Context<'_>, uid: OrderUid) -> Result<Option<Order>> {
+        let orders = ctx.data_unchecked::<DataLoader<loaders::Orders>>();
+        let order = orders.load_one(uid.0).await.map_err(internal_error)?;
+        Ok(order.map(Order))
+    }
+
+    /// Orders of an owner, most recently created first. The page starts after
+    /// the order `after`, usually the last order of the previous page.
+    #[graphql(complexity = "limit as usize * child_complexity")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        owner: Address,
+        after: Option<OrderUid>,
+        #[graphql(default_with = "DEFAULT_LIMIT", validator(minimum = 1, maximum = 1000))]
+        limit: u64,
+    ) -> Result<Vec<Order>> {
+        let database = ctx.data_unchecked::<Postgres>();
+        let orders = database
+            .user_orders(&owner.0, after.map(|uid| uid.0).as_ref(), Some(limit))
+            .await
+            .map_err(internal_error)?;
+        Ok(orders.into_iter().map(Order).collect())
+    }
+
+    /// Trades of an owner, most recent first. The page starts after the trade
+    /// `after`, usually the last trade of the previous page.
+    #[graphql(complexity = "limit as usize * child_complexity")]
+    async fn trades(
+        &self,
+        ctx: &Context<'_>,
+        owner: Address,
+        after: Option<TradeIndex>,
+        #[graphql(default_with = "DEFAULT_LIMIT", validator(minimum = 1, maximum = 1000))]
+        limit: u64,
+    ) -> Result<Vec<Trade>> {
+        let database = ctx.data_unchecked::<Postgres>();
+        let trades = database
+            .owner_trades(
+                &owner.0,
+                after.map(|trade| (trade.block_number, trade.log_index)),
+                limit,
+            )
+            .await
+            .map_err(internal_error)?;
+        Ok(trades.into_iter().map(Trade).collect())
+    }
+
+    /// Looks up a quote by its id.
+    async fn quote(&self, ctx: &Context<'_>, id: i64) -> Result<Option<Quote>> {
+        let database = ctx.data_unchecked::<Postgres>();
+        let quote = QuoteStoring::get(database, id)
+            .await
+            .map_err(internal_error)?;
+        Ok(quote.map(|data| Quote { id, data }))
+    }
+
+    /// The solver competition of an auction, identified either by the auction
+    /// id or the hash of its settlement transaction. Returns the latest
+    /// competition if neither is specified.
+    async fn solver_competition(
+        &self,
+        ctx: &Context<'_>,
+        auction_id: Option<i64>,
+        tx_hash: Option<TransactionHash>,
+    ) -> Result<Option<SolverCompetition>> {
+        let database = ctx.data_unchecked::<Postgres>();
+        let competition = match (auction_id, tx_hash) {
+            (Some(id), None) => database.load_competition(Identifier::Id(id)).await,
+            (None, Some(hash)) => {
+                database
+                    .load_competition(Identifier::Transaction(hash.0))
+                    .await
+            }
+            (None, None) => database.load_latest_competition().await,
+            (Some(_), Some(_)) => {
+                return Err("specify at most one of auctionId and txHash".into());
+            }
+        };
+        match competition {
+            Ok(competition) => Ok(Some(SolverCompetition(competition))),
+            Err(LoadSolverCompetitionError::NotFound) => Ok(None),
+            Err(LoadSolverCompetitionError::Other(err)) => Err(internal_error(err)),
+        }
+    }
+}
+
+pub struct Order(order::Order);
+
+#[Object]
+impl Order {
+    async fn uid(&self) -> OrderUid {
+        OrderUid(self.0.metadata.uid)
+    }
+
+    async fn owner(&self) -> Address {
+        Address(self.0.metadata.owner)
+    }
+
+    async fn sell_token(&self) -> Address {
+        Address(self.0.data.sell_token)
+    }
+
+    async fn buy_token(&self) -> Address {
+        Address(self.0.data.buy_token)
+    }
+
+    async fn receiver(&self) -> Option<Address> {
+        self.0.data.receiver.map(Address)
+    }
+
+    async fn sell_amount(&self) -> String {
+        self.0.data.sell_amount.to_string()
+    }
+
+    async fn buy_amount(&self) -> String {
+        self.0.data.buy_amount.to_string()
+    }
+
+    async fn fee_amount(&self) -> String {
+        self.0.data.fee_amount.to_string()
+    }
+
+    async fn valid_to(&self) -> u32 {
+        self.0.data.valid_to
+    }
+
+    async fn kind(&self) -> OrderKind {
+        self.0.data.kind.into()
+    }
+
+    async fn class(&self) -> OrderClass {
+        self.0.metadata.class.into()
+    }
+
+    async fn partially_fillable(&self) -> bool {
+        self.0.data.partially_fillable
+    }
+
+    async fn status(&self) -> OrderStatus {
+        self.0.metadata.status.into()
+    }
+
+    async fn creation_date(&self) -> DateTime<Utc> {
+        self.0.metadata.creation_date
+    }
+
+    async fn executed_sell_amount(&self) -> String {
+        self.0.metadata.executed_sell_amount.to_string()
+    }
+
+    async fn executed_buy_amount(&self) -> String {
+        self.0.metadata.executed_buy_amount.to_string()
+    }
+
+    async fn full_app_data(&self) -> Option<&str> {
+        self.0.metadata.full_app_data.as_deref()
+    }
+
+    /// All trades that (partially) executed this order.
+    async fn trades(&self, ctx: &Context<'_>) -> Result<Vec<Trade>> {
+        let trades = ctx.data_unchecked::<DataLoader<loaders::Trades>>();
+        let trades = trades
+            .load_one(self.0.metadata.uid)
+            .await
+            .map_err(internal_error)?;
+        Ok(trades.unwrap_or_default().into_iter().map(Trade).collect())
+    }
+}
+
+pub struct Trade(trade::Trade);
+
+#[Object]
+impl Trade {
+    async fn block_number(&self) -> u64 {
+        self.0.block_number
+    }
+
+    async fn log_index(&self) -> u64 {
+        self.0.log_index
+    }
+
+    async fn order_uid(&self) -> OrderUid {
+        OrderUid(self.0.order_uid)
+    }
+
+    async fn owner(&self) -> Address {
+        Address(self.0.owner)
+    }
+
+    async fn sell_token(&self) -> Address {
+        Address(self.0.sell_token)
+    }
+
+    async fn buy_token(&self) -> Address {
+        Address(self.0.buy_token)
+    }
+
+    async fn sell_amount(&self) -> String {
+        self.0.sell_amount.to_string()
+    }
+
+    async fn sell_amount_before_fees(&self) -> String {
+        self.0.sell_amount_before_fees.to_string()
+    }
+
+    async fn buy_amount(&self) -> String {
+        self.0.buy_amount.to_string()
+    }
+
+    async fn tx_hash(&self) -> Option<TransactionHash> {
+        self.0.tx_hash.map(TransactionHash)
+    }
+
+    async fn order(&self, ctx: &Context<'_>) -> Result<Option<Order>> {
+        let orders = ctx.data_unchecked::<DataLoader<loaders::Orders>>();
+        let order = orders
+            .load_one(self.0.order_uid)
+            .await
+            .map_err(internal_error)?;
+        Ok(order.map(Order))
+    }
+}
+
+pub struct Quote {
+    id: i64,
+    data: QuoteData,
+}
+
+#[Object]
+impl Quote {
+    async fn id(&self) -> i64 {
+        self.id
+    }
+
+    async fn sell_token(&self) -> Address {
+        Address(self.data.sell_token)
+    }
+
+    async fn buy_token(&self) -> Address {
+        Address(self.data.buy_token)
+    }
+
+    async fn sell_amount(&self) -> String {
+        self.data.quoted_sell_amount.to_string()
+    }
+
+    async fn buy_amount(&self) -> String {
+        self.data.quoted_buy_amount.to_string()
+    }
+
+    async fn kind(&self) -> OrderKind {
+        self.data.kind.into()
+    }
+
+    async fn expiration(&self) -> DateTime<Utc> {
+        self.data.expiration
+    }
+
+    /// The solver that provided the quote.
+    async fn solver(&self) -> Address {
+        Address(self.data.solver)
+    }
+
+    async fn verified(&self) -> bool {
+        self.data.verified
+    }
+}
+
+pub struct SolverCompetition(solver_competition::SolverCompetitionAPI);
+
+#[Object]
+impl SolverCompetition {
+    async fn auction_id(&self) -> i64 {
+        self.0.auction_id
+    }
+
+    async fn transaction_hashes(&self) -> Vec<TransactionHash> {
+        self.0
+            .transaction_hashes
+            .iter()
+            .copied()
+            .map(TransactionHash)
+            .collect()
+    }
+
+    async fn auction_start_block(&self) -> u64 {
+        self.0.common.auction_start_block
+    }
+
+    /// Orders that were part of the auction.
+    async fn orders(&self) -> Vec<OrderUid> {
+        self.0
+            .common
+            .auction
+            .orders
+            .iter()
+            .copied()
+            .map(OrderUid)
+            .collect()
+    }
+
+    async fn solutions(&self) -> Vec<Solution> {
+        self.0
+            .common
+            .solutions
+            .iter()
+            .cloned()
+            .map(Solution)
+            .collect()
+    }
+}
+
+pub struct Solution(solver_competition::SolverSettlement);
+
+#[Object]
+impl Solution {
+    async fn solver(&self) -> &str {
+        &self.0.solver
+    }
+
+    async fn solver_address(&self) -> Address {
+        Address(self.0.solver_address)
+    }
+
+    async fn score(&self) -> Option<String> {
+        self.0.score.map(|score| score.score().to_string())
+    }
+
+    async fn ranking(&self) -> usize {
+        self.0.ranking
+    }
+
+    async fn is_winner(&self) -> bool {
+        self.0.is_winner
+    }
+
+    async fn orders(&self) -> Vec<SolutionOrder> {
+        self.0.orders.iter().cloned().map(SolutionOrder).collect()
+    }
+}
+
+pub struct SolutionOrder(solver_competition::Order);
+
+impl SolutionOrder {
+    fn id(&self) -> order::OrderUid {
+        match &self.0 {
+            solver_competition::Order::Colocated { id, .. }
+            | solver_competition::Order::Legacy { id, .. } => *id,
+        }
+    }
+}
+
+#[Object]
+impl SolutionOrder {
+    async fn uid(&self) -> OrderUid {
+        OrderUid(self.id())
+    }
+
+    /// The amount that left the user's wallet including all fees. Not known
+    /// for legacy solutions.
+    async fn sell_amount(&self) -> Option<String> {
+        match &self.0 {
+            solver_competition::Order::Colocated { sell_amount, .. } => {
+                Some(sell_amount.to_string())
+            }
+            solver_competition::Order::Legacy { .. } => None,
+        }
+    }
+
+    /// The amount the user received after all fees. Not known for legacy
+    /// solutions.
+    async fn buy_amount(&self) -> Option<String> {
+        match &self.0 {
+            solver_competition::Order::Colocated { buy_amount, .. } => Some(buy_amount.to_string()),
+            solver_competition::Order::Legacy { .. } => None,
+        }
+    }
+
+    async fn order(&self, ctx: &Context<'_>) -> Result<Option<Order>> {
+        let orders = ctx.data_unchecked::<DataLoader<loaders::Orders>>();
+        let order = orders.load_one(self.id()).await.map_err(internal_error)?;
+        Ok(order.map(Order))
+    }
+}
+
+#[derive(Enum, Clone, Copy, Eq, PartialEq)]
+pub enum OrderKind {
+    Buy,
+    Sell,
+}
+
+impl From<order::OrderKind> for OrderKind {
+    fn from(value: order::OrderKind) -> Self {
+        match value {
+            order::OrderKind::Buy => Self::Buy,
+            order::OrderKind::Sell => Self::Sell,
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, Eq, PartialEq)]
+pub enum OrderClass {
+    Market,
+    Liquidity,
+    Limit,
+}
+
+impl From<order::OrderClass> for OrderClass {
+    fn from(value: order::OrderClass) -> Self {
+        match value {
+            order::OrderClass::Market => Self::Market,
+            order::OrderClass::Liquidity => Self::Liquidity,
+            order::OrderClass::Limit => Self::Limit,
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, Eq, PartialEq)]
+pub enum OrderStatus {
+    PresignaturePending,
+    Open,
+    Fulfilled,
+    Cancelled,
+    Expired,
+}
+
+impl From<order::OrderStatus> for OrderStatus {
+    fn from(value: order::OrderStatus) -> Self {
+        match value {
+            order::OrderStatus::PresignaturePending => Self::PresignaturePending,
+            order::OrderStatus::Open => Self::Open,
+            order::OrderStatus::Fulfilled => Self::Fulfilled,
+            order::OrderStatus::Cancelled => Self::Cancelled,
+            order::OrderStatus::Expired => Self::Expired,
+        }
+    }
+}
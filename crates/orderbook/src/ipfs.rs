@@ -1,6 +1,7 @@
 use {
     anyhow::{Context, Result},
-    reqwest::{Client, ClientBuilder, StatusCode},
+    reqwest::{multipart, Client, ClientBuilder, StatusCode},
+    serde::Deserialize,
     std::time::Duration,
     url::Url,
 };
@@ -60,6 +61,50 @@ impl Ipfs {
     }
 }
 
+/// Stores and pins blocks through the RPC API of an IPFS node (or a pinning
+/// service offering the same API), so that they can be fetched from any
+/// gateway.
+pub struct IpfsPinning {
+    client: Client,
+    api: Url,
+    auth: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BlockPutResponse {
+    key: String,
+}
+
+impl IpfsPinning {
+    pub fn new(client: ClientBuilder, api: Url, auth: Option<String>) -> Self {
+        assert!(!api.cannot_be_a_base());
+        Self {
+            client: client.timeout(Duration::from_secs(10)).build().unwrap(),
+            api,
+            auth,
+        }
+    }
+
+    /// Stores the content as a raw block addressed by its keccak-256 hash and
+    /// pins it. Returns the CID of the block.
+    pub async fn pin_keccak_raw_block(&self, content: Vec<u8>) -> Result<String> {
+        let mut url = shared::url::join(&self.api, "api/v0/block/put");
+        url.set_query(Some("cid-codec=raw&mhtype=keccak-256&pin=true"));
+        let form = multipart::Form::new().part("file", multipart::Part::bytes(content));
+        let mut request = self.client.post(url).multipart(form);
+        if let Some(auth) = &self.auth {
+            request = request.bearer_auth(auth);
+        }
+        let response = request.send().await.context("send")?;
+        let status = response.status();
+        let body = response.text().await.context("body")?;
+        anyhow::ensure!(status.is_success(), "status {status}: {body}");
+        let response: BlockPutResponse = serde_json::from_str(&body).context("response")?;
+        Ok(response.key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,4 +140,16 @@ mod tests {
         let result = ipfs.fetch(cid).await.unwrap();
         assert!(result.is_none());
     }
+
+    #[tokio::test]
+    #[ignore]
+    async fn local_node_pinning() {
+        let pinning = IpfsPinning::new(
+            Default::default(),
+            "http://localhost:5001".parse().unwrap(),
+            None,
+        );
+        let cid = pinning.pin_keccak_raw_block(b"{}".to_vec()).await.unwrap();
+        println!("{cid}");
+    }
 }
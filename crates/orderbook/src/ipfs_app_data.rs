@@ -1,6 +1,6 @@
 use {
-    crate::ipfs::Ipfs,
-    anyhow::Result,
+    crate::ipfs::{Ipfs, IpfsPinning},
+    anyhow::{ensure, Result},
     app_data::{create_ipfs_cid, AppDataHash},
     cached::{Cached, TimedSizedCache},
     std::sync::Mutex,
@@ -21,6 +21,10 @@ struct Metrics {
 
     /// Timing of IPFS app data fetches.
     fetches: prometheus::Histogram,
+
+    /// Number of attempts to pin uploaded app data on IPFS.
+    #[metric(labels("outcome"))]
+    pins: prometheus::IntCounterVec,
 }
 
 impl IpfsAppData {
@@ -120,6 +124,49 @@ impl IpfsAppData {
     }
 }
 
+/// Pins uploaded app data documents on IPFS, so that the CID derived from
+/// their app data hash resolves on public gateways.
+pub struct IpfsAppDataPinning {
+    pinning: IpfsPinning,
+    metrics: &'static Metrics,
+}
+
+impl IpfsAppDataPinning {
+    pub fn new(pinning: IpfsPinning) -> Self {
+        let metrics = Metrics::instance(observe::metrics::get_storage_registry()).unwrap();
+        for outcome in &["error", "success"] {
+            metrics.pins.with_label_values(&[outcome]);
+        }
+        Self { pinning, metrics }
+    }
+}
+
+/// Pins app data documents on IPFS.
+#[async_trait::async_trait]
+pub trait AppDataPinning: Send + Sync {
+    /// Pins the document whose keccak-256 hash is the app data hash.
+    async fn pin(&self, app_data: &AppDataHash, document: String) -> Result<()>;
+}
+
+#[async_trait::async_trait]
+impl AppDataPinning for IpfsAppDataPinning {
+    async fn pin(&self, app_data: &AppDataHash, document: String) -> Result<()> {
+        let result = async {
+            let cid = self
+                .pinning
+                .pin_keccak_raw_block(document.into_bytes())
+                .await?;
+            let expected = new_app_data_cid(app_data);
+            ensure!(cid == expected, "pinned CID {cid} instead of {expected}");
+            Ok(())
+        }
+        .await;
+        let outcome = if result.is_ok() { "success" } else { "error" };
+        self.metrics.pins.with_label_values(&[outcome]).inc();
+        result
+    }
+}
+
 fn new_app_data_cid(contract_app_data: &AppDataHash) -> String {
     let raw_cid = create_ipfs_cid(&contract_app_data.0);
     multibase::encode(multibase::Base::Base32Lower, raw_cid)
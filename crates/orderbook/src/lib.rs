@@ -1,10 +1,15 @@
 pub mod api;
 pub mod app_data;
 pub mod arguments;
+pub mod current_auction;
 pub mod database;
 pub mod dto;
+pub mod ethflow;
+pub mod graphql;
 mod ipfs;
 mod ipfs_app_data;
+pub mod notifications;
+pub mod order_events;
 pub mod orderbook;
 mod quoter;
 pub mod run;
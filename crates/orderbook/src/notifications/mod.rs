@@ -0,0 +1,745 @@
+//! Account level notifications about order activity.
+//!
+//! Accounts register where and about what they want to be notified with a
+//! signed message. A background task periodically collects the fills and
+//! expiries of their orders into digests and delivers them through the
+//! registered channels. Registering preferences without channels or events
+//! opts an account out again, as does a webhook responding with
+//! `410 Gone`.
+
+pub mod webhook;
+
+use {
+    crate::database::Postgres,
+    anyhow::{Context, Result},
+    chrono::{DateTime, Utc},
+    database::{byte_array::ByteArray, notification_preferences::Preferences},
+    futures::StreamExt,
+    model::{
+        notification::SignedNotificationPreferences,
+        order::OrderUid,
+        DomainSeparator,
+    },
+    number::{conversions::big_decimal_to_u256, serialization::HexOrDecimalU256},
+    primitive_types::{H160, U256},
+    serde::Serialize,
+    serde_with::serde_as,
+    std::{sync::Arc, time::Duration},
+    url::Url,
+};
+
+/// How long signed preferences can be registered after they were issued.
+const MAX_PREFERENCES_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// How far in the future preferences may be issued to tolerate clock skew.
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// How often an account can change its preferences. Opting out is always
+/// possible.
+const MIN_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often due digests get sent.
+const DIGEST_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How many digests get delivered concurrently.
+const DELIVERY_CONCURRENCY: usize = 10;
+
+/// How long a replica has to send the digests it claimed before other
+/// replicas may send them instead.
+const CLAIM_DURATION: Duration = Duration::from_secs(10 * 60);
+
+const MAX_WEBHOOK_LENGTH: usize = 2048;
+
+const MAX_EMAIL_LENGTH: usize = 254;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// How often an account gets sent a digest at most.
+    pub digest_interval: Duration,
+    /// How many digests get sent per [`DIGEST_POLL_INTERVAL`] at most. The
+    /// limit applies to every orderbook replica on its own.
+    pub max_digests_per_run: usize,
+    /// How many fills and expiries a digest reports at most each.
+    pub max_orders_per_digest: usize,
+}
+
+/// Delivers digests to the recipients registered for it, e.g. a webhook URL
+/// or an email address.
+#[async_trait::async_trait]
+pub trait Channel: Send + Sync {
+    async fn deliver(&self, recipient: &str, digest: &Digest) -> Result<(), DeliveryError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeliveryError {
+    /// The recipient no longer wants to receive digests.
+    #[error("recipient unsubscribed")]
+    Unsubscribed,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Order activity of an account in `(from, to]`. Truncated digests end right
+/// before the first activity that didn't fit so the next digest picks up
+/// from there.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Digest {
+    pub owner: H160,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub fills: Vec<Fill>,
+    pub expiries: Vec<Expiry>,
+    /// Whether there was more activity than a single digest reports.
+    pub truncated: bool,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.fills.is_empty() && self.expiries.is_empty()
+    }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fill {
+    pub uid: OrderUid,
+    pub sell_token: H160,
+    pub buy_token: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub executed_sell_amount: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub executed_buy_amount: U256,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Expiry {
+    pub uid: OrderUid,
+    pub sell_token: H160,
+    pub buy_token: H160,
+    pub valid_to: i64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistrationError {
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("preferences were signed by another account")]
+    WrongOwner,
+    #[error("preferences were issued too long ago or in the future")]
+    InvalidIssuedAt,
+    #[error("newer preferences are already registered")]
+    Outdated,
+    #[error("preferences were updated too recently")]
+    TooManyUpdates,
+    #[error("invalid webhook")]
+    InvalidWebhook,
+    #[error("invalid email address")]
+    InvalidEmail,
+    #[error("email notifications are not supported")]
+    EmailUnsupported,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "notifications")]
+struct Metrics {
+    /// Number of registered notification preferences by result.
+    #[metric(labels("result"))]
+    registrations: prometheus::IntCounterVec,
+
+    /// Number of delivered digests by channel and result.
+    #[metric(labels("channel", "result"))]
+    digests: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+pub struct Notifications {
+    domain_separator: DomainSeparator,
+    database: Postgres,
+    config: Config,
+    webhook: Arc<dyn Channel>,
+    email: Option<Arc<dyn Channel>>,
+}
+
+impl Notifications {
+    pub fn new(
+        domain_separator: DomainSeparator,
+        database: Postgres,
+        config: Config,
+        webhook: Arc<dyn Channel>,
+    ) -> Self {
+        Self {
+            domain_separator,
+            database,
+            config,
+            webhook,
+            email: None,
+        }
+    }
+
+    /// Enables registering email addresses and delivers their digests through
+    /// the channel.
+    pub fn with_email(mut self, email: Arc<dyn Channel>) -> Self {
+        self.email = Some(email);
+        self
+    }
+
+    /// Registers the preferences signed by the owner.
+    pub async fn register(
+        &self,
+        owner: H160,
+        signed: SignedNotificationPreferences,
+    ) -> Result<(), RegistrationError> {
+        let result = self.register_inner(owner, signed).await;
+        let label = match &result {
+            Ok(_) => "success",
+            Err(RegistrationError::Other(_)) => "error",
+            Err(_) => "rejected",
+        };
+        Metrics::get()
+            .registrations
+            .with_label_values(&[label])
+            .inc();
+        result
+    }
+
+    async fn register_inner(
+        &self,
+        owner: H160,
+        signed: SignedNotificationPreferences,
+    ) -> Result<(), RegistrationError> {
+        let signer = signed
+            .validate(&self.domain_separator)
+            .map_err(|_| RegistrationError::InvalidSignature)?;
+        if signer != owner {
+            return Err(RegistrationError::WrongOwner);
+        }
+        let now = Utc::now();
+        let preferences = signed.data;
+        let issued_at = validate_issued_at(preferences.issued_at, now)?;
+        if let Some(webhook) = &preferences.webhook {
+            validate_webhook(webhook).await?;
+        }
+        if let Some(email) = &preferences.email {
+            if self.email.is_none() {
+                return Err(RegistrationError::EmailUnsupported);
+            }
+            validate_email(email)?;
+        }
+
+        if let Some(existing) = self.database.notification_preferences(&owner).await? {
+            if existing.issued_at >= issued_at {
+                return Err(RegistrationError::Outdated);
+            }
+            if !preferences.is_opt_out()
+                && now.signed_duration_since(existing.updated_at)
+                    < chrono_duration(MIN_UPDATE_INTERVAL)
+            {
+                return Err(RegistrationError::TooManyUpdates);
+            }
+        }
+
+        let stored = self
+            .database
+            .upsert_notification_preferences(&Preferences {
+                owner: ByteArray(owner.0),
+                webhook: preferences.webhook,
+                email: preferences.email,
+                fills: preferences.fills,
+                expiries: preferences.expiries,
+                issued_at,
+                updated_at: now,
+                // Digests only report activity after the registration.
+                digested_until: now,
+            })
+            .await?;
+        if !stored {
+            return Err(RegistrationError::Outdated);
+        }
+        Ok(())
+    }
+
+    /// Periodically sends digests to all accounts that are due one.
+    pub async fn run_digests(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(DIGEST_POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.send_due_digests().await {
+                tracing::error!(?err, "failed to send notification digests");
+            }
+        }
+    }
+
+    async fn send_due_digests(&self) -> Result<()> {
+        let now = Utc::now();
+        let due = self
+            .database
+            .claim_due_notification_preferences(
+                now - chrono_duration(self.config.digest_interval),
+                now,
+                now + chrono_duration(CLAIM_DURATION),
+                self.config.max_digests_per_run,
+            )
+            .await?;
+        if due.is_empty() {
+            return Ok(());
+        }
+        tracing::debug!(accounts = due.len(), "sending notification digests");
+        futures::stream::iter(due)
+            .for_each_concurrent(DELIVERY_CONCURRENCY, |preferences| async move {
+                let owner = H160(preferences.owner.0);
+                if let Err(err) = self.send_digest(preferences, now).await {
+                    tracing::warn!(?owner, ?err, "failed to send notification digest");
+                }
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Delivers the activity since the last digest. Failed deliveries don't
+    /// get retried to not flood misbehaving recipients.
+    async fn send_digest(&self, preferences: Preferences, now: DateTime<Utc>) -> Result<()> {
+        let owner = H160(preferences.owner.0);
+        let digest = self.digest(&preferences, now).await?;
+        if !digest.is_empty() {
+            if let Some(webhook) = &preferences.webhook {
+                if self
+                    .deliver("webhook", self.webhook.as_ref(), webhook, &digest)
+                    .await
+                {
+                    self.database
+                        .remove_notification_webhook(&owner, webhook)
+                        .await?;
+                }
+            }
+            if let (Some(email), Some(channel)) = (&preferences.email, &self.email) {
+                if self
+                    .deliver("email", channel.as_ref(), email, &digest)
+                    .await
+                {
+                    self.database
+                        .remove_notification_email(&owner, email)
+                        .await?;
+                }
+            }
+        }
+        self.database
+            .mark_notifications_digested(&owner, digest.to)
+            .await
+    }
+
+    async fn digest(&self, preferences: &Preferences, now: DateTime<Utc>) -> Result<Digest> {
+        let owner = H160(preferences.owner.0);
+        let from = preferences.digested_until;
+        let (mut fills, mut expiries) = self.database.order_activity(&owner, from, now).await?;
+        if !preferences.fills {
+            fills.clear();
+        }
+        if !preferences.expiries {
+            expiries.clear();
+        }
+        let limit = self.config.max_orders_per_digest;
+        let to = digest_end(&fills, &expiries, limit, from, now);
+        let truncated = to < now;
+        fills.retain(|fill| fill.timestamp <= to);
+        expiries.retain(|expiry| expiry.valid_to <= to.timestamp());
+
+        let fills = fills
+            .into_iter()
+            .map(|fill| {
+                Ok(Fill {
+                    uid: OrderUid(fill.order_uid.0),
+                    sell_token: H160(fill.sell_token.0),
+                    buy_token: H160(fill.buy_token.0),
+                    executed_sell_amount: big_decimal_to_u256(&fill.executed_sell_amount)
+                        .context("executed sell amount is not a valid U256")?,
+                    executed_buy_amount: big_decimal_to_u256(&fill.executed_buy_amount)
+                        .context("executed buy amount is not a valid U256")?,
+                    timestamp: fill.timestamp,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let expiries = expiries
+            .into_iter()
+            .map(|expiry| Expiry {
+                uid: OrderUid(expiry.order_uid.0),
+                sell_token: H160(expiry.sell_token.0),
+                buy_token: H160(expiry.buy_token.0),
+                valid_to: expiry.valid_to,
+            })
+            .collect();
+        Ok(Digest {
+            owner,
+            from,
+            to,
+            fills,
+            expiries,
+            truncated,
+        })
+    }
+
+    /// Delivers the digest and returns whether the recipient unsubscribed.
+    async fn deliver(
+        &self,
+        label: &str,
+        channel: &dyn Channel,
+        recipient: &str,
+        digest: &Digest,
+    ) -> bool {
+        let result = channel.deliver(recipient, digest).await;
+        let (result_label, unsubscribed) = match &result {
+            Ok(()) => ("success", false),
+            Err(DeliveryError::Unsubscribed) => ("unsubscribed", true),
+            Err(DeliveryError::Other(err)) => {
+                tracing::debug!(owner = ?digest.owner, channel = label, ?err, "delivery failed");
+                ("failure", false)
+            }
+        };
+        Metrics::get()
+            .digests
+            .with_label_values(&[label, result_label])
+            .inc();
+        unsubscribed
+    }
+}
+
+/// Returns until when a digest of the activity in `(from, now]` reports
+/// everything. Without more than `limit` fills or expiries that's `now`.
+/// Otherwise it's right before the first activity that doesn't fit into the
+/// digest, so neither that nor other activity at the same time gets lost.
+fn digest_end(
+    fills: &[database::notification_preferences::Fill],
+    expiries: &[database::notification_preferences::Expiry],
+    limit: usize,
+    from: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let overflow = [
+        fills.get(limit).map(|fill| fill.timestamp),
+        expiries
+            .get(limit)
+            .and_then(|expiry| DateTime::from_timestamp(expiry.valid_to, 0)),
+    ]
+    .into_iter()
+    .flatten()
+    .min();
+    match overflow {
+        Some(overflow) if overflow - chrono::Duration::microseconds(1) > from => {
+            overflow - chrono::Duration::microseconds(1)
+        }
+        // More activity at the same time than fits into a digest. Skip the
+        // excess so digests keep making progress.
+        Some(overflow) => overflow,
+        None => now,
+    }
+}
+
+fn chrono_duration(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).expect("duration out of range")
+}
+
+fn validate_issued_at(
+    issued_at: u32,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, RegistrationError> {
+    let issued_at =
+        DateTime::from_timestamp(issued_at.into(), 0).ok_or(RegistrationError::InvalidIssuedAt)?;
+    if issued_at < now - chrono_duration(MAX_PREFERENCES_AGE)
+        || issued_at > now + chrono_duration(MAX_CLOCK_SKEW)
+    {
+        return Err(RegistrationError::InvalidIssuedAt);
+    }
+    Ok(issued_at)
+}
+
+/// Only HTTPS webhooks are supported so digests don't leak in transit. They
+/// must only resolve to public addresses so they can't be used to reach into
+/// the network the orderbook runs in.
+async fn validate_webhook(webhook: &str) -> Result<(), RegistrationError> {
+    let url = Url::parse(webhook).map_err(|_| RegistrationError::InvalidWebhook)?;
+    if webhook.len() > MAX_WEBHOOK_LENGTH || url.scheme() != "https" || url.host().is_none() {
+        return Err(RegistrationError::InvalidWebhook);
+    }
+    let ips = webhook::resolve(&url)
+        .await
+        .map_err(|_| RegistrationError::InvalidWebhook)?;
+    if !webhook::all_public(&ips) {
+        return Err(RegistrationError::InvalidWebhook);
+    }
+    Ok(())
+}
+
+/// Performs basic sanity checks only. Whether the address actually exists is
+/// up to the email channel.
+fn validate_email(email: &str) -> Result<(), RegistrationError> {
+    let valid = email.len() <= MAX_EMAIL_LENGTH
+        && !email.contains(char::is_whitespace)
+        && matches!(
+            email.split_once('@'),
+            Some((local, domain)) if !local.is_empty() && domain.contains('.') && !domain.contains('@')
+        );
+    if !valid {
+        return Err(RegistrationError::InvalidEmail);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ethcontract::web3::signing::{Key, SecretKeyRef},
+        model::{notification::NotificationPreferences, signature::EcdsaSigningScheme},
+        secp256k1::SecretKey,
+    };
+
+    struct NoChannel;
+
+    #[async_trait::async_trait]
+    impl Channel for NoChannel {
+        async fn deliver(&self, _: &str, _: &Digest) -> Result<(), DeliveryError> {
+            Ok(())
+        }
+    }
+
+    fn notifications() -> Notifications {
+        Notifications::new(
+            DomainSeparator([1; 32]),
+            Postgres::try_new("postgresql://").unwrap(),
+            Config {
+                digest_interval: Duration::from_secs(3600),
+                max_digests_per_run: 10,
+                max_orders_per_digest: 10,
+            },
+            Arc::new(NoChannel),
+        )
+    }
+
+    fn preferences(issued_at: DateTime<Utc>) -> NotificationPreferences {
+        NotificationPreferences {
+            webhook: Some("https://1.1.1.1/hooks/cow".to_string()),
+            email: None,
+            fills: true,
+            expiries: true,
+            issued_at: issued_at.timestamp().try_into().unwrap(),
+        }
+    }
+
+    fn sign(
+        preferences: NotificationPreferences,
+        key: &SecretKey,
+    ) -> SignedNotificationPreferences {
+        preferences.sign(
+            EcdsaSigningScheme::Eip712,
+            &DomainSeparator([1; 32]),
+            SecretKeyRef::new(key),
+        )
+    }
+
+    #[tokio::test]
+    async fn register_rejects_invalid_preferences() {
+        let notifications = notifications();
+        let key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let owner = SecretKeyRef::new(&key).address();
+        let now = Utc::now();
+
+        let signed = sign(preferences(now), &key);
+        assert!(matches!(
+            notifications.register(H160([3; 20]), signed).await,
+            Err(RegistrationError::WrongOwner)
+        ));
+
+        let mut tampered = sign(preferences(now), &key);
+        tampered.data.expiries = false;
+        assert!(matches!(
+            notifications.register(owner, tampered).await,
+            Err(RegistrationError::WrongOwner)
+        ));
+
+        let signed = sign(preferences(now - chrono::Duration::hours(1)), &key);
+        assert!(matches!(
+            notifications.register(owner, signed).await,
+            Err(RegistrationError::InvalidIssuedAt)
+        ));
+
+        let signed = sign(
+            NotificationPreferences {
+                webhook: Some("https://169.254.169.254/latest/meta-data".to_string()),
+                ..preferences(now)
+            },
+            &key,
+        );
+        assert!(matches!(
+            notifications.register(owner, signed).await,
+            Err(RegistrationError::InvalidWebhook)
+        ));
+
+        let signed = sign(
+            NotificationPreferences {
+                email: Some("user@example.com".to_string()),
+                ..preferences(now)
+            },
+            &key,
+        );
+        assert!(matches!(
+            notifications.register(owner, signed).await,
+            Err(RegistrationError::EmailUnsupported)
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_register() {
+        let notifications = notifications();
+        database::clear_DANGER(&notifications.database.pool)
+            .await
+            .unwrap();
+        let key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let owner = SecretKeyRef::new(&key).address();
+        let now = Utc::now();
+
+        let signed = sign(preferences(now - chrono::Duration::seconds(2)), &key);
+        notifications.register(owner, signed.clone()).await.unwrap();
+        let stored = notifications
+            .database
+            .notification_preferences(&owner)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.webhook, signed.data.webhook);
+
+        // Replaying the same preferences doesn't do anything.
+        assert!(matches!(
+            notifications.register(owner, signed).await,
+            Err(RegistrationError::Outdated)
+        ));
+
+        // Updates are rate limited...
+        let update = NotificationPreferences {
+            expiries: false,
+            ..preferences(now - chrono::Duration::seconds(1))
+        };
+        assert!(matches!(
+            notifications.register(owner, sign(update, &key)).await,
+            Err(RegistrationError::TooManyUpdates)
+        ));
+
+        // ...but opting out is always possible.
+        let opt_out = NotificationPreferences {
+            webhook: None,
+            email: None,
+            ..preferences(now)
+        };
+        notifications
+            .register(owner, sign(opt_out, &key))
+            .await
+            .unwrap();
+        let stored = notifications
+            .database
+            .notification_preferences(&owner)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.webhook, None);
+    }
+
+    #[test]
+    fn validates_issued_at() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert!(validate_issued_at(1_700_000_000 - 60, now).is_ok());
+        assert!(validate_issued_at(1_700_000_000 + 30, now).is_ok());
+        assert!(matches!(
+            validate_issued_at(1_700_000_000 - 3600, now),
+            Err(RegistrationError::InvalidIssuedAt)
+        ));
+        assert!(matches!(
+            validate_issued_at(1_700_000_000 + 3600, now),
+            Err(RegistrationError::InvalidIssuedAt)
+        ));
+    }
+
+    #[tokio::test]
+    async fn validates_webhooks() {
+        assert!(validate_webhook("https://1.1.1.1/hooks/cow").await.is_ok());
+        for webhook in [
+            "http://example.com",
+            "example.com",
+            "https://",
+            "ftp://example.com",
+            "https://localhost/hooks",
+            "https://127.0.0.1/hooks",
+            "https://10.0.0.1/hooks",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/hooks",
+            "https://[fd00::1]/hooks",
+            "https://unresolvable.invalid/hooks",
+        ] {
+            assert!(validate_webhook(webhook).await.is_err(), "{webhook}");
+        }
+    }
+
+    #[test]
+    fn truncated_digests_end_before_first_excess_activity() {
+        let at = |seconds: i64| DateTime::from_timestamp(seconds, 0).unwrap();
+        let fill = |timestamp: DateTime<Utc>| database::notification_preferences::Fill {
+            order_uid: Default::default(),
+            sell_token: Default::default(),
+            buy_token: Default::default(),
+            executed_sell_amount: Default::default(),
+            executed_buy_amount: Default::default(),
+            timestamp,
+        };
+        let expiry = |valid_to: i64| database::notification_preferences::Expiry {
+            order_uid: Default::default(),
+            sell_token: Default::default(),
+            buy_token: Default::default(),
+            valid_to,
+        };
+        let microsecond = chrono::Duration::microseconds(1);
+        let (from, now) = (at(100), at(200));
+        let fills = [fill(at(110)), fill(at(120)), fill(at(120)), fill(at(130))];
+        let expiries = [expiry(105), expiry(115)];
+
+        // Everything fits.
+        assert_eq!(digest_end(&fills, &expiries, 4, from, now), now);
+        // The fill at 120 that doesn't fit and the one at the same time that
+        // does are both left to the next digest.
+        assert_eq!(
+            digest_end(&fills, &expiries, 2, from, now),
+            at(120) - microsecond
+        );
+        // Expiries overflow first.
+        assert_eq!(
+            digest_end(&fills, &expiries, 1, from, now),
+            at(115) - microsecond
+        );
+        // More activity at a single point in time than fits into a digest.
+        let fills = [fill(from + microsecond), fill(from + microsecond)];
+        assert_eq!(digest_end(&fills, &[], 1, from, now), from + microsecond);
+    }
+
+    #[test]
+    fn validates_emails() {
+        assert!(validate_email("user@example.com").is_ok());
+        for email in [
+            "user",
+            "@example.com",
+            "user@localhost",
+            "us er@example.com",
+            "a@b@c.com",
+        ] {
+            assert!(validate_email(email).is_err(), "{email}");
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use {
+    super::{Channel, DeliveryError, Digest},
+    anyhow::anyhow,
+    reqwest::{
+        dns::{Addrs, Name, Resolve, Resolving},
+        redirect,
+        Client,
+        ClientBuilder,
+        StatusCode,
+    },
+    std::{
+        io,
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+        sync::Arc,
+    },
+    url::{Host, Url},
+};
+
+/// Posts digests as JSON to the registered URL. Webhooks can unsubscribe by
+/// responding with `410 Gone`.
+pub struct Webhook {
+    client: Client,
+}
+
+impl Webhook {
+    pub fn new(builder: ClientBuilder) -> Self {
+        Self {
+            client: builder
+                // Redirects could point digests to hosts the owner didn't
+                // register.
+                .redirect(redirect::Policy::none())
+                // The registered host could have been changed to resolve to
+                // internal addresses since it got validated.
+                .dns_resolver(Arc::new(PublicResolver))
+                .build()
+                .unwrap(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Channel for Webhook {
+    async fn deliver(&self, recipient: &str, digest: &Digest) -> Result<(), DeliveryError> {
+        // Hosts that are IP addresses don't go through the resolver.
+        let url = Url::parse(recipient).map_err(anyhow::Error::from)?;
+        if !resolve(&url).await.is_ok_and(|ips| all_public(&ips)) {
+            return Err(anyhow!("webhook does not point to a public address").into());
+        }
+        let response = self
+            .client
+            .post(url)
+            .json(digest)
+            .send()
+            .await
+            .map_err(anyhow::Error::from)?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::GONE => Err(DeliveryError::Unsubscribed),
+            status => Err(anyhow!("webhook responded with {status}").into()),
+        }
+    }
+}
+
+/// Resolves the addresses the host of the URL points to.
+pub async fn resolve(url: &Url) -> io::Result<Vec<IpAddr>> {
+    match url.host() {
+        Some(Host::Ipv4(ip)) => Ok(vec![ip.into()]),
+        Some(Host::Ipv6(ip)) => Ok(vec![ip.into()]),
+        Some(Host::Domain(domain)) => Ok(tokio::net::lookup_host((domain, 0))
+            .await?
+            .map(|address| address.ip())
+            .collect()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Whether there are addresses and all of them are reachable from the public
+/// internet.
+pub fn all_public(ips: &[IpAddr]) -> bool {
+    !ips.is_empty() && ips.iter().copied().all(is_public)
+}
+
+/// Whether the address is reachable from the public internet. Webhooks must
+/// not reach into the network the orderbook runs in.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    let this_network = a == 0;
+    let shared = a == 100 && (b & 0b1100_0000) == 64;
+    !(this_network
+        || shared
+        || ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation())
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    let unique_local = (first & 0xfe00) == 0xfc00;
+    let link_local = (first & 0xffc0) == 0xfe80;
+    !(unique_local || link_local || ip.is_loopback() || ip.is_unspecified() || ip.is_multicast())
+}
+
+/// Only resolves hosts to public addresses.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses: Vec<_> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| is_public(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                let err = format!("{} has no public address", name.as_str());
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, err).into());
+            }
+            let addresses: Addrs = Box::new(addresses.into_iter());
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(addresses)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_public_addresses() {
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "0.1.2.3",
+            "10.0.0.1",
+            "100.64.0.1",
+            "127.0.0.1",
+            "169.254.169.254",
+            "172.16.0.1",
+            "192.168.1.1",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "::ffff:127.0.0.1",
+            "fc00::1",
+            "fd12::1",
+            "fe80::1",
+            "ff02::1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! Live stream of order lifecycle events.
+//!
+//! A background task listens to the database announcing new rows of the
+//! `order_events` table once they got committed and broadcasts them to the
+//! clients connected to `/api/v1/events`. Every client only receives the
+//! events of the owners and orders it subscribed to. Delivery is best effort:
+//! events that happen while a client is disconnected or too slow to keep up,
+//! or while the orderbook lost its connection to the database, are lost, so
+//! clients should check the status of their orders after reconnecting or
+//! falling behind.
+
+use {
+    crate::database::Postgres,
+    anyhow::Result,
+    chrono::{DateTime, Utc},
+    database::order_events::{self, OrderEvent, OrderEventLabel},
+    futures::Stream,
+    model::order::OrderUid,
+    primitive_types::H160,
+    serde::Serialize,
+    sqlx::postgres::PgListener,
+    std::{collections::HashSet, sync::Arc, time::Duration},
+    tokio::sync::broadcast::{self, error::RecvError},
+};
+
+/// How long to wait before listening again after the listener failed.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the number of subscribers gets reported.
+const METRICS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How many events a subscriber can fall behind before it misses some.
+const CHANNEL_CAPACITY: usize = 10_000;
+
+/// How many clients can be subscribed at the same time.
+const MAX_SUBSCRIBERS: usize = 1_000;
+
+/// How many owners and orders a single client can subscribe to in total.
+pub const MAX_SUBSCRIPTION_SIZE: usize = 100;
+
+/// Kinds of order events that get published. Intermediate states of the
+/// auction like an order being considered by solvers are too frequent to be
+/// useful to clients.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventKind {
+    /// The order was added to the orderbook.
+    Created,
+    /// The order was cancelled by its owner.
+    Cancelled,
+    /// The order is part of the winning settlement which is being submitted.
+    Executing,
+    /// The order was settled on-chain.
+    Traded,
+}
+
+impl EventKind {
+    fn from_label(label: OrderEventLabel) -> Option<Self> {
+        match label {
+            OrderEventLabel::Created => Some(Self::Created),
+            OrderEventLabel::Cancelled => Some(Self::Cancelled),
+            OrderEventLabel::Executing => Some(Self::Executing),
+            OrderEventLabel::Traded => Some(Self::Traded),
+            OrderEventLabel::Ready
+            | OrderEventLabel::Filtered
+            | OrderEventLabel::Invalid
+            | OrderEventLabel::Considered
+            | OrderEventLabel::Unsupported => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Cancelled => "cancelled",
+            Self::Executing => "executing",
+            Self::Traded => "traded",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub kind: EventKind,
+    pub order_uid: OrderUid,
+    pub owner: H160,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Event {
+    fn from_db(event: &OrderEvent) -> Option<Self> {
+        let order_uid = OrderUid(event.order_uid.0);
+        let (_, owner, _) = order_uid.parts();
+        Some(Self {
+            kind: EventKind::from_label(event.label)?,
+            order_uid,
+            owner,
+            timestamp: event.timestamp,
+        })
+    }
+}
+
+/// The owners and orders a client wants to receive events of.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Subscription {
+    pub owners: HashSet<H160>,
+    pub order_uids: HashSet<OrderUid>,
+}
+
+impl Subscription {
+    pub fn len(&self) -> usize {
+        self.owners.len() + self.order_uids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        self.owners.contains(&event.owner) || self.order_uids.contains(&event.order_uid)
+    }
+}
+
+/// What a subscriber receives.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Update {
+    Event(Arc<Event>),
+    /// The subscriber fell behind and missed this many events, which may or
+    /// may not have matched its subscription.
+    Lagged(u64),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubscribeError {
+    #[error("too many subscribers")]
+    TooManySubscribers,
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+#[metric(subsystem = "order_events_stream")]
+struct Metrics {
+    /// Number of clients subscribed to order events.
+    subscribers: prometheus::IntGauge,
+
+    /// Number of published order events by kind.
+    #[metric(labels("kind"))]
+    published: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+pub struct OrderEvents {
+    database: Postgres,
+    sender: broadcast::Sender<Arc<Event>>,
+}
+
+impl OrderEvents {
+    pub fn new(database: Postgres) -> Self {
+        Self {
+            database,
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Streams the events matching the subscription that get published from
+    /// now on.
+    pub fn subscribe(
+        &self,
+        subscription: Subscription,
+    ) -> Result<impl Stream<Item = Update> + Send + 'static, SubscribeError> {
+        if self.sender.receiver_count() >= MAX_SUBSCRIBERS {
+            return Err(SubscribeError::TooManySubscribers);
+        }
+        let receiver = self.sender.subscribe();
+        Ok(futures::stream::unfold(
+            (receiver, subscription),
+            |(mut receiver, subscription)| async move {
+                let update = loop {
+                    match receiver.recv().await {
+                        Ok(event) if subscription.matches(&event) => break Update::Event(event),
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(missed)) => break Update::Lagged(missed),
+                        Err(RecvError::Closed) => return None,
+                    }
+                };
+                Some((update, (receiver, subscription)))
+            },
+        ))
+    }
+
+    /// Publishes the events announced by the database.
+    pub async fn run_forever(self: Arc<Self>) {
+        loop {
+            if let Err(err) = self.listen().await {
+                tracing::warn!(?err, "failed to listen to order events");
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    }
+
+    async fn listen(&self) -> Result<()> {
+        let mut listener = PgListener::connect_with(&self.database.pool).await?;
+        listener.listen(order_events::NOTIFICATION_CHANNEL).await?;
+        let mut interval = tokio::time::interval(METRICS_INTERVAL);
+        loop {
+            tokio::select! {
+                notification = listener.try_recv() => match notification? {
+                    Some(notification) => {
+                        let event = order_events::parse_notification(notification.payload());
+                        match event.as_ref().and_then(Event::from_db) {
+                            Some(event) => self.publish(event),
+                            None => tracing::warn!(
+                                payload = notification.payload(),
+                                "unexpected order event notification"
+                            ),
+                        }
+                    }
+                    // The listener reconnects with the next call.
+                    None => tracing::warn!("lost connection, order events may have been missed"),
+                },
+                _ = interval.tick() => {
+                    let subscribers = self.sender.receiver_count();
+                    Metrics::get()
+                        .subscribers
+                        .set(subscribers.try_into().unwrap_or(i64::MAX));
+                }
+            }
+        }
+    }
+
+    fn publish(&self, event: Event) {
+        Metrics::get()
+            .published
+            .with_label_values(&[event.kind.as_str()])
+            .inc();
+        // Sending only fails if nobody is subscribed.
+        let _ = self.sender.send(Arc::new(event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, database::byte_array::ByteArray, futures::StreamExt};
+
+    fn event(kind: EventKind, order_uid: OrderUid) -> Event {
+        Event {
+            kind,
+            order_uid,
+            owner: order_uid.parts().1,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn converts_db_events() {
+        let order_uid = OrderUid::from_parts(Default::default(), H160([1; 20]), 0);
+        let db_event = |label| OrderEvent {
+            order_uid: ByteArray(order_uid.0),
+            timestamp: Default::default(),
+            label,
+        };
+        let event = Event::from_db(&db_event(OrderEventLabel::Traded)).unwrap();
+        assert_eq!(event.kind, EventKind::Traded);
+        assert_eq!(event.order_uid, order_uid);
+        assert_eq!(event.owner, H160([1; 20]));
+        assert_eq!(Event::from_db(&db_event(OrderEventLabel::Ready)), None);
+    }
+
+    #[tokio::test]
+    async fn streams_subscribed_events() {
+        let events = OrderEvents::new(Postgres::try_new("postgresql://").unwrap());
+        let owner = H160([1; 20]);
+        let owned = OrderUid::from_parts(Default::default(), owner, 0);
+        let subscribed = OrderUid([2; 56]);
+        let other = OrderUid([3; 56]);
+        let stream = events
+            .subscribe(Subscription {
+                owners: [owner].into(),
+                order_uids: [subscribed].into(),
+            })
+            .unwrap();
+        futures::pin_mut!(stream);
+
+        events.publish(event(EventKind::Created, owned));
+        events.publish(event(EventKind::Created, other));
+        events.publish(event(EventKind::Traded, subscribed));
+        let Update::Event(first) = stream.next().await.unwrap() else {
+            panic!("expected event");
+        };
+        assert_eq!(first.order_uid, owned);
+        let Update::Event(second) = stream.next().await.unwrap() else {
+            panic!("expected event");
+        };
+        assert_eq!(second.order_uid, subscribed);
+        assert_eq!(second.kind, EventKind::Traded);
+
+        // Slow subscribers get told how many events they missed. The channel
+        // rounds its capacity up to a power of two.
+        for _ in 0..CHANNEL_CAPACITY.next_power_of_two() + 1 {
+            events.publish(event(EventKind::Created, other));
+        }
+        assert_eq!(stream.next().await, Some(Update::Lagged(1)));
+    }
+}
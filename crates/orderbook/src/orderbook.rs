@@ -12,6 +12,7 @@ use {
     chrono::Utc,
     database::order_events::OrderEventLabel,
     ethcontract::H256,
+    futures::StreamExt,
     model::{
         order::{
             Order,
@@ -29,7 +30,9 @@ use {
     number::conversions::big_decimal_to_u256,
     observe::metrics::LivenessChecking,
     primitive_types::H160,
+    rate_limit::Quota,
     shared::{
+        feature_flags::{FeatureFlags, Flag},
         fee::FeeParameters,
         order_quoting::Quote,
         order_validation::{
@@ -39,11 +42,14 @@ use {
             ValidationError,
         },
     },
-    std::{borrow::Cow, sync::Arc},
+    std::{borrow::Cow, collections::HashMap, num::NonZeroUsize, sync::Arc, time::Duration},
     strum_macros::Display,
     thiserror::Error,
 };
 
+/// How many orders of a batch get validated concurrently.
+const BATCH_VALIDATION_CONCURRENCY: usize = 10;
+
 #[derive(prometheus_metric_storage::MetricStorage, Clone, Debug)]
 #[metric(subsystem = "orderbook")]
 struct Metrics {
@@ -143,9 +149,27 @@ pub enum AddOrderError {
     },
     #[error("quote metadata failed to serialize as json, error: {0}")]
     MetadataSerializationFailed(serde_json::Error),
+    #[error("order embargoes are not enabled")]
+    EmbargoNotSupported,
+    #[error("orders replacing other orders cannot be placed in batches")]
+    ReplacementInBatch,
+    #[error("the app data of the new order does not reference the replaced order")]
+    ReplacedOrderMismatch,
+    #[error("owner exceeded its rate limit, retry after {0:?}")]
+    RateLimited(Duration),
 }
 
 impl AddOrderError {
+    /// How long the client has to wait before retrying if the request was
+    /// rate limited.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited(retry_after) => Some(*retry_after),
+            Self::OrderNotFound(err) => err.retry_after(),
+            _ => None,
+        }
+    }
+
     fn from_insertion(err: InsertionError, order: &Order) -> Self {
         match err {
             InsertionError::DuplicatedRecord => AddOrderError::DuplicatedOrder,
@@ -195,16 +219,42 @@ pub enum OrderCancellationError {
     OrderExpired,
     #[error("on-chain orders cannot be cancelled with off-chain signature")]
     OnChainOrder,
+    #[error("owner exceeded its rate limit, retry after {0:?}")]
+    RateLimited(Duration),
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+impl OrderCancellationError {
+    /// How long the client has to wait before retrying if the request was
+    /// rate limited.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited(retry_after) => Some(*retry_after),
+            _ => None,
+        }
+    }
+}
+
+/// Kill switch for order embargoes. Orders asking for an embargo get rejected
+/// while it is disabled, even if embargoes are configured.
+const ORDER_EMBARGOES: Flag<bool> = Flag::new("order_embargoes", true);
+
 pub struct Orderbook {
     domain_separator: DomainSeparator,
     settlement_contract: H160,
     database: crate::database::Postgres,
     order_validator: Arc<dyn OrderValidating>,
     app_data: Arc<crate::app_data::Registry>,
+    /// Longest embargo orders can be submitted with. Embargoes are disabled
+    /// if not set.
+    embargo_max_duration: Option<Duration>,
+    /// Most orders that can be placed in a single batch. Batches are
+    /// disabled if not set.
+    max_orders_per_batch: Option<NonZeroUsize>,
+    /// Limits how many orders every owner can place, replace and cancel.
+    owner_quota: Option<Quota<H160>>,
+    feature_flags: Option<Arc<FeatureFlags>>,
 }
 
 impl Orderbook {
@@ -223,6 +273,58 @@ impl Orderbook {
             database,
             order_validator,
             app_data,
+            embargo_max_duration: None,
+            max_orders_per_batch: None,
+            owner_quota: None,
+            feature_flags: None,
+        }
+    }
+
+    /// Allows submitting orders that stay hidden from the public order APIs
+    /// until the next auction gets cut, but at most for `max_duration`.
+    /// Embargoes can be disabled at runtime with the `order_embargoes` feature
+    /// flag.
+    pub fn with_order_embargo(mut self, max_duration: Duration) -> Self {
+        self.embargo_max_duration = Some(max_duration);
+        self
+    }
+
+    /// Allows placing up to `max_orders` orders with a single request.
+    pub fn with_order_batches(mut self, max_orders: NonZeroUsize) -> Self {
+        self.max_orders_per_batch = Some(max_orders);
+        self
+    }
+
+    pub fn max_orders_per_batch(&self) -> Option<NonZeroUsize> {
+        self.max_orders_per_batch
+    }
+
+    /// Limits how many orders every owner can place, replace and cancel.
+    /// Requests only count against the quota of an owner once their
+    /// signature was verified, so nobody can exhaust the quota of others.
+    pub fn with_owner_rate_limit(mut self, quota: Quota<H160>) -> Self {
+        self.owner_quota = Some(quota);
+        self
+    }
+
+    /// Evaluates feature flags stored in the database, which otherwise
+    /// evaluate to their defaults.
+    pub fn with_feature_flags(mut self, feature_flags: Arc<FeatureFlags>) -> Self {
+        self.feature_flags = Some(feature_flags);
+        self
+    }
+
+    async fn enabled(&self, flag: &Flag<bool>) -> bool {
+        match &self.feature_flags {
+            Some(flags) => flags.enabled(flag).await,
+            None => flag.default,
+        }
+    }
+
+    fn check_owner_quota(&self, owner: H160) -> Result<(), Duration> {
+        match &self.owner_quota {
+            Some(quota) => quota.try_acquire(owner),
+            None => Ok(()),
         }
     }
 
@@ -230,24 +332,7 @@ impl Orderbook {
         &self,
         payload: OrderCreation,
     ) -> Result<(OrderUid, Option<QuoteId>), AddOrderError> {
-        let full_app_data_override = match payload.app_data {
-            OrderCreationAppData::Hash { hash } => self.app_data.find(&hash).await?,
-            _ => None,
-        };
-
-        let replaced_order = self
-            .get_replaced_order(&payload, full_app_data_override.as_deref())
-            .await?;
-
-        let (order, quote) = self
-            .order_validator
-            .validate_and_construct_order(
-                payload,
-                &self.domain_separator,
-                self.settlement_contract,
-                full_app_data_override,
-            )
-            .await?;
+        let (order, quote, replaced_order) = self.validate_order(payload).await?;
 
         // Check if it has to replace an existing order
         if let Some(old_order) = replaced_order {
@@ -269,6 +354,156 @@ impl Orderbook {
         }
     }
 
+    /// Replaces the order with `old_order_uid` by the new order, which has to
+    /// reference it as the replaced order in its app data.
+    pub async fn replace_order_by_uid(
+        &self,
+        old_order_uid: &OrderUid,
+        payload: OrderCreation,
+    ) -> Result<(OrderUid, Option<QuoteId>), AddOrderError> {
+        let (order, quote, replaced_order) = self.validate_order(payload).await?;
+        match replaced_order {
+            Some(old_order) if old_order.order.metadata.uid == *old_order_uid => {
+                self.replace_order(order, old_order, quote).await
+            }
+            _ => Err(AddOrderError::ReplacedOrderMismatch),
+        }
+    }
+
+    /// Adds multiple orders at once. Every order gets validated and inserted
+    /// on its own, so the result of every order is returned in the same
+    /// order as the payloads. Orders replacing other orders are not supported
+    /// in batches.
+    ///
+    /// Limit orders of the same owner are counted against the owner's limit
+    /// as of before the batch.
+    pub async fn add_orders(
+        &self,
+        payloads: Vec<OrderCreation>,
+    ) -> Vec<Result<(OrderUid, Option<QuoteId>), AddOrderError>> {
+        let mut validated: Vec<_> = futures::stream::iter(payloads)
+            .map(|payload| async move {
+                match self.validate_order(payload).await? {
+                    (_, _, Some(_)) => Err(AddOrderError::ReplacementInBatch),
+                    (order, quote, None) => Ok((order, quote)),
+                }
+            })
+            .buffered(BATCH_VALIDATION_CONCURRENCY)
+            .collect()
+            .await;
+
+        // The orders got validated concurrently, so the limit order check
+        // couldn't account for the limit orders of the same owner that come
+        // earlier in the batch.
+        let mut limit_orders = HashMap::<H160, u64>::new();
+        for result in &mut validated {
+            let Ok((order, Some(quote))) = result else {
+                continue;
+            };
+            let outside_market_price = is_order_outside_market_price(
+                &Amounts {
+                    sell: order.data.sell_amount,
+                    buy: order.data.buy_amount,
+                    fee: order.data.fee_amount,
+                },
+                &Amounts {
+                    sell: quote.sell_amount,
+                    buy: quote.buy_amount,
+                    fee: quote.fee_amount,
+                },
+                order.data.kind,
+            );
+            if order.metadata.class != model::order::OrderClass::Limit || !outside_market_price {
+                continue;
+            }
+            let pending = limit_orders.entry(order.metadata.owner).or_default();
+            if *pending > 0 {
+                if let Err(err) = self
+                    .order_validator
+                    .check_max_limit_orders(order.metadata.owner, *pending)
+                    .await
+                {
+                    *result = Err(err.into());
+                    continue;
+                }
+            }
+            *pending += 1;
+        }
+
+        let valid: Vec<_> = validated
+            .iter()
+            .filter_map(|result| result.as_ref().ok().cloned())
+            .collect();
+        let mut insertions = match self.database.insert_orders(&valid).await {
+            Ok(insertions) => Some(insertions.into_iter()),
+            Err(err) => {
+                tracing::warn!(?err, "failed to insert order batch");
+                None
+            }
+        };
+
+        validated
+            .into_iter()
+            .map(|result| -> Result<_, AddOrderError> {
+                let (order, quote) = result?;
+                let insertion = insertions
+                    .as_mut()
+                    .context("failed to insert order batch")?
+                    .next()
+                    .expect("one insertion result per valid order");
+                insertion.map_err(|err| AddOrderError::from_insertion(err, &order))?;
+                let quote_id = quote.as_ref().and_then(|quote| quote.id);
+                let order_uid = order.metadata.uid;
+                Metrics::on_order_operation(
+                    &OrderWithQuote::try_new(order, quote)?,
+                    OrderOperation::Created,
+                );
+                Ok((order_uid, quote_id))
+            })
+            .collect()
+    }
+
+    /// Validates the order and constructs it together with its quote and the
+    /// order it replaces, if any.
+    async fn validate_order(
+        &self,
+        payload: OrderCreation,
+    ) -> Result<(Order, Option<Quote>, Option<OrderWithQuote>), AddOrderError> {
+        let embargo_until = match (payload.embargo, self.embargo_max_duration) {
+            (false, _) => None,
+            (true, Some(max_duration)) if self.enabled(&ORDER_EMBARGOES).await => Some(
+                Utc::now()
+                    + chrono::Duration::from_std(max_duration)
+                        .context("invalid embargo duration")?,
+            ),
+            (true, _) => return Err(AddOrderError::EmbargoNotSupported),
+        };
+
+        let full_app_data_override = match payload.app_data {
+            OrderCreationAppData::Hash { hash } => self.app_data.find(&hash).await?,
+            _ => None,
+        };
+
+        let replaced_order = self
+            .get_replaced_order(&payload, full_app_data_override.as_deref())
+            .await?;
+
+        let (mut order, quote) = self
+            .order_validator
+            .validate_and_construct_order(
+                payload,
+                &self.domain_separator,
+                self.settlement_contract,
+                full_app_data_override,
+            )
+            .await?;
+        self.check_owner_quota(order.metadata.owner)
+            .map_err(AddOrderError::RateLimited)?;
+        order.metadata.embargo_until = embargo_until;
+
+        Ok((order, quote, replaced_order))
+    }
+
     /// Finds an order for cancellation.
     ///
     /// Returns an error if the order cannot be found or cannot be cancelled.
@@ -315,6 +550,8 @@ impl Orderbook {
         {
             return Err(OrderCancellationError::WrongOwner);
         };
+        self.check_owner_quota(signer)
+            .map_err(OrderCancellationError::RateLimited)?;
 
         // orders are already known to exist in DB at this point, and signer is
         // known to be correct!
@@ -345,6 +582,8 @@ impl Orderbook {
         if signer != order.order.metadata.owner {
             return Err(OrderCancellationError::WrongOwner);
         };
+        self.check_owner_quota(signer)
+            .map_err(OrderCancellationError::RateLimited)?;
 
         // order is already known to exist in DB at this point, and signer is
         // known to be correct!
@@ -449,11 +688,11 @@ impl Orderbook {
     pub async fn get_user_orders(
         &self,
         owner: &H160,
-        offset: u64,
+        after: Option<&OrderUid>,
         limit: u64,
     ) -> Result<Vec<Order>> {
         self.database
-            .user_orders(owner, offset, Some(limit))
+            .user_orders(owner, after, Some(limit))
             .await
             .context("get_user_orders error")
     }
@@ -528,6 +767,7 @@ impl Orderbook {
             OrderEventLabel::Cancelled => dto::order::Status::Cancelled,
             OrderEventLabel::Filtered => dto::order::Status::Open,
             OrderEventLabel::Invalid => dto::order::Status::Open,
+            OrderEventLabel::Unsupported => dto::order::Status::Unsupported,
         };
         Ok(Some(status))
     }
@@ -614,6 +854,10 @@ mod tests {
             domain_separator: Default::default(),
             settlement_contract: H160([0xba; 20]),
             app_data,
+            embargo_max_duration: None,
+            max_orders_per_batch: None,
+            owner_quota: None,
+            feature_flags: None,
         };
 
         // Different owner
@@ -689,4 +933,77 @@ mod tests {
             .unwrap();
         assert_eq!(order_id, new_order_uid,);
     }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_batch_counts_earlier_limit_orders_of_owner() {
+        let owner = H160([1; 20]);
+        let mut order_validator = MockOrderValidating::new();
+        order_validator
+            .expect_validate_and_construct_order()
+            .returning(move |creation, _, _, _| {
+                Ok((
+                    Order {
+                        metadata: OrderMetadata {
+                            owner,
+                            uid: OrderUid::from_integer(creation.valid_to),
+                            class: model::order::OrderClass::Limit,
+                            ..Default::default()
+                        },
+                        data: creation.data(),
+                        signature: creation.signature,
+                        ..Default::default()
+                    },
+                    Some(Quote {
+                        sell_amount: 100.into(),
+                        buy_amount: 100.into(),
+                        ..Default::default()
+                    }),
+                ))
+            });
+        // The owner doesn't have any limit orders yet but may only have one.
+        order_validator
+            .expect_check_max_limit_orders()
+            .returning(|_, pending| match pending {
+                0 => Ok(()),
+                _ => Err(ValidationError::TooManyLimitOrders),
+            });
+
+        let database = crate::database::Postgres::try_new("postgresql://").unwrap();
+        database::clear_DANGER(&database.pool).await.unwrap();
+        let app_data = Arc::new(crate::app_data::Registry::new(
+            Validator::new(8192),
+            database.clone(),
+            None,
+        ));
+        let orderbook = Orderbook {
+            database,
+            order_validator: Arc::new(order_validator),
+            domain_separator: Default::default(),
+            settlement_contract: H160([0xba; 20]),
+            app_data,
+            embargo_max_duration: None,
+            max_orders_per_batch: NonZeroUsize::new(2),
+            owner_quota: None,
+            feature_flags: None,
+        };
+
+        // Both orders ask for twice the quoted buy amount.
+        let order = |valid_to| OrderCreation {
+            from: Some(owner),
+            sell_amount: 100.into(),
+            buy_amount: 200.into(),
+            valid_to,
+            signature: Signature::Eip712(Default::default()),
+            ..Default::default()
+        };
+        let results = orderbook.add_orders(vec![order(1), order(2)]).await;
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(AddOrderError::OrderValidation(
+                ValidationError::TooManyLimitOrders
+            ))
+        ));
+    }
 }
@@ -14,6 +14,7 @@ use {
             PreOrderData,
         },
         price_estimation::Verification,
+        quote_commitment::QuoteCommitments,
         trade_finding,
     },
     std::sync::Arc,
@@ -26,6 +27,7 @@ pub struct QuoteHandler {
     optimal_quoter: Arc<dyn OrderQuoting>,
     fast_quoter: Arc<dyn OrderQuoting>,
     app_data: Arc<app_data::Registry>,
+    commitments: Option<Arc<QuoteCommitments>>,
 }
 
 impl QuoteHandler {
@@ -39,6 +41,7 @@ impl QuoteHandler {
             optimal_quoter: quoter.clone(),
             fast_quoter: quoter,
             app_data,
+            commitments: None,
         }
     }
 
@@ -46,6 +49,12 @@ impl QuoteHandler {
         self.fast_quoter = fast_quoter;
         self
     }
+
+    /// Returns signed commitments for stored quotes.
+    pub fn with_commitments(mut self, commitments: Arc<QuoteCommitments>) -> Self {
+        self.commitments = Some(commitments);
+        self
+    }
 }
 
 impl QuoteHandler {
@@ -128,6 +137,10 @@ impl QuoteHandler {
             expiration: quote.data.expiration,
             id: quote.id,
             verified: quote.data.verified,
+            commitment: self
+                .commitments
+                .as_ref()
+                .and_then(|commitments| commitments.commit(&quote)),
         };
 
         tracing::debug!(?response, "finished computing quote");
@@ -1,10 +1,15 @@
 use {
     crate::{
-        api,
+        api::{self, rate_limit::IpRateLimit},
         arguments::Arguments,
+        current_auction::CurrentAuction,
         database::Postgres,
-        ipfs::Ipfs,
-        ipfs_app_data::IpfsAppData,
+        ethflow::EthFlow,
+        graphql::{self, GraphQl},
+        ipfs::{Ipfs, IpfsPinning},
+        ipfs_app_data::{IpfsAppData, IpfsAppDataPinning},
+        notifications::{self, webhook::Webhook, Notifications},
+        order_events::OrderEvents,
         orderbook::Orderbook,
         quoter::QuoteHandler,
     },
@@ -13,11 +18,16 @@ use {
     chain::Chain,
     clap::Parser,
     contracts::{BalancerV2Vault, GPv2Settlement, HooksTrampoline, IUniswapV3Factory, WETH9},
-    ethcontract::errors::DeployError,
-    futures::{FutureExt, StreamExt},
+    ethcontract::{errors::DeployError, PrivateKey},
+    futures::{future::BoxFuture, FutureExt, StreamExt},
     model::{order::BUY_ETH_ADDRESS, DomainSeparator},
-    observe::metrics::{serve_metrics, DEFAULT_METRICS_PORT},
+    observe::{
+        health::HealthServer,
+        metrics::{LivenessChecking, DEFAULT_METRICS_PORT},
+    },
     order_validation,
+    prometheus_metric_storage::StorageRegistry,
+    rate_limit::{Quota, RateLimiterRegistry},
     shared::{
         account_balances,
         bad_token::{
@@ -29,44 +39,158 @@ use {
         },
         baseline_solver::BaseTokens,
         code_fetching::CachedCodeFetcher,
+        feature_flags::FeatureFlags,
         gas_price::InstrumentedGasEstimator,
         http_client::HttpClientFactory,
         order_quoting::{self, OrderQuoter},
-        order_validation::{OrderValidPeriodConfiguration, OrderValidator},
+        order_validation::{
+            MandatoryQuoteVerification,
+            OrderValidPeriodConfiguration,
+            OrderValidator,
+        },
         price_estimation::{
             factory::{self, PriceEstimatorFactory},
-            native::NativePriceEstimating,
             PriceEstimating,
             QuoteVerificationMode,
         },
+        quote_commitment::QuoteCommitments,
         signature_validator,
         sources::{self, uniswap_v2::UniV2BaselineSourceParameters, BaselineSource},
         token_info::{CachedTokenInfoFetcher, TokenInfoFetcher},
     },
-    std::{future::Future, net::SocketAddr, sync::Arc, time::Duration},
+    std::{
+        collections::{BTreeMap, HashMap},
+        future::Future,
+        net::SocketAddr,
+        num::NonZeroUsize,
+        sync::Arc,
+        time::Duration,
+    },
     tokio::{task, task::JoinHandle},
     warp::Filter,
 };
 
 pub async fn start(args: impl Iterator<Item = String>) {
-    let args = Arguments::parse_from(args);
-    observe::tracing::initialize(
-        args.shared.logging.log_filter.as_str(),
-        args.shared.logging.log_stderr_threshold,
-    );
+    let mut args = Arguments::parse_from(args);
+    let observe_config = args.shared.logging.observe_config();
+    observe::tracing::initialize_with_config(&observe_config);
+    args.metrics_port = args.metrics_port.or(observe_config.metrics_port);
     tracing::info!("running order book with validated arguments:\n{}", args);
     observe::panic_hook::install();
     observe::metrics::setup_registry(Some("gp_v2_api".into()), None);
     run(args).await;
 }
 
-pub async fn run(args: Arguments) {
+pub async fn run(mut args: Arguments) {
+    args.shared.register_custom_chains();
+    database::instrumentation::configure("orderbook", args.shared.db_slow_query_threshold);
+    let bind_address = args.bind_address;
+    let metrics_port = args.metrics_port.unwrap_or(DEFAULT_METRICS_PORT);
+    let chains = std::mem::take(&mut args.chains);
+    let mut metrics_registries = HashMap::new();
+    let apis = if chains.is_empty() {
+        vec![(None, build(args).await)]
+    } else {
+        let mut apis = Vec::new();
+        for chain in chains {
+            let args = chain.load().expect("failed to load chain arguments");
+            tracing::info!(prefix = %chain.prefix, "serving chain with arguments:\n{}", args);
+            // Every chain reports its metrics to its own registry so they can
+            // be told apart by the `chain` label.
+            let registry = observe::metrics::labelled_registry(HashMap::from([(
+                "chain".to_string(),
+                chain.prefix.clone(),
+            )]));
+            let api = observe::metrics::scope(registry, build(args)).await;
+            metrics_registries.insert(chain.prefix.clone(), registry);
+            apis.push((Some(chain.prefix), api));
+        }
+        apis
+    };
+    let liveness = Liveness(apis.iter().map(|(_, api)| api.orderbook.clone()).collect());
+    let registries: Vec<_> = apis
+        .iter()
+        .map(|(prefix, api)| (prefix.clone(), api.rate_limiters.clone()))
+        .collect();
+
+    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
+    let serve_api = serve_api(apis, metrics_registries, bind_address, async {
+        let _ = shutdown_receiver.await;
+    });
+
+    let mut metrics_address = bind_address;
+    metrics_address.set_port(metrics_port);
+    let metrics_task = HealthServer::default()
+        .with("orderbook", Arc::new(liveness))
+        .with_report("rate_limits", move || {
+            warp::reply::json(&rate_limit_report(&registries))
+        })
+        .serve(metrics_address);
+
+    futures::pin_mut!(serve_api);
+    tokio::select! {
+        result = &mut serve_api => panic!("API task exited {result:?}"),
+        result = metrics_task => panic!("metrics task exited {result:?}"),
+        _ = shutdown_signal() => {
+            tracing::info!("Gracefully shutting down API");
+            shutdown_sender.send(()).expect("failed to send shutdown signal");
+            match tokio::time::timeout(Duration::from_secs(10), serve_api).await {
+                Ok(inner) => inner.expect("API failed during shutdown"),
+                Err(_) => panic!("API shutdown exceeded timeout"),
+            }
+        }
+    };
+}
+
+/// The process is alive if the orderbooks of all chains are.
+struct Liveness(Vec<Arc<Orderbook>>);
+
+#[async_trait::async_trait]
+impl LivenessChecking for Liveness {
+    async fn is_alive(&self) -> bool {
+        futures::future::join_all(self.0.iter().map(|orderbook| orderbook.is_alive()))
+            .await
+            .into_iter()
+            .all(|alive| alive)
+    }
+}
+
+/// Remaining back off in seconds of every rate limited endpoint. Endpoints of
+/// the per chain registries are prefixed with the chain's prefix.
+fn rate_limit_report(
+    registries: &[(Option<String>, Arc<RateLimiterRegistry>)],
+) -> BTreeMap<String, f64> {
+    registries
+        .iter()
+        .flat_map(|(prefix, registry)| {
+            registry
+                .back_offs()
+                .into_iter()
+                .map(move |(name, back_off)| {
+                    let name = match prefix {
+                        Some(prefix) => format!("{prefix}/{name}"),
+                        None => name,
+                    };
+                    (name, back_off.as_secs_f64())
+                })
+        })
+        .chain(
+            RateLimiterRegistry::global()
+                .back_offs()
+                .into_iter()
+                .map(|(name, back_off)| (name, back_off.as_secs_f64())),
+        )
+        .collect()
+}
+
+async fn build(args: Arguments) -> api::Api {
     let http_factory = HttpClientFactory::new(&args.http_client);
 
-    let web3 = shared::ethrpc::web3(
+    let web3 = shared::ethrpc::web3_with_fallbacks(
         &args.shared.ethrpc,
         &http_factory,
         &args.shared.node_url,
+        &args.shared.fallback_node_urls,
         "base",
     );
     let simulation_web3 = args.shared.simulation_node_url.as_ref().map(|node_url| {
@@ -97,15 +221,19 @@ pub async fn run(args: Arguments) {
         .call()
         .await
         .expect("Couldn't get vault relayer address");
-    let native_token = match args.shared.native_token_address {
+    let chain = Chain::try_from(chain_id).expect("incorrect chain ID");
+
+    let native_token = match args
+        .shared
+        .native_token_address
+        .or(chain.custom().map(|config| config.native_token))
+    {
         Some(address) => contracts::WETH9::with_deployment_info(&web3, address, None),
         None => WETH9::deployed(&web3)
             .await
             .expect("load native token contract"),
     };
 
-    let chain = Chain::try_from(chain_id).expect("incorrect chain ID");
-
     let signature_validator = signature_validator::validator(
         &web3,
         signature_validator::Contracts {
@@ -139,7 +267,24 @@ pub async fn run(args: Arguments) {
         .await
         .expect("Deployed contract constants don't match the ones in this binary");
     let domain_separator = DomainSeparator::new(chain_id, settlement_contract.address());
-    let postgres = Postgres::try_new(args.db_url.as_str()).expect("failed to create database");
+    let mut postgres = Postgres::try_new(args.db_url.as_str()).expect("failed to create database");
+    if let Some(url) = &args.shared.db_read_replica_url {
+        let replica = database::replica::ReadReplica::connect_lazy(
+            url.as_str(),
+            postgres.pool.clone(),
+            args.shared.db_read_replica_max_lag,
+        )
+        .expect("failed to create read replica pool");
+        postgres = postgres.with_replica(replica);
+    }
+    observe::metrics::spawn_in_current_scope(database::instrumentation::pool_metrics_task(
+        postgres.pool.clone(),
+        Duration::from_secs(10),
+    ));
+    args.shared
+        .ethrpc
+        .budget
+        .configure(Some(postgres.pool.clone()));
 
     let balance_fetcher = account_balances::fetcher(
         &web3,
@@ -195,6 +340,7 @@ pub async fn run(args: Arguments) {
         other => Some(other.unwrap()),
     };
 
+    let rate_limiters = Arc::new(RateLimiterRegistry::default());
     let finder = token_owner_finder::init(
         &args.token_owner_finder,
         web3.clone(),
@@ -205,6 +351,7 @@ pub async fn run(args: Arguments) {
         uniswapv3_factory.as_ref(),
         &base_tokens,
         settlement_contract.address(),
+        &rate_limiters,
     )
     .await
     .expect("failed to initialize token owner finders");
@@ -245,6 +392,7 @@ pub async fn run(args: Arguments) {
 
     let token_info_fetcher = Arc::new(CachedTokenInfoFetcher::new(Arc::new(TokenInfoFetcher {
         web3: web3.clone(),
+        overrides: args.shared.token_info_overrides.clone(),
     })));
 
     let code_fetcher = Arc::new(CachedCodeFetcher::new(Arc::new(web3.clone())));
@@ -271,6 +419,8 @@ pub async fn run(args: Arguments) {
             bad_token_detector: bad_token_detector.clone(),
             tokens: token_info_fetcher.clone(),
             code_fetcher: code_fetcher.clone(),
+            db: postgres.pool.clone(),
+            rate_limiters: rate_limiters.clone(),
         },
     )
     .await
@@ -343,7 +493,20 @@ pub async fn run(args: Arguments) {
 
     let app_data_validator = Validator::new(args.app_data_size_limit);
     let chainalysis_oracle = contracts::ChainalysisOracle::deployed(&web3).await.ok();
-    let order_validator = Arc::new(OrderValidator::new(
+    let quote_commitments = args.quote_commitment_signing_key.map(|key| {
+        let key = key
+            .parse::<PrivateKey>()
+            .expect("invalid quote commitment signing key");
+        let commitments = QuoteCommitments::new(
+            domain_separator,
+            *key,
+            args.quote_commitment_previous_signers,
+            args.quote_commitment_validity,
+        );
+        tracing::info!(signer = ?commitments.signer(), "signing quote commitments");
+        Arc::new(commitments)
+    });
+    let mut order_validator = OrderValidator::new(
         native_token.clone(),
         Arc::new(order_validation::banned::Users::new(
             chainalysis_oracle,
@@ -361,7 +524,16 @@ pub async fn run(args: Arguments) {
         code_fetcher,
         app_data_validator.clone(),
         args.max_gas_per_order,
-    ));
+    )
+    .with_mandatory_quote_verification(MandatoryQuoteVerification {
+        classes: args.mandatory_quote_verification,
+        amount_tolerance_bps: args.mandatory_quote_verification_tolerance_bps,
+    })
+    .with_bridging(chain_id);
+    if let Some(commitments) = &quote_commitments {
+        order_validator = order_validator.with_quote_commitments(commitments.clone());
+    }
+    let order_validator = Arc::new(order_validator);
     let ipfs = args
         .ipfs_gateway
         .map(|url| {
@@ -373,56 +545,120 @@ pub async fn run(args: Arguments) {
             )
         })
         .map(IpfsAppData::new);
-    let app_data = Arc::new(crate::app_data::Registry::new(
-        app_data_validator,
-        postgres.clone(),
-        ipfs,
-    ));
-    let orderbook = Arc::new(Orderbook::new(
+    let mut app_data = crate::app_data::Registry::new(app_data_validator, postgres.clone(), ipfs);
+    if let Some(api) = args.ipfs_pinning_api {
+        let pinning = IpfsPinning::new(http_factory.builder(), api, args.ipfs_pinning_auth);
+        app_data = app_data.with_pinning(
+            Arc::new(IpfsAppDataPinning::new(pinning)),
+            args.ipfs_pinning_max_concurrent,
+        );
+    }
+    let app_data = Arc::new(app_data);
+    let mut orderbook = Orderbook::new(
         domain_separator,
         settlement_contract.address(),
         postgres.clone(),
         order_validator.clone(),
         app_data.clone(),
-    ));
+    );
+    if let Some(max_duration) = args.order_embargo_max_duration {
+        orderbook = orderbook.with_order_embargo(max_duration);
+    }
+    if let Some(max_orders) = NonZeroUsize::new(args.max_orders_per_batch) {
+        orderbook = orderbook.with_order_batches(max_orders);
+    }
+    if let Some(config) = args.api_rate_limit_per_owner {
+        orderbook = orderbook.with_owner_rate_limit(
+            Quota::try_new("api_owner".into(), config).expect("invalid owner rate limit"),
+        );
+    }
+    orderbook = orderbook.with_feature_flags(Arc::new(FeatureFlags::new(
+        postgres.pool.clone(),
+        chain_id,
+        args.shared.feature_flags_cache_ttl,
+    )));
+    let orderbook = Arc::new(orderbook);
+
+    let ethflow = args.ethflow_contract.map(|contract| {
+        Arc::new(EthFlow::new(
+            contract,
+            native_token.address(),
+            domain_separator,
+            order_validator.clone(),
+            optimal_quoter.clone(),
+            postgres.clone(),
+        ))
+    });
 
     check_database_connection(orderbook.as_ref()).await;
-    let quotes = Arc::new(
-        QuoteHandler::new(order_validator, optimal_quoter, app_data.clone())
-            .with_fast_quoter(fast_quoter),
-    );
+    let mut quotes = QuoteHandler::new(order_validator, optimal_quoter, app_data.clone())
+        .with_fast_quoter(fast_quoter);
+    if let Some(commitments) = quote_commitments {
+        quotes = quotes.with_commitments(commitments);
+    }
+    let quotes = Arc::new(quotes);
+
+    let graphql = args.graphql_enabled.then(|| {
+        Arc::new(GraphQl::new(
+            postgres.clone(),
+            graphql::Limits {
+                max_depth: args.graphql_max_depth,
+                max_complexity: args.graphql_max_complexity,
+            },
+        ))
+    });
 
-    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel();
-    let serve_api = serve_api(
-        postgres,
-        orderbook.clone(),
+    let notifications = args.notifications_enabled.then(|| {
+        let webhook = Webhook::new(
+            http_factory
+                .builder()
+                .timeout(args.notification_webhook_timeout),
+        );
+        let notifications = Arc::new(Notifications::new(
+            domain_separator,
+            postgres.clone(),
+            notifications::Config {
+                digest_interval: args.notification_digest_interval,
+                max_digests_per_run: args.notification_max_digests_per_minute,
+                max_orders_per_digest: args.notification_max_orders_per_digest,
+            },
+            Arc::new(webhook),
+        ));
+        observe::metrics::spawn_in_current_scope(notifications.clone().run_digests());
+        notifications
+    });
+
+    let order_events = args.order_events_enabled.then(|| {
+        let order_events = Arc::new(OrderEvents::new(postgres.clone()));
+        observe::metrics::spawn_in_current_scope(order_events.clone().run_forever());
+        order_events
+    });
+
+    let current_auction = Arc::new(CurrentAuction::new(postgres.clone()));
+    observe::metrics::spawn_in_current_scope(current_auction.clone().run_forever());
+
+    let ip_rate_limit = args.api_rate_limit_per_ip.map(|config| {
+        Arc::new(IpRateLimit {
+            quota: Quota::try_new("api_ip".into(), config).expect("invalid IP rate limit"),
+            trusted_proxies: args.api_rate_limit_trusted_proxies,
+        })
+    });
+
+    api::Api {
+        database: postgres,
+        orderbook,
         quotes,
         app_data,
-        args.bind_address,
-        async {
-            let _ = shutdown_receiver.await;
-        },
         native_price_estimator,
-    );
-
-    let mut metrics_address = args.bind_address;
-    metrics_address.set_port(DEFAULT_METRICS_PORT);
-    tracing::info!(%metrics_address, "serving metrics");
-    let metrics_task = serve_metrics(orderbook, metrics_address);
-
-    futures::pin_mut!(serve_api);
-    tokio::select! {
-        result = &mut serve_api => panic!("API task exited {result:?}"),
-        result = metrics_task => panic!("metrics task exited {result:?}"),
-        _ = shutdown_signal() => {
-            tracing::info!("Gracefully shutting down API");
-            shutdown_sender.send(()).expect("failed to send shutdown signal");
-            match tokio::time::timeout(Duration::from_secs(10), serve_api).await {
-                Ok(inner) => inner.expect("API failed during shutdown"),
-                Err(_) => panic!("API shutdown exceeded timeout"),
-            }
-        }
-    };
+        partner_api_keys: args.partner_api_keys,
+        graphql,
+        notifications,
+        order_events,
+        ethflow,
+        current_auction,
+        ip_rate_limit,
+        rate_limiters,
+    }
 }
 
 #[cfg(unix)]
@@ -459,26 +695,42 @@ async fn check_database_connection(orderbook: &Orderbook) {
         .expect("failed to connect to database");
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Serves the APIs of all chains. APIs with a path prefix are served under
+/// `/<prefix>/api/...`, the others directly under `/api/...`. Requests for a
+/// prefix are handled in the scope of the prefix's metrics registry.
 fn serve_api(
-    database: Postgres,
-    orderbook: Arc<Orderbook>,
-    quotes: Arc<QuoteHandler>,
-    app_data: Arc<crate::app_data::Registry>,
+    apis: Vec<(Option<String>, api::Api)>,
+    metrics_registries: HashMap<String, &'static StorageRegistry>,
     address: SocketAddr,
     shutdown_receiver: impl Future<Output = ()> + Send + 'static,
-    native_price_estimator: Arc<dyn NativePriceEstimating>,
 ) -> JoinHandle<()> {
-    let filter = api::handle_all_routes(
-        database,
-        orderbook,
-        quotes,
-        app_data,
-        native_price_estimator,
-    )
-    .boxed();
+    let filter = apis
+        .into_iter()
+        .map(|(prefix, api)| {
+            let registry = prefix
+                .as_ref()
+                .and_then(|prefix| metrics_registries.get(prefix))
+                .copied();
+            let routes = match registry {
+                Some(registry) => observe::metrics::sync_scope(registry, || {
+                    api::box_filter(api::handle_all_routes(api))
+                }),
+                None => api::box_filter(api::handle_all_routes(api)),
+            };
+            match prefix {
+                // The prefix is matched before the routes recover from
+                // rejections so that requests for other chains fall through.
+                Some(prefix) => warp::path(prefix).and(routes).boxed(),
+                None => routes,
+            }
+        })
+        .reduce(|routes, next| routes.or(next).unify().boxed())
+        .expect("at least one chain is served");
     tracing::info!(%address, "serving order book");
-    let warp_svc = warp::service(filter);
+    let warp_svc = ScopedMetrics {
+        inner: warp::service(filter),
+        registries: Arc::new(metrics_registries),
+    };
     let warp_svc = observe::make_service_with_task_local_storage!(warp_svc);
     let server = hyper::Server::bind(&address)
         .serve(warp_svc)
@@ -487,6 +739,46 @@ fn serve_api(
     task::spawn(server)
 }
 
+/// Service handling every request in the scope of the metrics registry of the
+/// chain whose prefix the request path starts with.
+#[derive(Clone)]
+struct ScopedMetrics<S> {
+    inner: S,
+    registries: Arc<HashMap<String, &'static StorageRegistry>>,
+}
+
+impl<S> hyper::service::Service<hyper::Request<hyper::Body>> for ScopedMetrics<S>
+where
+    S: hyper::service::Service<hyper::Request<hyper::Body>>,
+    S::Future: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+    type Response = S::Response;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: hyper::Request<hyper::Body>) -> Self::Future {
+        let registry = request
+            .uri()
+            .path()
+            .split('/')
+            .nth(1)
+            .and_then(|prefix| self.registries.get(prefix))
+            .copied();
+        let Some(registry) = registry else {
+            return self.inner.call(request).boxed();
+        };
+        let future = observe::metrics::sync_scope(registry, || self.inner.call(request));
+        observe::metrics::scope(registry, future).boxed()
+    }
+}
+
 /// Check that important constants such as the EIP 712 Domain Separator and
 /// Order Type Hash used in this binary match the ones on the deployed
 /// contract instance. Signature inconsistencies due to a mismatch of these
@@ -1,15 +1,24 @@
 use {
-    anyhow::{ensure, Context, Result},
+    anyhow::{anyhow, ensure, Context, Result},
     std::{
+        collections::HashMap,
         fmt::{Display, Formatter},
         future::Future,
         str::FromStr,
-        sync::{Arc, Mutex, MutexGuard},
+        sync::{Arc, Mutex, MutexGuard, OnceLock},
         time::{Duration, Instant},
     },
     thiserror::Error,
 };
 
+mod middleware;
+mod quota;
+
+pub use {
+    middleware::{Middleware, SendError},
+    quota::{Quota, QuotaConfig},
+};
+
 #[derive(prometheus_metric_storage::MetricStorage, Clone, Debug)]
 #[metric(subsystem = "rate_limiter")]
 struct Metrics {
@@ -22,6 +31,14 @@ struct Metrics {
     /// Number of successful requests.
     #[metric(labels("endpoint"))]
     successful_requests: prometheus::IntCounterVec,
+    /// Back off applied after the most recent rate limiting response. Zero if
+    /// the endpoint is currently not rate limited.
+    #[metric(labels("endpoint"))]
+    current_back_off_seconds: prometheus::GaugeVec,
+    /// Number of incoming requests rejected because their key exhausted its
+    /// quota.
+    #[metric(labels("quota"))]
+    quota_exceeded: prometheus::IntCounterVec,
 }
 
 fn metrics() -> &'static Metrics {
@@ -37,6 +54,81 @@ pub struct Strategy {
     back_off_growth_factor: f64,
     min_back_off: Duration,
     max_back_off: Duration,
+    /// Proactively limits the request rate independent of rate limiting
+    /// responses.
+    token_bucket: Option<TokenBucket>,
+}
+
+/// Allows `requests_per_second` requests on average with up to `burst`
+/// requests at once.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    requests_per_second: f64,
+    burst: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn try_new(requests_per_second: f64, burst: Option<f64>) -> Result<Self> {
+        ensure!(
+            requests_per_second.is_normal() && requests_per_second > 0.,
+            "requests per second must be a positive number"
+        );
+        let burst = burst.unwrap_or(requests_per_second.ceil());
+        ensure!(burst >= 1., "burst needs to be at least 1");
+        Ok(Self {
+            requests_per_second,
+            burst,
+            tokens: burst,
+            refilled_at: Instant::now(),
+        })
+    }
+
+    /// Whether both buckets are configured the same way, ignoring their
+    /// current fill level.
+    fn same_config(&self, other: &Self) -> bool {
+        self.requests_per_second == other.requests_per_second && self.burst == other.burst
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.requests_per_second).min(self.burst);
+        self.refilled_at = now;
+    }
+
+    /// Consumes a token if one is available.
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens < 1. {
+            return false;
+        }
+        self.tokens -= 1.;
+        true
+    }
+
+    /// Returns how long it takes until the next token becomes available.
+    fn time_until_token(&mut self, now: Instant) -> Option<Duration> {
+        self.refill(now);
+        (self.tokens < 1.)
+            .then(|| Duration::from_secs_f64((1. - self.tokens) / self.requests_per_second))
+    }
+}
+
+impl Strategy {
+    /// Whether both strategies are configured the same way, ignoring their
+    /// current back off state.
+    fn same_config(&self, other: &Self) -> bool {
+        self.back_off_growth_factor == other.back_off_growth_factor
+            && self.min_back_off == other.min_back_off
+            && self.max_back_off == other.max_back_off
+            && match (&self.token_bucket, &other.token_bucket) {
+                (Some(a), Some(b)) => a.same_config(b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
 }
 
 impl Default for Strategy {
@@ -49,32 +141,74 @@ impl Display for Strategy {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "RateLimitingStrategy{{ min_back_off: {:?}, max_back_off: {:?}, growth_factor: {:?} }}",
+            "RateLimitingStrategy{{ min_back_off: {:?}, max_back_off: {:?}, growth_factor: {:?}",
             self.min_back_off, self.max_back_off, self.back_off_growth_factor
-        )
+        )?;
+        if let Some(bucket) = &self.token_bucket {
+            write!(
+                f,
+                ", requests_per_second: {:?}, burst: {:?}",
+                bucket.requests_per_second, bucket.burst
+            )?;
+        }
+        write!(f, " }}")
     }
 }
 
 impl FromStr for Strategy {
     type Err = anyhow::Error;
 
+    /// Parses "<back_off_growth_factor>,<min_back_off>,<max_back_off>" for the
+    /// reactive back off, "<N>rps[,burst=<N>]" for the proactive token bucket
+    /// or both separated by a comma.
     fn from_str(config: &str) -> Result<Self> {
-        let mut parts = config.split(',');
-        let back_off_growth_factor = parts.next().context("missing back_off_growth_factor")?;
-        let min_back_off = parts.next().context("missing min_back_off")?;
-        let max_back_off = parts.next().context("missing max_back_off")?;
-        ensure!(
-            parts.next().is_none(),
-            "extraneous rate limiting parameters"
-        );
-        let back_off_growth_factor: f64 = back_off_growth_factor
-            .parse()
-            .context("parsing back_off_growth_factor")?;
-        let min_back_off =
-            humantime::parse_duration(min_back_off).context("parsing min_back_off")?;
-        let max_back_off =
-            humantime::parse_duration(max_back_off).context("parsing max_back_off")?;
-        Self::try_new(back_off_growth_factor, min_back_off, max_back_off)
+        let mut requests_per_second = None;
+        let mut burst = None;
+        let mut back_off = Vec::new();
+        for part in config.split(',').map(str::trim) {
+            if let Some(rps) = part.strip_suffix("rps") {
+                ensure!(
+                    requests_per_second.is_none(),
+                    "duplicate requests per second"
+                );
+                requests_per_second = Some(rps.parse::<f64>().context("parsing rps")?);
+            } else if let Some(value) = part.strip_prefix("burst=") {
+                ensure!(burst.is_none(), "duplicate burst");
+                burst = Some(value.parse::<u32>().context("parsing burst")?.into());
+            } else {
+                back_off.push(part);
+            }
+        }
+
+        let mut strategy = match back_off.as_slice() {
+            [] => {
+                ensure!(
+                    requests_per_second.is_some(),
+                    "missing rate limiting parameters"
+                );
+                Self::default()
+            }
+            [back_off_growth_factor, min_back_off, max_back_off] => {
+                let back_off_growth_factor: f64 = back_off_growth_factor
+                    .parse()
+                    .context("parsing back_off_growth_factor")?;
+                let min_back_off =
+                    humantime::parse_duration(min_back_off).context("parsing min_back_off")?;
+                let max_back_off =
+                    humantime::parse_duration(max_back_off).context("parsing max_back_off")?;
+                Self::try_new(back_off_growth_factor, min_back_off, max_back_off)?
+            }
+            [_] => anyhow::bail!("missing min_back_off"),
+            [_, _] => anyhow::bail!("missing max_back_off"),
+            _ => anyhow::bail!("extraneous rate limiting parameters"),
+        };
+        match requests_per_second {
+            Some(requests_per_second) => {
+                strategy = strategy.with_token_bucket(requests_per_second, burst)?;
+            }
+            None => ensure!(burst.is_none(), "burst requires requests per second"),
+        }
+        Ok(strategy)
     }
 }
 
@@ -102,15 +236,32 @@ impl Strategy {
             back_off_growth_factor,
             min_back_off,
             max_back_off,
+            token_bucket: None,
         })
     }
 
+    /// Additionally limits requests to `requests_per_second` on average with
+    /// bursts of up to `burst` requests (defaults to one second worth of
+    /// requests).
+    pub fn with_token_bucket(
+        mut self,
+        requests_per_second: f64,
+        burst: Option<f64>,
+    ) -> Result<Self> {
+        self.token_bucket = Some(TokenBucket::try_new(requests_per_second, burst)?);
+        Ok(self)
+    }
+
     /// Resets back off and stops rate limiting requests.
     pub fn response_ok(&mut self, name: &str) {
         metrics()
             .successful_requests
             .with_label_values(&[name])
             .inc();
+        metrics()
+            .current_back_off_seconds
+            .with_label_values(&[name])
+            .set(0.);
         self.times_rate_limited = 0;
         self.drop_requests_until = Instant::now();
     }
@@ -128,11 +279,13 @@ impl Strategy {
     }
 
     /// Returns updated back off if no other thread increased it in the mean
-    /// time.
+    /// time. A back off requested by the server via `hint` takes precedence
+    /// over the exponential back off but is still capped by `max_back_off`.
     pub fn response_rate_limited(
         &mut self,
         previous_rate_limits: u64,
         name: &str,
+        hint: BackOffHint,
     ) -> Option<Duration> {
         metrics()
             .rate_limited_requests
@@ -143,22 +296,49 @@ impl Strategy {
             return None;
         }
 
-        let new_back_off = self.get_current_back_off();
+        let new_back_off = match hint.until {
+            Some(until) => until.min(self.max_back_off),
+            None => self.get_current_back_off(),
+        };
         self.times_rate_limited += 1;
         self.drop_requests_until = Instant::now() + new_back_off;
+        metrics()
+            .current_back_off_seconds
+            .with_label_values(&[name])
+            .set(new_back_off.as_secs_f64());
         Some(new_back_off)
     }
 
     /// Returns number of times we got rate limited in a row if we are currently
-    /// allowing requests.
-    pub fn times_rate_limited(&self, now: Instant, name: &str) -> Option<u64> {
-        if self.drop_requests_until > now {
+    /// allowing requests. Consumes a token of the token bucket if configured.
+    pub fn times_rate_limited(&mut self, now: Instant, name: &str) -> Option<u64> {
+        // Tokens are only consumed by requests that aren't dropped anyway.
+        if self.drop_requests_until > now
+            || self
+                .token_bucket
+                .as_mut()
+                .is_some_and(|bucket| !bucket.try_acquire(now))
+        {
             metrics().requests_dropped.with_label_values(&[name]).inc();
             return None;
         }
 
         Some(self.times_rate_limited)
     }
+
+    /// Returns how long to wait until a request would be allowed by the token
+    /// bucket.
+    fn time_until_token(&mut self, now: Instant) -> Option<Duration> {
+        self.token_bucket.as_mut()?.time_until_token(now)
+    }
+}
+
+/// Indicates that a response requires backing off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackOffHint {
+    /// How long the server asked to back off for (e.g. via a `Retry-After`
+    /// header). If `None` the back off grows exponentially.
+    pub until: Option<Duration>,
 }
 
 #[derive(Debug)]
@@ -183,6 +363,10 @@ impl RateLimiter {
             .successful_requests
             .with_label_values(&[&name])
             .reset();
+        metrics
+            .current_back_off_seconds
+            .with_label_values(&[&name])
+            .set(0.);
         Self {
             strategy: Mutex::new(strategy),
             name,
@@ -198,6 +382,54 @@ impl RateLimiter {
     }
 }
 
+/// Hands out one rate limiter per endpoint so that all call sites hitting the
+/// same endpoint share their back off state.
+#[derive(Debug, Default)]
+pub struct RateLimiterRegistry {
+    limiters: Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl RateLimiterRegistry {
+    /// The registry shared by the whole process.
+    pub fn global() -> &'static Self {
+        static REGISTRY: OnceLock<RateLimiterRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(Default::default)
+    }
+
+    /// Returns the rate limiter of the endpoint. The limiter gets created with
+    /// the given strategy if the endpoint is not registered yet. Fails if the
+    /// endpoint is already registered with a different strategy.
+    pub fn get_or_create(&self, name: &str, strategy: Strategy) -> Result<Arc<RateLimiter>> {
+        let mut limiters = self.limiters.lock().unwrap();
+        if let Some(existing) = limiters.get(name) {
+            let existing_strategy = existing.strategy();
+            if !existing_strategy.same_config(&strategy) {
+                return Err(anyhow!(
+                    "rate limiter {name} is already configured with {existing_strategy} which \
+                     conflicts with {strategy}"
+                ));
+            }
+            return Ok(existing.clone());
+        }
+        let limiter = Arc::new(RateLimiter::from_strategy(strategy, name.to_owned()));
+        limiters.insert(name.to_owned(), limiter.clone());
+        Ok(limiter)
+    }
+
+    /// Returns the remaining back off of every registered endpoint.
+    pub fn back_offs(&self) -> HashMap<String, Duration> {
+        self.limiters
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, limiter)| {
+                let back_off = limiter.get_back_off_duration_if_limited();
+                (name.clone(), back_off.unwrap_or_default())
+            })
+            .collect()
+    }
+}
+
 #[derive(Error, Debug, Clone, Default, PartialEq)]
 pub enum Error {
     #[default]
@@ -206,6 +438,7 @@ pub enum Error {
 }
 
 impl RateLimiter {
+    /// Requests exceeding the configured token bucket rate get dropped.
     /// If a task produces a result which indicates rate limiting is required
     /// future requests will get dropped for some time. Every successive
     /// response like that increases that time exponentially. When a task
@@ -215,6 +448,19 @@ impl RateLimiter {
         &self,
         task: impl Future<Output = T>,
         requires_back_off: impl Fn(&T) -> bool,
+    ) -> Result<T, Error> {
+        self.execute_with_hint(task, |result| {
+            requires_back_off(result).then(BackOffHint::default)
+        })
+        .await
+    }
+
+    /// Like [`RateLimiter::execute`] but lets the result specify how long to
+    /// back off for. Returning `None` indicates that no back off is required.
+    pub async fn execute_with_hint<T>(
+        &self,
+        task: impl Future<Output = T>,
+        back_off_hint: impl Fn(&T) -> Option<BackOffHint>,
     ) -> Result<T, Error> {
         let times_rate_limited = self
             .strategy()
@@ -229,10 +475,10 @@ impl RateLimiter {
 
         let result = task.await;
 
-        if requires_back_off(&result) {
-            let new_back_off = self
-                .strategy()
-                .response_rate_limited(times_rate_limited, &self.name);
+        if let Some(hint) = back_off_hint(&result) {
+            let new_back_off =
+                self.strategy()
+                    .response_rate_limited(times_rate_limited, &self.name, hint);
             if let Some(new_back_off) = new_back_off {
                 tracing::warn!(?self.name, ?new_back_off, "extended rate limiting");
             }
@@ -254,6 +500,10 @@ impl RateLimiter {
         if let Some(back_off_duration) = self.get_back_off_duration_if_limited() {
             tokio::time::sleep(back_off_duration).await;
         }
+        let token_wait = self.strategy().time_until_token(Instant::now());
+        if let Some(token_wait) = token_wait {
+            tokio::time::sleep(token_wait).await;
+        }
 
         self.execute(task, requires_back_off).await
     }
@@ -273,13 +523,30 @@ impl RateLimiter {
 
 /// Shared module with common back-off checks.
 pub mod back_off {
-    use reqwest::Response;
+    use {
+        super::BackOffHint,
+        reqwest::{header::RETRY_AFTER, Response},
+        std::time::Duration,
+    };
 
     /// Determines if the HTTP response indicates that the API should back off
     /// for a while.
     pub fn on_http_429(response: &Result<Response, reqwest::Error>) -> bool {
         matches!(response, Ok(response) if response.status() == 429)
     }
+
+    /// Like [`on_http_429`] but honors the back off requested by the server
+    /// in the `Retry-After` header (in seconds).
+    pub fn retry_after(response: &Result<Response, reqwest::Error>) -> Option<BackOffHint> {
+        let response = response.as_ref().ok().filter(|r| r.status() == 429)?;
+        let until = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        Some(BackOffHint { until })
+    }
 }
 
 #[cfg(test)]
@@ -296,6 +563,7 @@ mod tests {
             back_off_growth_factor: f64::MAX,
             min_back_off: Duration::from_millis(16),
             max_back_off: max,
+            token_bucket: None,
         }
         .get_current_back_off();
         assert_eq!(max, back_off);
@@ -307,6 +575,7 @@ mod tests {
             back_off_growth_factor: 2.,
             min_back_off: Duration::from_millis(16),
             max_back_off: max,
+            token_bucket: None,
         }
         .get_current_back_off();
         assert_eq!(Duration::from_millis(16 * 8), back_off);
@@ -362,6 +631,113 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn honors_back_off_hint() {
+        let strategy =
+            Strategy::try_new(2.0, Duration::from_millis(20), Duration::from_secs(60)).unwrap();
+        let rate_limiter = RateLimiter::from_strategy(strategy, "test_hint".into());
+
+        let hint = BackOffHint {
+            until: Some(Duration::from_secs(30)),
+        };
+        let result = rate_limiter
+            .execute_with_hint(async { 1 }, |_| Some(hint))
+            .await;
+        assert_eq!(result, Ok(1));
+        let back_off = rate_limiter.get_back_off_duration_if_limited().unwrap();
+        assert!(back_off > Duration::from_secs(29));
+
+        // Server provided back offs are capped.
+        rate_limiter.strategy().drop_requests_until = Instant::now();
+        let hint = BackOffHint {
+            until: Some(Duration::from_secs(600)),
+        };
+        rate_limiter
+            .execute_with_hint(async {}, |_| Some(hint))
+            .await
+            .unwrap();
+        let back_off = rate_limiter.get_back_off_duration_if_limited().unwrap();
+        assert!(back_off <= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn parses_strategies() {
+        let strategy: Strategy = "1.5,10s,30s".parse().unwrap();
+        assert!(strategy.token_bucket.is_none());
+        assert_eq!(strategy.max_back_off, Duration::from_secs(30));
+
+        let strategy: Strategy = "10rps,burst=20".parse().unwrap();
+        let bucket = strategy.token_bucket.unwrap();
+        assert_eq!((bucket.requests_per_second, bucket.burst), (10., 20.));
+        assert_eq!(strategy.max_back_off, Duration::ZERO);
+
+        let strategy: Strategy = "2,1s,5s,0.5rps".parse().unwrap();
+        let bucket = strategy.token_bucket.unwrap();
+        assert_eq!((bucket.requests_per_second, bucket.burst), (0.5, 1.));
+        assert_eq!(strategy.max_back_off, Duration::from_secs(5));
+
+        for invalid in [
+            "",
+            "burst=2",
+            "1.5,10s",
+            "0rps",
+            "1rps,burst=0",
+            "1,1s,1s,1s",
+        ] {
+            assert!(invalid.parse::<Strategy>().is_err(), "{invalid}");
+        }
+    }
+
+    #[tokio::test]
+    async fn token_bucket_limits_request_rate() {
+        let strategy = Strategy::default()
+            .with_token_bucket(20., Some(2.))
+            .unwrap();
+        let rate_limiter = RateLimiter::from_strategy(strategy, "test_bucket".into());
+
+        // The burst is available immediately.
+        assert_eq!(rate_limiter.execute(async { 1 }, |_| false).await, Ok(1));
+        assert_eq!(rate_limiter.execute(async { 2 }, |_| false).await, Ok(2));
+        assert_eq!(
+            rate_limiter.execute(async { 3 }, |_| false).await,
+            Err(Error::RateLimited)
+        );
+
+        // Waits for the next token instead of dropping the request.
+        let result = rate_limiter
+            .execute_with_back_off(async { 4 }, |_| false)
+            .await;
+        assert_eq!(result, Ok(4));
+
+        // Tokens get refilled over time.
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(rate_limiter.execute(async { 5 }, |_| false).await, Ok(5));
+        assert_eq!(rate_limiter.execute(async { 6 }, |_| false).await, Ok(6));
+    }
+
+    #[tokio::test]
+    async fn registry_shares_back_off() {
+        let registry = RateLimiterRegistry::default();
+        let strategy =
+            Strategy::try_new(1.0, Duration::from_secs(60), Duration::from_secs(60)).unwrap();
+        let first = registry
+            .get_or_create("test_registry", strategy.clone())
+            .unwrap();
+        let second = registry.get_or_create("test_registry", strategy).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(registry
+            .get_or_create("test_registry", Default::default())
+            .is_err());
+        assert_eq!(registry.back_offs()["test_registry"], Duration::ZERO);
+
+        first.execute(async {}, |_| true).await.unwrap();
+        assert_eq!(
+            second.execute(async {}, |_| false).await,
+            Err(Error::RateLimited)
+        );
+        assert!(registry.back_offs()["test_registry"] > Duration::from_secs(50));
+    }
+
     #[tokio::test]
     async fn test_execute_with_no_back_off() {
         let timeout = Duration::from_secs(30);
@@ -0,0 +1,117 @@
+use {
+    super::{back_off, Error, RateLimiter},
+    reqwest::{Client, IntoUrl, Request, RequestBuilder, Response},
+    std::{collections::HashMap, sync::Arc},
+    thiserror::Error,
+};
+
+/// Wraps a [`Client`] so that every request to a rate limited host goes
+/// through the host's [`RateLimiter`]. Requests to other hosts are sent
+/// unchanged.
+#[derive(Clone, Debug, Default)]
+pub struct Middleware {
+    client: Client,
+    limiters: HashMap<String, Arc<RateLimiter>>,
+}
+
+#[derive(Error, Debug)]
+pub enum SendError {
+    #[error(transparent)]
+    RateLimited(#[from] Error),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+}
+
+impl Middleware {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            limiters: Default::default(),
+        }
+    }
+
+    /// Rate limits all requests to `host` (e.g. "api.0x.org") with the given
+    /// limiter.
+    pub fn with_rate_limiter(mut self, host: &str, limiter: Arc<RateLimiter>) -> Self {
+        self.limiters.insert(host.to_owned(), limiter);
+        self
+    }
+
+    /// The wrapped client. Requests sent with it directly are not rate
+    /// limited.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Builds and sends the request. See [`Middleware::execute`].
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, SendError> {
+        self.execute(request.build()?).await
+    }
+
+    /// Sends the request. If the host is rate limited this waits for the
+    /// current back off to end and backs off further if the response asks for
+    /// it (HTTP 429).
+    pub async fn execute(&self, request: Request) -> Result<Response, SendError> {
+        let limiter = request
+            .url()
+            .host_str()
+            .and_then(|host| self.limiters.get(host));
+        let response = self.client.execute(request);
+        match limiter {
+            Some(limiter) => Ok(limiter
+                .execute_with_back_off(response, back_off::on_http_429)
+                .await??),
+            None => Ok(response.await?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::Strategy,
+        std::time::{Duration, Instant},
+    };
+
+    #[tokio::test]
+    async fn only_limits_configured_hosts() {
+        let strategy = Strategy::default().with_token_bucket(1., None).unwrap();
+        let limiter = Arc::new(RateLimiter::from_strategy(
+            strategy,
+            "test_middleware".into(),
+        ));
+        let middleware = Middleware::default().with_rate_limiter("127.0.0.1", limiter);
+
+        // Nothing listens on the port so every request fails immediately.
+        let url = "http://127.0.0.1:1/";
+        let start = Instant::now();
+        assert!(matches!(
+            middleware.send(middleware.get(url)).await,
+            Err(SendError::Request(_))
+        ));
+        assert!(matches!(
+            middleware.send(middleware.get(url)).await,
+            Err(SendError::Request(_))
+        ));
+        // The second request had to wait for a token.
+        assert!(start.elapsed() >= Duration::from_millis(900));
+
+        let start = Instant::now();
+        for _ in 0..2 {
+            assert!(middleware
+                .send(middleware.get("http://localhost:1/"))
+                .await
+                .is_err());
+        }
+        assert!(start.elapsed() < Duration::from_millis(900));
+    }
+}
@@ -0,0 +1,202 @@
+use {
+    super::{metrics, TokenBucket},
+    anyhow::{ensure, Context, Result},
+    std::{
+        collections::HashMap,
+        fmt::{self, Display, Formatter},
+        hash::Hash,
+        str::FromStr,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// Keys with full buckets get forgotten once the number of tracked keys
+/// reaches this threshold (or twice the number of keys that were kept by the
+/// last cleanup).
+const MIN_CLEANUP_THRESHOLD: usize = 1024;
+
+/// How many requests every key of a [`Quota`] may make.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuotaConfig {
+    pub requests_per_second: f64,
+    /// Defaults to one second worth of requests.
+    pub burst: Option<f64>,
+}
+
+impl FromStr for QuotaConfig {
+    type Err = anyhow::Error;
+
+    /// Parses "<N>rps[,burst=<N>]".
+    fn from_str(config: &str) -> Result<Self> {
+        let mut parts = config.split(',').map(str::trim);
+        let requests_per_second = parts
+            .next()
+            .and_then(|part| part.strip_suffix("rps"))
+            .context("missing requests per second")?
+            .parse()
+            .context("parsing rps")?;
+        let burst = parts
+            .next()
+            .map(|part| {
+                let burst = part.strip_prefix("burst=").context("expected burst")?;
+                anyhow::Ok(burst.parse::<u32>().context("parsing burst")?.into())
+            })
+            .transpose()?;
+        ensure!(parts.next().is_none(), "extraneous quota parameters");
+        let config = Self {
+            requests_per_second,
+            burst,
+        };
+        TokenBucket::try_new(config.requests_per_second, config.burst)?;
+        Ok(config)
+    }
+}
+
+impl Display for QuotaConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}rps", self.requests_per_second)?;
+        if let Some(burst) = self.burst {
+            write!(f, ",burst={burst}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Limits how many requests each of many keys (e.g. the IP addresses of API
+/// clients) may make. Unlike a [`super::RateLimiter`] this limits requests
+/// coming in instead of requests going out, so exceeding the quota doesn't
+/// wait or back off but tells the caller how long the key has to wait.
+#[derive(Debug)]
+pub struct Quota<K> {
+    name: String,
+    config: QuotaConfig,
+    state: Mutex<State<K>>,
+}
+
+#[derive(Debug)]
+struct State<K> {
+    buckets: HashMap<K, TokenBucket>,
+    cleanup_threshold: usize,
+}
+
+impl<K: Eq + Hash> Quota<K> {
+    pub fn try_new(name: String, config: QuotaConfig) -> Result<Self> {
+        TokenBucket::try_new(config.requests_per_second, config.burst)?;
+        metrics().quota_exceeded.with_label_values(&[&name]).reset();
+        Ok(Self {
+            name,
+            config,
+            state: Mutex::new(State {
+                buckets: Default::default(),
+                cleanup_threshold: MIN_CLEANUP_THRESHOLD,
+            }),
+        })
+    }
+
+    /// Uses up one request of the key's quota. If the quota is exhausted
+    /// returns how long the key has to wait until the next request is allowed.
+    pub fn try_acquire(&self, key: K) -> Result<(), Duration> {
+        self.try_acquire_at(key, Instant::now())
+    }
+
+    fn try_acquire_at(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        if state.buckets.len() >= state.cleanup_threshold {
+            state.cleanup(now);
+        }
+        let bucket = state.buckets.entry(key).or_insert_with(|| {
+            // The config was validated on construction.
+            TokenBucket::try_new(self.config.requests_per_second, self.config.burst).unwrap()
+        });
+        if bucket.try_acquire(now) {
+            return Ok(());
+        }
+        metrics()
+            .quota_exceeded
+            .with_label_values(&[&self.name])
+            .inc();
+        Err(bucket.time_until_token(now).unwrap_or_default())
+    }
+}
+
+impl<K> State<K> {
+    /// Forgets keys that didn't use their quota recently. Their buckets are
+    /// full, so they would get the same quota as keys that are new.
+    fn cleanup(&mut self, now: Instant) {
+        self.buckets.retain(|_, bucket| {
+            bucket.refill(now);
+            bucket.tokens < bucket.burst
+        });
+        self.cleanup_threshold = (self.buckets.len() * 2).max(MIN_CLEANUP_THRESHOLD);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_config() {
+        assert_eq!(
+            "2rps".parse::<QuotaConfig>().unwrap(),
+            QuotaConfig {
+                requests_per_second: 2.,
+                burst: None,
+            }
+        );
+        let config = "0.5rps,burst=3".parse::<QuotaConfig>().unwrap();
+        assert_eq!(config.burst, Some(3.));
+        assert_eq!(config.to_string().parse::<QuotaConfig>().unwrap(), config);
+        assert!("2".parse::<QuotaConfig>().is_err());
+        assert!("0rps".parse::<QuotaConfig>().is_err());
+        assert!("2rps,3".parse::<QuotaConfig>().is_err());
+        assert!("2rps,burst=3,4".parse::<QuotaConfig>().is_err());
+    }
+
+    #[test]
+    fn limits_every_key_separately() {
+        let config = QuotaConfig {
+            requests_per_second: 1.,
+            burst: Some(2.),
+        };
+        let quota = Quota::try_new("test_quota".into(), config).unwrap();
+        let now = Instant::now();
+
+        assert_eq!(quota.try_acquire_at(1, now), Ok(()));
+        assert_eq!(quota.try_acquire_at(1, now), Ok(()));
+        assert_eq!(quota.try_acquire_at(1, now), Err(Duration::from_secs(1)));
+        // Other keys have their own quota.
+        assert_eq!(quota.try_acquire_at(2, now), Ok(()));
+        // The quota refills over time.
+        let later = now + Duration::from_millis(500);
+        assert_eq!(
+            quota.try_acquire_at(1, later),
+            Err(Duration::from_millis(500))
+        );
+        assert_eq!(
+            quota.try_acquire_at(1, now + Duration::from_secs(1)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn forgets_idle_keys() {
+        let config = QuotaConfig {
+            requests_per_second: 1.,
+            burst: None,
+        };
+        let quota = Quota::try_new("test_quota_cleanup".into(), config).unwrap();
+        let now = Instant::now();
+        for key in 0..MIN_CLEANUP_THRESHOLD {
+            quota.try_acquire_at(key, now).unwrap();
+        }
+        // Only the key that just used its quota is kept.
+        quota
+            .try_acquire_at(0, now + Duration::from_secs(1))
+            .unwrap();
+        let state = quota.state.lock().unwrap();
+        assert_eq!(state.buckets.len(), 1);
+        assert_eq!(state.cleanup_threshold, MIN_CLEANUP_THRESHOLD);
+    }
+}
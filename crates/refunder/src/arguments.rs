@@ -1,8 +1,14 @@
 use {
+    anyhow::{Context, Result},
     clap::Parser,
     ethcontract::H160,
+    serde::Deserialize,
     shared::{arguments::display_option, ethrpc, http_client, logging_args_with_default_filter},
-    std::time::Duration,
+    std::{
+        fmt::{self, Display, Formatter},
+        path::{Path, PathBuf},
+        time::Duration,
+    },
     tracing::level_filters::LevelFilter,
     url::Url,
 };
@@ -26,7 +32,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "2m",
-        value_parser = humantime::parse_duration,
+        value_parser = shared::arguments::parse_duration,
     )]
     pub min_validity_duration: Duration,
 
@@ -52,16 +58,31 @@ pub struct Arguments {
     #[clap(long, env)]
     pub chain_id: Option<u64>,
 
-    /// Address of the ethflow contract
-    #[clap(long, env)]
-    pub ethflow_contract: H160,
+    /// Addresses of the ethflow contracts to refund orders of.
+    #[clap(
+        long,
+        env,
+        alias = "ethflow-contract",
+        use_value_delimiter = true,
+        required = true
+    )]
+    pub ethflow_contracts: Vec<H160>,
 
     #[clap(long, env, hide_env_values = true)]
     pub refunder_pk: String,
 
-    /// The port at which we serve our metrics
-    #[clap(long, env, default_value = "9590")]
-    pub metrics_port: u16,
+    /// Path to a TOML file configuring additional chains to refund orders on
+    /// with the same service. Every chain is a `[[chain]]` table with the
+    /// `node-url`, `db-url`, `refunder-pk` and `ethflow-contracts` keys. The
+    /// file contains secrets, so it should be mounted like the other secrets
+    /// of the service.
+    #[clap(long, env)]
+    pub additional_chains_config: Option<PathBuf>,
+
+    /// The port at which we serve our metrics. Defaults to the `metrics-port`
+    /// of the observability config, or 9590.
+    #[clap(long, env)]
+    pub metrics_port: Option<u16>,
 }
 
 impl std::fmt::Display for Arguments {
@@ -73,11 +94,12 @@ impl std::fmt::Display for Arguments {
             min_slippage_bps,
             node_url,
             chain_id,
-            ethflow_contract,
+            ethflow_contracts,
             metrics_port,
             logging,
             db_url,
             refunder_pk,
+            additional_chains_config,
         } = self;
 
         write!(f, "{}", http_client)?;
@@ -89,10 +111,108 @@ impl std::fmt::Display for Arguments {
         writeln!(f, "db_url: SECRET")?;
         writeln!(f, "node_url: {}", node_url)?;
         display_option(f, "chain_id", chain_id)?;
-        writeln!(f, "ethflow_contract: {:?}", ethflow_contract)?;
+        writeln!(f, "ethflow_contracts: {:?}", ethflow_contracts)?;
         let _intentionally_ignored = refunder_pk;
         writeln!(f, "refunder_pk: SECRET")?;
-        writeln!(f, "metrics_port: {}", metrics_port)?;
+        display_option(f, "metrics_port", metrics_port)?;
+        display_option(
+            f,
+            "additional_chains_config",
+            &additional_chains_config.as_ref().map(|path| path.display()),
+        )?;
         Ok(())
     }
 }
+
+/// Connection details and ethflow contracts of a chain to refund orders on.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ChainArguments {
+    pub node_url: Url,
+    pub db_url: Url,
+    pub refunder_pk: String,
+    pub ethflow_contracts: Vec<H160>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChainsConfig {
+    #[serde(default)]
+    chain: Vec<ChainArguments>,
+}
+
+/// Reads the additional chains from the TOML file.
+pub fn load_chains(path: &Path) -> Result<Vec<ChainArguments>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("reading chains config {}", path.display()))?;
+    parse_chains(&content).with_context(|| format!("parsing chains config {}", path.display()))
+}
+
+fn parse_chains(content: &str) -> Result<Vec<ChainArguments>> {
+    // Only report the message of parsing errors, since their full form
+    // quotes the offending line which could contain secrets.
+    let config: ChainsConfig =
+        toml::from_str(content).map_err(|err| anyhow::anyhow!("{}", err.message()))?;
+    Ok(config.chain)
+}
+
+impl Display for ChainArguments {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node_url: {}, db_url: SECRET, refunder_pk: SECRET, ethflow_contracts: {:?}",
+            self.node_url, self.ethflow_contracts
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_chains_config() {
+        let chains = parse_chains(
+            r#"
+            [[chain]]
+            node-url = "http://node:8545"
+            db-url = "postgresql://db"
+            refunder-pk = "0x01"
+            ethflow-contracts = [
+                "0x0000000000000000000000000000000000000001",
+                "0x0000000000000000000000000000000000000002",
+            ]
+            "#,
+        )
+        .unwrap();
+        let [chain] = &chains[..] else {
+            panic!("expected a single chain");
+        };
+        assert_eq!(chain.node_url.as_str(), "http://node:8545/");
+        assert_eq!(chain.db_url.as_str(), "postgresql://db");
+        assert_eq!(chain.refunder_pk, "0x01");
+        assert_eq!(
+            chain.ethflow_contracts,
+            [H160::from_low_u64_be(1), H160::from_low_u64_be(2)]
+        );
+
+        assert!(parse_chains("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn chains_config_errors_do_not_leak_secrets() {
+        let err = parse_chains(
+            r#"
+            [[chain]]
+            node-url = "http://node:8545"
+            db-url = "postgresql://user:hunter2@db"
+            refunder-pk = "0xsecret"
+            ethflow-contracts = ["not an address"]
+            "#,
+        )
+        .unwrap_err();
+        let err = format!("{err:?}");
+        assert!(!err.contains("hunter2"), "{err}");
+        assert!(!err.contains("0xsecret"), "{err}");
+    }
+}
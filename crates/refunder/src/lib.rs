@@ -4,7 +4,8 @@ pub mod refund_service;
 pub mod submitter;
 
 use {
-    crate::arguments::Arguments,
+    crate::arguments::{load_chains, Arguments, ChainArguments},
+    chain::Chain,
     clap::Parser,
     contracts::CoWSwapEthFlow,
     ethcontract::{Account, PrivateKey},
@@ -16,17 +17,22 @@ use {
         sync::{Arc, RwLock},
         time::{Duration, Instant},
     },
+    tracing::Instrument,
 };
 
+/// Minimum time between two refunding loops of a chain. The actual interval
+/// is rounded up to a multiple of the chain's block time.
 const LOOP_INTERVAL: Duration = Duration::from_secs(30);
-const DELAY_FROM_LAST_LOOP_BEFORE_UNHEALTHY: Duration = LOOP_INTERVAL.saturating_mul(4);
+/// Number of loop intervals without a successful loop after which a chain is
+/// considered unhealthy.
+const LOOPS_BEFORE_UNHEALTHY: u32 = 4;
+const DEFAULT_METRICS_PORT: u16 = 9590;
 
 pub async fn start(args: impl Iterator<Item = String>) {
-    let args = Arguments::parse_from(args);
-    observe::tracing::initialize(
-        args.logging.log_filter.as_str(),
-        args.logging.log_stderr_threshold,
-    );
+    let mut args = Arguments::parse_from(args);
+    let observe_config = args.logging.observe_config();
+    observe::tracing::initialize_with_config(&observe_config);
+    args.metrics_port = args.metrics_port.or(observe_config.metrics_port);
     observe::panic_hook::install();
     tracing::info!("running refunder with validated arguments:\n{}", args);
     observe::metrics::setup_registry(Some("refunder".into()), None);
@@ -35,63 +41,159 @@ pub async fn start(args: impl Iterator<Item = String>) {
 
 pub async fn run(args: arguments::Arguments) {
     let http_factory = HttpClientFactory::new(&args.http_client);
-    let web3 = shared::ethrpc::web3(&args.ethrpc, &http_factory, &args.node_url, "base");
-    if let Some(expected_chain_id) = args.chain_id {
+    let additional_chains = match &args.additional_chains_config {
+        Some(path) => load_chains(path).expect("invalid additional chains config"),
+        None => Vec::new(),
+    };
+    let chains = std::iter::once(ChainArguments {
+        node_url: args.node_url.clone(),
+        db_url: args.db_url.clone(),
+        refunder_pk: args.refunder_pk.clone(),
+        ethflow_contracts: args.ethflow_contracts.clone(),
+    })
+    .chain(additional_chains);
+
+    let mut refunders = Vec::new();
+    for (i, chain) in chains.enumerate() {
+        let web3 = shared::ethrpc::web3(&args.ethrpc, &http_factory, &chain.node_url, "base");
         let chain_id = web3
             .eth()
             .chain_id()
             .await
             .expect("Could not get chainId")
             .as_u64();
-        assert_eq!(
-            chain_id, expected_chain_id,
-            "connected to node with incorrect chain ID",
+        // The expected chain ID only applies to the primary chain.
+        if let (0, Some(expected_chain_id)) = (i, args.chain_id) {
+            assert_eq!(
+                chain_id, expected_chain_id,
+                "connected to node with incorrect chain ID",
+            );
+        }
+        let pg_pool =
+            PgPool::connect_lazy(chain.db_url.as_str()).expect("failed to create database");
+        // Requests to the nodes of all chains count towards the RPC budget
+        // shared with the services of the primary chain.
+        if i == 0 {
+            args.ethrpc.budget.configure(Some(pg_pool.clone()));
+        }
+        let ethflow_contracts = chain
+            .ethflow_contracts
+            .iter()
+            .map(|address| CoWSwapEthFlow::at(&web3, *address))
+            .collect();
+        let refunder_account =
+            Account::Offline(chain.refunder_pk.parse::<PrivateKey>().unwrap(), None);
+        let refunder = RefundService::new(
+            pg_pool,
+            web3,
+            ethflow_contracts,
+            i64::try_from(args.min_validity_duration.as_secs()).unwrap_or(i64::MAX),
+            args.min_slippage_bps,
+            refunder_account,
         );
+        let interval = match Chain::try_from(chain_id) {
+            Ok(chain) => loop_interval(chain.block_time_in_ms()),
+            Err(_) => LOOP_INTERVAL,
+        };
+        refunders.push((chain_id, interval, refunder));
     }
 
-    let pg_pool = PgPool::connect_lazy(args.db_url.as_str()).expect("failed to create database");
-
     let liveness = Arc::new(Liveness {
-        // Program will be healthy at the start even if no loop was ran yet.
-        last_successful_loop: RwLock::new(Instant::now()),
+        chains: refunders
+            .iter()
+            .map(|(chain_id, interval, _)| ChainLiveness {
+                chain_id: *chain_id,
+                max_delay: interval.saturating_mul(LOOPS_BEFORE_UNHEALTHY),
+                // Program will be healthy at the start even if no loop was ran yet.
+                last_successful_loop: RwLock::new(Instant::now()),
+            })
+            .collect(),
     });
-    observe::metrics::serve_metrics(liveness.clone(), ([0, 0, 0, 0], args.metrics_port).into());
-
-    let ethflow_contract = CoWSwapEthFlow::at(&web3, args.ethflow_contract);
-    let refunder_account = Account::Offline(args.refunder_pk.parse::<PrivateKey>().unwrap(), None);
-    let mut refunder = RefundService::new(
-        pg_pool,
-        web3,
-        ethflow_contract,
-        i64::try_from(args.min_validity_duration.as_secs()).unwrap_or(i64::MAX),
-        args.min_slippage_bps,
-        refunder_account,
-    );
+    let metrics_port = args.metrics_port.unwrap_or(DEFAULT_METRICS_PORT);
+    observe::health::HealthServer::default()
+        .with("refunder", liveness.clone())
+        .serve(([0, 0, 0, 0], metrics_port).into());
+
+    let loops = refunders
+        .into_iter()
+        .enumerate()
+        .map(|(i, (chain_id, interval, refunder))| {
+            let liveness = liveness.clone();
+            async move {
+                run_loop(refunder, interval, &liveness.chains[i]).await;
+            }
+            .instrument(tracing::info_span!("chain", id = chain_id))
+        });
+    futures::future::join_all(loops).await;
+}
+
+async fn run_loop(mut refunder: RefundService, interval: Duration, liveness: &ChainLiveness) {
+    let chain = liveness.chain_id.to_string();
     loop {
         tracing::info!("Staring a new refunding loop");
         match refunder.try_to_refund_all_eligble_orders().await {
             Ok(_) => {
-                track_refunding_loop_result("success");
+                track_refunding_loop_result(&chain, "success");
                 *liveness.last_successful_loop.write().unwrap() = Instant::now()
             }
             Err(err) => {
-                track_refunding_loop_result("error");
+                track_refunding_loop_result(&chain, "error");
                 tracing::warn!("Error while refunding ethflow orders: {:?}", err)
             }
         }
-        tokio::time::sleep(LOOP_INTERVAL).await;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Rounds the loop interval up to a multiple of the block time so that every
+/// loop sees a new block.
+fn loop_interval(block_time: Duration) -> Duration {
+    if block_time.is_zero() {
+        return LOOP_INTERVAL;
     }
+    let blocks = LOOP_INTERVAL.as_nanos().div_ceil(block_time.as_nanos());
+    block_time.saturating_mul(u32::try_from(blocks).unwrap_or(u32::MAX))
 }
 
 struct Liveness {
+    chains: Vec<ChainLiveness>,
+}
+
+struct ChainLiveness {
+    chain_id: u64,
+    max_delay: Duration,
     last_successful_loop: RwLock<Instant>,
 }
 
+impl ChainLiveness {
+    fn is_alive(&self) -> bool {
+        Instant::now().duration_since(*self.last_successful_loop.read().unwrap()) < self.max_delay
+    }
+}
+
 #[async_trait::async_trait]
 impl LivenessChecking for Liveness {
+    /// The service is alive as long as any chain gets refunded, so a single
+    /// failing chain (e.g. its node being down) doesn't get the refunders of
+    /// the healthy chains restarted. The health of every chain gets reported
+    /// separately.
     async fn is_alive(&self) -> bool {
-        Instant::now().duration_since(*self.last_successful_loop.read().unwrap())
-            < DELAY_FROM_LAST_LOOP_BEFORE_UNHEALTHY
+        let mut any_alive = false;
+        for chain in &self.chains {
+            let alive = chain.is_alive();
+            if !alive {
+                tracing::warn!(
+                    chain = chain.chain_id,
+                    "no recent successful refunding loop"
+                );
+            }
+            Metrics::get()
+                .chain_healthy
+                .with_label_values(&[&chain.chain_id.to_string()])
+                .set(alive.into());
+            any_alive |= alive;
+        }
+        any_alive
     }
 }
 
@@ -99,14 +201,60 @@ impl LivenessChecking for Liveness {
 #[metric(subsystem = "main")]
 struct Metrics {
     /// Tracks the result of every refunding loops.
-    #[metric(labels("result"))]
+    #[metric(labels("chain", "result"))]
     refunding_loops: prometheus::IntCounterVec,
+
+    /// Whether the chain had a recent successful refunding loop.
+    #[metric(labels("chain"))]
+    chain_healthy: prometheus::IntGaugeVec,
 }
 
-fn track_refunding_loop_result(result: &str) {
-    Metrics::instance(observe::metrics::get_storage_registry())
-        .expect("unexpected error getting metrics instance")
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry())
+            .expect("unexpected error getting metrics instance")
+    }
+}
+
+fn track_refunding_loop_result(chain: &str, result: &str) {
+    Metrics::get()
         .refunding_loops
-        .with_label_values(&[result])
+        .with_label_values(&[chain, result])
         .inc();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loop_interval_is_multiple_of_block_time() {
+        assert_eq!(
+            loop_interval(Duration::from_secs(12)),
+            Duration::from_secs(36)
+        );
+        assert_eq!(loop_interval(Duration::from_secs(5)), LOOP_INTERVAL);
+        assert_eq!(loop_interval(Duration::from_millis(250)), LOOP_INTERVAL);
+        assert_eq!(loop_interval(Duration::ZERO), LOOP_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn alive_while_any_chain_is_alive() {
+        let chain = |chain_id, max_delay| ChainLiveness {
+            chain_id,
+            max_delay,
+            last_successful_loop: RwLock::new(Instant::now()),
+        };
+        let liveness = Liveness {
+            chains: vec![chain(1, Duration::ZERO), chain(2, Duration::MAX)],
+        };
+        assert!(!liveness.chains[0].is_alive());
+        assert!(liveness.chains[1].is_alive());
+        assert!(liveness.is_alive().await);
+
+        let liveness = Liveness {
+            chains: vec![chain(1, Duration::ZERO), chain(2, Duration::ZERO)],
+        };
+        assert!(!liveness.is_alive().await);
+    }
+}
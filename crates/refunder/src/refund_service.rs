@@ -16,7 +16,9 @@ use {
         MAX_BATCH_SIZE,
     },
     futures::{stream, StreamExt},
-    sqlx::PgPool,
+    number::conversions::to_base_units,
+    sqlx::{types::BigDecimal, PgPool},
+    std::collections::HashMap,
 };
 
 pub const NO_OWNER: H160 = H160([0u8; 20]);
@@ -26,9 +28,9 @@ const MAX_NUMBER_OF_UIDS_PER_REFUND_TX: usize = 30;
 pub struct RefundService {
     pub db: PgPool,
     pub web3: Web3,
-    pub ethflow_contract: CoWSwapEthFlow,
+    pub ethflow_contracts: Vec<CoWSwapEthFlow>,
     pub min_validity_duration: i64,
-    pub min_slippage: f64,
+    pub min_slippage: BigDecimal,
     pub submitter: Submitter,
 }
 
@@ -43,7 +45,7 @@ impl RefundService {
     pub fn new(
         db: PgPool,
         web3: Web3,
-        ethflow_contract: CoWSwapEthFlow,
+        ethflow_contracts: Vec<CoWSwapEthFlow>,
         min_validity_duration: i64,
         min_slippage_bps: u64,
         account: Account,
@@ -51,12 +53,12 @@ impl RefundService {
         RefundService {
             db,
             web3: web3.clone(),
-            ethflow_contract: ethflow_contract.clone(),
+            ethflow_contracts,
             min_validity_duration,
-            min_slippage: min_slippage_bps as f64 / 10000f64,
+            // Basis points are ten thousandths, i.e. units with 4 decimals.
+            min_slippage: to_base_units(min_slippage_bps.into(), 4),
             submitter: Submitter {
                 web3: web3.clone(),
-                ethflow_contract,
                 account,
                 gas_estimator: Box::new(web3),
                 gas_parameters_of_last_tx: None,
@@ -68,12 +70,43 @@ impl RefundService {
     pub async fn try_to_refund_all_eligble_orders(&mut self) -> Result<()> {
         let refundable_order_uids = self.get_refundable_ethflow_orders_from_db().await?;
 
-        let to_be_refunded_uids = self
-            .identify_uids_refunding_status_via_web3_calls(refundable_order_uids)
-            .await?;
+        // The owner of ethflow orders is the contract they were placed with.
+        let mut refundable_order_uids_by_contract = HashMap::<_, Vec<_>>::new();
+        for order in refundable_order_uids {
+            refundable_order_uids_by_contract
+                .entry(H160::from_slice(&order.uid.0[32..52]))
+                .or_default()
+                .push(order);
+        }
 
-        self.send_out_refunding_tx(to_be_refunded_uids).await?;
-        Ok(())
+        // Refunding is attempted for all contracts even if some of them fail.
+        let mut result = Ok(());
+        for contract in self.ethflow_contracts.clone() {
+            let Some(refundable_order_uids) =
+                refundable_order_uids_by_contract.remove(&contract.address())
+            else {
+                continue;
+            };
+            let refunded = async {
+                let to_be_refunded_uids = self
+                    .identify_uids_refunding_status_via_web3_calls(&contract, refundable_order_uids)
+                    .await?;
+                self.send_out_refunding_tx(&contract, to_be_refunded_uids)
+                    .await
+            }
+            .await;
+            if let Err(err) = refunded {
+                tracing::warn!(contract = ?contract.address(), ?err, "failed to refund orders");
+                result = result.and(Err(err));
+            }
+        }
+        if !refundable_order_uids_by_contract.is_empty() {
+            tracing::debug!(
+                contracts = ?refundable_order_uids_by_contract.keys().collect::<Vec<_>>(),
+                "skipping refundable orders of unknown ethflow contracts"
+            );
+        }
+        result
     }
 
     pub async fn get_refundable_ethflow_orders_from_db(&self) -> Result<Vec<EthOrderPlacement>> {
@@ -84,7 +117,7 @@ impl RefundService {
             &mut ex,
             block_time,
             self.min_validity_duration,
-            self.min_slippage,
+            &self.min_slippage,
         )
         .await
         .map_err(|err| {
@@ -97,6 +130,7 @@ impl RefundService {
 
     async fn identify_uids_refunding_status_via_web3_calls(
         &self,
+        ethflow_contract: &CoWSwapEthFlow,
         refundable_order_uids: Vec<EthOrderPlacement>,
     ) -> Result<Vec<OrderUid>> {
         let mut batch = Web3CallBatch::new(self.web3.transport().clone());
@@ -106,8 +140,7 @@ impl RefundService {
                 let order_hash: [u8; 32] = eth_order_placement.uid.0[0..32]
                     .try_into()
                     .expect("order_uid slice with incorrect length");
-                let order = self
-                    .ethflow_contract
+                let order = ethflow_contract
                     .orders(ethcontract::tokens::Bytes(order_hash))
                     .batch_call(&mut batch);
                 async move {
@@ -170,7 +203,11 @@ impl RefundService {
         Ok(order_to_ethflow_data(order, ethflow_order))
     }
 
-    async fn send_out_refunding_tx(&mut self, uids: Vec<OrderUid>) -> Result<()> {
+    async fn send_out_refunding_tx(
+        &mut self,
+        ethflow_contract: &CoWSwapEthFlow,
+        uids: Vec<OrderUid>,
+    ) -> Result<()> {
         if uids.is_empty() {
             return Ok(());
         }
@@ -206,7 +243,9 @@ impl RefundService {
             .collect()
             .await;
 
-        self.submitter.submit(uids, encoded_ethflow_orders).await?;
+        self.submitter
+            .submit(ethflow_contract, uids, encoded_ethflow_orders)
+            .await?;
         Ok(())
     }
 }
@@ -43,7 +43,6 @@ const GAS_PRICE_BUMP: f64 = 1.125;
 
 pub struct Submitter {
     pub web3: Web3,
-    pub ethflow_contract: CoWSwapEthFlow,
     pub account: Account,
     pub gas_estimator: Box<dyn GasPriceEstimating>,
     pub gas_parameters_of_last_tx: Option<GasPrice1559>,
@@ -63,6 +62,7 @@ impl Submitter {
 
     pub async fn submit(
         &mut self,
+        ethflow_contract: &CoWSwapEthFlow,
         uids: Vec<OrderUid>,
         encoded_ethflow_orders: Vec<EncodedEthflowOrder>,
     ) -> Result<()> {
@@ -82,8 +82,7 @@ impl Submitter {
 
         self.gas_parameters_of_last_tx = Some(gas_price);
         self.nonce_of_last_submission = Some(nonce);
-        let tx_result = self
-            .ethflow_contract
+        let tx_result = ethflow_contract
             .invalidate_orders_ignoring_not_allowed(encoded_ethflow_orders)
             .gas_price(into_gas_price(&gas_price))
             .from(self.account.clone())
@@ -0,0 +1,297 @@
+//! [`ObjectStore`] backed by an AWS S3 bucket.
+
+use {
+    crate::{Object, ObjectStore, Parts},
+    anyhow::{Context, Result},
+    aws_sdk_s3::{
+        operation::get_object::GetObjectError,
+        primitives::ByteStream,
+        types::{CompletedMultipartUpload, CompletedPart},
+        Client,
+    },
+    chrono::DateTime,
+    futures::{stream, StreamExt, TryStreamExt},
+    tokio::task::JoinSet,
+};
+
+#[derive(Debug)]
+pub struct Store {
+    bucket: String,
+    upload_concurrency: usize,
+    client: Client,
+}
+
+impl Store {
+    pub async fn new(bucket: String, upload_concurrency: usize) -> Self {
+        Self {
+            bucket,
+            upload_concurrency,
+            client: Client::new(&aws_config::from_env().load().await),
+        }
+    }
+
+    /// Uploads all parts of a multipart upload with up to
+    /// `upload_concurrency` parts in flight. Returns the uploaded parts in
+    /// order.
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        mut parts: Parts<'_>,
+    ) -> Result<Vec<CompletedPart>> {
+        let mut in_flight = JoinSet::new();
+        let mut completed = Vec::new();
+        let mut part_number = 0;
+
+        while let Some(body) = parts.try_next().await? {
+            part_number += 1;
+            if in_flight.len() >= self.upload_concurrency {
+                if let Some(part) = in_flight.join_next().await {
+                    completed.push(part??);
+                }
+            }
+            let request = self
+                .client
+                .upload_part()
+                .bucket(self.bucket.clone())
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from(body));
+            in_flight.spawn(async move {
+                let response = request.send().await?;
+                Ok::<_, anyhow::Error>(
+                    CompletedPart::builder()
+                        .set_e_tag(response.e_tag().map(str::to_string))
+                        .part_number(part_number)
+                        .build(),
+                )
+            });
+        }
+        while let Some(part) = in_flight.join_next().await {
+            completed.push(part??);
+        }
+
+        completed.sort_by_key(|part| part.part_number());
+        Ok(completed)
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for Store {
+    /// Objects consisting of a single part are uploaded with a single request,
+    /// bigger ones with a multipart upload.
+    async fn put(&self, key: &str, mut parts: Parts<'_>) -> Result<()> {
+        let first = parts.try_next().await?.unwrap_or_default();
+        let Some(second) = parts.try_next().await? else {
+            self.client
+                .put_object()
+                .bucket(self.bucket.clone())
+                .key(key)
+                .body(ByteStream::from(first))
+                .content_encoding("gzip")
+                .content_type("application/json")
+                .send()
+                .await?;
+            return Ok(());
+        };
+        let parts = stream::iter([Ok(first), Ok(second)]).chain(parts).boxed();
+
+        let upload = self
+            .client
+            .create_multipart_upload()
+            .bucket(self.bucket.clone())
+            .key(key)
+            .content_encoding("gzip")
+            .content_type("application/json")
+            .send()
+            .await?;
+        let upload_id = upload
+            .upload_id()
+            .context("multipart upload without id")?
+            .to_string();
+
+        match self.upload_parts(key, &upload_id, parts).await {
+            Ok(parts) => {
+                self.client
+                    .complete_multipart_upload()
+                    .bucket(self.bucket.clone())
+                    .key(key)
+                    .upload_id(upload_id)
+                    .multipart_upload(
+                        CompletedMultipartUpload::builder()
+                            .set_parts(Some(parts))
+                            .build(),
+                    )
+                    .send()
+                    .await?;
+                Ok(())
+            }
+            Err(err) => {
+                if let Err(err) = self
+                    .client
+                    .abort_multipart_upload()
+                    .bucket(self.bucket.clone())
+                    .key(key)
+                    .upload_id(upload_id)
+                    .send()
+                    .await
+                {
+                    tracing::warn!(?err, "failed to abort multipart upload");
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let object = match self
+            .client
+            .get_object()
+            .bucket(self.bucket.clone())
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(object) => object,
+            Err(err) => match err.as_service_error() {
+                Some(GetObjectError::NoSuchKey(_)) => return Ok(None),
+                _ => return Err(err.into()),
+            },
+        };
+        Ok(Some(object.body.collect().await?.to_vec()))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<Object>> {
+        let mut pages = self
+            .client
+            .list_objects_v2()
+            .bucket(self.bucket.clone())
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+        let mut objects = Vec::new();
+        while let Some(page) = pages.next().await {
+            for object in page?.contents() {
+                let (Some(key), Some(last_modified)) = (object.key(), object.last_modified())
+                else {
+                    continue;
+                };
+                objects.push(Object {
+                    key: key.to_string(),
+                    last_modified: DateTime::from_timestamp(
+                        last_modified.secs(),
+                        last_modified.subsec_nanos(),
+                    )
+                    .context("invalid last modified timestamp")?,
+                });
+            }
+        }
+        Ok(objects)
+    }
+
+    /// S3 doesn't report an error for keys that don't exist.
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object()
+            .bucket(self.bucket.clone())
+            .key(key)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{json_reader, Backend, Config, Uploader, MIN_PART_SIZE},
+        flate2::read::GzDecoder,
+        serde_json::json,
+        std::io::Read,
+    };
+
+    // These tests require AWS credentials to be set via env variables.
+    // See https://docs.rs/aws-config/latest/aws_config/default_provider/credentials/struct.DefaultCredentialsChain.html
+    // to know which arguments are expected and in what precedence they
+    // get loaded.
+    #[tokio::test]
+    #[ignore]
+    async fn real_upload() {
+        let config = Config {
+            backend: Backend::S3 {
+                bucket: std::env::var("BUCKET").unwrap(),
+            },
+            filename_prefix: "test/".to_string(),
+            ..Default::default()
+        };
+
+        // Upload a reasonable amount of data. This helps see the benefits of
+        // compression.
+        let value = serde_json::to_string(&json!({
+            "content": include_str!("../../../README.md"),
+            "timestamp": chrono::Utc::now().to_string(),
+        }))
+        .unwrap();
+
+        let uploader = Uploader::new(config).await.unwrap();
+        let key = uploader
+            .upload("test".to_string(), value.as_bytes())
+            .await
+            .unwrap();
+
+        let mut decoded = String::new();
+        GzDecoder::new(download(&key).await.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(value, decoded);
+    }
+
+    // Same as above but big and random enough to require a multipart upload.
+    #[tokio::test]
+    #[ignore]
+    async fn real_multipart_upload() {
+        let config = Config {
+            backend: Backend::S3 {
+                bucket: std::env::var("BUCKET").unwrap(),
+            },
+            filename_prefix: "test/".to_string(),
+            part_size: MIN_PART_SIZE,
+            ..Default::default()
+        };
+        // Random hex strings which don't compress well.
+        let mut state = 0x9e3779b97f4a7c15_u64;
+        let value: Vec<String> = (0..2_000_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                format!("{state:x}")
+            })
+            .collect();
+
+        let uploader = Uploader::new(config).await.unwrap();
+        let key = uploader
+            .upload_stream("test_multipart".to_string(), json_reader(value.clone()))
+            .await
+            .unwrap();
+
+        let body = download(&key).await;
+        assert!(body.len() > MIN_PART_SIZE);
+        let decoded: Vec<String> =
+            serde_json::from_reader(GzDecoder::new(body.as_slice())).unwrap();
+        assert_eq!(value, decoded);
+    }
+
+    async fn download(key: &str) -> Vec<u8> {
+        let client = aws_sdk_s3::Client::new(&aws_config::from_env().load().await);
+        let object = client
+            .get_object()
+            .bucket(std::env::var("BUCKET").unwrap())
+            .key(key)
+            .send()
+            .await
+            .unwrap();
+        object.body.collect().await.unwrap().to_vec()
+    }
+}
@@ -0,0 +1,125 @@
+//! Streams the json serialization of a value without buffering it as a whole.
+
+use {
+    serde::Serialize,
+    std::{
+        io::{self, BufWriter, Write},
+        pin::Pin,
+        task::{ready, Context, Poll},
+    },
+    tokio::{
+        io::{AsyncRead, ReadBuf},
+        sync::mpsc,
+    },
+};
+
+/// Size of the chunks the serialized json gets handed over in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How many serialized chunks may be buffered before serialization waits for
+/// the reader to catch up.
+const BUFFERED_CHUNKS: usize = 4;
+
+/// Serializes `value` as json on a blocking thread and returns a reader
+/// yielding the serialized bytes, so large values can be passed to
+/// [`crate::Uploader::upload_stream`] without allocating the whole json.
+///
+/// Serialization errors surface as errors of the reader.
+pub fn json_reader<T>(value: T) -> impl AsyncRead + Unpin + Send
+where
+    T: Serialize + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(BUFFERED_CHUNKS);
+    tokio::task::spawn_blocking(move || {
+        let mut writer = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter(sender.clone()));
+        let result = serde_json::to_writer(&mut writer, &value)
+            .map_err(io::Error::from)
+            .and_then(|()| writer.flush());
+        if let Err(err) = result {
+            // Fails only if the reader was dropped in which case nobody cares.
+            let _ = sender.blocking_send(Err(err));
+        }
+    });
+    JsonReader {
+        chunks: receiver,
+        current: Vec::new(),
+        offset: 0,
+    }
+}
+
+struct ChannelWriter(mpsc::Sender<io::Result<Vec<u8>>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "reader dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct JsonReader {
+    chunks: mpsc::Receiver<io::Result<Vec<u8>>>,
+    current: Vec<u8>,
+    offset: usize,
+}
+
+impl AsyncRead for JsonReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            let remaining = &this.current[this.offset..];
+            if !remaining.is_empty() {
+                let len = remaining.len().min(buf.remaining());
+                buf.put_slice(&remaining[..len]);
+                this.offset += len;
+                return Poll::Ready(Ok(()));
+            }
+            match ready!(this.chunks.poll_recv(cx)) {
+                Some(Ok(chunk)) => {
+                    this.current = chunk;
+                    this.offset = 0;
+                }
+                Some(Err(err)) => return Poll::Ready(Err(err)),
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tokio::io::AsyncReadExt};
+
+    #[tokio::test]
+    async fn streams_serialized_value() {
+        let value: Vec<u64> = (0..100_000).collect();
+
+        let mut streamed = Vec::new();
+        json_reader(value.clone())
+            .read_to_end(&mut streamed)
+            .await
+            .unwrap();
+
+        assert_eq!(streamed, serde_json::to_vec(&value).unwrap());
+    }
+
+    #[tokio::test]
+    async fn surfaces_serialization_errors() {
+        // Maps with non-string keys can't be serialized as json.
+        let value: std::collections::HashMap<Vec<u8>, u8> = [(vec![1], 1)].into();
+
+        let mut streamed = Vec::new();
+        let result = json_reader(value).read_to_end(&mut streamed).await;
+
+        assert!(result.is_err());
+    }
+}
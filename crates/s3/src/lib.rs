@@ -1,132 +1,357 @@
-//! Small abstraction over the AWS S3 SDK to upload arbitrary json object to S3.
+//! Small abstraction over object storages to upload and read back arbitrary
+//! json objects.
+//!
+//! Objects are always stored gzip compressed. The [`Uploader`] takes care of
+//! serialization, compression and naming while the actual storage is
+//! implemented by an [`ObjectStore`]. AWS S3, Google Cloud Storage and the
+//! local filesystem are supported out of the box.
 
 use {
     anyhow::{anyhow, Context, Result},
-    aws_sdk_s3::{primitives::ByteStream, Client},
-    flate2::{bufread::GzEncoder, Compression},
-    serde::Serialize,
-    std::io::Read,
+    chrono::{DateTime, Utc},
+    flate2::{read::GzDecoder, write::GzEncoder, Compression},
+    futures::{stream::BoxStream, StreamExt},
+    serde::{de::DeserializeOwned, Serialize},
+    std::{fmt::Debug, io::Write, ops::RangeBounds, path::PathBuf, sync::Arc},
+    tokio::io::{AsyncRead, AsyncReadExt},
 };
 
-#[derive(Default)]
+mod aws;
+mod gcs;
+mod json;
+mod local;
+
+pub use json::json_reader;
+
+/// S3 rejects multipart uploads with parts (except for the last one) smaller
+/// than this.
+pub const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// How many bytes get read from an upload stream at once.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
 pub struct Config {
-    pub bucket: String,
+    pub backend: Backend,
     /// Prepended to the the final filename for each uploaded object.
     pub filename_prefix: String,
+    /// Size of the compressed parts large objects get uploaded in. Values
+    /// below [`MIN_PART_SIZE`] are raised to it.
+    pub part_size: usize,
+    /// How many parts of a single object may be uploaded at the same time.
+    pub upload_concurrency: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            backend: Backend::S3 {
+                bucket: Default::default(),
+            },
+            filename_prefix: Default::default(),
+            part_size: 8 * 1024 * 1024,
+            upload_concurrency: 4,
+        }
+    }
+}
+
+/// Where objects get stored.
+#[derive(Clone, Debug)]
+pub enum Backend {
+    /// An AWS S3 bucket. Credentials are loaded from the environment.
+    S3 { bucket: String },
+    /// A Google Cloud Storage bucket. The access token is taken from the
+    /// `GCS_ACCESS_TOKEN` environment variable, requested for the service
+    /// account whose key file `GOOGLE_APPLICATION_CREDENTIALS` points to or
+    /// requested from the metadata server of the instance.
+    Gcs { bucket: String },
+    /// A directory on the local filesystem.
+    Local { path: PathBuf },
+}
+
+/// Gzip compressed content passed to an [`ObjectStore`] in parts. All parts
+/// except for the last one are at least as big as the configured part size.
+pub type Parts<'a> = BoxStream<'a, Result<Vec<u8>>>;
+
+/// An object found in an [`ObjectStore`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Object {
+    pub key: String,
+    pub last_modified: DateTime<Utc>,
+}
+
+/// A storage for gzip compressed json objects.
+#[async_trait::async_trait]
+pub trait ObjectStore: Debug + Send + Sync {
+    /// Stores the object consisting of all `parts` under `key`.
+    async fn put(&self, key: &str, parts: Parts<'_>) -> Result<()>;
+
+    /// Returns the still compressed content of the object stored under `key`
+    /// or `None` if there is no such object.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Returns all objects whose key starts with `prefix`.
+    async fn list(&self, prefix: &str) -> Result<Vec<Object>>;
+
+    /// Removes the object stored under `key`. Removing an object that
+    /// doesn't exist is not an error.
+    async fn delete(&self, key: &str) -> Result<()>;
 }
 
 #[derive(Debug, Clone)]
 pub struct Uploader {
-    bucket: String,
+    store: Arc<dyn ObjectStore>,
     filename_prefix: String,
-    client: Client,
+    part_size: usize,
 }
 
 impl Uploader {
-    pub async fn new(config: Config) -> Self {
-        let uploader = Self {
-            bucket: config.bucket,
-            filename_prefix: config.filename_prefix,
-            client: Client::new(&aws_config::from_env().load().await),
+    /// Creates an uploader for the configured backend and verifies that it
+    /// can upload objects.
+    pub async fn new(config: Config) -> Result<Self> {
+        let store: Arc<dyn ObjectStore> = match config.backend {
+            Backend::S3 { bucket } => {
+                Arc::new(aws::Store::new(bucket, config.upload_concurrency.max(1)).await)
+            }
+            Backend::Gcs { bucket } => Arc::new(gcs::Store::new(bucket)?),
+            Backend::Local { path } => Arc::new(local::Store::new(path)),
         };
-        uploader.assert_credentials_are_usable().await;
-        uploader
+        let uploader = Self::with_store(store, config.filename_prefix, config.part_size);
+        uploader.check_uploads_work().await?;
+        Ok(uploader)
+    }
+
+    /// Creates an uploader storing objects in a custom [`ObjectStore`].
+    pub fn with_store(
+        store: Arc<dyn ObjectStore>,
+        filename_prefix: String,
+        part_size: usize,
+    ) -> Self {
+        Self {
+            store,
+            filename_prefix,
+            part_size: part_size.max(MIN_PART_SIZE),
+        }
     }
 
-    /// Upload the bytes json encoded to the configured S3 bucket. Returns the
+    /// Upload the bytes json encoded to the configured storage. Returns the
     /// key under which the file can be queried
     pub async fn upload(&self, id: String, content: impl Serialize) -> Result<String> {
         let bytes = serde_json::to_vec(&content)?;
-        let encoded = self.gzip(&bytes)?;
-        let key = std::path::Path::new(&self.filename_prefix)
-            .join(format!("{id}.json"))
-            .to_str()
-            .context(anyhow!("invalid path: {id}"))?
-            .to_string();
-        self.client
-            .put_object()
-            .bucket(self.bucket.clone())
-            .key(key.clone())
-            .body(ByteStream::new(encoded.into()))
-            .content_encoding("gzip")
-            .content_type("application/json")
-            .send()
+        self.upload_stream(id, bytes.as_slice()).await
+    }
+
+    /// Gzips the json read from `content` and uploads it to the configured
+    /// storage. Returns the key under which the file can be queried.
+    ///
+    /// The content is never buffered as a whole but passed to the storage in
+    /// compressed parts.
+    pub async fn upload_stream(
+        &self,
+        id: String,
+        content: impl AsyncRead + Unpin + Send,
+    ) -> Result<String> {
+        let key = self.key(&format!("{id}.json"))?;
+        self.store
+            .put(&key, compressed_parts(content, self.part_size))
             .await?;
         Ok(key)
     }
 
-    /// Uploads a small test file to verify that the credentials loaded from the
-    /// environment allow uploads to S3.
-    async fn assert_credentials_are_usable(&self) {
+    /// Downloads, decompresses and deserializes the object uploaded with
+    /// `id`. Returns `None` if there is no such object.
+    pub async fn get<T: DeserializeOwned>(&self, id: &str) -> Result<Option<T>> {
+        let key = self.key(&format!("{id}.json"))?;
+        let Some(content) = self.store.get(&key).await? else {
+            return Ok(None);
+        };
+        let value = serde_json::from_reader(GzDecoder::new(content.as_slice()))
+            .with_context(|| format!("decode object {key}"))?;
+        Ok(Some(value))
+    }
+
+    /// Returns the ids of all uploaded objects whose id starts with `prefix`
+    /// and which were last modified within `date_range`, oldest first.
+    pub async fn list(
+        &self,
+        prefix: &str,
+        date_range: impl RangeBounds<DateTime<Utc>>,
+    ) -> Result<Vec<String>> {
+        let base = self.key("")?;
+        let mut objects = self
+            .store
+            .list(&format!("{base}{prefix}"))
+            .await?
+            .into_iter()
+            .filter(|object| date_range.contains(&object.last_modified))
+            .collect::<Vec<_>>();
+        objects.sort_by(|a, b| (a.last_modified, &a.key).cmp(&(b.last_modified, &b.key)));
+        Ok(objects
+            .into_iter()
+            .filter_map(|object| {
+                let id = object.key.strip_prefix(&base)?.strip_suffix(".json")?;
+                Some(id.to_string())
+            })
+            .collect())
+    }
+
+    /// Removes the object uploaded with `id` if it exists.
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let key = self.key(&format!("{id}.json"))?;
+        self.store.delete(&key).await
+    }
+
+    /// Key of the file `name` within the configured prefix.
+    fn key(&self, name: &str) -> Result<String> {
+        Ok(std::path::Path::new(&self.filename_prefix)
+            .join(name)
+            .to_str()
+            .context(anyhow!("invalid path: {name}"))?
+            .to_string())
+    }
+
+    /// Uploads a small test file to verify that the configured storage
+    /// accepts uploads.
+    async fn check_uploads_work(&self) -> Result<()> {
         const DOCS_URL: &str = "https://docs.rs/aws-config/latest/aws_config/default_provider/credentials/struct.DefaultCredentialsChain.html";
         self.upload(
             "test".into(),
             "test file to verify uploading capabilities".as_bytes(),
         )
         .await
-        .unwrap_or_else(|err| {
-            panic!(
-                "Could not upload test file to S3.\n Either disable uploads to S3 by removing the \
-                 s3_instance_upload_* arguments.\n Or make sure your environment variables are \
-                 set up to contain the correct AWS credentials.\n See {DOCS_URL} for more details \
-                 on that. \n{err:?}"
+        .with_context(|| {
+            format!(
+                "Could not upload test file to {:?}.\n Either disable uploads by removing the \
+                 s3_instance_upload_* arguments.\n Or make sure the environment contains the \
+                 correct credentials. For AWS see {DOCS_URL} for more details.",
+                self.store
             )
-        });
+        })?;
+        Ok(())
     }
+}
 
-    /// Compresses the input bytes using Gzip.
-    fn gzip(&self, bytes: &[u8]) -> Result<Vec<u8>> {
-        let mut encoder = GzEncoder::new(bytes, Compression::best());
-        let mut encoded: Vec<u8> = Vec::with_capacity(bytes.len());
-        encoder.read_to_end(&mut encoded).context("gzip encoding")?;
-        Ok(encoded)
-    }
+/// Gzips `content` into parts of at least `part_size` bytes.
+fn compressed_parts<'a>(
+    content: impl AsyncRead + Unpin + Send + 'a,
+    part_size: usize,
+) -> Parts<'a> {
+    let encoder = GzEncoder::new(Vec::new(), Compression::best());
+    futures::stream::try_unfold(Some((encoder, content)), move |state| async move {
+        let Some((mut encoder, mut content)) = state else {
+            return Ok(None);
+        };
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+        while encoder.get_ref().len() < part_size {
+            let read = content.read(&mut chunk).await.context("read content")?;
+            if read == 0 {
+                let last = encoder.finish().context("gzip encoding")?;
+                return Ok(Some((last, None)));
+            }
+            encoder.write_all(&chunk[..read]).context("gzip encoding")?;
+        }
+        let part = std::mem::take(encoder.get_mut());
+        Ok(Some((part, Some((encoder, content)))))
+    })
+    .boxed()
 }
 
 #[cfg(test)]
 mod tests {
-    use {super::*, flate2::read::GzDecoder, serde_json::json};
+    use {
+        super::*,
+        flate2::read::GzDecoder,
+        futures::TryStreamExt,
+        serde_json::json,
+        std::io::Read,
+    };
+
+    #[tokio::test]
+    async fn compresses_content_in_parts() {
+        // Random hex strings which don't compress well.
+        let mut state = 0x9e3779b97f4a7c15_u64;
+        let value: Vec<String> = (0..1_000_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                format!("{state:x}")
+            })
+            .collect();
+
+        let parts: Vec<Vec<u8>> = compressed_parts(json_reader(value.clone()), MIN_PART_SIZE)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(parts.len() > 1);
+        assert!(parts[..parts.len() - 1]
+            .iter()
+            .all(|part| part.len() >= MIN_PART_SIZE));
+
+        let decoded: Vec<String> =
+            serde_json::from_reader(GzDecoder::new(parts.concat().as_slice())).unwrap();
+        assert_eq!(value, decoded);
+    }
 
-    // This test requires AWS credentials to be set via env variables.
-    // See https://docs.rs/aws-config/latest/aws_config/default_provider/credentials/struct.DefaultCredentialsChain.html
-    // to know which arguments are expected and in what precedence they
-    // get loaded.
     #[tokio::test]
-    #[ignore]
-    async fn real_upload() {
+    async fn local_upload() {
+        let dir = std::env::temp_dir().join(format!("s3-local-upload-{}", std::process::id()));
         let config = Config {
-            bucket: std::env::var("BUCKET").unwrap(),
+            backend: Backend::Local { path: dir.clone() },
             filename_prefix: "test/".to_string(),
+            ..Default::default()
         };
 
-        // Upload a reasonable amount of data. This helps see the benefits of
-        // compression.
-        let value = serde_json::to_string(&json!({
-            "content": include_str!("../../../README.md"),
-            "timestamp": chrono::Utc::now().to_string(),
-        }))
-        .unwrap();
-
-        let uploader = Uploader::new(config).await;
+        let uploader = Uploader::new(config).await.unwrap();
         let key = uploader
-            .upload("test".to_string(), value.as_bytes())
+            .upload("local".to_string(), json!({ "hello": "world" }))
             .await
             .unwrap();
 
-        let get_object = uploader
-            .client
-            .get_object()
-            .bucket(uploader.bucket)
-            .key(key)
-            .send()
-            .await
+        let body = std::fs::read(dir.join(key)).unwrap();
+        let mut decoded = String::new();
+        GzDecoder::new(body.as_slice())
+            .read_to_string(&mut decoded)
             .unwrap();
-        let body = get_object.body.collect().await.unwrap().to_vec();
+        assert_eq!(decoded, r#"{"hello":"world"}"#);
 
-        let mut decoder = GzDecoder::new(body.as_slice());
-        let mut decoded = String::new();
-        decoder.read_to_string(&mut decoded).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 
-        assert_eq!(value, decoded);
+    #[tokio::test]
+    async fn local_get_and_list() {
+        let dir = std::env::temp_dir().join(format!("s3-local-list-{}", std::process::id()));
+        let config = Config {
+            backend: Backend::Local { path: dir.clone() },
+            filename_prefix: "auctions/".to_string(),
+            ..Default::default()
+        };
+
+        // Filesystem timestamps can be slightly behind the clock.
+        let start = chrono::Utc::now() - chrono::Duration::seconds(1);
+        let uploader = Uploader::new(config).await.unwrap();
+        for id in ["11", "12", "21"] {
+            uploader
+                .upload(id.to_string(), json!({ "id": id }))
+                .await
+                .unwrap();
+        }
+
+        let value: Option<serde_json::Value> = uploader.get("12").await.unwrap();
+        assert_eq!(value, Some(json!({ "id": "12" })));
+        let missing: Option<serde_json::Value> = uploader.get("13").await.unwrap();
+        assert_eq!(missing, None);
+
+        let mut ids = uploader.list("1", start..).await.unwrap();
+        ids.sort();
+        assert_eq!(ids, ["11", "12"]);
+        assert_eq!(uploader.list("", ..start).await.unwrap(), Vec::<String>::new());
+
+        uploader.delete("12").await.unwrap();
+        uploader.delete("13").await.unwrap();
+        let mut ids = uploader.list("1", start..).await.unwrap();
+        ids.sort();
+        assert_eq!(ids, ["11"]);
+
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,97 @@
+//! [`ObjectStore`] writing objects to a directory on the local filesystem.
+
+use {
+    crate::{Object, ObjectStore, Parts},
+    anyhow::{Context, Result},
+    futures::TryStreamExt,
+    std::{io::ErrorKind, path::PathBuf},
+    tokio::{fs, io::AsyncWriteExt},
+};
+
+#[derive(Debug)]
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for Store {
+    /// Writes the object to a temporary file first and moves it to its final
+    /// location once complete, so readers never see partial objects.
+    async fn put(&self, key: &str, mut parts: Parts<'_>) -> Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("create directory {parent:?}"))?;
+        }
+        let mut temporary = path.clone().into_os_string();
+        temporary.push(".partial");
+        let temporary = PathBuf::from(temporary);
+
+        let mut file = fs::File::create(&temporary)
+            .await
+            .with_context(|| format!("create {temporary:?}"))?;
+        while let Some(part) = parts.try_next().await? {
+            file.write_all(&part).await.context("write part")?;
+        }
+        file.sync_all().await.context("sync file")?;
+        fs::rename(&temporary, &path)
+            .await
+            .with_context(|| format!("move object to {path:?}"))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.root.join(key);
+        match fs::read(&path).await {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("read {path:?}")),
+        }
+    }
+
+    /// Walks the whole directory since keys may contain `/` which map to
+    /// subdirectories.
+    async fn list(&self, prefix: &str) -> Result<Vec<Object>> {
+        let mut objects = Vec::new();
+        let mut directories = vec![(self.root.clone(), String::new())];
+        while let Some((directory, key_prefix)) = directories.pop() {
+            let mut entries = match fs::read_dir(&directory).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err).with_context(|| format!("read {directory:?}")),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                    continue;
+                };
+                let key = format!("{key_prefix}{name}");
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    directories.push((entry.path(), format!("{key}/")));
+                } else if key.starts_with(prefix) && !key.ends_with(".partial") {
+                    objects.push(Object {
+                        key,
+                        last_modified: metadata.modified()?.into(),
+                    });
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.root.join(key);
+        match fs::remove_file(&path).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                Err(err).with_context(|| format!("remove {path:?}"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use {
-    crate::account_balances::{BalanceFetching, Query, TransferSimulationError},
+    crate::{
+        account_balances::{BalanceFetching, Query, TransferSimulationError},
+        request_sharing::BoxRequestSharing,
+    },
     anyhow::Result,
     ethrpc::block_stream::{into_stream, CurrentBlockWatcher},
-    futures::StreamExt,
+    futures::{FutureExt, StreamExt},
     itertools::Itertools,
     primitive_types::U256,
     std::{
@@ -71,9 +74,14 @@ struct BalanceEntry {
     balance: U256,
 }
 
+/// Errors are not `Clone` so they get wrapped in an `Arc` to allow sharing
+/// the result of in flight requests.
+type SharedBalance = Result<U256, Arc<anyhow::Error>>;
+
 pub struct Balances {
     inner: Arc<dyn BalanceFetching>,
     balance_cache: Arc<Mutex<BalanceCache>>,
+    requests: BoxRequestSharing<Query, SharedBalance>,
 }
 
 impl Balances {
@@ -81,6 +89,7 @@ impl Balances {
         Self {
             inner,
             balance_cache: Default::default(),
+            requests: BoxRequestSharing::labelled("balances".into()),
         }
     }
 }
@@ -151,7 +160,9 @@ impl Balances {
             }
             tracing::error!("block stream terminated unexpectedly");
         };
-        tokio::spawn(task.instrument(tracing::info_span!("balance_cache")));
+        observe::metrics::spawn_in_current_scope(
+            task.instrument(tracing::info_span!("balance_cache")),
+        );
     }
 }
 
@@ -169,7 +180,26 @@ impl BalanceFetching for Balances {
         }
 
         let missing_queries: Vec<Query> = missing.iter().map(|i| queries[*i].clone()).collect();
-        let new_balances = self.inner.get_balances(&missing_queries).await;
+        // Concurrent requests for the same balance share a single request to `inner`.
+        let new_balances = futures::future::join_all(missing_queries.iter().map(|query| {
+            self.requests.shared_or_else(query.clone(), |query| {
+                let inner = self.inner.clone();
+                let query = query.clone();
+                async move {
+                    inner
+                        .get_balances(std::slice::from_ref(&query))
+                        .await
+                        .pop()
+                        .expect("one result per query")
+                        .map_err(Arc::new)
+                }
+                .boxed()
+            })
+        }))
+        .await
+        .into_iter()
+        .map(|result| result.map_err(|err| crate::clone_anyhow_error(&err)))
+        .collect::<Vec<_>>();
 
         {
             let mut cache = self.balance_cache.lock().unwrap();
@@ -10,13 +10,16 @@ use {
             BaselineSource,
         },
         tenderly_api,
+        token_info::TokenInfoOverride,
     },
     anyhow::{ensure, Context, Result},
     bigdecimal::BigDecimal,
     ethcontract::{H160, U256},
+    reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION},
     std::{
         fmt::{self, Display, Formatter},
         num::NonZeroU64,
+        ops::RangeInclusive,
         str::FromStr,
         time::Duration,
     },
@@ -27,13 +30,65 @@ use {
 #[macro_export]
 macro_rules! logging_args_with_default_filter {
     ($struct_name:ident ,$default_filter:literal) => {
-        #[derive(clap::Parser)]
+        #[derive(clap::Parser, Debug)]
         pub struct $struct_name {
-            #[clap(long, env, default_value = $default_filter)]
-            pub log_filter: String,
+            /// Filter for the logs. Defaults to
+            #[doc = concat!("\"", $default_filter, "\".")]
+            #[clap(long, env)]
+            pub log_filter: Option<String>,
+
+            /// Logs at or above this level get written to stderr. Defaults to
+            /// "error".
+            #[clap(long, env)]
+            pub log_stderr_threshold: Option<LevelFilter>,
+
+            /// Format of the log lines, "plain" or "json". Defaults to "plain".
+            #[clap(long, env)]
+            pub log_format: Option<::observe::config::LogFormat>,
+
+            /// Exposes the tokio runtime to `tokio-console`. Defaults to false.
+            #[clap(long, env)]
+            pub tokio_console: Option<bool>,
+
+            /// gRPC endpoint of an OpenTelemetry collector to export spans to.
+            #[clap(long, env)]
+            pub otlp_endpoint: Option<String>,
+
+            /// TOML file with the observability settings above. Arguments and
+            /// environment variables take precedence over the file.
+            #[clap(long, env)]
+            pub observe_config: Option<::std::path::PathBuf>,
+
+            /// Prints the effective observability settings and exits.
+            #[clap(long)]
+            pub print_effective_config: bool,
+        }
 
-            #[clap(long, env, default_value = "error")]
-            pub log_stderr_threshold: LevelFilter,
+        impl $struct_name {
+            /// Combines the arguments with the config file and the defaults.
+            /// Exits the process after printing the result if
+            /// `--print-effective-config` is set.
+            pub fn observe_config(&self) -> ::observe::config::Config {
+                let file = match &self.observe_config {
+                    Some(path) => ::observe::config::Partial::load(path)
+                        .expect("invalid observability config file"),
+                    None => Default::default(),
+                };
+                let arguments = ::observe::config::Partial {
+                    log_filter: self.log_filter.clone(),
+                    log_stderr_threshold: self.log_stderr_threshold,
+                    log_format: self.log_format,
+                    tokio_console: self.tokio_console,
+                    otlp_endpoint: self.otlp_endpoint.clone(),
+                    metrics_port: None,
+                };
+                let config = arguments.or(file).resolve($default_filter);
+                if self.print_effective_config {
+                    print!("{}", config.to_toml());
+                    ::std::process::exit(0);
+                }
+                config
+            }
         }
 
         impl ::std::fmt::Display for $struct_name {
@@ -41,10 +96,24 @@ macro_rules! logging_args_with_default_filter {
                 let Self {
                     log_filter,
                     log_stderr_threshold,
+                    log_format,
+                    tokio_console,
+                    otlp_endpoint,
+                    observe_config,
+                    print_effective_config,
                 } = self;
 
-                writeln!(f, "log_filter: {}", log_filter)?;
-                writeln!(f, "log_stderr_threshold: {}", log_stderr_threshold)?;
+                $crate::arguments::display_option(f, "log_filter", log_filter)?;
+                $crate::arguments::display_option(f, "log_stderr_threshold", log_stderr_threshold)?;
+                $crate::arguments::display_option(f, "log_format", log_format)?;
+                $crate::arguments::display_option(f, "tokio_console", tokio_console)?;
+                $crate::arguments::display_option(f, "otlp_endpoint", otlp_endpoint)?;
+                $crate::arguments::display_option(
+                    f,
+                    "observe_config",
+                    &observe_config.as_ref().map(|path| path.display()),
+                )?;
+                writeln!(f, "print_effective_config: {}", print_effective_config)?;
                 Ok(())
             }
         }
@@ -58,6 +127,28 @@ pub struct ExternalSolver {
     pub fairness_threshold: Option<U256>,
 }
 
+/// Bearer token authenticating requests to a driver whose API requires it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DriverCredential {
+    pub name: String,
+    /// The value of the `Authorization` header.
+    authorization: HeaderValue,
+}
+
+impl DriverCredential {
+    /// Headers to send with every request to the driver.
+    pub fn headers(&self) -> HeaderMap {
+        HeaderMap::from_iter([(AUTHORIZATION, self.authorization.clone())])
+    }
+
+    /// Finds the credential of the driver with the given name.
+    pub fn find<'a>(credentials: &'a [Self], name: &str) -> Option<&'a Self> {
+        credentials
+            .iter()
+            .find(|credential| credential.name == name)
+    }
+}
+
 // The following arguments are used to configure the order creation process
 // The arguments are shared between the orderbook crate and the autopilot crate,
 // as both crates can create orders
@@ -65,6 +156,9 @@ pub struct ExternalSolver {
 pub struct OrderQuotingArguments {
     /// A list of external drivers used for price estimation in the following
     /// format: `<NAME>|<URL>,<NAME>|<URL>`
+    ///
+    /// Quotes are requested from the `/quote` endpoint of every driver
+    /// concurrently, each bounded by `--quote-timeout`, and the best one wins.
     #[clap(long, env, use_value_delimiter = true)]
     pub price_estimation_drivers: Vec<ExternalSolver>,
 
@@ -73,7 +167,7 @@ pub struct OrderQuotingArguments {
         long,
         env,
         default_value = "10m",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub eip1271_onchain_quote_validity: Duration,
 
@@ -82,7 +176,7 @@ pub struct OrderQuotingArguments {
         long,
         env,
         default_value = "10m",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub presign_onchain_quote_validity: Duration,
 
@@ -92,7 +186,7 @@ pub struct OrderQuotingArguments {
         long,
         env,
         default_value = "1m",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub standard_offchain_quote_validity: Duration,
 }
@@ -121,6 +215,11 @@ pub struct Arguments {
     #[clap(long, env, default_value = "http://localhost:8545")]
     pub node_url: Url,
 
+    /// Ethereum node URLs to fail over to, in order, when the node at
+    /// `node_url` returns errors or times out.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub fallback_node_urls: Vec<Url>,
+
     /// An Ethereum node URL that supports `eth_call`s with state overrides to
     /// be used for simulations.
     #[clap(long, env)]
@@ -132,6 +231,14 @@ pub struct Arguments {
     #[clap(long, env)]
     pub chain_id: Option<u64>,
 
+    /// Chains that are not built into the services. Supplied in the form of:
+    /// "<ID>|<NAME>|<BLOCK_TIME_MS>|<NATIVE_TOKEN>|<NATIVE_PRICE_ESTIMATION_AMOUNT>"
+    /// where the native token is the address of the wrapped native token and
+    /// the amount is in native token atoms. The finality depth of the chain in
+    /// blocks can be appended as "|<FINALITY_DEPTH>".
+    #[clap(long, env, use_value_delimiter = true)]
+    pub custom_chains: Vec<chain::ChainConfig>,
+
     /// Which gas estimators to use. Multiple estimators are used in sequence if
     /// a previous one fails. Individual estimators support different
     /// networks. `EthGasStation`: supports mainnet.
@@ -186,7 +293,7 @@ pub struct Arguments {
     pub pool_cache_maximum_retries: u32,
 
     /// How long to sleep in seconds between retries in the pool cache.
-    #[clap(long, env, default_value = "1s", value_parser = humantime::parse_duration)]
+    #[clap(long, env, default_value = "1s", value_parser = crate::arguments::parse_duration)]
     pub pool_cache_delay_between_retries: Duration,
 
     /// If solvers should use internal buffers to improve solution quality.
@@ -209,7 +316,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "30s",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub liquidity_fetcher_max_age_update: Duration,
 
@@ -218,7 +325,7 @@ pub struct Arguments {
     pub max_pools_to_initialize_cache: usize,
 
     /// The time between new blocks on the network.
-    #[clap(long, env, value_parser = humantime::parse_duration)]
+    #[clap(long, env, value_parser = crate::arguments::parse_duration)]
     pub network_block_interval: Option<Duration>,
 
     /// Override address of the settlement contract.
@@ -239,7 +346,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "10m",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub token_quality_cache_expiry: Duration,
 
@@ -254,9 +361,60 @@ pub struct Arguments {
         long,
         env,
         default_value = "2m",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub token_quality_cache_prefetch_time: Duration,
+
+    /// Database queries taking longer than this get logged and counted as
+    /// slow queries.
+    #[clap(
+        long,
+        env,
+        default_value = "1s",
+        value_parser = crate::arguments::parse_duration,
+    )]
+    pub db_slow_query_threshold: Duration,
+
+    /// Url of a read replica of the database. Read-only API queries of the
+    /// orderbook that can tolerate slightly outdated data get routed to it.
+    /// The autopilot always reads from the primary.
+    #[clap(long, env)]
+    pub db_read_replica_url: Option<Url>,
+
+    /// Read-only queries fall back to the primary database while the read
+    /// replica lags behind it by more than this.
+    #[clap(
+        long,
+        env,
+        default_value = "5s",
+        value_parser = crate::arguments::parse_duration,
+    )]
+    pub db_read_replica_max_lag: Duration,
+
+    /// For how long feature flag values read from the database get cached
+    /// before services pick up changes.
+    #[clap(
+        long,
+        env,
+        default_value = "10s",
+        value_parser = crate::arguments::parse_duration,
+    )]
+    pub feature_flags_cache_ttl: Duration,
+
+    /// Token infos that take precedence over the ones read from the chain, for
+    /// tokens with broken or non-standard metadata. Supplied in the form of
+    /// "<address>|<symbol>|<decimals>,...". Either the symbol or the decimals
+    /// can be left empty to keep using the on-chain value.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub token_info_overrides: Vec<TokenInfoOverride>,
+
+    /// Bearer tokens for drivers that require requests to be authenticated.
+    /// Supplied in the form of "<NAME>|<TOKEN>,<NAME>|<TOKEN>" where the name
+    /// is the one the driver is configured with, e.g. in
+    /// `price_estimation_drivers`, `native_price_estimators` or the
+    /// autopilot's `drivers`.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub driver_credentials: Vec<DriverCredential>,
 }
 
 pub fn display_secret_option<T>(
@@ -328,6 +486,20 @@ impl Display for OrderQuotingArguments {
 }
 // We have a custom Display implementation so that we can log the arguments on
 // start up without leaking any potentially secret values.
+impl Arguments {
+    /// Makes the configured custom chains known. Has to be called before any
+    /// chain gets created from its chain ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a custom chain conflicts with another chain.
+    pub fn register_custom_chains(&self) {
+        for config in &self.custom_chains {
+            chain::register(config.clone()).expect("invalid custom chain");
+        }
+    }
+}
+
 impl Display for Arguments {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let Self {
@@ -336,7 +508,9 @@ impl Display for Arguments {
             tenderly,
             logging,
             node_url,
+            fallback_node_urls,
             chain_id,
+            custom_chains,
             simulation_node_url,
             gas_estimators,
             blocknative_api_key,
@@ -358,6 +532,12 @@ impl Display for Arguments {
             max_pools_to_initialize_cache,
             token_quality_cache_expiry,
             token_quality_cache_prefetch_time,
+            db_slow_query_threshold,
+            db_read_replica_url,
+            db_read_replica_max_lag,
+            feature_flags_cache_ttl,
+            token_info_overrides,
+            driver_credentials,
         } = self;
 
         write!(f, "{}", ethrpc)?;
@@ -365,7 +545,9 @@ impl Display for Arguments {
         write!(f, "{}", tenderly)?;
         write!(f, "{}", logging)?;
         writeln!(f, "node_url: {}", node_url)?;
+        writeln!(f, "fallback_node_urls: {:?}", fallback_node_urls)?;
         display_option(f, "chain_id", chain_id)?;
+        writeln!(f, "custom_chains: {:?}", custom_chains)?;
         display_option(f, "simulation_node_url", simulation_node_url)?;
         writeln!(f, "gas_estimators: {:?}", gas_estimators)?;
         display_secret_option(f, "blocknative_api_key", blocknative_api_key.as_ref())?;
@@ -439,6 +621,12 @@ impl Display for Arguments {
             "token_quality_cache_prefetch_time: {:?}",
             token_quality_cache_prefetch_time
         )?;
+        writeln!(f, "db_slow_query_threshold: {:?}", db_slow_query_threshold)?;
+        display_secret_option(f, "db_read_replica_url", db_read_replica_url.as_ref())?;
+        writeln!(f, "db_read_replica_max_lag: {:?}", db_read_replica_max_lag)?;
+        writeln!(f, "feature_flags_cache_ttl: {:?}", feature_flags_cache_ttl)?;
+        display_list(f, "token_info_overrides", token_info_overrides)?;
+        display_list(f, "driver_credentials", driver_credentials)?;
 
         Ok(())
     }
@@ -450,21 +638,64 @@ impl Display for ExternalSolver {
     }
 }
 
+impl Display for DriverCredential {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(SECRET)", self.name)
+    }
+}
+
+impl fmt::Debug for DriverCredential {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DriverCredential")
+            .field("name", &self.name)
+            .field("token", &"SECRET")
+            .finish()
+    }
+}
+
+// Parsers for use as `value_parser` of command line arguments. Clap prefixes
+// their errors with the name of the offending flag.
+
+/// Parses a human readable duration like `500ms`, `30s` or `1h 5m`.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    humantime::parse_duration(s)
+        .with_context(|| format!("expected a duration like `30s` or `1h 5m` but got `{s}`"))
+}
+
+/// Parses an amount of wei. The amount can also be given in Gwei or Ether
+/// with a unit suffix, e.g. `30gwei` or `1.5eth`.
+pub fn parse_wei(s: &str) -> Result<U256> {
+    let (amount, decimals) = [("gwei", 9), ("wei", 0), ("ether", 18), ("eth", 18)]
+        .into_iter()
+        .find_map(|(unit, decimals)| Some((s.strip_suffix(unit)?, decimals)))
+        .unwrap_or((s, 0));
+    let amount = amount.trim().parse::<BigDecimal>().with_context(|| {
+        format!("expected an amount like `1000`, `30gwei` or `1.5eth` but got `{s}`")
+    })?;
+    let wei = amount * BigDecimal::new(1.into(), -decimals);
+    number::conversions::big_decimal_to_u256(&wei)
+        .with_context(|| format!("`{s}` is not a whole, non-negative number of wei"))
+}
+
+/// Parses a factor between 0 and 1 (inclusive).
 pub fn parse_percentage_factor(s: &str) -> Result<f64> {
-    let percentage_factor = f64::from_str(s)?;
-    ensure!(percentage_factor.is_finite() && (0. ..=1.0).contains(&percentage_factor));
-    Ok(percentage_factor)
+    parse_factor(s, 0. ..=1.)
 }
 
-pub fn wei_from_ether(s: &str) -> anyhow::Result<U256> {
-    let in_ether = s.parse::<BigDecimal>()?;
-    let base = BigDecimal::new(1.into(), -18);
-    number::conversions::big_decimal_to_u256(&(in_ether * base)).context("invalid Ether value")
+/// Parses a factor that is 0 or larger.
+pub fn parse_non_negative_factor(s: &str) -> Result<f64> {
+    parse_factor(s, 0. ..=f64::MAX)
 }
 
-pub fn wei_from_gwei(s: &str) -> anyhow::Result<f64> {
-    let in_gwei: f64 = s.parse()?;
-    Ok(in_gwei * 1e9)
+fn parse_factor(s: &str, bounds: RangeInclusive<f64>) -> Result<f64> {
+    let factor = f64::from_str(s).with_context(|| format!("`{s}` is not a number"))?;
+    ensure!(
+        factor.is_finite() && bounds.contains(&factor),
+        "`{s}` is not within {} and {}",
+        bounds.start(),
+        bounds.end(),
+    );
+    Ok(factor)
 }
 
 impl FromStr for ExternalSolver {
@@ -489,6 +720,25 @@ impl FromStr for ExternalSolver {
     }
 }
 
+impl FromStr for DriverCredential {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, token) = s
+            .split_once('|')
+            .context("credential is not of the form <NAME>|<TOKEN>")?;
+        ensure!(!name.is_empty(), "credential is missing driver name");
+        ensure!(!token.is_empty(), "credential is missing token");
+        let mut authorization = HeaderValue::from_str(&format!("Bearer {token}"))
+            .context("token is not a valid header value")?;
+        authorization.set_sensitive(true);
+        Ok(Self {
+            name: name.to_owned(),
+            authorization,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -531,4 +781,54 @@ mod test {
             ExternalSolver::from_str("name1|http://localhost:8080|additional_argument").is_err()
         );
     }
+
+    #[test]
+    fn parse_driver_credential() {
+        let credential = DriverCredential::from_str("name1|se|cret").unwrap();
+        assert_eq!(credential.name, "name1");
+        assert_eq!(credential.headers()[AUTHORIZATION], "Bearer se|cret");
+        assert!(!format!("{credential} {credential:?}").contains("se|cret"));
+
+        assert!(DriverCredential::from_str("name1").is_err());
+        assert!(DriverCredential::from_str("|token").is_err());
+        assert!(DriverCredential::from_str("name1|").is_err());
+        assert!(DriverCredential::from_str("name1|new\nline").is_err());
+    }
+
+    #[test]
+    fn parse_wei_amounts() {
+        assert_eq!(parse_wei("1000").unwrap(), U256::from(1000));
+        assert_eq!(parse_wei("1000wei").unwrap(), U256::from(1000));
+        assert_eq!(parse_wei("30gwei").unwrap(), U256::from(30_000_000_000u64));
+        assert_eq!(parse_wei("1.5eth").unwrap(), U256::exp10(18) * 3 / 2);
+        assert_eq!(parse_wei("2 ether").unwrap(), U256::exp10(18) * 2);
+        assert!(parse_wei("0.5").is_err());
+        assert!(parse_wei("-1").is_err());
+        assert!(parse_wei("1btc").is_err());
+    }
+
+    #[test]
+    fn parse_factors() {
+        assert_eq!(parse_percentage_factor("0.5").unwrap(), 0.5);
+        assert!(parse_percentage_factor("1.1").is_err());
+        assert!(parse_percentage_factor("NaN").is_err());
+        assert_eq!(parse_non_negative_factor("2").unwrap(), 2.);
+        assert!(parse_non_negative_factor("-0.1").is_err());
+        assert!(parse_non_negative_factor("inf").is_err());
+    }
+
+    #[test]
+    fn errors_name_the_flag() {
+        #[derive(clap::Parser, Debug)]
+        struct Arguments {
+            #[clap(long, value_parser = parse_duration)]
+            timeout: Duration,
+        }
+
+        let err = <Arguments as clap::Parser>::try_parse_from(["test", "--timeout", "5"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--timeout"), "{err}");
+        assert!(err.contains("expected a duration"), "{err}");
+    }
 }
@@ -75,7 +75,7 @@ impl CachingDetector {
         let maintenance_timeout = self.prefetch_time.div(2);
         let detector = Arc::clone(&self);
 
-        tokio::task::spawn(async move {
+        observe::metrics::spawn_in_current_scope(async move {
             loop {
                 let start = Instant::now();
 
@@ -5,16 +5,17 @@ use {
     ethcontract::H160,
     prometheus::IntCounterVec,
     prometheus_metric_storage::MetricStorage,
-    rate_limit::{back_off, RateLimiter, Strategy},
+    rate_limit::{back_off, RateLimiter},
     reqwest::{Client, Url},
     serde::Deserialize,
+    std::sync::Arc,
 };
 
 pub struct BlockscoutTokenOwnerFinder {
     client: Client,
     base: Url,
     api_key: Option<String>,
-    rate_limiter: Option<RateLimiter>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl BlockscoutTokenOwnerFinder {
@@ -27,6 +28,7 @@ impl BlockscoutTokenOwnerFinder {
             Chain::ArbitrumOne => "https://arbitrum.blockscout.com/api",
             Chain::Base => "https://base.blockscout.com/api",
             Chain::Hardhat => anyhow::bail!("Hardhat chain not supported"),
+            Chain::Custom(_) => anyhow::bail!("{} chain not supported", chain.name()),
         };
 
         Ok(Self {
@@ -47,11 +49,8 @@ impl BlockscoutTokenOwnerFinder {
         self
     }
 
-    pub fn with_rate_limiter(&mut self, strategy: Strategy) -> &mut Self {
-        self.rate_limiter = Some(RateLimiter::from_strategy(
-            strategy,
-            "blockscout".to_owned(),
-        ));
+    pub fn with_rate_limiter(&mut self, rate_limiter: Arc<RateLimiter>) -> &mut Self {
+        self.rate_limiter = Some(rate_limiter);
         self
     }
 
@@ -71,7 +70,11 @@ impl BlockscoutTokenOwnerFinder {
 
         let request = self.client.get(url).send();
         let response = match &self.rate_limiter {
-            Some(limiter) => limiter.execute(request, back_off::on_http_429).await??,
+            Some(limiter) => {
+                limiter
+                    .execute_with_hint(request, back_off::retry_after)
+                    .await??
+            }
             _ => request.await?,
         };
         let status = response.status();
@@ -5,9 +5,10 @@ use {
     ethcontract::H160,
     prometheus::IntCounterVec,
     prometheus_metric_storage::MetricStorage,
-    rate_limit::{back_off, RateLimiter, Strategy},
+    rate_limit::{back_off, RateLimiter},
     reqwest::{Client, StatusCode, Url},
     serde::Deserialize,
+    std::sync::Arc,
 };
 
 const BASE: &str = "https://api.ethplorer.io";
@@ -20,7 +21,7 @@ pub struct EthplorerTokenOwnerFinder {
 
     /// The low tiers for Ethplorer have very aggressive rate limiting, so be
     /// sure to setup a rate limiter for Ethplorer requests.
-    rate_limiter: Option<RateLimiter>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 
     metrics: &'static Metrics,
 }
@@ -49,8 +50,8 @@ impl EthplorerTokenOwnerFinder {
         self
     }
 
-    pub fn with_rate_limiter(&mut self, strategy: Strategy) -> &mut Self {
-        self.rate_limiter = Some(RateLimiter::from_strategy(strategy, "ethplorer".to_owned()));
+    pub fn with_rate_limiter(&mut self, rate_limiter: Arc<RateLimiter>) -> &mut Self {
+        self.rate_limiter = Some(rate_limiter);
         self
     }
 
@@ -66,7 +67,11 @@ impl EthplorerTokenOwnerFinder {
 
         let request = self.client.get(url).send();
         let response = match &self.rate_limiter {
-            Some(limiter) => limiter.execute(request, back_off::on_http_429).await??,
+            Some(limiter) => {
+                limiter
+                    .execute_with_hint(request, back_off::retry_after)
+                    .await??
+            }
             _ => request.await?,
         };
 
@@ -35,7 +35,7 @@ use {
     ethcontract::U256,
     futures::{Stream, StreamExt as _},
     primitive_types::H160,
-    rate_limit::Strategy,
+    rate_limit::{RateLimiterRegistry, Strategy},
     reqwest::Url,
     std::{
         collections::HashMap,
@@ -105,7 +105,7 @@ pub struct Arguments {
     /// Interval in seconds between consecutive queries to update the solver
     /// token owner pairs. Values should be in pair with
     /// `solver_token_owners_urls`
-    #[clap(long, env, use_value_delimiter = true, value_parser = humantime::parse_duration)]
+    #[clap(long, env, use_value_delimiter = true, value_parser = crate::arguments::parse_duration)]
     pub solver_token_owners_cache_update_intervals: Vec<Duration>,
 }
 
@@ -194,7 +194,11 @@ impl TokenOwnerFindingStrategy {
         match chain {
             Chain::Mainnet => &[Self::Liquidity, Self::Blockscout, Self::Ethplorer],
             Chain::Gnosis => &[Self::Liquidity, Self::Blockscout],
-            Chain::Sepolia | Chain::Goerli | Chain::ArbitrumOne | Chain::Base => &[Self::Liquidity],
+            Chain::Sepolia
+            | Chain::Goerli
+            | Chain::ArbitrumOne
+            | Chain::Base
+            | Chain::Custom(_) => &[Self::Liquidity],
             Chain::Hardhat => panic!("unsupported chain for token owner finding"),
         }
     }
@@ -278,6 +282,7 @@ pub async fn init(
     uniswapv3_factory: Option<&IUniswapV3Factory>,
     base_tokens: &BaseTokens,
     settlement_contract: H160,
+    rate_limiters: &RateLimiterRegistry,
 ) -> Result<Arc<dyn TokenOwnerFinding>> {
     let web3 = ethrpc::instrumented::instrument_with_label(&web3, "tokenOwners".into());
     let finders = args
@@ -322,7 +327,7 @@ pub async fn init(
             blockscout.with_api_key(blockscout_config.blockscout_api_key.clone());
         }
         if let Some(strategy) = args.token_owner_finder_rate_limiter.clone() {
-            blockscout.with_rate_limiter(strategy);
+            blockscout.with_rate_limiter(rate_limiters.get_or_create("blockscout", strategy)?);
         }
         proposers.push(Arc::new(blockscout));
     }
@@ -339,7 +344,7 @@ pub async fn init(
             ethplorer.with_base_url(ethplorer_config.ethplorer_api_url.clone());
         }
         if let Some(strategy) = args.token_owner_finder_rate_limiter.clone() {
-            ethplorer.with_rate_limiter(strategy);
+            ethplorer.with_rate_limiter(rate_limiters.get_or_create("ethplorer", strategy)?);
         }
         proposers.push(Arc::new(ethplorer));
     }
@@ -354,7 +359,7 @@ pub async fn init(
             let identifier = url.to_string();
             let solver = Box::new(SolverConfiguration {
                 url,
-                client: http_factory.create(),
+                client: http_factory.create_caching(),
             });
             let solver =
                 AutoUpdatingSolverTokenOwnerFinder::new(solver, update_interval, identifier);
@@ -1,8 +1,9 @@
 use {
     super::TokenOwnerSolverApi,
+    crate::http_cache::CachingClient,
     anyhow::{Context, Result},
     ethcontract::H160,
-    reqwest::{Client, Url},
+    reqwest::Url,
     std::collections::HashMap,
 };
 
@@ -12,19 +13,18 @@ type Owner = H160;
 #[derive(Clone, Debug)]
 pub struct SolverConfiguration {
     pub url: Url,
-    pub client: Client,
+    pub client: CachingClient,
 }
 
 #[async_trait::async_trait]
 impl TokenOwnerSolverApi for SolverConfiguration {
     async fn get_token_owner_pairs(&self) -> Result<HashMap<Token, Vec<Owner>>> {
-        let response = self
-            .client
-            .get(self.url.clone())
-            .send()
-            .await?
-            .text()
-            .await?;
-        serde_json::from_str(&response).context(format!("bad query response: {response:?}"))
+        let response = self.client.get(self.url.clone()).await?;
+        serde_json::from_slice(&response.body).with_context(|| {
+            format!(
+                "bad query response: {:?}",
+                String::from_utf8_lossy(&response.body)
+            )
+        })
     }
 }
@@ -77,7 +77,7 @@ impl AutoUpdatingSolverTokenOwnerFinder {
                     tokio::time::sleep(update_interval).await;
                 }
             };
-            tokio::task::spawn(
+            observe::metrics::spawn_in_current_scope(
                 updater.instrument(tracing::info_span!("auto_updating_token_owner_finder")),
             );
         }
@@ -126,7 +126,7 @@ mod tests {
     use {
         super::*,
         crate::bad_token::token_owner_finder::solvers::solver_api::SolverConfiguration,
-        reqwest::{Client, Url},
+        reqwest::Url,
         std::str::FromStr,
     };
 
@@ -136,7 +136,7 @@ mod tests {
         let url = std::env::var("SEASOLVER_TOKEN_HOLDERS").unwrap();
         let configuration = Box::new(SolverConfiguration {
             url: Url::from_str(&url).unwrap(),
-            client: Client::new(),
+            client: Default::default(),
         });
         let finder = AutoUpdatingSolverTokenOwnerFinder::new(
             configuration,
@@ -889,7 +889,7 @@ mod tests {
                     .unwrap()
                     .parse()
                     .unwrap(),
-                client: reqwest::Client::new(),
+                client: Default::default(),
             }),
             Duration::MAX,
             "test".to_owned(),
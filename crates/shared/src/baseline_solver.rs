@@ -3,6 +3,7 @@
 
 use {
     ethcontract::{H160, U256},
+    itertools::Itertools,
     model::TokenPair,
     std::collections::{HashMap, HashSet},
 };
@@ -119,6 +120,269 @@ pub fn estimate_sell_amount<'a, L: BaselineSolvable>(
         })
 }
 
+/// An estimate where the input amount is split into multiple parts, each of
+/// which gets traded over a separate liquidity path.
+pub struct SplitEstimate<'a, L> {
+    // The total result amount over all parts
+    pub value: U256,
+    // The parts of the split: the amount of the input routed over the part and
+    // the resulting estimate
+    pub parts: Vec<(U256, Estimate<'a, U256, L>)>,
+}
+
+impl<L: BaselineSolvable> SplitEstimate<'_, L> {
+    /// Returns the approximate amount of gas of executing all parts.
+    pub fn gas_cost(&self) -> usize {
+        // All parts get executed in the same settlement, so the fixed overhead
+        // of an estimate is only paid once.
+        let cost_of_hops: usize = self
+            .parts
+            .iter()
+            .flat_map(|(_, estimate)| &estimate.path)
+            .map(|item| item.gas_cost())
+            .sum();
+        50_000 + cost_of_hops
+    }
+}
+
+// Given path candidates and a sell amount estimates the buy amount when
+// splitting the sell amount over up to `max_splits` liquidity paths that don't
+// share any liquidity. Falls back to the best single path if splitting does not
+// improve the estimate by more than the cost of its additional gas, which
+// `gas_cost` converts into the sell token. Returns None if no path candidate is
+// valid.
+pub fn estimate_buy_amount_split<'a, 'p, L: BaselineSolvable>(
+    sell_amount: U256,
+    paths: impl IntoIterator<Item = &'p PathCandidate>,
+    liquidity: &'a HashMap<TokenPair, Vec<L>>,
+    max_splits: usize,
+    gas_cost: impl Fn(usize) -> U256,
+) -> Option<SplitEstimate<'a, L>> {
+    let paths = paths.into_iter().collect::<Vec<_>>();
+    let single = paths
+        .iter()
+        .filter_map(|path| estimate_buy_amount(sell_amount, path, liquidity))
+        .max_by_key(|estimate| estimate.value)
+        .map(|estimate| SplitEstimate {
+            value: estimate.value,
+            parts: vec![(sell_amount, estimate)],
+        });
+    let split = split_amount(
+        sell_amount,
+        &liquidity_routes(&paths, liquidity),
+        max_splits,
+        Route::amount_out,
+        |a, b| a > b,
+    );
+
+    match (single, split) {
+        (Some(single), Some(split)) => {
+            // Convert the cost of the additional gas into the buy token at the
+            // exchange rate of the single path.
+            let additional_cost = gas_cost(split.gas_cost().saturating_sub(single.gas_cost()))
+                .checked_mul(single.value)
+                .map(|cost| cost / sell_amount);
+            match additional_cost.and_then(|cost| single.value.checked_add(cost)) {
+                Some(threshold) if split.value > threshold => Some(split),
+                _ => Some(single),
+            }
+        }
+        (single, split) => single.or(split),
+    }
+}
+
+// Given path candidates and a buy amount estimates the sell amount when
+// splitting the buy amount over up to `max_splits` liquidity paths that don't
+// share any liquidity. Falls back to the best single path if splitting does not
+// improve the estimate by more than the cost of its additional gas, which
+// `gas_cost` converts into the sell token. Returns None if no path candidate is
+// valid.
+pub fn estimate_sell_amount_split<'a, 'p, L: BaselineSolvable>(
+    buy_amount: U256,
+    paths: impl IntoIterator<Item = &'p PathCandidate>,
+    liquidity: &'a HashMap<TokenPair, Vec<L>>,
+    max_splits: usize,
+    gas_cost: impl Fn(usize) -> U256,
+) -> Option<SplitEstimate<'a, L>> {
+    let paths = paths.into_iter().collect::<Vec<_>>();
+    let single = paths
+        .iter()
+        .filter_map(|path| estimate_sell_amount(buy_amount, path, liquidity))
+        .min_by_key(|estimate| estimate.value)
+        .map(|estimate| SplitEstimate {
+            value: estimate.value,
+            parts: vec![(buy_amount, estimate)],
+        });
+    let split = split_amount(
+        buy_amount,
+        &liquidity_routes(&paths, liquidity),
+        max_splits,
+        Route::amount_in,
+        |a, b| a < b,
+    );
+
+    match (single, split) {
+        (Some(single), Some(split)) => {
+            let additional_cost = gas_cost(split.gas_cost().saturating_sub(single.gas_cost()));
+            match split.value.checked_add(additional_cost) {
+                Some(total) if total < single.value => Some(split),
+                _ => Some(single),
+            }
+        }
+        (single, split) => single.or(split),
+    }
+}
+
+/// The number of chunks an amount gets divided into when searching for the
+/// best split. This bounds the search and determines the granularity of the
+/// resulting split.
+const SPLIT_STEPS: usize = 20;
+
+/// The maximum number of liquidity routes to consider per path candidate when
+/// splitting, in order to bound the search for pairs with lots of liquidity.
+const MAX_ROUTES_PER_PATH: usize = 16;
+
+/// A path candidate with a specific piece of liquidity for every hop.
+struct Route<'p, 'a, L> {
+    path: &'p [H160],
+    liquidity: Vec<&'a L>,
+}
+
+impl<L: BaselineSolvable> Route<'_, '_, L> {
+    fn amount_out(&self, sell_amount: U256) -> Option<U256> {
+        if sell_amount.is_zero() {
+            return Some(U256::zero());
+        }
+        self.path
+            .windows(2)
+            .zip(&self.liquidity)
+            .try_fold(sell_amount, |amount, (pair, liquidity)| {
+                liquidity.get_amount_out(pair[1], (amount, pair[0]))
+            })
+    }
+
+    fn amount_in(&self, buy_amount: U256) -> Option<U256> {
+        if buy_amount.is_zero() {
+            return Some(U256::zero());
+        }
+        self.path
+            .windows(2)
+            .zip(&self.liquidity)
+            .rev()
+            .try_fold(buy_amount, |amount, (pair, liquidity)| {
+                liquidity.get_amount_in(pair[0], (amount, pair[1]))
+            })
+    }
+
+    fn shares_liquidity(&self, other: &Self) -> bool {
+        self.liquidity
+            .iter()
+            .any(|a| other.liquidity.iter().any(|b| std::ptr::eq(*a, *b)))
+    }
+}
+
+fn liquidity_routes<'p, 'a, L>(
+    paths: &[&'p PathCandidate],
+    liquidity: &'a HashMap<TokenPair, Vec<L>>,
+) -> Vec<Route<'p, 'a, L>> {
+    paths
+        .iter()
+        .copied()
+        .filter_map(|path| {
+            let hops = path
+                .windows(2)
+                .map(|pair| liquidity.get(&TokenPair::new(pair[0], pair[1])?))
+                .collect::<Option<Vec<_>>>()?;
+            Some(
+                hops.into_iter()
+                    .map(|liquidity| liquidity.iter())
+                    .multi_cartesian_product()
+                    .take(MAX_ROUTES_PER_PATH)
+                    .map(move |liquidity| Route {
+                        path: path.as_slice(),
+                        liquidity,
+                    }),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+/// Greedily splits the amount over at most `max_splits` routes that don't share
+/// any liquidity. The amount gets divided into chunks and every chunk is
+/// assigned to the route with the best marginal value, which converges to the
+/// optimal split for liquidity with convex pricing curves (i.e. all AMMs).
+fn split_amount<'p, 'a, L: BaselineSolvable>(
+    amount: U256,
+    routes: &[Route<'p, 'a, L>],
+    max_splits: usize,
+    evaluate: impl Fn(&Route<'p, 'a, L>, U256) -> Option<U256>,
+    is_better: impl Fn(U256, U256) -> bool,
+) -> Option<SplitEstimate<'a, L>> {
+    let mut amounts = vec![U256::zero(); routes.len()];
+    let mut values = vec![U256::zero(); routes.len()];
+    let mut used = Vec::<usize>::new();
+
+    let chunk = amount / SPLIT_STEPS;
+    for step in 0..SPLIT_STEPS {
+        let chunk = if step + 1 == SPLIT_STEPS {
+            amount - chunk * (SPLIT_STEPS - 1)
+        } else {
+            chunk
+        };
+        if chunk.is_zero() {
+            continue;
+        }
+
+        let (index, value) = routes
+            .iter()
+            .enumerate()
+            .filter(|(index, route)| {
+                used.contains(index)
+                    || (used.len() < max_splits
+                        && used
+                            .iter()
+                            .all(|used| !routes[*used].shares_liquidity(route)))
+            })
+            .filter_map(|(index, route)| {
+                Some((index, evaluate(route, amounts[index].checked_add(chunk)?)?))
+            })
+            .reduce(|best, current| {
+                let marginal = |(index, value): (usize, U256)| value.saturating_sub(values[index]);
+                if is_better(marginal(current), marginal(best)) {
+                    current
+                } else {
+                    best
+                }
+            })?;
+
+        if !used.contains(&index) {
+            used.push(index);
+        }
+        amounts[index] += chunk;
+        values[index] = value;
+    }
+
+    Some(SplitEstimate {
+        value: used.iter().try_fold(U256::zero(), |total, index| {
+            total.checked_add(values[*index])
+        })?,
+        parts: used
+            .into_iter()
+            .map(|index| {
+                (
+                    amounts[index],
+                    Estimate {
+                        value: values[index],
+                        path: routes[index].liquidity.clone(),
+                    },
+                )
+            })
+            .collect(),
+    })
+    .filter(|split| !split.parts.is_empty())
+}
+
 pub struct BaseTokens {
     /// The base tokens used to determine potential paths in the baseline
     /// solver.
@@ -524,4 +788,89 @@ mod tests {
         assert!(pairs.contains(&TokenPair::new(tokens[1], tokens[4]).unwrap()));
         assert!(pairs.contains(&TokenPair::new(tokens[3], tokens[4]).unwrap()));
     }
+
+    fn no_gas(_: usize) -> U256 {
+        U256::zero()
+    }
+
+    #[test]
+    fn test_estimate_amount_split_over_parallel_pools() {
+        let sell_token = H160::from_low_u64_be(1);
+        let buy_token = H160::from_low_u64_be(2);
+
+        let path = vec![sell_token, buy_token];
+        let pair = TokenPair::new(sell_token, buy_token).unwrap();
+        let pools = hashmap! {
+            pair => vec![
+                Pool::uniswap(H160::from_low_u64_be(1), pair, (1_000_000, 1_000_000)),
+                Pool::uniswap(H160::from_low_u64_be(2), pair, (1_000_000, 1_000_000)),
+            ],
+        };
+
+        let single = estimate_buy_amount(100_000.into(), &path, &pools).unwrap();
+        let split = estimate_buy_amount_split(100_000.into(), [&path], &pools, 2, no_gas).unwrap();
+        assert!(split.value > single.value);
+        assert_eq!(
+            split
+                .parts
+                .iter()
+                .map(|(amount, _)| amount.as_u64())
+                .collect::<Vec<_>>(),
+            [50_000, 50_000]
+        );
+        assert!(!std::ptr::eq(
+            split.parts[0].1.path[0],
+            split.parts[1].1.path[0]
+        ));
+
+        let single = estimate_sell_amount(50_000.into(), &path, &pools).unwrap();
+        let split = estimate_sell_amount_split(50_000.into(), [&path], &pools, 2, no_gas).unwrap();
+        assert!(split.value < single.value);
+        assert_eq!(split.parts.len(), 2);
+    }
+
+    #[test]
+    fn test_estimate_amount_split_falls_back_to_single_path() {
+        let sell_token = H160::from_low_u64_be(1);
+        let buy_token = H160::from_low_u64_be(2);
+
+        let path = vec![sell_token, buy_token];
+        let pair = TokenPair::new(sell_token, buy_token).unwrap();
+        let pools = hashmap! {
+            pair => vec![
+                Pool::uniswap(H160::from_low_u64_be(1), pair, (1_000_000, 1_000_000)),
+                Pool::uniswap(H160::from_low_u64_be(2), pair, (1_000_000, 1_000_000)),
+            ],
+        };
+
+        let single = estimate_buy_amount(100_000.into(), &path, &pools).unwrap();
+        let split = estimate_buy_amount_split(100_000.into(), [&path], &pools, 1, no_gas).unwrap();
+        assert_eq!(split.value, single.value);
+        assert_eq!(split.parts.len(), 1);
+        assert_eq!(split.parts[0].0, 100_000.into());
+
+        let unknown_path = vec![sell_token, H160::from_low_u64_be(3)];
+        assert!(
+            estimate_buy_amount_split(100_000.into(), [&unknown_path], &pools, 2, no_gas).is_none()
+        );
+
+        // Splitting only pays off if its price improvement exceeds the cost of
+        // the additional swap. The sell token price of gas is 1/10 and 1/20 of
+        // a token atom.
+        let split = estimate_buy_amount_split(100_000.into(), [&path], &pools, 2, |gas| {
+            U256::from(gas / 10)
+        })
+        .unwrap();
+        assert_eq!(split.parts.len(), 1);
+        let split = estimate_buy_amount_split(100_000.into(), [&path], &pools, 2, |gas| {
+            U256::from(gas / 20)
+        })
+        .unwrap();
+        assert_eq!(split.parts.len(), 2);
+        let split = estimate_sell_amount_split(50_000.into(), [&path], &pools, 2, |gas| {
+            U256::from(gas / 20)
+        })
+        .unwrap();
+        assert_eq!(split.parts.len(), 1);
+    }
 }
@@ -46,8 +46,7 @@ impl U256Ext for U256 {
     }
 
     fn checked_ceil_div(&self, other: &Self) -> Option<Self> {
-        self.checked_add(other.checked_sub(1.into())?)?
-            .checked_div(*other)
+        number::conversions::mul_ratio_ceil(*self, 1.into(), *other).ok()
     }
 
     fn ceil_div(&self, other: &Self) -> Self {
@@ -25,7 +25,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "5s",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub block_stream_poll_interval: Duration,
 }
@@ -20,6 +20,7 @@ use {
             BuyTokenDestination,
             EthflowData,
             Interactions,
+            InvalidationReason,
             OnchainOrderData,
             OnchainOrderPlacementError,
             Order,
@@ -72,6 +73,11 @@ pub fn full_order_into_model_order(order: database::orders::FullOrder) -> Result
         OrderClass::Limit | OrderClass::Market => full_fee_amount,
     };
 
+    let full_app_data = order
+        .full_app_data
+        .map(String::from_utf8)
+        .transpose()
+        .context("full app data isn't utf-8")?;
     let metadata = OrderMetadata {
         creation_date: order.creation_timestamp,
         owner: H160(order.owner.0),
@@ -105,11 +111,16 @@ pub fn full_order_into_model_order(order: database::orders::FullOrder) -> Result
         ethflow_data,
         onchain_user,
         onchain_order_data,
-        full_app_data: order
-            .full_app_data
-            .map(String::from_utf8)
-            .transpose()
-            .context("full app data isn't utf-8")?,
+        invalidation_reason: order.unsupported_token.map(|token| {
+            InvalidationReason::UnsupportedToken {
+                token: H160(token.0),
+            }
+        }),
+        embargo_until: order.embargo_until,
+        bridging: full_app_data
+            .as_deref()
+            .and_then(|full| model::app_data::bridging(&AppDataHash(order.app_data.0), full)),
+        full_app_data,
     };
     let data = OrderData {
         sell_token: H160(order.sell_token.0),
@@ -184,7 +195,13 @@ pub fn order_class_into(class: &OrderClass) -> DbOrderClass {
 pub fn onchain_order_placement_error_from(
     order: &FullOrderDb,
 ) -> Option<OnchainOrderPlacementError> {
-    match order.onchain_placement_error {
+    onchain_placement_error_from(order.onchain_placement_error.as_ref())
+}
+
+pub fn onchain_placement_error_from(
+    error: Option<&DbOnchainOrderPlacementError>,
+) -> Option<OnchainOrderPlacementError> {
+    match error {
         Some(DbOnchainOrderPlacementError::InvalidOrderData) => {
             Some(OnchainOrderPlacementError::InvalidOrderData)
         }
@@ -1,11 +1,18 @@
+//! Encoding and decoding of the calldata of `GPv2Settlement.settle()`.
+//!
+//! Decoding is the exact inverse of encoding so that external tooling can
+//! verify settlements with the same implementation the services use.
+
 use {
     crate::interaction::EncodedInteraction,
-    ethcontract::Bytes,
+    app_data::AppDataHash,
+    ethcontract::{common::FunctionExt, tokens::Tokenize, Bytes},
     model::{
         order::{BuyTokenDestination, OrderData, OrderKind, SellTokenSource},
         signature::{Signature, SigningScheme},
     },
     primitive_types::{H160, U256},
+    web3::ethabi::{Function, Token},
 };
 
 pub type EncodedTrade = (
@@ -46,6 +53,157 @@ pub fn encode_trade(
     )
 }
 
+/// A trade decoded from its [`EncodedTrade`] representation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedTrade {
+    pub order: OrderData,
+    pub signature: Signature,
+    /// The owner if it is part of the encoded signature. ECDSA signatures
+    /// don't include it and the owner has to be recovered instead.
+    pub owner: Option<H160>,
+    pub sell_token_index: usize,
+    pub buy_token_index: usize,
+    pub executed_amount: U256,
+}
+
+impl DecodedTrade {
+    /// Encodes the trade again. Inverse of [`decode_trade`].
+    pub fn encode(&self) -> EncodedTrade {
+        encode_trade(
+            &self.order,
+            &self.signature,
+            self.owner.unwrap_or_default(),
+            self.sell_token_index,
+            self.buy_token_index,
+            &self.executed_amount,
+        )
+    }
+}
+
+/// Decodes a trade of a settlement with the given tokens. Inverse of
+/// [`encode_trade`].
+pub fn decode_trade(trade: &EncodedTrade, tokens: &[H160]) -> Result<DecodedTrade, DecodingError> {
+    let (
+        sell_token_index,
+        buy_token_index,
+        receiver,
+        sell_amount,
+        buy_amount,
+        valid_to,
+        app_data,
+        fee_amount,
+        flags,
+        executed_amount,
+        signature,
+    ) = trade;
+    let token = |index: &U256| {
+        let index = usize::try_from(*index)
+            .ok()
+            .filter(|index| *index < tokens.len())
+            .ok_or(DecodingError::TokenIndex(*index))?;
+        Ok::<_, DecodingError>((index, tokens[index]))
+    };
+    let (sell_token_index, sell_token) = token(sell_token_index)?;
+    let (buy_token_index, buy_token) = token(buy_token_index)?;
+    let flags = OrderFlags::decode(*flags)?;
+    let (signature, owner) = decode_signature(flags.signing_scheme, &signature.0)?;
+
+    Ok(DecodedTrade {
+        order: OrderData {
+            sell_token,
+            buy_token,
+            receiver: (!receiver.is_zero()).then_some(*receiver),
+            sell_amount: *sell_amount,
+            buy_amount: *buy_amount,
+            valid_to: *valid_to,
+            app_data: AppDataHash(app_data.0),
+            fee_amount: *fee_amount,
+            kind: flags.kind,
+            partially_fillable: flags.partially_fillable,
+            sell_token_balance: flags.sell_token_balance,
+            buy_token_balance: flags.buy_token_balance,
+        },
+        signature,
+        owner,
+        sell_token_index,
+        buy_token_index,
+        executed_amount: *executed_amount,
+    })
+}
+
+/// Splits encoded signature bytes into the signature and the owner if it is
+/// part of the encoding.
+fn decode_signature(
+    scheme: SigningScheme,
+    bytes: &[u8],
+) -> Result<(Signature, Option<H160>), DecodingError> {
+    let (owner, bytes) = match scheme {
+        SigningScheme::Eip712 | SigningScheme::EthSign => (None, bytes),
+        // The settlement contract only accepts pre-signatures that consist of
+        // exactly the owner.
+        SigningScheme::PreSign if bytes.len() != 20 => {
+            return Err(DecodingError::Signature(anyhow::anyhow!(
+                "pre-signature must be the owner"
+            )));
+        }
+        SigningScheme::Eip1271 | SigningScheme::PreSign => {
+            if bytes.len() < 20 {
+                return Err(DecodingError::Signature(anyhow::anyhow!(
+                    "signature is missing the owner"
+                )));
+            }
+            let (owner, bytes) = bytes.split_at(20);
+            (Some(H160::from_slice(owner)), bytes)
+        }
+    };
+    let signature = Signature::from_bytes(scheme, bytes).map_err(DecodingError::Signature)?;
+    Ok((signature, owner))
+}
+
+/// The order properties encoded in the flags of a trade. For more information
+/// on how flags are encoded see:
+/// <https://github.com/cowprotocol/contracts/blob/v1.0.0/src/contracts/libraries/GPv2Trade.sol#L58-L94>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderFlags {
+    pub kind: OrderKind,
+    pub partially_fillable: bool,
+    pub sell_token_balance: SellTokenSource,
+    pub buy_token_balance: BuyTokenDestination,
+    pub signing_scheme: SigningScheme,
+}
+
+impl OrderFlags {
+    /// Decodes the flags. Fails if bits outside of the 7 used ones are set.
+    pub fn decode(flags: U256) -> Result<Self, DecodingError> {
+        if flags > U256::from(0b111_1111) {
+            return Err(DecodingError::Flags(flags));
+        }
+        let flags = flags.byte(0);
+        Ok(Self {
+            kind: match flags & 0b1 {
+                0 => OrderKind::Sell,
+                _ => OrderKind::Buy,
+            },
+            partially_fillable: flags & 0b10 != 0,
+            sell_token_balance: match (flags >> 2) & 0b11 {
+                0b00 | 0b01 => SellTokenSource::Erc20,
+                0b10 => SellTokenSource::External,
+                _ => SellTokenSource::Internal,
+            },
+            buy_token_balance: match (flags >> 4) & 0b1 {
+                0 => BuyTokenDestination::Erc20,
+                _ => BuyTokenDestination::Internal,
+            },
+            signing_scheme: match (flags >> 5) & 0b11 {
+                0b00 => SigningScheme::Eip712,
+                0b01 => SigningScheme::EthSign,
+                0b10 => SigningScheme::Eip1271,
+                _ => SigningScheme::PreSign,
+            },
+        })
+    }
+}
+
 fn order_flags(order: &OrderData, signature: &Signature) -> U256 {
     let mut result = 0u8;
     // The kind is encoded as 1 bit in position 0.
@@ -84,9 +242,306 @@ pub struct EncodedSettlement {
     pub interactions: [Vec<EncodedInteraction>; 3],
 }
 
+impl EncodedSettlement {
+    /// Encodes the settlement as `GPv2Settlement.settle()` calldata.
+    pub fn encode_calldata(&self) -> Vec<u8> {
+        let Token::Tuple(tokens) = (
+            self.tokens.clone(),
+            self.clearing_prices.clone(),
+            self.trades.clone(),
+            self.interactions.clone(),
+        )
+            .into_token()
+        else {
+            unreachable!("tuples tokenize into tuples");
+        };
+        settle_function()
+            .encode_input(&tokens)
+            .expect("tokens match the settle() signature")
+    }
+
+    /// Decodes `GPv2Settlement.settle()` calldata. Inverse of
+    /// [`EncodedSettlement::encode_calldata`]. Any data appended to the
+    /// calldata (e.g. the auction id) has to be stripped beforehand.
+    pub fn decode_calldata(calldata: &[u8]) -> Result<Self, DecodingError> {
+        let function = settle_function();
+        let data = calldata
+            .strip_prefix(&function.selector())
+            .ok_or(DecodingError::InvalidSelector)?;
+        let tokens = function.decode_input(data)?;
+        let (tokens, clearing_prices, trades, interactions) =
+            Tokenize::from_token(Token::Tuple(tokens))?;
+        Ok(Self {
+            tokens,
+            clearing_prices,
+            trades,
+            interactions,
+        })
+    }
+
+    /// Decodes all trades of the settlement.
+    pub fn decode_trades(&self) -> Result<Vec<DecodedTrade>, DecodingError> {
+        self.trades
+            .iter()
+            .map(|trade| decode_trade(trade, &self.tokens))
+            .collect()
+    }
+}
+
+fn settle_function() -> &'static Function {
+    contracts::GPv2Settlement::raw_contract()
+        .interface
+        .abi
+        .function("settle")
+        .unwrap()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodingError {
+    #[error("transaction calldata is not a settlement")]
+    InvalidSelector,
+    #[error("unable to decode settlement calldata: {0}")]
+    Ethabi(#[from] web3::ethabi::Error),
+    #[error("unable to tokenize calldata into expected format: {0}")]
+    Tokenizing(#[from] ethcontract::tokens::Error),
+    #[error("token index {0} is out of bounds")]
+    TokenIndex(U256),
+    #[error("invalid trade flags {0}")]
+    Flags(U256),
+    #[error("invalid signature: {0}")]
+    Signature(anyhow::Error),
+}
+
 #[cfg(test)]
 mod tests {
-    use {super::*, ethcontract::H256, hex_literal::hex, model::signature::EcdsaSignature};
+    use {
+        super::*,
+        ethcontract::H256,
+        hex_literal::hex,
+        model::signature::EcdsaSignature,
+        proptest::{collection::vec, option, prelude::*},
+    };
+
+    fn u256() -> impl Strategy<Value = U256> {
+        any::<[u8; 32]>().prop_map(|bytes| U256::from_big_endian(&bytes))
+    }
+
+    fn address() -> impl Strategy<Value = H160> {
+        any::<[u8; 20]>().prop_map(H160)
+    }
+
+    fn order() -> impl Strategy<Value = OrderData> {
+        (
+            // A zero receiver is encoded the same as no receiver.
+            option::of(address().prop_filter("zero receiver", |a| !a.is_zero())),
+            u256(),
+            u256(),
+            any::<u32>(),
+            any::<[u8; 32]>(),
+            u256(),
+            prop_oneof![Just(OrderKind::Sell), Just(OrderKind::Buy)],
+            any::<bool>(),
+            prop_oneof![
+                Just(SellTokenSource::Erc20),
+                Just(SellTokenSource::External),
+                Just(SellTokenSource::Internal),
+            ],
+            prop_oneof![
+                Just(BuyTokenDestination::Erc20),
+                Just(BuyTokenDestination::Internal),
+            ],
+        )
+            .prop_map(
+                |(
+                    receiver,
+                    sell_amount,
+                    buy_amount,
+                    valid_to,
+                    app_data,
+                    fee_amount,
+                    kind,
+                    partially_fillable,
+                    sell_token_balance,
+                    buy_token_balance,
+                )| OrderData {
+                    sell_token: Default::default(),
+                    buy_token: Default::default(),
+                    receiver,
+                    sell_amount,
+                    buy_amount,
+                    valid_to,
+                    app_data: AppDataHash(app_data),
+                    fee_amount,
+                    kind,
+                    partially_fillable,
+                    sell_token_balance,
+                    buy_token_balance,
+                },
+            )
+    }
+
+    fn signature() -> impl Strategy<Value = Signature> {
+        let ecdsa = || {
+            (any::<[u8; 32]>(), any::<[u8; 32]>(), any::<u8>()).prop_map(|(r, s, v)| {
+                EcdsaSignature {
+                    r: H256(r),
+                    s: H256(s),
+                    v,
+                }
+            })
+        };
+        prop_oneof![
+            ecdsa().prop_map(Signature::Eip712),
+            ecdsa().prop_map(Signature::EthSign),
+            vec(any::<u8>(), 0..100).prop_map(Signature::Eip1271),
+            Just(Signature::PreSign),
+        ]
+    }
+
+    fn settlement() -> impl Strategy<Value = EncodedSettlement> {
+        vec(address(), 1..5)
+            .prop_flat_map(|tokens| {
+                let len = tokens.len();
+                let trade = (order(), signature(), address(), 0..len, 0..len, u256()).prop_map(
+                    |(order, signature, owner, sell_token_index, buy_token_index, executed)| {
+                        encode_trade(
+                            &order,
+                            &signature,
+                            owner,
+                            sell_token_index,
+                            buy_token_index,
+                            &executed,
+                        )
+                    },
+                );
+                let interactions = || {
+                    vec(
+                        (address(), u256(), vec(any::<u8>(), 0..100))
+                            .prop_map(|(target, value, data)| (target, value, Bytes(data))),
+                        0..3,
+                    )
+                };
+                (
+                    Just(tokens),
+                    vec(u256(), len),
+                    vec(trade, 0..4),
+                    (interactions(), interactions(), interactions()),
+                )
+            })
+            .prop_map(
+                |(tokens, clearing_prices, trades, (pre, intra, post))| EncodedSettlement {
+                    tokens,
+                    clearing_prices,
+                    trades,
+                    interactions: [pre, intra, post],
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn settlement_round_trip(settlement in settlement()) {
+            let calldata = settlement.encode_calldata();
+            let decoded = EncodedSettlement::decode_calldata(&calldata).unwrap();
+            prop_assert_eq!(&decoded, &settlement);
+
+            for (trade, decoded) in settlement
+                .trades
+                .iter()
+                .zip(settlement.decode_trades().unwrap())
+            {
+                prop_assert_eq!(&decoded.encode(), trade);
+            }
+        }
+
+        #[test]
+        fn trade_round_trip(
+            order in order(),
+            signature in signature(),
+            owner in address(),
+            tokens in vec(address(), 2),
+            executed_amount in u256(),
+        ) {
+            let trade = encode_trade(&order, &signature, owner, 0, 1, &executed_amount);
+            let decoded = decode_trade(&trade, &tokens).unwrap();
+            prop_assert_eq!(
+                decoded,
+                DecodedTrade {
+                    order: OrderData {
+                        sell_token: tokens[0],
+                        buy_token: tokens[1],
+                        ..order
+                    },
+                    owner: match signature {
+                        Signature::Eip712(_) | Signature::EthSign(_) => None,
+                        Signature::Eip1271(_) | Signature::PreSign => Some(owner),
+                    },
+                    signature,
+                    sell_token_index: 0,
+                    buy_token_index: 1,
+                    executed_amount,
+                }
+            );
+        }
+
+        #[test]
+        fn decoded_signatures_encode_to_the_same_bytes(
+            scheme in prop_oneof![
+                Just(SigningScheme::Eip712),
+                Just(SigningScheme::EthSign),
+                Just(SigningScheme::Eip1271),
+                Just(SigningScheme::PreSign),
+            ],
+            bytes in prop_oneof![
+                vec(any::<u8>(), 0..100),
+                vec(any::<u8>(), 20),
+                vec(any::<u8>(), 65),
+            ],
+        ) {
+            if let Ok((signature, owner)) = decode_signature(scheme, &bytes) {
+                prop_assert_eq!(
+                    signature.encode_for_settlement(owner.unwrap_or_default()),
+                    bytes
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_encodings() {
+        let mut calldata = EncodedSettlement::default().encode_calldata();
+        calldata[0] ^= 1;
+        assert!(matches!(
+            EncodedSettlement::decode_calldata(&calldata),
+            Err(DecodingError::InvalidSelector)
+        ));
+
+        let mut trade = encode_trade(
+            &Default::default(),
+            &Signature::PreSign,
+            Default::default(),
+            0,
+            1,
+            &Default::default(),
+        );
+        assert!(matches!(
+            decode_trade(&trade, &[H160::zero()]),
+            Err(DecodingError::TokenIndex(_))
+        ));
+        for signature in [vec![], vec![1; 40]] {
+            let mut trade = trade.clone();
+            trade.10 = Bytes(signature);
+            assert!(matches!(
+                decode_trade(&trade, &[H160::zero(); 2]),
+                Err(DecodingError::Signature(_))
+            ));
+        }
+        trade.8 = U256::from(0b1000_0000);
+        assert!(matches!(
+            decode_trade(&trade, &[H160::zero(); 2]),
+            Err(DecodingError::Flags(_))
+        ));
+    }
 
     #[test]
     fn order_flag_permutations() {
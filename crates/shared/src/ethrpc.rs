@@ -7,7 +7,10 @@ pub use ethrpc::{
 };
 use {
     crate::http_client::HttpClientFactory,
+    anyhow::Context,
+    chrono::{Days, NaiveDate},
     reqwest::Url,
+    sqlx::PgPool,
     std::{
         fmt::{self, Display, Formatter},
         time::Duration,
@@ -32,8 +35,11 @@ pub struct Arguments {
 
     /// Buffering "nagle" delay to wait for additional requests before sending
     /// out an incomplete batch.
-    #[clap(long, env, value_parser = humantime::parse_duration, default_value = "0s")]
+    #[clap(long, env, value_parser = crate::arguments::parse_duration, default_value = "0s")]
     pub ethrpc_batch_delay: Duration,
+
+    #[clap(flatten)]
+    pub budget: BudgetArguments,
 }
 
 impl Display for Arguments {
@@ -42,6 +48,7 @@ impl Display for Arguments {
             ethrpc_max_batch_size,
             ethrpc_max_concurrent_requests,
             ethrpc_batch_delay,
+            budget,
         } = self;
 
         writeln!(f, "ethrpc_max_batch_size: {}", ethrpc_max_batch_size)?;
@@ -51,11 +58,136 @@ impl Display for Arguments {
             ethrpc_max_concurrent_requests
         )?;
         writeln!(f, "ethrpc_batch_delay: {:?}", ethrpc_batch_delay)?;
+        write!(f, "{}", budget)?;
+
+        Ok(())
+    }
+}
+
+/// Command line arguments for the daily budget of Ethereum RPC requests.
+#[derive(clap::Parser, Debug, Default)]
+#[group(skip)]
+pub struct BudgetArguments {
+    /// Number of RPC requests all components together may send per UTC day.
+    /// Only used to throttle background components, requests never get
+    /// rejected because of it.
+    #[clap(long, env)]
+    pub ethrpc_daily_request_budget: Option<u64>,
+
+    /// Share of the daily request budget after which requests of background
+    /// components get throttled.
+    #[clap(long, env, default_value = "0.8")]
+    pub ethrpc_budget_throttle_threshold: f64,
+
+    /// Labels of components whose requests get throttled when the daily
+    /// request budget runs low, e.g. `tokenOwners,trace`.
+    #[clap(long, env, use_value_delimiter = true)]
+    pub ethrpc_background_components: Vec<String>,
+
+    /// How long throttled requests of background components get delayed.
+    #[clap(long, env, value_parser = crate::arguments::parse_duration, default_value = "1s")]
+    pub ethrpc_throttle_delay: Duration,
+
+    /// How often the RPC requests of the service get persisted in the
+    /// database, which shares the budget with the other services using the
+    /// database.
+    #[clap(long, env, value_parser = crate::arguments::parse_duration, default_value = "10s")]
+    pub ethrpc_budget_sync_interval: Duration,
+}
+
+impl Display for BudgetArguments {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Self {
+            ethrpc_daily_request_budget,
+            ethrpc_budget_throttle_threshold,
+            ethrpc_background_components,
+            ethrpc_throttle_delay,
+            ethrpc_budget_sync_interval,
+        } = self;
+
+        writeln!(
+            f,
+            "ethrpc_daily_request_budget: {:?}",
+            ethrpc_daily_request_budget
+        )?;
+        writeln!(
+            f,
+            "ethrpc_budget_throttle_threshold: {}",
+            ethrpc_budget_throttle_threshold
+        )?;
+        writeln!(
+            f,
+            "ethrpc_background_components: {:?}",
+            ethrpc_background_components
+        )?;
+        writeln!(f, "ethrpc_throttle_delay: {:?}", ethrpc_throttle_delay)?;
+        writeln!(
+            f,
+            "ethrpc_budget_sync_interval: {:?}",
+            ethrpc_budget_sync_interval
+        )?;
 
         Ok(())
     }
 }
 
+impl BudgetArguments {
+    /// Configures the daily RPC request budget of the process if one is set.
+    /// With a database the usage gets persisted so that the budget survives
+    /// restarts and gets shared with the other services using the database.
+    pub fn configure(&self, db: Option<PgPool>) {
+        let Some(daily_requests) = self.ethrpc_daily_request_budget else {
+            return;
+        };
+        ethrpc::budget::configure(ethrpc::budget::Budget {
+            daily_requests,
+            throttle_threshold: self.ethrpc_budget_throttle_threshold,
+            background_components: self.ethrpc_background_components.clone(),
+            throttle_delay: self.ethrpc_throttle_delay,
+        });
+        if let Some(db) = db {
+            observe::metrics::spawn_in_current_scope(sync_budget_forever(
+                db,
+                self.ethrpc_budget_sync_interval,
+            ));
+        }
+    }
+}
+
+/// Periodically persists the RPC requests of the process and picks up the
+/// requests other services sent in the meantime.
+async fn sync_budget_forever(db: PgPool, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        let unsynced = ethrpc::budget::take_unsynced();
+        let result = sync_budget(&db, &unsynced).await;
+        if let Err(err) = &result {
+            tracing::warn!(?err, "failed to sync RPC budget");
+        }
+        ethrpc::budget::synced(unsynced, result.ok());
+    }
+}
+
+async fn sync_budget(db: &PgPool, unsynced: &ethrpc::budget::Unsynced) -> anyhow::Result<u64> {
+    let date = |day: u64| {
+        NaiveDate::default()
+            .checked_add_days(Days::new(day))
+            .context("day out of range")
+    };
+    let mut ex = database::instrumentation::acquire(db).await?;
+    if let Some((day, requests)) = unsynced.carry_over {
+        database::rpc_usage::add(&mut ex, date(day)?, i64::try_from(requests)?).await?;
+    }
+    let total = database::rpc_usage::add(
+        &mut ex,
+        date(unsynced.day)?,
+        i64::try_from(unsynced.requests)?,
+    )
+    .await?;
+    Ok(u64::try_from(total)?)
+}
+
 impl Arguments {
     fn ethrpc(&self) -> ethrpc::Config {
         ethrpc::Config {
@@ -76,3 +208,15 @@ pub fn web3(
     let http_builder = http_factory.builder();
     ethrpc::web3(args.ethrpc(), http_builder, url, name)
 }
+
+/// Create a Web3 instance that fails over to the fallback nodes in order.
+pub fn web3_with_fallbacks(
+    args: &Arguments,
+    http_factory: &HttpClientFactory,
+    url: &Url,
+    fallbacks: &[Url],
+    name: impl ToString,
+) -> Web3 {
+    let http_builder = http_factory.builder();
+    ethrpc::web3_with_fallbacks(args.ethrpc(), http_builder, url, fallbacks, name)
+}
@@ -15,15 +15,8 @@ use {
     tracing::Instrument,
 };
 
-// We expect that there is never a reorg that changes more than the last n
-// blocks.
-pub const MAX_REORG_BLOCK_COUNT: u64 = 64;
 // Saving events, we process at most this many at a time.
 const INSERT_EVENT_BATCH_SIZE: usize = 10_000;
-// MAX_BLOCKS_QUERIED is bigger than MAX_REORG_BLOCK_COUNT to increase the
-// chances of avoiding the need for history fetch of block events, since history
-// fetch is less efficient than latest block fetch
-const MAX_BLOCKS_QUERIED: u64 = 2 * MAX_REORG_BLOCK_COUNT;
 // Max number of rpc calls that can be sent at the same time to the node.
 const MAX_PARALLEL_RPC_CALLS: usize = 128;
 
@@ -50,6 +43,9 @@ where
     contract: C,
     store: S,
     last_handled_blocks: Vec<BlockNumberHash>,
+    /// We expect that there is never a reorg that changes more than this many
+    /// of the latest blocks.
+    finality_depth: u64,
 }
 
 /// `EventStoring` is used by `EventHandler` for the purpose of giving the user
@@ -122,9 +118,18 @@ where
                     None => vec![],
                 }
             },
+            finality_depth: chain::DEFAULT_FINALITY_DEPTH,
         }
     }
 
+    /// Sets the deepest reorg the handler recovers from, usually
+    /// [`chain::Chain::finality_depth`]. Defaults to
+    /// [`chain::DEFAULT_FINALITY_DEPTH`].
+    pub fn with_finality_depth(mut self, finality_depth: u64) -> Self {
+        self.finality_depth = finality_depth;
+        self
+    }
+
     /// Creates a new instance of the event handler that does not index events
     /// appearing in blocks before the specified input date. Note that this
     /// is a different behavior compared to [`Self::new()`]: that function
@@ -217,7 +222,7 @@ where
         if let Ok(block_range) =
             RangeInclusive::try_new(last_handled_block_number, current_block_number)
         {
-            if block_range.end() - block_range.start() <= MAX_REORG_BLOCK_COUNT {
+            if block_range.end() - block_range.start() <= self.finality_depth {
                 let mut new_blocks = self.block_retriever.blocks(block_range).await?;
                 if new_blocks.first().map(|b| b.1) == Some(last_handled_block_hash) {
                     // first block is not actually new and was only fetched to detect a reorg
@@ -238,11 +243,14 @@ where
 
         // full range of blocks which are considered for event update
         let block_range = RangeInclusive::try_new(
-            last_handled_block_number.saturating_sub(MAX_REORG_BLOCK_COUNT),
+            last_handled_block_number.saturating_sub(self.finality_depth),
             current_block_number,
         )?;
 
-        let (history_range, latest_range) = split_range(block_range);
+        // Query more blocks than the finality depth to increase the chances of
+        // avoiding the need for history fetch of block events, since history
+        // fetch is less efficient than latest block fetch.
+        let (history_range, latest_range) = split_range(block_range, 2 * self.finality_depth);
         tracing::debug!(
             "history range {:?}, latest_range {:?}",
             history_range,
@@ -306,7 +314,7 @@ where
         let blocks = self
             .block_retriever
             .blocks(RangeInclusive::try_new(
-                range.end().saturating_sub(MAX_REORG_BLOCK_COUNT),
+                range.end().saturating_sub(self.finality_depth),
                 *range.end(),
             )?)
             .await?;
@@ -334,11 +342,11 @@ where
         // of the code calculates the total executed amount of an order.
         //    If this happened right after deletion but before insertion, then the
         // result would be    wrong. In theory this could still happen if the
-        // last MAX_REORG_BLOCK_COUNT blocks had    more than
+        // last `finality_depth` blocks had    more than
         // INSERT_TRADE_BATCH_SIZE trade events but this is unlikely.
         // There alternative solutions for 2. but this one is the most practical. For
         // example, we could keep all reorg-able events in this struct and only
-        // store ones that are older than MAX_REORG_BLOCK_COUNT in the database
+        // store ones that are older than `finality_depth` in the database
         // but then any code using trade events would have to go through this
         // class instead of being able to work with the database directly. Or we
         // could make the batch size unlimited but this runs into problems when we have
@@ -472,11 +480,11 @@ where
             .retain(|block| block.0 < blocks.first().unwrap().0);
         // append new canonical blocks
         self.last_handled_blocks.extend(blocks.iter());
-        // cap number of blocks to MAX_REORG_BLOCK_COUNT
+        // cap number of blocks to the finality depth
         let start_index = self
             .last_handled_blocks
             .len()
-            .saturating_sub(MAX_REORG_BLOCK_COUNT as usize);
+            .saturating_sub(self.finality_depth as usize);
         self.last_handled_blocks = self.last_handled_blocks[start_index..].to_vec();
         tracing::debug!(
             "last_handled_blocks after update: {:?} - {:?}",
@@ -515,15 +523,18 @@ fn detect_reorg_path<'a>(
 }
 
 /// Splits range into two disjuctive consecutive ranges, second one containing
-/// last (up to) MAX_BLOCKS_QUERIED elements, first one containing the rest (if
-/// any)
-fn split_range(range: RangeInclusive<u64>) -> (Option<RangeInclusive<u64>>, RangeInclusive<u64>) {
+/// last (up to) `max_blocks_queried` elements, first one containing the rest
+/// (if any)
+fn split_range(
+    range: RangeInclusive<u64>,
+    max_blocks_queried: u64,
+) -> (Option<RangeInclusive<u64>>, RangeInclusive<u64>) {
     let (start, end) = range.clone().into_inner();
 
-    if end.saturating_sub(start) > MAX_BLOCKS_QUERIED {
+    if end.saturating_sub(start) > max_blocks_queried {
         (
-            Some(RangeInclusive::try_new(start, end - MAX_BLOCKS_QUERIED).unwrap()),
-            RangeInclusive::try_new(end - MAX_BLOCKS_QUERIED + 1, end).unwrap(),
+            Some(RangeInclusive::try_new(start, end - max_blocks_queried).unwrap()),
+            RangeInclusive::try_new(end - max_blocks_queried + 1, end).unwrap(),
         )
     } else {
         (None, range)
@@ -634,6 +645,8 @@ mod tests {
         pub GPv2SettlementContract for gpv2_settlement
     }
 
+    const MAX_BLOCKS_QUERIED: u64 = 2 * chain::DEFAULT_FINALITY_DEPTH;
+
     /// Simple event storage for testing purposes of EventHandler
     struct EventStorage<T> {
         pub events: Vec<EthcontractEvent<T>>,
@@ -749,28 +762,28 @@ mod tests {
     #[test]
     fn split_range_test_equal() {
         let range = RangeInclusive::try_new(0, 0).unwrap();
-        let (history_range, latest_range) = split_range(range.clone());
+        let (history_range, latest_range) = split_range(range.clone(), MAX_BLOCKS_QUERIED);
         assert!(history_range.is_none() && latest_range == range);
     }
 
     #[test]
     fn split_range_test_max_queries() {
         let range = RangeInclusive::try_new(0, MAX_BLOCKS_QUERIED).unwrap();
-        let (history_range, latest_range) = split_range(range.clone());
+        let (history_range, latest_range) = split_range(range.clone(), MAX_BLOCKS_QUERIED);
         assert!(history_range.is_none() && latest_range == range);
     }
 
     #[test]
     fn split_range_test_max_queries_minus_one() {
         let range = RangeInclusive::try_new(0, MAX_BLOCKS_QUERIED - 1).unwrap();
-        let (history_range, latest_range) = split_range(range.clone());
+        let (history_range, latest_range) = split_range(range.clone(), MAX_BLOCKS_QUERIED);
         assert!(history_range.is_none() && latest_range == range);
     }
 
     #[test]
     fn split_range_test_max_queries_plus_one() {
         let range = RangeInclusive::try_new(0, MAX_BLOCKS_QUERIED + 1).unwrap();
-        let (history_range, latest_range) = split_range(range);
+        let (history_range, latest_range) = split_range(range, MAX_BLOCKS_QUERIED);
         assert_eq!(history_range, Some(RangeInclusive::try_new(0, 1).unwrap()));
         assert_eq!(
             latest_range,
@@ -906,13 +919,13 @@ mod tests {
                 .filter(|e| {
                     // We make the test robust against reorgs by removing events that are too new
                     e.meta.as_ref().unwrap().block_number
-                        <= (current_block - MAX_REORG_BLOCK_COUNT).as_u64()
+                        <= (current_block - chain::DEFAULT_FINALITY_DEPTH).as_u64()
                 })
                 .collect::<Vec<_>>()
         };
 
         // We expect that in the past ~24h intervals there have been two events in the
-        // settlement contract that are at least MAX_REORG_BLOCK_COUNT apart.
+        // settlement contract that are at least the finality depth apart.
         const RANGE_SIZE: u64 = 24 * 3600 / 12;
 
         let storage_empty = EventStorage { events: vec![] };
@@ -969,7 +982,7 @@ mod tests {
             .expect("Should have some events")
             .clone();
         assert!(
-            first_event.meta.as_ref().unwrap().block_number + MAX_REORG_BLOCK_COUNT + 1
+            first_event.meta.as_ref().unwrap().block_number + chain::DEFAULT_FINALITY_DEPTH + 1
                 < last_event.meta.as_ref().unwrap().block_number,
             "Test assumption broken"
         );
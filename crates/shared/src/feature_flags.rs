@@ -0,0 +1,350 @@
+//! Feature flags that can be flipped at runtime.
+//!
+//! Flags are stored in the database, so risky changes can be rolled out and
+//! rolled back without redeploying the services evaluating them. Every flag has
+//! a default value for all chains which can be overridden per chain. Services
+//! evaluate flags through typed [`Flag`]s that fall back to their built-in
+//! default if no value is stored or the stored value has the wrong type.
+//! Stored values get cached for a short time, so flags can be evaluated on hot
+//! paths. Evaluations never wait for the database while a previous value is
+//! cached: one of them reloads the values while the others keep evaluating the
+//! previous ones.
+
+use {
+    anyhow::Result,
+    chrono::{DateTime, Utc},
+    database::feature_flags::{FeatureFlag, ALL_CHAINS},
+    serde::{de::DeserializeOwned, Serialize},
+    serde_json::Value,
+    sqlx::PgPool,
+    std::{
+        collections::HashMap,
+        sync::{Arc, RwLock},
+        time::{Duration, Instant},
+    },
+    tokio::sync::Mutex,
+};
+
+/// A typed feature flag together with the value services use if no value is
+/// stored.
+#[derive(Clone, Copy, Debug)]
+pub struct Flag<T> {
+    pub name: &'static str,
+    pub default: T,
+}
+
+impl<T> Flag<T> {
+    pub const fn new(name: &'static str, default: T) -> Self {
+        Self { name, default }
+    }
+}
+
+/// A flag value stored in the database.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredValue {
+    pub name: String,
+    /// Chain the value is specific to. `None` for the default of all chains.
+    pub chain_id: Option<u64>,
+    pub value: Value,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Where the value of an evaluated flag came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    /// The value stored for the chain of the service.
+    Chain,
+    /// The value stored for all chains.
+    AllChains,
+    /// No value is stored.
+    Default,
+    /// The stored value has the wrong type.
+    Invalid,
+}
+
+impl Source {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Chain => "chain",
+            Self::AllChains => "all_chains",
+            Self::Default => "default",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Cache {
+    /// `None` until the values got loaded for the first time.
+    values: Option<Values>,
+    fetched_at: Option<Instant>,
+}
+
+type Values = Arc<HashMap<String, (Value, Source)>>;
+
+impl Cache {
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.fetched_at
+            .is_some_and(|fetched_at| fetched_at.elapsed() < ttl)
+    }
+}
+
+pub struct FeatureFlags {
+    db: PgPool,
+    chain_id: u64,
+    ttl: Duration,
+    /// Only ever locked for swapping the values, never across a database
+    /// query.
+    cache: RwLock<Cache>,
+    /// Held while reloading the values, so only one evaluation queries the
+    /// database at a time.
+    reload: Mutex<()>,
+}
+
+impl FeatureFlags {
+    /// Creates feature flags evaluated for the chain. Stored values get
+    /// reloaded once they are older than `ttl`.
+    pub fn new(db: PgPool, chain_id: u64, ttl: Duration) -> Self {
+        Self {
+            db,
+            chain_id,
+            ttl,
+            cache: Default::default(),
+            reload: Default::default(),
+        }
+    }
+
+    /// Evaluates the flag.
+    pub async fn get<T: DeserializeOwned + Clone>(&self, flag: &Flag<T>) -> T {
+        let values = self.values().await;
+        let (value, source) = match values.get(flag.name) {
+            Some((value, source)) => match serde_json::from_value(value.clone()) {
+                Ok(value) => (value, *source),
+                Err(err) => {
+                    tracing::warn!(?err, flag = flag.name, "invalid feature flag value");
+                    (flag.default.clone(), Source::Invalid)
+                }
+            },
+            None => (flag.default.clone(), Source::Default),
+        };
+        Metrics::get()
+            .feature_flag_evaluations
+            .with_label_values(&[flag.name, source.as_str()])
+            .inc();
+        value
+    }
+
+    /// Returns the cached values, reloading them first if they expired.
+    async fn values(&self) -> Values {
+        if let Some(values) = self.fresh_values() {
+            return values;
+        }
+        let cached = self.cache.read().unwrap().values.clone();
+        let _reload = match (self.reload.try_lock(), cached) {
+            (Ok(guard), _) => guard,
+            // Another evaluation is reloading the values already.
+            (Err(_), Some(values)) => return values,
+            // Nothing got loaded yet, so wait for the values instead of
+            // evaluating every flag to its default.
+            (Err(_), None) => self.reload.lock().await,
+        };
+        // The values could have been reloaded while waiting for the lock.
+        if let Some(values) = self.fresh_values() {
+            return values;
+        }
+
+        let loaded = self.load().await;
+        let mut cache = self.cache.write().unwrap();
+        match loaded {
+            Ok(values) => cache.values = Some(Arc::new(values)),
+            // Keep evaluating the previous values until the database is
+            // reachable again.
+            Err(err) => tracing::warn!(?err, "failed to load feature flags"),
+        }
+        cache.fetched_at = Some(Instant::now());
+        cache.values.get_or_insert_with(Default::default).clone()
+    }
+
+    fn fresh_values(&self) -> Option<Values> {
+        let cache = self.cache.read().unwrap();
+        cache.values.clone().filter(|_| cache.is_fresh(self.ttl))
+    }
+
+    /// Whether the boolean flag is enabled.
+    pub async fn enabled(&self, flag: &Flag<bool>) -> bool {
+        self.get(flag).await
+    }
+
+    /// Returns all values stored for the chain and for all chains.
+    pub async fn stored(&self) -> Result<Vec<StoredValue>> {
+        let mut ex = database::instrumentation::acquire(&self.db).await?;
+        database::feature_flags::load(&mut ex, self.chain_id.try_into()?)
+            .await?
+            .into_iter()
+            .map(|flag| {
+                Ok::<_, anyhow::Error>(StoredValue {
+                    name: flag.name,
+                    chain_id: match flag.chain_id {
+                        ALL_CHAINS => None,
+                        chain_id => Some(chain_id.try_into()?),
+                    },
+                    value: flag.value,
+                    updated_at: flag.updated_at,
+                })
+            })
+            .collect()
+    }
+
+    /// Stores the value of the flag for the chain of the service or, if
+    /// `chain_specific` is false, for all chains.
+    pub async fn set(&self, name: &str, chain_specific: bool, value: Value) -> Result<()> {
+        let flag = FeatureFlag {
+            name: name.to_string(),
+            chain_id: self.scope(chain_specific)?,
+            value,
+            updated_at: Utc::now(),
+        };
+        let mut ex = database::instrumentation::acquire(&self.db).await?;
+        database::feature_flags::upsert(&mut ex, &flag).await?;
+        tracing::info!(?flag, "feature flag updated");
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Removes the value stored for the chain of the service or, if
+    /// `chain_specific` is false, for all chains. Returns whether a value got
+    /// removed.
+    pub async fn unset(&self, name: &str, chain_specific: bool) -> Result<bool> {
+        let chain_id = self.scope(chain_specific)?;
+        let mut ex = database::instrumentation::acquire(&self.db).await?;
+        let removed = database::feature_flags::delete(&mut ex, name, chain_id).await?;
+        if removed {
+            tracing::info!(name, chain_id, "feature flag removed");
+            self.invalidate();
+        }
+        Ok(removed)
+    }
+
+    fn scope(&self, chain_specific: bool) -> Result<i64> {
+        match chain_specific {
+            true => Ok(self.chain_id.try_into()?),
+            false => Ok(ALL_CHAINS),
+        }
+    }
+
+    /// Makes the next evaluation reload the stored values. Other services
+    /// pick up the change once their cache expires.
+    fn invalidate(&self) {
+        self.cache.write().unwrap().fetched_at = None;
+    }
+
+    async fn load(&self) -> Result<HashMap<String, (Value, Source)>> {
+        let _timer = database::instrumentation::time_query("load_feature_flags");
+        let mut ex = database::instrumentation::acquire(&self.db).await?;
+        let flags = database::feature_flags::load(&mut ex, self.chain_id.try_into()?).await?;
+        Ok(resolve(flags))
+    }
+}
+
+/// Resolves the value of every flag. Values stored for the chain override the
+/// ones stored for all chains.
+fn resolve(flags: Vec<FeatureFlag>) -> HashMap<String, (Value, Source)> {
+    let mut values = HashMap::new();
+    for flag in flags {
+        let source = match flag.chain_id {
+            ALL_CHAINS => Source::AllChains,
+            _ => Source::Chain,
+        };
+        match values.get(&flag.name) {
+            Some((_, Source::Chain)) if source == Source::AllChains => (),
+            _ => {
+                values.insert(flag.name, (flag.value, source));
+            }
+        }
+    }
+    values
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Number of feature flag evaluations by where the value came from.
+    #[metric(labels("flag", "source"))]
+    feature_flag_evaluations: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json, sqlx::postgres::PgPoolOptions};
+
+    fn stored(name: &str, chain_id: i64, value: Value) -> FeatureFlag {
+        FeatureFlag {
+            name: name.to_string(),
+            chain_id,
+            value,
+            updated_at: Default::default(),
+        }
+    }
+
+    #[test]
+    fn chain_values_override_defaults() {
+        let values = resolve(vec![
+            stored("a", 1, json!(true)),
+            stored("a", ALL_CHAINS, json!(false)),
+            stored("b", ALL_CHAINS, json!(false)),
+            stored("b", 1, json!(true)),
+            stored("c", ALL_CHAINS, json!(1)),
+        ]);
+        assert_eq!(values["a"], (json!(true), Source::Chain));
+        assert_eq!(values["b"], (json!(true), Source::Chain));
+        assert_eq!(values["c"], (json!(1), Source::AllChains));
+    }
+
+    #[tokio::test]
+    async fn evaluates_typed_flags() {
+        let flags = FeatureFlags::new(
+            PgPool::connect_lazy("postgresql://").unwrap(),
+            1,
+            Duration::from_secs(60),
+        );
+        *flags.cache.write().unwrap() = Cache {
+            values: Some(Arc::new(resolve(vec![
+                stored("enabled", ALL_CHAINS, json!(true)),
+                stored("limit", 1, json!(5)),
+                stored("wrong_type", ALL_CHAINS, json!("yes")),
+            ]))),
+            fetched_at: Some(Instant::now()),
+        };
+
+        assert!(flags.enabled(&Flag::new("enabled", false)).await);
+        assert!(!flags.enabled(&Flag::new("wrong_type", false)).await);
+        assert!(flags.enabled(&Flag::new("missing", true)).await);
+        assert_eq!(flags.get(&Flag::new("limit", 1u32)).await, 5);
+    }
+
+    #[tokio::test]
+    async fn keeps_values_while_database_is_unreachable() {
+        let db = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgresql://localhost:1/")
+            .unwrap();
+        let flags = FeatureFlags::new(db, 1, Duration::ZERO);
+        *flags.cache.write().unwrap() = Cache {
+            values: Some(Arc::new(resolve(vec![stored(
+                "enabled",
+                ALL_CHAINS,
+                json!(true),
+            )]))),
+            fetched_at: None,
+        };
+
+        assert!(flags.enabled(&Flag::new("enabled", false)).await);
+        assert!(flags.cache.read().unwrap().fetched_at.is_some());
+    }
+}
@@ -0,0 +1,519 @@
+//! Caching of HTTP responses of third-party APIs.
+//!
+//! Metadata like token lists rarely changes but gets fetched periodically.
+//! Responses get cached according to their `Cache-Control` header and are
+//! revalidated with `If-None-Match` (`ETag`) or `If-Modified-Since`
+//! (`Last-Modified`) once they are stale, so unchanged resources don't get
+//! downloaded again.
+//!
+//! The cache is bounded by the total size of the cached bodies and evicts the
+//! least recently used responses first. Optionally, it persists responses in a
+//! directory so they survive restarts. Persisted responses count against the
+//! same bound and get loaded back into memory on startup.
+
+use {
+    anyhow::{Context, Result},
+    reqwest::{
+        header::{self, HeaderMap},
+        Client,
+        StatusCode,
+        Url,
+    },
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+        time::{Duration, SystemTime},
+    },
+};
+
+/// A shared store of cached responses.
+#[derive(Debug)]
+pub struct ResponseCache {
+    /// Maximum total size of the cached bodies in bytes.
+    capacity: usize,
+    /// Directory the responses get persisted in.
+    dir: Option<PathBuf>,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    size: usize,
+    /// Incremented on every access to find the least recently used entry.
+    clock: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Entry {
+    /// The key of the entry, persisted to restore the cache after restarts.
+    key: String,
+    /// Value of the clock when the entry was last used. Persisted so restored
+    /// entries keep the order in which they were stored.
+    last_used: u64,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Until when the response can be used without revalidating it.
+    fresh_until: SystemTime,
+    #[serde(with = "bytes_hex")]
+    body: Vec<u8>,
+}
+
+/// How a response may be cached according to its headers.
+#[derive(Debug, PartialEq)]
+enum Policy {
+    /// The response must not be stored.
+    NoStore,
+    /// The response may be used without revalidation for the given time.
+    FreshFor(Duration),
+}
+
+impl Policy {
+    fn of(headers: &HeaderMap) -> Self {
+        let directives = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|directive| directive.trim().to_ascii_lowercase());
+        let mut max_age = None;
+        for directive in directives {
+            match directive.split_once('=') {
+                None if directive == "no-store" => return Self::NoStore,
+                None if directive == "no-cache" => max_age = Some(0),
+                Some(("max-age", seconds)) if max_age.is_none() => {
+                    max_age = seconds.trim_matches('"').parse().ok();
+                }
+                _ => (),
+            }
+        }
+        // The response might have been cached by intermediaries already.
+        let age = headers
+            .get(header::AGE)
+            .and_then(|age| age.to_str().ok()?.parse().ok())
+            .unwrap_or(0);
+        Self::FreshFor(Duration::from_secs(
+            max_age.unwrap_or(0).saturating_sub(age),
+        ))
+    }
+}
+
+impl Inner {
+    /// Stores the entry and returns the keys of the entries that got evicted
+    /// to make room for it, or `None` if it is too large to be stored.
+    fn insert(&mut self, capacity: usize, mut entry: Entry) -> Option<Vec<String>> {
+        if entry.body.len() > capacity {
+            return None;
+        }
+        self.clock = self.clock.max(entry.last_used) + 1;
+        entry.last_used = self.clock;
+        self.size += entry.body.len();
+        if let Some(previous) = self.entries.insert(entry.key.clone(), entry) {
+            self.size -= previous.body.len();
+        }
+        let mut evicted = Vec::new();
+        while self.size > capacity {
+            let key = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+                .unwrap();
+            let entry = self.entries.remove(&key).unwrap();
+            self.size -= entry.body.len();
+            evicted.push(key);
+        }
+        Some(evicted)
+    }
+}
+
+impl ResponseCache {
+    /// Creates a cache holding bodies of up to `capacity` bytes in total.
+    /// Responses get persisted in `dir` if provided. Responses persisted by
+    /// earlier runs are loaded from it and files exceeding the capacity or not
+    /// belonging to any response get removed.
+    pub fn new(capacity: usize, dir: Option<PathBuf>) -> Result<Self> {
+        let cache = Self {
+            capacity,
+            dir,
+            inner: Default::default(),
+        };
+        if let Some(dir) = &cache.dir {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("could not create cache directory {dir:?}"))?;
+            cache.load(dir)?;
+        }
+        Ok(cache)
+    }
+
+    /// Restores the responses persisted in the directory. Only called on
+    /// startup so blocking file system access is fine.
+    fn load(&self, dir: &Path) -> Result<()> {
+        let mut entries = Vec::new();
+        for file in fs::read_dir(dir).with_context(|| format!("could not read {dir:?}"))? {
+            let path = file?.path();
+            let entry = fs::read(&path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<Entry>(&bytes).ok())
+                .filter(|entry| self.path(&entry.key).as_ref() == Some(&path));
+            match entry {
+                Some(entry) => entries.push(entry),
+                None => remove_file(&path),
+            }
+        }
+        entries.sort_by_key(|entry| entry.last_used);
+
+        let mut inner = self.inner.lock().unwrap();
+        for entry in entries {
+            let key = entry.key.clone();
+            let evicted = inner.insert(self.capacity, entry).unwrap_or(vec![key]);
+            for key in evicted {
+                if let Some(path) = self.path(&key) {
+                    remove_file(&path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        let hash = web3::signing::keccak256(key.as_bytes());
+        Some(
+            self.dir
+                .as_ref()?
+                .join(format!("{}.json", hex::encode(&hash[..16]))),
+        )
+    }
+
+    fn get(&self, key: &str) -> Option<Entry> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.clock += 1;
+        let clock = inner.clock;
+        let entry = inner.entries.get_mut(key)?;
+        entry.last_used = clock;
+        Some(entry.clone())
+    }
+
+    async fn insert(&self, entry: Entry) {
+        let path = self.path(&entry.key);
+        let Some(evicted) = self
+            .inner
+            .lock()
+            .unwrap()
+            .insert(self.capacity, entry.clone())
+        else {
+            return;
+        };
+        let Some(path) = path else {
+            return;
+        };
+
+        for key in evicted {
+            if let Some(path) = self.path(&key) {
+                if let Err(err) = tokio::fs::remove_file(&path).await {
+                    tracing::warn!(?err, ?path, "failed to remove evicted response");
+                }
+            }
+        }
+        let result = match serde_json::to_vec(&entry) {
+            Ok(bytes) => tokio::fs::write(&path, bytes).await.map_err(Into::into),
+            Err(err) => Err(anyhow::Error::from(err)),
+        };
+        if let Err(err) = result {
+            tracing::warn!(?err, ?path, "failed to persist cached response");
+        }
+    }
+}
+
+fn remove_file(path: &Path) {
+    if let Err(err) = fs::remove_file(path) {
+        tracing::warn!(?err, ?path, "failed to remove cached response");
+    }
+}
+
+/// A client that caches the responses of `GET` requests.
+#[derive(Clone, Debug)]
+pub struct CachingClient {
+    client: Client,
+    cache: Arc<ResponseCache>,
+}
+
+/// The body of a successful response.
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+    pub body: Vec<u8>,
+    /// Whether the body was served from the cache.
+    pub cached: bool,
+}
+
+impl CachedResponse {
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body).context("invalid json")
+    }
+}
+
+impl Default for CachingClient {
+    fn default() -> Self {
+        crate::http_client::HttpClientFactory::default().create_caching()
+    }
+}
+
+impl CachingClient {
+    pub fn new(client: Client, cache: Arc<ResponseCache>) -> Self {
+        Self { client, cache }
+    }
+
+    /// Fetches the resource at `url`, using the cached response if it is
+    /// still fresh or the server confirms that it didn't change.
+    pub async fn get(&self, url: Url) -> Result<CachedResponse> {
+        let key = url.to_string();
+        let cached = self.cache.get(&key);
+        if let Some(entry) = &cached {
+            if entry.fresh_until > SystemTime::now() {
+                Metrics::get().requests("hit");
+                return Ok(CachedResponse {
+                    body: entry.body.clone(),
+                    cached: true,
+                });
+            }
+        }
+
+        let mut request = self.client.get(url);
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = request.send().await?;
+        let headers = response.headers().clone();
+        let header = |name: header::HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+
+        let (entry, cached) = match cached {
+            Some(entry) if response.status() == StatusCode::NOT_MODIFIED => {
+                Metrics::get().requests("revalidated");
+                // A `304` response only has to repeat the validators if they
+                // changed.
+                let entry = Entry {
+                    etag: header(header::ETAG).or(entry.etag),
+                    last_modified: header(header::LAST_MODIFIED).or(entry.last_modified),
+                    ..entry
+                };
+                (entry, true)
+            }
+            _ => {
+                Metrics::get().requests("miss");
+                let entry = Entry {
+                    key: key.clone(),
+                    last_used: 0,
+                    etag: header(header::ETAG),
+                    last_modified: header(header::LAST_MODIFIED),
+                    fresh_until: SystemTime::now(),
+                    body: response.error_for_status()?.bytes().await?.to_vec(),
+                };
+                (entry, false)
+            }
+        };
+
+        if let Policy::FreshFor(fresh_for) = Policy::of(&headers) {
+            // Responses that can neither be reused nor revalidated aren't
+            // worth storing.
+            if !fresh_for.is_zero() || entry.etag.is_some() || entry.last_modified.is_some() {
+                self.cache
+                    .insert(Entry {
+                        fresh_until: SystemTime::now() + fresh_for,
+                        ..entry.clone()
+                    })
+                    .await;
+            }
+        }
+
+        Ok(CachedResponse {
+            body: entry.body,
+            cached,
+        })
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Requests of caching HTTP clients by whether they were served from the
+    /// cache (`hit`), the server confirmed that the cached response is still
+    /// valid (`revalidated`) or the response had to be downloaded (`miss`).
+    #[metric(labels("result"))]
+    http_cache_requests: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+
+    fn requests(&self, result: &str) {
+        self.http_cache_requests.with_label_values(&[result]).inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        reqwest::header::HeaderValue,
+        std::sync::atomic::{AtomicUsize, Ordering},
+        tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        },
+    };
+
+    fn entry(key: &str, body: &[u8]) -> Entry {
+        Entry {
+            key: key.to_string(),
+            last_used: 0,
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+            fresh_until: SystemTime::UNIX_EPOCH,
+            body: body.to_vec(),
+        }
+    }
+
+    #[test]
+    fn parses_cache_control() {
+        let policy = |cache_control: &str, age: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CACHE_CONTROL,
+                HeaderValue::from_str(cache_control).unwrap(),
+            );
+            if let Some(age) = age {
+                headers.insert(header::AGE, HeaderValue::from_str(age).unwrap());
+            }
+            Policy::of(&headers)
+        };
+
+        assert_eq!(
+            policy("public, max-age=300", None),
+            Policy::FreshFor(Duration::from_secs(300))
+        );
+        assert_eq!(
+            policy("Max-Age=300", Some("100")),
+            Policy::FreshFor(Duration::from_secs(200))
+        );
+        assert_eq!(
+            policy("max-age=300", Some("400")),
+            Policy::FreshFor(Duration::ZERO)
+        );
+        assert_eq!(
+            policy("no-cache, max-age=300", None),
+            Policy::FreshFor(Duration::ZERO)
+        );
+        assert_eq!(policy("max-age=300, no-store", None), Policy::NoStore);
+        assert_eq!(
+            Policy::of(&HeaderMap::new()),
+            Policy::FreshFor(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used() {
+        let cache = ResponseCache::new(10, None).unwrap();
+        cache.insert(entry("a", b"aaaa")).await;
+        cache.insert(entry("b", b"bbbb")).await;
+        assert!(cache.get("a").is_some());
+        cache.insert(entry("c", b"cccc")).await;
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+
+        // Replacing an entry frees the space of the old one.
+        cache.insert(entry("c", b"cc")).await;
+        cache.insert(entry("d", b"dd")).await;
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("d").is_some());
+
+        // Responses that exceed the capacity aren't cached at all.
+        cache.insert(entry("e", &[0; 11])).await;
+        assert!(cache.get("e").is_none());
+        assert!(cache.get("a").is_some());
+    }
+
+    #[tokio::test]
+    async fn persists_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = || fs::read_dir(dir.path()).unwrap().count();
+        let cache = ResponseCache::new(10, Some(dir.path().to_owned())).unwrap();
+        cache.insert(entry("a", b"aaaa")).await;
+        cache.insert(entry("b", b"bbbb")).await;
+        cache.insert(entry("c", b"cccc")).await;
+        // Evicted responses get removed from disk as well.
+        assert_eq!(files(), 2);
+
+        let restarted = ResponseCache::new(10, Some(dir.path().to_owned())).unwrap();
+        let cached = restarted.get("c").unwrap();
+        assert_eq!(cached.body, b"cccc");
+        assert_eq!(cached.etag.as_deref(), Some("\"v1\""));
+        assert!(restarted.get("a").is_none());
+        assert!(restarted.get("b").is_some());
+
+        // Persisted responses count against the capacity after a restart and
+        // files that don't belong to a response get cleaned up.
+        fs::write(dir.path().join("unknown.json"), b"{}").unwrap();
+        let restarted = ResponseCache::new(6, Some(dir.path().to_owned())).unwrap();
+        assert!(restarted.get("b").is_none());
+        assert!(restarted.get("c").is_some());
+        assert_eq!(files(), 1);
+    }
+
+    #[tokio::test]
+    async fn revalidates_stale_responses() {
+        // Serves a response that always has to be revalidated and answers
+        // requests with a matching `If-None-Match` header with `304`.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/tokens", listener.local_addr().unwrap());
+        let not_modified = Arc::new(AtomicUsize::new(0));
+        tokio::spawn({
+            let not_modified = not_modified.clone();
+            async move {
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let mut request = Vec::new();
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let mut buffer = [0; 1024];
+                        let read = stream.read(&mut buffer).await.unwrap();
+                        request.extend_from_slice(&buffer[..read]);
+                    }
+                    let request = String::from_utf8(request).unwrap().to_lowercase();
+                    let response = if request.contains("if-none-match: \"v1\"") {
+                        not_modified.fetch_add(1, Ordering::SeqCst);
+                        "HTTP/1.1 304 Not Modified\r\nconnection: close\r\n\r\n"
+                    } else {
+                        "HTTP/1.1 200 OK\r\nconnection: close\r\netag: \"v1\"\r\n\
+                         cache-control: no-cache\r\ncontent-length: 2\r\n\r\n[]"
+                    };
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                }
+            }
+        });
+
+        let client = CachingClient::default();
+        let response = client.get(url.parse().unwrap()).await.unwrap();
+        assert_eq!(response.body, b"[]");
+        assert!(!response.cached);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 0);
+
+        let response = client.get(url.parse().unwrap()).await.unwrap();
+        assert_eq!(response.body, b"[]");
+        assert!(response.cached);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+    }
+}
@@ -15,9 +15,11 @@ pub mod ethrpc;
 pub mod event_handling;
 pub mod event_storing_helpers;
 pub mod external_prices;
+pub mod feature_flags;
 pub mod fee;
 pub mod gas_price;
 pub mod gas_price_estimation;
+pub mod http_cache;
 pub mod http_client;
 pub mod http_solver;
 pub mod interaction;
@@ -25,6 +27,7 @@ pub mod maintenance;
 pub mod order_quoting;
 pub mod order_validation;
 pub mod price_estimation;
+pub mod quote_commitment;
 pub mod recent_block_cache;
 pub mod remaining_amounts;
 pub mod request_sharing;
@@ -450,6 +450,7 @@ impl OrderQuoter {
                 interactions: trade_estimate.execution.interactions,
                 pre_interactions: trade_estimate.execution.pre_interactions,
                 jit_orders: trade_estimate.execution.jit_orders,
+                owner: Some(parameters.verification.from).filter(|from| !from.is_zero()),
             }
             .into(),
         };
@@ -699,6 +700,9 @@ pub struct QuoteMetadataV1 {
     pub pre_interactions: Vec<InteractionData>,
     /// Orders that were settled outside of the auction.
     pub jit_orders: Vec<dto::JitOrder>,
+    /// The account the quote was requested for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<H160>,
 }
 
 #[cfg(test)]
@@ -841,7 +845,11 @@ mod tests {
                 quote_kind: QuoteKind::Standard,
                 solver: H160([1; 20]),
                 verified: false,
-                metadata: Default::default(),
+                metadata: QuoteMetadataV1 {
+                    owner: Some(H160([3; 20])),
+                    ..Default::default()
+                }
+                .into(),
             }))
             .returning(|_| Ok(1337));
 
@@ -878,7 +886,11 @@ mod tests {
                     quote_kind: QuoteKind::Standard,
                     solver: H160([1; 20]),
                     verified: false,
-                    metadata: Default::default(),
+                    metadata: QuoteMetadataV1 {
+                        owner: Some(H160([3; 20])),
+                        ..Default::default()
+                    }
+                    .into(),
                 },
                 sell_amount: 70.into(),
                 buy_amount: 29.into(),
@@ -979,7 +991,11 @@ mod tests {
                 quote_kind: QuoteKind::Standard,
                 solver: H160([1; 20]),
                 verified: false,
-                metadata: Default::default(),
+                metadata: QuoteMetadataV1 {
+                    owner: Some(H160([3; 20])),
+                    ..Default::default()
+                }
+                .into(),
             }))
             .returning(|_| Ok(1337));
 
@@ -1016,7 +1032,11 @@ mod tests {
                     quote_kind: QuoteKind::Standard,
                     solver: H160([1; 20]),
                     verified: false,
-                    metadata: Default::default(),
+                    metadata: QuoteMetadataV1 {
+                        owner: Some(H160([3; 20])),
+                        ..Default::default()
+                    }
+                    .into(),
                 },
                 sell_amount: 100.into(),
                 buy_amount: 42.into(),
@@ -1112,7 +1132,11 @@ mod tests {
                 quote_kind: QuoteKind::Standard,
                 solver: H160([1; 20]),
                 verified: false,
-                metadata: Default::default(),
+                metadata: QuoteMetadataV1 {
+                    owner: Some(H160([3; 20])),
+                    ..Default::default()
+                }
+                .into(),
             }))
             .returning(|_| Ok(1337));
 
@@ -1149,7 +1173,11 @@ mod tests {
                     quote_kind: QuoteKind::Standard,
                     solver: H160([1; 20]),
                     verified: false,
-                    metadata: Default::default(),
+                    metadata: QuoteMetadataV1 {
+                        owner: Some(H160([3; 20])),
+                        ..Default::default()
+                    }
+                    .into(),
                 },
                 sell_amount: 100.into(),
                 buy_amount: 42.into(),
@@ -1680,6 +1708,7 @@ mod tests {
                 signature: vec![1; 16],
                 signing_scheme: model::signature::SigningScheme::Eip712,
             }],
+            owner: None,
         }
         .into();
         let v = serde_json::to_value(q).unwrap();
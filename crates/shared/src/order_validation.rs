@@ -5,12 +5,15 @@ use {
         code_fetching::CodeFetching,
         order_quoting::{
             CalculateQuoteError,
+            FindQuoteError,
             OrderQuoting,
             Quote,
+            QuoteMetadata,
             QuoteParameters,
             QuoteSearchParameters,
         },
         price_estimation::{PriceEstimationError, Verification},
+        quote_commitment::{QuoteCommitmentError, QuoteCommitments},
         signature_validator::{SignatureCheck, SignatureValidating, SignatureValidationError},
         trade_finding,
     },
@@ -20,8 +23,12 @@ use {
     contracts::{HooksTrampoline, WETH9},
     ethcontract::{Bytes, H160, H256, U256},
     model::{
+        app_data::{AppDataDocument, Bridging, BridgingError},
         interaction::InteractionData,
         order::{
+            check_amounts,
+            check_token_balances,
+            check_tokens,
             AppdataFromMismatch,
             BuyTokenDestination,
             Interactions,
@@ -31,12 +38,13 @@ use {
             OrderCreationAppData,
             OrderData,
             OrderKind,
+            OrderDataError,
             OrderMetadata,
             SellTokenSource,
             VerificationError,
             BUY_ETH_ADDRESS,
         },
-        quote::{OrderQuoteSide, QuoteSigningScheme, SellAmount},
+        quote::{OrderQuoteSide, QuoteId, QuoteSigningScheme, SellAmount},
         signature::{self, hashed_eip712_message, Signature, SigningScheme},
         time,
         DomainSeparator,
@@ -95,6 +103,15 @@ pub trait OrderValidating: Send + Sync {
         settlement_contract: H160,
         full_app_data_override: Option<String>,
     ) -> Result<(Order, Option<Quote>), ValidationError>;
+
+    /// Checks that the owner can place another limit order outside the market
+    /// price, given that `pending` of them are about to be placed in addition
+    /// to the ones the owner already has.
+    async fn check_max_limit_orders(
+        &self,
+        owner: H160,
+        pending: u64,
+    ) -> Result<(), ValidationError>;
 }
 
 #[derive(Debug)]
@@ -116,6 +133,23 @@ impl From<OrderValidToError> for PartialValidationError {
     }
 }
 
+impl From<OrderDataError> for PartialValidationError {
+    fn from(err: OrderDataError) -> Self {
+        match err {
+            OrderDataError::SameBuyAndSellToken => Self::SameBuyAndSellToken,
+            OrderDataError::InvalidNativeSellToken => Self::InvalidNativeSellToken,
+            OrderDataError::UnsupportedBuyTokenDestination(destination) => {
+                Self::UnsupportedBuyTokenDestination(destination)
+            }
+            OrderDataError::UnsupportedSellTokenSource(source) => {
+                Self::UnsupportedSellTokenSource(source)
+            }
+            // Amounts are only known once the order gets placed.
+            OrderDataError::ZeroAmount => Self::Other(err.into()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum AppDataValidationError {
     Mismatch {
@@ -154,9 +188,55 @@ pub enum ValidationError {
     TooManyLimitOrders,
     TooMuchGas,
     QuoteNotVerified,
+    /// The order includes a quote commitment that can't be honored.
+    InvalidQuoteCommitment(QuoteCommitmentError),
+    /// The order failed mandatory quote verification.
+    QuoteVerification(QuoteVerificationError),
+    /// The bridging metadata in the app data doesn't fit the order.
+    Bridging(BridgingError),
     Other(anyhow::Error),
 }
 
+/// Reasons for rejecting an order during mandatory quote verification.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum QuoteVerificationError {
+    #[error("order does not reference a quote")]
+    MissingQuoteId,
+    #[error("referenced quote does not exist")]
+    NotFound,
+    #[error("referenced quote expired")]
+    Expired,
+    #[error("referenced quote does not match the order parameters")]
+    ParameterMismatch,
+    #[error("referenced quote was not requested by the order owner")]
+    WrongOwner,
+    #[error("order amounts deviate too much from the referenced quote")]
+    AmountMismatch,
+}
+
+impl QuoteVerificationError {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::MissingQuoteId => "missing_quote_id",
+            Self::NotFound => "not_found",
+            Self::Expired => "expired",
+            Self::ParameterMismatch => "parameter_mismatch",
+            Self::WrongOwner => "wrong_owner",
+            Self::AmountMismatch => "amount_mismatch",
+        }
+    }
+}
+
+/// Configuration for rejecting orders that don't reference a valid quote.
+#[derive(Clone, Debug, Default)]
+pub struct MandatoryQuoteVerification {
+    /// Order classes that have to reference a quote.
+    pub classes: Vec<OrderClass>,
+    /// How much the limit price of an order may deviate from the quoted price
+    /// in basis points.
+    pub amount_tolerance_bps: u32,
+}
+
 impl From<AppDataValidationError> for ValidationError {
     fn from(value: AppDataValidationError) -> Self {
         Self::AppData(value)
@@ -225,6 +305,11 @@ pub struct OrderValidator {
     pub code_fetcher: Arc<dyn CodeFetching>,
     app_data_validator: Validator,
     max_gas_per_order: u64,
+    quote_commitments: Option<Arc<QuoteCommitments>>,
+    mandatory_quote_verification: MandatoryQuoteVerification,
+    /// Chain the orders get placed on. Orders bridging their bought tokens to
+    /// another chain get rejected if not set.
+    bridging_chain_id: Option<u64>,
 }
 
 #[derive(Debug, Eq, PartialEq, Default)]
@@ -277,6 +362,7 @@ impl PreOrderData {
 pub struct OrderAppData {
     pub inner: ValidatedAppData,
     pub interactions: Interactions,
+    pub bridging: Option<Bridging>,
 }
 
 impl OrderValidator {
@@ -312,17 +398,124 @@ impl OrderValidator {
             code_fetcher,
             app_data_validator,
             max_gas_per_order,
+            quote_commitments: None,
+            mandatory_quote_verification: Default::default(),
+            bridging_chain_id: None,
         }
     }
 
-    async fn check_max_limit_orders(&self, owner: H160) -> Result<(), ValidationError> {
-        let num_limit_orders = self
-            .limit_order_counter
-            .count(owner)
-            .await
-            .map_err(ValidationError::Other)?;
-        if num_limit_orders >= self.max_limit_orders_per_user {
-            return Err(ValidationError::TooManyLimitOrders);
+    /// Honors signed quote commitments included in orders.
+    pub fn with_quote_commitments(mut self, quote_commitments: Arc<QuoteCommitments>) -> Self {
+        self.quote_commitments = Some(quote_commitments);
+        self
+    }
+
+    /// Rejects orders of the specified classes unless they reference a valid
+    /// quote of their owner.
+    pub fn with_mandatory_quote_verification(
+        mut self,
+        mandatory_quote_verification: MandatoryQuoteVerification,
+    ) -> Self {
+        self.mandatory_quote_verification = mandatory_quote_verification;
+        self
+    }
+
+    /// Accepts orders of the chain that bridge their bought tokens to other
+    /// chains according to their app data.
+    pub fn with_bridging(mut self, chain_id: u64) -> Self {
+        self.bridging_chain_id = Some(chain_id);
+        self
+    }
+
+    /// Retrieves the quote for an order. Orders with a quote commitment or
+    /// mandatory quote verification must use the referenced quote and never
+    /// fall back to a freshly computed one.
+    async fn get_quote(
+        &self,
+        quote_parameters: &QuoteSearchParameters,
+        quote_id: Option<QuoteId>,
+        class: OrderClass,
+        committed: bool,
+        fee_amount: Option<U256>,
+    ) -> Result<Quote, ValidationError> {
+        let verified = self.mandatory_quote_verification.classes.contains(&class);
+        if !committed && !verified {
+            return get_quote_and_check_fee(&*self.quoter, quote_parameters, quote_id, fee_amount)
+                .await;
+        }
+
+        if fee_amount.is_some_and(|fee| !fee.is_zero()) {
+            return Err(ValidationError::NonZeroFee);
+        }
+        let result = async {
+            if verified && quote_id.is_none() {
+                return Err(ValidationError::QuoteVerification(
+                    QuoteVerificationError::MissingQuoteId,
+                ));
+            }
+            let quote = self
+                .quoter
+                .find_quote(quote_id, quote_parameters.clone())
+                .await
+                .map_err(|err| match err {
+                    FindQuoteError::Other(err) => ValidationError::Other(err),
+                    err if committed => {
+                        tracing::debug!(?err, "failed to find committed quote");
+                        ValidationError::InvalidQuoteCommitment(QuoteCommitmentError::QuoteMismatch)
+                    }
+                    FindQuoteError::NotFound(_) => {
+                        ValidationError::QuoteVerification(QuoteVerificationError::NotFound)
+                    }
+                    FindQuoteError::Expired(_) => {
+                        ValidationError::QuoteVerification(QuoteVerificationError::Expired)
+                    }
+                    FindQuoteError::ParameterMismatch(_) => ValidationError::QuoteVerification(
+                        QuoteVerificationError::ParameterMismatch,
+                    ),
+                })?;
+            if verified {
+                self.verify_quote(&quote, quote_parameters)
+                    .map_err(ValidationError::QuoteVerification)?;
+            }
+            Ok(quote)
+        }
+        .await;
+
+        if let Err(ValidationError::QuoteVerification(err)) = &result {
+            tracing::debug!(?err, ?class, "order failed mandatory quote verification");
+            Metrics::get()
+                .quote_verification_rejections
+                .with_label_values(&[err.reason()])
+                .inc();
+        }
+        result
+    }
+
+    /// Checks that the quote was requested by the order owner and that the
+    /// order's limit price is within the configured tolerance of the quote.
+    fn verify_quote(
+        &self,
+        quote: &Quote,
+        quote_parameters: &QuoteSearchParameters,
+    ) -> Result<(), QuoteVerificationError> {
+        let QuoteMetadata::V1(metadata) = &quote.data.metadata;
+        if metadata.owner != Some(quote_parameters.verification.from) {
+            return Err(QuoteVerificationError::WrongOwner);
+        }
+        if !is_within_quote_tolerance(
+            &Amounts {
+                sell: quote_parameters.sell_amount,
+                buy: quote_parameters.buy_amount,
+                fee: quote_parameters.fee_amount,
+            },
+            &Amounts {
+                sell: quote.sell_amount,
+                buy: quote.buy_amount,
+                fee: quote.fee_amount,
+            },
+            self.mandatory_quote_verification.amount_tolerance_bps,
+        ) {
+            return Err(QuoteVerificationError::AmountMismatch);
         }
         Ok(())
     }
@@ -380,28 +573,14 @@ impl OrderValidating for OrderValidator {
             return Err(PartialValidationError::UnsupportedOrderType);
         }
 
-        if order.buy_token_balance != BuyTokenDestination::Erc20 {
-            return Err(PartialValidationError::UnsupportedBuyTokenDestination(
-                order.buy_token_balance,
-            ));
-        }
-        if !matches!(
-            order.sell_token_balance,
-            SellTokenSource::Erc20 | SellTokenSource::External
-        ) {
-            return Err(PartialValidationError::UnsupportedSellTokenSource(
-                order.sell_token_balance,
-            ));
-        }
+        check_token_balances(order.sell_token_balance, order.buy_token_balance)?;
 
         self.validity_configuration.validate_period(&order)?;
 
         if has_same_buy_and_sell_token(&order, &self.native_token) {
             return Err(PartialValidationError::SameBuyAndSellToken);
         }
-        if order.sell_token == BUY_ETH_ADDRESS {
-            return Err(PartialValidationError::InvalidNativeSellToken);
-        }
+        check_tokens(order.sell_token, order.buy_token)?;
 
         for &token in &[order.sell_token, order.buy_token] {
             if let TokenQuality::Bad { reason } = self
@@ -423,29 +602,33 @@ impl OrderValidating for OrderValidator {
         full_app_data_override: &Option<String>,
     ) -> Result<OrderAppData, AppDataValidationError> {
         let validate = |app_data: &str| -> Result<_, AppDataValidationError> {
-            let app_data = self
+            let validated = self
                 .app_data_validator
                 .validate(app_data.as_bytes())
                 .map_err(AppDataValidationError::Invalid)?;
-            Ok(app_data)
+            // Only fails for malformed protocol relevant fields that the
+            // validator doesn't know about, like bridging.
+            let document = AppDataDocument::parse(app_data)
+                .map_err(|err| AppDataValidationError::Invalid(err.into()))?;
+            Ok((validated, document.bridging().cloned()))
         };
 
-        let app_data = match app_data {
+        let (app_data, bridging) = match app_data {
             OrderCreationAppData::Both { full, expected } => {
-                let validated = validate(full)?;
+                let (validated, bridging) = validate(full)?;
                 if validated.hash != *expected {
                     return Err(AppDataValidationError::Mismatch {
                         provided: *expected,
                         actual: validated.hash,
                     });
                 }
-                validated
+                (validated, bridging)
             }
             OrderCreationAppData::Hash { hash } => {
                 // Eventually we're not going to accept orders that set only a
                 // hash and where we can't find full app data elsewhere.
-                let protocol = if let Some(full) = full_app_data_override {
-                    validate(full)?.protocol
+                let (validated, bridging) = if let Some(full) = full_app_data_override {
+                    validate(full)?
                 } else {
                     return Err(AppDataValidationError::Invalid(anyhow!(
                         "Unknown pre-image for app data hash {:?}",
@@ -453,11 +636,12 @@ impl OrderValidating for OrderValidator {
                     )));
                 };
 
-                ValidatedAppData {
+                let validated = ValidatedAppData {
                     hash: *hash,
                     document: String::new(),
-                    protocol,
-                }
+                    protocol: validated.protocol,
+                };
+                (validated, bridging)
             }
             OrderCreationAppData::Full { full } => validate(full)?,
         };
@@ -467,6 +651,7 @@ impl OrderValidating for OrderValidator {
         Ok(OrderAppData {
             inner: app_data,
             interactions,
+            bridging,
         })
     }
 
@@ -518,10 +703,38 @@ impl OrderValidating for OrderValidator {
             0u64
         };
 
-        if data.buy_amount.is_zero() || data.sell_amount.is_zero() {
-            return Err(ValidationError::ZeroAmount);
+        check_amounts(data.sell_amount, data.buy_amount)
+            .map_err(|_| ValidationError::ZeroAmount)?;
+
+        if let Some(bridging) = &app_data.bridging {
+            let chain_id = self
+                .bridging_chain_id
+                .ok_or(ValidationError::Bridging(BridgingError::Unsupported))?;
+            bridging
+                .validate(chain_id, actual_receiver(owner, &data))
+                .map_err(ValidationError::Bridging)?;
         }
 
+        let committed = match (&order.quote_commitment, &self.quote_commitments) {
+            (None, _) => false,
+            (Some(_), None) => {
+                return Err(ValidationError::InvalidQuoteCommitment(
+                    QuoteCommitmentError::Disabled,
+                ))
+            }
+            (Some(commitment), Some(quote_commitments)) => {
+                quote_commitments
+                    .verify(
+                        commitment,
+                        &data,
+                        order.quote_id,
+                        time::now_in_epoch_seconds(),
+                    )
+                    .map_err(ValidationError::InvalidQuoteCommitment)?;
+                true
+            }
+        };
+
         let pre_order = PreOrderData::from_order_creation(owner, &data, signing_scheme);
         let class = pre_order.class;
         self.partial_validate(pre_order)
@@ -606,13 +819,15 @@ impl OrderValidating for OrderValidator {
         let (class, quote) = match class {
             // This has to be here in order to keep the previous behaviour
             OrderClass::Market => {
-                let quote = get_quote_and_check_fee(
-                    &*self.quoter,
-                    &quote_parameters,
-                    order.quote_id,
-                    Some(data.fee_amount),
-                )
-                .await?;
+                let quote = self
+                    .get_quote(
+                        &quote_parameters,
+                        order.quote_id,
+                        class,
+                        committed,
+                        Some(data.fee_amount),
+                    )
+                    .await?;
                 tracing::debug!(
                     ?uid,
                     ?order,
@@ -639,13 +854,9 @@ impl OrderValidating for OrderValidator {
                 }
             }
             OrderClass::Limit => {
-                match get_quote_and_check_fee(
-                    &*self.quoter,
-                    &quote_parameters,
-                    order.quote_id,
-                    None,
-                )
-                .await
+                match self
+                    .get_quote(&quote_parameters, order.quote_id, class, committed, None)
+                    .await
                 {
                     Ok(quote) => {
                         // If the order is not "In-Market", check for the limit orders
@@ -662,7 +873,7 @@ impl OrderValidating for OrderValidator {
                             },
                             data.kind,
                         ) {
-                            self.check_max_limit_orders(owner).await?;
+                            self.check_max_limit_orders(owner, 0).await?;
                         }
                         (class, Some(quote))
                     }
@@ -676,9 +887,9 @@ impl OrderValidating for OrderValidator {
                 }
             }
             OrderClass::Liquidity => {
-                let quote =
-                    get_quote_and_check_fee(&*self.quoter, &quote_parameters, order.quote_id, None)
-                        .await?;
+                let quote = self
+                    .get_quote(&quote_parameters, order.quote_id, class, committed, None)
+                    .await?;
                 // If the order is not "In-Market", check for the limit orders
                 if is_order_outside_market_price(
                     &Amounts {
@@ -720,6 +931,7 @@ impl OrderValidating for OrderValidator {
                     | OrderCreationAppData::Full { full } => Some(full),
                     OrderCreationAppData::Hash { .. } => full_app_data_override,
                 },
+                bridging: app_data.bridging,
                 ..Default::default()
             },
             signature: order.signature.clone(),
@@ -729,6 +941,22 @@ impl OrderValidating for OrderValidator {
 
         Ok((order, quote))
     }
+
+    async fn check_max_limit_orders(
+        &self,
+        owner: H160,
+        pending: u64,
+    ) -> Result<(), ValidationError> {
+        let num_limit_orders = self
+            .limit_order_counter
+            .count(owner)
+            .await
+            .map_err(ValidationError::Other)?;
+        if num_limit_orders.saturating_add(pending) >= self.max_limit_orders_per_user {
+            return Err(ValidationError::TooManyLimitOrders);
+        }
+        Ok(())
+    }
 }
 
 /// Order validity period configuration.
@@ -914,6 +1142,26 @@ pub fn is_order_outside_market_price(
     })
 }
 
+/// Checks whether the limit price of an order deviates at most `tolerance_bps`
+/// from the price specified by the quote.
+fn is_within_quote_tolerance(order: &Amounts, quote: &Amounts, tolerance_bps: u32) -> bool {
+    let check = move || {
+        let order_price = order.buy.full_mul(quote.sell.checked_add(quote.fee)?);
+        let quote_price = quote.buy.full_mul(order.sell.checked_add(order.fee)?);
+        let deviation = if order_price > quote_price {
+            order_price - quote_price
+        } else {
+            quote_price - order_price
+        };
+        Some(
+            deviation.checked_mul(10_000.into())?
+                <= quote_price.checked_mul(tolerance_bps.into())?,
+        )
+    };
+
+    check().unwrap_or(false)
+}
+
 pub struct InvalidSigningScheme;
 
 pub fn convert_signing_scheme_into_quote_signing_scheme(
@@ -936,6 +1184,19 @@ pub fn convert_signing_scheme_into_quote_signing_scheme(
     }
 }
 
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Number of orders rejected by mandatory quote verification.
+    #[metric(labels("reason"))]
+    quote_verification_rejections: prometheus::IntCounterVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -1615,6 +1876,155 @@ mod tests {
         assert!(matches!(result, Err(ValidationError::ZeroAmount)));
     }
 
+    #[tokio::test]
+    async fn post_validate_err_bridging() {
+        let validator = || {
+            OrderValidator::new(
+                dummy_contract!(WETH9, [0xef; 20]),
+                Arc::new(order_validation::banned::Users::none()),
+                OrderValidPeriodConfiguration::any(),
+                false,
+                Arc::new(MockBadTokenDetecting::new()),
+                dummy_contract!(HooksTrampoline, [0xcf; 20]),
+                Arc::new(MockOrderQuoting::new()),
+                Arc::new(MockBalanceFetching::new()),
+                Arc::new(MockSignatureValidating::new()),
+                Arc::new(MockLimitOrderCounting::new()),
+                0,
+                Arc::new(MockCodeFetching::new()),
+                Default::default(),
+                u64::MAX,
+            )
+        };
+        let order = |receiver: H160| OrderCreation {
+            valid_to: time::now_in_epoch_seconds() + 2,
+            sell_token: H160::from_low_u64_be(1),
+            buy_token: H160::from_low_u64_be(2),
+            buy_amount: U256::from(1),
+            sell_amount: U256::from(1),
+            receiver: Some(receiver),
+            signature: Signature::Eip712(EcdsaSignature::non_zero()),
+            app_data: OrderCreationAppData::Full {
+                full: json!({
+                    "metadata": {
+                        "bridging": {
+                            "targetChainId": 100,
+                            "adapter": "0x4444444444444444444444444444444444444444",
+                            "recipient": "0x5555555555555555555555555555555555555555",
+                        },
+                    },
+                })
+                .to_string(),
+            },
+            ..Default::default()
+        };
+        let adapter = H160([0x44; 20]);
+        let validate = |validator: OrderValidator, order: OrderCreation| async move {
+            validator
+                .validate_and_construct_order(order, &Default::default(), Default::default(), None)
+                .await
+        };
+
+        assert!(matches!(
+            validate(validator(), order(adapter)).await,
+            Err(ValidationError::Bridging(BridgingError::Unsupported))
+        ));
+        assert!(matches!(
+            validate(validator().with_bridging(100), order(adapter)).await,
+            Err(ValidationError::Bridging(BridgingError::SameChain))
+        ));
+        assert!(matches!(
+            validate(validator().with_bridging(1), order(H160([0x55; 20]))).await,
+            Err(ValidationError::Bridging(BridgingError::ReceiverNotAdapter))
+        ));
+    }
+
+    #[tokio::test]
+    async fn post_validate_err_mandatory_quote_verification() {
+        let mut order_quoter = MockOrderQuoting::new();
+        let mut bad_token_detector = MockBadTokenDetecting::new();
+        let mut balance_fetcher = MockBalanceFetching::new();
+        order_quoter.expect_find_quote().returning(|_, _| {
+            Ok(Quote {
+                sell_amount: 1.into(),
+                buy_amount: 1.into(),
+                ..Default::default()
+            })
+        });
+        bad_token_detector
+            .expect_detect()
+            .returning(|_| Ok(TokenQuality::Good));
+        balance_fetcher
+            .expect_can_transfer()
+            .returning(|_, _| Ok(()));
+        let validator = OrderValidator::new(
+            dummy_contract!(WETH9, [0xef; 20]),
+            Arc::new(order_validation::banned::Users::none()),
+            OrderValidPeriodConfiguration::any(),
+            false,
+            Arc::new(bad_token_detector),
+            dummy_contract!(HooksTrampoline, [0xcf; 20]),
+            Arc::new(order_quoter),
+            Arc::new(balance_fetcher),
+            Arc::new(MockSignatureValidating::new()),
+            Arc::new(MockLimitOrderCounting::new()),
+            0,
+            Arc::new(MockCodeFetching::new()),
+            Default::default(),
+            u64::MAX,
+        )
+        .with_mandatory_quote_verification(MandatoryQuoteVerification {
+            classes: vec![OrderClass::Limit],
+            amount_tolerance_bps: 100,
+        });
+        let order = OrderCreation {
+            valid_to: time::now_in_epoch_seconds() + 2,
+            sell_token: H160::from_low_u64_be(1),
+            buy_token: H160::from_low_u64_be(2),
+            buy_amount: U256::from(1),
+            sell_amount: U256::from(1),
+            signature: Signature::Eip712(EcdsaSignature::non_zero()),
+            app_data: OrderCreationAppData::Full {
+                full: "{}".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let result = validator
+            .validate_and_construct_order(
+                order.clone(),
+                &Default::default(),
+                Default::default(),
+                None,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::QuoteVerification(
+                QuoteVerificationError::MissingQuoteId
+            ))
+        ));
+
+        // The quote was not requested by the owner of the order.
+        let result = validator
+            .validate_and_construct_order(
+                OrderCreation {
+                    quote_id: Some(42),
+                    ..order
+                },
+                &Default::default(),
+                Default::default(),
+                None,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::QuoteVerification(
+                QuoteVerificationError::WrongOwner
+            ))
+        ));
+    }
+
     #[tokio::test]
     async fn post_validate_err_wrong_owner() {
         let mut order_quoter = MockOrderQuoting::new();
@@ -2157,4 +2567,24 @@ mod tests {
             model::order::OrderKind::Sell,
         ));
     }
+
+    #[test]
+    fn checks_quote_tolerance() {
+        let quote = Amounts {
+            sell: 90.into(),
+            buy: 100.into(),
+            fee: 10.into(),
+        };
+        let order = |buy: u64| Amounts {
+            sell: 100.into(),
+            buy: buy.into(),
+            fee: 0.into(),
+        };
+
+        assert!(is_within_quote_tolerance(&order(100), &quote, 0));
+        assert!(is_within_quote_tolerance(&order(99), &quote, 100));
+        assert!(is_within_quote_tolerance(&order(101), &quote, 100));
+        assert!(!is_within_quote_tolerance(&order(98), &quote, 100));
+        assert!(!is_within_quote_tolerance(&order(102), &quote, 100));
+    }
 }
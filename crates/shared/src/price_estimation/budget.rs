@@ -0,0 +1,446 @@
+//! Request budgets for price estimators backed by paid APIs.
+//!
+//! Every request issued to a budgeted estimator gets accounted for. Once the
+//! daily budget or the burst allowance is used up the estimator responds with
+//! [`PriceEstimationError::RateLimited`] without issuing a request. Since all
+//! price estimators are composed in competitions the remaining (free)
+//! estimators then answer the queries instead.
+//!
+//! Usage gets persisted in the database so budgets survive restarts and get
+//! shared between all services using the same estimator.
+
+use {
+    super::{
+        native::{NativePriceEstimateResult, NativePriceEstimating},
+        PriceEstimateResult,
+        PriceEstimating,
+        PriceEstimationError,
+        Query,
+    },
+    anyhow::{ensure, Context, Result},
+    chrono::{NaiveDate, Utc},
+    futures::{future::BoxFuture, FutureExt},
+    primitive_types::H160,
+    sqlx::PgPool,
+    std::{
+        collections::BTreeMap,
+        num::NonZeroU64,
+        str::FromStr,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+const SECONDS_PER_DAY: f64 = 24. * 60. * 60.;
+
+/// Request budget of a single price estimator.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Budget {
+    /// Maximum number of requests per day (UTC).
+    pub requests_per_day: Option<NonZeroU64>,
+    /// Maximum number of requests that can be issued at once. The allowance
+    /// refills at the rate of `requests_per_day`.
+    pub burst: Option<NonZeroU64>,
+    /// Cost of a single request used to report the spend.
+    pub cost_per_request: f64,
+}
+
+/// Budget configuration for a named estimator. Parsed from
+/// `<NAME>|requests-per-day=<N>|burst=<N>|cost=<COST>` where all keys are
+/// optional.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetConfig {
+    pub estimator: String,
+    pub budget: Budget,
+}
+
+impl FromStr for BudgetConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split('|');
+        let estimator = parts
+            .next()
+            .filter(|name| !name.is_empty())
+            .context("budget is missing estimator name")?
+            .to_owned();
+        let mut budget = Budget::default();
+        for part in parts {
+            let (key, value) = part
+                .split_once('=')
+                .with_context(|| format!("budget {part:?} is not of the form <KEY>=<VALUE>"))?;
+            match key {
+                "requests-per-day" => {
+                    budget.requests_per_day =
+                        Some(value.parse().context("could not parse requests-per-day")?)
+                }
+                "burst" => budget.burst = Some(value.parse().context("could not parse burst")?),
+                "cost" => {
+                    budget.cost_per_request = value.parse().context("could not parse cost")?;
+                    ensure!(
+                        budget.cost_per_request.is_finite() && budget.cost_per_request >= 0.,
+                        "cost must be a non-negative number"
+                    );
+                }
+                _ => anyhow::bail!("unknown budget key {key:?}"),
+            }
+        }
+        ensure!(
+            budget.burst.is_none() || budget.requests_per_day.is_some(),
+            "burst requires requests-per-day to be configured"
+        );
+        Ok(Self { estimator, budget })
+    }
+}
+
+/// Keeps track of the requests issued to an estimator.
+pub struct BudgetTracker {
+    estimator: String,
+    budget: Budget,
+    state: Mutex<State>,
+}
+
+struct State {
+    day: NaiveDate,
+    /// Requests issued on `day` by all services as of the last sync.
+    synced: u64,
+    /// Requests issued on `day` by this service since the last sync.
+    pending: u64,
+    /// Requests of previous days that have not been persisted yet.
+    carry_over: BTreeMap<NaiveDate, u64>,
+    /// Remaining burst allowance.
+    burst: f64,
+    last_refill: Instant,
+}
+
+impl State {
+    fn roll_over(&mut self, today: NaiveDate) {
+        if self.day == today {
+            return;
+        }
+        self.carry(self.day, self.pending);
+        self.day = today;
+        self.synced = 0;
+        self.pending = 0;
+    }
+
+    /// Adds requests of a previous day that still have to be persisted.
+    fn carry(&mut self, day: NaiveDate, requests: u64) {
+        if requests > 0 {
+            *self.carry_over.entry(day).or_default() += requests;
+        }
+    }
+}
+
+impl BudgetTracker {
+    pub fn new(estimator: String, budget: Budget) -> Self {
+        Self {
+            state: Mutex::new(State {
+                day: today(),
+                synced: 0,
+                pending: 0,
+                carry_over: BTreeMap::new(),
+                burst: budget.burst.map_or(0., |burst| burst.get() as f64),
+                last_refill: Instant::now(),
+            }),
+            estimator,
+            budget,
+        }
+    }
+
+    /// Reserves the budget for a single request. Returns `false` if the
+    /// budget is exhausted and no request should be issued.
+    pub fn try_consume(&self) -> bool {
+        self.try_consume_at(today(), Instant::now())
+    }
+
+    fn try_consume_at(&self, today: NaiveDate, now: Instant) -> bool {
+        let metrics = Metrics::get();
+        let mut state = self.state.lock().unwrap();
+        state.roll_over(today);
+
+        if let Some(limit) = self.budget.requests_per_day {
+            if state.synced + state.pending >= limit.get() {
+                metrics
+                    .price_estimator_budget_rejections
+                    .with_label_values(&[&self.estimator, "daily"])
+                    .inc();
+                return false;
+            }
+        }
+
+        if let (Some(burst), Some(limit)) = (self.budget.burst, self.budget.requests_per_day) {
+            let elapsed = now.saturating_duration_since(state.last_refill);
+            let refill = elapsed.as_secs_f64() * limit.get() as f64 / SECONDS_PER_DAY;
+            state.burst = (state.burst + refill).min(burst.get() as f64);
+            state.last_refill = now;
+            if state.burst < 1. {
+                metrics
+                    .price_estimator_budget_rejections
+                    .with_label_values(&[&self.estimator, "burst"])
+                    .inc();
+                return false;
+            }
+            state.burst -= 1.;
+        }
+
+        state.pending += 1;
+        metrics
+            .price_estimator_requests
+            .with_label_values(&[&self.estimator])
+            .inc();
+        metrics
+            .price_estimator_spend
+            .with_label_values(&[&self.estimator])
+            .inc_by(self.budget.cost_per_request);
+        self.update_remaining(&state);
+        true
+    }
+
+    fn update_remaining(&self, state: &State) {
+        let Some(limit) = self.budget.requests_per_day else {
+            return;
+        };
+        let remaining = limit.get().saturating_sub(state.synced + state.pending);
+        Metrics::get()
+            .price_estimator_budget_remaining
+            .with_label_values(&[&self.estimator])
+            .set(i64::try_from(remaining).unwrap_or(i64::MAX));
+    }
+
+    /// Persists the requests issued since the last sync and picks up the
+    /// requests other services issued in the meantime.
+    pub async fn sync(&self, db: &PgPool) -> Result<()> {
+        let (day, pending, carry_over) = {
+            let mut state = self.state.lock().unwrap();
+            state.roll_over(today());
+            let pending = std::mem::take(&mut state.pending);
+            (state.day, pending, std::mem::take(&mut state.carry_over))
+        };
+
+        // All requests get persisted in one transaction so that they can
+        // simply be retried with the next sync if anything fails.
+        let result = async {
+            let mut ex = database::instrumentation::begin(db).await?;
+            for (&day, &requests) in &carry_over {
+                database::price_estimator_usage::add(
+                    &mut ex,
+                    &self.estimator,
+                    day,
+                    i64::try_from(requests)?,
+                )
+                .await?;
+            }
+            let total = database::price_estimator_usage::add(
+                &mut ex,
+                &self.estimator,
+                day,
+                i64::try_from(pending)?,
+            )
+            .await?;
+            ex.commit().await?;
+            anyhow::Ok(u64::try_from(total)?)
+        }
+        .await;
+
+        let mut state = self.state.lock().unwrap();
+        match result {
+            Ok(total) => {
+                if state.day == day {
+                    state.synced = total;
+                }
+                self.update_remaining(&state);
+                Ok(())
+            }
+            Err(err) => {
+                // Retry persisting the requests with the next sync. Requests
+                // carried over in the meantime are kept.
+                if state.day == day {
+                    state.pending += pending;
+                } else {
+                    state.carry(day, pending);
+                }
+                for (day, requests) in carry_over {
+                    state.carry(day, requests);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Periodically syncs the usage of all budgets with the database.
+    pub async fn sync_forever(budgets: Vec<Arc<Self>>, db: PgPool, interval: Duration) {
+        if budgets.is_empty() {
+            return;
+        }
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            for budget in &budgets {
+                if let Err(err) = budget.sync(&db).await {
+                    tracing::warn!(
+                        ?err,
+                        estimator = %budget.estimator,
+                        "failed to sync price estimator budget"
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+/// Price estimator that only forwards requests while its budget allows it.
+/// Estimators without budget forward all requests.
+pub struct BudgetedPriceEstimator<T> {
+    inner: T,
+    budget: Option<Arc<BudgetTracker>>,
+}
+
+impl<T> BudgetedPriceEstimator<T> {
+    pub fn new(inner: T, budget: Option<Arc<BudgetTracker>>) -> Self {
+        Self { inner, budget }
+    }
+
+    fn exhausted(&self) -> bool {
+        self.budget
+            .as_ref()
+            .is_some_and(|budget| !budget.try_consume())
+    }
+}
+
+impl<T: PriceEstimating> PriceEstimating for BudgetedPriceEstimator<T> {
+    fn estimate(&self, query: Arc<Query>) -> BoxFuture<'_, PriceEstimateResult> {
+        if self.exhausted() {
+            return async { Err(PriceEstimationError::RateLimited) }.boxed();
+        }
+        self.inner.estimate(query)
+    }
+}
+
+impl<T: NativePriceEstimating> NativePriceEstimating for BudgetedPriceEstimator<T> {
+    fn estimate_native_price(&self, token: H160) -> BoxFuture<'_, NativePriceEstimateResult> {
+        if self.exhausted() {
+            return async { Err(PriceEstimationError::RateLimited) }.boxed();
+        }
+        self.inner.estimate_native_price(token)
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Number of requests issued to budgeted price estimators.
+    #[metric(labels("estimator"))]
+    price_estimator_requests: prometheus::IntCounterVec,
+
+    /// Accumulated cost of the requests issued to budgeted price estimators.
+    #[metric(labels("estimator"))]
+    price_estimator_spend: prometheus::CounterVec,
+
+    /// Number of requests that were not issued because the budget was
+    /// exhausted.
+    #[metric(labels("estimator", "limit"))]
+    price_estimator_budget_rejections: prometheus::IntCounterVec,
+
+    /// Number of requests left in today's budget.
+    #[metric(labels("estimator"))]
+    price_estimator_budget_remaining: prometheus::IntGaugeVec,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(requests_per_day: u64, burst: Option<u64>) -> Budget {
+        Budget {
+            requests_per_day: NonZeroU64::new(requests_per_day),
+            burst: burst.and_then(NonZeroU64::new),
+            cost_per_request: 0.1,
+        }
+    }
+
+    #[test]
+    fn parses_budget_config() {
+        assert_eq!(
+            BudgetConfig::from_str("0x|requests-per-day=1000|burst=10|cost=0.5").unwrap(),
+            BudgetConfig {
+                estimator: "0x".to_string(),
+                budget: Budget {
+                    requests_per_day: NonZeroU64::new(1000),
+                    burst: NonZeroU64::new(10),
+                    cost_per_request: 0.5,
+                },
+            }
+        );
+        assert_eq!(
+            BudgetConfig::from_str("1inch").unwrap().budget,
+            Budget::default()
+        );
+        assert!(BudgetConfig::from_str("").is_err());
+        assert!(BudgetConfig::from_str("0x|burst=10").is_err());
+        assert!(BudgetConfig::from_str("0x|requests-per-day=0").is_err());
+        assert!(BudgetConfig::from_str("0x|cost=-1").is_err());
+        assert!(BudgetConfig::from_str("0x|unknown=1").is_err());
+    }
+
+    #[test]
+    fn enforces_daily_budget() {
+        let tracker = BudgetTracker::new("daily".to_string(), budget(2, None));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let now = Instant::now();
+        assert!(tracker.try_consume_at(day, now));
+        assert!(tracker.try_consume_at(day, now));
+        assert!(!tracker.try_consume_at(day, now));
+
+        // The budget resets on the next day and unsynced requests get carried
+        // over.
+        let next_day = day.succ_opt().unwrap();
+        assert!(tracker.try_consume_at(next_day, now));
+        let state = tracker.state.lock().unwrap();
+        assert_eq!(state.pending, 1);
+        assert_eq!(state.carry_over, BTreeMap::from([(day, 2)]));
+    }
+
+    #[test]
+    fn accumulates_carry_overs() {
+        let tracker = BudgetTracker::new("carry_over".to_string(), budget(10, None));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let now = Instant::now();
+        assert!(tracker.try_consume_at(day, now));
+        let next_day = day.succ_opt().unwrap();
+        assert!(tracker.try_consume_at(next_day, now));
+        assert!(tracker.try_consume_at(next_day.succ_opt().unwrap(), now));
+
+        // Rolling over again before a sync doesn't lose the older requests.
+        let mut state = tracker.state.lock().unwrap();
+        assert_eq!(state.carry_over, BTreeMap::from([(day, 1), (next_day, 1)]));
+
+        // Requests of a failed sync add up with the ones carried over since.
+        state.carry(day, 2);
+        assert_eq!(state.carry_over, BTreeMap::from([(day, 3), (next_day, 1)]));
+    }
+
+    #[test]
+    fn enforces_burst() {
+        // Refills one request every 864 seconds.
+        let tracker = BudgetTracker::new("burst".to_string(), budget(100, Some(2)));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let now = Instant::now();
+        assert!(tracker.try_consume_at(day, now));
+        assert!(tracker.try_consume_at(day, now));
+        assert!(!tracker.try_consume_at(day, now));
+
+        let later = now + Duration::from_secs(864);
+        assert!(tracker.try_consume_at(day, later));
+        assert!(!tracker.try_consume_at(day, later));
+    }
+}
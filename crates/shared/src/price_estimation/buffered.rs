@@ -163,7 +163,7 @@ where
         requests: mpsc::UnboundedReceiver<H160>,
         results_sender: broadcast::Sender<NativePriceResult>,
     ) -> JoinHandle<()> {
-        tokio::task::spawn(batched_for_each(
+        observe::metrics::spawn_in_current_scope(batched_for_each(
             config,
             requests,
             inner.max_batch_size(),
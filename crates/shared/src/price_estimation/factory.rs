@@ -1,5 +1,6 @@
 use {
     super::{
+        budget::{BudgetTracker, BudgetedPriceEstimator},
         competition::CompetitionEstimator,
         external::ExternalPriceEstimator,
         instrumented::InstrumentedPriceEstimator,
@@ -31,7 +32,7 @@ use {
     ethrpc::block_stream::CurrentBlockWatcher,
     gas_estimation::GasPriceEstimating,
     number::nonzero::U256 as NonZeroU256,
-    rate_limit::RateLimiter,
+    rate_limit::{RateLimiter, RateLimiterRegistry},
     reqwest::Url,
     std::{collections::HashMap, num::NonZeroUsize, sync::Arc},
 };
@@ -43,6 +44,9 @@ pub struct PriceEstimatorFactory<'a> {
     components: Components,
     trade_verifier: Option<Arc<dyn TradeVerifying>>,
     estimators: HashMap<String, EstimatorEntry>,
+    budgets: HashMap<String, Arc<BudgetTracker>>,
+    /// Credentials of the drivers requiring authentication.
+    driver_credentials: &'a [arguments::DriverCredential],
 }
 
 #[derive(Clone)]
@@ -70,6 +74,9 @@ pub struct Components {
     pub bad_token_detector: Arc<dyn BadTokenDetecting>,
     pub tokens: Arc<dyn TokenInfoFetching>,
     pub code_fetcher: Arc<CachedCodeFetcher>,
+    pub db: sqlx::PgPool,
+    /// The rate limiters of the chain the estimators are created for.
+    pub rate_limiters: Arc<RateLimiterRegistry>,
 }
 
 impl<'a> PriceEstimatorFactory<'a> {
@@ -79,12 +86,28 @@ impl<'a> PriceEstimatorFactory<'a> {
         network: Network,
         components: Components,
     ) -> Result<Self> {
+        let budgets: HashMap<_, _> = args
+            .price_estimation_budgets
+            .iter()
+            .map(|config| {
+                let tracker = BudgetTracker::new(config.estimator.clone(), config.budget);
+                (config.estimator.clone(), Arc::new(tracker))
+            })
+            .collect();
+        observe::metrics::spawn_in_current_scope(BudgetTracker::sync_forever(
+            budgets.values().cloned().collect(),
+            components.db.clone(),
+            args.price_estimation_budget_sync_interval,
+        ));
+
         Ok(Self {
             trade_verifier: Self::trade_verifier(args, shared_args, &network, &components).await?,
             args,
             network,
             components,
             estimators: HashMap::new(),
+            budgets,
+            driver_credentials: &shared_args.driver_credentials,
         })
     }
 
@@ -144,14 +167,19 @@ impl<'a> PriceEstimatorFactory<'a> {
         )
     }
 
-    fn rate_limiter(&self, name: &str) -> Arc<RateLimiter> {
-        Arc::new(RateLimiter::from_strategy(
+    fn rate_limiter(&self, name: &str) -> Result<Arc<RateLimiter>> {
+        self.components.rate_limiters.get_or_create(
+            &format!("{name}_estimator"),
             self.args
                 .price_estimation_rate_limiter
                 .clone()
                 .unwrap_or_default(),
-            format!("{name}_estimator"),
-        ))
+        )
+    }
+
+    /// Enforces the configured request budget of the estimator (if any).
+    fn budgeted<T>(&self, name: &str, estimator: T) -> BudgetedPriceEstimator<T> {
+        BudgetedPriceEstimator::new(estimator, self.budgets.get(name).cloned())
     }
 
     fn create_estimator_entry<T>(&self, name: &str, params: T::Params) -> Result<EstimatorEntry>
@@ -165,9 +193,9 @@ impl<'a> PriceEstimatorFactory<'a> {
             .as_ref()
             .and_then(|trade_verifier| estimator.verified(trade_verifier));
 
-        let fast = instrument(estimator, name);
+        let fast = instrument(self.budgeted(name, estimator), name);
         let optimal = match verified {
-            Some(verified) => instrument(verified, name),
+            Some(verified) => instrument(self.budgeted(name, verified), name),
             None => fast.clone(),
         };
 
@@ -178,7 +206,7 @@ impl<'a> PriceEstimatorFactory<'a> {
         // price estimator (this is because request sharing isn't benificial),
         // nor do we configure the trade verifier (because external price
         // precision is less critical).
-        let native = instrument(T::init(self, name, params)?, name);
+        let native = instrument(self.budgeted(name, T::init(self, name, params)?), name);
 
         Ok(EstimatorEntry {
             optimal,
@@ -214,13 +242,16 @@ impl<'a> PriceEstimatorFactory<'a> {
                 Ok((
                     name.clone(),
                     Arc::new(InstrumentedPriceEstimator::new(
-                        native::OneInch::new(
-                            self.components.http_factory.create(),
-                            self.args.one_inch_url.clone(),
-                            self.args.one_inch_api_key.clone(),
-                            self.network.chain.id(),
-                            self.network.block_stream.clone(),
-                            self.components.tokens.clone(),
+                        self.budgeted(
+                            &name,
+                            native::OneInch::new(
+                                self.components.http_factory.create(),
+                                self.args.one_inch_url.clone(),
+                                self.args.one_inch_api_key.clone(),
+                                self.network.chain.id(),
+                                self.network.block_stream.clone(),
+                                self.components.tokens.clone(),
+                            ),
                         ),
                         name,
                     )),
@@ -261,11 +292,17 @@ impl<'a> PriceEstimatorFactory<'a> {
                         };
 
                         Arc::new(InstrumentedPriceEstimator::new(
-                            BufferedRequest::with_config(coin_gecko, configuration),
+                            self.budgeted(
+                                &name,
+                                BufferedRequest::with_config(coin_gecko, configuration),
+                            ),
                             name.clone() + "Buffered",
                         ))
                     } else {
-                        Arc::new(InstrumentedPriceEstimator::new(coin_gecko, name.clone()))
+                        Arc::new(InstrumentedPriceEstimator::new(
+                            self.budgeted(&name, coin_gecko),
+                            name.clone(),
+                        ))
                     };
 
                 Ok((name, coin_gecko))
@@ -277,6 +314,8 @@ impl<'a> PriceEstimatorFactory<'a> {
         let params = ExternalEstimatorParams {
             driver: solver.url.clone(),
             timeout: self.args.quote_timeout,
+            credential: arguments::DriverCredential::find(self.driver_credentials, &solver.name)
+                .cloned(),
         };
         if !self.estimators.contains_key(&solver.name) {
             let estimator =
@@ -398,16 +437,24 @@ trait PriceEstimatorCreating: Sized {
 struct ExternalEstimatorParams {
     driver: Url,
     timeout: std::time::Duration,
+    credential: Option<arguments::DriverCredential>,
 }
 
 impl PriceEstimatorCreating for ExternalPriceEstimator {
     type Params = ExternalEstimatorParams;
 
     fn init(factory: &PriceEstimatorFactory, name: &str, params: Self::Params) -> Result<Self> {
+        let headers = params
+            .credential
+            .map(|credential| credential.headers())
+            .unwrap_or_default();
         Ok(Self::new(
             params.driver,
-            factory.components.http_factory.create(),
-            factory.rate_limiter(name),
+            factory
+                .components
+                .http_factory
+                .configure(|builder| builder.default_headers(headers)),
+            factory.rate_limiter(name)?,
             factory.network.block_stream.clone(),
             params.timeout,
         ))
@@ -26,6 +26,7 @@ use {
     thiserror::Error,
 };
 
+pub mod budget;
 mod buffered;
 pub mod competition;
 pub mod external;
@@ -122,15 +123,40 @@ pub struct Arguments {
     /// back_off_growth_factor: f64 >= 1.0
     /// min_back_off: Duration
     /// max_back_off: Duration
+    /// To additionally stay below a known request quota append (or pass
+    /// only) "<N>rps[,burst=<N>]" which drops requests exceeding N requests
+    /// per second on average.
     #[clap(long, env, verbatim_doc_comment)]
     pub price_estimation_rate_limiter: Option<Strategy>,
 
+    /// Request budgets for price estimators backed by paid APIs. Needs to be
+    /// passed as
+    /// "<NAME>|requests-per-day=<N>|burst=<N>|cost=<COST>,<NAME>|...".
+    /// NAME: name of the driver or native price estimator
+    /// requests-per-day: maximum number of requests per day (UTC)
+    /// burst: maximum number of requests issued at once
+    /// cost: cost of a single request used to report the spend
+    /// Estimators with an exhausted budget get skipped in favour of the
+    /// remaining estimators.
+    #[clap(long, env, use_value_delimiter = true, verbatim_doc_comment)]
+    pub price_estimation_budgets: Vec<budget::BudgetConfig>,
+
+    /// How often the usage of budgeted price estimators gets persisted and
+    /// synchronized with other services.
+    #[clap(
+        long,
+        env,
+        default_value = "10s",
+        value_parser = crate::arguments::parse_duration,
+    )]
+    pub price_estimation_budget_sync_interval: Duration,
+
     /// How often the native price estimator should refresh its cache.
     #[clap(
         long,
         env,
         default_value = "1s",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub native_price_cache_refresh: Duration,
 
@@ -139,7 +165,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "10m",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub native_price_cache_max_age: Duration,
 
@@ -151,7 +177,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "80s",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub native_price_prefetch_time: Duration,
 
@@ -167,8 +193,9 @@ pub struct Arguments {
 
     /// The amount in native tokens atoms to use for price estimation. Should be
     /// reasonably large so that small pools do not influence the prices. If
-    /// not set a reasonable default is used based on network id.
-    #[clap(long, env, value_parser = U256::from_dec_str)]
+    /// not set a reasonable default is used based on network id. Accepts unit
+    /// suffixes like `0.1eth`.
+    #[clap(long, env, value_parser = crate::arguments::parse_wei)]
     pub amount_to_estimate_prices_with: Option<U256>,
 
     /// The API endpoint for the Balancer SOR API for solving.
@@ -209,7 +236,7 @@ pub struct Arguments {
         long,
         env,
         default_value = "5s",
-        value_parser = humantime::parse_duration,
+        value_parser = crate::arguments::parse_duration,
     )]
     pub quote_timeout: Duration,
 
@@ -250,11 +277,11 @@ pub struct CoinGeckoBuffered {
     /// An additional minimum delay to wait for collecting CoinGecko requests.
     ///
     /// The delay to start counting after receiving the first request.
-    #[clap(long, env, value_parser = humantime::parse_duration, group = "coin_gecko_buffered")]
+    #[clap(long, env, value_parser = crate::arguments::parse_duration, group = "coin_gecko_buffered")]
     pub coin_gecko_debouncing_time: Option<Duration>,
 
     /// The timeout to wait for the result to be ready
-    #[clap(long, env, value_parser = humantime::parse_duration, group = "coin_gecko_buffered")]
+    #[clap(long, env, value_parser = crate::arguments::parse_duration, group = "coin_gecko_buffered")]
     pub coin_gecko_result_ready_timeout: Option<Duration>,
 
     /// Maximum capacity of the broadcast channel to store the CoinGecko native
@@ -281,6 +308,8 @@ impl Display for Arguments {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let Self {
             price_estimation_rate_limiter,
+            price_estimation_budgets,
+            price_estimation_budget_sync_interval,
             native_price_cache_refresh,
             native_price_cache_max_age,
             native_price_prefetch_time,
@@ -302,6 +331,16 @@ impl Display for Arguments {
             "price_estimation_rate_limites",
             price_estimation_rate_limiter,
         )?;
+        writeln!(
+            f,
+            "price_estimation_budgets: {:?}",
+            price_estimation_budgets
+        )?;
+        writeln!(
+            f,
+            "price_estimation_budget_sync_interval: {:?}",
+            price_estimation_budget_sync_interval
+        )?;
         writeln!(
             f,
             "native_price_cache_refresh: {:?}",
@@ -74,7 +74,7 @@ impl CoinGecko {
             Chain::Gnosis => "xdai".to_string(),
             Chain::ArbitrumOne => "arbitrum-one".to_string(),
             Chain::Base => "base".to_string(),
-            Chain::Sepolia | Chain::Goerli | Chain::Hardhat => {
+            Chain::Sepolia | Chain::Goerli | Chain::Hardhat | Chain::Custom(_) => {
                 anyhow::bail!("unsupported network {}", chain.name())
             }
         };
@@ -366,6 +366,7 @@ mod tests {
                         // use tokens with 18 decimals
                         decimals: Some(18),
                         symbol: None,
+                        ..Default::default()
                     };
                     (*t, info)
                 })
@@ -480,6 +481,7 @@ mod tests {
                     let info = TokenInfo {
                         decimals,
                         symbol: None,
+                        ..Default::default()
                     };
                     (*t, info)
                 })
@@ -528,6 +530,7 @@ mod tests {
                     let info = TokenInfo {
                         decimals,
                         symbol: None,
+                        ..Default::default()
                     };
                     (*t, info)
                 })
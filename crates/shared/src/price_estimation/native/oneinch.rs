@@ -5,7 +5,7 @@ use {
     ethrpc::block_stream::{into_stream, CurrentBlockWatcher},
     futures::{future::BoxFuture, FutureExt, StreamExt},
     num::ToPrimitive,
-    number::{conversions::u256_to_big_rational, serialization::HexOrDecimalU256},
+    number::{conversions::to_base_units, serialization::HexOrDecimalU256},
     primitive_types::{H160, U256},
     reqwest::{header::AUTHORIZATION, Client},
     serde::Deserialize,
@@ -60,7 +60,7 @@ impl OneInch {
         token_info: Arc<dyn TokenInfoFetching>,
     ) {
         let prices = self.prices.clone();
-        tokio::task::spawn(async move {
+        observe::metrics::spawn_in_current_scope(async move {
             let mut block_stream = into_stream(current_block);
             loop {
                 let current_prices = get_current_prices(
@@ -141,10 +141,7 @@ async fn get_current_prices(
                 tracing::debug!(?token, "could not fetch decimals; discarding spot price");
                 return None;
             };
-            let unit =
-                num::BigRational::from_integer(num::BigInt::from(10u64).pow(decimals.into()));
-            let normalized_price = u256_to_big_rational(&price) / unit;
-            Some((token, normalized_price.to_f64()?))
+            Some((token, to_base_units(price, decimals).to_f64()?))
         })
         .collect();
     Ok(normalized_prices)
@@ -176,6 +173,7 @@ mod tests {
                             symbol: None,
                             // hard code 6 decimals because we are testing with USDC
                             decimals: Some(6),
+                            ..Default::default()
                         },
                     )
                 })
@@ -1,9 +1,12 @@
 use {
     super::PriceEstimationError,
-    crate::price_estimation::native::{
-        from_normalized_price,
-        NativePriceEstimateResult,
-        NativePriceEstimating,
+    crate::{
+        price_estimation::native::{
+            from_normalized_price,
+            NativePriceEstimateResult,
+            NativePriceEstimating,
+        },
+        request_sharing::BoxRequestSharing,
     },
     bigdecimal::BigDecimal,
     futures::{FutureExt, StreamExt},
@@ -51,9 +54,13 @@ pub struct CachingNativePriceEstimator(Arc<Inner>);
 struct Inner {
     cache: Mutex<HashMap<H160, CachedResult>>,
     high_priority: Mutex<IndexSet<H160>>,
-    estimator: Box<dyn NativePriceEstimating>,
+    estimator: Arc<dyn NativePriceEstimating>,
+    requests: BoxRequestSharing<H160, NativePriceEstimateResult>,
     max_age: Duration,
     concurrent_requests: usize,
+    /// Met once the cache got populated from persisted prices or by the first
+    /// maintenance run.
+    warm: observe::readiness::Condition,
 }
 
 struct UpdateTask {
@@ -178,7 +185,14 @@ impl Inner {
                 }
             };
 
-            let result = self.estimator.estimate_native_price(*token).await;
+            let result = self
+                .requests
+                .shared_or_else(*token, |token| {
+                    let estimator = self.estimator.clone();
+                    let token = *token;
+                    async move { estimator.estimate_native_price(token).await }.boxed()
+                })
+                .await;
 
             // update price in cache
             if should_cache(&result) {
@@ -269,6 +283,7 @@ impl UpdateTask {
         while let Some(inner) = self.inner.upgrade() {
             let now = Instant::now();
             self.single_update(&inner).await;
+            inner.warm.ready();
             tokio::time::sleep(self.update_interval.saturating_sub(now.elapsed())).await;
         }
     }
@@ -301,6 +316,7 @@ impl CachingNativePriceEstimator {
             .collect::<HashMap<_, _>>();
 
         *self.0.cache.lock().unwrap() = cache;
+        self.0.warm.ready();
     }
 
     /// Creates new CachingNativePriceEstimator using `estimator` to calculate
@@ -319,11 +335,13 @@ impl CachingNativePriceEstimator {
         concurrent_requests: usize,
     ) -> Self {
         let inner = Arc::new(Inner {
-            estimator,
+            estimator: Arc::from(estimator),
+            requests: BoxRequestSharing::labelled("native_price_estimation".into()),
             cache: Default::default(),
             high_priority: Default::default(),
             max_age,
             concurrent_requests,
+            warm: observe::readiness::register("native_price_cache"),
         });
 
         let update_task = UpdateTask {
@@ -334,7 +352,7 @@ impl CachingNativePriceEstimator {
         }
         .run()
         .instrument(tracing::info_span!("caching_native_price_estimator"));
-        tokio::spawn(update_task);
+        observe::metrics::spawn_in_current_scope(update_task);
 
         Self(inner)
     }
@@ -830,9 +848,11 @@ mod tests {
                 .collect(),
             ),
             high_priority: Default::default(),
-            estimator: Box::new(MockNativePriceEstimating::new()),
+            estimator: Arc::new(MockNativePriceEstimating::new()),
+            requests: BoxRequestSharing::labelled("native_price_estimation".into()),
             max_age: Default::default(),
             concurrent_requests: 1,
+            warm: observe::readiness::register("native_price_cache"),
         };
 
         let now = now + Duration::from_secs(1);
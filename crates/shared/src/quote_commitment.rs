@@ -0,0 +1,260 @@
+//! Signed quote commitments.
+//!
+//! When enabled, quotes get returned together with a commitment signed by the
+//! backend. Orders that include the commitment are validated against the
+//! committed quote instead of a freshly computed one, so the quoted fee is
+//! guaranteed for as long as the commitment is valid. The set of accepted
+//! signers can contain previous signing keys so commitments issued right
+//! before a key rotation stay valid.
+
+use {
+    crate::order_quoting::Quote,
+    ethcontract::H160,
+    model::{
+        order::{OrderData, OrderKind},
+        quote::{QuoteCommitment, QuoteId, SignedQuoteCommitment},
+        time,
+        DomainSeparator,
+    },
+    secp256k1::SecretKey,
+    std::{collections::HashSet, time::Duration},
+    web3::signing::{Key, SecretKeyRef},
+};
+
+pub struct QuoteCommitments {
+    domain: DomainSeparator,
+    key: SecretKey,
+    /// Signers whose commitments are accepted, including the current one.
+    signers: HashSet<H160>,
+    validity: Duration,
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum QuoteCommitmentError {
+    #[error("quote commitments are not enabled")]
+    Disabled,
+    #[error("quote commitment has an invalid signature")]
+    InvalidSignature,
+    #[error("quote commitment was signed by unknown signer {0:?}")]
+    UnknownSigner(H160),
+    #[error("quote commitment expired")]
+    Expired,
+    #[error("quote commitment does not match the order")]
+    QuoteMismatch,
+}
+
+impl QuoteCommitments {
+    pub fn new(
+        domain: DomainSeparator,
+        key: SecretKey,
+        previous_signers: impl IntoIterator<Item = H160>,
+        validity: Duration,
+    ) -> Self {
+        let signer = SecretKeyRef::new(&key).address();
+        Self {
+            domain,
+            key,
+            signers: previous_signers
+                .into_iter()
+                .chain(std::iter::once(signer))
+                .collect(),
+            validity,
+        }
+    }
+
+    /// Address of the key new commitments get signed with.
+    pub fn signer(&self) -> H160 {
+        SecretKeyRef::new(&self.key).address()
+    }
+
+    /// Signs a commitment for a stored quote. Returns `None` for quotes that
+    /// were not stored since orders can't reference them.
+    pub fn commit(&self, quote: &Quote) -> Option<SignedQuoteCommitment> {
+        let quote_id = quote.id?;
+        let expiry = time::timestamp_after_duration(time::now_in_epoch_seconds(), self.validity)
+            .min(u32::try_from(quote.data.expiration.timestamp()).unwrap_or(u32::MAX));
+        let commitment = QuoteCommitment {
+            quote_id,
+            sell_token: quote.data.sell_token,
+            buy_token: quote.data.buy_token,
+            sell_amount: quote.sell_amount,
+            buy_amount: quote.buy_amount,
+            fee_amount: quote.fee_amount,
+            expiry,
+        };
+        Some(commitment.sign(&self.domain, SecretKeyRef::new(&self.key)))
+    }
+
+    /// Verifies that the commitment was signed by an accepted signer, is not
+    /// expired, belongs to the quote referenced by the order and that the order
+    /// trades the committed amounts.
+    pub fn verify(
+        &self,
+        commitment: &SignedQuoteCommitment,
+        order: &OrderData,
+        quote_id: Option<QuoteId>,
+        now: u32,
+    ) -> Result<(), QuoteCommitmentError> {
+        let signer = commitment
+            .signer(&self.domain)
+            .map_err(|_| QuoteCommitmentError::InvalidSignature)?;
+        if !self.signers.contains(&signer) {
+            return Err(QuoteCommitmentError::UnknownSigner(signer));
+        }
+        if commitment.commitment.expiry < now {
+            return Err(QuoteCommitmentError::Expired);
+        }
+        if quote_id != Some(commitment.commitment.quote_id)
+            || order.sell_token != commitment.commitment.sell_token
+            || order.buy_token != commitment.commitment.buy_token
+            || !trades_committed_amounts(order, &commitment.commitment)
+        {
+            return Err(QuoteCommitmentError::QuoteMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Whether the order trades exactly the committed amount on its fixed side
+/// and its limit price is no better than the committed one. Orders don't pay
+/// a separate fee, the committed fee is part of the sell amount.
+fn trades_committed_amounts(order: &OrderData, commitment: &QuoteCommitment) -> bool {
+    let Some(sell_amount) = commitment.sell_amount.checked_add(commitment.fee_amount) else {
+        return false;
+    };
+    order.fee_amount.is_zero()
+        && match order.kind {
+            OrderKind::Sell => {
+                order.sell_amount == sell_amount && order.buy_amount <= commitment.buy_amount
+            }
+            OrderKind::Buy => {
+                order.buy_amount == commitment.buy_amount && order.sell_amount >= sell_amount
+            }
+        }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::order_quoting::QuoteData, chrono::Utc};
+
+    fn key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn verifies_commitments() {
+        let domain = DomainSeparator([1; 32]);
+        let previous = QuoteCommitments::new(domain, key(1), [], Duration::from_secs(30));
+        let commitments =
+            QuoteCommitments::new(domain, key(2), [previous.signer()], Duration::from_secs(30));
+        let quote = Quote {
+            id: Some(42),
+            data: QuoteData {
+                sell_token: H160([1; 20]),
+                buy_token: H160([2; 20]),
+                expiration: Utc::now() + chrono::Duration::seconds(10),
+                ..Default::default()
+            },
+            sell_amount: 100.into(),
+            buy_amount: 99.into(),
+            fee_amount: 1.into(),
+        };
+        let order = OrderData {
+            sell_token: H160([1; 20]),
+            buy_token: H160([2; 20]),
+            sell_amount: 101.into(),
+            buy_amount: 99.into(),
+            kind: OrderKind::Sell,
+            ..Default::default()
+        };
+        let now = time::now_in_epoch_seconds();
+
+        // The expiry is capped by the expiration of the quote.
+        let commitment = commitments.commit(&quote).unwrap();
+        assert!(commitment.commitment.expiry <= now + 11);
+        assert_eq!(
+            commitments.verify(&commitment, &order, Some(42), now),
+            Ok(())
+        );
+
+        // Commitments of rotated keys are still accepted.
+        let rotated = previous.commit(&quote).unwrap();
+        assert_eq!(commitments.verify(&rotated, &order, Some(42), now), Ok(()));
+        let unknown = QuoteCommitments::new(domain, key(3), [], Duration::from_secs(30));
+        assert_eq!(
+            commitments.verify(&unknown.commit(&quote).unwrap(), &order, Some(42), now),
+            Err(QuoteCommitmentError::UnknownSigner(unknown.signer()))
+        );
+
+        assert_eq!(
+            commitments.verify(&commitment, &order, Some(43), now),
+            Err(QuoteCommitmentError::QuoteMismatch)
+        );
+        assert_eq!(
+            commitments.verify(&commitment, &order, Some(42), now + 60),
+            Err(QuoteCommitmentError::Expired)
+        );
+
+        let mut tampered = commitment;
+        tampered.commitment.fee_amount = 0.into();
+        assert!(commitments
+            .verify(&tampered, &order, Some(42), now)
+            .is_err());
+
+        assert!(commitments.commit(&Quote { id: None, ..quote }).is_none());
+    }
+
+    #[test]
+    fn rejects_orders_with_other_amounts() {
+        let domain = DomainSeparator([1; 32]);
+        let commitments = QuoteCommitments::new(domain, key(1), [], Duration::from_secs(30));
+        let quote = Quote {
+            id: Some(42),
+            data: QuoteData {
+                expiration: Utc::now() + chrono::Duration::seconds(10),
+                ..Default::default()
+            },
+            sell_amount: 100.into(),
+            buy_amount: 99.into(),
+            fee_amount: 1.into(),
+        };
+        let commitment = commitments.commit(&quote).unwrap();
+        let now = time::now_in_epoch_seconds();
+        let verify = |kind, sell_amount: u64, buy_amount: u64, fee_amount: u64| {
+            let order = OrderData {
+                sell_amount: sell_amount.into(),
+                buy_amount: buy_amount.into(),
+                fee_amount: fee_amount.into(),
+                kind,
+                ..Default::default()
+            };
+            commitments.verify(&commitment, &order, Some(42), now)
+        };
+
+        // Worse limit prices than the committed one are fine.
+        assert_eq!(verify(OrderKind::Sell, 101, 99, 0), Ok(()));
+        assert_eq!(verify(OrderKind::Sell, 101, 90, 0), Ok(()));
+        assert_eq!(verify(OrderKind::Buy, 101, 99, 0), Ok(()));
+        assert_eq!(verify(OrderKind::Buy, 110, 99, 0), Ok(()));
+
+        for (kind, sell_amount, buy_amount, fee_amount) in [
+            // The fixed side differs from the committed amount.
+            (OrderKind::Sell, 100, 99, 0),
+            (OrderKind::Sell, 1000, 99, 0),
+            (OrderKind::Buy, 101, 98, 0),
+            (OrderKind::Buy, 1010, 990, 0),
+            // The limit price is better than the committed one.
+            (OrderKind::Sell, 101, 100, 0),
+            (OrderKind::Buy, 100, 99, 0),
+            // The committed fee is paid separately.
+            (OrderKind::Sell, 100, 99, 1),
+            (OrderKind::Buy, 100, 99, 1),
+        ] {
+            assert_eq!(
+                verify(kind, sell_amount, buy_amount, fee_amount),
+                Err(QuoteCommitmentError::QuoteMismatch),
+                "{kind:?} {sell_amount} {buy_amount} {fee_amount}"
+            );
+        }
+    }
+}
@@ -3,42 +3,56 @@
 //! The design of this module is driven by the need to always return data
 //! quickly so that end users going through the api do not have to wait longer
 //! than necessary:
-//! - The mutex is never locked while waiting on an async operation (getting
+//! - Locks are never held while waiting on an async operation (getting
 //!   on-chain data from the node).
 //! - Automatically updating the cache is decoupled from normal on-chain data
 //!   fetches.
+//! - Entries are spread over shards by key. Lookups only take read locks on
+//!   the shards of the requested keys and record accesses with atomics, so
+//!   concurrent readers never block each other.
 //!
 //! A result of this is that it is possible that the same uncached entry is
 //! requested multiple times simultaneously and some work is wasted. This is
 //! unlikely to happen in practice and the value is going to be cached the next
 //! time it is needed.
 //!
-//! When entries are requested we mark all those entries as recently used which
-//! potentially evicts other entries from the lru cache. Cache misses are
-//! fetched and inserted into the cache. Then when the automatic update runs the
-//! next time, we request and cache all recently used entries. For some
-//! consumers we only care about the "recent" state of the entries. So we can
-//! return any result from the cache even if it comes from previous blocks.
+//! Every fetch is a new generation. When entries are requested we stamp them
+//! with the current generation. Cache misses are fetched and inserted into the
+//! cache. Then when the automatic update runs the next time, we request and
+//! cache the entries that were most recently requested at a specific block.
+//! For some consumers we only care about the "recent" state of the entries.
+//! So we can return any result from the cache even if it comes from previous
+//! blocks.
 //!
 //! On the other hand for others we need to fetch on-chain data at exact blocks
 //! which is why we keep a cache of previous blocks in the first place as we
 //! could simplify this module if it was only used by by the former.
+//!
+//! The number of cached keys is bounded. Once the bound is exceeded the keys
+//! that haven't been requested for the most generations get evicted.
 
 use {
     crate::request_sharing::BoxRequestSharing,
     anyhow::{Context, Result},
-    cached::{Cached, SizedCache},
     ethcontract::BlockNumber,
     ethrpc::block_stream::CurrentBlockWatcher,
     futures::{FutureExt, StreamExt},
-    itertools::Itertools,
-    prometheus::IntCounterVec,
+    prometheus::{IntCounterVec, IntGaugeVec},
     std::{
         cmp,
-        collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
-        hash::Hash,
+        collections::{
+            hash_map::{Entry, RandomState},
+            BTreeMap,
+            HashMap,
+            HashSet,
+        },
+        hash::{BuildHasher, Hash},
         num::{NonZeroU64, NonZeroUsize},
-        sync::{Arc, Mutex},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+            RwLock,
+        },
         time::Duration,
     },
     tracing::Instrument,
@@ -47,6 +61,9 @@ use {
 /// How many liqudity sources should at most be fetched in a single chunk.
 const REQUEST_BATCH_SIZE: usize = 200;
 
+/// Into how many independently locked shards the entries get split.
+const SHARDS: usize = 16;
+
 /// A trait used to define `RecentBlockCache` updating behaviour.
 #[async_trait::async_trait]
 pub trait CacheFetching<K, V>: Send + Sync + 'static {
@@ -100,8 +117,16 @@ where
     K: CacheKey<V>,
     F: CacheFetching<K, V>,
 {
-    mutexed: Mutex<Mutexed<K, V>>,
+    shards: Box<[RwLock<Shard<K, V>>]>,
+    hasher: RandomState,
+    // Incremented with every fetch. Accessed keys get stamped with it.
+    generation: AtomicU64,
+    // The last block at which the automatic cache updating happened.
+    last_update_block: AtomicU64,
     number_of_blocks_to_cache: NonZeroU64,
+    number_of_entries_to_auto_update: NonZeroUsize,
+    maximum_recent_block_age: u64,
+    maximum_cached_keys: NonZeroUsize,
     fetcher: Arc<F>,
     maximum_retries: u32,
     delay_between_retries: Duration,
@@ -117,6 +142,7 @@ pub struct CacheConfig {
     pub maximum_recent_block_age: u64,
     pub max_retries: u32,
     pub delay_between_retries: Duration,
+    pub maximum_cached_keys: NonZeroUsize,
 }
 
 impl Default for CacheConfig {
@@ -127,6 +153,7 @@ impl Default for CacheConfig {
             maximum_recent_block_age: Default::default(),
             max_retries: Default::default(),
             delay_between_retries: Default::default(),
+            maximum_cached_keys: NonZeroUsize::MAX,
         }
     }
 }
@@ -140,7 +167,16 @@ struct Metrics {
     /// misses
     #[metric(labels("cache_type"))]
     recent_block_cache_misses: IntCounterVec,
+
+    /// Keys evicted because the cache exceeded its maximum number of keys.
+    #[metric(labels("cache_type"))]
+    recent_block_cache_evictions: IntCounterVec,
+
+    /// Number of currently cached keys.
+    #[metric(labels("cache_type"))]
+    recent_block_cache_keys: IntGaugeVec,
 }
+
 impl<K, V, F> RecentBlockCache<K, V, F>
 where
     K: CacheKey<V>,
@@ -158,6 +194,9 @@ where
     ///
     /// maximum_recent_block_age: When a recent block is requested, this is the
     /// maximum a cached block can have to be considered.
+    ///
+    /// maximum_cached_keys: The least recently used keys get evicted when the
+    /// cache holds more keys than this.
     pub fn new(
         config: CacheConfig,
         fetcher: F,
@@ -166,12 +205,14 @@ where
     ) -> Result<Self> {
         let block = block_stream.borrow().number;
         let inner = Arc::new(Inner {
-            mutexed: Mutex::new(Mutexed::new(
-                config.number_of_entries_to_auto_update,
-                block,
-                config.maximum_recent_block_age,
-            )),
+            shards: (0..SHARDS).map(|_| Default::default()).collect(),
+            hasher: RandomState::new(),
+            generation: AtomicU64::new(1),
+            last_update_block: AtomicU64::new(block),
             number_of_blocks_to_cache: config.number_of_blocks_to_cache,
+            number_of_entries_to_auto_update: config.number_of_entries_to_auto_update,
+            maximum_recent_block_age: config.maximum_recent_block_age,
+            maximum_cached_keys: config.maximum_cached_keys,
             fetcher: Arc::new(fetcher),
             maximum_retries: config.max_retries,
             delay_between_retries: config.delay_between_retries,
@@ -198,7 +239,7 @@ where
         block_stream: CurrentBlockWatcher,
         label: String,
     ) {
-        tokio::task::spawn(
+        observe::metrics::spawn_in_current_scope(
             async move {
                 let mut stream = ethrpc::block_stream::into_stream(block_stream);
                 while let Some(block) = stream.next().await {
@@ -224,21 +265,20 @@ where
 {
     async fn update_cache_at_block(&self, new_block: u64) -> Result<()> {
         let keys = self
-            .mutexed
-            .lock()
-            .unwrap()
             .keys_of_recently_used_entries()
+            .into_iter()
             .collect::<HashSet<_>>();
         tracing::debug!("automatically updating {} entries", keys.len());
         let found_values = self
             .fetch_inner_many(keys.clone(), Block::Number(new_block))
             .await?;
 
-        let mut mutexed = self.mutexed.lock().unwrap();
-        mutexed.insert(new_block, keys, found_values);
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.insert(new_block, keys, found_values, generation, false);
         let oldest_to_keep = new_block.saturating_sub(self.number_of_blocks_to_cache.get() - 1);
-        mutexed.remove_cached_blocks_older_than(oldest_to_keep);
-        mutexed.last_update_block = new_block;
+        self.remove_cached_blocks_older_than(oldest_to_keep);
+        self.last_update_block.store(new_block, Ordering::Relaxed);
+        self.enforce_maximum_cached_keys();
 
         Ok(())
     }
@@ -286,15 +326,19 @@ where
             Block::Recent => None,
             Block::Number(number) => Some(number),
         };
+        let lookup = self.lookup(block);
 
+        let mut keys_by_shard = HashMap::<_, Vec<_>>::new();
+        for key in keys {
+            keys_by_shard.entry(self.shard(&key)).or_default().push(key);
+        }
         let mut cache_hit_count = 0usize;
         let mut cache_hits = Vec::new();
         let mut cache_misses = HashSet::new();
-        let last_update_block;
-        {
-            let mut mutexed = self.mutexed.lock().unwrap();
+        for (shard, keys) in keys_by_shard {
+            let shard = self.shards[shard].read().unwrap();
             for key in keys {
-                match mutexed.get(key.clone(), block) {
+                match shard.get(&key, &lookup) {
                     Some(values) => {
                         cache_hit_count += 1;
                         cache_hits.extend_from_slice(values);
@@ -304,7 +348,6 @@ where
                     }
                 }
             }
-            last_update_block = mutexed.last_update_block;
         }
 
         self.metrics
@@ -320,7 +363,7 @@ where
             return Ok(cache_hits);
         }
 
-        let cache_miss_block = block.unwrap_or(last_update_block);
+        let cache_miss_block = block.unwrap_or(lookup.last_update_block);
         let cache_misses: Vec<_> = cache_misses.into_iter().collect();
         // Splits fetches into chunks because we can get over 1400 requests when the
         // cache is empty which tend to time out if we don't chunk them.
@@ -329,107 +372,254 @@ where
             let fetched = self
                 .fetch_inner_many(keys, Block::Number(cache_miss_block))
                 .await?;
-            let found_keys = fetched.iter().map(K::for_value).unique().collect_vec();
             cache_hits.extend_from_slice(&fetched);
-
-            let mut mutexed = self.mutexed.lock().unwrap();
-            mutexed.insert(cache_miss_block, chunk.iter().cloned(), fetched);
-            if block.is_some() {
-                // Only if a block number was specified the caller actually cared about the most
-                // accurate data for these keys. Only in that case we want to be nice and
-                // remember the key for future background updates of the cached
-                // liquidity.
-                for key in found_keys {
-                    mutexed.recently_used.cache_set(key, ());
-                }
-            }
+            // Only if a block number was specified the caller actually cared about the
+            // most accurate data for these keys. Only in that case we want to be nice
+            // and remember the keys for future background updates of the cached
+            // liquidity.
+            self.insert(
+                cache_miss_block,
+                chunk.iter().cloned(),
+                fetched,
+                lookup.generation,
+                block.is_some(),
+            );
         }
+        self.enforce_maximum_cached_keys();
 
         Ok(cache_hits)
     }
+
+    fn shard(&self, key: &K) -> usize {
+        // Truncating the hash is fine since it only has to be evenly distributed.
+        (self.hasher.hash_one(key) % SHARDS as u64) as usize
+    }
+
+    /// Starts a new generation for looking up entries at the block.
+    fn lookup(&self, block: Option<u64>) -> Lookup {
+        Lookup {
+            block,
+            generation: self.generation.fetch_add(1, Ordering::Relaxed),
+            last_update_block: self.last_update_block.load(Ordering::Relaxed),
+            maximum_recent_block_age: self.maximum_recent_block_age,
+        }
+    }
+
+    /// Caches the values at the block. Keys with values get marked for
+    /// automatic updates if `auto_update` is set.
+    fn insert(
+        &self,
+        block: u64,
+        keys: impl IntoIterator<Item = K>,
+        values: impl IntoIterator<Item = V>,
+        generation: u64,
+        auto_update: bool,
+    ) {
+        let mut keys_by_shard = HashMap::<_, Vec<_>>::new();
+        for key in keys {
+            keys_by_shard.entry(self.shard(&key)).or_default().push(key);
+        }
+        let mut values_by_shard = HashMap::<_, Vec<_>>::new();
+        for value in values {
+            values_by_shard
+                .entry(self.shard(&K::for_value(&value)))
+                .or_default()
+                .push(value);
+        }
+        for (shard, keys) in keys_by_shard {
+            let values = values_by_shard.remove(&shard).unwrap_or_default();
+            self.shards[shard].write().unwrap().insert(
+                block,
+                keys,
+                values,
+                generation,
+                auto_update,
+            );
+        }
+    }
+
+    fn remove_cached_blocks_older_than(&self, oldest_to_keep: u64) {
+        tracing::debug!("dropping blocks older than {} from cache", oldest_to_keep);
+        let (mut entries, mut items) = (0, 0);
+        for shard in self.shards.iter() {
+            let mut shard = shard.write().unwrap();
+            items += shard.remove_cached_blocks_older_than(oldest_to_keep);
+            entries += shard.entries.len();
+        }
+        tracing::debug!(entries, items, "cache was updated and now contains");
+    }
+
+    /// Keys that were most recently requested at a specific block, most recent
+    /// first.
+    fn keys_of_recently_used_entries(&self) -> Vec<K> {
+        let mut keys = Vec::new();
+        for shard in self.shards.iter() {
+            let shard = shard.read().unwrap();
+            keys.extend(shard.keys.iter().filter_map(|(key, state)| {
+                let generation = state.last_auto_update_request.load(Ordering::Relaxed);
+                (generation != 0).then(|| (generation, key.clone()))
+            }));
+        }
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+        keys.into_iter()
+            .take(self.number_of_entries_to_auto_update.get())
+            .map(|(_, key)| key)
+            .collect()
+    }
+
+    /// Evicts the least recently used keys if there are more than the
+    /// configured maximum.
+    fn enforce_maximum_cached_keys(&self) {
+        let cached_keys: usize = self
+            .shards
+            .iter()
+            .map(|shard| shard.read().unwrap().keys.len())
+            .sum();
+        let excess = cached_keys.saturating_sub(self.maximum_cached_keys.get());
+        let mut evicted = 0;
+        if excess > 0 {
+            // Only the generations are collected to find the cutoff below which
+            // keys get evicted, so no key has to be cloned.
+            let mut generations = Vec::with_capacity(cached_keys);
+            for shard in self.shards.iter() {
+                let shard = shard.read().unwrap();
+                generations.extend(
+                    shard
+                        .keys
+                        .values()
+                        .map(|state| state.last_request.load(Ordering::Relaxed)),
+                );
+            }
+            // The number of keys might have changed in the meantime but the bound
+            // doesn't need to be exact.
+            let excess = excess.min(generations.len());
+            let (older, &mut cutoff, _) = generations.select_nth_unstable(excess - 1);
+            // Keys last requested at the cutoff only get evicted until the excess
+            // is gone.
+            let mut at_cutoff = excess - older.iter().filter(|&&g| g < cutoff).count();
+            for shard in self.shards.iter() {
+                evicted += shard
+                    .write()
+                    .unwrap()
+                    .evict_requested_before(cutoff, &mut at_cutoff);
+            }
+            self.metrics
+                .recent_block_cache_evictions
+                .with_label_values(&[self.metrics_label])
+                .inc_by(evicted as u64);
+        }
+        self.metrics
+            .recent_block_cache_keys
+            .with_label_values(&[self.metrics_label])
+            .set(
+                cached_keys
+                    .saturating_sub(evicted)
+                    .try_into()
+                    .unwrap_or(i64::MAX),
+            );
+    }
+}
+
+/// Parameters of a single cache lookup.
+struct Lookup {
+    block: Option<u64>,
+    generation: u64,
+    last_update_block: u64,
+    maximum_recent_block_age: u64,
 }
 
 #[derive(Debug)]
-struct Mutexed<K, V>
-where
-    K: CacheKey<V>,
-{
-    recently_used: SizedCache<K, ()>,
-    // For quickly finding at which block an entry is cached.
-    cached_most_recently_at_block: HashMap<K, u64>,
+struct Shard<K, V> {
+    keys: HashMap<K, KeyState>,
     // Tuple ordering allows us to efficiently construct range queries by block.
     entries: BTreeMap<(u64, K), Vec<V>>,
-    // The last block at which the automatic cache updating happened.
-    last_update_block: u64,
-    // Maximum age a cached block can have to count as recent.
-    maximum_recent_block_age: u64,
 }
 
-impl<K, V> Mutexed<K, V>
-where
-    K: CacheKey<V>,
-{
-    fn new(
-        entries_lru_size: NonZeroUsize,
-        current_block: u64,
-        maximum_recent_block_age: u64,
-    ) -> Self {
+#[derive(Debug)]
+struct KeyState {
+    // For quickly finding at which block an entry is cached.
+    cached_most_recently_at_block: u64,
+    // Generation of the last request of the key. Used to evict cold keys.
+    last_request: AtomicU64,
+    // Generation of the last request of the key at a specific block which found
+    // values. Keys that were never requested like this (0) don't get updated
+    // automatically.
+    last_auto_update_request: AtomicU64,
+}
+
+impl<K, V> Default for Shard<K, V> {
+    fn default() -> Self {
         Self {
-            recently_used: SizedCache::with_size(entries_lru_size.get()),
-            cached_most_recently_at_block: HashMap::new(),
+            keys: HashMap::new(),
             entries: BTreeMap::new(),
-            last_update_block: current_block,
-            maximum_recent_block_age,
         }
     }
+}
 
-    fn get(&mut self, key: K, block: Option<u64>) -> Option<&[V]> {
-        let allow_background_udpates = block.is_some();
-        let block = block.or_else(|| {
-            self.cached_most_recently_at_block
-                .get(&key)
-                .copied()
-                .filter(|&block| {
-                    self.last_update_block.saturating_sub(block) <= self.maximum_recent_block_age
-                })
+impl<K, V> Shard<K, V>
+where
+    K: CacheKey<V>,
+{
+    fn get(&self, key: &K, lookup: &Lookup) -> Option<&[V]> {
+        let state = self.keys.get(key)?;
+        let block = lookup.block.or_else(|| {
+            Some(state.cached_most_recently_at_block).filter(|&block| {
+                lookup.last_update_block.saturating_sub(block) <= lookup.maximum_recent_block_age
+            })
         })?;
-        let result = self.entries.get(&(block, key.clone())).map(Vec::as_slice);
-        if allow_background_udpates && result.is_some_and(|values| !values.is_empty()) {
-            self.recently_used.cache_set(key, ());
+        let result = self.entries.get(&(block, key.clone())).map(Vec::as_slice)?;
+        state
+            .last_request
+            .fetch_max(lookup.generation, Ordering::Relaxed);
+        let allow_background_updates = lookup.block.is_some();
+        if allow_background_updates && !result.is_empty() {
+            state
+                .last_auto_update_request
+                .fetch_max(lookup.generation, Ordering::Relaxed);
         }
-        result
+        Some(result)
     }
 
     fn insert(
         &mut self,
         block: u64,
-        keys: impl IntoIterator<Item = K>,
-        values: impl IntoIterator<Item = V>,
+        keys: Vec<K>,
+        values: Vec<V>,
+        generation: u64,
+        auto_update: bool,
     ) {
-        for key in keys {
-            match self.cached_most_recently_at_block.entry(key.clone()) {
+        for key in &keys {
+            match self.keys.entry(key.clone()) {
                 Entry::Occupied(mut entry) => {
-                    let value = entry.get_mut();
-                    *value = cmp::max(*value, block);
+                    let state = entry.get_mut();
+                    state.cached_most_recently_at_block =
+                        cmp::max(state.cached_most_recently_at_block, block);
                 }
                 Entry::Vacant(entry) => {
-                    entry.insert(block);
+                    entry.insert(KeyState {
+                        cached_most_recently_at_block: block,
+                        last_request: AtomicU64::new(generation),
+                        last_auto_update_request: AtomicU64::new(0),
+                    });
                 }
             }
             // Make sure entries without any values are cached.
-            self.entries.insert((block, key), Vec::new());
+            self.entries.insert((block, key.clone()), Vec::new());
         }
         for value in values {
+            let key = K::for_value(&value);
+            if auto_update {
+                self.keys[&key]
+                    .last_auto_update_request
+                    .fetch_max(generation, Ordering::Relaxed);
+            }
             // Unwrap because previous loop guarantees all keys have an entry.
-            self.entries
-                .get_mut(&(block, K::for_value(&value)))
-                .unwrap()
-                .push(value);
+            self.entries.get_mut(&(block, key)).unwrap().push(value);
         }
     }
 
-    fn remove_cached_blocks_older_than(&mut self, oldest_to_keep: u64) {
-        tracing::debug!("dropping blocks older than {} from cache", oldest_to_keep);
+    /// Returns the number of remaining cached values.
+    fn remove_cached_blocks_older_than(&mut self, oldest_to_keep: u64) -> usize {
         self.entries = self.entries.split_off(&(oldest_to_keep, K::first_ord()));
 
         // Iterate from newest block to oldest block and only keep the most recent
@@ -446,17 +636,27 @@ where
         // Afterwards drop all entries that are now empty.
         self.entries.retain(|_, values| !values.is_empty());
 
-        self.cached_most_recently_at_block
-            .retain(|_, block| *block >= oldest_to_keep);
-        tracing::debug!(
-            entries = self.entries.len(),
-            items,
-            "cache was updated and now contains",
-        );
+        self.keys
+            .retain(|_, state| state.cached_most_recently_at_block >= oldest_to_keep);
+        items
     }
 
-    fn keys_of_recently_used_entries(&self) -> impl Iterator<Item = K> + '_ {
-        self.recently_used.key_order().cloned()
+    /// Evicts the keys last requested before the cutoff generation and up to
+    /// `at_cutoff` keys last requested at it. Returns the number of evicted
+    /// keys.
+    fn evict_requested_before(&mut self, cutoff: u64, at_cutoff: &mut usize) -> usize {
+        let before = self.keys.len();
+        self.keys.retain(|_, state| {
+            let generation = state.last_request.load(Ordering::Relaxed);
+            if generation == cutoff && *at_cutoff > 0 {
+                *at_cutoff -= 1;
+                return false;
+            }
+            generation >= cutoff
+        });
+        let keys = &self.keys;
+        self.entries.retain(|(_, key), _| keys.contains_key(key));
+        before - self.keys.len()
     }
 }
 
@@ -466,7 +666,7 @@ mod tests {
         super::*,
         ethrpc::block_stream::{mock_single_block, BlockInfo},
         futures::FutureExt,
-        std::sync::Arc,
+        std::sync::Mutex,
     };
 
     #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -529,6 +729,27 @@ mod tests {
         keys.into_iter().map(TestKey)
     }
 
+    impl<F> Inner<TestKey, TestValue, F>
+    where
+        F: CacheFetching<TestKey, TestValue>,
+    {
+        fn entries(&self) -> usize {
+            self.shards
+                .iter()
+                .map(|shard| shard.read().unwrap().entries.len())
+                .sum()
+        }
+
+        fn is_cached(&self, key: TestKey, block: Option<u64>) -> bool {
+            let lookup = self.lookup(block);
+            self.shards[self.shard(&key)]
+                .read()
+                .unwrap()
+                .get(&key, &lookup)
+                .is_some()
+        }
+    }
+
     #[tokio::test]
     async fn marks_recently_used() {
         let fetcher = FakeCacheFetcher::new(vec![
@@ -555,12 +776,7 @@ mod tests {
         .inner;
 
         let assert_keys_recently_used = |expected_keys: &[usize]| {
-            let cached_keys = cache
-                .mutexed
-                .lock()
-                .unwrap()
-                .keys_of_recently_used_entries()
-                .collect::<Vec<_>>();
+            let cached_keys = cache.keys_of_recently_used_entries();
             let expected_keys: Vec<_> = expected_keys.iter().copied().map(TestKey).collect();
             assert_eq!(cached_keys, expected_keys);
         };
@@ -805,26 +1021,26 @@ mod tests {
             .fetch(test_keys(0..10), Block::Number(10))
             .await
             .unwrap();
-        assert_eq!(cache.mutexed.lock().unwrap().entries.len(), 10);
+        assert_eq!(cache.entries(), 10);
 
         block_sender.send(block(11)).unwrap();
         // Fetch updated liquidity for 2 of the initial 10 keys
         cache.update_cache_at_block(11).await.unwrap();
         // Fetch 2 new keys which are NOT scheduled for background updates
         cache.fetch(test_keys(10..12), Block::Recent).await.unwrap();
-        assert_eq!(cache.mutexed.lock().unwrap().entries.len(), 12);
+        assert_eq!(cache.entries(), 12);
 
         block_sender.send(block(12)).unwrap();
         // Fetch updated liquidity for 2 of the initial 10 keys
         cache.update_cache_at_block(12).await.unwrap();
-        assert_eq!(cache.mutexed.lock().unwrap().entries.len(), 4);
+        assert_eq!(cache.entries(), 4);
 
         block_sender.send(block(13)).unwrap();
         // Update 2 blocks in background but now it's time to evict the 2 additional
         // keys we fetched with `Block::Recent` because we are only allowed to
         // keep state that is up to 2 blocks old.
         cache.update_cache_at_block(13).await.unwrap();
-        assert_eq!(cache.mutexed.lock().unwrap().entries.len(), 2);
+        assert_eq!(cache.entries(), 2);
     }
 
     #[tokio::test]
@@ -855,8 +1071,8 @@ mod tests {
             .now_or_never()
             .unwrap()
             .unwrap();
-        assert!(cache.mutexed.lock().unwrap().get(key, Some(7)).is_some());
-        assert!(cache.mutexed.lock().unwrap().get(key, None).is_none());
+        assert!(cache.is_cached(key, Some(7)));
+        assert!(!cache.is_cached(key, None));
 
         // cache at block 8
         cache
@@ -864,8 +1080,81 @@ mod tests {
             .now_or_never()
             .unwrap()
             .unwrap();
-        assert!(cache.mutexed.lock().unwrap().get(key, Some(7)).is_some());
-        assert!(cache.mutexed.lock().unwrap().get(key, Some(8)).is_some());
-        assert!(cache.mutexed.lock().unwrap().get(key, None).is_some());
+        assert!(cache.is_cached(key, Some(7)));
+        assert!(cache.is_cached(key, Some(8)));
+        assert!(cache.is_cached(key, None));
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_keys() {
+        let values = (0..4).map(|key| TestValue::new(key, "")).collect();
+        let fetcher = FakeCacheFetcher::new(values);
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                number_of_entries_to_auto_update: NonZeroUsize::new(4).unwrap(),
+                maximum_cached_keys: NonZeroUsize::new(2).unwrap(),
+                ..Default::default()
+            },
+            fetcher,
+            block_stream,
+            "",
+        )
+        .unwrap()
+        .inner;
+
+        cache
+            .fetch(test_keys(0..2), Block::Number(10))
+            .await
+            .unwrap();
+        // Requesting key 0 again makes key 1 the coldest one.
+        cache.fetch(test_keys(0..1), Block::Recent).await.unwrap();
+        cache
+            .fetch(test_keys(2..3), Block::Number(10))
+            .await
+            .unwrap();
+        assert_eq!(cache.entries(), 2);
+        assert!(cache.is_cached(TestKey(0), Some(10)));
+        assert!(!cache.is_cached(TestKey(1), Some(10)));
+        assert!(cache.is_cached(TestKey(2), Some(10)));
+        // Evicted keys are no longer updated automatically.
+        assert_eq!(
+            cache.keys_of_recently_used_entries(),
+            vec![TestKey(2), TestKey(0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn evicts_only_excess_keys_requested_at_the_same_time() {
+        let values = (0..5).map(|key| TestValue::new(key, "")).collect();
+        let fetcher = FakeCacheFetcher::new(values);
+        let block_stream = mock_single_block(BlockInfo {
+            number: 10,
+            ..Default::default()
+        });
+        let cache = RecentBlockCache::new(
+            CacheConfig {
+                maximum_cached_keys: NonZeroUsize::new(3).unwrap(),
+                ..Default::default()
+            },
+            fetcher,
+            block_stream,
+            "",
+        )
+        .unwrap()
+        .inner;
+
+        cache
+            .fetch(test_keys(0..5), Block::Number(10))
+            .await
+            .unwrap();
+        let cached = (0..5)
+            .filter(|&key| cache.is_cached(TestKey(key), Some(10)))
+            .count();
+        assert_eq!(cached, 3);
+        assert_eq!(cache.entries(), 3);
     }
 }
@@ -5,6 +5,7 @@ use {
     },
     prometheus::{
         core::{AtomicU64, GenericGaugeVec},
+        HistogramVec,
         IntCounterVec,
     },
     std::{
@@ -27,9 +28,33 @@ use {
 
 /// Share an expensive to compute response with multiple requests that occur
 /// while one of them is already in flight.
+///
+/// Optionally a [`ResponseCache`] can be attached which gets consulted before
+/// issuing a new request and gets populated with completed responses. This
+/// allows components that already cache their results to get request
+/// coalescing without implementing it themselves.
 pub struct RequestSharing<Request, Fut: Future> {
-    in_flight: Arc<Mutex<HashMap<Request, WeakShared<Fut>>>>,
+    in_flight: Cache<Request, Fut>,
     request_label: String,
+    cache: Option<Arc<dyn ResponseCache<Request, Fut::Output>>>,
+}
+
+/// Hook allowing [`RequestSharing`] to serve responses from a cache and to
+/// store completed responses in it.
+pub trait ResponseCache<Request, Response>: Send + Sync {
+    /// Returns the cached response for the request if there is one.
+    fn get(&self, request: &Request) -> Option<Response>;
+
+    /// Gets called with every response computed by a shared future. The
+    /// implementation decides whether the response is worth caching (e.g. to
+    /// not cache errors).
+    fn insert(&self, request: Request, response: &Response);
+}
+
+/// An in flight future together with the number of requests it served.
+struct InFlight<Fut: Future> {
+    future: WeakShared<Fut>,
+    requests: u64,
 }
 
 /// Request sharing for boxed futures.
@@ -39,7 +64,7 @@ pub type BoxRequestSharing<Request, Response> =
 /// A boxed shared future.
 pub type BoxShared<T> = Shared<BoxFuture<'static, T>>;
 
-type Cache<Request, Response> = Arc<Mutex<HashMap<Request, WeakShared<Response>>>>;
+type Cache<Request, Fut> = Arc<Mutex<HashMap<Request, InFlight<Fut>>>>;
 
 impl<Request: Send + 'static, Fut: Future + Send + 'static> RequestSharing<Request, Fut>
 where
@@ -51,20 +76,38 @@ where
         Self {
             in_flight: cache,
             request_label,
+            cache: None,
         }
     }
 
+    /// Attaches a response cache which gets used by
+    /// [`RequestSharing::cached_or_else`].
+    pub fn with_cache(mut self, cache: Arc<dyn ResponseCache<Request, Fut::Output>>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     fn collect_garbage(cache: &Cache<Request, Fut>, label: &str) {
+        let metrics = Metrics::get();
         let mut cache = cache.lock().unwrap();
-        cache.retain(|_request, weak| weak.upgrade().is_some());
-        Metrics::get()
+        cache.retain(|_request, in_flight| {
+            let alive = in_flight.future.upgrade().is_some();
+            if !alive {
+                metrics
+                    .request_sharing_coalesced_requests
+                    .with_label_values(&[label])
+                    .observe(in_flight.requests as f64);
+            }
+            alive
+        });
+        metrics
             .request_sharing_cached_items
             .with_label_values(&[label])
             .set(cache.len() as u64);
     }
 
     fn spawn_gc(cache: Cache<Request, Fut>, label: String) {
-        tokio::task::spawn(async move {
+        observe::metrics::spawn_in_current_scope(async move {
             loop {
                 Self::collect_garbage(&cache, &label);
                 tokio::time::sleep(Duration::from_millis(500)).await;
@@ -88,6 +131,7 @@ impl<Request, Fut: Future> Clone for RequestSharing<Request, Fut> {
         Self {
             in_flight: Default::default(),
             request_label: self.request_label.clone(),
+            cache: self.cache.clone(),
         }
     }
 }
@@ -106,7 +150,11 @@ where
     {
         let mut in_flight = self.in_flight.lock().unwrap();
 
-        let existing = in_flight.get(&request).and_then(WeakShared::upgrade);
+        let existing = in_flight.get_mut(&request).and_then(|existing| {
+            let future = existing.future.upgrade()?;
+            existing.requests += 1;
+            Some(future)
+        });
 
         if let Some(existing) = existing {
             Metrics::get()
@@ -124,7 +172,13 @@ where
         let shared = future(&request).shared();
         // unwrap because downgrade only returns None if the Shared has already
         // completed which cannot be the case because we haven't polled it yet.
-        in_flight.insert(request, shared.downgrade().unwrap());
+        in_flight.insert(
+            request,
+            InFlight {
+                future: shared.downgrade().unwrap(),
+                requests: 1,
+            },
+        );
         Metrics::get()
             .request_sharing_cached_items
             .with_label_values(&[&self.request_label])
@@ -133,6 +187,42 @@ where
     }
 }
 
+impl<Request, Response> BoxRequestSharing<Request, Response>
+where
+    Request: Eq + Hash + Clone + Send + 'static,
+    Response: Clone + Send + Sync + 'static,
+{
+    /// Like [`RequestSharing::shared_or_else`] but first checks the attached
+    /// [`ResponseCache`] and stores the computed response in it.
+    pub fn cached_or_else<F>(&self, request: Request, future: F) -> BoxShared<Response>
+    where
+        F: FnOnce(&Request) -> BoxFuture<'static, Response>,
+    {
+        let Some(cache) = self.cache.clone() else {
+            return self.shared_or_else(request, future);
+        };
+
+        if let Some(response) = cache.get(&request) {
+            Metrics::get()
+                .request_sharing_access
+                .with_label_values(&[&self.request_label, "cached"])
+                .inc();
+            return futures::future::ready(response).boxed().shared();
+        }
+
+        self.shared_or_else(request, move |request| {
+            let request = request.clone();
+            let fut = future(&request);
+            async move {
+                let response = fut.await;
+                cache.insert(request, &response);
+                response
+            }
+            .boxed()
+        })
+    }
+}
+
 #[derive(prometheus_metric_storage::MetricStorage)]
 struct Metrics {
     /// Request sharing hits & misses
@@ -142,6 +232,11 @@ struct Metrics {
     /// Number of all currently cached requests
     #[metric(labels("request_label"))]
     request_sharing_cached_items: GenericGaugeVec<AtomicU64>,
+
+    /// Number of requests served by a single in flight future (i.e. the
+    /// coalescing ratio)
+    #[metric(labels("request_label"), buckets(1., 2., 3., 5., 10., 20., 50.))]
+    request_sharing_coalesced_requests: HistogramVec,
 }
 
 impl Metrics {
@@ -163,6 +258,7 @@ mod tests {
         let sharing = RequestSharing {
             in_flight: cache,
             request_label: label.clone(),
+            cache: None,
         };
 
         let shared0 = sharing.shared_or_else(0, |_| futures::future::ready(0).boxed());
@@ -182,6 +278,7 @@ mod tests {
         RequestSharing::collect_garbage(&sharing.in_flight, &label);
         assert_eq!(sharing.in_flight.lock().unwrap().len(), 1);
         assert!(sharing.in_flight.lock().unwrap().get(&0).is_some());
+        assert_eq!(sharing.in_flight.lock().unwrap()[&0].requests, 2);
 
         // complete second shared
         assert_eq!(shared1.now_or_never().unwrap(), 0);
@@ -191,4 +288,35 @@ mod tests {
         // GC deleted all now unused futures
         assert!(sharing.in_flight.lock().unwrap().is_empty());
     }
+
+    #[derive(Default)]
+    struct TestCache(Mutex<HashMap<u64, u64>>);
+
+    impl ResponseCache<u64, u64> for TestCache {
+        fn get(&self, request: &u64) -> Option<u64> {
+            self.0.lock().unwrap().get(request).copied()
+        }
+
+        fn insert(&self, request: u64, response: &u64) {
+            self.0.lock().unwrap().insert(request, *response);
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_responses_from_cache() {
+        let cache = Arc::new(TestCache::default());
+        let sharing = BoxRequestSharing::labelled("test_cache".into()).with_cache(cache.clone());
+
+        let response = sharing
+            .cached_or_else(0, |_| futures::future::ready(1).boxed())
+            .await;
+        assert_eq!(response, 1);
+        assert_eq!(cache.get(&0), Some(1));
+
+        // The response gets served from the cache without creating a new future.
+        let response = sharing
+            .cached_or_else(0, |_| async { panic!() }.boxed())
+            .await;
+        assert_eq!(response, 1);
+    }
 }
@@ -10,7 +10,7 @@
 
 use {
     super::swap::fixed_point::Bfp,
-    crate::{event_handling::MAX_REORG_BLOCK_COUNT, subgraph::SubgraphClient},
+    crate::subgraph::SubgraphClient,
     anyhow::Result,
     ethcontract::{H160, H256},
     reqwest::{Client, Url},
@@ -30,12 +30,23 @@ const QUERY_PAGE_SIZE: usize = 10;
 ///
 /// This client is not implemented to allow general GraphQL queries, but instead
 /// implements high-level methods that perform GraphQL queries under the hood.
-pub struct BalancerSubgraphClient(SubgraphClient);
+pub struct BalancerSubgraphClient {
+    client: SubgraphClient,
+    /// How many blocks behind the indexed head data is assumed reorg safe.
+    safe_block_offset: u64,
+}
 
 impl BalancerSubgraphClient {
     /// Creates a new Balancer subgraph client with full subgraph URL.
-    pub fn from_subgraph_url(subgraph_url: &Url, client: Client) -> Result<Self> {
-        Ok(Self(SubgraphClient::try_new(subgraph_url.clone(), client)?))
+    pub fn from_subgraph_url(
+        subgraph_url: &Url,
+        client: Client,
+        safe_block_offset: u64,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: SubgraphClient::try_new(subgraph_url.clone(), client)?,
+            safe_block_offset,
+        })
     }
 
     /// Retrieves the list of registered pools from the subgraph.
@@ -52,7 +63,7 @@ impl BalancerSubgraphClient {
         // <https://thegraph.com/docs/graphql-api#pagination>
         loop {
             let page = self
-                .0
+                .client
                 .query::<Data>(
                     QUERY,
                     Some(json_map! {
@@ -89,13 +100,13 @@ impl BalancerSubgraphClient {
         // retrieve historic block hashes just from the subgraph (it always
         // returns `null`).
         Ok(self
-            .0
+            .client
             .query::<block_number_query::Data>(block_number_query::QUERY, None)
             .await?
             .meta
             .block
             .number
-            .saturating_sub(MAX_REORG_BLOCK_COUNT))
+            .saturating_sub(self.safe_block_offset))
     }
 }
 
@@ -31,6 +31,7 @@ use {
         token_info::TokenInfoFetching,
     },
     anyhow::{Context, Result},
+    chain::Chain,
     clap::ValueEnum,
     contracts::{
         BalancerV2ComposableStablePoolFactory,
@@ -303,8 +304,13 @@ impl BalancerPoolFetcher {
         web3: Web3,
         contracts: &BalancerContracts,
         deny_listed_pool_ids: Vec<H256>,
+        chain: Chain,
     ) -> Result<Self> {
-        let pool_initializer = BalancerSubgraphClient::from_subgraph_url(subgraph_url, client)?;
+        let pool_initializer = BalancerSubgraphClient::from_subgraph_url(
+            subgraph_url,
+            client,
+            chain.safe_block_offset(),
+        )?;
         let web3 = ethrpc::instrumented::instrument_with_label(&web3, "balancerV2".into());
         let fetcher = Arc::new(Cache::new(
             create_aggregate_pool_fetcher(
@@ -313,6 +319,7 @@ impl BalancerPoolFetcher {
                 block_retriever,
                 token_infos,
                 contracts,
+                chain.finality_depth(),
             )
             .await?,
             config,
@@ -379,6 +386,7 @@ async fn create_aggregate_pool_fetcher(
     block_retriever: Arc<dyn BlockRetrieving>,
     token_infos: Arc<dyn TokenInfoFetching>,
     contracts: &BalancerContracts,
+    finality_depth: u64,
 ) -> Result<Aggregate> {
     let registered_pools = pool_initializer.initialize_pools().await?;
     let fetched_block_number = registered_pools.fetched_block_number;
@@ -407,6 +415,7 @@ async fn create_aggregate_pool_fetcher(
                     .remove(&$instance.address())
                     .unwrap_or_else(|| RegisteredPools::empty(fetched_block_number)),
                 fetched_block_hash,
+                finality_depth,
             )?
         }};
     }
@@ -460,6 +469,7 @@ async fn create_aggregate_pool_fetcher(
 
 /// Helper method for creating a boxed `InternalPoolFetching` instance for the
 /// specified factory and parameters.
+#[allow(clippy::too_many_arguments)]
 fn create_internal_pool_fetcher<Factory>(
     vault: BalancerV2Vault,
     factory: Factory,
@@ -468,6 +478,7 @@ fn create_internal_pool_fetcher<Factory>(
     factory_instance: &Instance<Web3Transport>,
     registered_pools: RegisteredPools,
     fetched_block_hash: H256,
+    finality_depth: u64,
 ) -> Result<Box<dyn InternalPoolFetching>>
 where
     Factory: FactoryIndexing,
@@ -485,6 +496,7 @@ where
         factory_instance,
         initial_pools,
         start_sync_at_block,
+        finality_depth,
     )))
 }
 
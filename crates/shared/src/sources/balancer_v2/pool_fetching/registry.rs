@@ -76,13 +76,17 @@ where
         factory_instance: &Instance<Web3Transport>,
         initial_pools: Vec<Factory::PoolInfo>,
         start_sync_at_block: Option<BlockNumberHash>,
+        finality_depth: u64,
     ) -> Self {
-        let updater = Mutex::new(EventHandler::new(
-            block_retreiver,
-            BasePoolFactoryContract(base_pool_factory(factory_instance)),
-            PoolStorage::new(initial_pools, fetcher.clone()),
-            start_sync_at_block,
-        ));
+        let updater = Mutex::new(
+            EventHandler::new(
+                block_retreiver,
+                BasePoolFactoryContract(base_pool_factory(factory_instance)),
+                PoolStorage::new(initial_pools, fetcher.clone()),
+                start_sync_at_block,
+            )
+            .with_finality_depth(finality_depth),
+        );
         Self { fetcher, updater }
     }
 }
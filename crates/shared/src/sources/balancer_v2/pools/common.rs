@@ -376,9 +376,9 @@ mod tests {
             .withf(move |t| t == tokens)
             .returning(move |_| {
                 hashmap! {
-                    tokens[0] => TokenInfo { decimals: Some(18), symbol: None },
-                    tokens[1] => TokenInfo { decimals: Some(18), symbol: None },
-                    tokens[2] => TokenInfo { decimals: Some(6), symbol: None },
+                    tokens[0] => TokenInfo { decimals: Some(18), symbol: None, ..Default::default() },
+                    tokens[1] => TokenInfo { decimals: Some(18), symbol: None, ..Default::default() },
+                    tokens[2] => TokenInfo { decimals: Some(6), symbol: None, ..Default::default() },
                 }
             });
 
@@ -762,7 +762,7 @@ mod tests {
         let mut token_infos = MockTokenInfoFetching::new();
         token_infos.expect_get_token_infos().returning(move |_| {
             hashmap! {
-                token => TokenInfo { decimals: None, symbol: None },
+                token => TokenInfo { decimals: None, symbol: None, ..Default::default() },
             }
         });
 
@@ -2,14 +2,22 @@
 
 use {
     super::{common, FactoryIndexing, PoolIndexing},
-    crate::sources::balancer_v2::{
-        graph_api::{PoolData, PoolType},
-        swap::fixed_point::Bfp,
+    crate::{
+        ethrpc::Web3,
+        sources::balancer_v2::{
+            graph_api::{PoolData, PoolType},
+            swap::fixed_point::Bfp,
+        },
     },
-    anyhow::Result,
-    contracts::{BalancerV2ComposableStablePool, BalancerV2ComposableStablePoolFactory},
-    ethcontract::BlockId,
-    futures::{future::BoxFuture, FutureExt as _},
+    anyhow::{ensure, Context, Result},
+    contracts::{
+        errors::EthcontractErrorType,
+        BalancerV2ComposableStablePool,
+        BalancerV2ComposableStablePoolFactory,
+        BalancerV2RateProvider,
+    },
+    ethcontract::{errors::MethodError, BlockId, U256},
+    futures::{future::BoxFuture, FutureExt as _, TryFutureExt as _},
 };
 
 pub use super::stable::{AmplificationParameter, PoolState};
@@ -46,13 +54,12 @@ impl FactoryIndexing for BalancerV2ComposableStablePoolFactory {
         common_pool_state: BoxFuture<'static, common::PoolState>,
         block: BlockId,
     ) -> BoxFuture<'static, Result<Option<Self::PoolState>>> {
-        let pool_contract = BalancerV2ComposableStablePool::at(
-            &self.raw_instance().web3(),
-            pool_info.common.address,
-        );
+        let web3 = self.raw_instance().web3();
+        let pool_contract = BalancerV2ComposableStablePool::at(&web3, pool_info.common.address);
 
         let fetch_common = common_pool_state.map(Result::Ok);
-        let fetch_scaling_factors = pool_contract.get_scaling_factors().block(block).call();
+        let fetch_scaling_factors =
+            fetch_scaling_factors(web3, pool_contract.clone(), pool_info.common.clone(), block);
         let fetch_amplification_parameter = pool_contract
             .get_amplification_parameter()
             .block(block)
@@ -62,7 +69,7 @@ impl FactoryIndexing for BalancerV2ComposableStablePoolFactory {
             let (common, scaling_factors, amplification_parameter) = futures::try_join!(
                 fetch_common,
                 fetch_scaling_factors,
-                fetch_amplification_parameter
+                fetch_amplification_parameter.map_err(anyhow::Error::from)
             )?;
             let amplification_parameter = {
                 let (factor, _, precision) = amplification_parameter;
@@ -78,7 +85,7 @@ impl FactoryIndexing for BalancerV2ComposableStablePoolFactory {
                         (
                             address,
                             common::TokenState {
-                                scaling_factor: Bfp::from_wei(scaling_factor),
+                                scaling_factor,
                                 ..token
                             },
                         )
@@ -92,12 +99,120 @@ impl FactoryIndexing for BalancerV2ComposableStablePoolFactory {
     }
 }
 
+/// The rate of a token with a rate provider.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct TokenRate {
+    /// Index of the token in the pool.
+    index: usize,
+    /// Rate the pool cached and included in its scaling factors.
+    cached: U256,
+    /// Timestamp after which the pool refreshes the cached rate.
+    expires: U256,
+    /// Rate currently reported by the rate provider.
+    current: U256,
+}
+
+/// Fetches the scaling factors the pool uses for the next swap. The scaling
+/// factors of tokens with a rate provider (e.g. wstETH) include the token's
+/// rate, which the pool caches for a while. Swaps first refresh expired
+/// caches with the current rate of the provider, so the scaling factors
+/// reported by the pool are outdated for tokens with an expired cache.
+///
+/// Tokens whose rate provider reverts keep their cached rate instead of
+/// dropping the whole pool.
+async fn fetch_scaling_factors(
+    web3: Web3,
+    pool: BalancerV2ComposableStablePool,
+    info: common::PoolInfo,
+    block: BlockId,
+) -> Result<Vec<Bfp>> {
+    let (scaling_factors, rate_providers) = futures::try_join!(
+        pool.get_scaling_factors().block(block).call(),
+        pool.get_rate_providers().block(block).call(),
+    )?;
+    ensure!(
+        scaling_factors.len() == info.tokens.len() && rate_providers.len() == info.tokens.len(),
+        "scaling factors or rate providers don't match pool tokens"
+    );
+    let mut scaling_factors: Vec<_> = scaling_factors.into_iter().map(Bfp::from_wei).collect();
+
+    // The BPT never has a rate provider.
+    let rates = futures::future::try_join_all(
+        info.tokens
+            .iter()
+            .zip(rate_providers)
+            .enumerate()
+            .filter(|(_, (_, provider))| !provider.is_zero())
+            .map(|(index, (&token, provider))| {
+                let fetch_cache = pool.get_token_rate_cache(token).block(block).call();
+                let fetch_current = BalancerV2RateProvider::at(&web3, provider)
+                    .get_rate()
+                    .block(block)
+                    .call();
+                async move {
+                    let (cache, current) = futures::join!(fetch_cache, fetch_current);
+                    let (cached, _, _, expires) = cache?;
+                    let current = match current {
+                        Ok(current) => current,
+                        Err(err) if EthcontractErrorType::is_contract_err(&err) => {
+                            tracing::debug!(?provider, ?err, "rate provider reverted");
+                            cached
+                        }
+                        Err(err) => return Err(err),
+                    };
+                    Result::<_, MethodError>::Ok(TokenRate {
+                        index,
+                        cached,
+                        expires,
+                        current,
+                    })
+                }
+            }),
+    )
+    .await?;
+
+    // The block is only needed if refreshing a cache would make a difference.
+    if rates.iter().all(|rate| rate.cached == rate.current) {
+        return Ok(scaling_factors);
+    }
+    let timestamp = web3
+        .eth()
+        .block(block)
+        .await?
+        .context("block not found")?
+        .timestamp;
+    refresh_expired_rates(
+        &mut scaling_factors,
+        &info.scaling_factors,
+        &rates,
+        timestamp,
+    )?;
+    Ok(scaling_factors)
+}
+
+/// Replaces the scaling factors of tokens whose rate cache expired at the
+/// timestamp with ones using the current rate. Like the pool, scales the
+/// decimal scaling factors of the tokens by their rates.
+fn refresh_expired_rates(
+    scaling_factors: &mut [Bfp],
+    decimal_scaling_factors: &[Bfp],
+    rates: &[TokenRate],
+    timestamp: U256,
+) -> Result<()> {
+    for rate in rates.iter().filter(|rate| timestamp > rate.expires) {
+        scaling_factors[rate.index] =
+            decimal_scaling_factors[rate.index].mul_down(Bfp::from_wei(rate.current))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
         crate::sources::balancer_v2::graph_api::Token,
-        ethcontract::{H160, H256},
+        ethcontract::{BlockNumber, H160, H256},
+        ethcontract_mock::Mock,
     };
 
     #[test]
@@ -124,4 +239,83 @@ mod tests {
 
         assert!(PoolInfo::from_graph_data(&pool, 42).is_err());
     }
+
+    #[test]
+    fn refreshes_expired_rates() {
+        let rate = |index, cached, expires: u64, current| TokenRate {
+            index,
+            cached: U256::exp10(cached),
+            expires: expires.into(),
+            current: U256::exp10(current),
+        };
+        let mut scaling_factors = vec![Bfp::exp10(0), Bfp::exp10(1), Bfp::exp10(12)];
+        refresh_expired_rates(
+            &mut scaling_factors,
+            &[Bfp::exp10(0), Bfp::exp10(0), Bfp::exp10(12)],
+            &[rate(1, 19, 100, 18), rate(2, 18, 200, 19)],
+            U256::from(150),
+        )
+        .unwrap();
+        // Only the expired rate gets refreshed.
+        assert_eq!(
+            scaling_factors,
+            vec![Bfp::exp10(0), Bfp::exp10(0), Bfp::exp10(12)]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetches_scaling_factors_with_token_rates() {
+        let mock = Mock::new(42);
+        let web3 = mock.web3();
+
+        let rate = U256::from(1_100_000_000_000_000_000u128);
+        let provider = mock.deploy(BalancerV2RateProvider::raw_contract().interface.abi.clone());
+        provider
+            .expect_call(BalancerV2RateProvider::signatures().get_rate())
+            .returns(rate);
+        let reverting = mock.deploy(BalancerV2RateProvider::raw_contract().interface.abi.clone());
+        reverting
+            .expect_call(BalancerV2RateProvider::signatures().get_rate())
+            .returns_error("BAL#000".to_owned());
+
+        let pool = mock.deploy(
+            BalancerV2ComposableStablePool::raw_contract()
+                .interface
+                .abi
+                .clone(),
+        );
+        let scaling_factors = vec![U256::exp10(18), rate, rate * 1_000_000];
+        pool.expect_call(BalancerV2ComposableStablePool::signatures().get_scaling_factors())
+            .returns(scaling_factors.clone());
+        pool.expect_call(BalancerV2ComposableStablePool::signatures().get_rate_providers())
+            .returns(vec![H160::zero(), provider.address(), reverting.address()]);
+        // Both caches hold the current rate and only expire in the future.
+        pool.expect_call(BalancerV2ComposableStablePool::signatures().get_token_rate_cache())
+            .returns((rate, rate, 3600.into(), U256::MAX));
+
+        let info = common::PoolInfo {
+            id: H256([0x90; 32]),
+            address: pool.address(),
+            tokens: vec![pool.address(), H160([1; 20]), H160([2; 20])],
+            scaling_factors: vec![Bfp::exp10(0), Bfp::exp10(0), Bfp::exp10(6)],
+            block_created: 42,
+        };
+        let fetched = fetch_scaling_factors(
+            web3.clone(),
+            BalancerV2ComposableStablePool::at(&web3, pool.address()),
+            info,
+            BlockNumber::Latest.into(),
+        )
+        .await
+        .unwrap();
+
+        // The reverting rate provider keeps its cached rate.
+        assert_eq!(
+            fetched,
+            scaling_factors
+                .into_iter()
+                .map(Bfp::from_wei)
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -70,6 +70,8 @@ pub fn defaults_for_network(chain: &Chain) -> Vec<BaselineSource> {
         ],
         Chain::Sepolia => vec![BaselineSource::TestnetUniswapV2],
         Chain::Hardhat => panic!("unsupported baseline sources for Hardhat"),
+        // Liquidity sources of custom chains have to be configured explicitly.
+        Chain::Custom(_) => vec![],
     }
 }
 
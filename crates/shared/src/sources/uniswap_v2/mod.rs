@@ -3,6 +3,7 @@
 pub mod pair_provider;
 pub mod pool_cache;
 pub mod pool_fetching;
+pub mod sync_events;
 
 use {
     self::{
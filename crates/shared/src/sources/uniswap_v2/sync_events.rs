@@ -0,0 +1,237 @@
+//! Keeps the reserves of Uniswap V2 like pools up to date by applying the
+//! `Sync` events emitted in every block instead of re-reading the reserves of
+//! all tracked pools.
+//!
+//! Pools get read from the chain once when they are first requested and are
+//! then updated from the logs of every new block, which only takes a single
+//! `eth_getLogs` request per block for all pools. Every pool gets re-read
+//! periodically to reconcile changes that don't emit `Sync` events (e.g.
+//! rebasing tokens). The tracked state gets dropped entirely on reorgs or when
+//! processing the logs fails.
+
+use {
+    super::pool_fetching::{Pool, PoolFetching},
+    crate::{ethrpc::Web3, recent_block_cache::Block},
+    anyhow::{ensure, Context, Result},
+    ethcontract::{H160, H256, U256},
+    hex_literal::hex,
+    model::TokenPair,
+    std::{
+        collections::{HashMap, HashSet},
+        num::NonZeroU64,
+        sync::Arc,
+    },
+    tokio::sync::Mutex,
+    web3::types::{BlockId, BlockNumber, FilterBuilder},
+};
+
+/// `keccak256("Sync(uint112,uint112)")`
+const SYNC_TOPIC: H256 = H256(hex!(
+    "1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1"
+));
+
+/// Maximum number of blocks that get caught up on by applying events. If
+/// more blocks passed since the last update all pools get re-read instead.
+const MAX_BLOCK_GAP: u64 = 20;
+
+/// Maximum number of pool addresses per `eth_getLogs` request.
+const MAX_ADDRESSES_PER_REQUEST: usize = 1000;
+
+pub struct SyncEventPoolFetcher {
+    inner: Arc<dyn PoolFetching>,
+    web3: Web3,
+    reconciliation_interval: NonZeroU64,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    /// The block up to which `Sync` events got applied and its hash.
+    block: Option<(u64, H256)>,
+    pools: HashMap<TokenPair, TrackedPool>,
+    pairs: HashMap<H160, TokenPair>,
+}
+
+struct TrackedPool {
+    pool: Pool,
+    /// Block at which the pool gets re-read from the chain.
+    reconcile_at: u64,
+}
+
+impl SyncEventPoolFetcher {
+    /// Wraps the fetcher used to read pools from the chain. Tracked pools get
+    /// re-read after `reconciliation_interval` blocks.
+    pub fn new(
+        inner: Arc<dyn PoolFetching>,
+        web3: Web3,
+        reconciliation_interval: NonZeroU64,
+    ) -> Self {
+        Self {
+            inner,
+            web3,
+            reconciliation_interval,
+            state: Default::default(),
+        }
+    }
+
+    /// Applies the `Sync` events up to the specified block to the tracked
+    /// pools.
+    async fn advance(&self, state: &mut State, block: u64) -> Result<()> {
+        let Some((current, hash)) = state.block else {
+            state.block = Some((block, self.block_hash(block).await?));
+            return Ok(());
+        };
+        if block <= current {
+            return Ok(());
+        }
+        ensure!(
+            block - current <= MAX_BLOCK_GAP,
+            "{} blocks behind",
+            block - current
+        );
+        ensure!(
+            self.block_hash(current).await? == hash,
+            "reorg at block {current}"
+        );
+
+        let addresses: Vec<_> = state.pairs.keys().copied().collect();
+        let mut updates = 0;
+        for chunk in addresses.chunks(MAX_ADDRESSES_PER_REQUEST) {
+            let filter = FilterBuilder::default()
+                .from_block(BlockNumber::Number((current + 1).into()))
+                .to_block(BlockNumber::Number(block.into()))
+                .address(chunk.to_vec())
+                .topics(Some(vec![SYNC_TOPIC]), None, None, None)
+                .build();
+            // Logs are returned in the order they were emitted, so applying
+            // them in order leaves the pools with their latest reserves.
+            for log in self.web3.eth().logs(filter).await? {
+                if log.removed == Some(true) {
+                    continue;
+                }
+                state.apply_sync(log.address, &log.data.0)?;
+                updates += 1;
+            }
+        }
+
+        state
+            .pools
+            .retain(|_, tracked| tracked.reconcile_at > block);
+        state.pairs.retain(|_, pair| state.pools.contains_key(pair));
+        state.block = Some((block, self.block_hash(block).await?));
+        Metrics::get().sync_event_updates.inc_by(updates);
+        Ok(())
+    }
+
+    async fn block_hash(&self, block: u64) -> Result<H256> {
+        self.web3
+            .eth()
+            .block(BlockId::Number(block.into()))
+            .await?
+            .and_then(|block| block.hash)
+            .with_context(|| format!("missing block {block}"))
+    }
+}
+
+impl State {
+    fn track(&mut self, pool: Pool, reconcile_at: u64) {
+        self.pairs.insert(pool.address, pool.tokens);
+        self.pools
+            .insert(pool.tokens, TrackedPool { pool, reconcile_at });
+    }
+
+    /// Updates the reserves of the pool from the data of a `Sync` event.
+    fn apply_sync(&mut self, address: H160, data: &[u8]) -> Result<()> {
+        ensure!(data.len() == 64, "invalid Sync event data");
+        let reserve = |word: &[u8]| {
+            let reserve = U256::from_big_endian(word);
+            ensure!(reserve <= U256::from(u128::MAX), "reserve overflow");
+            Ok(reserve.as_u128())
+        };
+        let reserves = (reserve(&data[..32])?, reserve(&data[32..])?);
+        if let Some(tracked) = self
+            .pairs
+            .get(&address)
+            .and_then(|pair| self.pools.get_mut(pair))
+        {
+            tracked.pool.reserves = reserves;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl PoolFetching for SyncEventPoolFetcher {
+    async fn fetch(&self, token_pairs: HashSet<TokenPair>, at_block: Block) -> Result<Vec<Pool>> {
+        let Block::Number(block) = at_block else {
+            return self.inner.fetch(token_pairs, at_block).await;
+        };
+
+        let mut state = self.state.lock().await;
+        if let Err(err) = self.advance(&mut state, block).await {
+            tracing::debug!(?err, "resetting pools tracked with Sync events");
+            Metrics::get().sync_event_resets.inc();
+            *state = State::default();
+            self.advance(&mut state, block).await?;
+        }
+        if state.block.map(|(number, _)| number) != Some(block) {
+            // Older blocks are not tracked.
+            drop(state);
+            return self.inner.fetch(token_pairs, at_block).await;
+        }
+
+        let (known, missing): (Vec<_>, HashSet<_>) = token_pairs
+            .into_iter()
+            .partition(|pair| state.pools.contains_key(pair));
+        let mut pools: Vec<_> = known.iter().map(|pair| state.pools[pair].pool).collect();
+        if !missing.is_empty() {
+            let fetched = self.inner.fetch(missing, at_block).await?;
+            for pool in &fetched {
+                state.track(*pool, block + self.reconciliation_interval.get());
+            }
+            pools.extend(fetched);
+        }
+        Ok(pools)
+    }
+}
+
+#[derive(prometheus_metric_storage::MetricStorage)]
+struct Metrics {
+    /// Number of pool reserve updates applied from Uniswap V2 Sync events.
+    sync_event_updates: prometheus::IntCounter,
+
+    /// Number of times the pools tracked with Sync events got reset.
+    sync_event_resets: prometheus::IntCounter,
+}
+
+impl Metrics {
+    fn get() -> &'static Self {
+        Metrics::instance(observe::metrics::get_storage_registry()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_sync_events() {
+        let pool = Pool::uniswap(
+            H160([1; 20]),
+            TokenPair::new(H160([2; 20]), H160([3; 20])).unwrap(),
+            (100, 200),
+        );
+        let mut state = State::default();
+        state.track(pool, 10);
+
+        let mut data = [0u8; 64];
+        data[31] = 7;
+        data[63] = 9;
+        state.apply_sync(pool.address, &data).unwrap();
+        assert_eq!(state.pools[&pool.tokens].pool.reserves, (7, 9));
+
+        // Events of untracked pools are ignored.
+        state.apply_sync(H160([4; 20]), &data).unwrap();
+        assert!(state.apply_sync(pool.address, &data[..32]).is_err());
+    }
+}
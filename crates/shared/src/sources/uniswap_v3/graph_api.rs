@@ -2,10 +2,7 @@
 //! data from the Uniswap V3 subgraph.
 
 use {
-    crate::{
-        event_handling::MAX_REORG_BLOCK_COUNT,
-        subgraph::{ContainsId, SubgraphClient},
-    },
+    crate::subgraph::{ContainsId, SubgraphClient},
     anyhow::Result,
     ethcontract::{H160, U256},
     num::BigInt,
@@ -103,17 +100,28 @@ const TICKS_BY_POOL_IDS_QUERY: &str = r#"
 ///
 /// This client is not implemented to allow general GraphQL queries, but instead
 /// implements high-level methods that perform GraphQL queries under the hood.
-pub struct UniV3SubgraphClient(SubgraphClient);
+pub struct UniV3SubgraphClient {
+    client: SubgraphClient,
+    /// How many blocks behind the indexed head data is assumed reorg safe.
+    safe_block_offset: u64,
+}
 
 impl UniV3SubgraphClient {
     /// Creates a new Uniswap V3 subgraph client from the specified URL.
-    pub fn from_subgraph_url(subgraph_url: &Url, client: Client) -> Result<Self> {
-        Ok(Self(SubgraphClient::try_new(subgraph_url.clone(), client)?))
+    pub fn from_subgraph_url(
+        subgraph_url: &Url,
+        client: Client,
+        safe_block_offset: u64,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: SubgraphClient::try_new(subgraph_url.clone(), client)?,
+            safe_block_offset,
+        })
     }
 
     async fn get_pools(&self, query: &str, variables: Map<String, Value>) -> Result<Vec<PoolData>> {
         Ok(self
-            .0
+            .client
             .paginated_query(query, variables)
             .await?
             .into_iter()
@@ -159,7 +167,7 @@ impl UniV3SubgraphClient {
             "pool_ids" => json!(pool_ids)
         };
         let result = self
-            .0
+            .client
             .paginated_query(TICKS_BY_POOL_IDS_QUERY, variables)
             .await?;
         Ok(result)
@@ -204,13 +212,13 @@ impl UniV3SubgraphClient {
         // retrieve historic block hashes just from the subgraph (it always
         // returns `null`).
         Ok(self
-            .0
+            .client
             .query::<block_number_query::Data>(block_number_query::QUERY, None)
             .await?
             .meta
             .block
             .number
-            .saturating_sub(MAX_REORG_BLOCK_COUNT))
+            .saturating_sub(self.safe_block_offset))
     }
 }
 
@@ -4,11 +4,12 @@ use {
         graph_api::{PoolData, Token, UniV3SubgraphClient},
     },
     crate::{
-        event_handling::{EventHandler, EventStoring, MAX_REORG_BLOCK_COUNT},
+        event_handling::{EventHandler, EventStoring},
         maintenance::Maintaining,
         recent_block_cache::Block,
     },
     anyhow::{Context, Result},
+    chain::Chain,
     ethcontract::{Event, H160, U256},
     ethrpc::{
         block_stream::{BlockRetrieving, RangeInclusive},
@@ -134,8 +135,13 @@ impl PoolsCheckpointHandler {
         subgraph_url: &Url,
         client: Client,
         max_pools_to_initialize_cache: usize,
+        chain: Chain,
     ) -> Result<Self> {
-        let graph_api = UniV3SubgraphClient::from_subgraph_url(subgraph_url, client)?;
+        let graph_api = UniV3SubgraphClient::from_subgraph_url(
+            subgraph_url,
+            client,
+            chain.safe_block_offset(),
+        )?;
         let mut registered_pools = graph_api.get_registered_pools().await?;
         tracing::debug!(
             block = %registered_pools.fetched_block_number, pools = %registered_pools.pools.len(),
@@ -264,6 +270,9 @@ pub struct UniswapV3PoolFetcher {
     /// Recent events used on top of pools_checkpoint to get the `latest_block`
     /// pools state.
     events: tokio::sync::Mutex<EventHandler<UniswapV3PoolEventFetcher, RecentEventsCache>>,
+    /// Events older than this many blocks are final and get moved into the
+    /// checkpoint.
+    finality_depth: u64,
 }
 
 impl UniswapV3PoolFetcher {
@@ -273,25 +282,34 @@ impl UniswapV3PoolFetcher {
         client: Client,
         block_retriever: Arc<dyn BlockRetrieving>,
         max_pools_to_initialize: usize,
+        chain: Chain,
     ) -> Result<Self> {
         let web3 = ethrpc::instrumented::instrument_with_label(&web3, "uniswapV3".into());
         let checkpoint =
-            PoolsCheckpointHandler::new(subgraph_url, client, max_pools_to_initialize).await?;
+            PoolsCheckpointHandler::new(subgraph_url, client, max_pools_to_initialize, chain)
+                .await?;
 
         let init_block = checkpoint.pools_checkpoint.lock().unwrap().block_number;
         let init_block = block_retriever.block(init_block).await?;
 
-        let events = tokio::sync::Mutex::new(EventHandler::new(
-            block_retriever,
-            UniswapV3PoolEventFetcher(web3),
-            RecentEventsCache::default(),
-            Some(init_block),
-        ));
+        let events = tokio::sync::Mutex::new(
+            EventHandler::new(
+                block_retriever,
+                UniswapV3PoolEventFetcher(web3),
+                RecentEventsCache::default(),
+                Some(init_block),
+            )
+            .with_finality_depth(chain.finality_depth()),
+        );
 
-        Ok(Self { checkpoint, events })
+        Ok(Self {
+            checkpoint,
+            events,
+            finality_depth: chain.finality_depth(),
+        })
     }
 
-    /// Moves the checkpoint to the block `latest_block - MAX_REORG_BLOCK_COUNT`
+    /// Moves the checkpoint to the block `latest_block - finality_depth`
     async fn move_checkpoint_to_future(&self) -> Result<()> {
         let last_event_block = self.events.lock().await.store().last_event_block().await?;
         let old_checkpoint_block = self
@@ -301,7 +319,7 @@ impl UniswapV3PoolFetcher {
             .unwrap()
             .block_number;
         let new_checkpoint_block = std::cmp::max(
-            last_event_block.saturating_sub(MAX_REORG_BLOCK_COUNT),
+            last_event_block.saturating_sub(self.finality_depth),
             old_checkpoint_block,
         );
 
@@ -1,19 +1,20 @@
 use {
-    anyhow::Result,
+    crate::request_sharing::{BoxRequestSharing, ResponseCache},
+    anyhow::{ensure, Context, Result},
     async_trait::async_trait,
     contracts::{errors::EthcontractErrorType, ERC20},
-    ethcontract::{errors::MethodError, H160},
+    ethcontract::{errors::MethodError, H160, U256},
     ethrpc::Web3,
-    futures::{
-        future::{BoxFuture, Shared},
-        FutureExt,
-    },
+    futures::FutureExt,
     model::order::BUY_ETH_ADDRESS,
     std::{
         collections::HashMap,
+        fmt::{self, Display, Formatter},
+        str::FromStr,
         sync::{Arc, Mutex},
     },
     thiserror::Error,
+    web3::types::CallRequest,
 };
 
 #[cfg_attr(test, derive(Eq, PartialEq))]
@@ -21,6 +22,73 @@ use {
 pub struct TokenInfo {
     pub decimals: Option<u8>,
     pub symbol: Option<String>,
+    /// Where the token info was resolved from.
+    pub source: TokenInfoSource,
+}
+
+/// Where a [`TokenInfo`] was resolved from.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TokenInfoSource {
+    /// The standard ERC-20 `symbol()` and `decimals()` methods.
+    #[default]
+    Erc20,
+    /// The token doesn't implement the standard methods and at least one value
+    /// was read from a non-standard fallback (e.g. a `bytes32` symbol).
+    Fallback,
+    /// At least one value was configured as an override.
+    Override,
+}
+
+/// Token info that takes precedence over the one read from the chain.
+///
+/// Parsed from `<address>|<symbol>|<decimals>`. Either the symbol or the
+/// decimals can be left empty to keep using the on-chain value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenInfoOverride {
+    pub address: H160,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+impl FromStr for TokenInfoOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split('|');
+        let address = parts.next().context("missing token address")?;
+        let symbol = parts.next().context("missing token symbol")?;
+        let decimals = parts.next().context("missing token decimals")?;
+        ensure!(
+            parts.next().is_none(),
+            "extraneous token info override parts"
+        );
+        let symbol = (!symbol.is_empty()).then(|| symbol.to_string());
+        let decimals = (!decimals.is_empty())
+            .then(|| decimals.parse())
+            .transpose()
+            .context("parsing token decimals")?;
+        ensure!(
+            symbol.is_some() || decimals.is_some(),
+            "token info override does not override anything"
+        );
+        Ok(Self {
+            address: address.parse().context("parsing token address")?,
+            symbol,
+            decimals,
+        })
+    }
+}
+
+impl Display for TokenInfoOverride {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}|{}|{}",
+            self.address,
+            self.symbol.as_deref().unwrap_or_default(),
+            self.decimals.map(|d| d.to_string()).unwrap_or_default()
+        )
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -41,6 +109,8 @@ pub trait TokenInfoFetching: Send + Sync {
 
 pub struct TokenInfoFetcher {
     pub web3: Web3,
+    /// Token infos that take precedence over the on-chain values.
+    pub overrides: Vec<TokenInfoOverride>,
 }
 
 impl TokenInfoFetcher {
@@ -49,6 +119,24 @@ impl TokenInfoFetcher {
             return Ok(TokenInfo {
                 decimals: Some(18),
                 symbol: Some("NATIVE_ASSET".to_string()),
+                source: TokenInfoSource::Erc20,
+            });
+        }
+
+        let overridden = self
+            .overrides
+            .iter()
+            .find(|overridden| overridden.address == address);
+        if let Some(TokenInfoOverride {
+            symbol: Some(symbol),
+            decimals: Some(decimals),
+            ..
+        }) = overridden
+        {
+            return Ok(TokenInfo {
+                decimals: Some(*decimals),
+                symbol: Some(symbol.clone()),
+                source: TokenInfoSource::Override,
             });
         }
 
@@ -58,10 +146,55 @@ impl TokenInfoFetcher {
             erc20.methods().symbol().call(),
         );
 
-        Ok(TokenInfo {
+        let mut info = TokenInfo {
             decimals: classify_error(decimals)?,
             symbol: classify_error(symbol)?,
-        })
+            source: TokenInfoSource::Erc20,
+        };
+        if info.symbol.is_none() {
+            // Some tokens (e.g. MKR) return their symbol as `bytes32`.
+            info.symbol = self
+                .call(address, "symbol()")
+                .await?
+                .as_deref()
+                .and_then(decode_bytes32_symbol);
+            if info.symbol.is_some() {
+                info.source = TokenInfoSource::Fallback;
+            }
+        }
+        if info.decimals.is_none() {
+            // Some old tokens only implement an upper case `DECIMALS()`.
+            info.decimals = self
+                .call(address, "DECIMALS()")
+                .await?
+                .as_deref()
+                .and_then(decode_decimals);
+            if info.decimals.is_some() {
+                info.source = TokenInfoSource::Fallback;
+            }
+        }
+        if let Some(overridden) = overridden {
+            info.symbol = overridden.symbol.clone().or(info.symbol);
+            info.decimals = overridden.decimals.or(info.decimals);
+            info.source = TokenInfoSource::Override;
+        }
+        Ok(info)
+    }
+
+    /// Calls a method without arguments and returns the raw return data or
+    /// `None` if the call reverted.
+    async fn call(&self, token: H160, signature: &str) -> Result<Option<Vec<u8>>, Error> {
+        let selector = web3::signing::keccak256(signature.as_bytes())[..4].to_vec();
+        let request = CallRequest {
+            to: Some(token),
+            data: Some(selector.into()),
+            ..Default::default()
+        };
+        match self.web3.eth().call(request, None).await {
+            Ok(data) => Ok(Some(data.0)),
+            Err(web3::Error::Rpc(_)) => Ok(None),
+            Err(err) => Err(Error(err.to_string())),
+        }
     }
 }
 
@@ -75,6 +208,28 @@ fn classify_error<T>(result: Result<T, MethodError>) -> Result<Option<T>, Error>
     }
 }
 
+/// Decodes a symbol that is returned as a null padded `bytes32`.
+fn decode_bytes32_symbol(data: &[u8]) -> Option<String> {
+    if data.len() != 32 {
+        return None;
+    }
+    let len = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    let symbol = std::str::from_utf8(&data[..len]).ok()?;
+    (!symbol.is_empty()).then(|| symbol.to_string())
+}
+
+/// Decodes decimals that are returned as a `uint256`.
+fn decode_decimals(data: &[u8]) -> Option<u8> {
+    if data.len() != 32 {
+        return None;
+    }
+    let decimals = U256::from_big_endian(data);
+    (decimals <= U256::from(u8::MAX)).then(|| decimals.low_u32() as u8)
+}
+
 #[async_trait]
 impl TokenInfoFetching for TokenInfoFetcher {
     async fn get_token_info(&self, address: H160) -> Result<TokenInfo, Error> {
@@ -101,46 +256,46 @@ impl TokenInfoFetching for TokenInfoFetcher {
     }
 }
 
-type SharedTokenInfo = Shared<BoxFuture<'static, Result<TokenInfo, Error>>>;
+/// Token infos never change so successfully fetched ones get cached forever.
+#[derive(Default)]
+struct TokenInfoCache(Mutex<HashMap<H160, TokenInfo>>);
+
+impl ResponseCache<H160, Result<TokenInfo, Error>> for TokenInfoCache {
+    fn get(&self, address: &H160) -> Option<Result<TokenInfo, Error>> {
+        self.0.lock().unwrap().get(address).cloned().map(Ok)
+    }
+
+    fn insert(&self, address: H160, info: &Result<TokenInfo, Error>) {
+        if let Ok(info) = info {
+            self.0.lock().unwrap().insert(address, info.clone());
+        }
+    }
+}
 
 pub struct CachedTokenInfoFetcher {
     inner: Arc<dyn TokenInfoFetching>,
-    cache: Arc<Mutex<HashMap<H160, SharedTokenInfo>>>,
+    requests: BoxRequestSharing<H160, Result<TokenInfo, Error>>,
 }
 
 impl CachedTokenInfoFetcher {
     pub fn new(inner: Arc<dyn TokenInfoFetching>) -> Self {
         Self {
             inner,
-            cache: Arc::new(Mutex::new(HashMap::new())),
+            requests: BoxRequestSharing::labelled("cached_token_info".into())
+                .with_cache(Arc::new(TokenInfoCache::default())),
         }
     }
 }
 
 impl CachedTokenInfoFetcher {
     async fn fetch_token(&self, address: H160) -> Result<TokenInfo, Error> {
-        let fetch = {
-            let mut cache = self.cache.lock().unwrap();
-            cache
-                .entry(address)
-                .or_insert({
-                    let inner = self.inner.clone();
-                    async move { inner.get_token_info(address).await }
-                        .boxed()
-                        .shared()
-                })
-                .clone()
-        };
-
-        let info = fetch.await;
-        if info.is_err() {
-            let mut cache = self.cache.lock().unwrap();
-            if let Some(Err(_)) = cache.get(&address).and_then(|fetch| fetch.peek()) {
-                cache.remove(&address);
-            }
-        }
-
-        info
+        self.requests
+            .cached_or_else(address, |address| {
+                let inner = self.inner.clone();
+                let address = *address;
+                async move { inner.get_token_info(address).await }.boxed()
+            })
+            .await
     }
 }
 
@@ -180,6 +335,7 @@ mod tests {
                 Ok(TokenInfo {
                     decimals: Some(18),
                     symbol: Some("CAT".to_string()),
+                    ..Default::default()
                 })
             });
         mock_token_info_fetcher
@@ -190,6 +346,7 @@ mod tests {
                 Ok(TokenInfo {
                     decimals: None,
                     symbol: None,
+                    ..Default::default()
                 })
             });
         mock_token_info_fetcher
@@ -210,10 +367,12 @@ mod tests {
                 address(0) => TokenInfo {
                     decimals: Some(18),
                     symbol: Some("CAT".to_string()),
+                    ..Default::default()
                 },
                 address(1) => TokenInfo {
                     decimals: None,
                     symbol: None,
+                    ..Default::default()
                 },
                 address(2) => TokenInfo::default(),
             }
@@ -226,4 +385,43 @@ mod tests {
         let cached_token_infos = cached_token_info_fetcher.get_token_infos(&addresses).await;
         assert_eq!(token_infos, cached_token_infos);
     }
+
+    #[test]
+    fn decodes_non_standard_values() {
+        let mut mkr = [0u8; 32];
+        mkr[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_bytes32_symbol(&mkr).as_deref(), Some("MKR"));
+        assert_eq!(decode_bytes32_symbol(&[0; 32]), None);
+        assert_eq!(decode_bytes32_symbol(&mkr[..31]), None);
+
+        let mut decimals = [0u8; 32];
+        decimals[31] = 18;
+        assert_eq!(decode_decimals(&decimals), Some(18));
+        decimals[30] = 1;
+        assert_eq!(decode_decimals(&decimals), None);
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let address = H160::from_low_u64_be(1);
+        assert_eq!(
+            format!("{address:?}|MKR|18")
+                .parse::<TokenInfoOverride>()
+                .unwrap(),
+            TokenInfoOverride {
+                address,
+                symbol: Some("MKR".to_string()),
+                decimals: Some(18),
+            }
+        );
+        let partial: TokenInfoOverride = format!("{address:?}||6").parse().unwrap();
+        assert_eq!(partial.symbol, None);
+        assert_eq!(partial.to_string(), format!("{address:?}||6"));
+        assert!(format!("{address:?}||")
+            .parse::<TokenInfoOverride>()
+            .is_err());
+        assert!(format!("{address:?}|MKR")
+            .parse::<TokenInfoOverride>()
+            .is_err());
+    }
 }
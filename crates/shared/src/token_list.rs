@@ -1,8 +1,9 @@
 use {
+    crate::http_cache::CachingClient,
     anyhow::Result,
     ethcontract::H160,
     prometheus::IntCounterVec,
-    reqwest::{Client, Url},
+    reqwest::Url,
     serde::Deserialize,
     std::{
         collections::HashSet,
@@ -16,7 +17,7 @@ use {
 pub struct TokenListConfiguration {
     pub url: Option<Url>,
     pub chain_id: u64,
-    pub client: Client,
+    pub client: CachingClient,
     pub update_interval: Duration,
     pub hardcoded: Vec<H160>,
 }
@@ -24,7 +25,7 @@ pub struct TokenListConfiguration {
 impl TokenListConfiguration {
     async fn get_external_list(&self) -> Result<HashSet<H160>> {
         let model: TokenListModel = if let Some(url) = &self.url {
-            self.client.get(url.clone()).send().await?.json().await?
+            self.client.get(url.clone()).await?.json()?
         } else {
             Default::default()
         };
@@ -47,8 +48,13 @@ pub struct AutoUpdatingTokenList {
 
 impl AutoUpdatingTokenList {
     pub async fn from_configuration(configuration: TokenListConfiguration) -> Self {
+        // The service is not ready before a token list was fetched once.
+        let warm = observe::readiness::register("token_list");
         let tokens = Arc::new(RwLock::new(match configuration.get_external_list().await {
-            Ok(tokens) => tokens,
+            Ok(tokens) => {
+                warm.ready();
+                tokens
+            }
             Err(err) => {
                 tracing::error!(?err, "failed to initialize token list");
                 Default::default()
@@ -66,6 +72,7 @@ impl AutoUpdatingTokenList {
 
                     match configuration.get_external_list().await {
                         Ok(new_tokens) => {
+                            warm.ready();
                             metrics
                                 .token_list_updates
                                 .with_label_values(&["success"])
@@ -83,7 +90,9 @@ impl AutoUpdatingTokenList {
                     }
                 }
             };
-            tokio::task::spawn(updater.instrument(tracing::info_span!("auto_updating_token_list")));
+            observe::metrics::spawn_in_current_scope(
+                updater.instrument(tracing::info_span!("auto_updating_token_list")),
+            );
         }
 
         Self { tokens }
@@ -19,6 +19,7 @@ use {
     anyhow::{anyhow, Context},
     ethrpc::block_stream::CurrentBlockWatcher,
     futures::FutureExt,
+    observe::distributed_tracing::{self, RequestBuilderExt},
     reqwest::{header, Client},
     url::Url,
 };
@@ -70,7 +71,7 @@ impl ExternalTradeFinder {
                 deadline: chrono::Utc::now() + self.timeout,
             };
             let block_dependent = query.block_dependent;
-            let id = observe::request_id::get_task_local_storage();
+            let context = distributed_tracing::Context::current();
             let timeout = self.timeout;
             let client = self.client.clone();
             let quote_endpoint = self.quote_endpoint.clone();
@@ -87,11 +88,8 @@ impl ExternalTradeFinder {
                     request = request.header("X-Current-Block-Hash", block_hash.to_string())
                 }
 
-                if let Some(id) = id {
-                    request = request.header("X-REQUEST-ID", id);
-                }
-
                 let response = request
+                    .with_tracing_context(&context)
                     .timeout(timeout)
                     .send()
                     .await
@@ -11,6 +11,8 @@ use {
     ethcontract::{H160, H256, U256},
     ethrpc::block_stream::{BlockInfo, CurrentBlockWatcher},
     number::serialization::HexOrDecimalU256,
+    observe::distributed_tracing::RequestBuilderExt,
+    rate_limit::{Middleware, RateLimiterRegistry, SendError, Strategy},
     reqwest::{
         header::{HeaderMap, HeaderValue},
         Client,
@@ -225,7 +227,7 @@ pub trait ZeroExApi: Send + Sync {
 /// 0x API Client implementation.
 #[derive(Debug)]
 pub struct DefaultZeroExApi {
-    client: Client,
+    client: Middleware,
     base_url: Url,
     block_stream: CurrentBlockWatcher,
 }
@@ -258,12 +260,21 @@ impl DefaultZeroExApi {
         };
 
         Ok(Self {
-            client: client_builder.build().unwrap(),
+            client: Middleware::new(client_builder.build().unwrap()),
             base_url: base_url.into_url().context("zeroex api url")?,
             block_stream,
         })
     }
 
+    /// Rate limits all requests to the 0x API with the given strategy.
+    pub fn with_rate_limiter(mut self, strategy: Strategy) -> Result<Self> {
+        if let Some(host) = self.base_url.host_str() {
+            let limiter = RateLimiterRegistry::global().get_or_create("zeroex", strategy)?;
+            self.client = self.client.with_rate_limiter(host, limiter);
+        }
+        Ok(self)
+    }
+
     /// Create a 0x HTTP API client for testing using the default HTTP client.
     ///
     /// This method will attempt to read the `ZEROEX_URL` (falling back to the
@@ -392,11 +403,12 @@ impl DefaultZeroExApi {
                     self.block_stream.borrow().hash.to_string(),
                 );
             };
-            if let Some(id) = observe::request_id::get_task_local_storage() {
-                request = request.header("X-REQUEST-ID", id);
-            }
+            request = request.with_tracing_headers();
 
-            let response = request.send().await.map_err(ZeroExResponseError::Send)?;
+            let response = self.client.send(request).await.map_err(|err| match err {
+                SendError::RateLimited(_) => ZeroExResponseError::RateLimited,
+                SendError::Request(err) => ZeroExResponseError::Send(err),
+            })?;
 
             let status = response.status();
             let response_text = response
@@ -92,7 +92,8 @@ impl BalancerV2Liquidity {
             .into_iter()
             .map(|pool| StablePoolOrder {
                 address: pool.common.address,
-                reserves: pool.reserves,
+                // Composable stable pools can't be used to swap their own BPT.
+                reserves: pool.reserves_without_bpt().collect(),
                 fee: pool.common.swap_fee,
                 amplification_parameter: pool.amplification_parameter,
                 settlement_handling: Arc::new(SettlementHandler {
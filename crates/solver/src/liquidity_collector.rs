@@ -48,7 +48,8 @@ impl LiquidityCollecting for LiquidityCollector {
 /// A liquidity source which might not be initialised on creation. Instead
 /// initialisation gets retried in a background task over and over until it
 /// succeeds. Until the liquidity source has been initialised no liquidity will
-/// be provided.
+/// be provided. The source registers a readiness condition named
+/// `liquidity_<label>` which is met once it got initialised.
 pub struct BackgroundInitLiquiditySource<L> {
     liquidity_source: Arc<OnceCell<L>>,
 }
@@ -66,6 +67,7 @@ impl<L> BackgroundInitLiquiditySource<L> {
             .liquidity_enabled
             .with_label_values(&[label])
             .set(0);
+        let warm = observe::readiness::register(&format!("liquidity_{label}"));
         let liquidity_source = Arc::new(OnceCell::new());
         let inner = liquidity_source.clone();
         let inner_label = label.to_owned();
@@ -93,6 +95,7 @@ impl<L> BackgroundInitLiquiditySource<L> {
                                     .liquidity_enabled
                                     .with_label_values(&[&inner_label])
                                     .inc();
+                                warm.ready();
                             }
 
                             break;
@@ -185,6 +188,8 @@ mod test {
             .liquidity_enabled
             .with_label_values(&["fake"]);
         assert_eq!(gauge.get(), 0);
+        let ready = || observe::readiness::gate().report().conditions["liquidity_fake"].ready;
+        assert!(!ready());
 
         let liquidity = source
             .get_liquidity(Default::default(), Block::Recent)
@@ -203,5 +208,6 @@ mod test {
             .await;
         assert_eq!(liquidity.unwrap_err().to_string(), "I am initialised");
         assert_eq!(gauge.get(), 1);
+        assert!(ready());
     }
 }
@@ -48,6 +48,15 @@ pub enum Kind {
     NonBufferableTokensUsed {
         tokens: BTreeSet<H160>,
     },
+    /// The token is absent if the limit of the whole settlement was exceeded
+    /// in which case the amounts are denominated in wei.
+    BufferLimitExceeded {
+        token: Option<H160>,
+        #[serde_as(as = "HexOrDecimalU256")]
+        used: U256,
+        #[serde_as(as = "HexOrDecimalU256")]
+        limit: U256,
+    },
     SolverAccountInsufficientBalance {
         #[serde_as(as = "HexOrDecimalU256")]
         required: U256,
@@ -4,13 +4,28 @@ use {
     serde::Serialize,
     serde_with::serde_as,
     std::collections::HashMap,
-    web3::types::{H160, U256},
+    web3::types::{H160, H256, U256},
 };
 
 #[derive(Debug, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Solutions {
     pub solutions: Vec<Solution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+}
+
+/// Milliseconds the solver engine spent in the different phases of solving
+/// the auction.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidity_processing: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimization: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<u64>,
 }
 
 #[serde_as]
@@ -28,6 +43,25 @@ pub struct Solution {
     pub post_interactions: Vec<Call>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transfer_authorizations: Vec<TransferAuthorization>,
+}
+
+/// A signed ERC-3009 `receiveWithAuthorization` call.
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferAuthorization {
+    pub token: H160,
+    pub from: H160,
+    pub to: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub value: U256,
+    pub valid_after: u64,
+    pub valid_before: u64,
+    pub nonce: H256,
+    #[serde_as(as = "serialize::Hex")]
+    pub signature: Vec<u8>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -35,6 +69,7 @@ pub struct Solution {
 pub enum Trade {
     Fulfillment(Fulfillment),
     Jit(JitTrade),
+    CowAmm(CowAmmTrade),
 }
 
 #[serde_as]
@@ -61,6 +96,24 @@ pub struct JitTrade {
     pub fee: Option<U256>,
 }
 
+/// Trade with a CoW AMM at the exchange rate implied by `sell_amount` and
+/// `buy_amount`. The driver generates the corresponding JIT order, its
+/// signature and the required interactions using the AMM's helper contract.
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CowAmmTrade {
+    pub amm: H160,
+    /// The token sold by the CoW AMM.
+    pub sell_token: H160,
+    /// The token bought by the CoW AMM.
+    pub buy_token: H160,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub sell_amount: U256,
+    #[serde_as(as = "HexOrDecimalU256")]
+    pub buy_amount: U256,
+}
+
 #[serde_as]
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
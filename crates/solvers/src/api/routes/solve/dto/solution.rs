@@ -1,10 +1,12 @@
 use {
     crate::domain::{eth, order, solution},
     solvers_dto::solution::*,
+    std::time::Duration,
 };
 
-/// Creates a new solution DTO from its domain object.
-pub fn from_domain(solutions: &[solution::Solution]) -> super::Solutions {
+/// Creates a new solution DTO from its domain object. `solving_time` is the
+/// time it took to compute the solutions.
+pub fn from_domain(solutions: &[solution::Solution], solving_time: Duration) -> super::Solutions {
     super::Solutions {
         solutions: solutions
             .iter()
@@ -115,8 +117,13 @@ pub fn from_domain(solutions: &[solution::Solution]) -> super::Solutions {
                     })
                     .collect(),
                 gas: solution.gas.map(|gas| gas.0.as_u64()),
+                transfer_authorizations: Default::default(),
             })
             .collect(),
+        timings: Some(Timings {
+            optimization: Some(solving_time.as_millis().try_into().unwrap_or(u64::MAX)),
+            ..Default::default()
+        }),
     }
 }
 
@@ -24,6 +24,7 @@ pub async fn solve(
         };
 
         let auction_id = auction.id;
+        let start = std::time::Instant::now();
         let solutions = state
             .solve(auction)
             .instrument(tracing::info_span!("auction", id = %auction_id))
@@ -31,7 +32,7 @@ pub async fn solve(
 
         tracing::trace!(?auction_id, ?solutions);
 
-        let solutions = dto::solution::from_domain(&solutions);
+        let solutions = dto::solution::from_domain(&solutions, start.elapsed());
         (
             axum::http::StatusCode::OK,
             axum::response::Json(Response::Ok(solutions)),
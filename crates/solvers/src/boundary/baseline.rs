@@ -33,13 +33,28 @@ impl<'a> Solver<'a> {
         }
     }
 
-    pub fn route(&self, request: solver::Request, max_hops: usize) -> Option<solver::Route<'a>> {
+    /// Finds the best route for the request. `gas_cost` converts an amount of
+    /// gas into its cost in the sell token and is used to decide whether
+    /// splitting the order is worth the additional gas.
+    pub fn route(
+        &self,
+        request: solver::Request,
+        max_hops: usize,
+        max_splits: usize,
+        gas_cost: impl Fn(usize) -> U256,
+    ) -> Option<solver::Route<'a>> {
         let candidates = self.base_tokens.path_candidates_with_hops(
             request.sell.token.0,
             request.buy.token.0,
             max_hops,
         );
 
+        if max_splits > 1 {
+            if let Some(route) = self.split_route(&request, &candidates, max_splits, gas_cost) {
+                return Some(route);
+            }
+        }
+
         let (segments, _) = match request.side {
             order::Side::Buy => candidates
                 .iter()
@@ -94,6 +109,71 @@ impl<'a> Solver<'a> {
         solver::Route::new(segments)
     }
 
+    /// Finds a route splitting the traded amount over multiple paths. Falls
+    /// back to a single path if splitting the order does not improve its
+    /// price by more than the cost of the additional gas.
+    fn split_route(
+        &self,
+        request: &solver::Request,
+        candidates: &HashSet<Vec<H160>>,
+        max_splits: usize,
+        gas_cost: impl Fn(usize) -> U256,
+    ) -> Option<solver::Route<'a>> {
+        let paths = match request.side {
+            order::Side::Buy => {
+                let sell = baseline_solver::estimate_sell_amount_split(
+                    request.buy.amount,
+                    candidates,
+                    &self.onchain_liquidity,
+                    max_splits,
+                    &gas_cost,
+                )?;
+                if sell.value > request.sell.amount {
+                    return None;
+                }
+                sell.parts
+                    .iter()
+                    .map(|(_, part)| {
+                        self.traverse_path(&part.path, request.sell.token.0, part.value)
+                    })
+                    .collect::<Option<Vec<_>>>()?
+            }
+            order::Side::Sell => {
+                let buy = baseline_solver::estimate_buy_amount_split(
+                    request.sell.amount,
+                    candidates,
+                    &self.onchain_liquidity,
+                    max_splits,
+                    &gas_cost,
+                )?;
+                if buy.value < request.buy.amount {
+                    return None;
+                }
+                buy.parts
+                    .iter()
+                    .map(|(amount, part)| {
+                        self.traverse_path(&part.path, request.sell.token.0, *amount)
+                    })
+                    .collect::<Option<Vec<_>>>()?
+            }
+        };
+
+        let route = solver::Route::split(paths)?;
+        let covered = match request.side {
+            order::Side::Buy => route.output().amount >= request.buy.amount,
+            order::Side::Sell => route.input().amount >= request.sell.amount,
+        };
+        if !covered {
+            tracing::warn!(
+                ?request,
+                ?route,
+                "invalid split estimate does not cover order"
+            );
+            return None;
+        }
+        Some(route)
+    }
+
     fn traverse_path(
         &self,
         path: &[&OnchainLiquidity],
@@ -192,6 +272,20 @@ fn to_boundary_liquidity(
                         }
                     }
                 }
+                liquidity::State::Concentrated(pool) => {
+                    if let Some(boundary_pool) =
+                        boundary::liquidity::concentrated::to_boundary_pool(pool, liquidity.gas)
+                    {
+                        onchain_liquidity
+                            .entry(boundary_pool.tokens)
+                            .or_default()
+                            .push(OnchainLiquidity {
+                                id: liquidity.id.clone(),
+                                token_pair: boundary_pool.tokens,
+                                source: LiquiditySource::Concentrated(boundary_pool),
+                            });
+                    }
+                }
                 liquidity::State::LimitOrder(limit_order) => {
                     if let Some(token_pair) =
                         TokenPair::new(limit_order.maker.token.0, limit_order.taker.token.0)
@@ -206,8 +300,6 @@ fn to_boundary_liquidity(
                             })
                     }
                 }
-                // The baseline solver does not currently support other AMMs.
-                _ => {}
             };
             onchain_liquidity
         })
@@ -225,6 +317,7 @@ enum LiquiditySource {
     ConstantProduct(boundary::liquidity::constant_product::Pool),
     WeightedProduct(boundary::liquidity::weighted_product::Pool),
     Stable(boundary::liquidity::stable::Pool),
+    Concentrated(boundary::liquidity::concentrated::Pool),
     LimitOrder(liquidity::limit_order::LimitOrder),
 }
 
@@ -234,6 +327,7 @@ impl BaselineSolvable for OnchainLiquidity {
             LiquiditySource::ConstantProduct(pool) => pool.get_amount_out(out_token, input),
             LiquiditySource::WeightedProduct(pool) => pool.get_amount_out(out_token, input),
             LiquiditySource::Stable(pool) => pool.get_amount_out(out_token, input),
+            LiquiditySource::Concentrated(pool) => pool.get_amount_out(out_token, input),
             LiquiditySource::LimitOrder(limit_order) => {
                 limit_order.get_amount_out(out_token, input)
             }
@@ -245,6 +339,7 @@ impl BaselineSolvable for OnchainLiquidity {
             LiquiditySource::ConstantProduct(pool) => pool.get_amount_in(in_token, out),
             LiquiditySource::WeightedProduct(pool) => pool.get_amount_in(in_token, out),
             LiquiditySource::Stable(pool) => pool.get_amount_in(in_token, out),
+            LiquiditySource::Concentrated(pool) => pool.get_amount_in(in_token, out),
             LiquiditySource::LimitOrder(limit_order) => limit_order.get_amount_in(in_token, out),
         }
     }
@@ -254,6 +349,7 @@ impl BaselineSolvable for OnchainLiquidity {
             LiquiditySource::ConstantProduct(pool) => pool.gas_cost(),
             LiquiditySource::WeightedProduct(pool) => pool.gas_cost(),
             LiquiditySource::Stable(pool) => pool.gas_cost(),
+            LiquiditySource::Concentrated(pool) => pool.gas_cost(),
             LiquiditySource::LimitOrder(limit_order) => limit_order.gas_cost(),
         }
     }
@@ -0,0 +1,534 @@
+//! Uniswap V3 swap math for concentrated liquidity pools.
+//!
+//! This is a port of the `TickMath`, `SqrtPriceMath` and `SwapMath` libraries
+//! of the Uniswap V3 core contracts, so that amounts computed here match the
+//! amounts the pool computes on-chain, rounding included.
+
+use {
+    crate::domain::{eth, liquidity},
+    ethereum_types::{H160, U256, U512},
+    model::TokenPair,
+    shared::baseline_solver::BaselineSolvable,
+    std::{collections::BTreeMap, ops::Bound},
+};
+
+/// A concentrated liquidity pool in a form that the [`shared`] baseline solver
+/// can route through.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub tokens: TokenPair,
+    pub sqrt_price: U256,
+    pub liquidity: u128,
+    pub tick: i32,
+    pub liquidity_net: BTreeMap<i32, i128>,
+    /// The fee in hundredths of a basis point, like the on-chain contract.
+    pub fee: u32,
+    pub gas: usize,
+}
+
+/// Converts a domain pool into a boundary pool. Returns `None` if the domain
+/// pool cannot be represented as a boundary pool.
+pub fn to_boundary_pool(pool: &liquidity::concentrated::Pool, gas: eth::Gas) -> Option<Pool> {
+    let (token0, token1) = pool.tokens.get();
+    let tokens = TokenPair::new(token0.0, token1.0).expect("tokens are distinct by construction");
+
+    // Fees are expressed in hundredths of a basis point on-chain, so only
+    // accept fees that can be represented exactly.
+    let pips = pool.fee.0.numer().checked_mul(FEE_DENOMINATOR.into())?;
+    if pool.fee.0.denom().is_zero() || !(pips % pool.fee.0.denom()).is_zero() {
+        return None;
+    }
+    let fee = pips / pool.fee.0.denom();
+    if fee >= FEE_DENOMINATOR.into() {
+        return None;
+    }
+
+    if pool.sqrt_price.0 < MIN_SQRT_RATIO.into() || pool.sqrt_price.0 >= max_sqrt_ratio() {
+        return None;
+    }
+
+    Some(Pool {
+        tokens,
+        sqrt_price: pool.sqrt_price.0,
+        liquidity: pool.liquidity.0,
+        tick: pool.tick.0,
+        liquidity_net: pool
+            .liquidity_net
+            .iter()
+            .map(|(tick, net)| (tick.0, net.0))
+            .collect(),
+        fee: fee.as_u32(),
+        gas: gas.0.try_into().unwrap_or(usize::MAX),
+    })
+}
+
+impl BaselineSolvable for Pool {
+    fn get_amount_out(&self, out_token: H160, (in_amount, in_token): (U256, H160)) -> Option<U256> {
+        let zero_for_one = self.direction(in_token, out_token)?;
+        let (amount_in, amount_out) = self.swap(zero_for_one, Amount::ExactIn(in_amount))?;
+        (amount_in == in_amount).then_some(amount_out)
+    }
+
+    fn get_amount_in(&self, in_token: H160, (out_amount, out_token): (U256, H160)) -> Option<U256> {
+        let zero_for_one = self.direction(in_token, out_token)?;
+        let (amount_in, amount_out) = self.swap(zero_for_one, Amount::ExactOut(out_amount))?;
+        (amount_out == out_amount).then_some(amount_in)
+    }
+
+    fn gas_cost(&self) -> usize {
+        self.gas
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Amount {
+    ExactIn(U256),
+    ExactOut(U256),
+}
+
+impl Pool {
+    /// Returns whether swapping `in_token` for `out_token` sells token 0 of the
+    /// pool.
+    fn direction(&self, in_token: H160, out_token: H160) -> Option<bool> {
+        let (token0, token1) = self.tokens.get();
+        match (in_token, out_token) {
+            (a, b) if a == token0 && b == token1 => Some(true),
+            (a, b) if a == token1 && b == token0 => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Simulates `UniswapV3Pool.swap` and returns the input and output amounts
+    /// of the swap. Returns `None` if the amount can't be swapped completely
+    /// with the liquidity the pool is known to have.
+    fn swap(&self, zero_for_one: bool, amount: Amount) -> Option<(U256, U256)> {
+        let specified = match amount {
+            Amount::ExactIn(amount) | Amount::ExactOut(amount) => amount,
+        };
+        // The on-chain contract takes the amount as an `int256`.
+        if specified.is_zero() || specified.bit(255) {
+            return None;
+        }
+        let exact_in = matches!(amount, Amount::ExactIn(_));
+
+        let mut remaining = specified;
+        let mut calculated = U256::zero();
+        let mut sqrt_price = self.sqrt_price;
+        let mut tick = self.tick;
+        let mut liquidity = self.liquidity;
+
+        while !remaining.is_zero() {
+            // Unlike on-chain, there is no full range of ticks to fall back to
+            // when all known initialized ticks are crossed. Bail instead of
+            // assuming that the current liquidity extends indefinitely.
+            let (tick_next, liquidity_net) = if zero_for_one {
+                self.liquidity_net.range(..=tick).next_back()?
+            } else {
+                self.liquidity_net
+                    .range((Bound::Excluded(tick), Bound::Unbounded))
+                    .next()?
+            };
+            let sqrt_price_next = sqrt_ratio_at_tick(*tick_next)?;
+
+            let step = swap_step(
+                sqrt_price,
+                sqrt_price_next,
+                liquidity,
+                remaining,
+                exact_in,
+                self.fee,
+            )?;
+            sqrt_price = step.sqrt_price;
+            if exact_in {
+                remaining = remaining.checked_sub(step.amount_in.checked_add(step.fee_amount)?)?;
+                calculated = calculated.checked_add(step.amount_out)?;
+            } else {
+                remaining = remaining.checked_sub(step.amount_out)?;
+                calculated = calculated
+                    .checked_add(step.amount_in)?
+                    .checked_add(step.fee_amount)?;
+            }
+
+            if sqrt_price != sqrt_price_next {
+                // The step didn't reach the next tick, so the whole remaining
+                // amount was swapped.
+                break;
+            }
+            let liquidity_net = if zero_for_one {
+                liquidity_net.checked_neg()?
+            } else {
+                *liquidity_net
+            };
+            liquidity = if liquidity_net < 0 {
+                liquidity.checked_sub(liquidity_net.unsigned_abs())?
+            } else {
+                liquidity.checked_add(liquidity_net.unsigned_abs())?
+            };
+            tick = if zero_for_one {
+                tick_next - 1
+            } else {
+                *tick_next
+            };
+        }
+
+        let swapped = specified - remaining;
+        Some(match exact_in {
+            true => (swapped, calculated),
+            false => (calculated, swapped),
+        })
+    }
+}
+
+const FEE_DENOMINATOR: u32 = 1_000_000;
+const MIN_TICK: i32 = -887272;
+const MAX_TICK: i32 = -MIN_TICK;
+const MIN_SQRT_RATIO: u64 = 4295128739;
+const RESOLUTION: usize = 96;
+
+fn max_sqrt_ratio() -> U256 {
+    U256::from_dec_str("1461446703485210103287273052203988822378723970342").unwrap()
+}
+
+fn q96() -> U256 {
+    U256::one() << RESOLUTION
+}
+
+/// `TickMath.getSqrtRatioAtTick`
+fn sqrt_ratio_at_tick(tick: i32) -> Option<U256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+    let abs_tick = tick.unsigned_abs();
+
+    // Multipliers `2^128 / sqrt(1.0001)^(2^i)` for every bit `i` of the tick.
+    const FACTORS: [u128; 20] = [
+        0xfffcb933bd6fad37aa2d162d1a594001,
+        0xfff97272373d413259a46990580e213a,
+        0xfff2e50f5f656932ef12357cf3c7fdcc,
+        0xffe5caca7e10e4e61c3624eaa0941cd0,
+        0xffcb9843d60f6159c9db58835c926644,
+        0xff973b41fa98c081472e6896dfb254c0,
+        0xff2ea16466c96a3843ec78b326b52861,
+        0xfe5dee046a99a2a811c461f1969c3053,
+        0xfcbe86c7900a88aedcffc83b479aa3a4,
+        0xf987a7253ac413176f2b074cf7815e54,
+        0xf3392b0822b70005940c7a398e4b70f3,
+        0xe7159475a2c29b7443b29c7fa6e889d9,
+        0xd097f3bdfd2022b8845ad8f792aa5825,
+        0xa9f746462d870fdf8a65dc1f90e061e5,
+        0x70d869a156d2a1b890bb3df62baf32f7,
+        0x31be135f97d08fd981231505542fcfa6,
+        0x9aa508b5b7a84e1c677de54f3e99bc9,
+        0x5d6af8dedb81196699c329225ee604,
+        0x2216e584f5fa1ea926041bedfe98,
+        0x48a170391f7dc42444e8fa2,
+    ];
+
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(FACTORS[0])
+    } else {
+        U256::one() << 128
+    };
+    for (bit, factor) in FACTORS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Round up to make sure that the price at a tick is never less than the
+    // exact ratio.
+    let rounding = if (ratio & U256::from(u32::MAX)).is_zero() {
+        U256::zero()
+    } else {
+        U256::one()
+    };
+    Some((ratio >> 32) + rounding)
+}
+
+struct Step {
+    sqrt_price: U256,
+    amount_in: U256,
+    amount_out: U256,
+    fee_amount: U256,
+}
+
+/// `SwapMath.computeSwapStep`
+fn swap_step(
+    sqrt_price: U256,
+    sqrt_price_target: U256,
+    liquidity: u128,
+    remaining: U256,
+    exact_in: bool,
+    fee: u32,
+) -> Option<Step> {
+    let zero_for_one = sqrt_price >= sqrt_price_target;
+    let fee_complement = U256::from(FEE_DENOMINATOR - fee);
+
+    let (next, amount_in, amount_out);
+    if exact_in {
+        let remaining_less_fee = mul_div(remaining, fee_complement, FEE_DENOMINATOR.into())?;
+        let max_in = if zero_for_one {
+            amount0_delta(sqrt_price_target, sqrt_price, liquidity, true)?
+        } else {
+            amount1_delta(sqrt_price, sqrt_price_target, liquidity, true)?
+        };
+        next = if remaining_less_fee >= max_in {
+            sqrt_price_target
+        } else {
+            next_sqrt_price_from_input(sqrt_price, liquidity, remaining_less_fee, zero_for_one)?
+        };
+        let max = next == sqrt_price_target;
+        amount_in = match (max, zero_for_one) {
+            (true, _) => max_in,
+            (false, true) => amount0_delta(next, sqrt_price, liquidity, true)?,
+            (false, false) => amount1_delta(sqrt_price, next, liquidity, true)?,
+        };
+        amount_out = if zero_for_one {
+            amount1_delta(next, sqrt_price, liquidity, false)?
+        } else {
+            amount0_delta(sqrt_price, next, liquidity, false)?
+        };
+    } else {
+        let max_out = if zero_for_one {
+            amount1_delta(sqrt_price_target, sqrt_price, liquidity, false)?
+        } else {
+            amount0_delta(sqrt_price, sqrt_price_target, liquidity, false)?
+        };
+        next = if remaining >= max_out {
+            sqrt_price_target
+        } else {
+            next_sqrt_price_from_output(sqrt_price, liquidity, remaining, zero_for_one)?
+        };
+        let max = next == sqrt_price_target;
+        amount_in = if zero_for_one {
+            amount0_delta(next, sqrt_price, liquidity, true)?
+        } else {
+            amount1_delta(sqrt_price, next, liquidity, true)?
+        };
+        amount_out = match (max, zero_for_one) {
+            (true, _) => max_out,
+            (false, true) => amount1_delta(next, sqrt_price, liquidity, false)?,
+            (false, false) => amount0_delta(sqrt_price, next, liquidity, false)?,
+        }
+        // The output can't exceed the remaining amount because of rounding.
+        .min(remaining);
+    }
+
+    let fee_amount = if exact_in && next != sqrt_price_target {
+        remaining.checked_sub(amount_in)?
+    } else {
+        mul_div_rounding_up(amount_in, fee.into(), fee_complement)?
+    };
+
+    Some(Step {
+        sqrt_price: next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// `SqrtPriceMath.getAmount0Delta`
+fn amount0_delta(a: U256, b: U256, liquidity: u128, round_up: bool) -> Option<U256> {
+    let (lower, upper) = if a > b { (b, a) } else { (a, b) };
+    if lower.is_zero() {
+        return None;
+    }
+    let numerator1 = U256::from(liquidity) << RESOLUTION;
+    let numerator2 = upper - lower;
+    if round_up {
+        Some(div_rounding_up(
+            mul_div_rounding_up(numerator1, numerator2, upper)?,
+            lower,
+        ))
+    } else {
+        Some(mul_div(numerator1, numerator2, upper)? / lower)
+    }
+}
+
+/// `SqrtPriceMath.getAmount1Delta`
+fn amount1_delta(a: U256, b: U256, liquidity: u128, round_up: bool) -> Option<U256> {
+    let (lower, upper) = if a > b { (b, a) } else { (a, b) };
+    if round_up {
+        mul_div_rounding_up(liquidity.into(), upper - lower, q96())
+    } else {
+        mul_div(liquidity.into(), upper - lower, q96())
+    }
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromInput`
+fn next_sqrt_price_from_input(
+    sqrt_price: U256,
+    liquidity: u128,
+    amount: U256,
+    zero_for_one: bool,
+) -> Option<U256> {
+    if zero_for_one {
+        next_sqrt_price_from_amount0_rounding_up(sqrt_price, liquidity, amount, true)
+    } else {
+        next_sqrt_price_from_amount1_rounding_down(sqrt_price, liquidity, amount, true)
+    }
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromOutput`
+fn next_sqrt_price_from_output(
+    sqrt_price: U256,
+    liquidity: u128,
+    amount: U256,
+    zero_for_one: bool,
+) -> Option<U256> {
+    if zero_for_one {
+        next_sqrt_price_from_amount1_rounding_down(sqrt_price, liquidity, amount, false)
+    } else {
+        next_sqrt_price_from_amount0_rounding_up(sqrt_price, liquidity, amount, false)
+    }
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromAmount0RoundingUp`
+fn next_sqrt_price_from_amount0_rounding_up(
+    sqrt_price: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Option<U256> {
+    if amount.is_zero() {
+        return Some(sqrt_price);
+    }
+    if liquidity == 0 {
+        return None;
+    }
+    let numerator1 = U256::from(liquidity) << RESOLUTION;
+    let product = amount.checked_mul(sqrt_price);
+    let next = if add {
+        match product.and_then(|product| numerator1.checked_add(product)) {
+            Some(denominator) => mul_div_rounding_up(numerator1, sqrt_price, denominator)?,
+            None => div_rounding_up(numerator1, (numerator1 / sqrt_price).checked_add(amount)?),
+        }
+    } else {
+        let denominator = numerator1.checked_sub(product?).filter(|d| !d.is_zero())?;
+        mul_div_rounding_up(numerator1, sqrt_price, denominator)?
+    };
+    to_sqrt_price(next)
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromAmount1RoundingDown`
+fn next_sqrt_price_from_amount1_rounding_down(
+    sqrt_price: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Option<U256> {
+    if liquidity == 0 {
+        return None;
+    }
+    let fits_u160 = amount.bits() <= 160;
+    if add {
+        let quotient = if fits_u160 {
+            (amount << RESOLUTION) / U256::from(liquidity)
+        } else {
+            mul_div(amount, q96(), liquidity.into())?
+        };
+        to_sqrt_price(sqrt_price.checked_add(quotient)?)
+    } else {
+        let quotient = if fits_u160 {
+            div_rounding_up(amount << RESOLUTION, liquidity.into())
+        } else {
+            mul_div_rounding_up(amount, q96(), liquidity.into())?
+        };
+        sqrt_price
+            .checked_sub(quotient)
+            .filter(|next| !next.is_zero())
+    }
+}
+
+/// Sqrt prices are `uint160` on-chain.
+fn to_sqrt_price(value: U256) -> Option<U256> {
+    (value.bits() <= 160).then_some(value)
+}
+
+/// `FullMath.mulDiv`
+fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    (a.full_mul(b) / U512::from(denominator)).try_into().ok()
+}
+
+/// `FullMath.mulDivRoundingUp`
+fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let (quotient, remainder) = a.full_mul(b).div_mod(U512::from(denominator));
+    let quotient = U256::try_from(quotient).ok()?;
+    if remainder.is_zero() {
+        Some(quotient)
+    } else {
+        quotient.checked_add(U256::one())
+    }
+}
+
+/// `UnsafeMath.divRoundingUp`
+fn div_rounding_up(a: U256, b: U256) -> U256 {
+    let (quotient, remainder) = a.div_mod(b);
+    if remainder.is_zero() {
+        quotient
+    } else {
+        quotient + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_ratio_at_tick_bounds() {
+        assert_eq!(sqrt_ratio_at_tick(0), Some(q96()));
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK), Some(MIN_SQRT_RATIO.into()));
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK), Some(max_sqrt_ratio()));
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK - 1), None);
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK + 1), None);
+    }
+
+    #[test]
+    fn swaps_across_ticks() {
+        let token0 = H160([1; 20]);
+        let token1 = H160([2; 20]);
+        let liquidity = 10_i128.pow(21);
+        // Two positions around the current price, `[-60, 60]` and
+        // `[-600, 600]`, both with the same liquidity.
+        let pool = Pool {
+            tokens: TokenPair::new(token0, token1).unwrap(),
+            sqrt_price: q96(),
+            liquidity: 2 * liquidity.unsigned_abs(),
+            tick: 0,
+            liquidity_net: [
+                (-600, liquidity),
+                (-60, liquidity),
+                (60, -liquidity),
+                (600, -liquidity),
+            ]
+            .into_iter()
+            .collect(),
+            fee: 3000,
+            gas: 110_000,
+        };
+
+        let sell = U256::exp10(19);
+        let buy = U256::from_dec_str("9912816306615181423").unwrap();
+        assert_eq!(pool.get_amount_out(token1, (sell, token0)), Some(buy));
+        assert_eq!(pool.get_amount_in(token0, (buy, token1)), Some(sell));
+
+        assert_eq!(
+            pool.get_amount_in(token1, (U256::exp10(19), token0)),
+            Some(U256::from_dec_str("10088676295547292704").unwrap()),
+        );
+
+        // Swapping past the last known initialized tick is not supported.
+        assert_eq!(pool.get_amount_out(token1, (U256::exp10(22), token0)), None);
+    }
+}
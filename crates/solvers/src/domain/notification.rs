@@ -38,6 +38,7 @@ pub enum Kind {
     SimulationFailed(BlockNo, Transaction, SimulationSucceededAtLeastOnce),
     ScoringFailed(ScoreKind),
     NonBufferableTokensUsed(TokensUsed),
+    BufferLimitExceeded(BufferLimit),
     SolverAccountInsufficientBalance(RequiredEther),
     Settled(Settlement),
     DriverError(String),
@@ -53,6 +54,21 @@ pub enum Settlement {
     Fail,
 }
 
+/// A limit on the usage of the settlement contract's buffers a solution
+/// exceeded.
+#[derive(Debug)]
+pub enum BufferLimit {
+    Token {
+        token: TokenAddress,
+        used: eth::U256,
+        limit: eth::U256,
+    },
+    Settlement {
+        used: Ether,
+        limit: Ether,
+    },
+}
+
 #[derive(Debug)]
 pub enum ScoreKind {
     InvalidClearingPrices,
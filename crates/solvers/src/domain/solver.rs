@@ -1,9 +1,10 @@
 //! "Baseline" solver implementation.
 //!
 //! The baseline solver is a simple solver implementation that finds the best
-//! path of at most length `max_hops + 1` over a set of on-chain liquidity. It
-//! **does not** try to split large orders into multiple parts and route them
-//! over separate paths.
+//! path of at most length `max_hops + 1` over a set of on-chain liquidity.
+//! Large orders can additionally be split into parts routed over up to
+//! `max_splits` paths that don't share any liquidity, if that results in a
+//! better price.
 
 use {
     crate::{
@@ -31,6 +32,7 @@ pub struct Config {
     pub weth: eth::WethAddress,
     pub base_tokens: Vec<eth::TokenAddress>,
     pub max_hops: usize,
+    pub max_splits: usize,
     pub max_partial_attempts: usize,
     pub solution_gas_offset: eth::SignedGas,
     pub native_token_price_estimation_amount: eth::U256,
@@ -53,6 +55,10 @@ struct Inner {
     /// - etc.
     max_hops: usize,
 
+    /// The maximum number of paths the traded amount of an order can be split
+    /// over. A value of 1 disables splitting.
+    max_splits: usize,
+
     /// The maximum number of attempts to solve a partially fillable order.
     /// Basically we continuously halve the amount to execute until we find a
     /// valid solution or exceed this count.
@@ -74,6 +80,7 @@ impl Solver {
             weth: config.weth,
             base_tokens: config.base_tokens.into_iter().collect(),
             max_hops: config.max_hops,
+            max_splits: config.max_splits,
             max_partial_attempts: config.max_partial_attempts,
             solution_gas_offset: config.solution_gas_offset,
             native_token_price_estimation_amount: config.native_token_price_estimation_amount,
@@ -140,8 +147,14 @@ impl Inner {
                 None => {
                     // Estimate the price of the sell token in the native token
                     let native_price_request = self.native_price_request(&order);
-                    if let Some(route) = boundary_solver.route(native_price_request, self.max_hops)
-                    {
+                    // The sell token price is what is being estimated, so the
+                    // gas of splitting can't be priced yet.
+                    if let Some(route) = boundary_solver.route(
+                        native_price_request,
+                        self.max_hops,
+                        self.max_splits,
+                        |_| U256::zero(),
+                    ) {
                         // how many units of buy_token are bought for one unit of sell_token
                         // (buy_amount / sell_amount).
                         let price = self.native_token_price_estimation_amount.to_f64_lossy()
@@ -162,7 +175,15 @@ impl Inner {
             let solution = self.requests_for_order(&order).find_map(|request| {
                 tracing::trace!(order =% order.uid, ?request, "finding route");
 
-                let route = boundary_solver.route(request, self.max_hops)?;
+                let gas_cost = |gas: usize| {
+                    sell_token_price
+                        .ether_value(eth::Ether(
+                            U256::from(gas).saturating_mul(auction.gas_price.0 .0),
+                        ))
+                        .unwrap_or(U256::MAX)
+                };
+                let route =
+                    boundary_solver.route(request, self.max_hops, self.max_splits, gas_cost)?;
                 let interactions = route
                     .segments
                     .iter()
@@ -294,7 +315,10 @@ pub struct Request {
 /// A trading route.
 #[derive(Debug)]
 pub struct Route<'a> {
+    /// The segments of all paths of the route in execution order.
     segments: Vec<Segment<'a>>,
+    input: eth::Asset,
+    output: eth::Asset,
 }
 
 /// A segment in a trading route.
@@ -314,21 +338,38 @@ pub struct Segment<'a> {
 
 impl<'a> Route<'a> {
     pub fn new(segments: Vec<Segment<'a>>) -> Option<Self> {
-        if segments.is_empty() {
-            return None;
+        Self::split(vec![segments])
+    }
+
+    /// Creates a route where the traded amount is split over multiple paths.
+    /// All paths must start with the same input and end with the same output
+    /// token.
+    pub fn split(paths: Vec<Vec<Segment<'a>>>) -> Option<Self> {
+        let mut input = paths.first()?.first()?.input;
+        let mut output = paths.first()?.last()?.output;
+        input.amount = U256::zero();
+        output.amount = U256::zero();
+        for path in &paths {
+            let (first, last) = (path.first()?, path.last()?);
+            if first.input.token != input.token || last.output.token != output.token {
+                return None;
+            }
+            input.amount = input.amount.checked_add(first.input.amount)?;
+            output.amount = output.amount.checked_add(last.output.amount)?;
         }
-        Some(Self { segments })
+        Some(Self {
+            segments: paths.into_iter().flatten().collect(),
+            input,
+            output,
+        })
     }
 
-    fn input(&self) -> eth::Asset {
-        self.segments[0].input
+    pub fn input(&self) -> eth::Asset {
+        self.input
     }
 
-    fn output(&self) -> eth::Asset {
-        self.segments
-            .last()
-            .expect("route has at least one segment by construction")
-            .output
+    pub fn output(&self) -> eth::Asset {
+        self.output
     }
 
     fn gas(&self) -> eth::Gas {
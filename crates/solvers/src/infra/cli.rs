@@ -2,20 +2,22 @@
 
 use {
     clap::{Parser, Subcommand},
+    shared::logging_args_with_default_filter,
     std::{net::SocketAddr, path::PathBuf},
+    tracing::level_filters::LevelFilter,
 };
 
+logging_args_with_default_filter!(
+    LoggingArguments,
+    "warn,solvers=debug,shared=debug,model=debug,solver=debug"
+);
+
 /// Run a solver engine
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Args {
-    /// The log filter.
-    #[arg(
-        long,
-        env,
-        default_value = "warn,solvers=debug,shared=debug,model=debug,solver=debug"
-    )]
-    pub log: String,
+    #[command(flatten)]
+    pub logging: LoggingArguments,
 
     /// The socket address to bind to.
     #[arg(long, env, default_value = "127.0.0.1:7872")]
@@ -35,6 +35,12 @@ struct Config {
     /// path.
     max_hops: usize,
 
+    /// The maximum number of paths the traded amount of an order can be split
+    /// over. Splitting large orders over paths that don't share any liquidity
+    /// reduces their price impact. Defaults to 1, which disables splitting.
+    #[serde(default = "default_max_splits")]
+    max_splits: usize,
+
     /// The maximum number of pieces to divide partially fillable limit orders
     /// when trying to solve it against baseline liquidity.
     max_partial_attempts: usize,
@@ -81,6 +87,7 @@ pub async fn load(path: &Path) -> solver::Config {
             .map(eth::TokenAddress)
             .collect(),
         max_hops: config.max_hops,
+        max_splits: config.max_splits,
         max_partial_attempts: config.max_partial_attempts,
         solution_gas_offset: config.solution_gas_offset.into(),
         native_token_price_estimation_amount: config.native_token_price_estimation_amount,
@@ -110,3 +117,8 @@ where
 fn default_gas_offset() -> i64 {
     SETTLEMENT_OVERHEAD.try_into().unwrap()
 }
+
+/// Splitting orders over multiple paths is disabled by default.
+fn default_max_splits() -> usize {
+    1
+}
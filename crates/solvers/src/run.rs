@@ -25,7 +25,7 @@ pub async fn run(
 }
 
 async fn run_with(args: cli::Args, bind: Option<oneshot::Sender<SocketAddr>>) {
-    observe::tracing::initialize_reentrant(&args.log);
+    observe::tracing::initialize_reentrant_with_config(&args.logging.observe_config());
     tracing::info!("running solver engine with {args:#?}");
 
     let solver = match args.command {
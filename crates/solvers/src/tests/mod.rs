@@ -35,7 +35,7 @@ impl SolverEngine {
         let mut args = vec![
             "/test/solvers/path".to_owned(),
             "--addr=0.0.0.0:0".to_owned(),
-            "--log=solvers=trace".to_owned(),
+            "--log-filter=solvers=trace".to_owned(),
             command.to_owned(),
         ];
         let tempfile = match config {
@@ -65,7 +65,8 @@ impl SolverEngine {
         }
     }
 
-    /// Solves a raw JSON auction.
+    /// Solves a raw JSON auction. The reported timings are removed from the
+    /// response since they are not deterministic.
     pub async fn solve(&self, auction: serde_json::Value) -> serde_json::Value {
         let client = reqwest::Client::new();
         let url = shared::url::join(&self.url, "solve");
@@ -79,7 +80,10 @@ impl SolverEngine {
             );
         }
 
-        response.json().await.unwrap()
+        let mut response: serde_json::Value = response.json().await.unwrap();
+        assert!(response["timings"]["optimization"].is_u64());
+        response.as_object_mut().unwrap().remove("timings");
+        response
     }
 }
 
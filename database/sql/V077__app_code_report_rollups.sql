@@ -0,0 +1,13 @@
+-- Daily aggregates of fees paid and surplus received by the users of partner
+-- integrations identified by the `appCode` of the order's app data.
+CREATE TABLE app_code_report_rollups (
+    app_code text NOT NULL,
+    day date NOT NULL,
+    token bytea NOT NULL,
+    trades bigint NOT NULL,
+    fee numeric(78,0) NOT NULL,
+    fee_in_wei numeric NOT NULL,
+    surplus numeric NOT NULL,
+    surplus_in_wei numeric NOT NULL,
+    PRIMARY KEY (app_code, day, token)
+);
@@ -0,0 +1,8 @@
+-- Number of requests issued to (paid) price estimation APIs per day. Used to
+-- enforce request budgets across restarts and multiple service instances.
+CREATE TABLE price_estimator_usage (
+    estimator text NOT NULL,
+    day date NOT NULL,
+    requests bigint NOT NULL,
+    PRIMARY KEY (estimator, day)
+);
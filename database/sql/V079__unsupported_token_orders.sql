@@ -0,0 +1,9 @@
+ALTER TYPE OrderEventLabel ADD VALUE 'unsupported';
+
+-- Open orders that can not be settled because one of their tokens got marked
+-- as unsupported. Rows get removed again once the token becomes supported.
+CREATE TABLE unsupported_token_orders (
+    order_uid bytea PRIMARY KEY,
+    token bytea NOT NULL,
+    timestamp timestamptz NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Candidate orders that got filtered out while building an auction together
+-- with the reason. Only the most recent auctions are kept.
+CREATE TABLE auction_filtered_orders (
+    auction_id bigint NOT NULL,
+    order_uid bytea NOT NULL,
+    reason text NOT NULL,
+    details text,
+    PRIMARY KEY (auction_id, order_uid)
+);
+
+CREATE INDEX auction_filtered_orders_order_uid ON auction_filtered_orders USING HASH (order_uid);
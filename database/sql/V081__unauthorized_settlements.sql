@@ -0,0 +1,15 @@
+-- Settlements submitted by solvers that were not allowed to settle the
+-- referenced auction because they didn't propose a solution or didn't win.
+CREATE TYPE UnauthorizedSettlementReason AS ENUM ('not_participated', 'not_winner');
+
+CREATE TABLE unauthorized_settlements (
+    block_number bigint NOT NULL,
+    log_index bigint NOT NULL,
+    tx_hash bytea NOT NULL,
+    auction_id bigint NOT NULL,
+    solver bytea NOT NULL,
+    reason UnauthorizedSettlementReason NOT NULL,
+    PRIMARY KEY (block_number, log_index)
+);
+
+CREATE INDEX unauthorized_settlements_solver ON unauthorized_settlements USING HASH (solver);
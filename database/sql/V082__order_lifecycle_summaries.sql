@@ -0,0 +1,14 @@
+-- Compact per-order summary of the raw `order_events`. Events get aggregated
+-- into this table before they are deleted by the retention policy.
+CREATE TABLE order_lifecycle_summaries (
+    order_uid bytea PRIMARY KEY,
+    -- Timestamp of the earliest event of the order.
+    first_seen timestamptz NOT NULL,
+    -- How often the order became ready, i.e. was (re-)included in an auction.
+    auctions bigint NOT NULL,
+    first_execution timestamptz,
+    last_execution timestamptz,
+    -- Label and timestamp of the latest event of the order.
+    final_state OrderEventLabel NOT NULL,
+    final_state_at timestamptz NOT NULL
+);
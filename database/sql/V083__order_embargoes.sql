@@ -0,0 +1,9 @@
+-- Orders that were submitted with an embargo. They are hidden from the public
+-- order APIs until the autopilot cuts an auction with an id greater than
+-- `auction_id` (the most recent auction at submission time) or `until` passed,
+-- whichever happens first.
+CREATE TABLE order_embargoes (
+    order_uid bytea PRIMARY KEY,
+    auction_id bigint NOT NULL,
+    until timestamptz NOT NULL
+);
@@ -0,0 +1,17 @@
+-- How well settled orders were executed compared to what the winning solver
+-- promised during the competition. A negative `deviation` means the user
+-- received a worse effective price onchain than promised.
+CREATE TABLE order_execution_quality (
+    auction_id bigint NOT NULL,
+    order_uid bytea NOT NULL,
+    solver bytea NOT NULL,
+    block_number bigint NOT NULL,
+    promised_sell numeric(78,0) NOT NULL,
+    promised_buy numeric(78,0) NOT NULL,
+    executed_sell numeric(78,0) NOT NULL,
+    executed_buy numeric(78,0) NOT NULL,
+    deviation double precision NOT NULL,
+    PRIMARY KEY (auction_id, order_uid)
+);
+
+CREATE INDEX order_execution_quality_solver ON order_execution_quality (solver, block_number DESC);
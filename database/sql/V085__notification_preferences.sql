@@ -0,0 +1,15 @@
+-- Notification preferences accounts registered with a signed message. The
+-- orderbook periodically sends them digests of their order activity between
+-- `digested_until` and the time of the digest.
+CREATE TABLE notification_preferences (
+    owner bytea PRIMARY KEY,
+    webhook text,
+    email text,
+    fills boolean NOT NULL,
+    expiries boolean NOT NULL,
+    issued_at timestamptz NOT NULL,
+    updated_at timestamptz NOT NULL,
+    digested_until timestamptz NOT NULL
+);
+
+CREATE INDEX notification_preferences_digested_until ON notification_preferences (digested_until);
@@ -0,0 +1,11 @@
+-- Runtime toggles for rolling out changes without redeploying services. Rows
+-- with `chain_id` 0 apply to all chains unless a row for the specific chain
+-- overrides them.
+CREATE TABLE feature_flags (
+    name text NOT NULL,
+    chain_id bigint NOT NULL,
+    value jsonb NOT NULL,
+    updated_at timestamptz NOT NULL,
+
+    PRIMARY KEY (name, chain_id)
+);
@@ -0,0 +1,8 @@
+-- Protocol fee configurations the autopilot was started with. A new version is
+-- only recorded if the configuration differs from the latest one, so the
+-- newest row is the currently active configuration.
+CREATE TABLE fee_policy_configurations (
+    version bigserial PRIMARY KEY,
+    configuration jsonb NOT NULL,
+    created_at timestamptz NOT NULL
+);
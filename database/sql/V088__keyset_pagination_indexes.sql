@@ -0,0 +1,6 @@
+-- Indexes for paginating through orders and order events with keyset pagination
+-- (see `orders_after` and `order_events_after`). Trades are paginated by their
+-- primary key.
+CREATE INDEX order_creation_timestamp_uid ON orders USING BTREE (creation_timestamp, uid);
+
+CREATE INDEX order_events_by_timestamp ON order_events USING BTREE (timestamp, order_uid, label);
@@ -0,0 +1,15 @@
+-- Prepares attaching `order_events` as a partition of the partitioned table
+-- created in V091. Attaching a table scans all of its rows under an ACCESS
+-- EXCLUSIVE lock unless a validated constraint already proves that they fall
+-- into the partition's range. The constraint gets added without validating
+-- the existing rows, which only needs a brief lock, and gets validated in the
+-- next migration without blocking writes. The bound leaves room for the next
+-- day in case the following migrations run after midnight.
+DO $$
+BEGIN
+    EXECUTE format(
+        'ALTER TABLE order_events ADD CONSTRAINT order_events_partition_bound CHECK (timestamp < %L) NOT VALID',
+        date_trunc('day', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' + interval '2 days'
+    );
+END
+$$;
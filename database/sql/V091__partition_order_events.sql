@@ -0,0 +1,40 @@
+-- Partition `order_events` by day so expired events can be removed by dropping
+-- whole partitions instead of deleting rows (see `database::partitions`).
+-- Existing tables can't be turned into partitioned tables, so the existing
+-- table becomes the partition holding all events until the bound validated in
+-- V090, which lets Postgres attach it without scanning its rows. Later
+-- partitions get created ahead of time by the autopilot and events not covered
+-- by any of them end up in the default partition.
+ALTER TABLE order_events RENAME TO order_events_legacy;
+ALTER INDEX order_events_by_uid RENAME TO order_events_legacy_by_uid;
+ALTER INDEX order_events_by_timestamp RENAME TO order_events_legacy_by_timestamp;
+
+CREATE TABLE order_events (
+    order_uid bytea NOT NULL,
+    timestamp timestamptz NOT NULL,
+    label OrderEventLabel NOT NULL
+) PARTITION BY RANGE (timestamp);
+
+CREATE INDEX order_events_by_uid ON order_events USING BTREE (order_uid, timestamp);
+CREATE INDEX order_events_by_timestamp ON order_events USING BTREE (timestamp, order_uid, label);
+
+CREATE TABLE order_events_default PARTITION OF order_events DEFAULT;
+
+-- The partition bound has to be implied by the validated constraint, so it
+-- gets read from the constraint instead of being computed again.
+DO $$
+DECLARE
+    bound timestamptz;
+BEGIN
+    SELECT substring(pg_get_constraintdef(oid) FROM '''([^'']+)''')::timestamptz INTO STRICT bound
+    FROM pg_constraint
+    WHERE conname = 'order_events_partition_bound';
+    EXECUTE format(
+        'ALTER TABLE order_events ATTACH PARTITION order_events_legacy FOR VALUES FROM (MINVALUE) TO (%L)',
+        bound
+    );
+END
+$$;
+
+-- The partition bound makes the constraint redundant.
+ALTER TABLE order_events_legacy DROP CONSTRAINT order_events_partition_bound;
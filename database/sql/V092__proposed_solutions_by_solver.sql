@@ -0,0 +1,3 @@
+-- Allows querying the competition results of individual solvers (see
+-- `solver_competition::stats`).
+CREATE INDEX proposed_solutions_solver_auction_id ON proposed_solutions USING BTREE (solver, auction_id);
@@ -0,0 +1,3 @@
+-- Orderbook replicas claim the notification preferences they send digests for
+-- so every digest only gets sent once.
+ALTER TABLE notification_preferences ADD COLUMN claimed_until timestamptz;
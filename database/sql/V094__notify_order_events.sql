@@ -0,0 +1,20 @@
+-- Announces new order events that the orderbook streams to its clients on the
+-- `order_events` channel. Postgres delivers notifications once the inserting
+-- transaction commits, so events committed late or timestamped by a host with
+-- a skewed clock still get streamed.
+CREATE FUNCTION notify_order_event() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('order_events', json_build_object(
+        'orderUid', encode(NEW.order_uid, 'hex'),
+        'timestamp', NEW.timestamp,
+        'label', NEW.label
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_order_event
+AFTER INSERT ON order_events
+FOR EACH ROW
+WHEN (NEW.label IN ('created', 'cancelled', 'executing', 'traded'))
+EXECUTE FUNCTION notify_order_event();
@@ -0,0 +1,11 @@
+-- Solvers the autopilot excluded from the competition for not settling the
+-- auctions they won. Persisted so that restarts neither end the cool-down
+-- early nor judge reinstated solvers on auctions from before their exclusion.
+CREATE TABLE solver_exclusions (
+    solver bytea PRIMARY KEY,
+    -- The solver takes part in the competition again after this time.
+    excluded_until timestamptz NOT NULL,
+    -- The most recent won auction the exclusion was based on. Only auctions
+    -- won after it count towards excluding the solver again.
+    latest_auction bigint NOT NULL
+);
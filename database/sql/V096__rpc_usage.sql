@@ -0,0 +1,6 @@
+-- Number of node RPC requests services sent per day. Used to share the daily
+-- RPC budget between services and keep it across restarts.
+CREATE TABLE rpc_usage (
+    day date PRIMARY KEY,
+    requests bigint NOT NULL
+);
@@ -0,0 +1,6 @@
+-- Trades used to be counted on the rows of both their fee and their surplus
+-- token, which inflated the total number of trades of an app code. They now
+-- only get counted on the row of their fee token. Drop the existing rollups
+-- so the autopilot recomputes them from the start.
+DELETE FROM app_code_report_rollups;
+DELETE FROM last_indexed_blocks WHERE contract = 'app_code_report_rollups';